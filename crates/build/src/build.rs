@@ -5,6 +5,7 @@ use cargo_metadata::camino::Utf8PathBuf;
 
 use crate::{
     command::{local::create_local_command, utils::execute_command},
+    lint::run_lints,
     utils::{cargo_rerun_if_changed, current_datetime},
     BuildArgs, BUILD_TARGET, HELPER_TARGET_SUBDIR,
 };
@@ -37,6 +38,9 @@ pub fn execute_build_program(
     let mut program_metadata_cmd = cargo_metadata::MetadataCommand::new();
     let program_metadata = program_metadata_cmd.manifest_path(program_metadata_file).exec()?;
 
+    // Flag the guest dependencies and std APIs the zkVM does not support.
+    run_lints(args, &program_dir)?;
+
     // Get the command
     let cmd = create_local_command(args, &program_dir, &program_metadata);
 
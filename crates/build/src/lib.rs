@@ -1,8 +1,10 @@
 mod build;
 mod command;
+mod lint;
 mod utils;
 use build::build_program_internal;
 pub use build::{execute_build_program, generate_elf_paths};
+pub use lint::{lint_program, LintFinding, LintKind, LintLevel};
 
 use clap::Parser;
 
@@ -70,6 +72,20 @@ pub struct BuildArgs {
         help = "Space or comma separated list of static C/C++ libraries to be linked"
     )]
     pub libraries: Vec<String>,
+    #[clap(
+        long,
+        value_enum,
+        default_value = "warn",
+        help = "How to report guest dependencies and std APIs unsupported by the zkVM"
+    )]
+    pub lint: LintLevel,
+    #[clap(
+        long,
+        action,
+        value_delimiter = ',',
+        help = "Space or comma separated list of crates to skip in the guest lints"
+    )]
+    pub lint_allow: Vec<String>,
 }
 
 // Implement default args to match clap defaults.
@@ -86,6 +102,8 @@ impl Default for BuildArgs {
             output_directory: DEFAULT_OUTPUT_DIR.to_string(),
             locked: false,
             no_default_features: false,
+            lint: LintLevel::Warn,
+            lint_allow: vec![],
         }
     }
 }
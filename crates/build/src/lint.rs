@@ -0,0 +1,353 @@
+//! Ahead-of-time lints over the dependency graph and sources of a guest.
+//!
+//! Some crates and std APIs build fine for the zkVM target, but make the guest fail or behave
+//! nondeterministically at runtime: reading the clock, spawning threads or drawing OS randomness
+//! are not supported by the zkVM. The lints flag them before the program is built, with a
+//! suggestion on how to avoid them.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt, fs,
+};
+
+use anyhow::{bail, Result};
+use cargo_metadata::{
+    camino::{Utf8Path, Utf8PathBuf},
+    CargoOpt, DependencyKind, Metadata, MetadataCommand, Package, PackageId,
+};
+use clap::ValueEnum;
+
+use crate::BuildArgs;
+
+/// How the findings of the guest lints are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LintLevel {
+    /// Skip the lints.
+    Allow,
+    /// Print the findings as warnings.
+    #[default]
+    Warn,
+    /// Print the findings and fail the build if there are any.
+    Deny,
+}
+
+/// The kind of problem a lint finding points to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintKind {
+    /// The guest output may depend on the machine executing it.
+    Nondeterminism,
+    /// The guest relies on a syscall the zkVM does not support.
+    UnsupportedSyscall,
+    /// The guest is expensive to prove.
+    Performance,
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintKind::Nondeterminism => f.write_str("nondeterminism"),
+            LintKind::UnsupportedSyscall => f.write_str("unsupported syscall"),
+            LintKind::Performance => f.write_str("performance"),
+        }
+    }
+}
+
+/// A problem found in the guest dependencies or sources.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintFinding {
+    pub kind: LintKind,
+    /// The crate, as `name@version`, or the source location the finding is about.
+    pub location: String,
+    pub message: String,
+    pub suggestion: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}: {} ({})", self.kind, self.location, self.message, self.suggestion)
+    }
+}
+
+/// A crate which is a problem in a guest, when any of `features` is enabled, or always if
+/// `features` is empty.
+struct DependencyRule {
+    krate: &'static str,
+    features: &'static [&'static str],
+    kind: LintKind,
+    message: &'static str,
+    suggestion: &'static str,
+}
+
+const DEPENDENCY_RULES: &[DependencyRule] = &[
+    DependencyRule {
+        krate: "rand",
+        features: &["thread_rng"],
+        kind: LintKind::Nondeterminism,
+        message: "`thread_rng` is seeded from the randomness of the executing machine",
+        suggestion: "seed a `StdRng` from the program input instead",
+    },
+    DependencyRule {
+        krate: "chrono",
+        features: &["clock"],
+        kind: LintKind::UnsupportedSyscall,
+        message: "the `clock` feature reads the system time",
+        suggestion: "disable the default features of `chrono`, and pass timestamps as input",
+    },
+    DependencyRule {
+        krate: "time",
+        features: &["local-offset"],
+        kind: LintKind::UnsupportedSyscall,
+        message: "the `local-offset` feature reads the system time zone",
+        suggestion: "disable the `local-offset` feature, and pass timestamps as input",
+    },
+    DependencyRule {
+        krate: "tokio",
+        features: &[],
+        kind: LintKind::UnsupportedSyscall,
+        message: "async runtimes need threads, timers and I/O",
+        suggestion: "keep async code in the host, and pass its results as input",
+    },
+    DependencyRule {
+        krate: "rayon",
+        features: &[],
+        kind: LintKind::UnsupportedSyscall,
+        message: "`rayon` spawns threads",
+        suggestion: "disable the parallel features of the crates depending on `rayon`",
+    },
+    DependencyRule {
+        krate: "num_cpus",
+        features: &[],
+        kind: LintKind::UnsupportedSyscall,
+        message: "`num_cpus` queries the machine it runs on",
+        suggestion: "disable the parallel features of the crates depending on `num_cpus`",
+    },
+    DependencyRule {
+        krate: "nalgebra",
+        features: &[],
+        kind: LintKind::Performance,
+        message: "floating point arithmetic is emulated in software",
+        suggestion: "prefer fixed point or integer arithmetic in the guest",
+    },
+    DependencyRule {
+        krate: "ndarray",
+        features: &[],
+        kind: LintKind::Performance,
+        message: "floating point arithmetic is emulated in software",
+        suggestion: "prefer fixed point or integer arithmetic in the guest",
+    },
+];
+
+/// A std API which is a problem in a guest, found by searching the guest sources for `pattern`.
+struct SourceRule {
+    pattern: &'static str,
+    kind: LintKind,
+    message: &'static str,
+    suggestion: &'static str,
+}
+
+const SOURCE_RULES: &[SourceRule] = &[
+    SourceRule {
+        pattern: "SystemTime::now",
+        kind: LintKind::UnsupportedSyscall,
+        message: "`SystemTime::now` reads the system time",
+        suggestion: "pass timestamps as input",
+    },
+    SourceRule {
+        pattern: "Instant::now",
+        kind: LintKind::UnsupportedSyscall,
+        message: "`Instant::now` reads the system clock",
+        suggestion: "measure cycles with the `cycle-tracker` instead",
+    },
+    SourceRule {
+        pattern: "thread::spawn",
+        kind: LintKind::UnsupportedSyscall,
+        message: "the zkVM runs a single thread",
+        suggestion: "run the work sequentially",
+    },
+];
+
+/// Lints the packages built by `args` in `program_dir` and their dependencies.
+///
+/// Crates named in [BuildArgs::lint_allow] are skipped.
+pub fn lint_program(args: &BuildArgs, program_dir: &Utf8Path) -> Result<Vec<LintFinding>> {
+    let mut metadata_cmd = MetadataCommand::new();
+    metadata_cmd.manifest_path(program_dir.join("Cargo.toml"));
+    if args.no_default_features {
+        metadata_cmd.features(CargoOpt::NoDefaultFeatures);
+    }
+    if !args.features.is_empty() {
+        metadata_cmd.features(CargoOpt::SomeFeatures(args.features.clone()));
+    }
+    let metadata = metadata_cmd.exec()?;
+
+    let roots = if args.packages.is_empty() {
+        metadata.workspace_default_members.to_vec()
+    } else {
+        metadata
+            .packages
+            .iter()
+            .filter(|p| args.packages.contains(&p.name))
+            .map(|p| p.id.clone())
+            .collect()
+    };
+
+    let mut findings = vec![];
+    for (package, features) in guest_dependencies(&metadata, &roots) {
+        if !args.lint_allow.contains(&package.name) {
+            findings.extend(lint_dependency(package, features));
+        }
+    }
+    for root in &roots {
+        let package = &metadata[root];
+        if let Some(dir) = package.manifest_path.parent() {
+            findings.extend(lint_sources(&dir.join("src"))?);
+        }
+    }
+    Ok(findings)
+}
+
+/// Runs the lints at the level of `args`, printing the findings as cargo warnings.
+pub(crate) fn run_lints(args: &BuildArgs, program_dir: &Utf8Path) -> Result<()> {
+    if args.lint == LintLevel::Allow {
+        return Ok(());
+    }
+    let findings = lint_program(args, program_dir)?;
+    for finding in &findings {
+        println!("cargo:warning=guest lint: {finding}");
+    }
+    if args.lint == LintLevel::Deny && !findings.is_empty() {
+        bail!(
+            "{} guest lint findings, pass `--lint warn` to build anyway or `--lint-allow <crate>` \
+             to skip a crate",
+            findings.len()
+        );
+    }
+    Ok(())
+}
+
+/// The packages the `roots` depend on at runtime, with their enabled features.
+///
+/// Build and dev dependencies are skipped, since they run on the host.
+fn guest_dependencies<'a>(
+    metadata: &'a Metadata,
+    roots: &[PackageId],
+) -> Vec<(&'a Package, &'a [String])> {
+    let Some(resolve) = &metadata.resolve else {
+        return vec![];
+    };
+    let nodes = resolve.nodes.iter().map(|node| (&node.id, node)).collect::<HashMap<_, _>>();
+
+    let mut visited = roots.iter().collect::<HashSet<_>>();
+    let mut queue = roots.iter().collect::<VecDeque<_>>();
+    let mut dependencies = vec![];
+    while let Some(id) = queue.pop_front() {
+        let Some(node) = nodes.get(id) else {
+            continue;
+        };
+        dependencies.push((&metadata[id], node.features.as_slice()));
+        for dep in &node.deps {
+            let runtime = dep.dep_kinds.iter().any(|k| k.kind == DependencyKind::Normal);
+            if runtime && visited.insert(&dep.pkg) {
+                queue.push_back(&dep.pkg);
+            }
+        }
+    }
+    dependencies
+}
+
+fn lint_dependency(package: &Package, features: &[String]) -> Option<LintFinding> {
+    let location = format!("{}@{}", package.name, package.version);
+
+    // `zkm-zkvm` registers the zkVM entropy source with getrandom 0.2, which needs the `custom`
+    // feature. Later versions have no way to register it.
+    if package.name == "getrandom" {
+        let (message, suggestion) = if package.version.major > 0 || package.version.minor > 2 {
+            (
+                "this version of getrandom has no zkVM backend",
+                "pin the crates depending on it to versions using getrandom 0.2",
+            )
+        } else if !features.iter().any(|f| f == "custom") {
+            (
+                "getrandom is built without the `custom` feature, so it has no zkVM backend",
+                "depend on `zkm-zkvm`, which enables the feature and registers the zkVM backend",
+            )
+        } else {
+            return None;
+        };
+        return Some(LintFinding {
+            kind: LintKind::UnsupportedSyscall,
+            location,
+            message: message.to_string(),
+            suggestion: suggestion.to_string(),
+        });
+    }
+
+    let rule = DEPENDENCY_RULES.iter().find(|rule| {
+        rule.krate == package.name
+            && (rule.features.is_empty()
+                || rule.features.iter().any(|f| features.iter().any(|g| g == f)))
+    })?;
+    Some(LintFinding {
+        kind: rule.kind,
+        location,
+        message: rule.message.to_string(),
+        suggestion: rule.suggestion.to_string(),
+    })
+}
+
+/// Searches the Rust sources in `dir` for the patterns of [SOURCE_RULES].
+fn lint_sources(dir: &Utf8Path) -> Result<Vec<LintFinding>> {
+    let mut findings = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = dir.read_dir_utf8() else {
+            continue;
+        };
+        for entry in entries {
+            let path: Utf8PathBuf = entry?.into_path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some("rs") {
+                findings.extend(lint_source(&path, &fs::read_to_string(&path)?));
+            }
+        }
+    }
+    Ok(findings)
+}
+
+fn lint_source(path: &Utf8Path, source: &str) -> Vec<LintFinding> {
+    let mut findings = vec![];
+    for (i, line) in source.lines().enumerate() {
+        if line.trim_start().starts_with("//") {
+            continue;
+        }
+        for rule in SOURCE_RULES.iter().filter(|rule| line.contains(rule.pattern)) {
+            findings.push(LintFinding {
+                kind: rule.kind,
+                location: format!("{}:{}", path, i + 1),
+                message: rule.message.to_string(),
+                suggestion: rule.suggestion.to_string(),
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_source() {
+        let source = r#"fn main() {
+    // Instant::now() is not supported.
+    let start = std::time::Instant::now();
+    std::thread::spawn(|| {});
+}
+"#;
+        let findings = lint_source(Utf8Path::new("src/main.rs"), source);
+        let locations = findings.iter().map(|f| f.location.as_str()).collect::<Vec<_>>();
+        assert_eq!(locations, ["src/main.rs:3", "src/main.rs:4"]);
+        assert!(findings.iter().all(|f| f.kind == LintKind::UnsupportedSyscall));
+    }
+}
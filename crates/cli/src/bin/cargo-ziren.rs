@@ -1,7 +1,10 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use zkm_cli::{
-    commands::{build::BuildCmd, new::NewCmd, vkey::VkeyCmd},
+    commands::{
+        build::BuildCmd, describe_proof::DescribeProofCmd, estimate::EstimateCmd,
+        execute::ExecuteCmd, new::NewCmd, prove::ProveCmd, verify::VerifyCmd, vkey::VkeyCmd,
+    },
     ZKM_VERSION_MESSAGE,
 };
 
@@ -23,6 +26,11 @@ pub enum ProveCliCommands {
     New(NewCmd),
     Build(BuildCmd),
     Vkey(VkeyCmd),
+    Execute(ExecuteCmd),
+    Estimate(EstimateCmd),
+    Prove(ProveCmd),
+    Verify(VerifyCmd),
+    DescribeProof(DescribeProofCmd),
 }
 
 fn main() -> Result<()> {
@@ -32,5 +40,10 @@ fn main() -> Result<()> {
         ProveCliCommands::New(cmd) => cmd.run(),
         ProveCliCommands::Build(cmd) => cmd.run(),
         ProveCliCommands::Vkey(cmd) => cmd.run(),
+        ProveCliCommands::Execute(cmd) => cmd.run(),
+        ProveCliCommands::Estimate(cmd) => cmd.run(),
+        ProveCliCommands::Prove(cmd) => cmd.run(),
+        ProveCliCommands::Verify(cmd) => cmd.run(),
+        ProveCliCommands::DescribeProof(cmd) => cmd.run(),
     }
 }
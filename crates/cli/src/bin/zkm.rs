@@ -0,0 +1,39 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use zkm_cli::{
+    commands::{
+        describe_proof::DescribeProofCmd, estimate::EstimateCmd, execute::ExecuteCmd,
+        prove::ProveCmd, verify::VerifyCmd, vkey::VkeyCmd,
+    },
+    ZKM_VERSION_MESSAGE,
+};
+
+#[derive(Parser)]
+#[command(name = "zkm", author, about, long_about = None, version = ZKM_VERSION_MESSAGE)]
+pub struct ZkmCli {
+    #[command(subcommand)]
+    pub command: ZkmCliCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ZkmCliCommands {
+    Execute(ExecuteCmd),
+    Estimate(EstimateCmd),
+    Prove(ProveCmd),
+    Verify(VerifyCmd),
+    Vkey(VkeyCmd),
+    DescribeProof(DescribeProofCmd),
+}
+
+fn main() -> Result<()> {
+    let args = ZkmCli::parse();
+
+    match args.command {
+        ZkmCliCommands::Execute(cmd) => cmd.run(),
+        ZkmCliCommands::Estimate(cmd) => cmd.run(),
+        ZkmCliCommands::Prove(cmd) => cmd.run(),
+        ZkmCliCommands::Verify(cmd) => cmd.run(),
+        ZkmCliCommands::Vkey(cmd) => cmd.run(),
+        ZkmCliCommands::DescribeProof(cmd) => cmd.run(),
+    }
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use zkm_sdk::{HashableKey, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues};
+
+#[derive(Parser)]
+#[command(name = "describe-proof", about = "Print the kind, version and public values of a proof.")]
+pub struct DescribeProofCmd {
+    /// The path to the proof
+    #[arg(long)]
+    proof: PathBuf,
+}
+
+impl DescribeProofCmd {
+    pub fn run(&self) -> Result<()> {
+        let proof = ZKMProofWithPublicValues::load(&self.proof)
+            .with_context(|| format!("failed to load proof {}", self.proof.display()))?;
+
+        println!("Kind: {:?}", ZKMProofKind::from(&proof.proof));
+        println!("Ziren Version: {}", proof.zkm_version);
+        match &proof.proof {
+            ZKMProof::Core(shard_proofs) => println!("Shards: {}", shard_proofs.len()),
+            ZKMProof::Compressed(reduce_proof) => {
+                println!("Recursion Key Hash: {}", reduce_proof.vk.bytes32())
+            }
+            ZKMProof::Plonk(plonk_proof) => {
+                println!("Plonk Key Hash: 0x{}", hex::encode(plonk_proof.plonk_vkey_hash))
+            }
+            ZKMProof::Groth16(groth16_proof) => {
+                println!("Groth16 Key Hash: 0x{}", hex::encode(groth16_proof.groth16_vkey_hash))
+            }
+            ZKMProof::CompressToGroth16 => {}
+        }
+        println!("Public Values:\n{}", proof.public_values.raw());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use clap::Parser;
+use zkm_sdk::ProverClient;
+
+use super::program::ProgramArgs;
+
+#[derive(Parser)]
+#[command(
+    name = "estimate",
+    about = "Estimate the shards, trace areas and proving time of a guest without proving it."
+)]
+pub struct EstimateCmd {
+    #[command(flatten)]
+    program: ProgramArgs,
+}
+
+impl EstimateCmd {
+    pub fn run(&self) -> Result<()> {
+        let elf = self.program.read_elf()?;
+        let stdin = self.program.read_stdin()?;
+
+        let estimate = ProverClient::new().estimate(&elf, stdin)?;
+        print!("{estimate}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Parser;
+use zkm_sdk::ProverClient;
+
+use super::program::ProgramArgs;
+
+#[derive(Parser)]
+#[command(name = "execute", about = "Execute a guest without generating a proof.")]
+pub struct ExecuteCmd {
+    #[command(flatten)]
+    program: ProgramArgs,
+    /// Abort the execution after this many cycles
+    #[arg(long)]
+    max_cycles: Option<u64>,
+    /// Print the full execution report
+    #[arg(long)]
+    report: bool,
+}
+
+impl ExecuteCmd {
+    pub fn run(&self) -> Result<()> {
+        let elf = self.program.read_elf()?;
+        let stdin = self.program.read_stdin()?;
+
+        let client = ProverClient::new();
+        let mut execute = client.execute(&elf, stdin);
+        if let Some(max_cycles) = self.max_cycles {
+            execute = execute.max_cycles(max_cycles);
+        }
+        let (public_values, report) = execute.run()?;
+
+        println!("Cycles: {}", report.total_instruction_count());
+        println!("Public Values:\n{}", public_values.raw());
+        if self.report {
+            println!("{report}");
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,9 @@
 pub mod build;
+pub mod describe_proof;
+pub mod estimate;
+pub mod execute;
 pub mod new;
+pub mod program;
+pub mod prove;
+pub mod verify;
 pub mod vkey;
@@ -0,0 +1,45 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use zkm_sdk::ZKMStdin;
+
+/// The guest program and input shared by the commands running a guest.
+#[derive(Debug, Clone, Args)]
+pub struct ProgramArgs {
+    /// The path to the ELF file
+    #[arg(long)]
+    pub elf: PathBuf,
+    /// The path to a `ZKMStdin` encoded with `ZKMStdin::to_bytes`, or serialized with bincode
+    #[arg(long, conflicts_with = "input")]
+    pub stdin: Option<PathBuf>,
+    /// A hex-encoded input buffer, read by the guest with `zkm_zkvm::io::read_vec`. Can be
+    /// repeated to write several buffers in order.
+    #[arg(long)]
+    pub input: Vec<String>,
+}
+
+impl ProgramArgs {
+    /// Reads the ELF file contents.
+    pub fn read_elf(&self) -> Result<Vec<u8>> {
+        fs::read(&self.elf).with_context(|| format!("failed to read ELF {}", self.elf.display()))
+    }
+
+    /// Builds the stdin of the guest from the `--stdin` file or the `--input` buffers.
+    pub fn read_stdin(&self) -> Result<ZKMStdin> {
+        if let Some(path) = &self.stdin {
+            let bytes =
+                fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+            return ZKMStdin::from_bytes(&bytes)
+                .with_context(|| format!("{} is not a serialized ZKMStdin", path.display()));
+        }
+
+        let mut stdin = ZKMStdin::new();
+        for input in &self.input {
+            let bytes = hex::decode(input.strip_prefix("0x").unwrap_or(input))
+                .with_context(|| format!("invalid hex input {input}"))?;
+            stdin.write_vec(bytes);
+        }
+        Ok(stdin)
+    }
+}
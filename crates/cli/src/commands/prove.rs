@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use zkm_sdk::{HashableKey, ProverClient};
+
+use super::program::ProgramArgs;
+
+/// The kind of proof to generate.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ProofMode {
+    Core,
+    Compressed,
+    Plonk,
+    Groth16,
+}
+
+#[derive(Parser)]
+#[command(name = "prove", about = "Generate a proof of a guest execution.")]
+pub struct ProveCmd {
+    #[command(flatten)]
+    program: ProgramArgs,
+    /// The kind of proof to generate
+    #[arg(long, value_enum, default_value_t = ProofMode::Compressed)]
+    mode: ProofMode,
+    /// The path to write the proof to
+    #[arg(long, short, default_value = "proof.bin")]
+    output: PathBuf,
+}
+
+impl ProveCmd {
+    pub fn run(&self) -> Result<()> {
+        let elf = self.program.read_elf()?;
+        let stdin = self.program.read_stdin()?;
+
+        let client = ProverClient::new();
+        let (pk, vk) = client.setup(&elf);
+        let prove = client.prove(&pk, stdin);
+        let prove = match self.mode {
+            ProofMode::Core => prove.core(),
+            ProofMode::Compressed => prove.compressed(),
+            ProofMode::Plonk => prove.plonk(),
+            ProofMode::Groth16 => prove.groth16(),
+        };
+        let proof = prove.run()?;
+        proof.save(&self.output)?;
+
+        println!("Verification Key Hash:\n{}", vk.bytes32());
+        println!("Public Values:\n{}", proof.public_values.raw());
+        println!("Proof written to {}", self.output.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use zkm_sdk::{ProverClient, ZKMProofWithPublicValues};
+
+#[derive(Parser)]
+#[command(name = "verify", about = "Verify a proof against the guest it was generated for.")]
+pub struct VerifyCmd {
+    /// The path to the ELF file
+    #[arg(long)]
+    elf: PathBuf,
+    /// The path to the proof
+    #[arg(long)]
+    proof: PathBuf,
+}
+
+impl VerifyCmd {
+    pub fn run(&self) -> Result<()> {
+        let elf = fs::read(&self.elf)
+            .with_context(|| format!("failed to read ELF {}", self.elf.display()))?;
+        let proof = ZKMProofWithPublicValues::load(&self.proof)
+            .with_context(|| format!("failed to load proof {}", self.proof.display()))?;
+
+        let client = ProverClient::new();
+        let (_, vk) = client.setup(&elf);
+        client.verify(&proof, &vk)?;
+
+        println!("Proof verified successfully.");
+
+        Ok(())
+    }
+}
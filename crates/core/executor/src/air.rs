@@ -115,6 +115,12 @@ pub enum MipsAirId {
     SysLinux = 47,
     /// The MovCondChip.
     MovCond = 49,
+    /// The BLAKE3 compress inner chip.
+    Blake3CompressInner = 50,
+    /// The SHA-512 extend chip.
+    Sha512Extend = 51,
+    /// The SHA-512 compress chip.
+    Sha512Compress = 52,
 }
 
 impl MipsAirId {
@@ -195,6 +201,9 @@ impl MipsAirId {
             Self::Byte => "Byte",
             Self::SysLinux => "SysLinux",
             Self::MovCond => "MovCond",
+            Self::Blake3CompressInner => "Blake3CompressInner",
+            Self::Sha512Extend => "Sha512Extend",
+            Self::Sha512Compress => "Sha512Compress",
         }
     }
 }
@@ -1,10 +1,24 @@
-use core::mem::take;
+use core::{mem::take, ops::Range};
+use std::{
+    path::PathBuf,
+    sync::mpsc::Sender,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use hashbrown::HashMap;
+use serde::{de::DeserializeOwned, Serialize};
+use zkm_stark::Progress;
 
 use crate::{
-    hook::{hookify, BoxedHook, HookEnv, HookRegistry},
+    hook::{hookify, typed_hook, BoxedHook, HookEnv, HookRegistry},
+    profiler::ProfilerConfig,
     subproof::SubproofVerifier,
+    syscalls::{
+        syscall_handlerify, BoxedSyscallHandler, SyscallHandlerRegistry, UserSyscallArgs,
+        USER_SYSCALL_RANGE,
+    },
+    watchpoint::{watchpointify, BoxedWatchpoint, WatchEvent},
+    ContinuationState, OpcodeSample,
 };
 
 /// Context to run a program inside Ziren.
@@ -15,6 +29,9 @@ pub struct ZKMContext<'a> {
     /// Note: `None` denotes the default list of hooks.
     pub hook_registry: Option<HookRegistry<'a>>,
 
+    /// The registry of handlers for user-defined syscalls.
+    pub syscall_handlers: SyscallHandlerRegistry<'a>,
+
     /// The verifier for verifying subproofs.
     pub subproof_verifier: Option<&'a dyn SubproofVerifier>,
 
@@ -23,6 +40,57 @@ pub struct ZKMContext<'a> {
 
     /// Skip deferred proof verification.
     pub skip_deferred_proof_verification: bool,
+
+    /// Whether to compute a digest of all input bytes consumed by the program.
+    pub input_transcript: bool,
+
+    /// The number of cycles between snapshots of the rolling execution state digest, if enabled.
+    pub state_digest_interval: Option<u64>,
+
+    /// Whether to estimate the number of events of each chip in every shard.
+    pub cost_estimation: bool,
+
+    /// The number of cycles to sample the opcode histogram over, and the channel to send the
+    /// sample to, if enabled.
+    pub opcode_sample: Option<(u64, Sender<OpcodeSample>)>,
+
+    /// The seed of the random number generator of the program, if set.
+    pub rng_seed: Option<[u8; 32]>,
+
+    /// The time of the deterministic clock of the program at its first cycle, as a duration since
+    /// the Unix epoch, if set.
+    pub time_base: Option<Duration>,
+
+    /// The configuration of the profiler of the cycles of the program, if enabled.
+    pub profiler: Option<ProfilerConfig<'a>>,
+
+    /// The ELF of the program, whose DWARF information maps the executed instructions to the
+    /// lines of the guest source, if coverage is enabled.
+    pub coverage: Option<&'a [u8]>,
+
+    /// The watchpoints, called on every access to a word of memory overlapping their range.
+    pub watchpoints: Vec<(Range<u32>, BoxedWatchpoint<'a>)>,
+
+    /// The maximum number of cycles between the start and the end markers of the cycle tracker
+    /// regions, by name.
+    pub region_budgets: HashMap<String, u64>,
+
+    /// The highest address, exclusive, which the program may access below its stack, if set.
+    pub max_memory: Option<u32>,
+
+    /// The maximum size of the stack of the program in bytes, if set.
+    pub max_stack_size: Option<u32>,
+
+    /// The number of cycles after which the execution pauses at the end of a continuation
+    /// segment, if set.
+    pub segment_cycles: Option<u64>,
+
+    /// The channel to send the state of the execution to when it pauses at the end of a
+    /// continuation segment, if set.
+    pub continuation_tx: Option<Sender<ContinuationState>>,
+
+    /// The handler of the progress events of the proof of the execution.
+    pub progress: Progress,
 }
 
 /// A builder for [`ZKMContext`].
@@ -30,9 +98,23 @@ pub struct ZKMContext<'a> {
 pub struct ZKMContextBuilder<'a> {
     no_default_hooks: bool,
     hook_registry_entries: Vec<(u32, BoxedHook<'a>)>,
+    syscall_handler_entries: Vec<(Range<u32>, BoxedSyscallHandler<'a>)>,
     subproof_verifier: Option<&'a dyn SubproofVerifier>,
     max_cycles: Option<u64>,
     skip_deferred_proof_verification: bool,
+    input_transcript: bool,
+    state_digest_interval: Option<u64>,
+    cost_estimation: bool,
+    opcode_sample: Option<(u64, Sender<OpcodeSample>)>,
+    rng_seed: Option<[u8; 32]>,
+    time_base: Option<Duration>,
+    profiler: Option<ProfilerConfig<'a>>,
+    coverage: Option<&'a [u8]>,
+    watchpoints: Vec<(Range<u32>, BoxedWatchpoint<'a>)>,
+    region_budgets: HashMap<String, u64>,
+    max_memory: Option<u32>,
+    max_stack_size: Option<u32>,
+    segment_cycles: Option<u64>,
 }
 
 impl<'a> ZKMContext<'a> {
@@ -69,14 +151,45 @@ impl<'a> ZKMContextBuilder<'a> {
                 table.extend(take(&mut self.hook_registry_entries));
                 HookRegistry { table }
             });
+        let syscall_handlers =
+            SyscallHandlerRegistry { table: take(&mut self.syscall_handler_entries) };
         let subproof_verifier = take(&mut self.subproof_verifier);
         let cycle_limit = take(&mut self.max_cycles);
         let skip_deferred_proof_verification = take(&mut self.skip_deferred_proof_verification);
+        let input_transcript = take(&mut self.input_transcript);
+        let state_digest_interval = take(&mut self.state_digest_interval);
+        let cost_estimation = take(&mut self.cost_estimation);
+        let opcode_sample = take(&mut self.opcode_sample);
+        let rng_seed = take(&mut self.rng_seed);
+        let time_base = take(&mut self.time_base);
+        let profiler = take(&mut self.profiler);
+        let coverage = take(&mut self.coverage);
+        let watchpoints = take(&mut self.watchpoints);
+        let region_budgets = take(&mut self.region_budgets);
+        let max_memory = take(&mut self.max_memory);
+        let max_stack_size = take(&mut self.max_stack_size);
+        let segment_cycles = take(&mut self.segment_cycles);
         ZKMContext {
             hook_registry,
+            syscall_handlers,
             subproof_verifier,
             max_cycles: cycle_limit,
             skip_deferred_proof_verification,
+            input_transcript,
+            state_digest_interval,
+            cost_estimation,
+            opcode_sample,
+            rng_seed,
+            time_base,
+            profiler,
+            coverage,
+            watchpoints,
+            region_budgets,
+            max_memory,
+            max_stack_size,
+            segment_cycles,
+            continuation_tx: None,
+            progress: Progress::default(),
         }
     }
 
@@ -94,6 +207,19 @@ impl<'a> ZKMContextBuilder<'a> {
         self
     }
 
+    /// Add a typed runtime hook into the context.
+    ///
+    /// The program sends a request of type `Req` by calling `zkm_zkvm::io::call_hook` with `fd`,
+    /// and reads back the response of type `Resp`. See [`typed_hook`].
+    pub fn typed_hook<Req: DeserializeOwned, Resp: Serialize>(
+        &mut self,
+        fd: u32,
+        f: impl FnMut(&HookEnv, Req) -> Resp + Send + Sync + 'a,
+    ) -> &mut Self {
+        self.hook_registry_entries.push((fd, typed_hook(f)));
+        self
+    }
+
     /// Avoid registering the default hooks in the runtime.
     ///
     /// It is not necessary to call this to override hooks --- instead, simply
@@ -103,6 +229,34 @@ impl<'a> ZKMContextBuilder<'a> {
         self
     }
 
+    /// Add a handler for the user-defined syscalls with codes in `code_range`.
+    ///
+    /// The handler is invoked with the values of the registers `A0..=A3` and the memory slice
+    /// `[A0, A0 + A1)`, and returns a list of arbitrary data that may be read with successive calls
+    /// to [`zkm_zkvm::io::read`]. If several handlers serve the same code, the one registered last
+    /// is used.
+    ///
+    /// User syscalls are meant for prototyping precompiles in software. They are proven as no-ops,
+    /// and the data returned by the handler is not constrained by the proof, like the data of a
+    /// hook, so the program must check it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `code_range` is not contained in [`USER_SYSCALL_RANGE`].
+    pub fn with_syscall_handler(
+        &mut self,
+        code_range: Range<u32>,
+        f: impl FnMut(HookEnv, UserSyscallArgs) -> Vec<Vec<u8>> + Send + Sync + 'a,
+    ) -> &mut Self {
+        assert!(
+            USER_SYSCALL_RANGE.start <= code_range.start
+                && code_range.end <= USER_SYSCALL_RANGE.end,
+            "syscall code range {code_range:?} is outside of the user range {USER_SYSCALL_RANGE:?}"
+        );
+        self.syscall_handler_entries.push((code_range, syscall_handlerify(f)));
+        self
+    }
+
     /// Add a subproof verifier.
     ///
     /// The verifier is used to sanity check `verify_zkm_proof` during runtime.
@@ -122,19 +276,203 @@ impl<'a> ZKMContextBuilder<'a> {
         self.skip_deferred_proof_verification = skip;
         self
     }
+
+    /// Compute a digest of all input bytes read by the program.
+    ///
+    /// The digest is exposed as [`crate::ExecutionReport::input_digest`], allowing hosts to attest
+    /// exactly which inputs were consumed during execution.
+    pub fn input_transcript(&mut self, enabled: bool) -> &mut Self {
+        self.input_transcript = enabled;
+        self
+    }
+
+    /// Maintain a rolling digest of the execution state, snapshotted every `interval` cycles and
+    /// at the end of execution.
+    ///
+    /// The snapshots are exposed as [`crate::ExecutionReport::state_digests`]. Comparing the
+    /// snapshots of two runs locates the cycles in which they diverged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn state_digests(&mut self, interval: u64) -> &mut Self {
+        assert!(interval > 0, "state digest interval must be positive");
+        self.state_digest_interval = Some(interval);
+        self
+    }
+
+    /// Estimate the number of events of each chip in every shard, from the same counts the
+    /// executor uses to decide where shards end.
+    ///
+    /// The estimates are exposed as [`crate::ExecutionReport::shard_event_counts`], allowing the
+    /// cost of proving a program to be estimated without generating its trace.
+    pub fn cost_estimation(&mut self, enabled: bool) -> &mut Self {
+        self.cost_estimation = enabled;
+        self
+    }
+
+    /// Send the opcode histogram of the first `cycles` cycles of the execution to `tx`, while the
+    /// execution continues.
+    ///
+    /// The sample is sent earlier if the first shard ends or the program halts before `cycles`
+    /// cycles. It allows the prover to predict the shapes of the shards and prepare for them
+    /// before the execution completes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cycles` is zero.
+    pub fn opcode_sample(&mut self, cycles: u64, tx: Sender<OpcodeSample>) -> &mut Self {
+        assert!(cycles > 0, "opcode sample cycles must be positive");
+        self.opcode_sample = Some((cycles, tx));
+        self
+    }
+
+    /// Seed the random number generator of the program, used by `getrandom` when the program is
+    /// built with the `seeded-rng` feature of `zkm-zkvm`.
+    ///
+    /// The seed is committed at the end of the public values, so a verifier can check which seed
+    /// the randomness of the program was drawn from. See
+    /// [`zkm_primitives::io::ZKMPublicValues::rng_seed`].
+    pub fn with_rng_seed(&mut self, seed: [u8; 32]) -> &mut Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    /// Start the deterministic clock of the program at `time`.
+    ///
+    /// The clock read with `zkm_zkvm::lib::time::now` advances with the cycles of the program,
+    /// so that every execution of the program on the same input reads the same times. Once the
+    /// program reads the clock, the time base is committed at the end of the public values, see
+    /// [`zkm_primitives::io::ZKMPublicValues::time_base`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is before the Unix epoch.
+    pub fn with_time_base(&mut self, time: SystemTime) -> &mut Self {
+        let time_base =
+            time.duration_since(UNIX_EPOCH).expect("time base is before the Unix epoch");
+        self.time_base = Some(time_base);
+        self
+    }
+
+    /// Profile the cycles of the program, sampling its call stack every `sample_interval` cycles.
+    ///
+    /// The samples are symbolized against the DWARF information of `elf`, falling back to its
+    /// symbol table, and written to `output` in the folded stack format when the execution ends,
+    /// to be rendered by `inferno-flamegraph` or speedscope. Unconstrained cycles are not
+    /// sampled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sample_interval` is zero.
+    pub fn profiling(
+        &mut self,
+        elf: &'a [u8],
+        output: impl Into<PathBuf>,
+        sample_interval: u64,
+    ) -> &mut Self {
+        assert!(sample_interval > 0, "profiler sample interval must be positive");
+        self.profiler = Some(ProfilerConfig { elf, output: output.into(), sample_interval });
+        self
+    }
+
+    /// Record which lines of the guest source the program executes, and how many times.
+    ///
+    /// The executed instructions are mapped to lines with the DWARF information of `elf`, so the
+    /// guest must be built with debug information. The coverage is exposed as
+    /// [`crate::ExecutionReport::coverage`], and can be exported as an lcov tracefile.
+    pub fn coverage(&mut self, elf: &'a [u8]) -> &mut Self {
+        self.coverage = Some(elf);
+        self
+    }
+
+    /// Call `f` on every read and write by the program of a word of memory overlapping `range`,
+    /// with the pc and the cycle of the access.
+    ///
+    /// Watchpoints see the accesses of the instructions and of the precompiles, including in
+    /// unconstrained blocks, but not the accesses to the registers. They help find which code
+    /// corrupts a buffer, for example in a C library linked into the guest.
+    pub fn watchpoint(
+        &mut self,
+        range: Range<u32>,
+        f: impl FnMut(&WatchEvent) + Send + Sync + 'a,
+    ) -> &mut Self {
+        self.watchpoints.push((range, watchpointify(f)));
+        self
+    }
+
+    /// Fail the execution with [`crate::ExecutionError::RegionBudgetExceeded`] if the program
+    /// spends more than `max_cycles` cycles between the markers `cycle-tracker-start: {region}`
+    /// and `cycle-tracker-end: {region}`, or their `report` variants.
+    ///
+    /// The budget bounds every run of the region, so a host can cap the cycles of an untrusted
+    /// part of the guest, such as a plugin, without capping the whole execution.
+    pub fn max_cycles_between_markers(
+        &mut self,
+        region: impl Into<String>,
+        max_cycles: u64,
+    ) -> &mut Self {
+        self.region_budgets.insert(region.into(), max_cycles);
+        self
+    }
+
+    /// Fail the execution with [`crate::ExecutionError::OutOfMemory`] if the program accesses an
+    /// address at or above `max_memory` below its stack, such as a heap allocation past the
+    /// limit.
+    ///
+    /// The peak usage is exposed as [`crate::ExecutionReport::memory_usage`].
+    pub fn max_memory(&mut self, max_memory: u32) -> &mut Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
+    /// Fail the execution with [`crate::ExecutionError::StackOverflow`] if the stack of the
+    /// program grows beyond `max_stack_size` bytes below [`crate::INIT_SP`], for example on a
+    /// deep recursion.
+    ///
+    /// The peak usage is exposed as [`crate::ExecutionReport::memory_usage`].
+    pub fn max_stack_size(&mut self, max_stack_size: u32) -> &mut Self {
+        self.max_stack_size = Some(max_stack_size);
+        self
+    }
+
+    /// Pause the execution after `cycles` cycles instead of running it to completion, to prove
+    /// it as a chain of segments.
+    ///
+    /// The execution only pauses outside of a delay slot, so it may run a cycle longer. Its state
+    /// is then exported with [`crate::Executor::continuation_state`], and the next segment
+    /// resumes from it.
+    pub fn segment_cycles(&mut self, cycles: u64) -> &mut Self {
+        self.segment_cycles = Some(cycles);
+        self
+    }
+
+    /// Log every read and write by the program of a word of memory overlapping `range`. See
+    /// [`Self::watchpoint`].
+    pub fn log_watchpoint(&mut self, range: Range<u32>) -> &mut Self {
+        self.watchpoint(range, |event| tracing::info!("watchpoint: {event}"))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
     use crate::{subproof::NoOpSubproofVerifier, ZKMContext};
 
     #[test]
     fn defaults() {
-        let ZKMContext { hook_registry, subproof_verifier, max_cycles: cycle_limit, .. } =
-            ZKMContext::builder().build();
+        let ZKMContext {
+            hook_registry,
+            subproof_verifier,
+            max_cycles: cycle_limit,
+            input_transcript,
+            ..
+        } = ZKMContext::builder().build();
         assert!(hook_registry.is_none());
         assert!(subproof_verifier.is_none());
         assert!(cycle_limit.is_none());
+        assert!(!input_transcript);
     }
 
     #[test]
@@ -158,6 +496,20 @@ mod tests {
         assert_eq!(&hook_registry.unwrap().table.into_keys().collect::<Vec<_>>(), &[30]);
     }
 
+    #[test]
+    fn with_syscall_handler() {
+        let ZKMContext { syscall_handlers, .. } =
+            ZKMContext::builder().with_syscall_handler(0xC0..0xC4, |_, _| vec![]).build();
+        assert!(syscall_handlers.get(0xC3).is_some());
+        assert!(syscall_handlers.get(0xC4).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the user range")]
+    fn with_syscall_handler_outside_user_range() {
+        ZKMContext::builder().with_syscall_handler(0x10..0x20, |_, _| vec![]);
+    }
+
     #[test]
     fn subproof_verifier() {
         let verifier = NoOpSubproofVerifier;
@@ -165,4 +517,46 @@ mod tests {
             ZKMContext::builder().subproof_verifier(&verifier).build();
         assert!(subproof_verifier.is_some());
     }
+
+    #[test]
+    fn watchpoints() {
+        let ZKMContext { watchpoints, .. } =
+            ZKMContext::builder().log_watchpoint(0x1000..0x1004).watchpoint(0..4, |_| {}).build();
+        let ranges = watchpoints.into_iter().map(|(range, _)| range).collect::<Vec<_>>();
+        assert_eq!(ranges, [0x1000..0x1004, 0..4]);
+    }
+
+    #[test]
+    fn region_budgets() {
+        let ZKMContext { region_budgets, .. } = ZKMContext::builder()
+            .max_cycles_between_markers("plugin", 1000)
+            .max_cycles_between_markers("plugin", 2000)
+            .build();
+        assert_eq!(region_budgets.len(), 1);
+        assert_eq!(region_budgets["plugin"], 2000);
+    }
+
+    #[test]
+    fn memory_limits() {
+        let ZKMContext { max_memory, max_stack_size, .. } =
+            ZKMContext::builder().max_memory(0x1000_0000).max_stack_size(1 << 20).build();
+        assert_eq!(max_memory, Some(0x1000_0000));
+        assert_eq!(max_stack_size, Some(1 << 20));
+    }
+
+    #[test]
+    fn segment_cycles() {
+        let ZKMContext { segment_cycles, .. } =
+            ZKMContext::builder().segment_cycles(1 << 20).build();
+        assert_eq!(segment_cycles, Some(1 << 20));
+    }
+
+    #[test]
+    fn with_time_base() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let ZKMContext { time_base, rng_seed, .. } =
+            ZKMContext::builder().with_time_base(time).build();
+        assert_eq!(time_base, Some(Duration::from_secs(1_700_000_000)));
+        assert!(rng_seed.is_none());
+    }
 }
@@ -0,0 +1,169 @@
+//! Continuations, to prove one execution of a program as a chain of segments.
+//!
+//! With [`crate::ZKMContextBuilder::segment_cycles`], the execution pauses after the given number
+//! of cycles instead of running to completion. Its state is exported with
+//! [`Executor::continuation_state`], and the next segment executes the program returned by
+//! [`ContinuationState::resume`], whose memory image is the memory of the paused execution.
+//!
+//! A paused execution does not finalize its memory, so the global cumulative sum of its proof is
+//! the opposite of [`ContinuationState::memory_digest`] instead of zero: the proof of a segment
+//! commits to the memory and registers it ends with, and the verifying key of the next segment
+//! commits to the same memory and registers through its initial global cumulative sum.
+
+use std::collections::BTreeMap;
+
+use p3_field::PrimeField32;
+use p3_maybe_rayon::prelude::{IntoParallelIterator, ParallelBridge, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zkm_stark::{
+    septic_curve::{SepticCurve, SepticCurveComplete},
+    septic_digest::SepticDigest,
+    septic_extension::SepticExtension,
+    LookupKind,
+};
+
+use crate::{events::MemoryRecord, Executor, Program, NUM_REGISTERS};
+
+/// The state of an execution paused at the end of a continuation segment, from which the next
+/// segment resumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuationState {
+    /// The program counter of the next instruction.
+    pub pc: u32,
+    /// The last record of every address accessed by the execution, registers included.
+    pub memory: BTreeMap<u32, MemoryRecord>,
+    /// The inputs which the execution did not read yet, read by the next segment.
+    pub input_stream: Vec<Vec<u8>>,
+    /// The number of cycles executed by the segment.
+    pub cycles: u64,
+}
+
+impl ContinuationState {
+    /// Returns `program` resuming from this state: it starts at [`Self::pc`] with the memory of
+    /// this state as its image.
+    #[must_use]
+    pub fn resume(&self, program: &Program) -> Program {
+        // The register `zero` is initialized by every execution, so it is not part of the image.
+        let image = self
+            .memory
+            .iter()
+            .filter(|(&addr, _)| addr != 0)
+            .map(|(&addr, record)| (addr, record.value))
+            .collect();
+        Program { pc_start: self.pc, next_pc: self.pc.wrapping_add(4), image, ..program.clone() }
+    }
+
+    /// The digest of the memory of this state, which the proof of the paused segment leaves
+    /// unmatched in its global cumulative sum.
+    ///
+    /// This is the sum of the memory finalization lookups the execution would have sent if it
+    /// halted instead of pausing.
+    pub fn memory_digest<F: PrimeField32>(&self) -> SepticDigest<F> {
+        let mut digests: Vec<SepticCurveComplete<F>> = self
+            .memory
+            .iter()
+            .par_bridge()
+            .map(|(&addr, record)| {
+                let values = [
+                    ((LookupKind::Memory as u32) << 16) + record.shard,
+                    record.timestamp,
+                    addr,
+                    record.value & 255,
+                    (record.value >> 8) & 255,
+                    (record.value >> 16) & 255,
+                    (record.value >> 24) & 255,
+                ];
+                let x_start =
+                    SepticExtension::<F>::from_base_fn(|i| F::from_canonical_u32(values[i]));
+                let (point, _) = SepticCurve::<F>::lift_x(x_start);
+                SepticCurveComplete::Affine(point)
+            })
+            .collect();
+        digests.push(SepticCurveComplete::Affine(SepticDigest::<F>::zero().0));
+        SepticDigest(
+            digests.into_par_iter().reduce(|| SepticCurveComplete::Infinity, |a, b| a + b).point(),
+        )
+    }
+
+    /// The SHA-256 hash of the program counter and of the value of every address of this state,
+    /// which identifies the state linking two segments.
+    #[must_use]
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.pc.to_le_bytes());
+        for (addr, record) in &self.memory {
+            hasher.update(addr.to_le_bytes());
+            hasher.update(record.value.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+}
+
+impl Executor<'_> {
+    /// Exports the state of the execution if it paused at the end of a continuation segment, see
+    /// [`crate::ZKMContextBuilder::segment_cycles`].
+    ///
+    /// The memory of the state is the memory the execution would have finalized if it halted.
+    #[must_use]
+    pub fn continuation_state(&self) -> Option<ContinuationState> {
+        if !self.state.paused {
+            return None;
+        }
+
+        // The address 0 is always finalized, like in `postprocess`.
+        let mut memory = BTreeMap::new();
+        let addr_0_record = self.state.memory.get(0).copied();
+        memory
+            .insert(0, addr_0_record.unwrap_or(MemoryRecord { value: 0, shard: 0, timestamp: 1 }));
+        for addr in 1..NUM_REGISTERS as u32 {
+            if let Some(record) = self.state.memory.registers.get(addr) {
+                memory.insert(addr, *record);
+            }
+        }
+        for addr in self.state.memory.page_table.keys() {
+            if addr != 0 {
+                memory.insert(addr, *self.state.memory.get(addr).unwrap());
+            }
+        }
+
+        Some(ContinuationState {
+            pc: self.state.pc,
+            memory,
+            input_stream: self.state.input_stream[self.state.input_stream_ptr..].to_vec(),
+            cycles: self.state.global_clk,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zkm_stark::ZKMCoreOpts;
+
+    use crate::{programs::tests::fibonacci_program, Executor, ZKMContext};
+
+    #[test]
+    fn test_continuation_resume() {
+        let program = fibonacci_program();
+        let mut runtime = Executor::new(program.clone(), ZKMCoreOpts::default());
+        runtime.run().unwrap();
+        assert!(runtime.continuation_state().is_none());
+        let (cycles, registers) = (runtime.state.global_clk, runtime.registers());
+
+        let (state_tx, state_rx) = std::sync::mpsc::channel();
+        let mut context = ZKMContext::builder().segment_cycles(cycles / 2).build();
+        context.continuation_tx = Some(state_tx);
+        let mut first = Executor::with_context(program.clone(), ZKMCoreOpts::default(), context);
+        first.run().unwrap();
+        let state = first.continuation_state().unwrap();
+        assert_eq!(state_rx.try_recv().unwrap().hash(), state.hash());
+        assert!(state.cycles >= cycles / 2 && state.cycles < cycles);
+        assert!(first.records.iter().all(|record| record.global_memory_finalize_events.is_empty()));
+
+        let mut second = Executor::new(state.resume(&program), ZKMCoreOpts::default());
+        second.run().unwrap();
+        assert!(second.continuation_state().is_none());
+        assert_eq!(state.cycles + second.state.global_clk, cycles);
+        assert_eq!(second.registers(), registers);
+    }
+}
@@ -2,7 +2,9 @@ use enum_map::EnumMap;
 use hashbrown::HashMap;
 use p3_koala_bear::KoalaBear;
 
-use crate::{events::NUM_LOCAL_MEMORY_ENTRIES_PER_ROW_EXEC, MipsAirId, Opcode};
+use crate::{
+    events::NUM_LOCAL_MEMORY_ENTRIES_PER_ROW_EXEC, syscalls::SyscallCode, MipsAirId, Opcode,
+};
 
 const BYTE_NUM_ROWS: u64 = 1 << 16;
 const MAX_PROGRAM_SIZE: u64 = 1 << 22;
@@ -223,3 +225,153 @@ pub fn pad_mips_event_counts(
     });
     event_counts
 }
+
+/// Returns the AIRs proving an instruction of `opcode`, besides the cpu AIR.
+#[must_use]
+pub fn opcode_air_ids(opcode: Opcode) -> &'static [MipsAirId] {
+    match opcode {
+        Opcode::ADD | Opcode::SUB => &[MipsAirId::AddSub],
+        Opcode::MUL | Opcode::MULT | Opcode::MULTU => &[MipsAirId::Mul],
+        // The divrem chip checks its result with the mul and lt chips.
+        Opcode::DIV | Opcode::DIVU | Opcode::MOD | Opcode::MODU => {
+            &[MipsAirId::DivRem, MipsAirId::Mul, MipsAirId::Lt]
+        }
+        Opcode::SLL => &[MipsAirId::ShiftLeft],
+        Opcode::SRL | Opcode::SRA | Opcode::ROR => &[MipsAirId::ShiftRight],
+        Opcode::SLT | Opcode::SLTU => &[MipsAirId::Lt],
+        Opcode::AND | Opcode::OR | Opcode::XOR | Opcode::NOR => &[MipsAirId::Bitwise],
+        Opcode::CLZ | Opcode::CLO => &[MipsAirId::CloClz],
+        Opcode::BEQ | Opcode::BGEZ | Opcode::BGTZ | Opcode::BLEZ | Opcode::BLTZ | Opcode::BNE => {
+            &[MipsAirId::Branch]
+        }
+        Opcode::Jump | Opcode::Jumpi | Opcode::JumpDirect => &[MipsAirId::Jump],
+        Opcode::SYSCALL => &[MipsAirId::SyscallInstrs],
+        Opcode::LB
+        | Opcode::LBU
+        | Opcode::LH
+        | Opcode::LHU
+        | Opcode::LW
+        | Opcode::LWL
+        | Opcode::LWR
+        | Opcode::LL
+        | Opcode::SB
+        | Opcode::SH
+        | Opcode::SW
+        | Opcode::SWL
+        | Opcode::SWR
+        | Opcode::SC => &[MipsAirId::MemoryInstrs],
+        Opcode::INS
+        | Opcode::MADDU
+        | Opcode::MSUBU
+        | Opcode::MADD
+        | Opcode::MSUB
+        | Opcode::EXT
+        | Opcode::TEQ
+        | Opcode::SEXT => &[MipsAirId::MiscInstrs],
+        Opcode::MEQ | Opcode::MNE | Opcode::WSBH => &[MipsAirId::MovCond],
+        Opcode::UNIMPL => &[],
+    }
+}
+
+/// Returns the AIR proving the calls of `syscall`, if the syscall has its own AIR.
+#[must_use]
+pub fn syscall_air_id(syscall: SyscallCode) -> Option<MipsAirId> {
+    let air = match syscall {
+        SyscallCode::SHA_EXTEND => MipsAirId::ShaExtend,
+        SyscallCode::SHA_COMPRESS => MipsAirId::ShaCompress,
+        SyscallCode::ED_ADD => MipsAirId::EdAddAssign,
+        SyscallCode::ED_DECOMPRESS => MipsAirId::EdDecompress,
+        SyscallCode::KECCAK_SPONGE => MipsAirId::KeccakSponge,
+        SyscallCode::SECP256K1_ADD => MipsAirId::Secp256k1AddAssign,
+        SyscallCode::SECP256K1_DOUBLE => MipsAirId::Secp256k1DoubleAssign,
+        SyscallCode::SECP256K1_DECOMPRESS => MipsAirId::Secp256k1Decompress,
+        SyscallCode::SECP256R1_ADD => MipsAirId::Secp256r1AddAssign,
+        SyscallCode::SECP256R1_DOUBLE => MipsAirId::Secp256r1DoubleAssign,
+        SyscallCode::SECP256R1_DECOMPRESS => MipsAirId::Secp256r1Decompress,
+        SyscallCode::BN254_ADD => MipsAirId::Bn254AddAssign,
+        SyscallCode::BN254_DOUBLE => MipsAirId::Bn254DoubleAssign,
+        SyscallCode::BLS12381_ADD => MipsAirId::Bls12381AddAssign,
+        SyscallCode::BLS12381_DOUBLE => MipsAirId::Bls12381DoubleAssign,
+        SyscallCode::BLS12381_DECOMPRESS => MipsAirId::Bls12381Decompress,
+        SyscallCode::UINT256_MUL => MipsAirId::Uint256MulMod,
+        SyscallCode::U256XU2048_MUL => MipsAirId::U256XU2048Mul,
+        SyscallCode::BLS12381_FP_ADD
+        | SyscallCode::BLS12381_FP_SUB
+        | SyscallCode::BLS12381_FP_MUL => MipsAirId::Bls12381FpOpAssign,
+        SyscallCode::BLS12381_FP2_ADD | SyscallCode::BLS12381_FP2_SUB => {
+            MipsAirId::Bls12831Fp2AddSubAssign
+        }
+        SyscallCode::BLS12381_FP2_MUL => MipsAirId::Bls12831Fp2MulAssign,
+        SyscallCode::BN254_FP_ADD | SyscallCode::BN254_FP_SUB | SyscallCode::BN254_FP_MUL => {
+            MipsAirId::Bn254FpOpAssign
+        }
+        SyscallCode::BN254_FP2_ADD | SyscallCode::BN254_FP2_SUB => MipsAirId::Bn254Fp2AddSubAssign,
+        SyscallCode::BN254_FP2_MUL => MipsAirId::Bn254Fp2MulAssign,
+        SyscallCode::POSEIDON2_PERMUTE => MipsAirId::Poseidon2Permute,
+        SyscallCode::BLAKE3_COMPRESS_INNER => MipsAirId::Blake3CompressInner,
+        SyscallCode::SHA512_EXTEND => MipsAirId::Sha512Extend,
+        SyscallCode::SHA512_COMPRESS => MipsAirId::Sha512Compress,
+        SyscallCode::SYS_LINUX
+        | SyscallCode::SYS_MMAP
+        | SyscallCode::SYS_MMAP2
+        | SyscallCode::SYS_BRK
+        | SyscallCode::SYS_CLONE
+        | SyscallCode::SYS_EXT_GROUP
+        | SyscallCode::SYS_READ
+        | SyscallCode::SYS_WRITE
+        | SyscallCode::SYS_FCNTL
+        | SyscallCode::SYS_OPEN
+        | SyscallCode::SYS_CLOSE
+        | SyscallCode::SYS_MUNMAP
+        | SyscallCode::SYS_RT_SIGACTION
+        | SyscallCode::SYS_RT_SIGPROCMASK
+        | SyscallCode::SYS_SIGALTSTACK
+        | SyscallCode::SYS_FSTAT64
+        | SyscallCode::SYS_MADVISE
+        | SyscallCode::SYS_GETTID
+        | SyscallCode::SYS_SCHED_GETAFFINITY
+        | SyscallCode::SYS_CLOCK_GETTIME
+        | SyscallCode::SYS_OPENAT
+        | SyscallCode::SYS_PRLIMIT64
+        | SyscallCode::SYS_FUTEX
+        | SyscallCode::SYS_SCHED_YIELD
+        | SyscallCode::SYS_NANOSLEEP => MipsAirId::SysLinux,
+        _ => return None,
+    };
+    Some(air)
+}
+
+/// Returns the gas of an instruction of `opcode`: the cells of the cpu row and of the rows of the
+/// AIRs proving it, with the per-row costs in `costs_per_air`.
+#[must_use]
+pub fn opcode_gas(opcode: Opcode, costs_per_air: &HashMap<MipsAirId, u64>) -> u64 {
+    costs_per_air[&MipsAirId::Cpu]
+        + opcode_air_ids(opcode).iter().map(|air| costs_per_air[air]).sum::<u64>()
+}
+
+/// Returns the gas of a call of `syscall`, on top of the gas of its `SYSCALL` instruction.
+///
+/// Syscalls with their own table are sent from the syscall core chip to the syscall precompile
+/// chip through the global chip, and pay for those rows as well.
+#[must_use]
+pub fn syscall_gas(syscall: SyscallCode, costs_per_air: &HashMap<MipsAirId, u64>) -> u64 {
+    let mut gas = syscall_air_id(syscall).map_or(0, |air| costs_per_air[&air]);
+    if syscall.should_send() == 1 {
+        gas += costs_per_air[&MipsAirId::SyscallCore]
+            + costs_per_air[&MipsAirId::Global]
+            + costs_per_air[&MipsAirId::SyscallPrecompile];
+    }
+    gas
+}
+
+/// Returns the gas of `touched_addresses` memory addresses: their global initialization and
+/// finalization, and their local memory rows.
+#[must_use]
+pub fn memory_gas(touched_addresses: u64, costs_per_air: &HashMap<MipsAirId, u64>) -> u64 {
+    let global = costs_per_air[&MipsAirId::MemoryGlobalInit]
+        + costs_per_air[&MipsAirId::MemoryGlobalFinalize]
+        + 2 * costs_per_air[&MipsAirId::Global];
+    touched_addresses * global
+        + (touched_addresses * costs_per_air[&MipsAirId::MemoryLocal])
+            .div_ceil(NUM_LOCAL_MEMORY_ENTRIES_PER_ROW_EXEC as u64)
+}
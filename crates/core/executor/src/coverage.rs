@@ -0,0 +1,103 @@
+//! Line coverage of the guest source, from the instructions executed by a program.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use elf::{endian::LittleEndian, ElfBytes};
+
+use crate::{profiler::load_dwarf, Program};
+
+/// The number of times every line of the guest source was executed, collected when coverage is
+/// enabled with [`crate::ZKMContextBuilder::coverage`].
+///
+/// A line counts as executed as many times as the most executed of its instructions. Lines with
+/// instructions which were never executed are recorded with a count of zero.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// The execution count of every line, by source file.
+    pub files: BTreeMap<PathBuf, BTreeMap<u32, u64>>,
+}
+
+impl Coverage {
+    /// Maps the execution counts of the instructions of `program` to the lines of the guest
+    /// source, with the DWARF information of its `elf`.
+    ///
+    /// The coverage is empty if the ELF has no DWARF information.
+    #[must_use]
+    pub(crate) fn new(elf: &[u8], program: &Program, hits: &[u64]) -> Self {
+        let mut coverage = Self::default();
+        let Some(dwarf) =
+            ElfBytes::<LittleEndian>::minimal_parse(elf).ok().and_then(|elf| load_dwarf(&elf))
+        else {
+            tracing::warn!("the ELF has no DWARF information, the coverage is empty");
+            return coverage;
+        };
+
+        let start = u64::from(program.pc_base);
+        let end = start + 4 * hits.len() as u64;
+        let Ok(locations) = dwarf.find_location_range(start, end) else {
+            return coverage;
+        };
+        for (addr, len, location) in locations {
+            let (Some(file), Some(line)) = (location.file, location.line) else {
+                continue;
+            };
+            let first = (addr.saturating_sub(start) / 4) as usize;
+            let last = (addr + len).saturating_sub(start).div_ceil(4) as usize;
+            let count = hits[first.min(hits.len())..last.min(hits.len())].iter().max().copied();
+            let entry = coverage.files.entry(file.into()).or_default().entry(line).or_default();
+            *entry = (*entry).max(count.unwrap_or_default());
+        }
+        coverage
+    }
+
+    /// Returns the number of times the given line was executed, or `None` if it has no
+    /// instructions.
+    #[must_use]
+    pub fn line_count(&self, file: impl AsRef<Path>, line: u32) -> Option<u64> {
+        self.files.get(file.as_ref())?.get(&line).copied()
+    }
+
+    /// Formats the coverage as an lcov tracefile, read by `genhtml` and most coverage tools.
+    #[must_use]
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (file, lines) in &self.files {
+            writeln!(lcov, "TN:").unwrap();
+            writeln!(lcov, "SF:{}", file.display()).unwrap();
+            for (line, count) in lines {
+                writeln!(lcov, "DA:{line},{count}").unwrap();
+            }
+            writeln!(lcov, "LF:{}", lines.len()).unwrap();
+            writeln!(lcov, "LH:{}", lines.values().filter(|&&count| count > 0).count()).unwrap();
+            writeln!(lcov, "end_of_record").unwrap();
+        }
+        lcov
+    }
+
+    /// Writes the coverage to `path` as an lcov tracefile.
+    pub fn write_lcov(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_lcov())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_lcov() {
+        let mut coverage = Coverage::default();
+        coverage.files.insert("src/main.rs".into(), BTreeMap::from([(3, 1), (4, 0), (7, 12)]));
+        assert_eq!(coverage.line_count("src/main.rs", 7), Some(12));
+        assert_eq!(coverage.line_count("src/main.rs", 5), None);
+        assert_eq!(
+            coverage.to_lcov(),
+            "TN:\nSF:src/main.rs\nDA:3,1\nDA:4,0\nDA:7,12\nLF:3\nLH:2\nend_of_record\n"
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! A stub of the GDB remote serial protocol, to debug a program with `gdb-multiarch` or LLDB.
+//!
+//! The stub serves a single debugger over TCP. It supports reading and writing the registers and
+//! the memory, software breakpoints, single-stepping and continuing, and interrupting a running
+//! program. The registers follow the layout GDB uses for 32-bit MIPS without a target
+//! description: the general purpose registers, `sr`, `lo`, `hi`, `bad`, `cause` and `pc`.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, BufReader, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use thiserror::Error;
+
+use crate::{events::MemoryRecord, ExecutionError, Executor, ExecutorMode, Register};
+
+/// The number of registers sent in reply to the `g` packet.
+const NUM_GDB_REGISTERS: u32 = 38;
+
+/// The GDB number of the program counter.
+const GDB_PC: u32 = 37;
+
+/// The maximum size of a packet, in bytes, advertised to the debugger. Longer packets are
+/// rejected, and memory reads are truncated to fit in a reply.
+const MAX_PACKET_SIZE: usize = 0x4000;
+
+/// The number of cycles between two checks for an interrupt from the debugger while the program
+/// runs.
+const INTERRUPT_CHECK_INTERVAL: u64 = 1 << 16;
+
+/// The signal reported when the program stops on a breakpoint or after a step.
+const SIGTRAP: u8 = 5;
+
+/// The signal reported when the program is interrupted by the debugger.
+const SIGINT: u8 = 2;
+
+/// The signal reported when the program fails.
+const SIGABRT: u8 = 6;
+
+/// An error returned by [`Executor::debug_listen`].
+#[derive(Error, Debug)]
+pub enum DebugError {
+    #[error("debugger connection error: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+}
+
+/// Why the program stopped running.
+enum Stop {
+    /// The program stopped with the given signal, and may resume.
+    Signal(u8),
+    /// The program halted with the given exit code.
+    Exited(u32),
+    /// The program failed.
+    Failed(ExecutionError),
+}
+
+impl Executor<'_> {
+    /// Waits for a debugger to connect on `port`, and runs the program under its control until
+    /// it halts or the debugger detaches or kills it.
+    ///
+    /// Once attached, for example with `target remote :<port>` in `gdb-multiarch` after
+    /// `set architecture mips` and `set endian little`, the debugger can inspect and modify the
+    /// registers and the memory, set breakpoints, step and continue. The program is executed as
+    /// with [`Executor::run_fast`]. If the debugger detaches, the program runs to completion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection fails, or if the execution fails.
+    pub fn debug_listen(&mut self, port: u16) -> Result<(), DebugError> {
+        self.executor_mode = ExecutorMode::Simple;
+        self.print_report = true;
+        if self.state.global_clk == 0 {
+            self.initialize();
+        }
+
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        tracing::info!("waiting for a debugger on port {port}");
+        let (stream, addr) = listener.accept()?;
+        tracing::info!("debugger connected from {addr}");
+
+        let mut session = Session {
+            reader: BufReader::new(stream.try_clone()?),
+            stream,
+            breakpoints: BTreeSet::new(),
+        };
+        session.serve(self)
+    }
+}
+
+/// The connection to the debugger and the breakpoints it set.
+struct Session {
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+    breakpoints: BTreeSet<u32>,
+}
+
+impl Session {
+    /// Answers the packets of the debugger until the program ends or the debugger leaves.
+    fn serve(&mut self, executor: &mut Executor) -> Result<(), DebugError> {
+        loop {
+            let Some(packet) = self.read_packet()? else {
+                // The debugger closed the connection.
+                return Ok(());
+            };
+
+            let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+            let reply = match command {
+                "?" => format!("S{SIGTRAP:02x}"),
+                "g" => {
+                    let registers = (0..NUM_GDB_REGISTERS)
+                        .map(|n| read_register(executor, n).map_or("xxxxxxxx".into(), hex_word));
+                    registers.collect()
+                }
+                "p" => match u32::from_str_radix(args, 16) {
+                    Ok(n) => read_register(executor, n).map_or("xxxxxxxx".into(), hex_word),
+                    Err(_) => "E01".into(),
+                },
+                "P" => match parse_register_write(args) {
+                    Some((n, value)) if write_register(executor, n, value) => "OK".into(),
+                    _ => "E01".into(),
+                },
+                "m" => match parse_range(args) {
+                    Some((addr, len)) => {
+                        // Each byte takes two hex digits, and GDB accepts partial reads.
+                        let len = len.min((MAX_PACKET_SIZE / 2) as u32);
+                        (0..len)
+                            .map(|i| format!("{:02x}", executor.byte(addr.wrapping_add(i))))
+                            .collect()
+                    }
+                    None => "E01".into(),
+                },
+                "M" => match parse_memory_write(args) {
+                    Some((addr, bytes)) => {
+                        for (i, byte) in bytes.into_iter().enumerate() {
+                            write_byte(executor, addr.wrapping_add(i as u32), byte);
+                        }
+                        "OK".into()
+                    }
+                    None => "E01".into(),
+                },
+                "Z" | "z" => match parse_breakpoint(args) {
+                    Some(addr) => {
+                        if command == "Z" {
+                            self.breakpoints.insert(addr);
+                        } else {
+                            self.breakpoints.remove(&addr);
+                        }
+                        "OK".into()
+                    }
+                    // Only software breakpoints are supported.
+                    None => String::new(),
+                },
+                "s" | "c" => {
+                    let stop = self.resume(executor, command == "s")?;
+                    match stop {
+                        Stop::Signal(signal) => format!("S{signal:02x}"),
+                        Stop::Exited(code) => {
+                            self.write_packet(&format!("W{:02x}", code as u8))?;
+                            return match code {
+                                0 => Ok(()),
+                                code => Err(ExecutionError::HaltWithNonZeroExitCode(code).into()),
+                            };
+                        }
+                        Stop::Failed(err) => {
+                            self.write_packet(&format!("X{SIGABRT:02x}"))?;
+                            return Err(err.into());
+                        }
+                    }
+                }
+                "D" => {
+                    self.write_packet("OK")?;
+                    while !executor.step()? {}
+                    return Ok(());
+                }
+                "k" => return Ok(()),
+                "H" => "OK".into(),
+                "q" => match args.split(':').next().unwrap_or_default() {
+                    "Supported" => format!("PacketSize={MAX_PACKET_SIZE:x};swbreak+"),
+                    "Attached" => "1".into(),
+                    "C" => "QC1".into(),
+                    "fThreadInfo" => "m1".into(),
+                    "sThreadInfo" => "l".into(),
+                    _ => String::new(),
+                },
+                _ => String::new(),
+            };
+            self.write_packet(&reply)?;
+        }
+    }
+
+    /// Runs the program for a single cycle, or until it reaches a breakpoint, is interrupted by
+    /// the debugger or ends.
+    fn resume(&mut self, executor: &mut Executor, single_step: bool) -> io::Result<Stop> {
+        loop {
+            match executor.step() {
+                Ok(true) => return Ok(Stop::Exited(0)),
+                Ok(false) => {}
+                Err(ExecutionError::HaltWithNonZeroExitCode(code)) => {
+                    return Ok(Stop::Exited(code));
+                }
+                Err(err) => return Ok(Stop::Failed(err)),
+            }
+            if single_step || self.breakpoints.contains(&executor.state.pc) {
+                return Ok(Stop::Signal(SIGTRAP));
+            }
+            if executor.state.global_clk.is_multiple_of(INTERRUPT_CHECK_INTERVAL)
+                && self.interrupted()?
+            {
+                return Ok(Stop::Signal(SIGINT));
+            }
+        }
+    }
+
+    /// Whether the debugger sent an interrupt, without blocking.
+    fn interrupted(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut byte = [0u8];
+        let result = self.reader.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+        match result {
+            Ok(1) => Ok(byte[0] == 0x03),
+            Ok(_) => Ok(false),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads the next packet, acknowledging it, or returns `None` if the connection is closed.
+    ///
+    /// Packets with a wrong checksum are rejected, and the debugger is expected to send them
+    /// again. Packets longer than [`MAX_PACKET_SIZE`] are rejected as well.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        let mut byte = [0u8];
+        loop {
+            // Skip the acknowledgements and interrupts until the start of a packet.
+            loop {
+                if self.reader.read(&mut byte)? == 0 {
+                    return Ok(None);
+                }
+                if byte[0] == b'$' {
+                    break;
+                }
+            }
+
+            let mut data = Vec::new();
+            loop {
+                if self.reader.read(&mut byte)? == 0 {
+                    return Ok(None);
+                }
+                if byte[0] == b'#' {
+                    break;
+                }
+                // Keep one byte past the limit, to tell that the packet is too long.
+                if data.len() <= MAX_PACKET_SIZE {
+                    data.push(byte[0]);
+                }
+            }
+            let mut checksum = [0u8; 2];
+            self.reader.read_exact(&mut checksum)?;
+
+            let expected =
+                u8::from_str_radix(std::str::from_utf8(&checksum).unwrap_or_default(), 16);
+            if data.len() > MAX_PACKET_SIZE || expected != Ok(self::checksum(&data)) {
+                self.stream.write_all(b"-")?;
+                continue;
+            }
+            self.stream.write_all(b"+")?;
+            return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    /// Sends a packet. Its acknowledgement is skipped by the next [`Self::read_packet`].
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${data}#{:02x}", checksum(data.as_bytes()));
+        self.stream.write_all(packet.as_bytes())?;
+        self.stream.flush()
+    }
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+}
+
+/// Formats a word in target byte order, as GDB expects register values.
+fn hex_word(value: u32) -> String {
+    value.to_le_bytes().iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Reads the register with the given GDB number, or `None` if the executor does not model it.
+fn read_register(executor: &mut Executor, n: u32) -> Option<u32> {
+    match n {
+        0..32 => Some(executor.register(Register::from(n as u8))),
+        33 => Some(executor.register(Register::LO)),
+        34 => Some(executor.register(Register::HI)),
+        GDB_PC => Some(executor.state.pc),
+        _ => None,
+    }
+}
+
+/// Writes the register with the given GDB number, returning whether the executor models it.
+fn write_register(executor: &mut Executor, n: u32, value: u32) -> bool {
+    let register = match n {
+        // The zero register is hardwired.
+        0 => return true,
+        1..32 => n,
+        33 => Register::LO as u32,
+        34 => Register::HI as u32,
+        GDB_PC => {
+            executor.state.pc = value;
+            executor.state.next_pc = value.wrapping_add(4);
+            return true;
+        }
+        _ => return false,
+    };
+    let record = executor.state.memory.registers.get(register).copied().unwrap_or_default();
+    executor.state.memory.registers.insert(register, MemoryRecord { value, ..record });
+    true
+}
+
+fn write_byte(executor: &mut Executor, addr: u32, byte: u8) {
+    let aligned = addr - addr % 4;
+    let shift = (addr % 4) * 8;
+    let record = executor.state.memory.page_table.get(aligned).copied().unwrap_or_default();
+    let value = (record.value & !(0xff << shift)) | (u32::from(byte) << shift);
+    executor.state.memory.page_table.insert(aligned, MemoryRecord { value, ..record });
+}
+
+/// Parses `addr,len`.
+fn parse_range(args: &str) -> Option<(u32, u32)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((u32::from_str_radix(addr, 16).ok()?, u32::from_str_radix(len, 16).ok()?))
+}
+
+/// Parses `addr,len:bytes`.
+fn parse_memory_write(args: &str) -> Option<(u32, Vec<u8>)> {
+    let (range, bytes) = args.split_once(':')?;
+    let (addr, len) = parse_range(range)?;
+    let bytes = hex::decode(bytes).ok()?;
+    (bytes.len() == len as usize).then_some((addr, bytes))
+}
+
+/// Parses `n=value`, with the value in target byte order.
+fn parse_register_write(args: &str) -> Option<(u32, u32)> {
+    let (n, value) = args.split_once('=')?;
+    let value: [u8; 4] = hex::decode(value).ok()?.try_into().ok()?;
+    Some((u32::from_str_radix(n, 16).ok()?, u32::from_le_bytes(value)))
+}
+
+/// Parses `0,addr,kind`, the arguments of a software breakpoint.
+fn parse_breakpoint(args: &str) -> Option<u32> {
+    let mut args = args.split(',');
+    if args.next()? != "0" {
+        return None;
+    }
+    u32::from_str_radix(args.next()?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_packets() {
+        assert_eq!(checksum(b"OK"), 0x9a);
+        assert_eq!(hex_word(0x0040_0a10), "100a4000");
+        assert_eq!(parse_range("400a10,4"), Some((0x400a10, 4)));
+        assert_eq!(parse_memory_write("1000,2:beef"), Some((0x1000, vec![0xbe, 0xef])));
+        assert_eq!(parse_memory_write("1000,3:beef"), None);
+        assert_eq!(parse_register_write("25=100a4000"), Some((0x25, 0x0040_0a10)));
+        assert_eq!(parse_breakpoint("0,400a10,4"), Some(0x400a10));
+        assert_eq!(parse_breakpoint("1,400a10,4"), None);
+    }
+}
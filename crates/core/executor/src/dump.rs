@@ -0,0 +1,134 @@
+//! Dumps of the per-shard execution records, to debug a single shard without re-executing.
+//!
+//! A dump starts with [TRACE_DUMP_MAGIC] and the little-endian `u32` [TRACE_DUMP_VERSION],
+//! followed by the bincode encoded [Program]. Every shard follows as a little-endian `u64` length
+//! and its bincode encoded [ExecutionRecord], without its copy of the program.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::{ExecutionError, ExecutionRecord, Executor, ExecutorMode, Program};
+
+/// The magic bytes starting a trace dump.
+pub const TRACE_DUMP_MAGIC: [u8; 4] = *b"ZKMT";
+
+/// The version of the format written by [Executor::run_with_trace_dump].
+pub const TRACE_DUMP_VERSION: u32 = 1;
+
+/// An error returned when writing or reading a trace dump.
+#[derive(Error, Debug)]
+pub enum TraceDumpError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("serialization error: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("failed to execute program: {0}")]
+    Execution(#[from] ExecutionError),
+    #[error("not a Ziren trace dump")]
+    InvalidMagic,
+    #[error("unsupported trace dump version {0}")]
+    UnsupportedVersion(u32),
+    #[error("shard {shard} is not in the dump, which has {num_shards} shards")]
+    ShardNotFound { shard: u32, num_shards: u32 },
+}
+
+impl Executor<'_> {
+    /// Executes the program with tracing, writing the record of every shard to a dump at `path`
+    /// instead of keeping them in memory. Returns the number of shards written.
+    ///
+    /// Shards are numbered from 1 in the order they are executed, as when proving.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the program execution fails, or if the dump cannot
+    /// be written.
+    pub fn run_with_trace_dump(&mut self, path: impl AsRef<Path>) -> Result<u32, TraceDumpError> {
+        self.executor_mode = ExecutorMode::Trace;
+        self.print_report = true;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&TRACE_DUMP_MAGIC)?;
+        writer.write_all(&TRACE_DUMP_VERSION.to_le_bytes())?;
+        write_chunk(&mut writer, self.program.as_ref())?;
+
+        let mut num_shards = 0;
+        loop {
+            let done = self.execute()?;
+            for mut record in std::mem::take(&mut self.records) {
+                num_shards += 1;
+                record.public_values.shard = num_shards;
+                record.program = Arc::default();
+                write_chunk(&mut writer, &record)?;
+            }
+            if done {
+                break;
+            }
+        }
+        writer.flush()?;
+        Ok(num_shards)
+    }
+}
+
+/// Loads the record of `shard`, numbered from 1, from a dump written by
+/// [Executor::run_with_trace_dump].
+pub fn load_trace_dump_shard(
+    path: impl AsRef<Path>,
+    shard: u32,
+) -> Result<ExecutionRecord, TraceDumpError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != TRACE_DUMP_MAGIC {
+        return Err(TraceDumpError::InvalidMagic);
+    }
+    let version = read_u32(&mut reader)?;
+    if version != TRACE_DUMP_VERSION {
+        return Err(TraceDumpError::UnsupportedVersion(version));
+    }
+    let program: Program = read_chunk(&mut reader)?;
+
+    let mut num_shards = 0;
+    loop {
+        let mut len = [0; 8];
+        match reader.read_exact(&mut len) {
+            Ok(()) => num_shards += 1,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(TraceDumpError::ShardNotFound { shard, num_shards });
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let len = u64::from_le_bytes(len);
+        if num_shards == shard {
+            let mut record: ExecutionRecord = bincode::deserialize_from(reader.by_ref().take(len))?;
+            record.program = Arc::new(program);
+            return Ok(record);
+        }
+        reader.seek(SeekFrom::Current(len as i64))?;
+    }
+}
+
+fn write_chunk<T: Serialize>(writer: &mut impl Write, value: &T) -> Result<(), TraceDumpError> {
+    let bytes = bincode::serialize(value)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+fn read_chunk<T: DeserializeOwned>(reader: &mut impl Read) -> Result<T, TraceDumpError> {
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    Ok(bincode::deserialize_from(reader.take(u64::from_le_bytes(len)))?)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{
+    memory::{MemoryReadRecord, MemoryWriteRecord},
+    MemoryLocalEvent,
+};
+
+/// BLAKE3 Compress Inner Event.
+///
+/// This event is emitted when the rounds of a BLAKE3 compression are applied to a state.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Blake3CompressInnerEvent {
+    /// The shard number.
+    pub shard: u32,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The pointer to the state.
+    pub state_ptr: u32,
+    /// The pointer to the message block.
+    pub msg_ptr: u32,
+    /// The memory reads of the first message word of each G function.
+    pub mx_reads: Vec<MemoryReadRecord>,
+    /// The memory reads of the second message word of each G function.
+    pub my_reads: Vec<MemoryReadRecord>,
+    /// The memory writes of the `a`, `b`, `c` and `d` state words of each G function.
+    pub state_writes: Vec<MemoryWriteRecord>,
+    /// The local memory accesses.
+    pub local_mem_access: Vec<MemoryLocalEvent>,
+}
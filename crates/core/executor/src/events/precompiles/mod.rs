@@ -1,3 +1,4 @@
+mod blake3_compress;
 mod ec;
 mod edwards;
 mod fptower;
@@ -6,11 +7,14 @@ mod linux;
 mod poseidon2_permute;
 mod sha256_compress;
 mod sha256_extend;
+mod sha512_compress;
+mod sha512_extend;
 mod u256x2048_mul;
 mod uint256;
 
 use super::{MemoryLocalEvent, SyscallEvent};
 use crate::syscalls::SyscallCode;
+pub use blake3_compress::*;
 pub use ec::*;
 pub use edwards::*;
 pub use fptower::*;
@@ -21,6 +25,8 @@ pub use poseidon2_permute::*;
 use serde::{Deserialize, Serialize};
 pub use sha256_compress::*;
 pub use sha256_extend::*;
+pub use sha512_compress::*;
+pub use sha512_extend::*;
 use strum::{EnumIter, IntoEnumIterator};
 pub use u256x2048_mul::*;
 pub use uint256::*;
@@ -80,6 +86,12 @@ pub enum PrecompileEvent {
     U256xU2048Mul(U256xU2048MulEvent),
     /// Poseidon2 permutation precompile event.
     Poseidon2Permute(Poseidon2PermuteEvent),
+    /// BLAKE3 compress inner precompile event.
+    Blake3CompressInner(Blake3CompressInnerEvent),
+    /// Sha512 extend precompile event.
+    Sha512Extend(Sha512ExtendEvent),
+    /// Sha512 compress precompile event.
+    Sha512Compress(Sha512CompressEvent),
     /// linux precompile event.
     Linux(LinuxEvent),
 }
@@ -145,6 +157,15 @@ impl PrecompileLocalMemory for Vec<(SyscallEvent, PrecompileEvent)> {
                 PrecompileEvent::Poseidon2Permute(e) => {
                     iterators.push(e.local_mem_access.iter());
                 }
+                PrecompileEvent::Blake3CompressInner(e) => {
+                    iterators.push(e.local_mem_access.iter());
+                }
+                PrecompileEvent::Sha512Extend(e) => {
+                    iterators.push(e.local_mem_access.iter());
+                }
+                PrecompileEvent::Sha512Compress(e) => {
+                    iterators.push(e.local_mem_access.iter());
+                }
                 PrecompileEvent::Linux(e) => {
                     iterators.push(e.local_mem_access.iter());
                 }
@@ -202,6 +223,18 @@ impl PrecompileEvents {
         self.events.is_empty()
     }
 
+    /// The nonempty lists of precompile events, ordered by syscall code.
+    pub(crate) fn sorted_events(&self) -> Vec<(u32, &Vec<(SyscallEvent, PrecompileEvent)>)> {
+        let mut events = self
+            .events
+            .iter()
+            .filter(|(_, events)| !events.is_empty())
+            .map(|(code, events)| (code.syscall_id(), events))
+            .collect::<Vec<_>>();
+        events.sort_unstable_by_key(|(code, _)| *code);
+        events
+    }
+
     /// Get all the precompile events.
     pub fn all_events(&self) -> impl Iterator<Item = &(SyscallEvent, PrecompileEvent)> {
         self.events.values().flatten()
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{
+    memory::{MemoryReadRecord, MemoryWriteRecord},
+    MemoryLocalEvent,
+};
+
+/// SHA-512 Compress Event.
+///
+/// This event is emitted when a SHA-512 compress operation is performed. Every 64-bit word is
+/// accessed as its low word followed by its high word.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Sha512CompressEvent {
+    /// The shard number.
+    pub shard: u32,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The pointer to the message schedule.
+    pub w_ptr: u32,
+    /// The pointer to the state.
+    pub h_ptr: u32,
+    /// The message schedule.
+    pub w: Vec<u64>,
+    /// The state before the compression.
+    pub h: [u64; 8],
+    /// The memory records for the reads of the state.
+    pub h_read_records: [MemoryReadRecord; 16],
+    /// The memory records for the reads of the message schedule.
+    pub w_i_read_records: Vec<MemoryReadRecord>,
+    /// The memory records for the writes of the state.
+    pub h_write_records: [MemoryWriteRecord; 16],
+    /// The local memory accesses.
+    pub local_mem_access: Vec<MemoryLocalEvent>,
+}
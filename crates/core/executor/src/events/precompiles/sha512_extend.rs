@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::events::{
+    memory::{MemoryReadRecord, MemoryWriteRecord},
+    MemoryLocalEvent,
+};
+
+/// SHA-512 Extend Event.
+///
+/// This event is emitted when a SHA-512 extend operation is performed. Every 64-bit word is
+/// accessed as its low word followed by its high word.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Sha512ExtendEvent {
+    /// The shard number.
+    pub shard: u32,
+    /// The clock cycle.
+    pub clk: u32,
+    /// The pointer to the word.
+    pub w_ptr: u32,
+    /// The memory reads of w[i-15].
+    pub w_i_minus_15_reads: Vec<[MemoryReadRecord; 2]>,
+    /// The memory reads of w[i-2].
+    pub w_i_minus_2_reads: Vec<[MemoryReadRecord; 2]>,
+    /// The memory reads of w[i-16].
+    pub w_i_minus_16_reads: Vec<[MemoryReadRecord; 2]>,
+    /// The memory reads of w[i-7].
+    pub w_i_minus_7_reads: Vec<[MemoryReadRecord; 2]>,
+    /// The memory writes of w[i].
+    pub w_i_writes: Vec<[MemoryWriteRecord; 2]>,
+    /// The local memory accesses.
+    pub local_mem_access: Vec<MemoryLocalEvent>,
+}
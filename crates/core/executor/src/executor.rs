@@ -1,13 +1,15 @@
 use std::{
     fs::File,
     io::{BufWriter, Write},
+    ops::Range,
     str::FromStr,
-    sync::Arc,
+    sync::{mpsc::Sender, Arc},
 };
 
 use enum_map::EnumMap;
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use zkm_stark::ZKMCoreOpts;
 
@@ -30,9 +32,13 @@ use crate::{
     sign_extend,
     state::{ExecutionState, ForkState},
     subproof::SubproofVerifier,
-    syscalls::{default_syscall_map, Syscall, SyscallCode, SyscallContext},
-    ExecutionReport, Instruction, MaximalShapes, MipsAirId, Opcode, Program, Register,
-    NUM_REGISTERS,
+    syscalls::{
+        default_syscall_map, handle_user_syscall, Syscall, SyscallCode, SyscallContext,
+        SyscallHandlerRegistry,
+    },
+    watchpoint::{word_overlaps, BoxedWatchpoint, MemoryAccessKind, WatchEvent},
+    ContinuationState, Coverage, ExecutionReport, Instruction, MaximalShapes, MemoryUsage,
+    MipsAirId, Opcode, OpcodeSample, Profiler, Program, Register, INIT_SP, NUM_REGISTERS,
 };
 
 /// The maximum number of instructions in a program.
@@ -112,6 +118,19 @@ pub struct Executor<'a> {
     /// The maximum number of cpu cycles to use for execution.
     pub max_cycles: Option<u64>,
 
+    /// The maximum number of cycles of the cycle tracker regions, by name.
+    pub region_budgets: HashMap<String, u64>,
+
+    /// The first cycle past the budget of an open region, updated at the region markers.
+    pub region_deadline: Option<u64>,
+
+    /// The memory limits of the execution and the memory used by the program, if limits are set.
+    pub memory_usage: Option<MemoryUsage>,
+
+    /// The first memory access over the memory limits, which fails the execution once the
+    /// instruction or the syscall which made it completes.
+    pub memory_limit_error: Option<ExecutionError>,
+
     /// Skip deferred proof verification. This check is informational only, not related to circuit
     /// correctness.
     pub deferred_proof_verification: DeferredProofVerification,
@@ -152,6 +171,9 @@ pub struct Executor<'a> {
     /// Registry of hooks, to be invoked by writing to certain file descriptors.
     pub hook_registry: HookRegistry<'a>,
 
+    /// Registry of handlers for user-defined syscalls.
+    pub syscall_handlers: SyscallHandlerRegistry<'a>,
+
     /// The maximal shapes for the program.
     pub maximal_shapes: Option<MaximalShapes>,
 
@@ -166,6 +188,38 @@ pub struct Executor<'a> {
 
     /// The maximum LDE size to allow.
     pub lde_size_threshold: u64,
+
+    /// A running hash of all input bytes consumed by the program, if enabled in the context.
+    pub input_transcript: Option<Sha256>,
+
+    /// The first error of the program reading a named input, which fails the execution when the
+    /// program panics on it.
+    pub named_input_error: Option<String>,
+
+    /// A rolling hash of the execution state and the number of cycles between its snapshots, if
+    /// enabled in the context.
+    pub state_digest: Option<(u64, Sha256)>,
+
+    /// Whether to record the estimated event counts of every shard in the report.
+    pub cost_estimation: bool,
+
+    /// The number of cycles to sample the opcode histogram over and the channel to send it to,
+    /// until the sample is sent.
+    pub opcode_sample: Option<(u64, Sender<OpcodeSample>)>,
+
+    /// The channel to send the state of the execution to when it pauses at the end of a
+    /// continuation segment.
+    pub continuation_tx: Option<Sender<ContinuationState>>,
+
+    /// The profiler of the cycles of the program, if enabled in the context.
+    pub profiler: Option<Profiler<'a>>,
+
+    /// The ELF of the program and the number of times each of its instructions was executed, if
+    /// coverage is enabled in the context.
+    pub coverage: Option<(&'a [u8], Vec<u64>)>,
+
+    /// The watchpoints, called on every access to a word of memory overlapping their range.
+    pub watchpoints: Vec<(Range<u32>, BoxedWatchpoint<'a>)>,
 }
 
 /// The different modes the executor can run in.
@@ -217,6 +271,10 @@ pub enum ExecutionError {
     #[error("exceeded cycle limit of {0}")]
     ExceededCycleLimit(u64),
 
+    /// The execution spent more cycles in a cycle tracker region than its budget.
+    #[error("exceeded the budget of {1} cycles of the region {0}")]
+    RegionBudgetExceeded(String, u64),
+
     /// The execution failed because the syscall was called in unconstrained mode.
     #[error("syscall called in unconstrained mode")]
     InvalidSyscallUsage(u64),
@@ -231,6 +289,27 @@ pub enum ExecutionError {
 
     #[error("Null Pointer Reference")]
     NullPointerReference(),
+
+    /// The program read a named input which was not written, or read it as another type.
+    #[error("invalid named input: {0}")]
+    InvalidNamedInput(String),
+
+    /// The program accessed its stack beyond the stack size limit.
+    #[error(
+        "stack overflow at pc {pc:#x}: address {addr:#x} is beyond the stack limit of {limit}"
+    )]
+    StackOverflow { pc: u32, addr: u32, limit: u32 },
+
+    /// The program accessed an address at or above the memory limit below its stack.
+    #[error(
+        "out of memory at pc {pc:#x}: address {addr:#x} is beyond the memory limit of {limit:#x}"
+    )]
+    OutOfMemory { pc: u32, addr: u32, limit: u32 },
+
+    /// The program passed an input buffer to a user syscall which is too long or wraps around
+    /// the address space.
+    #[error("invalid input buffer of user syscall {code:#x}: {len} bytes at {addr:#x}")]
+    InvalidUserSyscallInput { code: u32, addr: u32, len: u32 },
 }
 
 impl<'a> Executor<'a> {
@@ -267,6 +346,7 @@ impl<'a> Executor<'a> {
         };
 
         let hook_registry = context.hook_registry.unwrap_or_default();
+        let coverage = context.coverage.map(|elf| (elf, vec![0; program.instructions.len()]));
 
         let costs: HashMap<String, usize> = serde_json::from_str(MIPS_COSTS).unwrap();
         let costs: HashMap<MipsAirId, usize> =
@@ -275,7 +355,12 @@ impl<'a> Executor<'a> {
         Self {
             record,
             records: vec![],
-            state: ExecutionState::new(program.pc_start, program.next_pc),
+            state: ExecutionState {
+                rng_seed: context.rng_seed,
+                time_base: context.time_base,
+                segment_cycles: context.segment_cycles,
+                ..ExecutionState::new(program.pc_start, program.next_pc)
+            },
             program,
             memory_accesses: MemoryAccessRecord::default(),
             shard_size: (opts.shard_size as u32) * 4,
@@ -294,8 +379,18 @@ impl<'a> Executor<'a> {
             print_report: false,
             subproof_verifier: context.subproof_verifier,
             hook_registry,
+            syscall_handlers: context.syscall_handlers,
             opts,
             max_cycles: context.max_cycles,
+            region_budgets: context.region_budgets,
+            region_deadline: None,
+            memory_usage: (context.max_memory.is_some() || context.max_stack_size.is_some()).then(
+                || MemoryUsage {
+                    max_memory: context.max_memory,
+                    max_stack_size: context.max_stack_size,
+                    ..Default::default()
+                },
+            ),
             deferred_proof_verification: if context.skip_deferred_proof_verification {
                 DeferredProofVerification::Disabled
             } else {
@@ -309,6 +404,16 @@ impl<'a> Executor<'a> {
             shape_check_frequency: opts.shape_check_frequency,
             lde_size_check: false,
             lde_size_threshold: 0,
+            input_transcript: context.input_transcript.then(Sha256::new),
+            named_input_error: None,
+            memory_limit_error: None,
+            state_digest: context.state_digest_interval.map(|interval| (interval, Sha256::new())),
+            cost_estimation: context.cost_estimation,
+            opcode_sample: context.opcode_sample,
+            continuation_tx: context.continuation_tx,
+            profiler: context.profiler.map(Profiler::new),
+            coverage,
+            watchpoints: context.watchpoints,
         }
     }
 
@@ -343,6 +448,57 @@ impl<'a> Executor<'a> {
         runtime
     }
 
+    /// Absorbs the current clock, program counter and registers into the rolling state digest and
+    /// records a snapshot of it in the report.
+    fn snapshot_state_digest(&mut self) {
+        let Some((_, hasher)) = self.state_digest.as_mut() else {
+            return;
+        };
+        hasher.update(self.state.global_clk.to_le_bytes());
+        hasher.update(self.state.pc.to_le_bytes());
+        for i in 0..NUM_REGISTERS as u32 {
+            let value = self.state.memory.registers.get(i).map_or(0, |record| record.value);
+            hasher.update(value.to_le_bytes());
+        }
+        let digest = hasher.clone().finalize().into();
+        self.report.state_digests.push((self.state.global_clk, digest));
+    }
+
+    /// Records the estimated event counts of the current shard in the report, if cost estimation
+    /// is enabled. Must be called before the clock and the local counts are reset.
+    fn record_shard_costs(&mut self) {
+        if !self.cost_estimation || self.state.clk == 0 {
+            return;
+        }
+        let event_counts = estimate_mips_event_counts(
+            (self.state.clk / 5) as u64,
+            self.local_counts.local_mem as u64,
+            self.local_counts.syscalls_sent as u64,
+            *self.local_counts.event_counts,
+        );
+        self.report.shard_event_counts.push(event_counts);
+    }
+
+    /// Sends the opcode histogram sampled so far, if an opcode sample is pending. Must be called
+    /// before the clock and the local counts are reset.
+    fn send_opcode_sample(&mut self) {
+        let Some((_, tx)) = self.opcode_sample.take() else {
+            return;
+        };
+        let sample = OpcodeSample {
+            cycles: self.state.global_clk,
+            opcode_counts: *self.report.opcode_counts,
+            event_counts: estimate_mips_event_counts(
+                (self.state.clk / 5) as u64,
+                self.local_counts.local_mem as u64,
+                self.local_counts.syscalls_sent as u64,
+                *self.local_counts.event_counts,
+            ),
+        };
+        // The receiver may have given up on the sample, which does not affect the execution.
+        let _ = tx.send(sample);
+    }
+
     /// Get the current values of the registers.
     #[allow(clippy::single_match_else)]
     #[must_use]
@@ -450,6 +606,8 @@ impl<'a> Executor<'a> {
         timestamp: u32,
         local_memory_access: Option<&mut HashMap<u32, MemoryLocalEvent>>,
     ) -> MemoryReadRecord {
+        self.track_memory_access(addr);
+
         // Get the memory record entry.
         let entry = self.state.memory.page_table.entry(addr);
         if self.executor_mode == ExecutorMode::Checkpoint || self.unconstrained {
@@ -522,13 +680,15 @@ impl<'a> Executor<'a> {
         }
 
         // Construct the memory read record.
-        MemoryReadRecord::new(
+        let read = MemoryReadRecord::new(
             record.value,
             record.shard,
             record.timestamp,
             prev_record.shard,
             prev_record.timestamp,
-        )
+        );
+        self.watch(addr, MemoryAccessKind::Read, read.value, read.value);
+        read
     }
 
     /// Read a register and return its value.
@@ -664,6 +824,8 @@ impl<'a> Executor<'a> {
         timestamp: u32,
         local_memory_access: Option<&mut HashMap<u32, MemoryLocalEvent>>,
     ) -> MemoryWriteRecord {
+        self.track_memory_access(addr);
+
         // Get the memory record entry.
         let entry = self.state.memory.page_table.entry(addr);
         if self.executor_mode == ExecutorMode::Checkpoint || self.unconstrained {
@@ -738,14 +900,37 @@ impl<'a> Executor<'a> {
         }
 
         // Construct the memory write record.
-        MemoryWriteRecord::new(
+        let write = MemoryWriteRecord::new(
             record.value,
             record.shard,
             record.timestamp,
             prev_record.value,
             prev_record.shard,
             prev_record.timestamp,
-        )
+        );
+        self.watch(addr, MemoryAccessKind::Write, write.value, write.prev_value);
+        write
+    }
+
+    /// Calls the watchpoints whose range overlaps the word at `addr`.
+    #[inline]
+    fn watch(&mut self, addr: u32, kind: MemoryAccessKind, value: u32, prev_value: u32) {
+        if self.watchpoints.is_empty() {
+            return;
+        }
+        let event = WatchEvent {
+            pc: self.state.pc,
+            global_clk: self.state.global_clk,
+            addr,
+            kind,
+            value,
+            prev_value,
+        };
+        for (range, watchpoint) in &self.watchpoints {
+            if word_overlaps(range, addr) {
+                (watchpoint.write().unwrap())(&event);
+            }
+        }
     }
 
     /// Write a word to register and create an access record.
@@ -1555,6 +1740,7 @@ impl<'a> Executor<'a> {
                     // register. If it returns None, we just keep the
                     // syscall_id in t0.
                     let res = syscall_impl.execute(&mut precompile_rt, syscall, b, c);
+                    precompile_rt.rt.check_memory_limit_error()?;
                     if let Some(r0) = res {
                         a = r0;
                     } else {
@@ -1563,6 +1749,9 @@ impl<'a> Executor<'a> {
 
                     // If the syscall is `HALT` and the exit code is non-zero, return an error.
                     if syscall == SyscallCode::HALT && precompile_rt.exit_code != 0 {
+                        if let Some(error) = precompile_rt.rt.named_input_error.take() {
+                            return Err(ExecutionError::InvalidNamedInput(error));
+                        }
                         return Err(ExecutionError::HaltWithNonZeroExitCode(
                             precompile_rt.exit_code,
                         ));
@@ -1573,6 +1762,13 @@ impl<'a> Executor<'a> {
                         syscall_impl.num_extra_cycles(),
                         precompile_rt.exit_code,
                     )
+                } else if handle_user_syscall(&mut precompile_rt, syscall_id, b, c)? {
+                    precompile_rt.rt.check_memory_limit_error()?;
+                    // User syscalls have no `SyscallCode`, so record their own identifier, which
+                    // the syscall instructions chip recomputes from the code.
+                    syscall_code = syscall_id;
+                    a = syscall_id;
+                    (precompile_rt.next_pc, 0, precompile_rt.exit_code)
                 } else {
                     return Err(ExecutionError::UnsupportedSyscall(syscall_id));
                 };
@@ -1866,6 +2062,64 @@ impl<'a> Executor<'a> {
         self.alu_rw(instruction, rd, hi, a, b, c)
     }
 
+    /// Computes the last cycle within the budgets of the open cycle tracker regions, which is
+    /// checked on every cycle. It only changes when a region is opened or closed.
+    pub(crate) fn update_region_deadline(&mut self) {
+        if self.region_budgets.is_empty() {
+            return;
+        }
+        self.region_deadline = self
+            .cycle_tracker
+            .iter()
+            .filter_map(|(region, (start, _))| {
+                self.region_budgets.get(region).map(|budget| start.saturating_add(*budget))
+            })
+            .min();
+    }
+
+    /// Checks an access to `addr` against the memory limits, if any, keeping the first violation
+    /// in [`Self::memory_limit_error`].
+    ///
+    /// This is called by [`Self::mr`] and [`Self::mw`], so it covers the accesses of both the
+    /// instructions and the syscalls.
+    #[inline]
+    fn track_memory_access(&mut self, addr: u32) {
+        if self.memory_usage.is_some() && self.memory_limit_error.is_none() {
+            if let Err(err) = self.check_memory_limits(addr) {
+                self.memory_limit_error = Some(err);
+            }
+        }
+    }
+
+    /// Fails with the first memory access over the memory limits since the last call, if any.
+    fn check_memory_limit_error(&mut self) -> Result<(), ExecutionError> {
+        self.memory_limit_error.take().map_or(Ok(()), Err)
+    }
+
+    /// Checks an access to `addr` against the memory limits, and records the memory usage.
+    ///
+    /// Addresses at or above the stack pointer are in the stack, which grows down from
+    /// [`INIT_SP`], and the addresses below it are in the memory of the program.
+    fn check_memory_limits(&mut self, addr: u32) -> Result<(), ExecutionError> {
+        let Some(mut usage) = self.memory_usage else { return Ok(()) };
+        let pc = self.state.pc;
+        let sp = self.register(Register::SP);
+        if addr >= sp {
+            let stack_size = INIT_SP.saturating_sub(addr);
+            if let Some(limit) = usage.max_stack_size.filter(|&limit| stack_size > limit) {
+                return Err(ExecutionError::StackOverflow { pc, addr, limit });
+            }
+            usage.peak_stack_size = usage.peak_stack_size.max(stack_size);
+        } else {
+            if let Some(limit) = usage.max_memory.filter(|&limit| addr >= limit) {
+                return Err(ExecutionError::OutOfMemory { pc, addr, limit });
+            }
+            usage.peak_memory = usage.peak_memory.max(addr + 4);
+        }
+        self.memory_usage = Some(usage);
+        Ok(())
+    }
+
     fn execute_load(
         &mut self,
         instruction: &Instruction,
@@ -1881,6 +2135,7 @@ impl<'a> Executor<'a> {
         let virt = virt_raw & 0xFFFF_FFFC;
 
         let mem = self.mr_cpu(virt);
+        self.check_memory_limit_error()?;
         let rs = virt_raw;
 
         let val = match instruction.opcode {
@@ -1985,6 +2240,7 @@ impl<'a> Executor<'a> {
             virt_raw & 0xFFFF_FFFC, // align addr
             val,
         );
+        self.check_memory_limit_error()?;
         if instruction.opcode == Opcode::SC {
             self.rw_cpu(rt_reg, 1, MemoryAccessPosition::A);
 
@@ -2062,12 +2318,36 @@ impl<'a> Executor<'a> {
         #[cfg(debug_assertions)]
         self.log(&instruction);
 
+        // Sample the call stack, skipping unconstrained cycles which are reverted.
+        if let Some(profiler) = &mut self.profiler {
+            if !self.unconstrained {
+                profiler.record(self.state.global_clk, self.state.pc, &instruction);
+            }
+        }
+
+        if let Some((_, hits)) = &mut self.coverage {
+            hits[((self.state.pc - self.program.pc_base) / 4) as usize] += 1;
+        }
+
         // Execute the instruction.
         self.execute_operation(&instruction)?;
 
         // Increment the clock.
         self.state.global_clk += 1;
 
+        // Snapshot the execution state digest, skipping unconstrained cycles which are reverted.
+        if let Some((interval, _)) = self.state_digest {
+            if !self.unconstrained && self.state.global_clk.is_multiple_of(interval) {
+                self.snapshot_state_digest();
+            }
+        }
+
+        if let Some((cycles, _)) = self.opcode_sample {
+            if !self.unconstrained && self.state.global_clk >= cycles {
+                self.send_opcode_sample();
+            }
+        }
+
         // We restrict the execution of branch/jump and its delay slot to be in the same shard.
         if !self.unconstrained && !self.state.next_is_delayslot {
             // If there's not enough cycles left for another instruction, move to the next shard.
@@ -2161,6 +2441,8 @@ impl<'a> Executor<'a> {
             }
 
             if cpu_exit || !shape_match_found {
+                self.record_shard_costs();
+                self.send_opcode_sample();
                 self.state.current_shard += 1;
                 self.state.clk = 0;
                 self.bump_record();
@@ -2174,10 +2456,42 @@ impl<'a> Executor<'a> {
             }
         }
 
-        let done = self.state.pc == 0
+        // If a region with a budget is open for longer than its budget, return an error.
+        if self.region_deadline.is_some_and(|deadline| self.state.global_clk > deadline) {
+            for (region, (start, _)) in &self.cycle_tracker {
+                if let Some(&budget) = self.region_budgets.get(region) {
+                    if self.state.global_clk - start > budget {
+                        return Err(ExecutionError::RegionBudgetExceeded(region.clone(), budget));
+                    }
+                }
+            }
+        }
+
+        let halted = self.state.pc == 0
             || self.state.exited
             || self.state.pc.wrapping_sub(self.program.pc_base)
                 >= (self.program.instructions.len() * 4) as u32;
+
+        // Pause at the end of a continuation segment. The next segment resumes from a single
+        // program counter, so the execution never pauses before a delay slot.
+        if let Some(segment_cycles) = self.state.segment_cycles {
+            if !halted
+                && !self.unconstrained
+                && !self.state.next_is_delayslot
+                && self.state.next_pc == self.state.pc.wrapping_add(4)
+                && self.state.global_clk >= segment_cycles
+            {
+                self.state.paused = true;
+                if let (Some(tx), Some(state)) =
+                    (self.continuation_tx.take(), self.continuation_state())
+                {
+                    // The receiver may have stopped listening, e.g. if proving failed.
+                    tx.send(state).ok();
+                }
+            }
+        }
+
+        let done = halted || self.state.paused;
         if done && self.unconstrained {
             log::error!("program ended in unconstrained mode at clk {}", self.state.global_clk);
             return Err(ExecutionError::EndInUnconstrained());
@@ -2287,7 +2601,7 @@ impl<'a> Executor<'a> {
         Ok((checkpoint, done))
     }
 
-    fn initialize(&mut self) {
+    pub(crate) fn initialize(&mut self) {
         self.state.clk = 0;
 
         tracing::debug!("loading memory image");
@@ -2296,6 +2610,20 @@ impl<'a> Executor<'a> {
         }
     }
 
+    /// Executes a single cycle of the program, returning whether the program has finished.
+    ///
+    /// The program must have been initialized.
+    pub(crate) fn step(&mut self) -> Result<bool, ExecutionError> {
+        let done = self.execute_cycle()?;
+        if done {
+            self.record_shard_costs();
+            self.send_opcode_sample();
+            self.postprocess();
+            self.bump_record();
+        }
+        Ok(done)
+    }
+
     pub fn run_very_fast(&mut self) -> Result<(), ExecutionError> {
         self.executor_mode = ExecutorMode::Simple;
         self.print_report = false;
@@ -2365,6 +2693,8 @@ impl<'a> Executor<'a> {
         let public_values = self.record.public_values;
 
         if done {
+            self.record_shard_costs();
+            self.send_opcode_sample();
             self.postprocess();
 
             // Push the remaining execution record with memory initialize & finalize events.
@@ -2430,9 +2760,40 @@ impl<'a> Executor<'a> {
         many proofs in or forget to call verify_zkm_proof?"
             );
         }
-        if self.state.input_stream_ptr != self.state.input_stream.len() {
+        if self.state.input_stream_ptr != self.state.input_stream.len() && !self.state.paused {
             tracing::warn!("Not all input bytes were read.");
         }
+        self.report.unread_named_inputs = self.unread_named_inputs();
+        if !self.report.unread_named_inputs.is_empty() {
+            tracing::warn!(
+                "The program did not read the inputs {}.",
+                self.report.unread_named_inputs.join(", ")
+            );
+        }
+
+        // Finalize the digest of the consumed input bytes.
+        if let Some(transcript) = self.input_transcript.take() {
+            self.report.input_digest = Some(transcript.finalize().into());
+        }
+
+        self.report.public_logs = self.state.public_logs_stream.clone();
+        self.report.merkle_outputs = self.state.merkle_outputs.clone();
+        self.report.memory_usage = self.memory_usage;
+
+        // Snapshot the final execution state.
+        if self.state_digest.is_some() {
+            self.snapshot_state_digest();
+        }
+
+        if let Some(profiler) = &self.profiler {
+            if let Err(err) = profiler.write() {
+                tracing::error!("failed to write the profile: {err}");
+            }
+        }
+
+        if let Some((elf, hits)) = self.coverage.take() {
+            self.report.coverage = Some(Coverage::new(elf, &self.program, &hits));
+        }
 
         if self.emit_global_memory_events
             && (self.executor_mode == ExecutorMode::Trace
@@ -2498,6 +2859,12 @@ impl<'a> Executor<'a> {
                 memory_finalize_events
                     .push(MemoryInitializeFinalizeEvent::finalize_from_record(addr, &record));
             }
+
+            // A paused execution leaves its memory to the next segment, which commits to it in
+            // the verifying key instead, see `ContinuationState::memory_digest`.
+            if self.state.paused {
+                memory_finalize_events.clear();
+            }
         }
     }
 
@@ -2549,11 +2916,16 @@ mod tests {
         secp256r1_double_program, simple_memory_program, simple_program, ssz_withdrawals_program,
         u256xu2048_mul_program,
     };
+    use sha2::{Digest, Sha256};
+    use test_artifacts::FIBONACCI_ELF;
     use zkm_stark::ZKMCoreOpts;
 
-    use crate::{Instruction, Opcode, Register};
+    use crate::{
+        syscalls::SyscallCode, ExecutionReport, Instruction, MipsAirId, Opcode, Register,
+        ZKMContext,
+    };
 
-    use super::{Executor, Program};
+    use super::{ExecutionError, Executor, MemoryAccessKind, Program, WatchEvent};
 
     fn _assert_send<T: Send>() {}
 
@@ -2577,6 +2949,42 @@ mod tests {
         runtime.run_very_fast().unwrap();
     }
 
+    #[test]
+    fn test_input_transcript() {
+        let program = fibonacci_program();
+        let context = ZKMContext::builder().input_transcript(true).build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        let input = bincode::serialize(&10usize).unwrap();
+        runtime.write_stdin_slice(&input);
+        runtime.run_fast().unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update((input.len() as u64).to_le_bytes());
+        hasher.update(&input);
+        let expected: [u8; 32] = hasher.finalize().into();
+        assert_eq!(runtime.report.input_digest, Some(expected));
+    }
+
+    #[test]
+    fn test_region_budgets() {
+        let context = ZKMContext::builder()
+            .max_cycles_between_markers("outer", 100)
+            .max_cycles_between_markers("inner", 10)
+            .build();
+        let mut runtime = Executor::with_context(fibonacci_program(), Default::default(), context);
+        runtime.cycle_tracker.insert("outer".into(), (0, 0));
+        runtime.cycle_tracker.insert("inner".into(), (20, 1));
+        runtime.cycle_tracker.insert("untracked".into(), (0, 2));
+        runtime.update_region_deadline();
+        assert_eq!(runtime.region_deadline, Some(30));
+
+        runtime.write_stdin(&10usize);
+        let err = runtime.run_fast().unwrap_err();
+        assert!(
+            matches!(err, ExecutionError::RegionBudgetExceeded(region, 10) if region == "inner")
+        );
+    }
+
     #[test]
     fn test_max_memory_program_run() {
         let program = max_memory_program();
@@ -2584,6 +2992,43 @@ mod tests {
         runtime.run_very_fast().unwrap();
     }
 
+    #[test]
+    fn test_memory_limits() {
+        let context = ZKMContext::builder().max_stack_size(1 << 20).build();
+        let mut runtime = Executor::with_context(fibonacci_program(), Default::default(), context);
+        runtime.write_stdin(&10usize);
+        runtime.run_fast().unwrap();
+        let usage = runtime.report.memory_usage.unwrap();
+        assert!(usage.peak_stack_size > 0 && usage.peak_stack_size <= 1 << 20);
+        assert!(usage.peak_memory > 0);
+
+        let context = ZKMContext::builder().max_stack_size(16).build();
+        let mut runtime = Executor::with_context(fibonacci_program(), Default::default(), context);
+        runtime.write_stdin(&10usize);
+        let err = runtime.run_fast().unwrap_err();
+        assert!(matches!(err, ExecutionError::StackOverflow { limit: 16, .. }));
+
+        let limit = usage.peak_memory - 4;
+        let context = ZKMContext::builder().max_memory(limit).build();
+        let mut runtime = Executor::with_context(fibonacci_program(), Default::default(), context);
+        runtime.write_stdin(&10usize);
+        let err = runtime.run_fast().unwrap_err();
+        assert!(matches!(err, ExecutionError::OutOfMemory { limit: l, .. } if l == limit));
+
+        // The accesses of the syscalls are checked as well.
+        let context = ZKMContext::builder().max_memory(0x1000).build();
+        let mut runtime = Executor::with_context(simple_program(), Default::default(), context);
+        runtime.rw(Register::SP, crate::INIT_SP, 0, 0);
+        let mut ctx = crate::syscalls::SyscallContext::new(&mut runtime);
+        ctx.mw(0x0ffc, 1);
+        assert!(ctx.rt.memory_limit_error.is_none());
+        ctx.mw(0x1000, 1);
+        assert!(matches!(
+            runtime.memory_limit_error,
+            Some(ExecutionError::OutOfMemory { addr: 0x1000, limit: 0x1000, .. })
+        ));
+    }
+
     //
     #[test]
     fn test_secp256r1_add_program_run() {
@@ -2612,6 +3057,137 @@ mod tests {
         let mut runtime = Executor::new(program, ZKMCoreOpts::default());
         runtime.run().unwrap();
     }
+
+    #[test]
+    fn test_state_digests() {
+        let program = fibonacci_program();
+        let run = |interval| {
+            let context = ZKMContext::builder().state_digests(interval).build();
+            let mut runtime =
+                Executor::with_context(program.clone(), ZKMCoreOpts::default(), context);
+            runtime.run().unwrap();
+            (runtime.report.state_digests, runtime.state.global_clk)
+        };
+
+        let (digests, global_clk) = run(1000);
+        assert_eq!(digests, run(1000).0);
+        assert_eq!(digests.len() as u64, global_clk / 1000 + 1);
+        assert_eq!(digests.last().unwrap().0, global_clk);
+        assert!(digests.windows(2).all(|w| w[0].1 != w[1].1));
+    }
+
+    #[test]
+    fn test_cost_estimation() {
+        let program = fibonacci_program();
+        let opts = ZKMCoreOpts { shard_size: 1 << 12, ..ZKMCoreOpts::default() };
+        let context = ZKMContext::builder().cost_estimation(true).build();
+        let mut runtime = Executor::with_context(program, opts, context);
+        runtime.run_fast().unwrap();
+
+        let shard_event_counts = &runtime.report.shard_event_counts;
+        assert!(shard_event_counts.len() > 1);
+        assert_eq!(shard_event_counts.len() as u32, runtime.state.current_shard);
+        let cpu_events = shard_event_counts.iter().map(|counts| counts[MipsAirId::Cpu]);
+        assert!(cpu_events.clone().all(|events| events > 0));
+        assert!(cpu_events.sum::<u64>() <= runtime.state.global_clk);
+    }
+
+    #[test]
+    fn test_opcode_sample() {
+        let program = fibonacci_program();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let context = ZKMContext::builder().opcode_sample(1000, tx).build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        runtime.run_fast().unwrap();
+
+        let sample = rx.recv().unwrap();
+        assert_eq!(sample.cycles, 1000);
+        assert_eq!(sample.opcode_counts.values().sum::<u64>(), 1000);
+        assert!(sample.event_counts[MipsAirId::Cpu] > 0);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_profiling() {
+        let program = fibonacci_program();
+        let output = std::env::temp_dir().join("zkm-test-profiling.folded");
+        let context = ZKMContext::builder().profiling(FIBONACCI_ELF, &output, 100).build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        runtime.run_fast().unwrap();
+
+        let profile = std::fs::read_to_string(&output).unwrap();
+        let mut cycles = 0;
+        for line in profile.lines() {
+            let (stack, count) = line.rsplit_once(' ').unwrap();
+            assert!(!stack.is_empty());
+            cycles += count.parse::<u64>().unwrap();
+        }
+        assert!(profile.contains("main"));
+        assert!(cycles > 0 && cycles <= runtime.state.global_clk.div_ceil(100) * 100);
+    }
+
+    #[test]
+    fn test_watchpoint() {
+        let program = simple_memory_program();
+        let events = std::sync::Mutex::new(Vec::new());
+        let context = ZKMContext::builder()
+            .watchpoint(0x27654320..0x27654324, |event| events.lock().unwrap().push(*event))
+            .watchpoint(0x1000..0x2000, |_| panic!("unexpected access"))
+            .build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        runtime.run().unwrap();
+        drop(runtime);
+
+        let events = events.into_inner().unwrap();
+        assert_eq!(
+            events[0],
+            WatchEvent {
+                pc: 4,
+                global_clk: 1,
+                addr: 0x27654320,
+                kind: MemoryAccessKind::Write,
+                value: 0x12348765,
+                prev_value: 0,
+            }
+        );
+        assert!(events[1..].iter().all(|event| event.kind == MemoryAccessKind::Read));
+        assert!(events[1..].iter().all(|event| event.value == 0x12348765));
+    }
+
+    #[test]
+    fn test_coverage() {
+        let program = fibonacci_program();
+        let context = ZKMContext::builder().coverage(FIBONACCI_ELF).build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        runtime.run_fast().unwrap();
+
+        let coverage = runtime.report.coverage().unwrap();
+        for lines in coverage.files.values() {
+            assert!(lines.values().all(|&count| count <= runtime.state.global_clk));
+        }
+        assert!(ExecutionReport::default().coverage().is_none());
+    }
+
+    #[test]
+    fn test_gas() {
+        let program = fibonacci_program();
+        let mut runtime = Executor::new(program.clone(), ZKMCoreOpts::default());
+        runtime.run_fast().unwrap();
+        let report = &runtime.report;
+        assert_eq!(
+            report.gas(),
+            report.opcode_gas().values().sum::<u64>()
+                + report.syscall_gas().values().sum::<u64>()
+                + report.memory_gas()
+        );
+        assert!(report.opcode_gas()[Opcode::ADD] > report.opcode_counts[Opcode::ADD]);
+
+        // The gas does not depend on the shard size.
+        let opts = ZKMCoreOpts { shard_size: 1 << 12, ..ZKMCoreOpts::default() };
+        let mut sharded = Executor::new(program, opts);
+        sharded.run_fast().unwrap();
+        assert_eq!(sharded.report.gas(), report.gas());
+    }
     //
     #[test]
     #[should_panic]
@@ -3180,4 +3756,54 @@ mod tests {
         assert_eq!(runtime.register(12.into()), 0x12346525);
         assert_eq!(runtime.register(11.into()), 0x65256525);
     }
+
+    #[test]
+    fn test_user_syscall_run() {
+        const CODE: u32 = 0xC5;
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, Register::V0 as u8, 0, CODE, false, true),
+            Instruction::new(Opcode::ADD, Register::A0 as u8, 0, 0x200, false, true),
+            Instruction::new(Opcode::ADD, Register::A1 as u8, 0, 4, false, true),
+            Instruction::new(Opcode::ADD, Register::A2 as u8, 0, 7, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let context = ZKMContext::builder()
+            .with_syscall_handler(CODE..CODE + 1, |_, args| {
+                assert_eq!(args.code, CODE);
+                assert_eq!(args.registers, [0x200, 4, 7, 0]);
+                vec![args.input.to_vec(), vec![1, 2, 3]]
+            })
+            .build();
+        let mut runtime = Executor::with_context(program, ZKMCoreOpts::default(), context);
+        runtime.run().unwrap();
+
+        // The results of the handler are placed at the front of the input stream.
+        assert_eq!(runtime.state.input_stream, vec![vec![0; 4], vec![1, 2, 3]]);
+        assert_eq!(runtime.register(Register::V0), CODE);
+
+        // The syscall is recorded with the identifier the syscall instructions chip recomputes
+        // from the code, and is not sent to any precompile table.
+        let events = runtime
+            .records
+            .iter()
+            .chain([&runtime.record])
+            .flat_map(|record| record.syscall_events.iter())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].syscall_id, CODE);
+        assert_eq!(events[0].a_record.prev_value, CODE);
+        assert!(runtime.records.iter().all(|record| record.precompile_events.is_empty()));
+    }
+
+    #[test]
+    fn test_unregistered_user_syscall() {
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, Register::V0 as u8, 0, 0xC5, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+        ];
+        let program = Program::new(instructions, 0, 0);
+        let mut runtime = Executor::new(program, ZKMCoreOpts::default());
+        assert!(matches!(runtime.run(), Err(ExecutionError::UnsupportedSyscall(0xC5))));
+    }
 }
@@ -3,12 +3,16 @@ use core::fmt::Debug;
 use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
 use hashbrown::HashMap;
+use serde::{de::DeserializeOwned, Serialize};
 use zkm_curves::{BigUint, One, Zero};
 
 use crate::Executor;
 
 pub use zkm_primitives::consts::fd::*;
 
+/// The number of nanoseconds the deterministic clock read by [`hook_clock`] advances per cycle.
+pub const CLOCK_NANOS_PER_CYCLE: u64 = 1;
+
 /// A runtime hook, wrapped in a smart pointer.
 pub type BoxedHook<'a> = Arc<RwLock<dyn Hook + Send + Sync + 'a>>;
 
@@ -36,6 +40,25 @@ pub fn hookify<'a>(
     Arc::new(RwLock::new(f))
 }
 
+/// Wrap a typed function in a smart pointer so it may be placed in a `HookRegistry`.
+///
+/// The request is bincode-deserialized from the bytes written by the program, and the response is
+/// bincode-serialized into a single vector for the program to read. Programs call typed hooks with
+/// `zkm_zkvm::io::call_hook`, or the functions generated by `zkm_zkvm::lib::define_hooks!`.
+///
+/// # Panics
+///
+/// The hook panics if the bytes written by the program are not a valid request.
+pub fn typed_hook<'a, Req: DeserializeOwned, Resp: Serialize>(
+    mut f: impl FnMut(&HookEnv, Req) -> Resp + Send + Sync + 'a,
+) -> BoxedHook<'a> {
+    hookify(move |env: HookEnv, buf: &[u8]| {
+        let request = bincode::deserialize(buf).expect("failed to deserialize the hook request");
+        let response = f(&env, request);
+        vec![bincode::serialize(&response).expect("failed to serialize the hook response")]
+    })
+}
+
 /// A registry of hooks to call, indexed by the file descriptors through which they are accessed.
 #[derive(Clone)]
 pub struct HookRegistry<'a> {
@@ -66,6 +89,20 @@ impl<'a> HookRegistry<'a> {
         // Calling `.unwrap()` panics on a poisoned lock. Should never happen normally.
         self.table.get(&fd).map(|x| x.write().unwrap())
     }
+
+    /// Register a hook at `fd`, replacing the hook registered there if any.
+    pub fn register(&mut self, fd: u32, hook: BoxedHook<'a>) {
+        self.table.insert(fd, hook);
+    }
+
+    /// Register a typed hook at `fd`, see [`typed_hook`].
+    pub fn register_typed<Req: DeserializeOwned, Resp: Serialize>(
+        &mut self,
+        fd: u32,
+        f: impl FnMut(&HookEnv, Req) -> Resp + Send + Sync + 'a,
+    ) {
+        self.register(fd, typed_hook(f));
+    }
 }
 
 impl Default for HookRegistry<'_> {
@@ -79,6 +116,11 @@ impl Default for HookRegistry<'_> {
             (FD_FP_INV, hookify(fp_ops::hook_fp_inverse)),
             (FD_BLS12_381_SQRT, hookify(bls::hook_bls12_381_sqrt)),
             (FD_BLS12_381_INVERSE, hookify(bls::hook_bls12_381_inverse)),
+            (FD_BIGINT_DIV_REM, typed_hook(hook_bigint_div_rem)),
+            (FD_FS_READ, typed_hook(hook_fs_read)),
+            (FD_RNG_SEED, typed_hook(hook_rng_seed)),
+            (FD_CLOCK, typed_hook(hook_clock)),
+            (FD_CYCLE_COUNT, typed_hook(hook_cycle_count)),
         ]);
 
         Self { table }
@@ -132,6 +174,60 @@ pub fn hook_ecrecover(_: HookEnv, buf: &[u8]) -> Vec<Vec<u8>> {
     }
 }
 
+/// The hook for the `modexp` routine of `zkm_zkvm::lib`.
+///
+/// The request is a pair of little endian words `(a, m)`. Returns the quotient and the remainder
+/// of the division of `a` by `m`, as little endian words without leading zeros.
+///
+/// # Panics
+///
+/// Panics if `m` is zero.
+#[must_use]
+pub fn hook_bigint_div_rem(_: &HookEnv, (a, m): (Vec<u32>, Vec<u32>)) -> (Vec<u32>, Vec<u32>) {
+    let a = BigUint::from_slice(&a);
+    let m = BigUint::from_slice(&m);
+    assert!(!m.is_zero(), "BigintDivRem: division by zero");
+
+    ((&a / &m).to_u32_digits(), (a % m).to_u32_digits())
+}
+
+/// The hook for the `fs` module of `zkm_zkvm::lib`.
+///
+/// The request is the path of a virtual file written with `ZKMStdin::write_file`. Returns the
+/// contents of the file, or `None` if there is no file at this path.
+#[must_use]
+pub fn hook_fs_read(env: &HookEnv, path: String) -> Option<Vec<u8>> {
+    env.runtime.state.files.get(&path).cloned()
+}
+
+/// The hook for the `seeded-rng` feature of `zkm_zkvm`.
+///
+/// Returns the seed set with `ZKMContextBuilder::with_rng_seed`, or `None` if there is none.
+#[must_use]
+pub fn hook_rng_seed(env: &HookEnv, _: ()) -> Option<[u8; 32]> {
+    env.runtime.state.rng_seed
+}
+
+/// The hook for the `time` module of `zkm_zkvm::lib`.
+///
+/// Returns the time base set with `ZKMContextBuilder::with_time_base`, as seconds and nanoseconds
+/// since the Unix epoch, and the nanoseconds elapsed since, [`CLOCK_NANOS_PER_CYCLE`] per cycle.
+/// The program adds them, and commits the time base. Returns `None` if there is no time base.
+#[must_use]
+pub fn hook_clock(env: &HookEnv, _: ()) -> Option<(u64, u32, u64)> {
+    let time_base = env.runtime.state.time_base?;
+    let elapsed = env.runtime.state.global_clk.saturating_mul(CLOCK_NANOS_PER_CYCLE);
+    Some((time_base.as_secs(), time_base.subsec_nanos(), elapsed))
+}
+
+/// The hook for the `cycles` module of `zkm_zkvm::lib`.
+///
+/// Returns the number of cycles executed by the program.
+#[must_use]
+pub fn hook_cycle_count(env: &HookEnv, _: ()) -> u64 {
+    env.runtime.state.global_clk
+}
+
 mod ecrecover {
     use zkm_curves::{k256, p256};
 
@@ -539,6 +635,8 @@ mod bls {
 
 #[cfg(test)]
 pub mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
@@ -551,4 +649,98 @@ pub mod tests {
     pub fn registry_empty_is_empty() {
         assert_eq!(HookRegistry::empty().table.len(), 0);
     }
+
+    #[test]
+    pub fn registry_typed_hook() {
+        let mut registry = HookRegistry::empty();
+        registry.register_typed(100, |_, (a, b): (u64, u64)| a.checked_add(b));
+
+        let program = crate::Program::new(vec![], 0, 0);
+        let runtime = Executor::new(program, Default::default());
+        let request = bincode::serialize(&(1u64, 2u64)).unwrap();
+        let response = registry.get(100).unwrap().invoke_hook(runtime.hook_env(), &request);
+        assert_eq!(response.len(), 1);
+        assert_eq!(bincode::deserialize::<Option<u64>>(&response[0]).unwrap(), Some(3));
+    }
+
+    #[test]
+    pub fn registry_bigint_div_rem() {
+        let registry = HookRegistry::new();
+        let program = crate::Program::new(vec![], 0, 0);
+        let runtime = Executor::new(program, Default::default());
+
+        // 2^64 + 8 = 7 * 2635249153387078803 + 3.
+        let request = bincode::serialize(&(vec![8u32, 0, 1], vec![7u32])).unwrap();
+        let response =
+            registry.get(FD_BIGINT_DIV_REM).unwrap().invoke_hook(runtime.hook_env(), &request);
+        let (q, r): (Vec<u32>, Vec<u32>) = bincode::deserialize(&response[0]).unwrap();
+        let q = q.iter().rev().fold(0u128, |acc, &w| (acc << 32) | u128::from(w));
+        assert_eq!(q, 2635249153387078803);
+        assert_eq!(r, vec![3]);
+    }
+
+    #[test]
+    pub fn registry_fs_read() {
+        let registry = HookRegistry::new();
+        let program = crate::Program::new(vec![], 0, 0);
+        let mut runtime = Executor::new(program, Default::default());
+        runtime.write_files(&[("config.toml".to_string(), b"answer = 42".to_vec())].into());
+
+        let read = |path: &str| {
+            let request = bincode::serialize(path).unwrap();
+            let response =
+                registry.get(FD_FS_READ).unwrap().invoke_hook(runtime.hook_env(), &request);
+            bincode::deserialize::<Option<Vec<u8>>>(&response[0]).unwrap()
+        };
+        assert_eq!(read("config.toml"), Some(b"answer = 42".to_vec()));
+        assert_eq!(read("missing.toml"), None);
+    }
+
+    #[test]
+    pub fn registry_clock() {
+        let registry = HookRegistry::new();
+        let program = crate::Program::new(vec![], 0, 0);
+        let mut runtime = Executor::new(program, Default::default());
+
+        let now = |runtime: &Executor| {
+            let request = bincode::serialize(&()).unwrap();
+            let response =
+                registry.get(FD_CLOCK).unwrap().invoke_hook(runtime.hook_env(), &request);
+            bincode::deserialize::<Option<(u64, u32, u64)>>(&response[0]).unwrap()
+        };
+        assert_eq!(now(&runtime), None);
+
+        runtime.state.time_base = Some(Duration::new(1_700_000_000, 999_999_999));
+        runtime.state.global_clk = 2;
+        assert_eq!(now(&runtime), Some((1_700_000_000, 999_999_999, 2)));
+
+        // The elapsed time saturates instead of overflowing.
+        runtime.state.global_clk = u64::MAX;
+        assert_eq!(now(&runtime), Some((1_700_000_000, 999_999_999, u64::MAX)));
+    }
+
+    #[test]
+    pub fn registry_cycle_count() {
+        let registry = HookRegistry::new();
+        let program = crate::Program::new(vec![], 0, 0);
+        let mut runtime = Executor::new(program, Default::default());
+        runtime.state.global_clk = 1234;
+
+        let request = bincode::serialize(&()).unwrap();
+        let response =
+            registry.get(FD_CYCLE_COUNT).unwrap().invoke_hook(runtime.hook_env(), &request);
+        assert_eq!(bincode::deserialize::<u64>(&response[0]).unwrap(), 1234);
+    }
+
+    #[test]
+    pub fn registry_rng_seed() {
+        let registry = HookRegistry::new();
+        let program = crate::Program::new(vec![], 0, 0);
+        let mut runtime = Executor::new(program, Default::default());
+        runtime.state.rng_seed = Some([7; 32]);
+
+        let request = bincode::serialize(&()).unwrap();
+        let response = registry.get(FD_RNG_SEED).unwrap().invoke_hook(runtime.hook_env(), &request);
+        assert_eq!(bincode::deserialize::<Option<[u8; 32]>>(&response[0]).unwrap(), Some([7; 32]));
+    }
 }
@@ -1,10 +1,11 @@
-use std::io::Read;
+use std::{collections::BTreeMap, io::Read};
 
 use serde::{de::DeserializeOwned, Serialize};
+use zkm_primitives::io::short_type_name;
 use zkm_stark::{koala_bear_poseidon2::KoalaBearPoseidon2, StarkVerifyingKey};
 
 use super::Executor;
-use crate::ZKMReduceProof;
+use crate::{NamedInput, ZKMReduceProof};
 
 impl Read for Executor<'_> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
@@ -33,6 +34,68 @@ impl Executor<'_> {
         }
     }
 
+    /// Write virtual files, indexed by their path, for the program to read.
+    pub fn write_files(&mut self, files: &BTreeMap<String, Vec<u8>>) {
+        for (path, bytes) in files {
+            self.state.files.insert(path.clone(), bytes.clone());
+        }
+    }
+
+    /// Write the inputs named by their name, for the program to read with
+    /// `zkm_zkvm::io::read_named`.
+    pub fn write_named_inputs(&mut self, inputs: &BTreeMap<String, NamedInput>) {
+        for (name, input) in inputs {
+            self.state.named_inputs.insert(name.clone(), input.clone());
+        }
+    }
+
+    /// Answers the request of the program for a named input: the pair of its name and of the
+    /// name of the type the program reads it as.
+    ///
+    /// Returns the bincode serialization of the input, or of an error if the host did not write
+    /// it, or wrote it with another type. The error is kept to fail the execution with.
+    pub(crate) fn read_named_input(&mut self, request: &[u8]) -> Vec<u8> {
+        let (name, type_name): (String, String) =
+            bincode::deserialize(request).expect("failed to deserialize the named input request");
+        let response = match self.state.named_inputs.get(&name) {
+            Some(input) if short_type_name(&input.type_name) == short_type_name(&type_name) => {
+                Ok(input.bytes.clone())
+            }
+            Some(input) => Err(format!(
+                "the input `{name}` was written as `{}`, but the program reads it as `{type_name}`",
+                input.type_name
+            )),
+            None => {
+                let mut written: Vec<_> = self.state.named_inputs.keys().cloned().collect();
+                written.sort();
+                Err(format!(
+                    "the program reads the input `{name}` as `{type_name}`, but it was not \
+                     written; the inputs written are: {}",
+                    if written.is_empty() { "none".to_string() } else { written.join(", ") }
+                ))
+            }
+        };
+        self.state.named_inputs_read.insert(name);
+        if let Err(error) = &response {
+            self.named_input_error.get_or_insert_with(|| error.clone());
+        }
+        bincode::serialize(&response).expect("failed to serialize the named input")
+    }
+
+    /// The names of the named inputs the program did not read, in order.
+    #[must_use]
+    pub fn unread_named_inputs(&self) -> Vec<String> {
+        let mut unread: Vec<_> = self
+            .state
+            .named_inputs
+            .keys()
+            .filter(|name| !self.state.named_inputs_read.contains(*name))
+            .cloned()
+            .collect();
+        unread.sort();
+        unread
+    }
+
     /// Write a proof and verifying key to the proof stream.
     pub fn write_proof(
         &mut self,
@@ -58,3 +121,34 @@ impl Executor<'_> {
         self.state.public_values_stream_ptr = end;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Program;
+
+    #[test]
+    fn test_read_named_input() {
+        let mut runtime = Executor::new(Program::new(vec![], 0, 0), Default::default());
+        let input = |type_name: &str, value: u64| NamedInput {
+            type_name: type_name.to_string(),
+            bytes: bincode::serialize(&value).unwrap(),
+        };
+        runtime.write_named_inputs(
+            &[("height".to_string(), input("u64", 7)), ("nonce".to_string(), input("u64", 1))]
+                .into(),
+        );
+
+        let mut read = |name: &str, type_name: &str| {
+            let request = bincode::serialize(&(name, type_name)).unwrap();
+            bincode::deserialize::<Result<Vec<u8>, String>>(&runtime.read_named_input(&request))
+                .unwrap()
+        };
+        assert_eq!(read("height", "u64").unwrap(), bincode::serialize(&7u64).unwrap());
+        assert!(read("height", "u32").unwrap_err().contains("written as `u64`"));
+        assert!(read("header", "u64").unwrap_err().contains("written are: height, nonce"));
+
+        assert_eq!(runtime.unread_named_inputs(), vec!["nonce".to_string()]);
+        assert!(runtime.named_input_error.unwrap().contains("`height`"));
+    }
+}
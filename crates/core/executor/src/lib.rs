@@ -1,7 +1,11 @@
 mod air;
 mod context;
+mod continuation;
 mod cost;
+mod coverage;
+mod debugger;
 mod dependencies;
+mod dump;
 pub mod events;
 mod executor;
 pub mod hook;
@@ -9,6 +13,7 @@ mod instruction;
 mod io;
 pub mod memory;
 mod opcode;
+mod profiler;
 mod program;
 #[cfg(test)]
 pub mod programs;
@@ -16,26 +21,35 @@ mod record;
 pub mod reduce;
 mod register;
 pub mod report;
+mod snapshot;
 mod state;
 pub mod subproof;
 pub mod syscalls;
 mod utils;
+mod watchpoint;
 
 pub use air::*;
 pub use context::*;
+pub use continuation::*;
 pub use cost::*;
+pub use coverage::*;
+pub use debugger::*;
+pub use dump::*;
 pub use executor::*;
 pub use hook::*;
 pub use instruction::*;
 pub use opcode::*;
+pub use profiler::*;
 pub use program::*;
 pub use record::*;
 pub use reduce::*;
 pub use register::*;
 pub use report::*;
+pub use snapshot::*;
 pub use state::*;
 pub use subproof::*;
 pub use utils::*;
+pub use watchpoint::*;
 
 #[derive(Debug, Copy, Clone)]
 #[repr(u8)]
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::register::NUM_REGISTERS;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use vec_map::VecMap;
@@ -223,12 +225,17 @@ impl<V: Copy> Default for NewPage<V> {
 }
 
 /// Paged memory. Balances both memory locality and total memory usage.
+///
+/// The pages are shared between clones and copied on their first write, so cloning the memory of
+/// a program is cheap whatever its size. Only the methods which write a value copy a shared page:
+/// [`Self::get`] and [`Self::keys`] read it in place, and [`Self::get_mut`] and [`Self::remove`]
+/// leave it shared if the address is empty.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(serialize = "V: Serialize"))]
 #[serde(bound(deserialize = "V: DeserializeOwned"))]
 pub struct PagedMemory<V: Copy> {
     /// The internal page table.
-    pub page_table: Vec<NewPage<V>>,
+    pub page_table: Vec<Arc<NewPage<V>>>,
     pub index: Vec<u16>,
 }
 
@@ -256,10 +263,10 @@ impl<V: Copy> PagedMemory<V> {
     pub fn get_mut(&mut self, addr: u32) -> Option<&mut V> {
         let (upper, lower) = Self::indices(addr);
         let index = self.index[upper];
-        if index == NO_PAGE {
+        if index == NO_PAGE || self.page_table[index as usize].0[lower].is_none() {
             None
         } else {
-            self.page_table[index as usize].0[lower].as_mut()
+            self.page_mut(index).0[lower].as_mut()
         }
     }
 
@@ -271,23 +278,26 @@ impl<V: Copy> PagedMemory<V> {
         if index == NO_PAGE {
             index = self.page_table.len() as u16;
             self.index[upper] = index;
-            self.page_table.push(NewPage::new());
+            self.page_table.push(Arc::new(NewPage::new()));
         }
-        self.page_table[index as usize].0[lower].replace(value)
+        self.page_mut(index).0[lower].replace(value)
     }
 
     /// Remove the value at the given address if it exists, returning it.
     pub fn remove(&mut self, addr: u32) -> Option<V> {
         let (upper, lower) = Self::indices(addr);
         let index = self.index[upper];
-        if index == NO_PAGE {
+        if index == NO_PAGE || self.page_table[index as usize].0[lower].is_none() {
             None
         } else {
-            self.page_table[index as usize].0[lower].take()
+            self.page_mut(index).0[lower].take()
         }
     }
 
     /// Gets the memory entry for the given address.
+    ///
+    /// The entry can be written, so this copies the page of the address if it is shared. Use
+    /// [`Self::get`] to only read it.
     #[inline]
     pub fn entry(&mut self, addr: u32) -> Entry<'_, V> {
         let (upper, lower) = Self::indices(addr);
@@ -295,10 +305,10 @@ impl<V: Copy> PagedMemory<V> {
         if index == NO_PAGE {
             let index = self.page_table.len();
             self.index[upper] = index as u16;
-            self.page_table.push(NewPage::new());
-            Entry::Vacant(VacantEntry { entry: &mut self.page_table[index].0[lower] })
+            self.page_table.push(Arc::new(NewPage::new()));
+            Entry::Vacant(VacantEntry { entry: &mut self.page_mut(index as u16).0[lower] })
         } else {
-            let option = &mut self.page_table[index as usize].0[lower];
+            let option = &mut self.page_mut(index).0[lower];
             match option {
                 Some(v) => Entry::Occupied(OccupiedEntry { entry: v }),
                 None => Entry::Vacant(VacantEntry { entry: option }),
@@ -306,6 +316,12 @@ impl<V: Copy> PagedMemory<V> {
         }
     }
 
+    /// Get a mutable reference to the page at the given index, copying it first if it is shared.
+    #[inline]
+    fn page_mut(&mut self, index: u16) -> &mut NewPage<V> {
+        Arc::make_mut(&mut self.page_table[index as usize])
+    }
+
     /// Returns an iterator over the occupied addresses.
     pub fn keys(&self) -> impl Iterator<Item = u32> + '_ {
         self.index.iter().enumerate().filter(|(_, &i)| i != NO_PAGE).flat_map(|(i, index)| {
@@ -469,7 +485,7 @@ impl<V: Copy + 'static> IntoIterator for PagedMemory<V> {
         Box::new(self.index.into_iter().enumerate().filter(|(_, i)| *i != NO_PAGE).flat_map(
             move |(i, index)| {
                 let upper = i << LOG_PAGE_LEN;
-                std::mem::take(&mut self.page_table[index as usize])
+                Arc::unwrap_or_clone(std::mem::take(&mut self.page_table[index as usize]))
                     .0
                     .into_iter()
                     .enumerate()
@@ -480,3 +496,31 @@ impl<V: Copy + 'static> IntoIterator for PagedMemory<V> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paged_memory_copy_on_write() {
+        let mut memory = PagedMemory::<u32>::new_preallocated();
+        memory.insert(0x1000, 1);
+        memory.insert(0x100000, 2);
+        let mut fork = memory.clone();
+        let shared = |memory: &PagedMemory<u32>, fork: &PagedMemory<u32>, i: usize| {
+            Arc::ptr_eq(&memory.page_table[i], &fork.page_table[i])
+        };
+
+        // Reads and writes to empty addresses leave the pages shared.
+        assert_eq!(fork.get(0x1000), Some(&1));
+        assert_eq!(fork.get_mut(0x1004), None);
+        assert_eq!(fork.remove(0x1004), None);
+        assert!(shared(&memory, &fork, 0) && shared(&memory, &fork, 1));
+
+        // A write copies only the page it touches.
+        *fork.get_mut(0x1000).unwrap() = 3;
+        assert!(!shared(&memory, &fork, 0) && shared(&memory, &fork, 1));
+        assert_eq!(memory.get(0x1000), Some(&1));
+        assert_eq!(fork.get(0x1000), Some(&3));
+    }
+}
@@ -0,0 +1,210 @@
+//! A sampling profiler attributing the cycles of a program to the functions of its source.
+//!
+//! The profiler keeps a shadow call stack, pushing the return address of every jump which links a
+//! register and popping it once the program jumps back to it. Every `sample_interval` cycles, it
+//! records the call sites on the stack and the current program counter. Once the execution ends,
+//! the addresses are symbolized against the DWARF information of the ELF, falling back to its
+//! symbol table, and the samples are written in the folded stack format read by `inferno`,
+//! `flamegraph.pl` and speedscope.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use addr2line::{
+    gimli::{self, EndianSlice, LittleEndian as GimliLittleEndian},
+    Context,
+};
+use elf::{abi::STT_FUNC, endian::LittleEndian, ElfBytes};
+use hashbrown::HashMap;
+
+use crate::{Instruction, Register};
+
+/// The default number of cycles between two samples of the profiler.
+pub const DEFAULT_PROFILER_SAMPLE_INTERVAL: u64 = 10;
+
+/// The configuration of the profiler, see [`crate::ZKMContextBuilder::profiling`].
+#[derive(Clone)]
+pub struct ProfilerConfig<'a> {
+    /// The ELF of the program, whose DWARF information and symbol table symbolize the samples.
+    pub elf: &'a [u8],
+    /// The path of the folded stacks file written when the execution ends.
+    pub output: PathBuf,
+    /// The number of cycles between two samples.
+    pub sample_interval: u64,
+}
+
+/// A sampling profiler of the cycles of a program.
+pub struct Profiler<'a> {
+    symbolizer: Symbolizer<'a>,
+    output: PathBuf,
+    sample_interval: u64,
+    /// The return addresses of the calls in progress, from the outermost.
+    call_stack: Vec<u32>,
+    /// The program counter of the previous cycle.
+    prev_pc: u32,
+    /// The number of samples of every stack, as the call sites followed by the sampled pc.
+    samples: HashMap<Vec<u32>, u64>,
+}
+
+impl<'a> Profiler<'a> {
+    /// Creates a profiler from its configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample interval is zero.
+    #[must_use]
+    pub fn new(config: ProfilerConfig<'a>) -> Self {
+        assert!(config.sample_interval > 0, "profiler sample interval must be positive");
+        Self {
+            symbolizer: Symbolizer::new(config.elf),
+            output: config.output,
+            sample_interval: config.sample_interval,
+            call_stack: Vec::new(),
+            prev_pc: 0,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Records the cycle `global_clk`, which executes `instruction` at `pc`.
+    pub fn record(&mut self, global_clk: u64, pc: u32, instruction: &Instruction) {
+        // Returns are jumps, so the stack is only searched when the program did not fall through.
+        if pc != self.prev_pc.wrapping_add(4) {
+            if let Some(pos) = self.call_stack.iter().rposition(|&ret| ret == pc) {
+                self.call_stack.truncate(pos);
+                // A tail call shares the return address of its caller.
+                while self.call_stack.last() == Some(&pc) {
+                    self.call_stack.pop();
+                }
+            }
+        }
+        self.prev_pc = pc;
+
+        if global_clk.is_multiple_of(self.sample_interval) {
+            // A call returns after the jump and its delay slot.
+            let stack = self
+                .call_stack
+                .iter()
+                .map(|ret| ret.wrapping_sub(8))
+                .chain(std::iter::once(pc))
+                .collect::<Vec<_>>();
+            *self.samples.entry(stack).or_default() += 1;
+        }
+
+        if instruction.is_jump_instruction() && instruction.op_a != Register::ZERO as u8 {
+            self.call_stack.push(pc.wrapping_add(8));
+        }
+    }
+
+    /// Returns the number of cycles attributed to every symbolized stack, from the outermost
+    /// frame, sorted by stack.
+    #[must_use]
+    pub fn folded_stacks(&self) -> Vec<(String, u64)> {
+        let mut frames = HashMap::<u32, Vec<String>>::new();
+        let mut stacks = HashMap::<String, u64>::new();
+        for (stack, &count) in &self.samples {
+            let names = stack
+                .iter()
+                .flat_map(|&pc| {
+                    frames.entry(pc).or_insert_with(|| self.symbolizer.frames(pc)).clone()
+                })
+                .collect::<Vec<_>>();
+            *stacks.entry(names.join(";")).or_default() += count * self.sample_interval;
+        }
+        let mut stacks = stacks.into_iter().collect::<Vec<_>>();
+        stacks.sort_unstable();
+        stacks
+    }
+
+    /// Writes the folded stacks to the output file of the configuration.
+    pub fn write(&self) -> std::io::Result<()> {
+        let mut out = BufWriter::new(File::create(&self.output)?);
+        for (stack, cycles) in self.folded_stacks() {
+            writeln!(out, "{stack} {cycles}")?;
+        }
+        out.flush()
+    }
+}
+
+/// Maps addresses to the names of the functions containing them.
+struct Symbolizer<'a> {
+    /// The DWARF information of the ELF, if it has any.
+    dwarf: Option<Dwarf<'a>>,
+    /// The function symbols of the ELF as `(start, end, name)`, sorted by start.
+    symbols: Vec<(u32, u32, String)>,
+}
+
+impl<'a> Symbolizer<'a> {
+    fn new(elf: &'a [u8]) -> Self {
+        let Ok(elf) = ElfBytes::<LittleEndian>::minimal_parse(elf) else {
+            tracing::warn!("profiler: failed to parse the ELF, samples will not be symbolized");
+            return Self { dwarf: None, symbols: Vec::new() };
+        };
+
+        let dwarf = load_dwarf(&elf);
+
+        let mut symbols = Vec::new();
+        if let Ok(Some((table, names))) = elf.symbol_table() {
+            for symbol in table.iter().filter(|symbol| symbol.st_symtype() == STT_FUNC) {
+                if let Ok(name) = names.get(symbol.st_name as usize) {
+                    let start = symbol.st_value as u32;
+                    let end = start.saturating_add(symbol.st_size.max(1) as u32);
+                    symbols.push((start, end, demangle(name)));
+                }
+            }
+        }
+        symbols.sort_unstable();
+
+        Self { dwarf, symbols }
+    }
+
+    /// Returns the names of the functions containing `pc`, from the outermost inlined function.
+    fn frames(&self, pc: u32) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(dwarf) = &self.dwarf {
+            if let Ok(mut frames) = dwarf.find_frames(pc.into()).skip_all_loads() {
+                while let Ok(Some(frame)) = frames.next() {
+                    if let Some(Ok(name)) = frame.function.as_ref().map(|f| f.raw_name()) {
+                        names.push(demangle(&name));
+                    }
+                }
+            }
+        }
+        if names.is_empty() {
+            let i = self.symbols.partition_point(|&(start, _, _)| start <= pc);
+            match i.checked_sub(1).map(|i| &self.symbols[i]) {
+                Some((_, end, name)) if pc < *end => names.push(name.clone()),
+                _ => names.push(format!("{pc:#010x}")),
+            }
+        }
+        names.reverse();
+        names
+    }
+}
+
+/// The DWARF information of an ELF.
+pub(crate) type Dwarf<'a> = Context<EndianSlice<'a, GimliLittleEndian>>;
+
+/// Loads the DWARF information of an ELF, or returns `None` if it has none.
+pub(crate) fn load_dwarf<'a>(elf: &ElfBytes<'a, LittleEndian>) -> Option<Dwarf<'a>> {
+    gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+        let data = elf
+            .section_header_by_name(id.name())
+            .ok()
+            .flatten()
+            .and_then(|header| elf.section_data(&header).ok())
+            .filter(|(_, compression)| compression.is_none())
+            .map_or(&[][..], |(data, _)| data);
+        Ok(EndianSlice::new(data, GimliLittleEndian))
+    })
+    .and_then(Context::from_dwarf)
+    .inspect_err(|err| tracing::warn!("failed to load the DWARF information: {err}"))
+    .ok()
+}
+
+/// Demangles a Rust symbol, without its hash. Other symbols are returned as they are.
+fn demangle(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name)).replace(';', ":")
+}
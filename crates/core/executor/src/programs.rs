@@ -6,7 +6,7 @@ pub mod tests {
     use crate::{Instruction, Opcode, Program};
 
     use test_artifacts::{
-        FIBONACCI_ELF, HELLO_WORLD_ELF, KECCAK_SPONGE_ELF, MAX_MEMORY_ELF, PANIC_ELF,
+        FIBONACCI_ELF, HELLO_WORLD_ELF, HINT_IO_ELF, KECCAK_SPONGE_ELF, MAX_MEMORY_ELF, PANIC_ELF,
         SECP256R1_ADD_ELF, SECP256R1_DOUBLE_ELF, SHA3_CHAIN_ELF, U256XU2048_MUL_ELF,
         UNCONSTRAINED_ELF,
     };
@@ -51,6 +51,16 @@ pub mod tests {
         Program::from(HELLO_WORLD_ELF).unwrap()
     }
 
+    /// Get the hint-io program, which reads the same bytes twice and asserts that they match.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the program fails to load.
+    #[must_use]
+    pub fn hint_io_program() -> Program {
+        Program::from(HINT_IO_ELF).unwrap()
+    }
+
     /// Get the sha3-chain program.
     ///
     /// # Panics
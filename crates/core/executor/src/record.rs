@@ -9,6 +9,7 @@ use zkm_stark::{
 };
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{mem::take, str::FromStr, sync::Arc};
 
 use crate::{
@@ -81,6 +82,55 @@ pub struct ExecutionRecord {
 }
 
 impl ExecutionRecord {
+    /// A digest of the events, public values and shape of the record, which determine the traces
+    /// of its shard, so records with the same digest have the same traces.
+    ///
+    /// The program is not hashed, and neither are the byte lookups, which are derived from the
+    /// events. The maps of the record are hashed in a canonical order.
+    #[must_use]
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        macro_rules! hash {
+            ($($value:expr),* $(,)?) => {
+                $(bincode::serialize_into(&mut hasher, &$value).expect("failed to hash the record");)*
+            };
+        }
+        hash!(
+            self.cpu_events,
+            self.add_sub_events,
+            self.mul_events,
+            self.bitwise_events,
+            self.shift_left_events,
+            self.shift_right_events,
+            self.divrem_events,
+            self.lt_events,
+            self.cloclz_events,
+            self.memory_instr_events,
+            self.branch_events,
+            self.jump_events,
+            self.movcond_events,
+            self.misc_events,
+            self.precompile_events.sorted_events(),
+            self.global_memory_initialize_events,
+            self.global_memory_finalize_events,
+            self.cpu_local_memory_access,
+            self.syscall_events,
+            self.global_lookup_events,
+            self.public_values,
+        );
+        let shape = self.shape.as_ref().map(|shape| {
+            let mut shape = shape
+                .clone()
+                .into_iter()
+                .map(|(air, log_height)| (air.to_string(), log_height))
+                .collect::<Vec<_>>();
+            shape.sort_unstable();
+            shape
+        });
+        hash!(shape);
+        hasher.finalize().into()
+    }
+
     /// Create a new [`ExecutionRecord`].
     #[must_use]
     #[cfg(feature = "pre-alloc")]
@@ -142,6 +192,9 @@ impl ExecutionRecord {
                 SyscallCode::KECCAK_SPONGE => opts.keccak,
                 SyscallCode::SHA_EXTEND => opts.sha_extend,
                 SyscallCode::SHA_COMPRESS => opts.sha_compress,
+                SyscallCode::BLAKE3_COMPRESS_INNER => opts.blake3_compress,
+                SyscallCode::SHA512_EXTEND => opts.sha512_extend,
+                SyscallCode::SHA512_COMPRESS => opts.sha512_compress,
                 _ => opts.deferred,
             };
 
@@ -5,8 +5,12 @@ use std::{
 
 use enum_map::{EnumArray, EnumMap};
 use hashbrown::HashMap;
+use zkm_stark::ShardTuning;
 
-use crate::{events::generate_execution_report, syscalls::SyscallCode, Opcode};
+use crate::{
+    events::generate_execution_report, memory_gas, mips_costs, opcode_gas, syscall_gas,
+    syscalls::SyscallCode, Coverage, MipsAirId, Opcode,
+};
 
 /// An execution report.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -19,6 +23,44 @@ pub struct ExecutionReport {
     pub cycle_tracker: HashMap<String, u64>,
     /// The unique memory address counts.
     pub touched_memory_addresses: u64,
+    /// The SHA-256 digest of all input bytes consumed by the program, if the input transcript
+    /// was enabled in the [`crate::ZKMContext`].
+    pub input_digest: Option<[u8; 32]>,
+    /// Snapshots of the rolling execution state digest, as `(global_clk, digest)` pairs, if state
+    /// digests were enabled in the [`crate::ZKMContext`].
+    pub state_digests: Vec<(u64, [u8; 32])>,
+    /// The public logs committed by the program, whose digest ends its public values.
+    pub public_logs: Vec<u8>,
+    /// The chunks of the outputs committed by the program with `commit_merkle`, in the order
+    /// their roots were committed in the public values.
+    pub merkle_outputs: Vec<Vec<Vec<u8>>>,
+    /// The names of the named inputs written by the host which the program did not read.
+    pub unread_named_inputs: Vec<String>,
+    /// The estimated number of events of each chip in every shard, if cost estimation was
+    /// enabled in the [`crate::ZKMContext`].
+    pub shard_event_counts: Vec<EnumMap<MipsAirId, u64>>,
+    /// The shard parameters picked for the program, if shard auto-tuning was enabled in the
+    /// [`zkm_stark::ZKMCoreOpts`].
+    pub shard_tuning: Option<ShardTuning>,
+    /// The line coverage of the guest source, if coverage was enabled in the
+    /// [`crate::ZKMContext`].
+    pub(crate) coverage: Option<Coverage>,
+    /// The memory limits of the execution and the peak memory usage of the program, if a memory
+    /// or stack limit was set in the [`crate::ZKMContext`].
+    pub memory_usage: Option<MemoryUsage>,
+}
+
+/// The memory limits of an execution, and the memory the program used.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The highest address, exclusive, which the program may access below its stack.
+    pub max_memory: Option<u32>,
+    /// The maximum size of the stack of the program in bytes.
+    pub max_stack_size: Option<u32>,
+    /// The highest address, exclusive, which the program accessed below its stack.
+    pub peak_memory: u32,
+    /// The size of the deepest stack of the program in bytes.
+    pub peak_stack_size: u32,
 }
 
 impl ExecutionReport {
@@ -28,11 +70,68 @@ impl ExecutionReport {
         self.opcode_counts.values().sum()
     }
 
+    /// The line coverage of the guest source, if coverage was enabled in the
+    /// [`crate::ZKMContext`].
+    #[must_use]
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
     /// Compute the total number of syscalls made during the execution.
     #[must_use]
     pub fn total_syscall_count(&self) -> u64 {
         self.syscall_counts.values().sum()
     }
+
+    /// Compute the gas of the execution: the trace cells its instructions, syscalls and memory
+    /// accesses add to the proof, according to the per-row costs of the chips in
+    /// `mips_costs.json`.
+    ///
+    /// Unlike the trace area, the gas does not depend on the shard size or on the padding of the
+    /// traces, so it is the same for every execution of a program on the same input.
+    #[must_use]
+    pub fn gas(&self) -> u64 {
+        self.opcode_gas().values().sum::<u64>()
+            + self.syscall_gas().values().sum::<u64>()
+            + self.memory_gas()
+    }
+
+    /// Compute the gas of the instructions of each opcode, which includes the `SYSCALL`
+    /// instructions but not the syscalls themselves.
+    #[must_use]
+    pub fn opcode_gas(&self) -> EnumMap<Opcode, u64> {
+        let costs = gas_costs();
+        EnumMap::from_fn(|opcode| self.opcode_counts[opcode] * opcode_gas(opcode, &costs))
+    }
+
+    /// Compute the gas of the calls of each syscall.
+    #[must_use]
+    pub fn syscall_gas(&self) -> EnumMap<SyscallCode, u64> {
+        let costs = gas_costs();
+        EnumMap::from_fn(|syscall| self.syscall_counts[syscall] * syscall_gas(syscall, &costs))
+    }
+
+    /// Compute the gas of the touched memory addresses.
+    #[must_use]
+    pub fn memory_gas(&self) -> u64 {
+        memory_gas(self.touched_memory_addresses, &gas_costs())
+    }
+}
+
+fn gas_costs() -> HashMap<MipsAirId, u64> {
+    mips_costs().into_iter().map(|(air, cost)| (air, cost as u64)).collect()
+}
+
+/// The opcode histogram of the first cycles of an execution, sent to the channel set with
+/// [`crate::ZKMContextBuilder::opcode_sample`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcodeSample {
+    /// The number of cycles sampled.
+    pub cycles: u64,
+    /// The opcode counts over the sampled cycles.
+    pub opcode_counts: EnumMap<Opcode, u64>,
+    /// The estimated number of events of each chip over the sampled cycles.
+    pub event_counts: EnumMap<MipsAirId, u64>,
 }
 
 /// Combines two `HashMap`s together. If a key is in both maps, the values are added together.
@@ -74,6 +173,20 @@ impl Display for ExecutionReport {
         for line in generate_execution_report(self.syscall_counts.as_ref()) {
             writeln!(f, "  {line}")?;
         }
+
+        writeln!(f, "gas: {}", self.gas())?;
+
+        if let Some(tuning) = &self.shard_tuning {
+            writeln!(
+                f,
+                "shard tuning: shard_size={}, shard_batch_size={}",
+                tuning.shard_size, tuning.shard_batch_size
+            )?;
+        }
+
+        if let Some(input_digest) = &self.input_digest {
+            writeln!(f, "input digest: 0x{}", hex::encode(input_digest))?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,157 @@
+//! Snapshots of the executor, to fork the execution of a program on several inputs.
+//!
+//! A program is executed once on the input common to all forks with
+//! [`Executor::run_until_input_exhausted`], which stops when it is about to read past it. The
+//! state of the executor is then saved with [`Executor::snapshot`], and every fork restores it
+//! with [`Executor::restore`], writes its own input and resumes the execution.
+
+use hashbrown::HashMap;
+use sha2::Sha256;
+
+use crate::{
+    events::{MemoryLocalEvent, MemoryRecord},
+    memory::Memory,
+    record::{ExecutionRecord, MemoryAccessRecord},
+    state::ExecutionState,
+    syscalls::SyscallCode,
+    ExecutionError, ExecutionReport, Executor, ExecutorMode, LocalCounts, Opcode, Register,
+};
+
+/// A snapshot of the state of an [`Executor`], taken with [`Executor::snapshot`].
+///
+/// The memory is shared with the executor and its pages are only copied once written, so cloning
+/// a snapshot to restore it several times is cheap.
+#[derive(Debug, Clone)]
+pub struct ExecutorState {
+    /// The state of the execution.
+    pub state: ExecutionState,
+    record: Box<ExecutionRecord>,
+    records: Vec<ExecutionRecord>,
+    memory_checkpoint: Memory<Option<MemoryRecord>>,
+    uninitialized_memory_checkpoint: Memory<bool>,
+    memory_accesses: MemoryAccessRecord,
+    local_memory_access: HashMap<u32, MemoryLocalEvent>,
+    local_counts: LocalCounts,
+    cycle_tracker: HashMap<String, (u64, u32)>,
+    io_buf: HashMap<u32, String>,
+    report: ExecutionReport,
+    input_transcript: Option<Sha256>,
+    state_digest: Option<(u64, Sha256)>,
+    coverage: Option<Vec<u64>>,
+}
+
+impl Executor<'_> {
+    /// Executes the program until it is about to read past the end of its input stream, or until
+    /// it finishes, returning whether it has finished.
+    ///
+    /// The input written so far is the prefix shared by the forks of the execution, see
+    /// [`Self::snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the program execution fails.
+    pub fn run_until_input_exhausted(&mut self) -> Result<bool, ExecutionError> {
+        self.executor_mode = ExecutorMode::Simple;
+        self.print_report = true;
+        if self.state.global_clk == 0 {
+            self.initialize();
+        }
+        while !self.awaits_input() {
+            if self.step()? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Takes a snapshot of the state of the executor, to which [`Self::restore`] returns.
+    ///
+    /// The hooks, the watchpoints and the profiler are not part of the state: they keep running
+    /// across restores.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the executor is in an unconstrained block.
+    #[must_use]
+    pub fn snapshot(&self) -> ExecutorState {
+        assert!(!self.unconstrained, "cannot snapshot the executor in an unconstrained block");
+        ExecutorState {
+            state: self.state.clone(),
+            record: Box::new(self.record.clone()),
+            records: self.records.clone(),
+            memory_checkpoint: self.memory_checkpoint.clone(),
+            uninitialized_memory_checkpoint: self.uninitialized_memory_checkpoint.clone(),
+            memory_accesses: self.memory_accesses,
+            local_memory_access: self.local_memory_access.clone(),
+            local_counts: self.local_counts.clone(),
+            cycle_tracker: self.cycle_tracker.clone(),
+            io_buf: self.io_buf.clone(),
+            report: self.report.clone(),
+            input_transcript: self.input_transcript.clone(),
+            state_digest: self.state_digest.clone(),
+            coverage: self.coverage.as_ref().map(|(_, hits)| hits.clone()),
+        }
+    }
+
+    /// Restores a snapshot taken with [`Self::snapshot`] on an executor of the same program.
+    pub fn restore(&mut self, snapshot: ExecutorState) {
+        self.state = snapshot.state;
+        self.record = *snapshot.record;
+        self.records = snapshot.records;
+        self.memory_checkpoint = snapshot.memory_checkpoint;
+        self.uninitialized_memory_checkpoint = snapshot.uninitialized_memory_checkpoint;
+        self.memory_accesses = snapshot.memory_accesses;
+        self.local_memory_access = snapshot.local_memory_access;
+        self.local_counts = snapshot.local_counts;
+        self.cycle_tracker = snapshot.cycle_tracker;
+        self.update_region_deadline();
+        self.io_buf = snapshot.io_buf;
+        self.report = snapshot.report;
+        self.input_transcript = snapshot.input_transcript;
+        self.state_digest = snapshot.state_digest;
+        if let (Some((_, hits)), Some(snapshot_hits)) = (&mut self.coverage, snapshot.coverage) {
+            *hits = snapshot_hits;
+        }
+        self.unconstrained = false;
+    }
+
+    /// Whether the next instruction reads the length of an input which was not written.
+    fn awaits_input(&self) -> bool {
+        self.state.input_stream_ptr == self.state.input_stream.len()
+            && self.program.fetch(self.state.pc).opcode == Opcode::SYSCALL
+            && self.state.memory.registers.get(Register::V0 as u32).map(|record| record.value)
+                == Some(SyscallCode::SYSHINTLEN as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zkm_stark::ZKMCoreOpts;
+
+    use crate::{programs::tests::hint_io_program, Executor};
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut runtime = Executor::new(hint_io_program(), ZKMCoreOpts::default());
+        runtime.write_stdin(&vec![7u8; 40]);
+        assert!(!runtime.run_until_input_exhausted().unwrap());
+        let snapshot = runtime.snapshot();
+        let prefix_clk = snapshot.state.global_clk;
+        assert!(prefix_clk > 0);
+
+        let mut cycles = Vec::new();
+        for _ in 0..2 {
+            runtime.restore(snapshot.clone());
+            runtime.write_stdin_slice(&[7u8; 40]);
+            runtime.run_fast().unwrap();
+            cycles.push(runtime.state.global_clk);
+        }
+        assert_eq!(cycles[0], cycles[1]);
+        assert!(cycles[0] > prefix_clk);
+
+        // The program asserts that both inputs are equal.
+        runtime.restore(snapshot);
+        runtime.write_stdin_slice(&[8u8; 40]);
+        assert!(runtime.run_fast().is_err());
+    }
+}
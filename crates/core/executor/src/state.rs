@@ -1,9 +1,10 @@
 use std::{
     fs::File,
     io::{Seek, Write},
+    time::Duration,
 };
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use zkm_stark::{koala_bear_poseidon2::KoalaBearPoseidon2, StarkVerifyingKey};
 
@@ -15,6 +16,16 @@ use crate::{
     ExecutorMode, ZKMReduceProof,
 };
 
+/// An input written by the host under a name, which the program reads with
+/// `zkm_zkvm::io::read_named`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedInput {
+    /// The name of the type of the input, checked against the type the program reads.
+    pub type_name: String,
+    /// The bincode serialization of the input.
+    pub bytes: Vec<u8>,
+}
+
 /// Holds data describing the current state of a program's execution.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[repr(C)]
@@ -55,6 +66,24 @@ pub struct ExecutionState {
     /// A ptr to the current position in the input stream incremented by `HINT_READ` opcode.
     pub input_stream_ptr: usize,
 
+    /// The virtual files provided to the program, indexed by their path. The program reads them
+    /// through the `FD_FS_READ` hook.
+    pub files: HashMap<String, Vec<u8>>,
+
+    /// The inputs written by the host under a name, which the program reads through the
+    /// `FD_NAMED_INPUT` file descriptor.
+    pub named_inputs: HashMap<String, NamedInput>,
+
+    /// The names of the named inputs the program read.
+    pub named_inputs_read: HashSet<String>,
+
+    /// The seed of the random number generator of the program, read through the `FD_RNG_SEED`
+    /// hook.
+    pub rng_seed: Option<[u8; 32]>,
+
+    /// The time of the deterministic clock at the first cycle, read through the `FD_CLOCK` hook.
+    pub time_base: Option<Duration>,
+
     /// A stream of proofs (reduce vk, proof, verifying key) inputted to the program.
     pub proof_stream:
         Vec<(ZKMReduceProof<KoalaBearPoseidon2>, StarkVerifyingKey<KoalaBearPoseidon2>)>,
@@ -68,8 +97,23 @@ pub struct ExecutionState {
     /// A ptr to the current position in the public values stream, incremented when reading from
     /// `public_values_stream`.
     pub public_values_stream_ptr: usize,
+
+    /// A stream of public logs from the program, whose digest is committed in the public values.
+    pub public_logs_stream: Vec<u8>,
+
+    /// The chunks of the outputs committed by the program with `commit_merkle`, whose roots are
+    /// committed in the public values.
+    pub merkle_outputs: Vec<Vec<Vec<u8>>>,
+
     // /// Keeps track of how many times a certain syscall has been called.
     pub syscall_counts: HashMap<SyscallCode, u64>,
+
+    /// The number of cycles after which the execution pauses at the end of a continuation
+    /// segment, if set. See [`crate::ContinuationState`].
+    pub segment_cycles: Option<u64>,
+
+    /// Whether the execution paused at the end of a continuation segment instead of halting.
+    pub paused: bool,
 }
 
 impl ExecutionState {
@@ -89,11 +133,20 @@ impl ExecutionState {
             uninitialized_memory: Memory::new_preallocated(),
             input_stream: Vec::new(),
             input_stream_ptr: 0,
+            files: HashMap::new(),
+            named_inputs: HashMap::new(),
+            named_inputs_read: HashSet::new(),
+            rng_seed: None,
+            time_base: None,
             public_values_stream: Vec::new(),
             public_values_stream_ptr: 0,
+            public_logs_stream: Vec::new(),
+            merkle_outputs: Vec::new(),
             proof_stream: Vec::new(),
             proof_stream_ptr: 0,
             syscall_counts: HashMap::new(),
+            segment_cycles: None,
+            paused: false,
         }
     }
 }
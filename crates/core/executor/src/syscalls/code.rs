@@ -166,12 +166,15 @@ pub enum SyscallCode {
     SYS_OPEN = 4005,
     SYS_CLOSE = 4006,
     SYS_MUNMAP = 4091,
+    SYS_SCHED_YIELD = 4162,
+    SYS_NANOSLEEP = 4166,
     SYS_RT_SIGACTION = 4194,
     SYS_RT_SIGPROCMASK = 4195,
     SYS_SIGALTSTACK = 4206,
     SYS_FSTAT64 = 4215,
     SYS_MADVISE = 4218,
     SYS_GETTID = 4222,
+    SYS_FUTEX = 4238,
     SYS_SCHED_GETAFFINITY = 4240,
     SYS_CLOCK_GETTIME = 4263,
     SYS_OPENAT = 4288,
@@ -180,6 +183,15 @@ pub enum SyscallCode {
     /// Executes the `POSEIDON2_PERMUTE` precompile.
     POSEIDON2_PERMUTE = 0x00_01_00_30,
 
+    /// Executes the `BLAKE3_COMPRESS_INNER` precompile.
+    BLAKE3_COMPRESS_INNER = 0x38_01_00_31,
+
+    /// Executes the `SHA512_EXTEND` precompile.
+    SHA512_EXTEND = 0x40_01_00_32,
+
+    /// Executes the `SHA512_COMPRESS` precompile.
+    SHA512_COMPRESS = 0x01_01_00_33,
+
     SYS_LINUX = 4000, // not real syscall, used for represent all linux syscalls
 
     UNIMPLEMENTED = 0xFF_FF_FF_FF,
@@ -212,6 +224,9 @@ impl SyscallCode {
             0x00_00_00_1A => SyscallCode::COMMIT_DEFERRED_PROOFS,
             0x00_00_00_1B => SyscallCode::VERIFY_ZKM_PROOF,
             0x00_01_00_30 => SyscallCode::POSEIDON2_PERMUTE,
+            0x38_01_00_31 => SyscallCode::BLAKE3_COMPRESS_INNER,
+            0x40_01_00_32 => SyscallCode::SHA512_EXTEND,
+            0x01_01_00_33 => SyscallCode::SHA512_COMPRESS,
             0x00_01_00_1C => SyscallCode::BLS12381_DECOMPRESS,
             0x01_01_00_1D => SyscallCode::UINT256_MUL,
             0x01_01_00_1E => SyscallCode::BLS12381_ADD,
@@ -242,6 +257,8 @@ impl SyscallCode {
             4090 => SyscallCode::SYS_MMAP2,
             4091 => SyscallCode::SYS_MUNMAP,
             4120 => SyscallCode::SYS_CLONE,
+            4162 => SyscallCode::SYS_SCHED_YIELD,
+            4166 => SyscallCode::SYS_NANOSLEEP,
             4194 => SyscallCode::SYS_RT_SIGACTION,
             4195 => SyscallCode::SYS_RT_SIGPROCMASK,
             4206 => SyscallCode::SYS_SIGALTSTACK,
@@ -249,6 +266,7 @@ impl SyscallCode {
             4215 => SyscallCode::SYS_FSTAT64,
             4218 => SyscallCode::SYS_MADVISE,
             4222 => SyscallCode::SYS_GETTID,
+            4238 => SyscallCode::SYS_FUTEX,
             4240 => SyscallCode::SYS_SCHED_GETAFFINITY,
             4246 => SyscallCode::SYS_EXT_GROUP,
             4263 => SyscallCode::SYS_CLOCK_GETTIME,
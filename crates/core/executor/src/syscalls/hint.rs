@@ -1,3 +1,5 @@
+use sha2::Digest;
+
 use super::{Syscall, SyscallCode, SyscallContext};
 
 pub(crate) struct HintLenSyscall;
@@ -37,6 +39,12 @@ impl Syscall for HintReadSyscall {
         assert!(!ctx.rt.unconstrained, "hint read should not be used in a unconstrained block");
         assert_eq!(vec.len() as u32, len, "hint input stream read length mismatch");
         assert_eq!(ptr % 4, 0, "hint read address not aligned to 4 bytes");
+        // Absorb the consumed buffer into the input transcript, prefixed with its length so that
+        // the digest is unambiguous with respect to buffer boundaries.
+        if let Some(transcript) = ctx.rt.input_transcript.as_mut() {
+            transcript.update((vec.len() as u64).to_le_bytes());
+            transcript.update(vec);
+        }
         // Iterate through the vec in 4-byte chunks
         for i in (0..len).step_by(4) {
             // Get each byte in the chunk
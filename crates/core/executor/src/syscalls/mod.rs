@@ -8,6 +8,7 @@ mod halt;
 mod hint;
 pub(crate) mod precompiles;
 mod unconstrained;
+mod user;
 mod verify;
 mod write;
 
@@ -22,11 +23,13 @@ pub use code::*;
 pub use context::*;
 use hint::{HintLenSyscall, HintReadSyscall};
 use precompiles::{
+    blake3::compress::Blake3CompressInnerSyscall,
     edwards::{add::EdwardsAddAssignSyscall, decompress::EdwardsDecompressSyscall},
     fptower::{Fp2AddSubSyscall, Fp2MulSyscall, FpOpSyscall},
     keccak::sponge::KeccakSpongeSyscall,
     poseidon2::permute::Poseidon2PermuteSyscall,
     sha256::{compress::Sha256CompressSyscall, extend::Sha256ExtendSyscall},
+    sha512::{compress::Sha512CompressSyscall, extend::Sha512ExtendSyscall},
     sys_linux::{
         sysbrk::SysBrkSyscall, sysclone::SysCloneSyscall, sysexitgroup::SysExitGroupSyscall,
         sysfcntl::SysFcntlSyscall, sysmmap::SysMmapSyscall, sysnop::SysNopSyscall,
@@ -41,6 +44,7 @@ use precompiles::{
 };
 
 use unconstrained::{EnterUnconstrainedSyscall, ExitUnconstrainedSyscall};
+pub use user::*;
 use verify::VerifySyscall;
 use write::WriteSyscall;
 use zkm_curves::{
@@ -91,6 +95,10 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 
     syscall_map.insert(SyscallCode::SHA_COMPRESS, Arc::new(Sha256CompressSyscall));
 
+    syscall_map.insert(SyscallCode::SHA512_EXTEND, Arc::new(Sha512ExtendSyscall));
+
+    syscall_map.insert(SyscallCode::SHA512_COMPRESS, Arc::new(Sha512CompressSyscall));
+
     syscall_map.insert(SyscallCode::ED_ADD, Arc::new(EdwardsAddAssignSyscall::<Ed25519>::new()));
 
     syscall_map.insert(
@@ -102,6 +110,8 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
 
     syscall_map.insert(SyscallCode::POSEIDON2_PERMUTE, Arc::new(Poseidon2PermuteSyscall));
 
+    syscall_map.insert(SyscallCode::BLAKE3_COMPRESS_INNER, Arc::new(Blake3CompressInnerSyscall));
+
     syscall_map.insert(SyscallCode::KECCAK_SPONGE, Arc::new(KeccakSpongeSyscall));
 
     syscall_map.insert(
@@ -256,6 +266,12 @@ pub fn default_syscall_map() -> HashMap<SyscallCode, Arc<dyn Syscall>> {
     syscall_map.insert(SyscallCode::SYS_OPENAT, Arc::new(SysNopSyscall));
     syscall_map.insert(SyscallCode::SYS_FSTAT64, Arc::new(SysNopSyscall));
     syscall_map.insert(SyscallCode::SYS_MUNMAP, Arc::new(SysNopSyscall));
+    // `clone` is not emulated: it reports success but the thread never runs. The Go runtime can
+    // only multiplex its goroutines on the main thread, so waiting on a futex, yielding or
+    // sleeping returns immediately rather than switching threads.
+    syscall_map.insert(SyscallCode::SYS_FUTEX, Arc::new(SysNopSyscall));
+    syscall_map.insert(SyscallCode::SYS_SCHED_YIELD, Arc::new(SysNopSyscall));
+    syscall_map.insert(SyscallCode::SYS_NANOSLEEP, Arc::new(SysNopSyscall));
 
     syscall_map
 }
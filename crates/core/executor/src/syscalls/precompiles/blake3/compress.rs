@@ -0,0 +1,91 @@
+use crate::{
+    events::{Blake3CompressInnerEvent, PrecompileEvent},
+    syscalls::{Syscall, SyscallCode, SyscallContext},
+};
+
+pub use zkm_primitives::consts::blake3::{BLAKE3_G_INDEX, BLAKE3_MSG_SCHEDULE};
+
+/// The number of rounds of the BLAKE3 compression function.
+pub const BLAKE3_NUM_ROUNDS: usize = 7;
+
+/// The BLAKE3 G function, mixing the state words `(a, b, c, d)` with the message words `mx` and
+/// `my`.
+#[must_use]
+pub fn blake3_g(abcd: [u32; 4], mx: u32, my: u32) -> [u32; 4] {
+    let [mut a, mut b, mut c, mut d] = abcd;
+    a = a.wrapping_add(b).wrapping_add(mx);
+    d = (d ^ a).rotate_right(16);
+    c = c.wrapping_add(d);
+    b = (b ^ c).rotate_right(12);
+    a = a.wrapping_add(b).wrapping_add(my);
+    d = (d ^ a).rotate_right(8);
+    c = c.wrapping_add(d);
+    b = (b ^ c).rotate_right(7);
+    [a, b, c, d]
+}
+
+pub(crate) struct Blake3CompressInnerSyscall;
+
+impl Syscall for Blake3CompressInnerSyscall {
+    fn num_extra_cycles(&self) -> u32 {
+        (BLAKE3_NUM_ROUNDS * BLAKE3_G_INDEX.len()) as u32
+    }
+
+    fn execute(
+        &self,
+        rt: &mut SyscallContext,
+        syscall_code: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+    ) -> Option<u32> {
+        let clk_init = rt.clk;
+        let state_ptr = arg1;
+        let msg_ptr = arg2;
+        assert!(state_ptr.is_multiple_of(4), "state_ptr must be aligned");
+        assert!(msg_ptr.is_multiple_of(4), "msg_ptr must be aligned");
+        assert!(state_ptr.abs_diff(msg_ptr) >= 64, "the state and the message must not overlap");
+
+        // Read the state with `slice_unsafe`, as every word of it is written with a record later.
+        let mut state: [u32; 16] = rt.slice_unsafe(state_ptr, 16).try_into().unwrap();
+
+        let num_rows = BLAKE3_NUM_ROUNDS * BLAKE3_G_INDEX.len();
+        let mut mx_reads = Vec::with_capacity(num_rows);
+        let mut my_reads = Vec::with_capacity(num_rows);
+        let mut state_writes = Vec::with_capacity(4 * num_rows);
+        for schedule in BLAKE3_MSG_SCHEDULE.iter() {
+            for (g, index) in BLAKE3_G_INDEX.iter().enumerate() {
+                // Read the message words of this G function.
+                let (record, mx) = rt.mr(msg_ptr + schedule[2 * g] as u32 * 4);
+                mx_reads.push(record);
+                let (record, my) = rt.mr(msg_ptr + schedule[2 * g + 1] as u32 * 4);
+                my_reads.push(record);
+
+                // Mix the state words in place.
+                let abcd = blake3_g(index.map(|i| state[i]), mx, my);
+                for (&i, value) in index.iter().zip(abcd) {
+                    state[i] = value;
+                    state_writes.push(rt.mw(state_ptr + i as u32 * 4, value));
+                }
+                rt.clk += 1;
+            }
+        }
+
+        // Push the BLAKE3 compress event.
+        let shard = rt.current_shard();
+        let event = PrecompileEvent::Blake3CompressInner(Blake3CompressInnerEvent {
+            shard,
+            clk: clk_init,
+            state_ptr,
+            msg_ptr,
+            mx_reads,
+            my_reads,
+            state_writes,
+            local_mem_access: rt.postprocess(),
+        });
+        let syscall_event =
+            rt.rt.syscall_event(clk_init, None, rt.next_pc, syscall_code.syscall_id(), arg1, arg2);
+        rt.add_precompile_event(syscall_code, syscall_event, event);
+
+        None
+    }
+}
@@ -1,8 +1,10 @@
+pub mod blake3;
 pub mod edwards;
 pub mod fptower;
 pub mod keccak;
 pub mod poseidon2;
 pub mod sha256;
+pub mod sha512;
 pub mod sys_linux;
 pub mod u256x2048_mul;
 pub mod uint256;
@@ -0,0 +1,95 @@
+use crate::{
+    events::{PrecompileEvent, Sha512CompressEvent},
+    syscalls::{Syscall, SyscallCode, SyscallContext},
+};
+
+pub use zkm_primitives::consts::sha512::SHA512_COMPRESS_K;
+
+pub(crate) struct Sha512CompressSyscall;
+
+impl Syscall for Sha512CompressSyscall {
+    fn num_extra_cycles(&self) -> u32 {
+        1
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn execute(
+        &self,
+        rt: &mut SyscallContext,
+        syscall_code: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+    ) -> Option<u32> {
+        let w_ptr = arg1;
+        let h_ptr = arg2;
+        assert_ne!(w_ptr, h_ptr);
+        assert!(w_ptr.is_multiple_of(4), "w_ptr must be aligned");
+        assert!(h_ptr.is_multiple_of(4), "h_ptr must be aligned");
+
+        let start_clk = rt.clk;
+
+        // Execute the "initialize" phase where we read in the h values.
+        let (h_read_records, h_words) = rt.mr_slice(h_ptr, 16);
+        let hx: [u64; 8] =
+            core::array::from_fn(|i| h_words[2 * i] as u64 | (h_words[2 * i + 1] as u64) << 32);
+
+        // Execute the "compress" phase.
+        let (w_i_read_records, w_words) = rt.mr_slice(w_ptr, 160);
+        let original_w: Vec<u64> =
+            w_words.chunks_exact(2).map(|w| w[0] as u64 | (w[1] as u64) << 32).collect();
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = hx;
+        for (i, w_i) in original_w.iter().enumerate() {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA512_COMPRESS_K[i])
+                .wrapping_add(*w_i);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        // Increment the clk by 1 before writing to h, since we've already read h at the start_clk
+        // during the initialization phase.
+        rt.clk += 1;
+
+        // Execute the "finalize" phase.
+        let v = [a, b, c, d, e, f, g, h];
+        let mut h_write_records = Vec::with_capacity(16);
+        for i in 0..8 {
+            let value = hx[i].wrapping_add(v[i]);
+            h_write_records.push(rt.mw(h_ptr + i as u32 * 8, value as u32));
+            h_write_records.push(rt.mw(h_ptr + i as u32 * 8 + 4, (value >> 32) as u32));
+        }
+
+        // Push the SHA-512 compress event.
+        let shard = rt.current_shard();
+        let event = PrecompileEvent::Sha512Compress(Sha512CompressEvent {
+            shard,
+            clk: start_clk,
+            w_ptr,
+            h_ptr,
+            w: original_w,
+            h: hx,
+            h_read_records: h_read_records.try_into().unwrap(),
+            w_i_read_records,
+            h_write_records: h_write_records.try_into().unwrap(),
+            local_mem_access: rt.postprocess(),
+        });
+        let syscall_event =
+            rt.rt.syscall_event(start_clk, None, rt.next_pc, syscall_code.syscall_id(), arg1, arg2);
+        rt.add_precompile_event(syscall_code, syscall_event, event);
+
+        None
+    }
+}
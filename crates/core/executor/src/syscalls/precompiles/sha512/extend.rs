@@ -0,0 +1,85 @@
+use crate::{
+    events::{MemoryReadRecord, PrecompileEvent, Sha512ExtendEvent},
+    syscalls::{Syscall, SyscallCode, SyscallContext},
+};
+
+pub(crate) struct Sha512ExtendSyscall;
+
+impl Sha512ExtendSyscall {
+    /// Reads the 64-bit word at `addr`, stored as its low word followed by its high word.
+    fn mr_u64(rt: &mut SyscallContext, addr: u32, reads: &mut Vec<[MemoryReadRecord; 2]>) -> u64 {
+        let (lo_record, lo) = rt.mr(addr);
+        let (hi_record, hi) = rt.mr(addr + 4);
+        reads.push([lo_record, hi_record]);
+        lo as u64 | (hi as u64) << 32
+    }
+}
+
+impl Syscall for Sha512ExtendSyscall {
+    fn num_extra_cycles(&self) -> u32 {
+        64
+    }
+
+    fn execute(
+        &self,
+        rt: &mut SyscallContext,
+        syscall_code: SyscallCode,
+        arg1: u32,
+        arg2: u32,
+    ) -> Option<u32> {
+        let clk_init = rt.clk;
+        let w_ptr = arg1;
+        assert!(arg2 == 0, "arg2 must be 0");
+        assert!(w_ptr.is_multiple_of(4), "w_ptr must be aligned");
+
+        let mut w_i_minus_15_reads = Vec::with_capacity(64);
+        let mut w_i_minus_2_reads = Vec::with_capacity(64);
+        let mut w_i_minus_16_reads = Vec::with_capacity(64);
+        let mut w_i_minus_7_reads = Vec::with_capacity(64);
+        let mut w_i_writes = Vec::with_capacity(64);
+        for i in 16..80 {
+            // Read w[i-15] and compute `s0`.
+            let w_i_minus_15 = Self::mr_u64(rt, w_ptr + (i - 15) * 8, &mut w_i_minus_15_reads);
+            let s0 =
+                w_i_minus_15.rotate_right(1) ^ w_i_minus_15.rotate_right(8) ^ (w_i_minus_15 >> 7);
+
+            // Read w[i-2] and compute `s1`.
+            let w_i_minus_2 = Self::mr_u64(rt, w_ptr + (i - 2) * 8, &mut w_i_minus_2_reads);
+            let s1 =
+                w_i_minus_2.rotate_right(19) ^ w_i_minus_2.rotate_right(61) ^ (w_i_minus_2 >> 6);
+
+            // Read w[i-16] and w[i-7].
+            let w_i_minus_16 = Self::mr_u64(rt, w_ptr + (i - 16) * 8, &mut w_i_minus_16_reads);
+            let w_i_minus_7 = Self::mr_u64(rt, w_ptr + (i - 7) * 8, &mut w_i_minus_7_reads);
+
+            // Compute `w_i`.
+            let w_i = w_i_minus_16.wrapping_add(s0).wrapping_add(w_i_minus_7).wrapping_add(s1);
+
+            // Write w[i].
+            w_i_writes.push([
+                rt.mw(w_ptr + i * 8, w_i as u32),
+                rt.mw(w_ptr + i * 8 + 4, (w_i >> 32) as u32),
+            ]);
+            rt.clk += 1;
+        }
+
+        // Push the SHA-512 extend event.
+        let shard = rt.current_shard();
+        let event = PrecompileEvent::Sha512Extend(Sha512ExtendEvent {
+            shard,
+            clk: clk_init,
+            w_ptr,
+            w_i_minus_15_reads,
+            w_i_minus_2_reads,
+            w_i_minus_16_reads,
+            w_i_minus_7_reads,
+            w_i_writes,
+            local_mem_access: rt.postprocess(),
+        });
+        let syscall_event =
+            rt.rt.syscall_event(clk_init, None, rt.next_pc, syscall_code.syscall_id(), arg1, arg2);
+        rt.add_precompile_event(syscall_code, syscall_event, event);
+
+        None
+    }
+}
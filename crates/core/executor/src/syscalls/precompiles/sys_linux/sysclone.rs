@@ -4,6 +4,8 @@ use crate::{
     Register,
 };
 
+/// The `clone` system call, which is not emulated: it returns a thread id of 1 to the caller, but
+/// no thread is created, so the function passed to it never runs.
 pub(crate) struct SysCloneSyscall;
 
 impl Syscall for SysCloneSyscall {
@@ -0,0 +1,161 @@
+use core::{fmt::Debug, ops::Range};
+
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+
+use crate::{hook::HookEnv, ExecutionError, Register};
+
+use super::SyscallContext;
+
+/// The range of syscall codes reserved for user-defined syscall handlers.
+///
+/// None of the built-in syscalls or precompiles use a code in this range. Like the codes of the
+/// hint syscalls right above it, the codes only set byte 0, so the syscall is not sent to any
+/// precompile table and is proven as a no-op. The data returned by the handler is a hint, which
+/// the program must check like any other input.
+pub const USER_SYSCALL_RANGE: Range<u32> = 0x00_00_00_C0..0x00_00_00_F0;
+
+/// The maximum length of the input buffer of a user syscall, in bytes.
+pub const MAX_USER_SYSCALL_INPUT_LEN: u32 = 1 << 20;
+
+/// The arguments passed to a user-defined syscall handler.
+///
+/// The guest invokes a user syscall with the code in `V0` and arguments in `A0..=A3`. By
+/// convention, `A0` and `A1` hold the address and length of an input buffer, which is read from
+/// memory and passed to the handler as `input`.
+#[derive(Debug, Clone, Copy)]
+pub struct UserSyscallArgs<'a> {
+    /// The syscall code, within [`USER_SYSCALL_RANGE`].
+    pub code: u32,
+    /// The values of the registers `A0`, `A1`, `A2` and `A3`.
+    pub registers: [u32; 4],
+    /// The memory slice `[A0, A0 + A1)`.
+    pub input: &'a [u8],
+}
+
+/// A user-defined syscall handler, wrapped in a smart pointer.
+pub type BoxedSyscallHandler<'a> = Arc<RwLock<dyn SyscallHandler + Send + Sync + 'a>>;
+
+/// A host-side handler for syscalls in [`USER_SYSCALL_RANGE`].
+///
+/// Like a [Hook](crate::Hook), the handler returns a list of arbitrary data which is placed at the
+/// front of the input stream, to be read by the guest with successive calls to
+/// [`zkm_zkvm::io::read`]. Handlers must be deterministic.
+pub trait SyscallHandler {
+    /// Handle a user syscall with a standard environment and the syscall arguments.
+    /// Returns the computed data.
+    fn handle(&mut self, env: HookEnv, args: UserSyscallArgs) -> Vec<Vec<u8>>;
+}
+
+impl<F: FnMut(HookEnv, UserSyscallArgs) -> Vec<Vec<u8>>> SyscallHandler for F {
+    /// Invokes the function `self` as a syscall handler.
+    fn handle(&mut self, env: HookEnv, args: UserSyscallArgs) -> Vec<Vec<u8>> {
+        self(env, args)
+    }
+}
+
+/// Wrap a function in a smart pointer so it may be placed in a [`SyscallHandlerRegistry`].
+pub fn syscall_handlerify<'a>(
+    f: impl FnMut(HookEnv, UserSyscallArgs) -> Vec<Vec<u8>> + Send + Sync + 'a,
+) -> BoxedSyscallHandler<'a> {
+    Arc::new(RwLock::new(f))
+}
+
+/// A registry of user-defined syscall handlers, indexed by the syscall code ranges they serve.
+#[derive(Clone, Default)]
+pub struct SyscallHandlerRegistry<'a> {
+    /// Table of registered handlers. Prefer using `SyscallHandlerRegistry::get` over interacting
+    /// with this field directly.
+    pub(crate) table: Vec<(Range<u32>, BoxedSyscallHandler<'a>)>,
+}
+
+impl<'a> SyscallHandlerRegistry<'a> {
+    /// Create an empty [`SyscallHandlerRegistry`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no handlers are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Get the handler for the syscall `code` with exclusive write access, if it exists.
+    ///
+    /// If several registered ranges contain `code`, the most recently registered one is used.
+    #[must_use]
+    pub fn get(
+        &self,
+        code: u32,
+    ) -> Option<RwLockWriteGuard<'_, dyn SyscallHandler + Send + Sync + 'a>> {
+        // Calling `.unwrap()` panics on a poisoned lock. Should never happen normally.
+        self.table
+            .iter()
+            .rev()
+            .find(|(range, _)| range.contains(&code))
+            .map(|(_, handler)| handler.write().unwrap())
+    }
+}
+
+impl Debug for SyscallHandlerRegistry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges = self.table.iter().map(|(range, _)| range).collect::<Vec<_>>();
+        f.debug_struct("SyscallHandlerRegistry")
+            .field(
+                "table",
+                &format_args!("{{{} handlers registered at {:?}}}", ranges.len(), ranges),
+            )
+            .finish()
+    }
+}
+
+/// Invokes the user syscall handler registered for `code`, if any.
+///
+/// Returns false if no handler is registered for `code`, and an error if the input buffer is
+/// longer than [`MAX_USER_SYSCALL_INPUT_LEN`] or wraps around the address space.
+pub(crate) fn handle_user_syscall(
+    ctx: &mut SyscallContext,
+    code: u32,
+    arg1: u32,
+    arg2: u32,
+) -> Result<bool, ExecutionError> {
+    let rt = &mut ctx.rt;
+    if rt.syscall_handlers.get(code).is_none() {
+        return Ok(false);
+    }
+    if arg2 > MAX_USER_SYSCALL_INPUT_LEN || arg1.checked_add(arg2).is_none() {
+        return Err(ExecutionError::InvalidUserSyscallInput { code, addr: arg1, len: arg2 });
+    }
+    let registers = [arg1, arg2, rt.register(Register::A2), rt.register(Register::A3)];
+    // Read `arg2` bytes from memory starting at `arg1`.
+    let input = (0..arg2).map(|i| rt.byte(arg1 + i)).collect::<Vec<u8>>();
+    let args = UserSyscallArgs { code, registers, input: &input };
+    let res = rt.syscall_handlers.get(code).unwrap().handle(rt.hook_env(), args);
+    // Add result vectors to the beginning of the stream.
+    let ptr = rt.state.input_stream_ptr;
+    rt.state.input_stream.splice(ptr..ptr, res);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_last_registered_wins() {
+        let mut registry = SyscallHandlerRegistry::new();
+        registry.table.push((0xC0..0xD0, syscall_handlerify(|_, _| vec![vec![1]])));
+        registry.table.push((0xC8..0xE0, syscall_handlerify(|_, _| vec![vec![2]])));
+        assert!(registry.get(0xBF).is_none());
+        assert!(registry.get(0xE0).is_none());
+
+        let args = |code| UserSyscallArgs { code, registers: [0; 4], input: &[] };
+        let program = crate::Program::new(vec![], 0, 0);
+        let executor = crate::Executor::new(program, zkm_stark::ZKMCoreOpts::default());
+        let mut call = |code| registry.get(code).unwrap().handle(executor.hook_env(), args(code));
+        assert_eq!(call(0xC0), vec![vec![1]]);
+        assert_eq!(call(0xC8), vec![vec![2]]);
+        assert_eq!(call(0xDF), vec![vec![2]]);
+    }
+}
@@ -51,8 +51,19 @@ pub fn write_fd(ctx: &mut SyscallContext, fd: u32, slice: &[u8]) {
         }
     } else if fd == FD_PUBLIC_VALUES {
         rt.state.public_values_stream.extend_from_slice(slice);
+    } else if fd == FD_PUBLIC_LOGS {
+        rt.state.public_logs_stream.extend_from_slice(slice);
+    } else if fd == FD_MERKLE_CHUNKS {
+        match bincode::deserialize(slice) {
+            Ok(chunks) => rt.state.merkle_outputs.push(chunks),
+            Err(err) => tracing::warn!("failed to decode the chunks of a merkle output: {err}"),
+        }
     } else if fd == FD_HINT {
         rt.state.input_stream.push(slice.to_vec());
+    } else if fd == FD_NAMED_INPUT {
+        let response = rt.read_named_input(slice);
+        let ptr = rt.state.input_stream_ptr;
+        rt.state.input_stream.insert(ptr, response);
     } else if let Some(mut hook) = rt.hook_registry.get(fd) {
         let res = hook.invoke_hook(rt.hook_env(), slice);
         // Add result vectors to the beginning of the stream.
@@ -114,6 +125,7 @@ fn handle_cycle_tracker_command(rt: &mut Executor, command: CycleTrackerCommand)
 fn start_cycle_tracker(rt: &mut Executor, name: &str) {
     let depth = rt.cycle_tracker.len() as u32;
     rt.cycle_tracker.insert(name.to_string(), (rt.state.global_clk, depth));
+    rt.update_region_deadline();
     let padding = "│ ".repeat(depth as usize);
     log::info!("{padding}┌╴{name}");
 }
@@ -122,6 +134,7 @@ fn start_cycle_tracker(rt: &mut Executor, name: &str) {
 /// in the span. If the name is not found in the cycle tracker cache, returns None.
 fn end_cycle_tracker(rt: &mut Executor, name: &str) -> Option<u64> {
     if let Some((start, depth)) = rt.cycle_tracker.remove(name) {
+        rt.update_region_deadline();
         let padding = "│ ".repeat(depth as usize);
         let total_cycles = rt.state.global_clk - start;
         log::info!("{}└╴{} cycles", padding, num_to_comma_separated(total_cycles));
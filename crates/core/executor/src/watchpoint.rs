@@ -0,0 +1,62 @@
+use core::{fmt, ops::Range};
+use std::sync::{Arc, RwLock};
+
+/// A watchpoint, wrapped in a smart pointer.
+pub type BoxedWatchpoint<'a> = Arc<RwLock<dyn FnMut(&WatchEvent) + Send + Sync + 'a>>;
+
+/// The kind of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccessKind {
+    /// The word was read.
+    Read,
+    /// The word was written.
+    Write,
+}
+
+/// An access to a word of memory in the range of a watchpoint, see
+/// [`crate::ZKMContextBuilder::watchpoint`].
+///
+/// Byte and halfword accesses are reported as accesses to the word containing them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchEvent {
+    /// The program counter of the instruction accessing the memory.
+    pub pc: u32,
+    /// The cycle of the access.
+    pub global_clk: u64,
+    /// The address of the word.
+    pub addr: u32,
+    /// The kind of the access.
+    pub kind: MemoryAccessKind,
+    /// The value of the word after the access.
+    pub value: u32,
+    /// The value of the word before the access.
+    pub prev_value: u32,
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            MemoryAccessKind::Read => write!(
+                f,
+                "clk {} pc {:#010x}: read {:#010x} from {:#010x}",
+                self.global_clk, self.pc, self.value, self.addr
+            ),
+            MemoryAccessKind::Write => write!(
+                f,
+                "clk {} pc {:#010x}: write {:#010x} to {:#010x} (was {:#010x})",
+                self.global_clk, self.pc, self.value, self.addr, self.prev_value
+            ),
+        }
+    }
+}
+
+/// Wraps a closure into a [`BoxedWatchpoint`].
+pub fn watchpointify<'a>(f: impl FnMut(&WatchEvent) + Send + Sync + 'a) -> BoxedWatchpoint<'a> {
+    Arc::new(RwLock::new(f))
+}
+
+/// Whether the word at `addr` overlaps `range`.
+#[inline]
+pub(crate) fn word_overlaps(range: &Range<u32>, addr: u32) -> bool {
+    range.start < addr.saturating_add(4) && addr < range.end
+}
@@ -232,11 +232,9 @@ mod tests {
     use p3_koala_bear::KoalaBear;
     use p3_matrix::dense::RowMajorMatrix;
     use zkm_core_executor::{events::AluEvent, ExecutionRecord, Opcode};
-    use zkm_stark::{
-        air::MachineAir, koala_bear_poseidon2::KoalaBearPoseidon2, StarkGenericConfig,
-    };
+    use zkm_stark::air::MachineAir;
 
-    use crate::utils::{uni_stark_prove, uni_stark_verify};
+    use crate::utils::{prove_single_chip, verify_single_chip};
 
     use super::BitwiseChip;
 
@@ -257,9 +255,6 @@ mod tests {
 
     #[test]
     fn prove_koalabear() {
-        let config = KoalaBearPoseidon2::new();
-        let mut challenger = config.challenger();
-
         let mut shard = ExecutionRecord::default();
         shard.bitwise_events = [
             AluEvent::new(0, Opcode::XOR, 25, 10, 19),
@@ -269,12 +264,7 @@ mod tests {
         ]
         .repeat(1000);
         let chip = BitwiseChip::default();
-        let trace: RowMajorMatrix<KoalaBear> =
-            chip.generate_trace(&shard, &mut ExecutionRecord::default());
-        let proof =
-            uni_stark_prove::<KoalaBearPoseidon2, _>(&config, &chip, &mut challenger, trace);
-
-        let mut challenger = config.challenger();
-        uni_stark_verify(&config, &chip, &mut challenger, &proof).unwrap();
+        let proof = prove_single_chip(&chip, &shard);
+        verify_single_chip(&chip, &proof).unwrap();
     }
 }
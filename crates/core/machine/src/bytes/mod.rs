@@ -8,7 +8,12 @@ pub mod utils;
 use zkm_core_executor::{events::ByteLookupEvent, ByteOpcode};
 
 use core::borrow::BorrowMut;
-use std::marker::PhantomData;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{LazyLock, Mutex},
+};
 
 use itertools::Itertools;
 use p3_field::Field;
@@ -110,6 +115,23 @@ impl<F: Field> ByteChip<F> {
 
         initial_trace
     }
+
+    /// Returns the preprocessed byte trace, computing it only once per field.
+    ///
+    /// The trace does not depend on the program, so it is shared between the setups of all
+    /// programs.
+    pub fn cached_trace() -> RowMajorMatrix<F> {
+        static CACHE: LazyLock<Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>> =
+            LazyLock::new(Default::default);
+
+        let mut cache = CACHE.lock().unwrap();
+        cache
+            .entry(TypeId::of::<F>())
+            .or_insert_with(|| Box::new(Self::trace()))
+            .downcast_ref::<RowMajorMatrix<F>>()
+            .unwrap()
+            .clone()
+    }
 }
 
 #[cfg(test)]
@@ -125,4 +147,12 @@ mod tests {
         ByteChip::<KoalaBear>::trace();
         println!("trace and map: {:?}", start.elapsed());
     }
+
+    #[test]
+    pub fn test_cached_trace() {
+        assert_eq!(
+            ByteChip::<KoalaBear>::cached_trace().values,
+            ByteChip::<KoalaBear>::trace().values
+        );
+    }
 }
@@ -28,7 +28,7 @@ impl<F: PrimeField32> MachineAir<F> for ByteChip<F> {
     }
 
     fn generate_preprocessed_trace(&self, _program: &Self::Program) -> Option<RowMajorMatrix<F>> {
-        let trace = Self::trace();
+        let trace = Self::cached_trace();
         Some(trace)
     }
 
@@ -1,34 +1,146 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use bincode::Options;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use zkm_core_executor::ZKMReduceProof;
+use thiserror::Error;
+use zkm_core_executor::{NamedInput, ZKMReduceProof};
+#[cfg(feature = "cbor")]
+use zkm_primitives::cbor::{self, CborError, Value};
+use zkm_primitives::redact::Redacted;
 use zkm_stark::{koala_bear_poseidon2::KoalaBearPoseidon2, StarkVerifyingKey};
 
+/// The default maximum size in bytes of a serialized proof accepted from an untrusted source.
+pub const MAX_PROOF_SIZE: u64 = 1 << 30;
+
+/// The magic bytes starting a stdin encoded with [ZKMStdin::to_bytes].
+pub const STDIN_MAGIC: [u8; 4] = *b"ZKMI";
+
+/// The version of the encoding written by [ZKMStdin::to_bytes].
+pub const STDIN_VERSION: u32 = 1;
+
+/// An error returned when deserializing untrusted input.
+#[derive(Error, Debug)]
+pub enum DeserializeError {
+    #[error("payload of {size} bytes exceeds the limit of {limit} bytes")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("no more input to read at index {0}")]
+    EndOfInput(usize),
+    #[error("malformed payload: {0}")]
+    Malformed(#[from] bincode::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("unsupported encoding version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// The bincode options matching `bincode::deserialize`, with the total allocation capped at
+/// `limit` bytes.
+fn bincode_options(limit: u64) -> impl Options {
+    bincode::DefaultOptions::new().with_fixint_encoding().allow_trailing_bytes().with_limit(limit)
+}
+
+/// Deserialize a value from `bytes`, rejecting payloads larger than `limit` bytes.
+pub fn deserialize_limited<T: DeserializeOwned>(
+    bytes: &[u8],
+    limit: u64,
+) -> Result<T, DeserializeError> {
+    let size = bytes.len() as u64;
+    if size > limit {
+        return Err(DeserializeError::TooLarge { size, limit });
+    }
+    Ok(bincode_options(limit).deserialize(bytes)?)
+}
+
+/// Deserialize a value from `reader`, reading at most `limit` bytes.
+pub fn deserialize_from_limited<T: DeserializeOwned, R: Read>(
+    reader: R,
+    limit: u64,
+) -> Result<T, DeserializeError> {
+    Ok(bincode_options(limit).deserialize_from(reader)?)
+}
+
+/// Opens the file at `path` for reading, rejecting files larger than `limit` bytes.
+pub fn open_limited(
+    path: impl AsRef<Path>,
+    limit: u64,
+) -> Result<BufReader<File>, DeserializeError> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    if size > limit {
+        return Err(DeserializeError::TooLarge { size, limit });
+    }
+    Ok(BufReader::new(file))
+}
+
 /// Standard input for the prover.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+///
+/// The bincode encoding of the stdin follows its fields, so the stdins serialized before the
+/// virtual files and the named inputs are decoded with [LegacyZKMStdin]. Prefer
+/// [ZKMStdin::to_bytes], whose encoding is versioned.
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ZKMStdin {
     /// Input stored as a vec of vec of bytes. It's stored this way because the read syscall reads
     /// a vec of bytes at a time.
     pub buffer: Vec<Vec<u8>>,
     pub ptr: usize,
     pub proofs: Vec<(ZKMReduceProof<KoalaBearPoseidon2>, StarkVerifyingKey<KoalaBearPoseidon2>)>,
+    /// Virtual files indexed by their path, which the program reads with `zkm_zkvm::lib::fs`.
+    #[serde(default)]
+    pub files: BTreeMap<String, Vec<u8>>,
+    /// Inputs indexed by their name, which the program reads with `zkm_zkvm::io::read_named`.
+    #[serde(default)]
+    pub named_inputs: BTreeMap<String, NamedInput>,
+}
+
+/// The layout of [ZKMStdin] before the virtual files and the named inputs, to decode the stdins
+/// serialized by older versions.
+#[derive(Deserialize)]
+pub struct LegacyZKMStdin {
+    pub buffer: Vec<Vec<u8>>,
+    pub ptr: usize,
+    pub proofs: Vec<(ZKMReduceProof<KoalaBearPoseidon2>, StarkVerifyingKey<KoalaBearPoseidon2>)>,
+}
+
+impl From<LegacyZKMStdin> for ZKMStdin {
+    fn from(legacy: LegacyZKMStdin) -> Self {
+        Self { buffer: legacy.buffer, ptr: legacy.ptr, proofs: legacy.proofs, ..Self::new() }
+    }
 }
 
 impl ZKMStdin {
     /// Create a new `ZKMStdin`.
     pub const fn new() -> Self {
-        Self { buffer: Vec::new(), ptr: 0, proofs: Vec::new() }
+        Self {
+            buffer: Vec::new(),
+            ptr: 0,
+            proofs: Vec::new(),
+            files: BTreeMap::new(),
+            named_inputs: BTreeMap::new(),
+        }
     }
 
     /// Create a `ZKMStdin` from a slice of bytes.
     pub fn from(data: &[u8]) -> Self {
-        Self { buffer: vec![data.to_vec()], ptr: 0, proofs: Vec::new() }
+        Self { buffer: vec![data.to_vec()], ..Self::new() }
     }
 
     /// Read a value from the buffer.
     pub fn read<T: DeserializeOwned>(&mut self) -> T {
-        let result: T =
-            bincode::deserialize(&self.buffer[self.ptr]).expect("failed to deserialize");
+        self.try_read().expect("failed to deserialize")
+    }
+
+    /// Read a value from the buffer, returning an error if the buffer is exhausted or the value
+    /// is malformed.
+    pub fn try_read<T: DeserializeOwned>(&mut self) -> Result<T, DeserializeError> {
+        let bytes = self.buffer.get(self.ptr).ok_or(DeserializeError::EndOfInput(self.ptr))?;
+        let result = deserialize_limited(bytes, bytes.len() as u64)?;
         self.ptr += 1;
-        result
+        Ok(result)
     }
 
     /// Read a slice of bytes from the buffer.
@@ -60,6 +172,125 @@ impl ZKMStdin {
     ) {
         self.proofs.push((proof, vk));
     }
+
+    /// Write a virtual file at `path`, replacing the file written there if any.
+    pub fn write_file(&mut self, path: impl Into<String>, bytes: Vec<u8>) {
+        self.files.insert(path.into(), bytes);
+    }
+
+    /// Write a value under `name`, replacing the value written under it if any.
+    ///
+    /// Unlike the values written with [Self::write], which the program reads in order, the
+    /// program reads it by its name with `zkm_zkvm::io::read_named`. The execution fails with a
+    /// readable error if the program reads a name which was not written, or reads it as another
+    /// type, and the names written but not read are reported.
+    pub fn write_named<T: Serialize>(&mut self, name: impl Into<String>, data: &T) {
+        let bytes = bincode::serialize(data).expect("serialization failed");
+        let type_name = std::any::type_name::<T>().to_string();
+        self.named_inputs.insert(name.into(), NamedInput { type_name, bytes });
+    }
+
+    /// Encodes the stdin as [STDIN_MAGIC] and [STDIN_VERSION], followed by its bincode encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&STDIN_MAGIC);
+        bytes.extend_from_slice(&STDIN_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, self).expect("serialization failed");
+        bytes
+    }
+
+    /// Decodes a stdin encoded with [Self::to_bytes], rejecting payloads larger than
+    /// [MAX_PROOF_SIZE] bytes.
+    ///
+    /// A stdin serialized with bincode instead is decoded as well, including one serialized
+    /// before the virtual files and the named inputs, see [LegacyZKMStdin].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let Some(header) = bytes.strip_prefix(&STDIN_MAGIC) else {
+            return deserialize_limited::<Self>(bytes, MAX_PROOF_SIZE).or_else(|err| {
+                deserialize_limited::<LegacyZKMStdin>(bytes, MAX_PROOF_SIZE)
+                    .map(Into::into)
+                    .map_err(|_| err)
+            });
+        };
+        let (version, payload) = header
+            .split_first_chunk::<4>()
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?;
+        match u32::from_le_bytes(*version) {
+            STDIN_VERSION => deserialize_limited(payload, MAX_PROOF_SIZE),
+            version => Err(DeserializeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl ZKMStdin {
+    /// Encodes the stdin in CBOR, for hosts not written in Rust, see [zkm_primitives::cbor]:
+    ///
+    /// ```cddl
+    /// stdin = { "version": 1, "buffer": [* bstr], "files": { * tstr => bstr } }
+    /// ```
+    ///
+    /// The items of `buffer` are read by the program in order, e.g. the bincode encodings of the
+    /// values it reads with `zkm_zkvm::io::read`. Fails if the stdin holds proofs to verify,
+    /// which have no CBOR encoding, or named inputs, whose types are Rust types.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, CborError> {
+        if !self.proofs.is_empty() {
+            return Err(CborError::Unsupported("proofs"));
+        }
+        if !self.named_inputs.is_empty() {
+            return Err(CborError::Unsupported("named inputs"));
+        }
+        let buffer = self.buffer.iter().map(|item| Value::Bytes(item.clone())).collect();
+        let files = self
+            .files
+            .iter()
+            .map(|(path, bytes)| (Value::Text(path.clone()), Value::Bytes(bytes.clone())))
+            .collect();
+        Ok(cbor::to_vec(&cbor::versioned_map([
+            ("buffer", Value::Array(buffer)),
+            ("files", Value::Map(files)),
+        ])))
+    }
+
+    /// Decodes a stdin encoded with [Self::to_cbor]. The `files` key may be omitted.
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        let fields = cbor::Fields::new(cbor::from_slice(bytes)?)?;
+        let buffer = fields
+            .get("buffer")
+            .and_then(Value::as_array)
+            .ok_or(CborError::InvalidField("buffer"))?
+            .iter()
+            .map(|item| item.as_bytes().cloned().ok_or(CborError::InvalidField("buffer")))
+            .collect::<Result<_, _>>()?;
+        let files = match fields.get("files") {
+            None => BTreeMap::new(),
+            Some(files) => files
+                .as_map()
+                .ok_or(CborError::InvalidField("files"))?
+                .iter()
+                .map(|(path, bytes)| match (path.as_text(), bytes.as_bytes()) {
+                    (Some(path), Some(bytes)) => Ok((path.to_string(), bytes.clone())),
+                    _ => Err(CborError::InvalidField("files")),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Self { buffer, files, ..Self::new() })
+    }
+}
+
+impl std::fmt::Debug for ZKMStdin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZKMStdin")
+            .field(
+                "buffer",
+                &self.buffer.iter().map(|b| Redacted(b.as_slice())).collect::<Vec<_>>(),
+            )
+            .field("ptr", &self.ptr)
+            .field("proofs", &self.proofs.len())
+            .field("files", &self.files.keys().collect::<Vec<_>>())
+            .field("named_inputs", &self.named_inputs.keys().collect::<Vec<_>>())
+            .finish()
+    }
 }
 
 pub mod proof_serde {
@@ -89,8 +320,8 @@ pub mod proof_serde {
         D: Deserializer<'de>,
     {
         if deserializer.is_human_readable() {
-            let hex_bytes = String::deserialize(deserializer).unwrap();
-            let bytes = hex::decode(hex_bytes).unwrap();
+            let hex_bytes = String::deserialize(deserializer)?;
+            let bytes = hex::decode(hex_bytes).map_err(serde::de::Error::custom)?;
             let proof = bincode::deserialize(&bytes).map_err(serde::de::Error::custom)?;
             Ok(proof)
         } else {
@@ -98,3 +329,98 @@ pub mod proof_serde {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_read() {
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        stdin.write_slice(&[0xff]);
+
+        assert_eq!(stdin.try_read::<usize>().unwrap(), 10);
+        assert!(matches!(stdin.try_read::<usize>(), Err(DeserializeError::Malformed(_))));
+        stdin.ptr = 2;
+        assert!(matches!(stdin.try_read::<usize>(), Err(DeserializeError::EndOfInput(2))));
+    }
+
+    #[test]
+    fn test_deserialize_limited() {
+        let bytes = bincode::serialize(&vec![0u8; 64]).unwrap();
+        assert_eq!(deserialize_limited::<Vec<u8>>(&bytes, MAX_PROOF_SIZE).unwrap().len(), 64);
+        assert!(matches!(
+            deserialize_limited::<Vec<u8>>(&bytes, 16),
+            Err(DeserializeError::TooLarge { size: 72, limit: 16 })
+        ));
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &bytes).unwrap();
+        let reader = open_limited(file.path(), 72).unwrap();
+        assert_eq!(deserialize_from_limited::<Vec<u8>, _>(reader, 72).unwrap().len(), 64);
+        assert!(matches!(
+            open_limited(file.path(), 71),
+            Err(DeserializeError::TooLarge { size: 72, limit: 71 })
+        ));
+
+        // A length prefix claiming more data than the limit must not be allocated.
+        let bytes = u64::MAX.to_le_bytes();
+        assert!(matches!(
+            deserialize_limited::<Vec<u8>>(&bytes, MAX_PROOF_SIZE),
+            Err(DeserializeError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_stdin_bytes() {
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        stdin.write_file("input.json", b"{}".to_vec());
+        stdin.write_named("height", &7u32);
+
+        for bytes in [stdin.to_bytes(), bincode::serialize(&stdin).unwrap()] {
+            let decoded = ZKMStdin::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded.buffer, stdin.buffer);
+            assert_eq!(decoded.files, stdin.files);
+            assert_eq!(decoded.named_inputs.len(), 1);
+        }
+
+        // The layout before the virtual files and the named inputs.
+        let legacy = bincode::serialize(&(&stdin.buffer, stdin.ptr, &stdin.proofs)).unwrap();
+        let decoded = ZKMStdin::from_bytes(&legacy).unwrap();
+        assert_eq!(decoded.buffer, stdin.buffer);
+        assert!(decoded.files.is_empty() && decoded.named_inputs.is_empty());
+
+        let mut bytes = stdin.to_bytes();
+        bytes[4] = 2;
+        assert!(matches!(
+            ZKMStdin::from_bytes(&bytes),
+            Err(DeserializeError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_stdin_cbor() {
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        stdin.write_slice(&[1, 2]);
+        stdin.write_file("input.json", b"{}".to_vec());
+
+        let mut decoded = ZKMStdin::from_cbor(&stdin.to_cbor().unwrap()).unwrap();
+        assert_eq!(decoded.buffer, stdin.buffer);
+        assert_eq!(decoded.files, stdin.files);
+        assert_eq!(decoded.read::<usize>(), 10);
+
+        // A stdin without files, as encoded by a host which has none.
+        let bytes = cbor::to_vec(&cbor::versioned_map([(
+            "buffer",
+            Value::Array(vec![Value::Bytes(vec![3])]),
+        )]));
+        assert_eq!(ZKMStdin::from_cbor(&bytes).unwrap().buffer, vec![vec![3]]);
+
+        let bytes = cbor::to_vec(&cbor::versioned_map([("buffer", Value::Bytes(vec![3]))]));
+        assert!(matches!(ZKMStdin::from_cbor(&bytes), Err(CborError::InvalidField("buffer"))));
+    }
+}
@@ -33,6 +33,7 @@ pub mod shape;
 pub mod sys;
 pub mod syscall;
 pub mod utils;
+pub mod verify;
 pub use cpu::*;
 pub use mips::*;
 
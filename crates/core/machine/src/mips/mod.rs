@@ -6,11 +6,13 @@ use crate::{
         poseidon2::Poseidon2PermuteChip,
     },
 };
-use core::fmt;
+use core::{fmt, str::FromStr};
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 pub use mips_chips::*;
+use p3_air::BaseAir;
 use p3_field::PrimeField32;
+use serde::{Deserialize, Serialize};
 use strum_macros::{EnumDiscriminants, EnumIter};
 use zkm_core_executor::events::PrecompileEvent;
 use zkm_core_executor::{
@@ -39,9 +41,11 @@ pub(crate) mod mips_chips {
             chip::SyscallChip,
             instructions::SyscallInstrsChip,
             precompiles::{
+                blake3::Blake3CompressInnerChip,
                 edwards::{EdAddAssignChip, EdDecompressChip},
                 keccak_sponge::KeccakSpongeChip,
                 sha256::{ShaCompressChip, ShaExtendChip},
+                sha512::{Sha512CompressChip, Sha512ExtendChip},
                 sys_linux::SysLinuxChip,
                 u256x2048_mul::U256x2048MulChip,
                 uint256::Uint256MulChip,
@@ -145,6 +149,12 @@ pub enum MipsAir<F: PrimeField32> {
     Poseidon2Permute(Poseidon2PermuteChip),
     /// A precompile for the Keccak Sponge
     KeccakSponge(KeccakSpongeChip),
+    /// A precompile for the BLAKE3 compression function.
+    Blake3CompressInner(Blake3CompressInnerChip),
+    /// A precompile for the SHA-512 message schedule.
+    Sha512Extend(Sha512ExtendChip),
+    /// A precompile for the SHA-512 compression function.
+    Sha512Compress(Sha512CompressChip),
     /// A precompile for addition on the Elliptic curve bn254.
     Bn254Add(WeierstrassAddAssignChip<SwCurve<Bn254Parameters>>),
     /// A precompile for doubling a point on the Elliptic curve bn254.
@@ -175,6 +185,34 @@ pub enum MipsAir<F: PrimeField32> {
     SysLinux(SysLinuxChip),
 }
 
+/// The kind of shard a [MipsAir] is included in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MipsAirKind {
+    /// The program and byte AIRs, which have preprocessed traces.
+    Preprocessed,
+    /// The AIRs proving the execution of instructions.
+    Core,
+    /// The AIRs proving the global memory initialization and finalization.
+    Memory,
+    /// The AIRs proving precompile syscalls.
+    Precompile,
+}
+
+/// Static information about a [MipsAir], intended for external tooling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MipsAirInfo {
+    /// The identifier of the AIR.
+    pub id: MipsAirId,
+    /// The number of main trace columns.
+    pub width: usize,
+    /// The number of preprocessed trace columns.
+    pub preprocessed_width: usize,
+    /// The cost of a row, as used by the executor to estimate the shard size.
+    pub cost: u64,
+    /// The kind of shard the AIR is included in.
+    pub kind: MipsAirKind,
+}
+
 impl<F: PrimeField32> MipsAir<F> {
     pub fn machine<SC: StarkGenericConfig<Val = F>>(config: SC) -> StarkMachine<SC, Self> {
         let chips = Self::chips();
@@ -199,6 +237,31 @@ impl<F: PrimeField32> MipsAir<F> {
         (chips.into_iter().map(|chip| chip.into_inner()).collect(), costs)
     }
 
+    /// Get the [MipsAirInfo] of all the different MIPS AIRs, in [MipsAirId] order.
+    pub fn air_infos() -> Vec<MipsAirInfo> {
+        let core_airs = Self::get_all_core_airs();
+        let memory_airs = Self::memory_init_final_airs();
+        Self::chips()
+            .into_iter()
+            .map(|chip| {
+                let id = MipsAirId::from_str(&chip.name()).unwrap();
+                let kind = match &chip.air {
+                    Self::Program(_) | Self::ByteLookup(_) => MipsAirKind::Preprocessed,
+                    air if core_airs.contains(air) => MipsAirKind::Core,
+                    air if memory_airs.contains(air) => MipsAirKind::Memory,
+                    _ => MipsAirKind::Precompile,
+                };
+                MipsAirInfo {
+                    id,
+                    width: chip.width(),
+                    preprocessed_width: chip.preprocessed_width(),
+                    cost: chip.cost(),
+                    kind,
+                }
+            })
+            .collect()
+    }
+
     /// Get all the different MIPS chips and their costs.
     pub fn get_chips_and_costs() -> (Vec<Chip<F, Self>>, HashMap<String, u64>) {
         let mut costs: HashMap<String, u64> = HashMap::new();
@@ -436,6 +499,19 @@ impl<F: PrimeField32> MipsAir<F> {
         costs.insert(movcond_instrs.name(), movcond_instrs.cost());
         chips.push(movcond_instrs);
 
+        let blake3_compress_inner =
+            Chip::new(MipsAir::Blake3CompressInner(Blake3CompressInnerChip::default()));
+        costs.insert(blake3_compress_inner.name(), 56 * blake3_compress_inner.cost());
+        chips.push(blake3_compress_inner);
+
+        let sha512_extend = Chip::new(MipsAir::Sha512Extend(Sha512ExtendChip::default()));
+        costs.insert(sha512_extend.name(), 64 * sha512_extend.cost());
+        chips.push(sha512_extend);
+
+        let sha512_compress = Chip::new(MipsAir::Sha512Compress(Sha512CompressChip::default()));
+        costs.insert(sha512_compress.name(), 96 * sha512_compress.cost());
+        chips.push(sha512_compress);
+
         (chips, costs)
     }
 
@@ -582,6 +658,9 @@ impl<F: PrimeField32> MipsAir<F> {
             Self::Sha256Compress(_) => 80,
             Self::Sha256Extend(_) => 48,
             Self::KeccakSponge(_) => 24,
+            Self::Blake3CompressInner(_) => 56,
+            Self::Sha512Extend(_) => 64,
+            Self::Sha512Compress(_) => 96,
             _ => 1,
         }
     }
@@ -632,6 +711,9 @@ impl<F: PrimeField32> MipsAir<F> {
             Self::Bls12381Fp2AddSub(_) => SyscallCode::BLS12381_FP2_ADD,
             Self::Poseidon2Permute(_) => SyscallCode::POSEIDON2_PERMUTE,
             Self::KeccakSponge(_) => SyscallCode::KECCAK_SPONGE,
+            Self::Blake3CompressInner(_) => SyscallCode::BLAKE3_COMPRESS_INNER,
+            Self::Sha512Extend(_) => SyscallCode::SHA512_EXTEND,
+            Self::Sha512Compress(_) => SyscallCode::SHA512_COMPRESS,
             Self::SysLinux(_) => SyscallCode::SYS_LINUX,
             Self::Add(_) => unreachable!("Invalid for core chip"),
             Self::Bitwise(_) => unreachable!("Invalid for core chip"),
@@ -691,9 +773,9 @@ pub mod tests {
     };
     use crate::{
         io::ZKMStdin,
-        mips::MipsAir,
+        mips::{MipsAir, MipsAirKind},
         utils,
-        utils::{prove, run_test, setup_logger},
+        utils::{debug_dumped_shard, prove, run_test, setup_logger},
     };
 
     use hashbrown::HashMap;
@@ -701,7 +783,7 @@ pub mod tests {
     use p3_koala_bear::KoalaBear;
     use strum::IntoEnumIterator;
 
-    use zkm_core_executor::{Instruction, MipsAirId, Opcode, Program};
+    use zkm_core_executor::{Executor, Instruction, MipsAirId, Opcode, Program};
     use zkm_stark::air::MachineAir;
     use zkm_stark::{
         koala_bear_poseidon2::KoalaBearPoseidon2, CpuProver, StarkProvingKey, StarkVerifyingKey,
@@ -716,6 +798,91 @@ pub mod tests {
         }
     }
 
+    /// The order and discriminants of the AIRs determine the shapes and the vk map, so they must
+    /// not change accidentally.
+    #[test]
+    fn test_mips_air_id_snapshot() {
+        let ids = MipsAirId::iter().map(|id| (id as usize, id.to_string())).collect::<Vec<_>>();
+        let expected = [
+            (0, "Cpu"),
+            (1, "Program"),
+            (2, "ShaExtend"),
+            (3, "ShaCompress"),
+            (4, "EdAddAssign"),
+            (5, "EdDecompress"),
+            (6, "Secp256k1Decompress"),
+            (7, "Secp256k1AddAssign"),
+            (8, "Secp256k1DoubleAssign"),
+            (9, "Secp256r1Decompress"),
+            (10, "Secp256r1AddAssign"),
+            (11, "Secp256r1DoubleAssign"),
+            (46, "Poseidon2Permute"),
+            (48, "KeccakSponge"),
+            (13, "Bn254AddAssign"),
+            (14, "Bn254DoubleAssign"),
+            (15, "Bls12381AddAssign"),
+            (16, "Bls12381DoubleAssign"),
+            (17, "Uint256MulMod"),
+            (18, "U256XU2048Mul"),
+            (19, "Bls12381FpOpAssign"),
+            (20, "Bls12831Fp2AddSubAssign"),
+            (21, "Bls12831Fp2MulAssign"),
+            (22, "Bn254FpOpAssign"),
+            (23, "Bn254Fp2AddSubAssign"),
+            (24, "Bn254Fp2MulAssign"),
+            (25, "Bls12381Decompress"),
+            (26, "SyscallCore"),
+            (27, "SyscallPrecompile"),
+            (28, "DivRem"),
+            (29, "AddSub"),
+            (30, "Bitwise"),
+            (31, "Mul"),
+            (32, "ShiftRight"),
+            (33, "ShiftLeft"),
+            (34, "Lt"),
+            (35, "CloClz"),
+            (36, "Branch"),
+            (37, "Jump"),
+            (38, "SyscallInstrs"),
+            (39, "MemoryInstrs"),
+            (40, "MiscInstrs"),
+            (41, "MemoryGlobalInit"),
+            (42, "MemoryGlobalFinalize"),
+            (43, "MemoryLocal"),
+            (44, "Global"),
+            (45, "Byte"),
+            (47, "SysLinux"),
+            (49, "MovCond"),
+            (50, "Blake3CompressInner"),
+            (51, "Sha512Extend"),
+            (52, "Sha512Compress"),
+        ]
+        .map(|(discriminant, name)| (discriminant, name.to_string()));
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_air_infos() {
+        let infos = MipsAir::<KoalaBear>::air_infos();
+        assert_eq!(
+            infos.iter().map(|info| info.id).collect::<Vec<_>>(),
+            MipsAirId::iter().collect::<Vec<_>>()
+        );
+
+        let costs = MipsAir::<KoalaBear>::costs();
+        for info in &infos {
+            assert_eq!(info.cost, costs[info.id.as_str()]);
+        }
+
+        let kind = |id| infos.iter().find(|info| info.id == id).unwrap().kind;
+        assert_eq!(kind(MipsAirId::Byte), MipsAirKind::Preprocessed);
+        assert_eq!(kind(MipsAirId::Cpu), MipsAirKind::Core);
+        assert_eq!(kind(MipsAirId::Global), MipsAirKind::Core);
+        assert_eq!(kind(MipsAirId::MemoryGlobalInit), MipsAirKind::Memory);
+        assert_eq!(kind(MipsAirId::KeccakSponge), MipsAirKind::Precompile);
+        assert_eq!(kind(MipsAirId::SyscallPrecompile), MipsAirKind::Precompile);
+    }
+
     #[test]
     fn core_air_cost_consistency() {
         let file = std::fs::File::open("../executor/src/artifacts/mips_costs.json").unwrap();
@@ -1067,6 +1234,22 @@ pub mod tests {
         run_test::<CpuProver<_, _>>(program).unwrap();
     }
 
+    #[test]
+    fn test_fibonacci_trace_dump() {
+        setup_logger();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fibonacci.trace");
+
+        let mut opts = ZKMCoreOpts::default();
+        opts.shard_size = 1024;
+        let mut runtime = Executor::new(fibonacci_program(), opts);
+        let num_shards = runtime.run_with_trace_dump(&path).unwrap();
+        assert!(num_shards > 1);
+
+        debug_dumped_shard(&path, 2).unwrap();
+        assert!(debug_dumped_shard(&path, num_shards + 1).is_err());
+    }
+
     #[test]
     fn test_max_memory_prove_simple() {
         setup_logger();
@@ -0,0 +1,130 @@
+use p3_field::{Field, FieldAlgebra};
+use zkm_core_executor::{
+    events::{ByteLookupEvent, ByteRecord},
+    ByteOpcode,
+};
+use zkm_derive::AlignedBorrow;
+use zkm_stark::{air::ZKMAirBuilder, Word};
+
+use crate::bytes::utils::shr_carry;
+
+/// A set of columns needed to compute `>>` of a 64-bit value with a fixed offset R. The value is
+/// given as its low and high words.
+///
+/// Note that we decompose shifts into a byte shift and a bit shift.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct FixedShiftRightU64Operation<T> {
+    /// The output value.
+    pub value: [Word<T>; 2],
+
+    /// The shift output of `shrcarry` on each byte of the value.
+    pub shift: [T; 8],
+
+    /// The carry output of `shrcarry` on each byte of the value.
+    pub carry: [T; 8],
+}
+
+impl<F: Field> FixedShiftRightU64Operation<F> {
+    pub const fn nb_bytes_to_shift(rotation: usize) -> usize {
+        rotation / 8
+    }
+
+    pub const fn nb_bits_to_shift(rotation: usize) -> usize {
+        rotation % 8
+    }
+
+    pub const fn carry_multiplier(rotation: usize) -> u32 {
+        let nb_bits_to_shift = Self::nb_bits_to_shift(rotation);
+        1 << (8 - nb_bits_to_shift)
+    }
+
+    pub fn populate(&mut self, record: &mut impl ByteRecord, input: u64, rotation: usize) -> u64 {
+        let input_bytes = input.to_le_bytes();
+        let expected = input >> rotation;
+
+        // Compute some constants with respect to the rotation needed for the rotation.
+        let nb_bytes_to_shift = Self::nb_bytes_to_shift(rotation);
+        let nb_bits_to_shift = Self::nb_bits_to_shift(rotation);
+        let carry_multiplier = F::from_canonical_u32(Self::carry_multiplier(rotation));
+
+        // For each byte of the byte shifted input, calculate the shift and carry. If it's not the
+        // first byte, calculate the new byte value using the current shifted byte and the last
+        // carry.
+        let mut value = [F::ZERO; 8];
+        let mut last_carry = F::ZERO;
+        for i in (0..8).rev() {
+            let b = if i + nb_bytes_to_shift < 8 { input_bytes[i + nb_bytes_to_shift] } else { 0 };
+            let c = nb_bits_to_shift as u8;
+
+            let (shift, carry) = shr_carry(b, c);
+
+            let byte_event =
+                ByteLookupEvent { opcode: ByteOpcode::ShrCarry, a1: shift as u16, a2: carry, b, c };
+            record.add_byte_lookup_event(byte_event);
+
+            self.shift[i] = F::from_canonical_u8(shift);
+            self.carry[i] = F::from_canonical_u8(carry);
+
+            // For the first byte, we don't move over the carry as this is a shift, not a rotate.
+            value[i] =
+                if i == 7 { self.shift[i] } else { self.shift[i] + last_carry * carry_multiplier };
+
+            last_carry = self.carry[i];
+        }
+        self.value = [
+            Word([value[0], value[1], value[2], value[3]]),
+            Word([value[4], value[5], value[6], value[7]]),
+        ];
+
+        // Assert the answer is correct.
+        assert_eq!(self.value[0].to_u32() as u64 | (self.value[1].to_u32() as u64) << 32, expected);
+
+        expected
+    }
+
+    pub fn eval<AB: ZKMAirBuilder>(
+        builder: &mut AB,
+        input: [Word<AB::Var>; 2],
+        rotation: usize,
+        cols: FixedShiftRightU64Operation<AB::Var>,
+        is_real: AB::Expr,
+    ) {
+        // Compute some constants with respect to the rotation needed for the rotation.
+        let nb_bytes_to_shift = Self::nb_bytes_to_shift(rotation);
+        let nb_bits_to_shift = Self::nb_bits_to_shift(rotation);
+        let carry_multiplier = AB::F::from_canonical_u32(Self::carry_multiplier(rotation));
+
+        let byte = |word: &[Word<AB::Var>; 2], i: usize| word[i / 4][i % 4];
+
+        // For each byte of the byte shifted input, calculate the shift and carry. If it's not the
+        // first byte, calculate the new byte value using the current shifted byte and the last
+        // carry.
+        let mut last_carry = AB::Expr::zero();
+        for i in (0..8).rev() {
+            let input_byte: AB::Expr = if i + nb_bytes_to_shift < 8 {
+                byte(&input, i + nb_bytes_to_shift).into()
+            } else {
+                AB::Expr::zero()
+            };
+            builder.send_byte_pair(
+                AB::F::from_canonical_u32(ByteOpcode::ShrCarry as u32),
+                cols.shift[i],
+                cols.carry[i],
+                input_byte,
+                AB::F::from_canonical_usize(nb_bits_to_shift),
+                is_real.clone(),
+            );
+
+            // For the first byte, we don't move over the carry as this is a shift, not a rotate.
+            if i == 7 {
+                builder.assert_eq(byte(&cols.value, i), cols.shift[i]);
+            } else {
+                builder
+                    .assert_eq(byte(&cols.value, i), cols.shift[i] + last_carry * carry_multiplier);
+            }
+
+            last_carry = cols.carry[i].into();
+        }
+    }
+}
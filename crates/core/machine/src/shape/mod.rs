@@ -9,7 +9,7 @@ use p3_koala_bear::KoalaBear;
 use p3_util::log2_ceil_usize;
 use thiserror::Error;
 
-use zkm_core_executor::{ExecutionRecord, MipsAirId, Program};
+use zkm_core_executor::{ExecutionRecord, ExecutionReport, MipsAirId, Program};
 use zkm_stark::{
     air::MachineAir,
     shape::{OrderedShape, Shape, ShapeCluster},
@@ -35,6 +35,13 @@ const MAXIMAL_SHAPES: &[u8] = include_bytes!("maximal_shapes.json");
 /// These shapes are used to optimize performance for smaller programs.
 const SMALL_SHAPES: &[u8] = include_bytes!("small_shapes.json");
 
+/// The program chip log2 heights allowed by the tiny program profile, below the smallest height
+/// of the default shapes.
+const TINY_PROGRAM_LOG2_HEIGHTS: std::ops::RangeInclusive<usize> = 10..=18;
+
+/// The smallest log2 height of a chip in the tiny program profile.
+const TINY_MIN_LOG2_HEIGHT: usize = 4;
+
 /// A configuration for what shapes are allowed to be used by the prover.
 #[derive(Debug)]
 pub struct CoreShapeConfig<F: PrimeField32> {
@@ -43,10 +50,47 @@ pub struct CoreShapeConfig<F: PrimeField32> {
     partial_memory_shapes: ShapeCluster<MipsAirId>,
     partial_precompile_shapes: HashMap<MipsAir<F>, (usize, Vec<usize>)>,
     partial_small_shapes: Vec<ShapeCluster<MipsAirId>>,
+    partial_tiny_shapes: Vec<ShapeCluster<MipsAirId>>,
     costs: HashMap<MipsAirId, usize>,
 }
 
 impl<F: PrimeField32> CoreShapeConfig<F> {
+    /// Enables the tiny program profile, for hello-world-scale programs proven in a single shard.
+    ///
+    /// The program chip may be as small as `2^10` rows instead of `2^19`, and the chips of a shard
+    /// packing the core and memory events may be shorter than in the small shapes, or omitted when
+    /// they have no events. The byte chip keeps its `2^16` rows, which are fixed by its table.
+    ///
+    /// These shapes are not part of the allowed recursion verification keys, so their proofs can
+    /// only be compressed with vk verification disabled.
+    pub fn with_tiny_program_profile(mut self) -> Self {
+        let mut preprocessed_log2_heights = self
+            .partial_preprocessed_shapes
+            .iter()
+            .map(|(air, heights)| (*air, heights.clone()))
+            .collect::<HashMap<_, _>>();
+        let program_log2_heights = preprocessed_log2_heights.get_mut(&MipsAirId::Program).unwrap();
+        let default_heights = std::mem::take(program_log2_heights);
+        *program_log2_heights =
+            TINY_PROGRAM_LOG2_HEIGHTS.map(Some).chain(default_heights).collect();
+        self.partial_preprocessed_shapes = ShapeCluster::new(preprocessed_log2_heights);
+
+        self.partial_tiny_shapes = self
+            .partial_small_shapes
+            .iter()
+            .map(|cluster| {
+                let log2_heights = cluster.iter().map(|(air, heights)| {
+                    let max_log2_height = heights.iter().flatten().max().copied().unwrap_or(0);
+                    let tiny_heights = (TINY_MIN_LOG2_HEIGHT..=max_log2_height).map(Some);
+                    (*air, std::iter::once(None).chain(tiny_heights).collect())
+                });
+                ShapeCluster::new(log2_heights.collect())
+            })
+            .collect();
+
+        self
+    }
+
     /// Fix the preprocessed shape of the proof.
     pub fn fix_preprocessed_shape(&self, program: &mut Program) -> Result<(), CoreShapeError> {
         // If the preprocessed shape is already fixed, return an error.
@@ -94,7 +138,8 @@ impl<F: PrimeField32> CoreShapeConfig<F> {
             let mut minimal_shape = None;
             let mut minimal_area = usize::MAX;
             let mut minimal_cluster = None;
-            for (i, cluster) in self.partial_small_shapes.iter().enumerate() {
+            let clusters = self.partial_tiny_shapes.iter().chain(&self.partial_small_shapes);
+            for (i, cluster) in clusters.enumerate() {
                 if let Some(shape) = cluster.find_shape(&heights) {
                     if self.estimate_lde_size(&shape) < minimal_area {
                         minimal_area = self.estimate_lde_size(&shape);
@@ -141,24 +186,8 @@ impl<F: PrimeField32> CoreShapeConfig<F> {
 
             // Try to find the smallest shape fitting within at least one of the candidate shapes.
             let log2_shard_size = record.cpu_events.len().next_power_of_two().ilog2() as usize;
-            let mut minimal_shape = None;
-            let mut minimal_area = usize::MAX;
-            let mut minimal_cluster = None;
-            for (_, clusters) in self.partial_core_shapes.range(log2_shard_size..) {
-                for (i, cluster) in clusters.iter().enumerate() {
-                    if let Some(shape) = cluster.find_shape(&heights) {
-                        if self.estimate_lde_size(&shape) < minimal_area {
-                            minimal_area = self.estimate_lde_size(&shape);
-                            minimal_shape = Some(shape.clone());
-                            minimal_cluster = Some(i);
-                        }
-                    }
-                }
-            }
-
-            if let Some(shape) = minimal_shape {
+            if let Some((cluster, shape)) = self.find_core_shape(&heights, log2_shard_size) {
                 let shard = record.public_values.shard;
-                let cluster = minimal_cluster.unwrap();
                 tracing::info!("Shard Lifted: Index={}, Cluster={}", shard, cluster);
 
                 for (air, height) in heights.iter() {
@@ -235,6 +264,108 @@ impl<F: PrimeField32> CoreShapeConfig<F> {
         Err(CoreShapeError::PrecompileNotIncluded(record.stats()))
     }
 
+    /// Finds the smallest core shape fitting `heights`, among the clusters for shards of at least
+    /// `2^log2_shard_size` cycles, with the index of its cluster.
+    fn find_core_shape(
+        &self,
+        heights: &[(MipsAirId, usize)],
+        log2_shard_size: usize,
+    ) -> Option<(usize, Shape<MipsAirId>)> {
+        let mut minimal_shape = None;
+        let mut minimal_area = usize::MAX;
+        for (_, clusters) in self.partial_core_shapes.range(log2_shard_size..) {
+            for (i, cluster) in clusters.iter().enumerate() {
+                if let Some(shape) = cluster.find_shape(heights) {
+                    if self.estimate_lde_size(&shape) < minimal_area {
+                        minimal_area = self.estimate_lde_size(&shape);
+                        minimal_shape = Some((i, shape));
+                    }
+                }
+            }
+        }
+        minimal_shape
+    }
+
+    /// Predicts the shape [Self::fix_shape] gives a core shard of a program with the given
+    /// preprocessed shape, from estimated heights of the chips in the shard.
+    ///
+    /// Heights of chips which are not core chips are ignored, and missing heights of core chips
+    /// are taken to be zero.
+    pub fn predict_core_shape(
+        &self,
+        preprocessed_shape: &Shape<MipsAirId>,
+        heights: &[(MipsAirId, usize)],
+    ) -> Option<OrderedShape> {
+        let core_cluster = self.partial_core_shapes.values().flatten().next()?;
+        let heights = core_cluster
+            .iter()
+            .map(|(air, _)| {
+                let height = heights.iter().find(|(a, _)| a == air).map_or(0, |(_, h)| *h);
+                (*air, height)
+            })
+            .collect::<Vec<_>>();
+        let cpu_height = heights.iter().find(|(air, _)| *air == MipsAirId::Cpu)?.1;
+        let log2_shard_size = cpu_height.next_power_of_two().ilog2() as usize;
+        let (_, shape) = self.find_core_shape(&heights, log2_shard_size)?;
+        Some(
+            preprocessed_shape
+                .iter()
+                .chain(shape.iter())
+                .map(|(air, log2_height)| (air.to_string(), *log2_height))
+                .collect(),
+        )
+    }
+
+    /// Reports, for every shard of `report`, the core cluster which its core chips fit best and
+    /// the heights which do not fit it, to triage shards which [Self::fix_shape] rejects.
+    ///
+    /// The heights of the shards are the event counts estimated with cost estimation enabled in
+    /// the [`zkm_core_executor::ZKMContext`], so the report is empty without them. The closest
+    /// cluster is the one with the fewest missing heights, and then the smallest excess of log2
+    /// heights over the allowed ones.
+    pub fn shape_coverage(&self, report: &ExecutionReport) -> Vec<ShapeCoverage> {
+        let Some(core_cluster) = self.partial_core_shapes.values().flatten().next() else {
+            return Vec::new();
+        };
+        report
+            .shard_event_counts
+            .iter()
+            .enumerate()
+            .filter_map(|(shard, counts)| {
+                let heights = core_cluster
+                    .iter()
+                    .map(|(air, _)| (*air, counts[*air] as usize))
+                    .filter(|(_, height)| *height > 0)
+                    .collect::<Vec<_>>();
+                self.partial_core_shapes
+                    .iter()
+                    .flat_map(|(log2_shard_size, clusters)| {
+                        clusters.iter().enumerate().map(|(cluster, partial_shape)| {
+                            let missing = heights
+                                .iter()
+                                .filter_map(|(air, height)| {
+                                    let log2_height = log2_ceil_usize(*height);
+                                    let max_log2_height = partial_shape.max_log2_height(air);
+                                    (max_log2_height < Some(log2_height)).then_some(MissingHeight {
+                                        air: *air,
+                                        log2_height,
+                                        max_log2_height,
+                                    })
+                                })
+                                .collect();
+                            ShapeCoverage {
+                                shard,
+                                log2_shard_size: *log2_shard_size,
+                                cluster,
+                                missing,
+                            }
+                        })
+                    })
+                    .min_by_key(|coverage| (coverage.missing.len(), coverage.excess()))
+            })
+            .collect()
+    }
+
     fn get_precompile_shapes(
         &self,
         air: &MipsAir<F>,
@@ -514,6 +645,7 @@ impl<F: PrimeField32> Default for CoreShapeConfig<F> {
                     ShapeCluster::new(x.into_iter().map(|(k, v)| (k, vec![Some(v)])).collect())
                 })
                 .collect(),
+            partial_tiny_shapes: vec![],
             costs: serde_json::from_str(include_str!(
                 "../../../executor/src/artifacts/mips_costs.json"
             ))
@@ -599,6 +731,60 @@ fn derive_cluster_from_maximal_shape(shape: &Shape<MipsAirId>) -> ShapeCluster<M
     ShapeCluster::new(maybe_log2_heights)
 }
 
+/// The core cluster which the heights of a shard fit best, see [CoreShapeConfig::shape_coverage].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeCoverage {
+    /// The index of the shard in the execution.
+    pub shard: usize,
+    /// The log2 shard size of the cluster.
+    pub log2_shard_size: usize,
+    /// The index of the cluster among the clusters of its shard size.
+    pub cluster: usize,
+    /// The heights of the shard which do not fit the cluster.
+    pub missing: Vec<MissingHeight>,
+}
+
+impl ShapeCoverage {
+    /// Whether the shard fits the cluster.
+    pub fn fits(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// The sum of the log2 heights missing from the cluster.
+    fn excess(&self) -> usize {
+        self.missing
+            .iter()
+            .map(|missing| missing.log2_height - missing.max_log2_height.unwrap_or(0))
+            .sum()
+    }
+}
+
+impl std::fmt::Display for ShapeCoverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "shard {}: cluster {} of 2^{}", self.shard, self.cluster, self.log2_shard_size)?;
+        if self.fits() {
+            return write!(f, " fits");
+        }
+        let missing = self.missing.iter().map(|missing| match missing.max_log2_height {
+            Some(max) => format!("{} needs 2^{} > 2^{max}", missing.air, missing.log2_height),
+            None => format!("{} needs 2^{} but must be empty", missing.air, missing.log2_height),
+        });
+        write!(f, " misses {}", missing.format(", "))
+    }
+}
+
+/// A chip height of a shard which does not fit the cluster of a [ShapeCoverage].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingHeight {
+    /// The chip.
+    pub air: MipsAirId,
+    /// The log2 height the shard needs for the chip.
+    pub log2_height: usize,
+    /// The largest log2 height the cluster allows for the chip, or `None` if the chip must be
+    /// empty.
+    pub max_log2_height: Option<usize>,
+}
+
 #[derive(Debug, Error)]
 pub enum CoreShapeError {
     #[error("no preprocessed shape found")]
@@ -675,6 +861,77 @@ pub mod tests {
         println!("There are {num_shapes} core shapes");
     }
 
+    #[test]
+    fn test_tiny_program_profile() {
+        use p3_koala_bear::KoalaBear;
+
+        use zkm_core_executor::{Instruction, Opcode};
+
+        let instruction = Instruction::new(Opcode::ADD, 29, 0, 5, false, true);
+        let tiny_program = || Program::new(vec![instruction; 3000], 1 << 5, 1 << 5);
+
+        let mut program = tiny_program();
+        CoreShapeConfig::<KoalaBear>::default().fix_preprocessed_shape(&mut program).unwrap();
+        let shape = program.preprocessed_shape.unwrap();
+        assert_eq!(shape.log2_height(&MipsAirId::Program), Some(19));
+
+        let mut program = tiny_program();
+        let config = CoreShapeConfig::<KoalaBear>::default().with_tiny_program_profile();
+        config.fix_preprocessed_shape(&mut program).unwrap();
+        let shape = program.preprocessed_shape.unwrap();
+        assert_eq!(shape.log2_height(&MipsAirId::Program), Some(12));
+        assert_eq!(shape.log2_height(&MipsAirId::Byte), Some(16));
+
+        let heights = [(MipsAirId::Cpu, 100), (MipsAirId::AddSub, 20), (MipsAirId::Mul, 0)];
+        let shape = config.partial_tiny_shapes[0].find_shape(&heights).unwrap();
+        assert_eq!(shape.log2_height(&MipsAirId::Cpu), Some(7));
+        assert_eq!(shape.log2_height(&MipsAirId::AddSub), Some(5));
+        assert!(!shape.contains(&MipsAirId::Mul));
+    }
+
+    #[test]
+    fn test_predict_core_shape() {
+        use p3_koala_bear::KoalaBear;
+
+        let config = CoreShapeConfig::<KoalaBear>::default();
+        let preprocessed_shape =
+            Shape::from_log2_heights(&[(MipsAirId::Program, 19), (MipsAirId::Byte, 16)]);
+        let heights = [
+            (MipsAirId::Cpu, 1 << 20),
+            (MipsAirId::AddSub, 1 << 19),
+            (MipsAirId::ShaExtend, 1 << 10),
+        ];
+        let shape = config.predict_core_shape(&preprocessed_shape, &heights).unwrap();
+        let log2_height = |name: &str| shape.inner.iter().find(|(air, _)| air == name).map(|s| s.1);
+        assert_eq!(log2_height("Program"), Some(19));
+        assert!(log2_height("Cpu").unwrap() >= 20);
+        assert!(log2_height("AddSub").unwrap() >= 19);
+        assert_eq!(log2_height("ShaExtend"), None);
+    }
+
+    #[test]
+    fn test_shape_coverage() {
+        use p3_koala_bear::KoalaBear;
+
+        let config = CoreShapeConfig::<KoalaBear>::default();
+        let mut report = ExecutionReport::default();
+        report.shard_event_counts = vec![Default::default(); 2];
+        for counts in report.shard_event_counts.iter_mut() {
+            counts[MipsAirId::Cpu] = 1 << 16;
+            counts[MipsAirId::AddSub] = 1 << 15;
+        }
+        report.shard_event_counts[1][MipsAirId::AddSub] = 1 << 30;
+
+        let coverage = config.shape_coverage(&report);
+        assert_eq!(coverage.len(), 2);
+        assert!(coverage[0].fits());
+        assert_eq!(coverage[1].shard, 1);
+        assert_eq!(coverage[1].missing.len(), 1);
+        assert_eq!(coverage[1].missing[0].air, MipsAirId::AddSub);
+        assert_eq!(coverage[1].missing[0].log2_height, 30);
+        assert!(coverage[1].to_string().contains("AddSub needs 2^30"));
+    }
+
     #[test]
     fn test_dummy_record() {
         use crate::utils::setup_logger;
@@ -13,3 +13,51 @@ impl<F> BaseAir<F> for SyscallInstrsChip {
         NUM_SYSCALL_INSTR_COLS
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use zkm_core_executor::{Instruction, Opcode, Program, Register, ZKMContext};
+    use zkm_stark::{
+        koala_bear_poseidon2::KoalaBearPoseidon2, CpuProver, MachineProver, StarkGenericConfig,
+        ZKMCoreOpts,
+    };
+
+    use crate::{
+        io::ZKMStdin,
+        mips::MipsAir,
+        shape::CoreShapeConfig,
+        utils::{prove_with_context, setup_logger},
+    };
+
+    #[test]
+    fn prove_user_syscall() {
+        setup_logger();
+        let instructions = vec![
+            Instruction::new(Opcode::ADD, Register::V0 as u8, 0, 0xC0, false, true),
+            Instruction::new(Opcode::ADD, Register::A0 as u8, 0, 0x200, false, true),
+            Instruction::new(Opcode::ADD, Register::A1 as u8, 0, 4, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+        ];
+        let mut program = Program::new(instructions, 0, 0);
+        let shape_config = CoreShapeConfig::default();
+        shape_config.fix_preprocessed_shape(&mut program).unwrap();
+
+        let context =
+            ZKMContext::builder().with_syscall_handler(0xC0..0xC1, |_, _| vec![vec![1]]).build();
+        let prover = CpuProver::new(MipsAir::machine(KoalaBearPoseidon2::new()));
+        let (pk, vk) = prover.setup(&program);
+        let (proof, _, _) = prove_with_context(
+            &prover,
+            &pk,
+            program,
+            &ZKMStdin::new(),
+            ZKMCoreOpts::default(),
+            context,
+            Some(&shape_config),
+        )
+        .unwrap();
+
+        let mut challenger = prover.config().challenger();
+        prover.machine().verify(&vk, &proof, &mut challenger).unwrap();
+    }
+}
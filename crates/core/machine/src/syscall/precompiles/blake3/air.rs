@@ -0,0 +1,310 @@
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::FieldAlgebra;
+use p3_matrix::Matrix;
+use zkm_core_executor::syscalls::SyscallCode;
+use zkm_stark::air::{LookupScope, ZKMAirBuilder};
+
+use super::{
+    columns::{Blake3CompressInnerCols, NUM_BLAKE3_COMPRESS_INNER_COLS},
+    Blake3CompressInnerChip, BLAKE3_G_INDEX, BLAKE3_MSG_SCHEDULE,
+};
+use crate::{
+    air::{MemoryAirBuilder, WordAirBuilder},
+    memory::MemoryCols,
+    operations::{AddOperation, FixedRotateRightOperation, XorOperation},
+};
+use zkm_stark::air::BaseAirBuilder;
+
+impl<F> BaseAir<F> for Blake3CompressInnerChip {
+    fn width(&self) -> usize {
+        NUM_BLAKE3_COMPRESS_INNER_COLS
+    }
+}
+
+impl<AB> Air<AB> for Blake3CompressInnerChip
+where
+    AB: ZKMAirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &Blake3CompressInnerCols<AB::Var> = (*local).borrow();
+        let next: &Blake3CompressInnerCols<AB::Var> = (*next).borrow();
+
+        self.eval_control_flow_flags(builder, local, next);
+
+        self.eval_memory(builder, local);
+
+        self.eval_g(builder, local);
+
+        builder.assert_eq(local.start, local.is_real * local.g_index[0] * local.round[0]);
+        builder.receive_syscall(
+            local.shard,
+            local.clk,
+            AB::F::from_canonical_u32(SyscallCode::BLAKE3_COMPRESS_INNER.syscall_id()),
+            local.state_ptr,
+            local.msg_ptr,
+            local.start,
+            LookupScope::Local,
+        );
+    }
+}
+
+impl Blake3CompressInnerChip {
+    fn eval_control_flow_flags<AB: ZKMAirBuilder>(
+        &self,
+        builder: &mut AB,
+        local: &Blake3CompressInnerCols<AB::Var>,
+        next: &Blake3CompressInnerCols<AB::Var>,
+    ) {
+        // Verify that exactly one of the g_index columns is true.
+        let mut g_index_sum = AB::Expr::zero();
+        for i in 0..8 {
+            builder.assert_bool(local.g_index[i]);
+            g_index_sum = g_index_sum.clone() + local.g_index[i].into();
+        }
+        builder.assert_one(g_index_sum);
+
+        // Verify that exactly one of the round columns is true.
+        let mut round_sum = AB::Expr::zero();
+        for i in 0..7 {
+            builder.assert_bool(local.round[i]);
+            round_sum = round_sum.clone() + local.round[i].into();
+        }
+        builder.assert_one(round_sum);
+
+        // The first row is the first G function of the first round.
+        builder.when_first_row().assert_one(local.g_index[0]);
+        builder.when_first_row().assert_one(local.round[0]);
+
+        // The G function rotates by 1 on every row.
+        for i in 0..8 {
+            builder.when_transition().when(local.g_index[i]).assert_one(next.g_index[(i + 1) % 8]);
+        }
+
+        // The round stays the same, except after the last G function where it rotates by 1.
+        for i in 0..7 {
+            builder
+                .when_transition()
+                .when_not(local.g_index[7])
+                .assert_eq(local.round[i], next.round[i]);
+            builder
+                .when_transition()
+                .when(local.g_index[7])
+                .assert_eq(local.round[i], next.round[(i + 1) % 7]);
+        }
+
+        builder.assert_eq(local.is_last_row, local.g_index[7] * local.round[6]);
+
+        // If this row is real and not the last one of the syscall, then next row should have the
+        // same inputs.
+        builder
+            .when_transition()
+            .when(local.is_real)
+            .when_not(local.is_last_row)
+            .assert_eq(local.shard, next.shard);
+        builder
+            .when_transition()
+            .when(local.is_real)
+            .when_not(local.is_last_row)
+            .assert_eq(local.clk, next.clk);
+        builder
+            .when_transition()
+            .when(local.is_real)
+            .when_not(local.is_last_row)
+            .assert_eq(local.state_ptr, next.state_ptr);
+        builder
+            .when_transition()
+            .when(local.is_real)
+            .when_not(local.is_last_row)
+            .assert_eq(local.msg_ptr, next.msg_ptr);
+
+        // Assert that is_real is a bool.
+        builder.assert_bool(local.is_real);
+
+        // If this row is real and not the last one of the syscall, then next row should also be
+        // real.
+        builder
+            .when_transition()
+            .when(local.is_real)
+            .when_not(local.is_last_row)
+            .assert_one(next.is_real);
+
+        // Once the is_real flag is changed to false, it should not be changed back.
+        builder.when_transition().when_not(local.is_real).assert_zero(next.is_real);
+
+        // Assert that the table ends in nonreal columns. Since each syscall is 56 cycles and the
+        // table is padded to a power of 2, the last row of the table should always be padding.
+        builder.when_last_row().assert_zero(local.is_real);
+    }
+
+    /// Constrains that the memory addresses are correct and that memory is correctly written/read.
+    fn eval_memory<AB: ZKMAirBuilder>(
+        &self,
+        builder: &mut AB,
+        local: &Blake3CompressInnerCols<AB::Var>,
+    ) {
+        // Every G function is computed in its own cycle.
+        let mut cycle = AB::Expr::zero();
+        for i in 0..7 {
+            cycle = cycle.clone() + local.round[i] * AB::Expr::from_canonical_usize(8 * i);
+        }
+        for i in 0..8 {
+            cycle = cycle.clone() + local.g_index[i] * AB::Expr::from_canonical_usize(i);
+        }
+        let clk = local.clk + cycle;
+
+        // The message words are selected by both the round and the G function.
+        let mut mx_offset = AB::Expr::zero();
+        let mut my_offset = AB::Expr::zero();
+        for (r, schedule) in BLAKE3_MSG_SCHEDULE.iter().enumerate() {
+            for g in 0..8 {
+                let selector = local.round[r] * local.g_index[g];
+                mx_offset = mx_offset.clone()
+                    + selector.clone() * AB::Expr::from_canonical_usize(4 * schedule[2 * g]);
+                my_offset = my_offset.clone()
+                    + selector * AB::Expr::from_canonical_usize(4 * schedule[2 * g + 1]);
+            }
+        }
+        builder.assert_eq(local.mx_addr, local.msg_ptr + mx_offset);
+        builder.assert_eq(local.my_addr, local.msg_ptr + my_offset);
+
+        builder.eval_memory_access(
+            local.shard,
+            clk.clone(),
+            local.mx_addr,
+            &local.mx,
+            local.is_real,
+        );
+        builder.eval_memory_access(
+            local.shard,
+            clk.clone(),
+            local.my_addr,
+            &local.my,
+            local.is_real,
+        );
+
+        // The state words are selected by the G function only.
+        let state = [&local.a, &local.b, &local.c, &local.d];
+        for (k, word) in state.into_iter().enumerate() {
+            let mut offset = AB::Expr::zero();
+            for (g, index) in BLAKE3_G_INDEX.iter().enumerate() {
+                offset = offset.clone()
+                    + local.g_index[g] * AB::Expr::from_canonical_usize(4 * index[k]);
+            }
+            builder.eval_memory_access(
+                local.shard,
+                clk.clone(),
+                local.state_ptr + offset,
+                word,
+                local.is_real,
+            );
+        }
+    }
+
+    /// Constrains the G function, from the previous values of the state words to their new values.
+    fn eval_g<AB: ZKMAirBuilder>(
+        &self,
+        builder: &mut AB,
+        local: &Blake3CompressInnerCols<AB::Var>,
+    ) {
+        let (a, b, c, d) = (
+            *local.a.prev_value(),
+            *local.b.prev_value(),
+            *local.c.prev_value(),
+            *local.d.prev_value(),
+        );
+
+        // Calculate a1 := a + b + mx.
+        AddOperation::<AB::F>::eval(builder, a, b, local.a_plus_b, local.is_real.into());
+        AddOperation::<AB::F>::eval(
+            builder,
+            local.a_plus_b.value,
+            *local.mx.value(),
+            local.a1,
+            local.is_real.into(),
+        );
+        // Calculate d1 := (d xor a1) rightrotate 16.
+        XorOperation::<AB::F>::eval(builder, d, local.a1.value, local.d_xor_a1, local.is_real);
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            local.d_xor_a1.value,
+            16,
+            local.d1,
+            local.is_real,
+        );
+        // Calculate c1 := c + d1.
+        AddOperation::<AB::F>::eval(builder, c, local.d1.value, local.c1, local.is_real.into());
+        // Calculate b1 := (b xor c1) rightrotate 12.
+        XorOperation::<AB::F>::eval(builder, b, local.c1.value, local.b_xor_c1, local.is_real);
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            local.b_xor_c1.value,
+            12,
+            local.b1,
+            local.is_real,
+        );
+
+        // Calculate a2 := a1 + b1 + my.
+        AddOperation::<AB::F>::eval(
+            builder,
+            local.a1.value,
+            local.b1.value,
+            local.a1_plus_b1,
+            local.is_real.into(),
+        );
+        AddOperation::<AB::F>::eval(
+            builder,
+            local.a1_plus_b1.value,
+            *local.my.value(),
+            local.a2,
+            local.is_real.into(),
+        );
+        // Calculate d2 := (d1 xor a2) rightrotate 8.
+        XorOperation::<AB::F>::eval(
+            builder,
+            local.d1.value,
+            local.a2.value,
+            local.d1_xor_a2,
+            local.is_real,
+        );
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            local.d1_xor_a2.value,
+            8,
+            local.d2,
+            local.is_real,
+        );
+        // Calculate c2 := c1 + d2.
+        AddOperation::<AB::F>::eval(
+            builder,
+            local.c1.value,
+            local.d2.value,
+            local.c2,
+            local.is_real.into(),
+        );
+        // Calculate b2 := (b1 xor c2) rightrotate 7.
+        XorOperation::<AB::F>::eval(
+            builder,
+            local.b1.value,
+            local.c2.value,
+            local.b1_xor_c2,
+            local.is_real,
+        );
+        FixedRotateRightOperation::<AB::F>::eval(
+            builder,
+            local.b1_xor_c2.value,
+            7,
+            local.b2,
+            local.is_real,
+        );
+
+        // The new state words are the ones written to memory.
+        builder.when(local.is_real).assert_word_eq(*local.a.value(), local.a2.value);
+        builder.when(local.is_real).assert_word_eq(*local.b.value(), local.b2.value);
+        builder.when(local.is_real).assert_word_eq(*local.c.value(), local.c2.value);
+        builder.when(local.is_real).assert_word_eq(*local.d.value(), local.d2.value);
+    }
+}
@@ -0,0 +1,75 @@
+use std::mem::size_of;
+
+use zkm_derive::AlignedBorrow;
+
+use crate::{
+    memory::{MemoryReadCols, MemoryWriteCols},
+    operations::{AddOperation, FixedRotateRightOperation, XorOperation},
+};
+
+pub const NUM_BLAKE3_COMPRESS_INNER_COLS: usize = size_of::<Blake3CompressInnerCols<u8>>();
+
+/// A set of columns needed to compute the rounds of the BLAKE3 compression function.
+///
+/// Each syscall is processed over 56 rows, one for each G function of the 7 rounds. A row reads
+/// the two message words of its G function and overwrites the four state words it mixes.
+#[derive(AlignedBorrow, Default, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Blake3CompressInnerCols<T> {
+    /// Inputs.
+    pub shard: T,
+    pub clk: T,
+    pub state_ptr: T,
+    pub msg_ptr: T,
+
+    pub start: T,
+
+    /// Which round is currently processed.
+    pub round: [T; 7],
+
+    /// Which G function of the round is currently processed.
+    pub g_index: [T; 8],
+
+    /// Whether the current row is the last G function of the last round.
+    pub is_last_row: T,
+
+    /// The addresses of the two message words mixed by the current G function.
+    pub mx_addr: T,
+    pub my_addr: T,
+
+    /// The state words mixed by the current G function, with their values before and after it.
+    pub a: MemoryWriteCols<T>,
+    pub b: MemoryWriteCols<T>,
+    pub c: MemoryWriteCols<T>,
+    pub d: MemoryWriteCols<T>,
+
+    /// The message words mixed by the current G function.
+    pub mx: MemoryReadCols<T>,
+    pub my: MemoryReadCols<T>,
+
+    /// `a1 := a + b + mx`.
+    pub a_plus_b: AddOperation<T>,
+    pub a1: AddOperation<T>,
+    /// `d1 := (d xor a1) rightrotate 16`.
+    pub d_xor_a1: XorOperation<T>,
+    pub d1: FixedRotateRightOperation<T>,
+    /// `c1 := c + d1`.
+    pub c1: AddOperation<T>,
+    /// `b1 := (b xor c1) rightrotate 12`.
+    pub b_xor_c1: XorOperation<T>,
+    pub b1: FixedRotateRightOperation<T>,
+
+    /// `a2 := a1 + b1 + my`.
+    pub a1_plus_b1: AddOperation<T>,
+    pub a2: AddOperation<T>,
+    /// `d2 := (d1 xor a2) rightrotate 8`.
+    pub d1_xor_a2: XorOperation<T>,
+    pub d2: FixedRotateRightOperation<T>,
+    /// `c2 := c1 + d2`.
+    pub c2: AddOperation<T>,
+    /// `b2 := (b1 xor c2) rightrotate 7`.
+    pub b1_xor_c2: XorOperation<T>,
+    pub b2: FixedRotateRightOperation<T>,
+
+    pub is_real: T,
+}
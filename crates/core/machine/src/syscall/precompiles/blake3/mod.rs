@@ -0,0 +1,70 @@
+mod air;
+mod columns;
+mod trace;
+
+pub use zkm_primitives::consts::blake3::{BLAKE3_G_INDEX, BLAKE3_MSG_SCHEDULE};
+
+/// Implements the 7 rounds of the BLAKE3 compression function on a 16-word state, in place, with a
+/// 16-word message block. The inputs to the syscall are the 4byte-aligned pointers to the state
+/// and to the message. Setting up the state and the final feed-forward are left to the guest.
+///
+/// In the AIR, each syscall takes up 56 rows, where each row computes a single G function.
+#[derive(Default)]
+pub struct Blake3CompressInnerChip;
+
+impl Blake3CompressInnerChip {
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(test)]
+pub mod compress_inner_tests {
+    use test_artifacts::BLAKE3_ELF;
+    use zkm_core_executor::{syscalls::SyscallCode, Instruction, Opcode, Program};
+    use zkm_stark::CpuProver;
+
+    use crate::utils::{run_test, setup_logger};
+
+    pub fn blake3_compress_inner_program() -> Program {
+        let state_ptr = 100;
+        let msg_ptr = 1000;
+        let mut instructions = vec![Instruction::new(Opcode::ADD, 29, 0, 5, false, true)];
+        for i in 0..16 {
+            instructions.extend(vec![
+                Instruction::new(Opcode::ADD, 30, 0, state_ptr + i * 4, false, true),
+                Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+                Instruction::new(Opcode::ADD, 30, 0, msg_ptr + i * 4, false, true),
+                Instruction::new(Opcode::SW, 29, 30, 0, false, true),
+            ]);
+        }
+        instructions.extend(vec![
+            Instruction::new(
+                Opcode::ADD,
+                2,
+                0,
+                SyscallCode::BLAKE3_COMPRESS_INNER as u32,
+                false,
+                true,
+            ),
+            Instruction::new(Opcode::ADD, 4, 0, state_ptr, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, msg_ptr, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+        ]);
+        Program::new(instructions, 0, 0)
+    }
+
+    #[test]
+    fn prove_koalabear() {
+        setup_logger();
+        let program = blake3_compress_inner_program();
+        run_test::<CpuProver<_, _>>(program).unwrap();
+    }
+
+    #[test]
+    fn test_blake3_program_prove() {
+        setup_logger();
+        let program = Program::from(BLAKE3_ELF).unwrap();
+        run_test::<CpuProver<_, _>>(program).unwrap();
+    }
+}
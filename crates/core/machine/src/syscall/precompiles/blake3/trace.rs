@@ -0,0 +1,174 @@
+use std::borrow::BorrowMut;
+
+use hashbrown::HashMap;
+use itertools::Itertools;
+use p3_field::PrimeField32;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::{ParallelIterator, ParallelSlice};
+use zkm_core_executor::{
+    events::{Blake3CompressInnerEvent, ByteLookupEvent, ByteRecord, PrecompileEvent},
+    syscalls::SyscallCode,
+    ExecutionRecord, Program,
+};
+use zkm_stark::air::MachineAir;
+
+use super::{
+    columns::{Blake3CompressInnerCols, NUM_BLAKE3_COMPRESS_INNER_COLS},
+    Blake3CompressInnerChip, BLAKE3_MSG_SCHEDULE,
+};
+use crate::utils::pad_rows_fixed;
+
+impl<F: PrimeField32> MachineAir<F> for Blake3CompressInnerChip {
+    type Record = ExecutionRecord;
+
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Blake3CompressInner".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        _: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let rows = Vec::new();
+
+        let mut wrapped_rows = Some(rows);
+        for (_, event) in input.get_precompile_events(SyscallCode::BLAKE3_COMPRESS_INNER) {
+            let event = if let PrecompileEvent::Blake3CompressInner(event) = event {
+                event
+            } else {
+                unreachable!()
+            };
+            self.event_to_rows(event, &mut wrapped_rows, &mut Vec::new());
+        }
+        let mut rows = wrapped_rows.unwrap();
+
+        let num_real_rows = rows.len();
+
+        pad_rows_fixed(
+            &mut rows,
+            || [F::ZERO; NUM_BLAKE3_COMPRESS_INNER_COLS],
+            input.fixed_log2_rows::<F, _>(self),
+        );
+
+        // Set the round and g_index columns, and the message addresses, for the padded rows.
+        for (i, row) in rows[num_real_rows..].iter_mut().enumerate() {
+            let cols: &mut Blake3CompressInnerCols<F> = row.as_mut_slice().borrow_mut();
+            cols.populate_flags(i % 56, 0);
+        }
+
+        // Convert the trace to a row major matrix.
+        RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_BLAKE3_COMPRESS_INNER_COLS,
+        )
+    }
+
+    fn generate_dependencies(&self, input: &Self::Record, output: &mut Self::Record) {
+        let events = input.get_precompile_events(SyscallCode::BLAKE3_COMPRESS_INNER);
+        let chunk_size = std::cmp::max(events.len() / num_cpus::get(), 1);
+
+        let blu_batches = events
+            .par_chunks(chunk_size)
+            .map(|events| {
+                let mut blu: HashMap<ByteLookupEvent, usize> = HashMap::new();
+                events.iter().for_each(|(_, event)| {
+                    let event = if let PrecompileEvent::Blake3CompressInner(event) = event {
+                        event
+                    } else {
+                        unreachable!()
+                    };
+                    self.event_to_rows::<F>(event, &mut None, &mut blu);
+                });
+                blu
+            })
+            .collect::<Vec<_>>();
+
+        output.add_byte_lookup_events_from_maps(blu_batches.iter().collect_vec());
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        if let Some(shape) = shard.shape.as_ref() {
+            shape.included::<F, _>(self)
+        } else {
+            !shard.get_precompile_events(SyscallCode::BLAKE3_COMPRESS_INNER).is_empty()
+        }
+    }
+}
+
+impl Blake3CompressInnerChip {
+    fn event_to_rows<F: PrimeField32>(
+        &self,
+        event: &Blake3CompressInnerEvent,
+        rows: &mut Option<Vec<[F; NUM_BLAKE3_COMPRESS_INNER_COLS]>>,
+        blu: &mut impl ByteRecord,
+    ) {
+        for j in 0..56usize {
+            let mut row = [F::ZERO; NUM_BLAKE3_COMPRESS_INNER_COLS];
+            let cols: &mut Blake3CompressInnerCols<F> = row.as_mut_slice().borrow_mut();
+            cols.is_real = F::ONE;
+            cols.start = F::from_bool(j == 0);
+            cols.populate_flags(j, event.msg_ptr);
+            cols.shard = F::from_canonical_u32(event.shard);
+            cols.clk = F::from_canonical_u32(event.clk);
+            cols.state_ptr = F::from_canonical_u32(event.state_ptr);
+            cols.msg_ptr = F::from_canonical_u32(event.msg_ptr);
+
+            let writes = &event.state_writes[4 * j..4 * j + 4];
+            cols.a.populate(writes[0], blu);
+            cols.b.populate(writes[1], blu);
+            cols.c.populate(writes[2], blu);
+            cols.d.populate(writes[3], blu);
+            cols.mx.populate(event.mx_reads[j], blu);
+            cols.my.populate(event.my_reads[j], blu);
+
+            let [a, b, c, d] = [0, 1, 2, 3].map(|k| writes[k].prev_value);
+            let (mx, my) = (event.mx_reads[j].value, event.my_reads[j].value);
+
+            // `a1 := a + b + mx`, `d1 := (d xor a1) rightrotate 16`.
+            let a_plus_b = cols.a_plus_b.populate(blu, a, b);
+            let a1 = cols.a1.populate(blu, a_plus_b, mx);
+            let d_xor_a1 = cols.d_xor_a1.populate(blu, d, a1);
+            let d1 = cols.d1.populate(blu, d_xor_a1, 16);
+
+            // `c1 := c + d1`, `b1 := (b xor c1) rightrotate 12`.
+            let c1 = cols.c1.populate(blu, c, d1);
+            let b_xor_c1 = cols.b_xor_c1.populate(blu, b, c1);
+            let b1 = cols.b1.populate(blu, b_xor_c1, 12);
+
+            // `a2 := a1 + b1 + my`, `d2 := (d1 xor a2) rightrotate 8`.
+            let a1_plus_b1 = cols.a1_plus_b1.populate(blu, a1, b1);
+            let a2 = cols.a2.populate(blu, a1_plus_b1, my);
+            let d1_xor_a2 = cols.d1_xor_a2.populate(blu, d1, a2);
+            let d2 = cols.d2.populate(blu, d1_xor_a2, 8);
+
+            // `c2 := c1 + d2`, `b2 := (b1 xor c2) rightrotate 7`.
+            let c2 = cols.c2.populate(blu, c1, d2);
+            let b1_xor_c2 = cols.b1_xor_c2.populate(blu, b1, c2);
+            let b2 = cols.b2.populate(blu, b1_xor_c2, 7);
+
+            assert_eq!([a2, b2, c2, d2], [0, 1, 2, 3].map(|k| writes[k].value));
+
+            if rows.as_ref().is_some() {
+                rows.as_mut().unwrap().push(row);
+            }
+        }
+    }
+}
+
+impl<F: PrimeField32> Blake3CompressInnerCols<F> {
+    /// Populates the round and G function flags of the `i`-th row of a syscall, and the addresses
+    /// of the message words it reads from the message at `msg_ptr`.
+    pub fn populate_flags(&mut self, i: usize, msg_ptr: u32) {
+        let (round, g) = (i / 8, i % 8);
+        self.round[round] = F::ONE;
+        self.g_index[g] = F::ONE;
+        self.is_last_row = F::from_bool(i == 55);
+
+        let schedule = BLAKE3_MSG_SCHEDULE[round];
+        self.mx_addr = F::from_canonical_u32(msg_ptr + 4 * schedule[2 * g] as u32);
+        self.my_addr = F::from_canonical_u32(msg_ptr + 4 * schedule[2 * g + 1] as u32);
+    }
+}
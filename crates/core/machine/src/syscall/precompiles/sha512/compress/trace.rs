@@ -0,0 +1,308 @@
+use std::borrow::BorrowMut;
+
+use hashbrown::HashMap;
+use itertools::Itertools;
+use p3_field::PrimeField32;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::{ParallelIterator, ParallelSlice};
+use zkm_core_executor::{
+    events::{ByteLookupEvent, ByteRecord, PrecompileEvent, Sha512CompressEvent},
+    syscalls::SyscallCode,
+    ExecutionRecord, Program,
+};
+use zkm_stark::{air::MachineAir, Word};
+
+use super::{
+    columns::{Sha512CompressCols, NUM_SHA512_COMPRESS_COLS},
+    Sha512CompressChip, SHA512_COMPRESS_K,
+};
+use crate::utils::pad_rows_fixed;
+
+/// The low and high words of a 64-bit value.
+fn u64_words<F: PrimeField32>(value: u64) -> [Word<F>; 2] {
+    [Word::from(value as u32), Word::from((value >> 32) as u32)]
+}
+
+impl<F: PrimeField32> MachineAir<F> for Sha512CompressChip {
+    type Record = ExecutionRecord;
+
+    type Program = Program;
+
+    fn name(&self) -> String {
+        "Sha512Compress".to_string()
+    }
+
+    fn generate_trace(
+        &self,
+        input: &ExecutionRecord,
+        _: &mut ExecutionRecord,
+    ) -> RowMajorMatrix<F> {
+        let rows = Vec::new();
+
+        let mut wrapped_rows = Some(rows);
+        for (_, event) in input.get_precompile_events(SyscallCode::SHA512_COMPRESS) {
+            let event = if let PrecompileEvent::Sha512Compress(event) = event {
+                event
+            } else {
+                unreachable!()
+            };
+            self.event_to_rows(event, &mut wrapped_rows, &mut Vec::new());
+        }
+        let mut rows = wrapped_rows.unwrap();
+
+        let num_real_rows = rows.len();
+
+        pad_rows_fixed(
+            &mut rows,
+            || [F::ZERO; NUM_SHA512_COMPRESS_COLS],
+            input.fixed_log2_rows::<F, _>(self),
+        );
+
+        // Set the octet_num and octet columns for the padded rows.
+        let mut octet_num = 0;
+        let mut octet = 0;
+        for row in rows[num_real_rows..].iter_mut() {
+            let cols: &mut Sha512CompressCols<F> = row.as_mut_slice().borrow_mut();
+            cols.octet_num[octet_num] = F::ONE;
+            cols.octet[octet] = F::ONE;
+
+            // If in the compression phase, set the k value.
+            if octet_num != 0 && octet_num != 11 {
+                let compression_idx = octet_num - 1;
+                let k_idx = compression_idx * 8 + octet;
+                cols.k = u64_words(SHA512_COMPRESS_K[k_idx]);
+            }
+
+            octet = (octet + 1) % 8;
+            if octet == 0 {
+                octet_num = (octet_num + 1) % 12;
+            }
+
+            cols.is_last_row = cols.octet[7] * cols.octet_num[11];
+        }
+
+        // Convert the trace to a row major matrix.
+        RowMajorMatrix::new(
+            rows.into_iter().flatten().collect::<Vec<_>>(),
+            NUM_SHA512_COMPRESS_COLS,
+        )
+    }
+
+    fn generate_dependencies(&self, input: &Self::Record, output: &mut Self::Record) {
+        let events = input.get_precompile_events(SyscallCode::SHA512_COMPRESS);
+        let chunk_size = std::cmp::max(events.len() / num_cpus::get(), 1);
+
+        let blu_batches = events
+            .par_chunks(chunk_size)
+            .map(|events| {
+                let mut blu: HashMap<ByteLookupEvent, usize> = HashMap::new();
+                events.iter().for_each(|(_, event)| {
+                    let event = if let PrecompileEvent::Sha512Compress(event) = event {
+                        event
+                    } else {
+                        unreachable!()
+                    };
+                    self.event_to_rows::<F>(event, &mut None, &mut blu);
+                });
+                blu
+            })
+            .collect::<Vec<_>>();
+
+        output.add_byte_lookup_events_from_maps(blu_batches.iter().collect_vec());
+    }
+
+    fn included(&self, shard: &Self::Record) -> bool {
+        if let Some(shape) = shard.shape.as_ref() {
+            shape.included::<F, _>(self)
+        } else {
+            !shard.get_precompile_events(SyscallCode::SHA512_COMPRESS).is_empty()
+        }
+    }
+}
+
+impl Sha512CompressChip {
+    fn event_to_rows<F: PrimeField32>(
+        &self,
+        event: &Sha512CompressEvent,
+        rows: &mut Option<Vec<[F; NUM_SHA512_COMPRESS_COLS]>>,
+        blu: &mut impl ByteRecord,
+    ) {
+        let og_h = event.h;
+
+        let mut octet_num_idx = 0;
+
+        // Load a, b, c, d, e, f, g, h.
+        for j in 0..8usize {
+            let mut row = [F::ZERO; NUM_SHA512_COMPRESS_COLS];
+            let cols: &mut Sha512CompressCols<F> = row.as_mut_slice().borrow_mut();
+
+            cols.shard = F::from_canonical_u32(event.shard);
+            cols.clk = F::from_canonical_u32(event.clk);
+            cols.w_ptr = F::from_canonical_u32(event.w_ptr);
+            cols.h_ptr = F::from_canonical_u32(event.h_ptr);
+
+            cols.octet[j] = F::ONE;
+            cols.octet_num[octet_num_idx] = F::ONE;
+            cols.is_initialize = F::ONE;
+
+            for k in 0..2 {
+                cols.mem[k].populate_read(event.h_read_records[2 * j + k], blu);
+            }
+            cols.mem_addr = F::from_canonical_u32(event.h_ptr + (j * 8) as u32);
+
+            cols.a = u64_words(og_h[0]);
+            cols.b = u64_words(og_h[1]);
+            cols.c = u64_words(og_h[2]);
+            cols.d = u64_words(og_h[3]);
+            cols.e = u64_words(og_h[4]);
+            cols.f = u64_words(og_h[5]);
+            cols.g = u64_words(og_h[6]);
+            cols.h = u64_words(og_h[7]);
+
+            cols.is_real = F::ONE;
+            cols.start = cols.is_real * cols.octet_num[0] * cols.octet[0];
+            if rows.as_ref().is_some() {
+                rows.as_mut().unwrap().push(row);
+            }
+        }
+
+        // Performs the compress operation.
+        let mut h_array = event.h;
+        for j in 0..80 {
+            if (j as u32).is_multiple_of(8) {
+                octet_num_idx += 1;
+            }
+            let mut row = [F::ZERO; NUM_SHA512_COMPRESS_COLS];
+            let cols: &mut Sha512CompressCols<F> = row.as_mut_slice().borrow_mut();
+
+            cols.k = u64_words(SHA512_COMPRESS_K[j]);
+            cols.is_compression = F::ONE;
+            cols.octet[j % 8] = F::ONE;
+            cols.octet_num[octet_num_idx] = F::ONE;
+
+            cols.shard = F::from_canonical_u32(event.shard);
+            cols.clk = F::from_canonical_u32(event.clk);
+            cols.w_ptr = F::from_canonical_u32(event.w_ptr);
+            cols.h_ptr = F::from_canonical_u32(event.h_ptr);
+            for k in 0..2 {
+                cols.mem[k].populate_read(event.w_i_read_records[2 * j + k], blu);
+            }
+            cols.mem_addr = F::from_canonical_u32(event.w_ptr + (j * 8) as u32);
+
+            let [a, b, c, d, e, f, g, h] = h_array;
+            cols.a = u64_words(a);
+            cols.b = u64_words(b);
+            cols.c = u64_words(c);
+            cols.d = u64_words(d);
+            cols.e = u64_words(e);
+            cols.f = u64_words(f);
+            cols.g = u64_words(g);
+            cols.h = u64_words(h);
+
+            let e_rr_14 = cols.e_rr_14.populate(blu, e, 14);
+            let e_rr_18 = cols.e_rr_18.populate(blu, e, 18);
+            let e_rr_41 = cols.e_rr_41.populate(blu, e, 41);
+            let a_rr_28 = cols.a_rr_28.populate(blu, a, 28);
+            let a_rr_34 = cols.a_rr_34.populate(blu, a, 34);
+            let a_rr_39 = cols.a_rr_39.populate(blu, a, 39);
+
+            // The bitwise operations are computed on the low and high words independently.
+            let (mut s1, mut ch, mut s0, mut maj) = (0u64, 0u64, 0u64, 0u64);
+            for k in 0..2 {
+                let word = |value: u64| (value >> (32 * k)) as u32;
+
+                let s1_intermediate =
+                    cols.s1_intermediate[k].populate(blu, word(e_rr_14), word(e_rr_18));
+                let s1_k = cols.s1[k].populate(blu, s1_intermediate, word(e_rr_41));
+
+                let e_and_f = cols.e_and_f[k].populate(blu, word(e), word(f));
+                let e_not = cols.e_not[k].populate(blu, word(e));
+                let e_not_and_g = cols.e_not_and_g[k].populate(blu, e_not, word(g));
+                let ch_k = cols.ch[k].populate(blu, e_and_f, e_not_and_g);
+
+                let s0_intermediate =
+                    cols.s0_intermediate[k].populate(blu, word(a_rr_28), word(a_rr_34));
+                let s0_k = cols.s0[k].populate(blu, s0_intermediate, word(a_rr_39));
+
+                let a_and_b = cols.a_and_b[k].populate(blu, word(a), word(b));
+                let a_and_c = cols.a_and_c[k].populate(blu, word(a), word(c));
+                let b_and_c = cols.b_and_c[k].populate(blu, word(b), word(c));
+                let maj_intermediate = cols.maj_intermediate[k].populate(blu, a_and_b, a_and_c);
+                let maj_k = cols.maj[k].populate(blu, maj_intermediate, b_and_c);
+
+                s1 |= (s1_k as u64) << (32 * k);
+                ch |= (ch_k as u64) << (32 * k);
+                s0 |= (s0_k as u64) << (32 * k);
+                maj |= (maj_k as u64) << (32 * k);
+            }
+
+            let mut temp1 = h;
+            for (add, operand) in
+                cols.temp1.iter_mut().zip([s1, ch, SHA512_COMPRESS_K[j], event.w[j]])
+            {
+                temp1 = add.populate(blu, temp1, operand);
+            }
+
+            let temp2 = cols.temp2.populate(blu, s0, maj);
+
+            let d_add_temp1 = cols.d_add_temp1.populate(blu, d, temp1);
+            let temp1_add_temp2 = cols.temp1_add_temp2.populate(blu, temp1, temp2);
+
+            h_array[7] = g;
+            h_array[6] = f;
+            h_array[5] = e;
+            h_array[4] = d_add_temp1;
+            h_array[3] = c;
+            h_array[2] = b;
+            h_array[1] = a;
+            h_array[0] = temp1_add_temp2;
+
+            cols.is_real = F::ONE;
+            cols.start = cols.is_real * cols.octet_num[0] * cols.octet[0];
+
+            if rows.as_ref().is_some() {
+                rows.as_mut().unwrap().push(row);
+            }
+        }
+
+        octet_num_idx += 1;
+        // Store a, b, c, d, e, f, g, h.
+        for j in 0..8usize {
+            let mut row = [F::ZERO; NUM_SHA512_COMPRESS_COLS];
+            let cols: &mut Sha512CompressCols<F> = row.as_mut_slice().borrow_mut();
+
+            cols.shard = F::from_canonical_u32(event.shard);
+            cols.clk = F::from_canonical_u32(event.clk);
+            cols.w_ptr = F::from_canonical_u32(event.w_ptr);
+            cols.h_ptr = F::from_canonical_u32(event.h_ptr);
+
+            cols.octet[j] = F::ONE;
+            cols.octet_num[octet_num_idx] = F::ONE;
+            cols.is_finalize = F::ONE;
+
+            cols.finalize_add.populate(blu, og_h[j], h_array[j]);
+            for k in 0..2 {
+                cols.mem[k].populate_write(event.h_write_records[2 * j + k], blu);
+            }
+            cols.mem_addr = F::from_canonical_u32(event.h_ptr + (j * 8) as u32);
+
+            cols.a = u64_words(h_array[0]);
+            cols.b = u64_words(h_array[1]);
+            cols.c = u64_words(h_array[2]);
+            cols.d = u64_words(h_array[3]);
+            cols.e = u64_words(h_array[4]);
+            cols.f = u64_words(h_array[5]);
+            cols.g = u64_words(h_array[6]);
+            cols.h = u64_words(h_array[7]);
+            cols.finalized_operand = u64_words(h_array[j]);
+
+            cols.is_real = F::ONE;
+            cols.is_last_row = cols.octet[7] * cols.octet_num[11];
+            cols.start = cols.is_real * cols.octet_num[0] * cols.octet[0];
+
+            if rows.as_ref().is_some() {
+                rows.as_mut().unwrap().push(row);
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::FieldAlgebra;
+use p3_matrix::Matrix;
+use zkm_core_executor::syscalls::SyscallCode;
+use zkm_stark::{
+    air::{LookupScope, ZKMAirBuilder},
+    Word,
+};
+
+use super::{Sha512ExtendChip, Sha512ExtendCols, NUM_SHA512_EXTEND_COLS};
+use crate::{
+    air::{MemoryAirBuilder, WordAirBuilder},
+    memory::MemoryCols,
+    operations::{
+        AddU64Operation, FixedRotateRightU64Operation, FixedShiftRightU64Operation, XorOperation,
+    },
+};
+
+use core::borrow::Borrow;
+use zkm_stark::air::BaseAirBuilder;
+
+/// The low and high words of a 64-bit value accessed in memory.
+fn u64_value<T: Copy, M: MemoryCols<T>>(mem: &[M; 2]) -> [Word<T>; 2] {
+    [*mem[0].value(), *mem[1].value()]
+}
+
+/// The low and high words of the result of a 64-bit xor.
+fn xor_value<T: Copy>(xor: &[XorOperation<T>; 2]) -> [Word<T>; 2] {
+    [xor[0].value, xor[1].value]
+}
+
+impl<F> BaseAir<F> for Sha512ExtendChip {
+    fn width(&self) -> usize {
+        NUM_SHA512_EXTEND_COLS
+    }
+}
+
+impl<AB> Air<AB> for Sha512ExtendChip
+where
+    AB: ZKMAirBuilder,
+{
+    fn eval(&self, builder: &mut AB) {
+        // Initialize columns.
+        let main = builder.main();
+        let (local, next) = (main.row_slice(0), main.row_slice(1));
+        let local: &Sha512ExtendCols<AB::Var> = (*local).borrow();
+        let next: &Sha512ExtendCols<AB::Var> = (*next).borrow();
+
+        let i_start = AB::F::from_canonical_u32(16);
+        let nb_bytes_in_u64 = AB::F::from_canonical_u32(8);
+
+        // Evaluate the control flags.
+        self.eval_flags(builder);
+
+        // Copy over the inputs until the result has been computed (every 64 rows).
+        builder
+            .when_transition()
+            .when_not(local.cycle_16_end.result * local.cycle_64[3])
+            .assert_eq(local.shard, next.shard);
+        builder
+            .when_transition()
+            .when_not(local.cycle_16_end.result * local.cycle_64[3])
+            .assert_eq(local.clk, next.clk);
+        builder
+            .when_transition()
+            .when_not(local.cycle_16_end.result * local.cycle_64[3])
+            .assert_eq(local.w_ptr, next.w_ptr);
+
+        // Read w[i-15], w[i-2], w[i-16] and w[i-7], one word at a time.
+        let reads = [
+            (15, &local.w_i_minus_15),
+            (2, &local.w_i_minus_2),
+            (16, &local.w_i_minus_16),
+            (7, &local.w_i_minus_7),
+        ];
+        for (offset, mem) in reads {
+            builder.eval_memory_access_slice(
+                local.shard,
+                local.clk + (local.i - i_start),
+                local.w_ptr + (local.i - AB::F::from_canonical_u32(offset)) * nb_bytes_in_u64,
+                mem,
+                local.is_real,
+            );
+        }
+
+        // Compute `s0`.
+        let w_i_minus_15 = u64_value(&local.w_i_minus_15);
+        // w[i-15] rightrotate 1.
+        FixedRotateRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_15,
+            1,
+            local.w_i_minus_15_rr_1,
+            local.is_real,
+        );
+        // w[i-15] rightrotate 8.
+        FixedRotateRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_15,
+            8,
+            local.w_i_minus_15_rr_8,
+            local.is_real,
+        );
+        // w[i-15] rightshift 7.
+        FixedShiftRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_15,
+            7,
+            local.w_i_minus_15_rs_7,
+            local.is_real.into(),
+        );
+        for k in 0..2 {
+            // (w[i-15] rightrotate 1) xor (w[i-15] rightrotate 8)
+            XorOperation::<AB::F>::eval(
+                builder,
+                local.w_i_minus_15_rr_1.value[k],
+                local.w_i_minus_15_rr_8.value[k],
+                local.s0_intermediate[k],
+                local.is_real,
+            );
+            // s0 := (w[i-15] rightrotate 1) xor (w[i-15] rightrotate 8) xor (w[i-15] rightshift 7)
+            XorOperation::<AB::F>::eval(
+                builder,
+                local.s0_intermediate[k].value,
+                local.w_i_minus_15_rs_7.value[k],
+                local.s0[k],
+                local.is_real,
+            );
+        }
+
+        // Compute `s1`.
+        let w_i_minus_2 = u64_value(&local.w_i_minus_2);
+        // w[i-2] rightrotate 19.
+        FixedRotateRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_2,
+            19,
+            local.w_i_minus_2_rr_19,
+            local.is_real,
+        );
+        // w[i-2] rightrotate 61.
+        FixedRotateRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_2,
+            61,
+            local.w_i_minus_2_rr_61,
+            local.is_real,
+        );
+        // w[i-2] rightshift 6.
+        FixedShiftRightU64Operation::<AB::F>::eval(
+            builder,
+            w_i_minus_2,
+            6,
+            local.w_i_minus_2_rs_6,
+            local.is_real.into(),
+        );
+        for k in 0..2 {
+            // (w[i-2] rightrotate 19) xor (w[i-2] rightrotate 61)
+            XorOperation::<AB::F>::eval(
+                builder,
+                local.w_i_minus_2_rr_19.value[k],
+                local.w_i_minus_2_rr_61.value[k],
+                local.s1_intermediate[k],
+                local.is_real,
+            );
+            // s1 := (w[i-2] rightrotate 19) xor (w[i-2] rightrotate 61) xor (w[i-2] rightshift 6)
+            XorOperation::<AB::F>::eval(
+                builder,
+                local.s1_intermediate[k].value,
+                local.w_i_minus_2_rs_6.value[k],
+                local.s1[k],
+                local.is_real,
+            );
+        }
+
+        // s2 := w[i-16] + s0 + w[i-7] + s1.
+        AddU64Operation::<AB::F>::eval(
+            builder,
+            u64_value(&local.w_i_minus_16),
+            xor_value(&local.s0),
+            local.s2[0],
+            local.is_real.into(),
+        );
+        AddU64Operation::<AB::F>::eval(
+            builder,
+            local.s2[0].value,
+            u64_value(&local.w_i_minus_7),
+            local.s2[1],
+            local.is_real.into(),
+        );
+        AddU64Operation::<AB::F>::eval(
+            builder,
+            local.s2[1].value,
+            xor_value(&local.s1),
+            local.s2[2],
+            local.is_real.into(),
+        );
+
+        // Write `s2` to `w[i]`.
+        builder.eval_memory_access_slice(
+            local.shard,
+            local.clk + (local.i - i_start),
+            local.w_ptr + local.i * nb_bytes_in_u64,
+            &local.w_i,
+            local.is_real,
+        );
+
+        builder.assert_word_eq(*local.w_i[0].value(), local.s2[2].value[0]);
+        builder.assert_word_eq(*local.w_i[1].value(), local.s2[2].value[1]);
+
+        // Receive syscall event in first row of 64-cycle.
+        builder.receive_syscall(
+            local.shard,
+            local.clk,
+            AB::F::from_canonical_u32(SyscallCode::SHA512_EXTEND.syscall_id()),
+            local.w_ptr,
+            AB::Expr::zero(),
+            local.cycle_64_start,
+            LookupScope::Local,
+        );
+
+        // Assert that is_real is a bool.
+        builder.assert_bool(local.is_real);
+
+        // Ensure that all rows in a 64 row cycle has the same `is_real` values.
+        builder
+            .when_transition()
+            .when_not(local.cycle_64_end)
+            .assert_eq(local.is_real, next.is_real);
+
+        // Assert that a real last row ends its 64-cycle. Unlike SHA-256, a table of extend syscalls
+        // can fill a power of 2 exactly, so the last row is not always padding.
+        builder.when_last_row().assert_eq(local.is_real, local.cycle_64_end);
+    }
+}
@@ -153,6 +153,18 @@ pub mod sys_linux_tests {
             Instruction::new(Opcode::ADD, 4, 0, 1, false, true),
             Instruction::new(Opcode::ADD, 5, 0, 1, false, true),
             Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+            Instruction::new(Opcode::ADD, 2, 0, SyscallCode::SYS_FUTEX as u32, false, true),
+            Instruction::new(Opcode::ADD, 4, 0, 1, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, 1, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+            Instruction::new(Opcode::ADD, 2, 0, SyscallCode::SYS_SCHED_YIELD as u32, false, true),
+            Instruction::new(Opcode::ADD, 4, 0, 1, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, 1, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
+            Instruction::new(Opcode::ADD, 2, 0, SyscallCode::SYS_NANOSLEEP as u32, false, true),
+            Instruction::new(Opcode::ADD, 4, 0, 1, false, true),
+            Instruction::new(Opcode::ADD, 5, 0, 1, false, true),
+            Instruction::new(Opcode::SYSCALL, 2, 4, 5, false, false),
             Instruction::new(Opcode::ADD, 2, 0, SyscallCode::SYS_READ as u32, false, true),
             Instruction::new(Opcode::ADD, 4, 0, 0, false, true),
             Instruction::new(Opcode::ADD, 5, 0, 0x33, false, true),
@@ -1,4 +1,7 @@
-use std::sync::{Condvar, Mutex};
+use std::{
+    sync::{Condvar, Mutex},
+    time::Duration,
+};
 
 /// A turn-based synchronization primitive.
 pub struct TurnBasedSync {
@@ -20,6 +23,26 @@ impl TurnBasedSync {
         }
     }
 
+    /// Waits for the current turn to be equal to the given turn, unless `abort` returns true
+    /// first. `abort` is polled every `poll` interval.
+    ///
+    /// Returns whether the turn was reached.
+    pub fn wait_for_turn_or_abort(
+        &self,
+        my_turn: usize,
+        poll: Duration,
+        abort: impl Fn() -> bool,
+    ) -> bool {
+        let mut turn = self.current_turn.lock().unwrap();
+        while *turn != my_turn {
+            if abort() {
+                return false;
+            }
+            turn = self.cv.wait_timeout(turn, poll).unwrap().0;
+        }
+        true
+    }
+
     /// Advances the current turn.
     pub fn advance_turn(&self) {
         let mut turn = self.current_turn.lock().unwrap();
@@ -1,8 +1,10 @@
 pub mod concurrency;
 mod logger;
+pub mod pipeline;
 mod prove;
 mod span;
 mod tracer;
+pub mod tune;
 
 pub use logger::*;
 use p3_field::Field;
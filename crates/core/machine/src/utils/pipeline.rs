@@ -0,0 +1,290 @@
+//! A scheduler keeping several shards in flight through the stages of the core prover.
+//!
+//! The trace generators of [`crate::utils::prove_with_context`] acquire a [`MemoryPermit`] for
+//! the traces of every shard before sending it to [`run_stage`], whose workers commit to and open
+//! the shards independently of each other. A worker picks the next shard as soon as it is done
+//! with its own, so the trace generation of a shard overlaps the commitment and the opening of the
+//! shards before it, and the [`MemoryBudget`] bounds the memory they take together. With
+//! [`TraceBacking::Disk`], the traces of the shards waiting for a worker are moved to
+//! [`MmapMatrix`]es and only reserve their share of the budget once they are loaded back.
+
+use std::{
+    io,
+    marker::PhantomData,
+    path::Path,
+    sync::{mpsc::Receiver, Arc, Condvar, Mutex},
+};
+
+use memmap2::MmapMut;
+use p3_matrix::{dense::RowMajorMatrix, Matrix};
+use zkm_stark::TraceBacking;
+
+/// The peak memory of proving a shard, as a multiple of the size of its main traces: the traces
+/// themselves, their low-degree extension and its Merkle tree, and the permutation and quotient
+/// traces.
+pub const SHARD_MEMORY_FACTOR: usize = 4;
+
+/// A number of bytes shared by the shards in flight.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    capacity: usize,
+    used: Mutex<usize>,
+    released: Condvar,
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `capacity` bytes. A capacity of zero is unlimited.
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self { capacity, used: Mutex::new(0), released: Condvar::new() })
+    }
+
+    /// Waits until `bytes` fit in the budget and reserves them until the permit is dropped.
+    ///
+    /// A reservation larger than the whole budget is granted once nothing else is reserved, so
+    /// that a single large shard does not block the prover forever.
+    pub fn acquire(self: &Arc<Self>, bytes: usize) -> MemoryPermit {
+        let mut used = self.used.lock().unwrap();
+        if self.capacity > 0 {
+            while *used > 0 && *used + bytes > self.capacity {
+                used = self.released.wait(used).unwrap();
+            }
+        }
+        *used += bytes;
+        MemoryPermit { budget: Arc::clone(self), bytes }
+    }
+
+    /// The number of bytes reserved.
+    pub fn used(&self) -> usize {
+        *self.used.lock().unwrap()
+    }
+}
+
+/// A reservation of bytes in a [`MemoryBudget`], released when dropped.
+#[derive(Debug)]
+pub struct MemoryPermit {
+    budget: Arc<MemoryBudget>,
+    bytes: usize,
+}
+
+impl Drop for MemoryPermit {
+    fn drop(&mut self) {
+        *self.budget.used.lock().unwrap() -= self.bytes;
+        self.budget.released.notify_all();
+    }
+}
+
+/// The number of bytes to reserve for proving a shard with the given main traces.
+pub fn shard_memory<F>(traces: &[(String, RowMajorMatrix<F>)]) -> usize {
+    let trace_bytes: usize =
+        traces.iter().map(|(_, trace)| trace.values.len() * std::mem::size_of::<F>()).sum();
+    trace_bytes * SHARD_MEMORY_FACTOR
+}
+
+/// A row-major matrix whose values are stored in a memory-mapped temporary file.
+///
+/// The pages of the matrix are backed by the file rather than by anonymous memory, so the
+/// operating system can write them out and drop them under memory pressure, and reads them back
+/// when the matrix is accessed. The file is deleted once the matrix is dropped.
+pub struct MmapMatrix<F> {
+    mmap: MmapMut,
+    len: usize,
+    width: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Copy> MmapMatrix<F> {
+    /// Copies `matrix` to a memory-mapped temporary file in `dir`.
+    pub fn new(dir: &Path, matrix: &RowMajorMatrix<F>) -> io::Result<Self> {
+        let values = matrix.values.as_slice();
+        let bytes = std::mem::size_of_val(values);
+        let file = tempfile::tempfile_in(dir)?;
+        // An empty file cannot be mapped.
+        file.set_len(bytes.max(1) as u64)?;
+        // SAFETY: the file is an anonymous temporary file, so no other process maps it.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        // SAFETY: the mapping holds at least `bytes` bytes, and the field elements of the traces
+        // are plain values without pointers or drop glue.
+        unsafe {
+            std::ptr::copy_nonoverlapping(values.as_ptr().cast::<u8>(), mmap.as_mut_ptr(), bytes);
+        }
+        Ok(Self { mmap, len: values.len(), width: matrix.width(), _marker: PhantomData })
+    }
+
+    /// The values of the matrix, in row-major order.
+    pub fn values(&self) -> &[F] {
+        // SAFETY: the mapping is page-aligned and holds the `len` values copied in `new`.
+        unsafe { std::slice::from_raw_parts(self.mmap.as_ptr().cast::<F>(), self.len) }
+    }
+
+    /// The number of columns of the matrix.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Copies the matrix back to memory.
+    pub fn to_row_major_matrix(&self) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new(self.values().to_vec(), self.width)
+    }
+}
+
+/// The main traces of a shard waiting to be proven.
+pub enum ShardTraces<F> {
+    /// Traces kept in memory, with their reservation in the memory budget.
+    Memory(Vec<(String, RowMajorMatrix<F>)>, MemoryPermit),
+    /// Traces moved to memory-mapped files, with the number of bytes to reserve once loaded.
+    Disk(Vec<(String, MmapMatrix<F>)>, usize),
+}
+
+impl<F: Copy> ShardTraces<F> {
+    /// Keeps the traces in memory once they fit in `budget`, or moves them to memory-mapped files,
+    /// according to `backing`.
+    pub fn new(
+        traces: Vec<(String, RowMajorMatrix<F>)>,
+        budget: &Arc<MemoryBudget>,
+        backing: &TraceBacking,
+    ) -> io::Result<Self> {
+        let bytes = shard_memory(&traces);
+        match backing {
+            TraceBacking::Disk(dir) if !traces.is_empty() => {
+                let traces = traces
+                    .iter()
+                    .map(|(name, trace)| Ok((name.clone(), MmapMatrix::new(dir, trace)?)))
+                    .collect::<io::Result<_>>()?;
+                Ok(Self::Disk(traces, bytes))
+            }
+            _ => Ok(Self::Memory(traces, budget.acquire(bytes))),
+        }
+    }
+
+    /// Returns the traces and their reservation in `budget`, copying them back to memory once
+    /// they fit in the budget if they were memory-mapped.
+    pub fn load(
+        self,
+        budget: &Arc<MemoryBudget>,
+    ) -> (Vec<(String, RowMajorMatrix<F>)>, MemoryPermit) {
+        match self {
+            Self::Memory(traces, permit) => (traces, permit),
+            Self::Disk(traces, bytes) => {
+                let permit = budget.acquire(bytes);
+                let traces = traces
+                    .into_iter()
+                    .map(|(name, trace)| (name, trace.to_row_major_matrix()))
+                    .collect();
+                (traces, permit)
+            }
+        }
+    }
+}
+
+/// Runs `f` on every item received from `items` on `workers` threads, until the channel is closed.
+///
+/// Returns the outputs in the order the items were completed.
+pub fn run_stage<T: Send, O: Send>(
+    items: Receiver<T>,
+    workers: usize,
+    f: impl Fn(T) -> O + Sync,
+) -> Vec<O> {
+    let items = Mutex::new(items);
+    let outputs = Mutex::new(Vec::new());
+    let span = tracing::Span::current();
+    std::thread::scope(|s| {
+        for _ in 0..workers.max(1) {
+            s.spawn(|| {
+                let _span = span.enter();
+                loop {
+                    // Release the lock before running `f`, so the other workers can pick items.
+                    let received = { items.lock().unwrap().recv() };
+                    let Ok(item) = received else { break };
+                    let output = f(item);
+                    outputs.lock().unwrap().push(output);
+                }
+            });
+        }
+    });
+    outputs.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc::sync_channel,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_memory_budget() {
+        let budget = MemoryBudget::new(100);
+        let permit = budget.acquire(60);
+        assert_eq!(budget.used(), 60);
+
+        // A second reservation waits for the first one to be released.
+        let waiter = {
+            let budget = Arc::clone(&budget);
+            thread::spawn(move || budget.acquire(60))
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(budget.used(), 60);
+        drop(permit);
+        let permit = waiter.join().unwrap();
+        assert_eq!(budget.used(), 60);
+        drop(permit);
+
+        // A reservation larger than the budget is granted when nothing else is reserved.
+        let permit = budget.acquire(150);
+        assert_eq!(budget.used(), 150);
+        drop(permit);
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn test_spill_traces() {
+        let traces = vec![
+            ("Cpu".to_string(), RowMajorMatrix::new((0..64u32).collect(), 4)),
+            ("Program".to_string(), RowMajorMatrix::new(vec![7u32; 16], 2)),
+        ];
+        let budget = MemoryBudget::new(0);
+        let backing = TraceBacking::Disk(std::env::temp_dir());
+        let spilled = ShardTraces::new(traces.clone(), &budget, &backing).unwrap();
+        let ShardTraces::Disk(mapped, bytes) = &spilled else { panic!("traces not spilled") };
+        assert_eq!(*bytes, shard_memory(&traces));
+        assert_eq!(mapped[0].1.values(), traces[0].1.values.as_slice());
+        assert_eq!(mapped[1].1.width(), 2);
+        assert_eq!(budget.used(), 0);
+
+        let (loaded, permit) = spilled.load(&budget);
+        assert_eq!(loaded, traces);
+        assert_eq!(budget.used(), shard_memory(&traces));
+        drop(permit);
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn test_run_stage() {
+        let (tx, rx) = sync_channel(1);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let producer = thread::spawn(move || {
+            for i in 0..16 {
+                tx.send(i).unwrap();
+            }
+        });
+        let mut outputs = run_stage(rx, 4, |i: u32| {
+            let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            max_in_flight.fetch_max(n, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            i * 2
+        });
+        producer.join().unwrap();
+
+        outputs.sort_unstable();
+        assert_eq!(outputs, (0..16).map(|i| i * 2).collect::<Vec<_>>());
+        assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+    }
+}
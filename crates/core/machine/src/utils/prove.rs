@@ -5,11 +5,17 @@ use serde::{de::DeserializeOwned, Serialize};
 use size::Size;
 use std::thread::ScopedJoinHandle;
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::{
         Seek, {self},
     },
-    sync::{mpsc::sync_channel, Arc, Mutex},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{sync_channel, Sender},
+        Arc, Mutex,
+    },
 };
 use thiserror::Error;
 use web_time::Instant;
@@ -20,25 +26,30 @@ use zkm_stark::{
 use p3_field::PrimeField32;
 use p3_koala_bear::KoalaBear;
 
-use crate::shape::CoreShapeConfig;
+use crate::shape::{CoreShapeConfig, CoreShapeError};
 use crate::{
-    io::ZKMStdin,
-    utils::{chunk_vec, concurrency::TurnBasedSync},
+    io::{DeserializeError, ZKMStdin},
+    utils::{
+        concurrency::TurnBasedSync,
+        pipeline::{run_stage, MemoryBudget, ShardTraces},
+        tune::tune_shards,
+    },
 };
 use zkm_core_executor::{
     events::{format_table_line, sorted_table_lines},
+    load_trace_dump_shard,
     subproof::NoOpSubproofVerifier,
     ExecutionError, ExecutionRecord, ExecutionReport, ExecutionState, Executor, Program,
-    ZKMContext,
+    TraceDumpError, ZKMContext,
 };
 use zkm_primitives::io::ZKMPublicValues;
 
 use zkm_stark::{
     air::{MachineAir, PublicValues},
     Com, CpuProver, DebugConstraintBuilder, LookupBuilder, MachineProof, MachineProver,
-    MachineRecord, OpeningProof, PcsProverData, ProverConstraintFolder, StarkGenericConfig,
-    StarkMachine, StarkProvingKey, StarkVerifyingKey, UniConfig, Val, VerifierConstraintFolder,
-    ZKMCoreOpts,
+    MachineRecord, OpeningProof, PcsProverData, ProgressEvent, ProverConstraintFolder,
+    ProverThreadPool, ShardProof, StarkGenericConfig, StarkMachine, StarkProvingKey,
+    StarkVerifyingKey, TraceBacking, UniConfig, Val, VerifierConstraintFolder, ZKMCoreOpts,
 };
 
 #[derive(Error, Debug)]
@@ -49,6 +60,39 @@ pub enum ZKMCoreProverError {
     IoError(io::Error),
     #[error("serialization error: {0}")]
     SerializationError(bincode::Error),
+    #[error("deserialization error: {0}")]
+    DeserializationError(DeserializeError),
+    #[error("shard {0} fits no allowed shape: {1}")]
+    ShapeError(u32, CoreShapeError),
+    #[error("dynamic shapes require vk verification to be disabled with VERIFY_VK=false")]
+    DynamicShapesWithVkVerification,
+}
+
+/// Progress metadata for a shard proof delivered by [`prove_with_context_streaming`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardProgress {
+    /// The index of the shard, starting at 1.
+    pub shard: u32,
+    /// The number of cycles executed in the shard. Zero for shards without cpu events, such as
+    /// the deferred precompile and memory shards.
+    pub cycles: u64,
+    /// The number of shards proven so far, including this one.
+    pub num_proven: usize,
+}
+
+/// A cache of shard proofs, keyed by the digest of the execution record of their shard, see
+/// [`ExecutionRecord::digest`].
+///
+/// Shards with the same record have the same traces, so [`prove_with_context_streaming`] reuses
+/// the cached proof of a shard whose record did not change instead of proving it again. Proofs
+/// are only valid for the proving key they were generated with, so a cache must only be shared by
+/// the proofs of one program.
+pub trait ShardProofCache<SC: StarkGenericConfig>: Send + Sync {
+    /// Returns the cached proof of the shard whose record has the digest `digest`.
+    fn get(&self, digest: &[u8; 32]) -> Option<ShardProof<SC>>;
+
+    /// Caches the proof of the shard whose record has the digest `digest`.
+    fn insert(&self, digest: [u8; 32], proof: &ShardProof<SC>);
 }
 
 pub fn prove_simple<SC: StarkGenericConfig, P: MachineProver<SC, MipsAir<SC::Val>>>(
@@ -137,13 +181,81 @@ where
     Com<SC>: Send + Sync,
     PcsProverData<SC>: Send + Sync,
 {
+    prove_with_context_streaming::<SC, P>(
+        prover,
+        pk,
+        program,
+        stdin,
+        opts,
+        context,
+        shape_config,
+        &TraceBacking::from_env(),
+        &ProverThreadPool::default(),
+        None,
+        BTreeMap::new(),
+        None,
+    )
+}
+
+/// Like [`prove_with_context`], but additionally sends a copy of each shard proof to `shard_tx`
+/// as soon as it is proven.
+///
+/// Shards are proven concurrently, so the proofs may arrive out of order; use
+/// [`ShardProgress::shard`] to restore the order. Proving continues if the receiver is dropped.
+///
+/// The traces of the shards waiting to be proven are kept according to `trace_backing`, the
+/// execution and proven shards are reported to the progress handler of `context`, and the traces
+/// are generated, committed to and opened on `thread_pool`.
+///
+/// The shards in `proven`, keyed by shard index, are not proven again: their traces are not
+/// generated and their given proofs are used instead. They are not sent to `shard_tx`. The caller
+/// is responsible for the given proofs belonging to the same program, input and options. The shard
+/// parameters picked with [`ZKMCoreOpts::auto_tune`] only depend on these and on the memory of the
+/// machine.
+///
+/// If `shard_cache` is given, the shards whose execution record is cached are not proven again
+/// either, and the proofs of the other shards are added to the cache.
+///
+/// A shard fitting no shape of `shape_config` fails the proof with
+/// [`ZKMCoreProverError::ShapeError`], unless [`ZKMCoreOpts::dynamic_shapes`] is set.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_with_context_streaming<
+    SC: StarkGenericConfig,
+    P: MachineProver<SC, MipsAir<SC::Val>>,
+>(
+    prover: &P,
+    pk: &P::DeviceProvingKey,
+    program: Program,
+    stdin: &ZKMStdin,
+    mut opts: ZKMCoreOpts,
+    context: ZKMContext,
+    shape_config: Option<&CoreShapeConfig<SC::Val>>,
+    trace_backing: &TraceBacking,
+    thread_pool: &ProverThreadPool,
+    shard_tx: Option<Sender<(ShardProgress, ShardProof<SC>)>>,
+    proven: BTreeMap<u32, ShardProof<SC>>,
+    shard_cache: Option<&dyn ShardProofCache<SC>>,
+) -> Result<(MachineProof<SC>, Vec<u8>, u64), ZKMCoreProverError>
+where
+    SC::Val: PrimeField32,
+    SC::Challenger: 'static + Clone + Send,
+    OpeningProof<SC>: Send,
+    Com<SC>: Send + Sync,
+    PcsProverData<SC>: Send + Sync,
+{
+    // Pick the shard parameters before the program is cut into shards.
+    let shard_tuning = if opts.auto_tune { tune_shards(&program, stdin, &mut opts) } else { None };
+
     // Setup the runtime.
+    let progress = &context.progress.clone();
     let mut runtime = Executor::with_context(program.clone(), opts, context);
     runtime.maximal_shapes = shape_config.map(|config| {
         config.maximal_core_shapes(opts.shard_size.ilog2() as usize).into_iter().collect()
     });
 
     runtime.write_vecs(&stdin.buffer);
+    runtime.write_files(&stdin.files);
+    runtime.write_named_inputs(&stdin.named_inputs);
     for proof in stdin.proofs.iter() {
         let (proof, vk) = proof.clone();
         runtime.write_proof(proof, vk);
@@ -152,6 +264,17 @@ where
     #[cfg(feature = "debug")]
     let (all_records_tx, all_records_rx) = std::sync::mpsc::channel::<Vec<ExecutionRecord>>();
 
+    // The trace generators skip the shards which are already proven, or whose proof is cached.
+    let proven = &Mutex::new(proven);
+
+    // The number of shards, known once the last checkpoint is traced.
+    let num_shards = AtomicUsize::new(0);
+    let num_shards = &num_shards;
+
+    // The first shard fitting no shape, which stops the execution and the trace generation.
+    let shape_error = Mutex::new(None);
+    let shape_error = &shape_error;
+
     // Record the start of the process.
     let proving_start = Instant::now();
     let span = tracing::Span::current().clone();
@@ -188,9 +311,14 @@ where
                         checkpoints_tx
                             .send((index, checkpoint_file, done, runtime.state.global_clk))
                             .unwrap();
-
-                        // If we've reached the final checkpoint, break out of the loop.
-                        if done {
+                        progress.report(ProgressEvent::Execution {
+                            cycles: runtime.state.global_clk,
+                            done,
+                        });
+
+                        // If we've reached the final checkpoint, or a shard fits no shape, break
+                        // out of the loop.
+                        if done || shape_error.lock().unwrap().is_some() {
                             break Ok(runtime.state.public_values_stream);
                         }
 
@@ -209,12 +337,14 @@ where
         let p2_trace_gen_sync = Arc::new(TurnBasedSync::new());
         let checkpoints_rx = Arc::new(Mutex::new(checkpoints_rx));
         let (p2_records_and_traces_tx, p2_records_and_traces_rx) =
-            sync_channel::<(Vec<ExecutionRecord>, Vec<Vec<(String, RowMajorMatrix<Val<SC>>)>>)>(
+            sync_channel::<(ExecutionRecord, ShardTraces<Val<SC>>, Option<[u8; 32]>)>(
                 opts.records_and_traces_channel_capacity,
             );
         let p2_records_and_traces_tx = Arc::new(Mutex::new(p2_records_and_traces_tx));
+        let memory_budget = MemoryBudget::new(opts.memory_budget);
 
-        let report_aggregate = Arc::new(Mutex::new(ExecutionReport::default()));
+        let report_aggregate =
+            Arc::new(Mutex::new(ExecutionReport { shard_tuning, ..Default::default() }));
         let state = Arc::new(Mutex::new(PublicValues::<u32, u32>::default().reset()));
         let deferred = Arc::new(Mutex::new(ExecutionRecord::new(program.clone().into())));
         let mut p2_record_and_trace_gen_handles = Vec::new();
@@ -223,6 +353,7 @@ where
             let trace_gen_sync = Arc::clone(&p2_trace_gen_sync);
             let records_and_traces_tx = Arc::clone(&p2_records_and_traces_tx);
             let checkpoints_rx = Arc::clone(&checkpoints_rx);
+            let memory_budget = Arc::clone(&memory_budget);
 
             let report_aggregate = Arc::clone(&report_aggregate);
             let state = Arc::clone(&state);
@@ -323,11 +454,13 @@ where
                                 // Generate the dependencies.
                                 tracing::debug_span!("generate dependencies", index).in_scope(
                                     || {
-                                        prover.machine().generate_dependencies(
-                                            &mut records_clone,
-                                            &opts,
-                                            None,
-                                        );
+                                        thread_pool.install(|| {
+                                            prover.machine().generate_dependencies(
+                                                &mut records_clone,
+                                                &opts,
+                                                None,
+                                            )
+                                        });
                                     },
                                 );
 
@@ -376,11 +509,13 @@ where
                                 // Generate the dependencies.
                                 tracing::debug_span!("generate dependencies", index).in_scope(
                                     || {
-                                        prover.machine().generate_dependencies(
-                                            &mut records,
-                                            &opts,
-                                            None,
-                                        );
+                                        thread_pool.install(|| {
+                                            prover.machine().generate_dependencies(
+                                                &mut records,
+                                                &opts,
+                                                None,
+                                            )
+                                        });
                                     },
                                 );
 
@@ -390,40 +525,85 @@ where
                                 // Fix the shape of the records.
                                 if let Some(shape_config) = shape_config {
                                     for record in records.iter_mut() {
-                                        shape_config.fix_shape(record).unwrap();
+                                        match shape_config.fix_shape(record) {
+                                            Ok(()) => {}
+                                            Err(err) if opts.dynamic_shapes => {
+                                                tracing::warn!(
+                                                    "shard {} fits no shape, proving it with a \
+                                                     dynamic shape: {err}",
+                                                    record.public_values.shard
+                                                );
+                                                record.shape = None;
+                                            }
+                                            Err(err) => {
+                                                let shard = record.public_values.shard;
+                                                shape_error.lock().unwrap().get_or_insert(
+                                                    ZKMCoreProverError::ShapeError(shard, err),
+                                                );
+                                            }
+                                        }
                                     }
                                 }
                                 shape_fixed_records = Some(records);
                             }
 
-                            let records = shape_fixed_records.unwrap();
+                            // Once a shard fits no shape, the proof fails, so the remaining shards
+                            // are not traced.
+                            let mut records = shape_fixed_records.unwrap();
+                            if shape_error.lock().unwrap().is_some() {
+                                records.clear();
+                            }
+                            if done {
+                                num_shards.store(state.shard as usize, Ordering::Relaxed);
+                            }
 
                             #[cfg(feature = "debug")]
                             all_records_tx.send(records.clone()).unwrap();
 
                             let mut main_traces = Vec::new();
+                            let mut digests = Vec::new();
                             tracing::debug_span!("generate main traces", index).in_scope(|| {
-                                main_traces = records
-                                    .par_iter()
-                                    .map(|record| prover.generate_traces(record))
-                                    .collect::<Vec<_>>();
+                                (main_traces, digests) = thread_pool.install(|| {
+                                    records
+                                        .par_iter()
+                                        .map(|record| {
+                                            let shard = record.public_values.shard;
+                                            if proven.lock().unwrap().contains_key(&shard) {
+                                                return (Vec::new(), None);
+                                            }
+                                            let digest = shard_cache.map(|_| record.digest());
+                                            let cached = shard_cache
+                                                .zip(digest.as_ref())
+                                                .and_then(|(cache, digest)| cache.get(digest));
+                                            if let Some(proof) = cached {
+                                                tracing::debug!(
+                                                    "reusing the cached proof of shard {shard}"
+                                                );
+                                                proven.lock().unwrap().insert(shard, proof);
+                                                return (Vec::new(), None);
+                                            }
+                                            (prover.generate_traces(record), digest)
+                                        })
+                                        .unzip()
+                                });
                             });
 
                             trace_gen_sync.wait_for_turn(index);
 
-                            // Send the records to the phase 2 prover.
-                            let chunked_records = chunk_vec(records, opts.shard_batch_size);
-                            let chunked_main_traces = chunk_vec(main_traces, opts.shard_batch_size);
-                            chunked_records
-                                .into_iter()
-                                .zip(chunked_main_traces.into_iter())
-                                .for_each(|(records, main_traces)| {
-                                    records_and_traces_tx
-                                        .lock()
-                                        .unwrap()
-                                        .send((records, main_traces))
-                                        .unwrap();
-                                });
+                            // Send the records to the phase 2 prover, once their traces fit in
+                            // the memory budget or are spilled to disk.
+                            for ((record, main_traces), digest) in
+                                records.into_iter().zip(main_traces).zip(digests)
+                            {
+                                let traces =
+                                    ShardTraces::new(main_traces, &memory_budget, trace_backing)
+                                        .expect("failed to spill the traces");
+                                records_and_traces_tx
+                                    .lock()
+                                    .unwrap()
+                                    .send((record, traces, digest))
+                                    .unwrap();
+                            }
 
                             trace_gen_sync.advance_turn();
                         } else {
@@ -442,50 +622,74 @@ where
         let p2_prover_span = tracing::Span::current().clone();
         let p2_prover_handle = s.spawn(move || {
             let _span = p2_prover_span.enter();
-            let mut shard_proofs = Vec::new();
-            tracing::debug_span!("phase 2 prover").in_scope(|| {
-                for (records, traces) in p2_records_and_traces_rx.into_iter() {
-                    tracing::debug_span!("batch").in_scope(|| {
-                        let span = tracing::Span::current().clone();
-                        shard_proofs.par_extend(
-                            records.into_par_iter().zip(traces.into_par_iter()).map(
-                                |(record, main_traces)| {
-                                    let _span = span.enter();
-
-                                    let main_data = prover.commit(&record, main_traces);
-
-                                    let opening_span = tracing::debug_span!("opening").entered();
-                                    let proof = prover
-                                        .open(pk, main_data, &mut challenger.clone())
-                                        .unwrap();
-                                    opening_span.exit();
-
-                                    #[cfg(debug_assertions)]
-                                    {
-                                        if let Some(ref shape) = record.shape {
-                                            assert_eq!(
-                                                proof.shape(),
-                                                shape
-                                                    .clone()
-                                                    .into_iter()
-                                                    .map(|(k, v)| (k.to_string(), v as usize))
-                                                    .collect(),
-                                            );
-                                        }
-                                    }
+            let num_proven = AtomicUsize::new(0);
+            let mut shard_proofs = tracing::debug_span!("phase 2 prover").in_scope(|| {
+                // Every worker commits to and opens its own shard, so the shards are at different
+                // stages of the proof.
+                run_stage(
+                    p2_records_and_traces_rx,
+                    opts.shard_prover_workers,
+                    |(record, traces, digest)| {
+                        let shard = record.public_values.shard;
+                        if let Some(proof) = proven.lock().unwrap().remove(&shard) {
+                            return (shard, proof);
+                        }
+                        let (main_traces, permit) = traces.load(&memory_budget);
+
+                        let cycles = record.cpu_events.len() as u64;
+                        let mut challenger = challenger.clone();
+                        let proof = thread_pool.install(|| {
+                            let main_data = tracing::debug_span!("commit", shard)
+                                .in_scope(|| prover.commit(&record, main_traces));
+
+                            let opening_span = tracing::debug_span!("opening", shard).entered();
+                            let proof = prover.open(pk, main_data, &mut challenger).unwrap();
+                            opening_span.exit();
+                            proof
+                        });
+                        drop(permit);
+
+                        #[cfg(debug_assertions)]
+                        {
+                            if let Some(ref shape) = record.shape {
+                                assert_eq!(
+                                    proof.shape(),
+                                    shape
+                                        .clone()
+                                        .into_iter()
+                                        .map(|(k, v)| (k.to_string(), v as usize))
+                                        .collect(),
+                                );
+                            }
+                        }
 
-                                    rayon::spawn(move || {
-                                        drop(record);
-                                    });
+                        rayon::spawn(move || {
+                            drop(record);
+                        });
+                        if let Some((cache, digest)) = shard_cache.zip(digest) {
+                            cache.insert(digest, &proof);
+                        }
 
-                                    proof
-                                },
-                            ),
-                        );
-                    });
-                }
+                        let num_proven = num_proven.fetch_add(1, Ordering::Relaxed) + 1;
+                        let total = num_shards.load(Ordering::Relaxed);
+                        progress.report(ProgressEvent::CoreShard {
+                            shard,
+                            num_proven,
+                            num_shards: (total > 0).then_some(total),
+                        });
+                        if let Some(shard_tx) = &shard_tx {
+                            let progress = ShardProgress { shard, cycles, num_proven };
+                            // Ignore a dropped receiver: the complete proof is still returned.
+                            let _ = shard_tx.send((progress, proof.clone()));
+                        }
+
+                        (shard, proof)
+                    },
+                )
             });
-            shard_proofs
+            // The workers complete the shards out of order.
+            shard_proofs.sort_unstable_by_key(|(shard, _)| *shard);
+            shard_proofs.into_iter().map(|(_, proof)| proof).collect::<Vec<_>>()
         });
 
         // Wait until the checkpoint generator handle has fully finished.
@@ -496,6 +700,9 @@ where
 
         // Wait until the phase 2 prover has finished.
         let shard_proofs = p2_prover_handle.join().unwrap();
+        if let Some(err) = shape_error.lock().unwrap().take() {
+            return Err(err);
+        }
 
         // Log some of the `ExecutionReport` information.
         let report_aggregate = report_aggregate.lock().unwrap();
@@ -505,6 +712,13 @@ where
             report_aggregate.total_syscall_count(),
             report_aggregate.touched_memory_addresses,
         );
+        if let Some(tuning) = &report_aggregate.shard_tuning {
+            tracing::info!(
+                "execution report (shard tuning): shard_size={}, shard_batch_size={}",
+                tuning.shard_size,
+                tuning.shard_batch_size,
+            );
+        }
 
         // Print the opcode and syscall count tables like `du`: sorted by count (descending) and
         // with the count in the first column.
@@ -563,6 +777,8 @@ pub fn run_test_io<P: MachineProver<KoalaBearPoseidon2, MipsAir<KoalaBear>>>(
     let runtime = tracing::debug_span!("runtime.run(...)").in_scope(|| {
         let mut runtime = Executor::new(program, ZKMCoreOpts::default());
         runtime.write_vecs(&inputs.buffer);
+        runtime.write_files(&inputs.files);
+        runtime.write_named_inputs(&inputs.named_inputs);
         runtime.run().unwrap();
         runtime
     });
@@ -709,6 +925,21 @@ where
     (records, runtime.report)
 }
 
+/// Loads `shard` from a dump written by [Executor::run_with_trace_dump], regenerates its traces
+/// and checks the constraints of every chip on it, panicking on the first failing one.
+pub fn debug_dumped_shard(path: impl AsRef<Path>, shard: u32) -> Result<(), TraceDumpError> {
+    let record = load_trace_dump_shard(path, shard)?;
+
+    let machine = MipsAir::machine(KoalaBearPoseidon2::new());
+    let (pk, _) = machine.setup(record.program.as_ref());
+    let mut records = vec![record];
+    machine.generate_dependencies(&mut records, &ZKMCoreOpts::default(), None);
+
+    let mut challenger = machine.config().challenger();
+    machine.debug_shard(&pk, &records[0], &mut challenger);
+    Ok(())
+}
+
 fn reset_seek(file: &mut File) {
     file.seek(std::io::SeekFrom::Start(0)).expect("failed to seek to start of tempfile");
 }
@@ -777,6 +1008,67 @@ where
     p3_uni_stark::verify(&UniConfig(config.clone()), air, challenger, proof, &vec![])
 }
 
+/// A proof of a single chip's trace, generated by [prove_single_chip].
+pub type ChipProof<SC> = Proof<UniConfig<SC>>;
+
+/// Generates the trace of `chip` from `record` and proves it in isolation.
+///
+/// This lets chip developers iterate on the constraints and trace generation of one AIR without
+/// running the full machine. The proof can be checked with [verify_single_chip].
+#[cfg(not(doctest))]
+pub fn prove_single_chip<C>(chip: &C, record: &ExecutionRecord) -> ChipProof<KoalaBearPoseidon2>
+where
+    C: MachineAir<KoalaBear, Record = ExecutionRecord>
+        + Air<p3_uni_stark::SymbolicAirBuilder<KoalaBear>>
+        + for<'a> Air<p3_uni_stark::ProverConstraintFolder<'a, UniConfig<KoalaBearPoseidon2>>>
+        + for<'a> Air<p3_uni_stark::DebugConstraintBuilder<'a, KoalaBear>>,
+{
+    let config = KoalaBearPoseidon2::new();
+    let mut challenger = config.challenger();
+    let trace = chip.generate_trace(record, &mut ExecutionRecord::default());
+    uni_stark_prove(&config, chip, &mut challenger, trace)
+}
+
+/// Verifies a proof generated by [prove_single_chip].
+#[cfg(not(doctest))]
+pub fn verify_single_chip<C>(
+    chip: &C,
+    proof: &ChipProof<KoalaBearPoseidon2>,
+) -> Result<
+    (),
+    p3_uni_stark::VerificationError<p3_uni_stark::PcsError<UniConfig<KoalaBearPoseidon2>>>,
+>
+where
+    C: Air<p3_uni_stark::SymbolicAirBuilder<KoalaBear>>
+        + for<'a> Air<p3_uni_stark::VerifierConstraintFolder<'a, UniConfig<KoalaBearPoseidon2>>>
+        + for<'a> Air<p3_uni_stark::DebugConstraintBuilder<'a, KoalaBear>>,
+{
+    let config = KoalaBearPoseidon2::new();
+    let mut challenger = config.challenger();
+    uni_stark_verify(&config, chip, &mut challenger, proof)
+}
+
 use p3_air::Air;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_uni_stark::Proof;
+
+#[cfg(test)]
+mod tests {
+    use zkm_core_executor::{events::AluEvent, ExecutionRecord, Opcode};
+
+    use super::*;
+    use crate::alu::{AddSubChip, BitwiseChip};
+
+    #[test]
+    fn test_prove_single_chip() {
+        let mut shard = ExecutionRecord::default();
+        shard.add_sub_events =
+            (0..100).map(|i| AluEvent::new(i << 2, Opcode::ADD, 2 * i + 1, i, i + 1)).collect();
+        let chip = AddSubChip::default();
+        let proof = prove_single_chip(&chip, &shard);
+        verify_single_chip(&chip, &proof).unwrap();
+
+        // The proof does not verify against the constraints of another chip.
+        assert!(verify_single_chip(&BitwiseChip::default(), &proof).is_err());
+    }
+}
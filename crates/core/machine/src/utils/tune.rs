@@ -0,0 +1,87 @@
+//! Auto-tuning of the shard parameters of the core prover, see [`ZKMCoreOpts::tune_shards`].
+
+use std::sync::mpsc::channel;
+
+use hashbrown::HashMap;
+use zkm_core_executor::{
+    estimate_mips_lde_size, mips_costs, Executor, MipsAirId, Program, ZKMContext,
+};
+use zkm_stark::{ShardTuning, ZKMCoreOpts};
+
+use crate::{io::ZKMStdin, utils::pipeline::SHARD_MEMORY_FACTOR};
+
+/// The number of cycles of the pre-execution profiling the chips of a program.
+pub const TUNING_SAMPLE_CYCLES: u64 = 1 << 20;
+
+/// Picks the shard parameters of `opts` for [`ZKMCoreOpts::tuning_memory`], from the chip heights
+/// of the first [`TUNING_SAMPLE_CYCLES`] cycles of the execution of `program` on `stdin`.
+///
+/// Returns `None` and leaves `opts` unchanged if the pre-execution fails before the end of the
+/// sample.
+pub fn tune_shards(
+    program: &Program,
+    stdin: &ZKMStdin,
+    opts: &mut ZKMCoreOpts,
+) -> Option<ShardTuning> {
+    // The chip heights are estimated for the current shard, so the sample must fit in one.
+    let sample_opts = ZKMCoreOpts { shard_size: 1 << 22, ..*opts };
+    let (sample_tx, sample_rx) = channel();
+    let context = ZKMContext::builder()
+        .opcode_sample(TUNING_SAMPLE_CYCLES, sample_tx)
+        .max_cycles(TUNING_SAMPLE_CYCLES)
+        .build();
+    let mut runtime = Executor::with_context(program.clone(), sample_opts, context);
+    runtime.write_vecs(&stdin.buffer);
+    runtime.write_files(&stdin.files);
+    runtime.write_named_inputs(&stdin.named_inputs);
+    for (proof, vk) in stdin.proofs.iter() {
+        runtime.write_proof(proof.clone(), vk.clone());
+    }
+    // The pre-execution stops at the cycle limit, after sending the sample.
+    let _ = runtime.run_fast();
+    drop(runtime);
+
+    let Ok(sample) = sample_rx.try_recv() else {
+        tracing::warn!("failed to profile the program, keeping the shard parameters");
+        return None;
+    };
+    let costs: HashMap<MipsAirId, u64> =
+        mips_costs().into_iter().map(|(air, cost)| (air, cost as u64)).collect();
+    let cycles = sample.event_counts[MipsAirId::Cpu].max(1);
+    let shard_memory = |shard_size: usize| {
+        let mut event_counts = sample.event_counts;
+        for count in event_counts.values_mut() {
+            *count = count.saturating_mul(shard_size as u64) / cycles;
+        }
+        // The estimate is of the traces after a blowup of 2.
+        estimate_mips_lde_size(event_counts, &costs) as usize / 2 * SHARD_MEMORY_FACTOR
+    };
+    let tuning = opts.tune_shards(opts.tuning_memory(), shard_memory);
+    tracing::info!(
+        "tuned shards for {} bytes: shard_size={}, shard_batch_size={}, shard_memory={}",
+        tuning.memory,
+        tuning.shard_size,
+        tuning.shard_batch_size,
+        tuning.shard_memory,
+    );
+    Some(tuning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::programs::tests::fibonacci_program;
+
+    use super::*;
+
+    #[test]
+    fn test_tune_shards() {
+        let mut opts = ZKMCoreOpts { memory_budget: 1 << 34, ..ZKMCoreOpts::default() };
+        let tuning = tune_shards(&fibonacci_program(), &ZKMStdin::new(), &mut opts).unwrap();
+        assert_eq!(tuning.shard_size, opts.shard_size);
+        assert_eq!(tuning.memory, 1 << 34);
+        assert!(
+            tuning.shard_memory * (opts.shard_prover_workers + opts.trace_gen_workers)
+                <= tuning.memory
+        );
+    }
+}
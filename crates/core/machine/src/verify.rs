@@ -0,0 +1,326 @@
+//! Verification of core proofs without the prover.
+
+use std::{borrow::Borrow, iter::once};
+
+use p3_field::FieldAlgebra;
+use p3_koala_bear::KoalaBear;
+use zkm_core_executor::ContinuationState;
+use zkm_primitives::consts::WORD_SIZE;
+use zkm_stark::{
+    air::{LookupScope, PublicValues, POSEIDON_NUM_WORDS, PV_DIGEST_NUM_WORDS},
+    koala_bear_poseidon2::KoalaBearPoseidon2,
+    septic_digest::SepticDigest,
+    MachineVerificationError, ShardProof, StarkGenericConfig, StarkVerifyingKey, Word,
+};
+
+use crate::{cpu::MAX_CPU_LOG_DEGREE, mips::MipsAir};
+
+/// The maximum number of shards in a core proof.
+pub const MAX_NUM_SHARDS: usize = 1 << 16;
+
+/// Checks that the public values of a sequence of shard proofs are contiguous and complete.
+///
+/// The shards are passed to [`Self::check`] in order, one at a time.
+pub struct ShardSequenceChecker {
+    num_shards: usize,
+    index: usize,
+    start_pc: KoalaBear,
+    end_pc: KoalaBear,
+    current_shard: KoalaBear,
+    current_execution_shard: KoalaBear,
+    prev_next_pc: KoalaBear,
+    last_init_addr_bits_prev: [KoalaBear; 32],
+    last_finalize_addr_bits_prev: [KoalaBear; 32],
+    committed_value_digest_prev: [Word<KoalaBear>; PV_DIGEST_NUM_WORDS],
+    deferred_proofs_digest_prev: [KoalaBear; POSEIDON_NUM_WORDS],
+}
+
+impl ShardSequenceChecker {
+    /// Creates a checker for a sequence of `num_shards` shards of a program starting at
+    /// `start_pc`.
+    pub fn new(start_pc: KoalaBear, num_shards: usize) -> Self {
+        Self {
+            num_shards,
+            index: 0,
+            start_pc,
+            end_pc: KoalaBear::ZERO,
+            current_shard: KoalaBear::ZERO,
+            current_execution_shard: KoalaBear::ZERO,
+            prev_next_pc: KoalaBear::ZERO,
+            last_init_addr_bits_prev: [KoalaBear::ZERO; 32],
+            last_finalize_addr_bits_prev: [KoalaBear::ZERO; 32],
+            committed_value_digest_prev: [Word([KoalaBear::ZERO; WORD_SIZE]); PV_DIGEST_NUM_WORDS],
+            deferred_proofs_digest_prev: [KoalaBear::ZERO; POSEIDON_NUM_WORDS],
+        }
+    }
+
+    /// Expects the sequence to pause at `end_pc` at the end of a continuation segment, instead of
+    /// halting.
+    #[must_use]
+    pub fn pausing_at(mut self, end_pc: KoalaBear) -> Self {
+        self.end_pc = end_pc;
+        self
+    }
+
+    /// Checks the next shard proof of the sequence.
+    #[allow(clippy::too_many_lines)]
+    pub fn check(
+        &mut self,
+        shard_proof: &ShardProof<KoalaBearPoseidon2>,
+    ) -> Result<(), MachineVerificationError<KoalaBearPoseidon2>> {
+        let i = self.index;
+        self.index += 1;
+        let public_values: &PublicValues<Word<_>, _> =
+            shard_proof.public_values.as_slice().borrow();
+
+        // First shard has a "CPU" constraint.
+        //
+        // Check that the first shard has a "CPU".
+        if i == 0 && !shard_proof.contains_cpu() {
+            return Err(MachineVerificationError::MissingCpuInFirstShard);
+        }
+
+        // CPU log degree bound constraints.
+        //
+        // Check that the CPU log degree does not exceed `MAX_CPU_LOG_DEGREE`. This is to ensure
+        // that the lookup argument's multiplicities do not overflow.
+        if shard_proof.contains_cpu() {
+            let log_degree_cpu = shard_proof.log_degree_cpu();
+            if log_degree_cpu > MAX_CPU_LOG_DEGREE {
+                return Err(MachineVerificationError::CpuLogDegreeTooLarge(log_degree_cpu));
+            }
+        }
+
+        // Shard constraints.
+        //
+        // Initialization:
+        // - Shard should start at one.
+        //
+        // Transition:
+        // - Shard should increment by one for each shard.
+        self.current_shard += KoalaBear::ONE;
+        if public_values.shard != self.current_shard {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "shard index should be the previous shard index + 1 and start at 1",
+            ));
+        }
+
+        // Execution shard constraints.
+        //
+        // Initialization:
+        // - Execution shard should start at one.
+        //
+        // Transition:
+        // - Execution shard should increment by one for each shard with "CPU".
+        // - Execution shard should stay the same for non-CPU shards.
+        // - For the other shards, execution shard does not matter.
+        if shard_proof.contains_cpu() {
+            self.current_execution_shard += KoalaBear::ONE;
+            if public_values.execution_shard != self.current_execution_shard {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "execution shard index should be the previous execution shard index + 1 if cpu exists and start at 1",
+                ));
+            }
+        }
+
+        // Program counter constraints.
+        //
+        // Initialization:
+        // - `start_pc` should start as `vk.start_pc`.
+        //
+        // Transition:
+        // - `next_pc` of the previous shard should equal `start_pc`.
+        // - If it's not a shard with "CPU", then `start_pc` equals `next_pc`.
+        // - If it's a shard with "CPU", then `start_pc` should never equal zero.
+        //
+        // Finalization:
+        // - `next_pc` should equal zero, or the end pc of a continuation segment.
+        if i == 0 && public_values.start_pc != self.start_pc {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "start_pc != vk.start_pc: program counter should start at vk.start_pc",
+            ));
+        } else if i != 0 && public_values.start_pc != self.prev_next_pc {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "start_pc != next_pc_prev: start_pc should equal next_pc_prev for all shards",
+            ));
+        } else if !shard_proof.contains_cpu() && public_values.start_pc != public_values.next_pc {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "start_pc != next_pc: start_pc should equal next_pc for non-cpu shards",
+            ));
+        } else if shard_proof.contains_cpu() && public_values.start_pc == KoalaBear::ZERO {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "start_pc == 0: execution should never start at halted state",
+            ));
+        } else if i == self.num_shards - 1 && public_values.next_pc != self.end_pc {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                if self.end_pc == KoalaBear::ZERO {
+                    "next_pc != 0: execution should have halted"
+                } else {
+                    "next_pc != end_pc: segment should pause at the pc of its end state"
+                },
+            ));
+        }
+        self.prev_next_pc = public_values.next_pc;
+
+        // Exit code constraints.
+        //
+        // - In every shard, the exit code should be zero.
+        if public_values.exit_code != KoalaBear::ZERO {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "exit_code != 0: exit code should be zero for all shards",
+            ));
+        }
+
+        // Memory initialization & finalization constraints.
+        //
+        // Initialization:
+        // - `previous_init_addr_bits` should be zero.
+        // - `previous_finalize_addr_bits` should be zero.
+        //
+        // Transition:
+        // - For all shards, `previous_init_addr_bits` should equal `last_init_addr_bits` of the
+        //   previous shard.
+        // - For all shards, `previous_finalize_addr_bits` should equal `last_finalize_addr_bits` of
+        //   the previous shard.
+        // - For shards without "MemoryInit", `previous_init_addr_bits` should equal
+        //   `last_init_addr_bits`.
+        // - For shards without "MemoryFinalize", `previous_finalize_addr_bits` should equal
+        //   `last_finalize_addr_bits`.
+        if public_values.previous_init_addr_bits != self.last_init_addr_bits_prev {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "previous_init_addr_bits != last_init_addr_bits_prev",
+            ));
+        } else if public_values.previous_finalize_addr_bits != self.last_finalize_addr_bits_prev {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "last_init_addr_bits != last_finalize_addr_bits_prev",
+            ));
+        } else if !shard_proof.contains_global_memory_init()
+            && public_values.previous_init_addr_bits != public_values.last_init_addr_bits
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "previous_init_addr_bits != last_init_addr_bits",
+            ));
+        } else if !shard_proof.contains_global_memory_finalize()
+            && public_values.previous_finalize_addr_bits != public_values.last_finalize_addr_bits
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "previous_finalize_addr_bits != last_finalize_addr_bits",
+            ));
+        }
+        self.last_init_addr_bits_prev = public_values.last_init_addr_bits;
+        self.last_finalize_addr_bits_prev = public_values.last_finalize_addr_bits;
+
+        // Digest constraints.
+        //
+        // Initialization:
+        // - `committed_value_digest` should be zero.
+        // - `deferred_proofs_digest` should be zero.
+        //
+        // Transition:
+        // - If `committed_value_digest_prev` is not zero, then `committed_value_digest` should equal
+        //  `committed_value_digest_prev`. Otherwise, `committed_value_digest` should equal zero.
+        // - If `deferred_proofs_digest_prev` is not zero, then `deferred_proofs_digest` should
+        //   equal
+        //  `deferred_proofs_digest_prev`. Otherwise, `deferred_proofs_digest` should equal zero.
+        // - If it's not a shard with "CPU", then `committed_value_digest` should not change from the
+        //  previous shard.
+        // - If it's not a shard with "CPU", then `deferred_proofs_digest` should not change from
+        //   the
+        //  previous shard.
+        let zero_committed_value_digest = [Word([KoalaBear::ZERO; WORD_SIZE]); PV_DIGEST_NUM_WORDS];
+        let zero_deferred_proofs_digest = [KoalaBear::ZERO; POSEIDON_NUM_WORDS];
+        if self.committed_value_digest_prev != zero_committed_value_digest
+            && public_values.committed_value_digest != self.committed_value_digest_prev
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "committed_value_digest != committed_value_digest_prev",
+            ));
+        } else if self.deferred_proofs_digest_prev != zero_deferred_proofs_digest
+            && public_values.deferred_proofs_digest != self.deferred_proofs_digest_prev
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "deferred_proofs_digest != deferred_proofs_digest_prev",
+            ));
+        } else if !shard_proof.contains_cpu()
+            && public_values.committed_value_digest != self.committed_value_digest_prev
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "committed_value_digest != committed_value_digest_prev",
+            ));
+        } else if !shard_proof.contains_cpu()
+            && public_values.deferred_proofs_digest != self.deferred_proofs_digest_prev
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "deferred_proofs_digest != deferred_proofs_digest_prev",
+            ));
+        }
+        self.committed_value_digest_prev = public_values.committed_value_digest;
+        self.deferred_proofs_digest_prev = public_values.deferred_proofs_digest;
+
+        Ok(())
+    }
+}
+
+/// Verifies the shard proofs of a core proof of the program with the verifying key `vk`, checking
+/// that the shards are contiguous and complete.
+pub fn verify_core_proof(
+    vk: &StarkVerifyingKey<KoalaBearPoseidon2>,
+    shard_proofs: &[ShardProof<KoalaBearPoseidon2>],
+) -> Result<(), MachineVerificationError<KoalaBearPoseidon2>> {
+    verify_shards(vk, shard_proofs, None)
+}
+
+/// Verifies the shard proofs of a continuation segment which pauses in the state `end` instead of
+/// halting, see [`ContinuationState`].
+///
+/// The segment must not finalize its memory, so that its global cumulative sum commits to the
+/// memory of `end`: the sum is checked against [`ContinuationState::memory_digest`] instead of
+/// zero.
+pub fn verify_segment_proof(
+    vk: &StarkVerifyingKey<KoalaBearPoseidon2>,
+    shard_proofs: &[ShardProof<KoalaBearPoseidon2>],
+    end: &ContinuationState,
+) -> Result<(), MachineVerificationError<KoalaBearPoseidon2>> {
+    if shard_proofs.iter().any(ShardProof::contains_global_memory_finalize) {
+        return Err(MachineVerificationError::InvalidPublicValues(
+            "a paused segment should not finalize its memory",
+        ));
+    }
+    verify_shards(vk, shard_proofs, Some(end))
+}
+
+fn verify_shards(
+    vk: &StarkVerifyingKey<KoalaBearPoseidon2>,
+    shard_proofs: &[ShardProof<KoalaBearPoseidon2>],
+    end: Option<&ContinuationState>,
+) -> Result<(), MachineVerificationError<KoalaBearPoseidon2>> {
+    if shard_proofs.is_empty() {
+        return Err(MachineVerificationError::EmptyProof);
+    }
+    if shard_proofs.len() > MAX_NUM_SHARDS {
+        return Err(MachineVerificationError::TooManyShards);
+    }
+
+    let machine = MipsAir::machine(KoalaBearPoseidon2::new());
+    let mut challenger = machine.config().challenger();
+    vk.observe_into(&mut challenger);
+
+    let end_pc = end.map_or(0, |end| end.pc);
+    let mut checker = ShardSequenceChecker::new(vk.pc_start, shard_proofs.len())
+        .pausing_at(KoalaBear::from_canonical_u32(end_pc));
+    for shard_proof in shard_proofs {
+        checker.check(shard_proof)?;
+        machine.verify_shard(vk, shard_proof, &challenger)?;
+    }
+
+    let sum = shard_proofs
+        .iter()
+        .map(ShardProof::global_cumulative_sum)
+        .chain(once(vk.initial_global_cumulative_sum))
+        .chain(end.map(ContinuationState::memory_digest::<KoalaBear>))
+        .sum::<SepticDigest<KoalaBear>>();
+    if !sum.is_zero() {
+        return Err(MachineVerificationError::NonZeroCumulativeSum(LookupScope::Global, 0));
+    }
+    Ok(())
+}
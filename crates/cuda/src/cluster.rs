@@ -0,0 +1,287 @@
+//! Core proving across several GPU servers, one per device.
+
+use std::{
+    borrow::Borrow,
+    collections::VecDeque,
+    error::Error as StdError,
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use p3_field::PrimeField32;
+use p3_koala_bear::KoalaBear;
+use zkm_core_executor::{ExecutionState, Executor, Program};
+use zkm_core_machine::{
+    io::{deserialize_limited, ZKMStdin},
+    utils::ZKMCoreProverError,
+};
+use zkm_prover::{ZKMCoreProof, ZKMCoreProofData, ZKMProvingKey};
+use zkm_stark::{air::PublicValues, Word, ZKMCoreOpts};
+
+use crate::{
+    metrics, session::GpuMethod, GpuMemoryBudget, GpuStage, RetryPolicy, SupervisorPolicy,
+    ZKMCudaProver, ZKMGpuServer,
+};
+
+/// The port of the server of the first device. The server of the `i`-th device listens on
+/// `BASE_PORT + i`.
+const BASE_PORT: u64 = 3000;
+
+/// The number of shard partitions queued per device. Devices take partitions from a shared queue,
+/// so using more partitions than devices lets faster devices prove more of them.
+const PARTITIONS_PER_DEVICE: u32 = 4;
+
+/// The number of times a partition is attempted before the proof fails.
+const MAX_PARTITION_ATTEMPTS: usize = 3;
+
+/// How often idle devices check for partitions queued again after a failure.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A set of GPU servers, one per device, proving the shards of a core proof in parallel.
+///
+/// The program is executed once, on the host, and every server traces the shards from the
+/// checkpoints of that execution, but only proves the shards of the partitions it is given. A
+/// partition failing on a device is queued again for the other devices, and the device stops
+/// taking work.
+pub struct ZKMCudaCluster {
+    devices: Vec<u64>,
+    members: Vec<ZKMCudaProver>,
+}
+
+impl ZKMCudaCluster {
+    /// Starts a GPU server for each of `devices`.
+    pub fn new(devices: Vec<u64>, memory: GpuMemoryBudget) -> Result<Self, Box<dyn StdError>> {
+        if devices.is_empty() {
+            return Err("a GPU cluster needs at least one device".into());
+        }
+        let members = devices
+            .iter()
+            .enumerate()
+            .map(|(i, &device)| {
+                let mut member = ZKMCudaProver::connect(ZKMGpuServer::Local {
+                    visible_device_index: Some(device),
+                    port: Some(BASE_PORT + i as u64),
+                })?;
+                member.set_memory_budget(memory);
+                Ok::<_, Box<dyn StdError>>(member)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { devices, members })
+    }
+
+    /// The devices of the cluster.
+    pub fn devices(&self) -> &[u64] {
+        &self.devices
+    }
+
+    /// Sets the VRAM each stage needs on the servers of the cluster.
+    pub(crate) fn set_memory_budget(&mut self, memory_budget: GpuMemoryBudget) {
+        for member in &mut self.members {
+            member.set_memory_budget(memory_budget);
+        }
+    }
+
+    /// Sets how the requests to the servers of the cluster are retried.
+    pub(crate) fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        for member in &mut self.members {
+            member.set_retry_policy(retry_policy);
+        }
+    }
+
+    /// Sets how the containers of the cluster are supervised.
+    pub(crate) fn set_supervisor_policy(&mut self, supervisor_policy: SupervisorPolicy) {
+        for member in &mut self.members {
+            member.set_supervisor_policy(supervisor_policy);
+        }
+    }
+
+    /// The server handling the requests which are not split across devices.
+    pub(crate) fn primary(&self) -> &ZKMCudaProver {
+        &self.members[0]
+    }
+
+    /// Proves the core shards of the program of `pk` on `stdin`, split across the devices.
+    pub fn prove_core_stateless(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+    ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        let opts = ZKMCoreOpts::default();
+        let checkpoints = execute_checkpoints(&pk.elf, stdin, opts)?;
+        // The payloads only differ by their partition, which is serialized last: the fields before
+        // it are serialized once, as the fields of a struct are serialized in order.
+        let shared = bincode::serialize(&(pk, &checkpoints, &opts)).unwrap();
+
+        let num_partitions = self.members.len() as u32 * PARTITIONS_PER_DEVICE;
+        let queue = Mutex::new((0..num_partitions).map(|p| (p, 0)).collect::<VecDeque<_>>());
+        let remaining = AtomicUsize::new(num_partitions as usize);
+        let active = AtomicUsize::new(self.members.len());
+        let proofs = Mutex::new(Vec::new());
+        let error = Mutex::new(None);
+
+        thread::scope(|s| {
+            for (member, &device) in self.members.iter().zip(&self.devices) {
+                let (queue, remaining, active, proofs, error) =
+                    (&queue, &remaining, &active, &proofs, &error);
+                s.spawn(move || {
+                    if let Err(e) = member.check_memory(GpuStage::Core) {
+                        tracing::warn!("device {} is not used: {}", device, e);
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        return;
+                    }
+                    while remaining.load(Ordering::SeqCst) > 0 && error.lock().unwrap().is_none() {
+                        let popped = {
+                            let mut queue = queue.lock().unwrap();
+                            let popped = queue.pop_front();
+                            metrics::set_queue_depth(queue.len());
+                            popped
+                        };
+                        let Some((partition, attempts)) = popped else {
+                            thread::sleep(POLL_INTERVAL);
+                            continue;
+                        };
+                        let mut payload = shared.clone();
+                        bincode::serialize_into(&mut payload, &(partition, num_partitions))
+                            .unwrap();
+                        let result = member
+                            .call(GpuMethod::ProveCoreShards, payload)
+                            .map_err(|e| e.to_string())
+                            .and_then(|response| {
+                                deserialize_limited::<ZKMCoreProof>(
+                                    &response,
+                                    member.max_proof_size,
+                                )
+                                .map_err(|e| e.to_string())
+                            });
+                        match result {
+                            Ok(proof) => {
+                                proofs.lock().unwrap().push(proof);
+                                remaining.fetch_sub(1, Ordering::SeqCst);
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "device {} failed to prove partition {}: {}",
+                                    device,
+                                    partition,
+                                    e
+                                );
+                                if attempts + 1 >= MAX_PARTITION_ATTEMPTS {
+                                    *error.lock().unwrap() = Some(format!(
+                                        "partition {partition} failed {MAX_PARTITION_ATTEMPTS} times, last on device {device}: {e}"
+                                    ));
+                                } else {
+                                    let mut queue = queue.lock().unwrap();
+                                    queue.push_back((partition, attempts + 1));
+                                    metrics::set_queue_depth(queue.len());
+                                    metrics::retry(metrics::Retry::Partition);
+                                }
+                                // Stop using the device, the other devices retry the partition.
+                                break;
+                            }
+                        }
+                    }
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+
+            // Fail if every device stopped before all partitions were proven.
+            while remaining.load(Ordering::SeqCst) > 0 && error.lock().unwrap().is_none() {
+                if active.load(Ordering::SeqCst) == 0 {
+                    *error.lock().unwrap() =
+                        Some("every device of the GPU cluster failed".to_string());
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+
+        if let Some(e) = error.into_inner().unwrap() {
+            return Err(cluster_error(e));
+        }
+        merge_partitions(proofs.into_inner().unwrap())
+    }
+}
+
+/// Executes the program `elf` on `stdin` once, returning the checkpoints the shards are traced
+/// from, as [crate::ProveCoreShardsRequestPayload::checkpoints].
+fn execute_checkpoints(
+    elf: &[u8],
+    stdin: &ZKMStdin,
+    opts: ZKMCoreOpts,
+) -> Result<Vec<ExecutionState>, ZKMCoreProverError> {
+    let program = Program::from(elf).map_err(|e| cluster_error(e.to_string()))?;
+    let mut runtime = Executor::new(program, opts);
+    runtime.write_vecs(&stdin.buffer);
+    runtime.write_files(&stdin.files);
+    runtime.write_named_inputs(&stdin.named_inputs);
+    for (proof, vk) in stdin.proofs.iter() {
+        runtime.write_proof(proof.clone(), vk.clone());
+    }
+
+    let mut checkpoints = Vec::new();
+    loop {
+        let (checkpoint, done) =
+            runtime.execute_state(false).map_err(ZKMCoreProverError::ExecutionError)?;
+        checkpoints.push(checkpoint);
+        if done {
+            return Ok(checkpoints);
+        }
+    }
+}
+
+/// Merges the proofs of the shard partitions into the proof of all shards, ordered by shard.
+fn merge_partitions(partitions: Vec<ZKMCoreProof>) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+    let mut partitions = partitions.into_iter();
+    let mut merged = partitions.next().ok_or_else(|| cluster_error("no partition was proven"))?;
+    for partition in partitions {
+        if partition.public_values.as_slice() != merged.public_values.as_slice() {
+            return Err(cluster_error("devices disagree on the public values"));
+        }
+        merged.proof.0.extend(partition.proof.0);
+    }
+
+    let shard = |proof: &zkm_stark::ShardProof<zkm_prover::CoreSC>| {
+        let public_values: &PublicValues<Word<KoalaBear>, KoalaBear> =
+            proof.public_values.as_slice().borrow();
+        public_values.shard.as_canonical_u32()
+    };
+    let mut shards = merged.proof.0;
+    shards.sort_by_key(shard);
+    for (i, proof) in shards.iter().enumerate() {
+        if shard(proof) != i as u32 + 1 {
+            return Err(cluster_error(format!("shard {} is missing or duplicated", i + 1)));
+        }
+    }
+    merged.proof = ZKMCoreProofData(shards);
+    Ok(merged)
+}
+
+fn cluster_error(e: impl Into<Box<dyn StdError + Send + Sync>>) -> ZKMCoreProverError {
+    ZKMCoreProverError::IoError(io::Error::new(io::ErrorKind::Other, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execute_checkpoints() {
+        let opts = ZKMCoreOpts::default();
+        let checkpoints =
+            execute_checkpoints(test_artifacts::FIBONACCI_ELF, &ZKMStdin::new(), opts).unwrap();
+        assert!(!checkpoints.is_empty());
+
+        // A program which cannot be loaded or executed fails before any server is called.
+        assert!(execute_checkpoints(b"not an elf", &ZKMStdin::new(), opts).is_err());
+        assert!(execute_checkpoints(test_artifacts::PANIC_ELF, &ZKMStdin::new(), opts).is_err());
+    }
+
+    #[test]
+    fn test_merge_no_partitions() {
+        assert!(merge_partitions(Vec::new()).is_err());
+    }
+}
@@ -2,13 +2,15 @@ use std::{
     collections::HashMap,
     error::Error as StdError,
     future::Future,
-    process::{Command, Stdio},
+    io,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
     sync::LazyLock,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc, Mutex,
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use async_trait::async_trait;
@@ -21,18 +23,37 @@ use twirp::{
     url::Url,
     Client, ClientError, Middleware, Next,
 };
-use zkm_core_machine::{io::ZKMStdin, reduce::ZKMReduceProof, utils::ZKMCoreProverError};
+use zkm_core_executor::ExecutionState;
+use zkm_core_machine::{
+    io::{deserialize_limited, ZKMStdin, MAX_PROOF_SIZE},
+    reduce::ZKMReduceProof,
+    utils::ZKMCoreProverError,
+};
 use zkm_prover::{
     InnerSC, OuterSC, ZKMCoreProof, ZKMProvingKey, ZKMRecursionProverError, ZKMVerifyingKey,
 };
+use zkm_stark::ZKMCoreOpts;
+
+use crate::api::{DeviceInfoRequest, ProverServiceClient, ReadyRequest};
 
-use crate::api::{ProverServiceClient, ReadyRequest};
+mod cluster;
+mod memory;
+mod metrics;
+mod retry;
+pub mod session;
+mod supervisor;
+
+pub use cluster::ZKMCudaCluster;
+pub use memory::*;
+pub use retry::*;
+use session::{GpuMethod, SessionRecorder, SessionReplayer};
+pub use supervisor::SupervisorPolicy;
 
 pub mod api {
     include!(concat!(env!("OUT_DIR"), "/api.rs"));
 }
 
-static GPU_CONTAINERS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
+static GPU_CONTAINERS: LazyLock<Mutex<HashMap<String, Arc<CudaProverContainer>>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// A remote client to [zkm_prover::ZKMProver] that runs inside a container.
@@ -41,17 +62,144 @@ static GPU_CONTAINERS: LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
 ///
 /// **WARNING**: This is an experimental feature and may not work as expected.
 pub struct ZKMCudaProver {
-    /// The gRPC client to communicate with the container.
-    client: Client,
+    /// The GPU server, or the recording replayed in its place.
+    backend: Backend,
+    /// The recorder of the calls made to the GPU server, if recording.
+    recorder: Option<SessionRecorder>,
     /// The GPU server container, if managed by the prover.
-    managed_container: Option<CudaProverContainer>,
+    managed_container: Option<Arc<CudaProverContainer>>,
+    /// The maximum size in bytes of a proof accepted from the GPU server.
+    max_proof_size: u64,
+    /// The VRAM each stage needs on the GPU server.
+    memory_budget: GpuMemoryBudget,
+    /// How the requests to the GPU server are retried.
+    retry_policy: RetryPolicy,
+    /// How the managed container is supervised.
+    supervisor_policy: SupervisorPolicy,
+    /// The prefix of the ids of the requests, unique to the prover.
+    session: String,
+    /// The number of requests sent, numbering the next request id.
+    num_requests: AtomicU64,
 }
 
+enum Backend {
+    /// The gRPC client to communicate with the container.
+    Server(Client),
+    /// The recording of an earlier session, answering the calls in order.
+    Replay(SessionReplayer),
+    /// One container per device, splitting the core shards between them.
+    Cluster(ZKMCudaCluster),
+}
+
+/// A GPU server started by the prover, in a container or as a child process.
 pub struct CudaProverContainer {
     /// The name of the container.
     name: String,
     /// A flag to indicate whether the container has already been cleaned up.
-    cleaned_up: Arc<AtomicBool>,
+    cleaned_up: AtomicBool,
+    /// How the server runs.
+    runtime: ServerRuntime,
+    /// The image the container runs.
+    image: String,
+    /// The port of the host the server listens on.
+    port: u64,
+    /// The device the server uses, or `None` for all devices.
+    device: Option<u64>,
+    /// The number of times the container was restarted, held while restarting it.
+    restarts: Mutex<u32>,
+}
+
+/// How a GPU server started by the prover runs.
+enum ServerRuntime {
+    /// A container run by Docker.
+    Docker,
+    /// A container run by Podman, given the devices through the Container Device Interface.
+    Podman,
+    /// A child process running the server binary.
+    Process { binary_path: PathBuf, child: Mutex<Option<Child>> },
+}
+
+impl CudaProverContainer {
+    /// Starts the server in the background.
+    fn run(&self) -> Result<(), Box<dyn StdError>> {
+        let rust_log_level = std::env::var("RUST_LOG").unwrap_or_else(|_| "none".to_string());
+        let (program, devices) = match &self.runtime {
+            ServerRuntime::Docker => (
+                "docker",
+                [
+                    "--gpus".to_string(),
+                    self.device.map_or("all".to_string(), |i| format!("device={i}")),
+                ],
+            ),
+            ServerRuntime::Podman => (
+                "podman",
+                [
+                    "--device".to_string(),
+                    format!(
+                        "nvidia.com/gpu={}",
+                        self.device.map_or("all".to_string(), |i| i.to_string())
+                    ),
+                ],
+            ),
+            ServerRuntime::Process { binary_path, child } => {
+                let mut command = Command::new(binary_path);
+                command.env("RUST_LOG", rust_log_level).env("PORT", self.port.to_string());
+                if let Some(device) = self.device {
+                    command.env("CUDA_VISIBLE_DEVICES", device.to_string());
+                }
+                let spawned = command
+                    // Redirect stdout and stderr to the parent process
+                    .stdout(Stdio::inherit())
+                    .stderr(Stdio::inherit())
+                    .spawn()
+                    .map_err(|e| {
+                        format!("Failed to start the GPU server {}: {e}.", binary_path.display())
+                    })?;
+                *child.lock().unwrap() = Some(spawned);
+                return Ok(());
+            }
+        };
+
+        // Start the container
+        Command::new(program)
+            .args([
+                "run",
+                "-e",
+                &format!("RUST_LOG={rust_log_level}"),
+                "-p",
+                &format!("{}:3000", self.port),
+                "--rm",
+            ])
+            .args(&devices)
+            .args(["--name", &self.name, &self.image])
+            // Redirect stdout and stderr to the parent process
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start {program} container: {e}. Please check your {program} installation and permissions."))?;
+        Ok(())
+    }
+
+    /// Stops the server, removing its container or killing its process.
+    fn stop(&self) {
+        match &self.runtime {
+            ServerRuntime::Docker => cleanup_container("docker", &self.name),
+            ServerRuntime::Podman => cleanup_container("podman", &self.name),
+            ServerRuntime::Process { child, .. } => {
+                if let Some(mut child) = child.lock().unwrap().take() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+        }
+    }
+
+    /// Stops the server, unless it was cleaned up already.
+    fn cleanup(&self) {
+        if !self.cleaned_up.swap(true, Ordering::SeqCst) {
+            self.stop();
+        }
+    }
 }
 
 /// The payload for the [zkm_prover::ZKMProver::setup] method.
@@ -93,6 +241,26 @@ pub struct StatelessProveCoreRequestPayload {
     pub pk: ZKMProvingKey,
 }
 
+/// The payload for proving a partition of the core shards, used by [ZKMCudaCluster].
+///
+/// The cluster executes the program once, and sends every server the checkpoints of the whole
+/// execution. The server traces the shards from the checkpoints with `opts`, as
+/// [zkm_core_machine::utils::trace_checkpoint] does, but only proves the shards whose index is
+/// congruent to `partition` modulo `num_partitions`, returning them in a [ZKMCoreProof].
+#[derive(Serialize, Deserialize)]
+pub struct ProveCoreShardsRequestPayload {
+    /// The proving key.
+    pub pk: ZKMProvingKey,
+    /// The checkpoints of the execution, in order.
+    pub checkpoints: Vec<ExecutionState>,
+    /// The options the checkpoints were generated with.
+    pub opts: ZKMCoreOpts,
+    /// The partition of the shards to prove.
+    pub partition: u32,
+    /// The number of partitions the shards are split into.
+    pub num_partitions: u32,
+}
+
 /// The payload for the [zkm_prover::ZKMProver::compress] method.
 ///
 /// This object is used to serialize and deserialize the payloads for the GPU server.
@@ -122,15 +290,64 @@ pub struct WrapRequestPayload {
     pub reduced_proof: ZKMReduceProof<InnerSC>,
 }
 
+/// The payload for the stateless [zkm_prover::ZKMProver::shrink] method.
+///
+/// The verifying key of the program is sent with the proof, so that any GPU server can shrink it
+/// without a prior setup of the program.
+#[derive(Serialize, Deserialize)]
+pub struct StatelessShrinkRequestPayload {
+    /// The verifying key of the program.
+    pub vk: ZKMVerifyingKey,
+    /// The compressed proof.
+    pub reduced_proof: ZKMReduceProof<InnerSC>,
+}
+
+/// The payload for the stateless [zkm_prover::ZKMProver::wrap_bn254] method.
+///
+/// The verifying key of the program is sent with the proof, so that any GPU server can wrap it
+/// without a prior setup of the program.
+#[derive(Serialize, Deserialize)]
+pub struct StatelessWrapRequestPayload {
+    /// The verifying key of the program.
+    pub vk: ZKMVerifyingKey,
+    /// The shrunk proof.
+    pub reduced_proof: ZKMReduceProof<InnerSC>,
+}
+
 /// Defines how the GPU server is created.
+///
+/// The VRAM each proving stage is expected to need on the server's device is checked before every
+/// request. It is read from the environment, see [GpuMemoryBudget::from_env], and changed with
+/// [ZKMCudaProver::set_memory_budget]. `Local` runs the server in a Docker container and `Podman` in a Podman
+/// container, whose devices are given through the Container Device Interface. `Process` runs the
+/// server binary at `binary_path` as a child process, listening on the port in its `PORT`
+/// environment variable, for hosts without a container runtime. `Replay` answers the calls from a recording made with
+/// [ZKMCudaProver::record_to] instead of a server. `Cluster` runs a container per device, see
+/// [ZKMCudaCluster].
 #[derive(Debug)]
 pub enum ZKMGpuServer {
     External { endpoint: String },
     Local { visible_device_index: Option<u64>, port: Option<u64> },
+    Podman { visible_device_index: Option<u64>, port: Option<u64> },
+    Process { binary_path: PathBuf, visible_device_index: Option<u64>, port: Option<u64> },
+    Replay { dir: PathBuf },
+    Cluster { devices: Vec<u64> },
 }
 
 impl Default for ZKMGpuServer {
     fn default() -> Self {
+        if let Ok(dir) = std::env::var("CUDA_REPLAY_DIR") {
+            return Self::Replay { dir: dir.into() };
+        }
+
+        if let Ok(devices) = std::env::var("CUDA_CLUSTER_DEVICES") {
+            let devices = devices
+                .split(',')
+                .map(|device| device.trim().parse().expect("Invalid CUDA device index"))
+                .collect();
+            return Self::Cluster { devices };
+        }
+
         if std::env::var("CUDA_RUN_DOCKER")
             .map(|s| s == "1" || s.to_lowercase() == "true")
             .unwrap_or(true)
@@ -146,7 +363,15 @@ impl Default for ZKMGpuServer {
             } else {
                 None
             };
-            return Self::Local { visible_device_index, port };
+            if let Ok(binary_path) = std::env::var("CUDA_SERVER_BINARY") {
+                let binary_path = binary_path.into();
+                return Self::Process { binary_path, visible_device_index, port };
+            }
+            return match std::env::var("CUDA_CONTAINER_RUNTIME").as_deref() {
+                Ok("podman") => Self::Podman { visible_device_index, port },
+                Ok("docker") | Err(_) => Self::Local { visible_device_index, port },
+                Ok(runtime) => panic!("Invalid CUDA_CONTAINER_RUNTIME: {runtime}"),
+            };
         }
 
         let endpoint =
@@ -157,9 +382,21 @@ impl Default for ZKMGpuServer {
 
 impl ZKMCudaProver {
     /// Creates a new [ZKMCudaProver] that can be used to communicate with the GPU server at
-    /// `gpu_endpoint`, or if not provided, create one that runs inside a Docker container.
+    /// `gpu_endpoint`, or if not provided, create one that runs inside a container or as a child
+    /// process.
+    ///
+    /// If `CUDA_RECORD_DIR` is set, the calls made to the server are recorded to it.
     pub fn new(gpu_server: ZKMGpuServer) -> Result<Self, Box<dyn StdError>> {
+        let mut prover = Self::connect(gpu_server)?;
+        if let Ok(dir) = std::env::var("CUDA_RECORD_DIR") {
+            prover.record_to(dir)?;
+        }
+        Ok(prover)
+    }
+
+    fn connect(gpu_server: ZKMGpuServer) -> Result<Self, Box<dyn StdError>> {
         let reqwest_middlewares = vec![Box::new(LoggingMiddleware) as Box<dyn Middleware>];
+        let memory = GpuMemoryBudget::from_env();
 
         let prover = match gpu_server {
             ZKMGpuServer::External { endpoint } => {
@@ -170,46 +407,98 @@ impl ZKMCudaProver {
                 )
                 .expect("failed to create client");
 
-                ZKMCudaProver { client, managed_container: None }
+                ZKMCudaProver {
+                    backend: Backend::Server(client),
+                    recorder: None,
+                    managed_container: None,
+                    max_proof_size: MAX_PROOF_SIZE,
+                    memory_budget: memory,
+                    retry_policy: RetryPolicy::from_env(),
+                    supervisor_policy: SupervisorPolicy::from_env(),
+                    session: new_session(),
+                    num_requests: AtomicU64::new(0),
+                }
             }
-            ZKMGpuServer::Local { visible_device_index, port } => {
-                Self::start_gpu_server(reqwest_middlewares, visible_device_index, port)?
+            ZKMGpuServer::Local { visible_device_index, port } => Self::start_gpu_server(
+                reqwest_middlewares,
+                ServerRuntime::Docker,
+                visible_device_index,
+                port,
+                memory,
+            )?,
+            ZKMGpuServer::Podman { visible_device_index, port } => Self::start_gpu_server(
+                reqwest_middlewares,
+                ServerRuntime::Podman,
+                visible_device_index,
+                port,
+                memory,
+            )?,
+            ZKMGpuServer::Process { binary_path, visible_device_index, port } => {
+                Self::start_gpu_server(
+                    reqwest_middlewares,
+                    ServerRuntime::Process { binary_path, child: Mutex::new(None) },
+                    visible_device_index,
+                    port,
+                    memory,
+                )?
+            }
+            ZKMGpuServer::Cluster { devices } => {
+                return Ok(ZKMCudaProver {
+                    backend: Backend::Cluster(ZKMCudaCluster::new(devices, memory)?),
+                    recorder: None,
+                    managed_container: None,
+                    max_proof_size: MAX_PROOF_SIZE,
+                    memory_budget: memory,
+                    retry_policy: RetryPolicy::from_env(),
+                    supervisor_policy: SupervisorPolicy::from_env(),
+                    session: new_session(),
+                    num_requests: AtomicU64::new(0),
+                });
+            }
+            ZKMGpuServer::Replay { dir } => {
+                tracing::info!("replaying the GPU server calls recorded in {}", dir.display());
+                return Ok(ZKMCudaProver {
+                    backend: Backend::Replay(SessionReplayer::load(dir)?),
+                    recorder: None,
+                    managed_container: None,
+                    max_proof_size: MAX_PROOF_SIZE,
+                    memory_budget: GpuMemoryBudget::unchecked(),
+                    retry_policy: RetryPolicy::none(),
+                    supervisor_policy: SupervisorPolicy::default(),
+                    session: new_session(),
+                    num_requests: AtomicU64::new(0),
+                });
             }
         };
+        let Backend::Server(client) = &prover.backend else { unreachable!() };
 
-        let timeout = Duration::from_secs(300);
-        let start_time = Instant::now();
+        block_on(wait_ready(client))?;
 
-        block_on(async {
-            tracing::info!("waiting for proving server to be ready");
-            loop {
-                if start_time.elapsed() > timeout {
-                    return Err("Timeout: proving server did not become ready within 300 seconds. Please check your Docker container and network settings.".to_string());
-                }
-
-                let request = ReadyRequest {};
-                match prover.client.ready(request).await {
-                    Ok(response) if response.ready => {
-                        tracing::info!("proving server is ready");
-                        break;
-                    }
-                    Ok(_) => {
-                        tracing::info!("proving server is not ready, retrying...");
-                    }
-                    Err(e) => {
-                        tracing::warn!("Error checking server readiness: {}", e);
+        match prover.device_memory() {
+            Some(device) => {
+                tracing::info!(
+                    "proving server runs on {} with {} of {} bytes of VRAM available",
+                    device.name,
+                    device.free,
+                    device.total
+                );
+                for stage in [GpuStage::Core, GpuStage::Compress, GpuStage::Shrink, GpuStage::Wrap]
+                {
+                    if let Err(e) = check_budget(&prover.memory_budget, stage, &device) {
+                        tracing::warn!("{}", e);
                     }
                 }
-                tokio::time::sleep(Duration::from_secs(2)).await;
             }
-            Ok(())
-        })?;
+            None => tracing::warn!(
+                "proving server does not report its VRAM, requests will not be checked against the memory budget"
+            ),
+        }
 
         Ok(prover)
     }
 
-    fn check_docker_availability() -> Result<bool, Box<dyn std::error::Error>> {
-        match Command::new("docker").arg("version").output() {
+    fn check_runtime_availability(program: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        match Command::new(program).arg("version").output() {
             Ok(output) => Ok(output.status.success()),
             Err(_) => Ok(false),
         }
@@ -217,52 +506,64 @@ impl ZKMCudaProver {
 
     fn start_gpu_server(
         reqwest_middlewares: Vec<Box<dyn Middleware>>,
+        runtime: ServerRuntime,
         visible_device_index: Option<u64>,
         port: Option<u64>,
+        memory_budget: GpuMemoryBudget,
     ) -> Result<ZKMCudaProver, Box<dyn StdError>> {
-        // If the gpu endpoint url hasn't been provided, we start the Docker container
+        // If the gpu endpoint url hasn't been provided, we start the server
         let container_name =
             port.map(|p| format!("ziren-gpu-{p}")).unwrap_or("ziren-gpu".to_string());
         let image_name = std::env::var("ZKM_GPU_IMAGE")
             .unwrap_or_else(|_| "projectzkm/ziren-gpu:latest".to_string());
-
-        let cleaned_up = Arc::new(AtomicBool::new(false));
         let port = port.unwrap_or(3000);
-        let gpus = visible_device_index.map(|i| format!("device={i}")).unwrap_or("all".to_string());
 
-        // Check if Docker is available and the user has necessary permissions
-        if !Self::check_docker_availability()? {
-            return Err("Docker is not available or you don't have the necessary permissions. Please ensure Docker is installed and you are part of the docker group.".into());
-        }
+        match &runtime {
+            ServerRuntime::Docker => {
+                // Check if Docker is available and the user has necessary permissions
+                if !Self::check_runtime_availability("docker")? {
+                    return Err("Docker is not available or you don't have the necessary permissions. Please ensure Docker is installed and you are part of the docker group.".into());
+                }
+
+                // Pull the docker image if it's not present
+                if let Err(e) = Command::new("docker").args(["pull", &image_name]).output() {
+                    return Err(format!("Failed to pull Docker image: {e}. Please check your internet connection and Docker permissions.").into());
+                }
+            }
+            ServerRuntime::Podman => {
+                if !Self::check_runtime_availability("podman")? {
+                    return Err("Podman is not available. Please ensure Podman is installed with the NVIDIA Container Toolkit's CDI specification generated.".into());
+                }
 
-        // Pull the docker image if it's not present
-        if let Err(e) = Command::new("docker").args(["pull", &image_name]).output() {
-            return Err(format!("Failed to pull Docker image: {e}. Please check your internet connection and Docker permissions.").into());
+                if let Err(e) = Command::new("podman").args(["pull", &image_name]).output() {
+                    return Err(format!("Failed to pull Podman image: {e}. Please check your internet connection and Podman configuration.").into());
+                }
+            }
+            ServerRuntime::Process { binary_path, .. } => {
+                if !binary_path.is_file() {
+                    return Err(format!(
+                        "The GPU server binary {} does not exist.",
+                        binary_path.display()
+                    )
+                    .into());
+                }
+            }
         }
 
-        // Start the docker container
-        let rust_log_level = std::env::var("RUST_LOG").unwrap_or_else(|_| "none".to_string());
-        Command::new("docker")
-            .args([
-                "run",
-                "-e",
-                &format!("RUST_LOG={rust_log_level}"),
-                "-p",
-                &format!("{port}:3000"),
-                "--rm",
-                "--gpus",
-                &gpus,
-                "--name",
-                &container_name,
-                &image_name,
-            ])
-            // Redirect stdout and stderr to the parent process
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .map_err(|e| format!("Failed to start Docker container: {e}. Please check your Docker installation and permissions."))?;
+        let container = Arc::new(CudaProverContainer {
+            name: container_name.clone(),
+            cleaned_up: AtomicBool::new(false),
+            runtime,
+            image: image_name,
+            port,
+            device: visible_device_index,
+            restarts: Mutex::new(0),
+        });
+        container.run()?;
 
-        GPU_CONTAINERS.lock()?.insert(container_name.clone(), cleaned_up.clone());
+        if GPU_CONTAINERS.lock()?.insert(container_name, container.clone()).is_some() {
+            metrics::container_restart();
+        }
 
         // Kill the container on control-c
         // The error returned by set_handler is ignored to avoid panic when the handler has already
@@ -270,11 +571,8 @@ impl ZKMCudaProver {
         let _ = ctrlc::set_handler(move || {
             tracing::info!("received Ctrl+C, cleaning up...");
 
-            for (container_name, cleanup_flag) in GPU_CONTAINERS.lock().unwrap().iter() {
-                if !cleanup_flag.load(Ordering::SeqCst) {
-                    cleanup_container(container_name);
-                    cleanup_flag.store(true, Ordering::SeqCst);
-                }
+            for container in GPU_CONTAINERS.lock().unwrap().values() {
+                container.cleanup();
             }
             std::process::exit(0);
         });
@@ -290,90 +588,400 @@ impl ZKMCudaProver {
         .expect("failed to create client");
 
         Ok(ZKMCudaProver {
-            client,
-            managed_container: Some(CudaProverContainer { name: container_name, cleaned_up }),
+            backend: Backend::Server(client),
+            recorder: None,
+            managed_container: Some(container),
+            max_proof_size: MAX_PROOF_SIZE,
+            memory_budget,
+            retry_policy: RetryPolicy::from_env(),
+            supervisor_policy: SupervisorPolicy::from_env(),
+            session: new_session(),
+            num_requests: AtomicU64::new(0),
         })
     }
 
+    /// Sets the maximum size in bytes of a proof accepted from the GPU server.
+    ///
+    /// Defaults to [MAX_PROOF_SIZE].
+    pub fn set_max_proof_size(&mut self, max_proof_size: u64) {
+        self.max_proof_size = max_proof_size;
+    }
+
+    /// Sets the VRAM each stage needs on the GPU server, for every server of a cluster.
+    ///
+    /// Defaults to [GpuMemoryBudget::from_env].
+    pub fn set_memory_budget(&mut self, memory_budget: GpuMemoryBudget) {
+        if let Backend::Cluster(cluster) = &mut self.backend {
+            cluster.set_memory_budget(memory_budget);
+        }
+        self.memory_budget = memory_budget;
+    }
+
+    /// Sets how the requests to the GPU server are retried, for every server of a cluster.
+    ///
+    /// Defaults to [RetryPolicy::from_env].
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        if let Backend::Cluster(cluster) = &mut self.backend {
+            cluster.set_retry_policy(retry_policy);
+        }
+        self.retry_policy = retry_policy;
+    }
+
+    /// Sets how the managed GPU server containers are supervised, for every server of a cluster.
+    ///
+    /// Defaults to [SupervisorPolicy::from_env].
+    pub fn set_supervisor_policy(&mut self, supervisor_policy: SupervisorPolicy) {
+        if let Backend::Cluster(cluster) = &mut self.backend {
+            cluster.set_supervisor_policy(supervisor_policy);
+        }
+        self.supervisor_policy = supervisor_policy;
+    }
+
+    /// Records the calls made to the GPU server to `dir`, to be replayed later with
+    /// [ZKMGpuServer::Replay].
+    pub fn record_to(&mut self, dir: impl AsRef<Path>) -> io::Result<()> {
+        tracing::info!("recording the GPU server calls to {}", dir.as_ref().display());
+        self.recorder = Some(SessionRecorder::new(dir)?);
+        Ok(())
+    }
+
+    /// Sends the serialized payload `data` to `method` of the GPU server, returning the serialized
+    /// payload of the response.
+    ///
+    /// The request is retried according to the [RetryPolicy] of the prover.
+    fn call(&self, method: GpuMethod, data: Vec<u8>) -> Result<Vec<u8>, GpuCallError> {
+        let client = match &self.backend {
+            Backend::Server(client) => client,
+            Backend::Replay(replayer) => return Ok(replayer.replay(method, &data)?),
+            Backend::Cluster(cluster) => {
+                let result = cluster.primary().call(method, data.clone())?;
+                if let Some(recorder) = &self.recorder {
+                    recorder.record(method, &data, &result);
+                }
+                return Ok(result);
+            }
+        };
+        let request_id =
+            format!("{}-{}", self.session, self.num_requests.fetch_add(1, Ordering::SeqCst));
+        let policy = self.retry_policy;
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            let generation = self.managed_container.as_ref().map(|c| *c.restarts.lock().unwrap());
+            let start = Instant::now();
+            let in_flight = metrics::request_in_flight();
+            let attempt = block_on(async {
+                let request = async {
+                    let request = send(client, method, data.clone(), request_id.clone());
+                    match policy.timeout {
+                        Some(timeout) => tokio::time::timeout(timeout, request)
+                            .await
+                            .map_or(Attempt::TimedOut, Attempt::Response),
+                        None => Attempt::Response(request.await),
+                    }
+                };
+                let Some(container) = &self.managed_container else {
+                    return request.await;
+                };
+                let supervision = supervisor::watch(client, container, &self.supervisor_policy);
+                tokio::select! {
+                    attempt = request => attempt,
+                    reason = supervision => Attempt::Unhealthy(reason),
+                }
+            });
+            drop(in_flight);
+            let ok = matches!(attempt, Attempt::Response(Ok(_)));
+            metrics::observe_request(method, start.elapsed(), ok);
+            // A connection lost because the container exited is not retried against it.
+            let attempt = match attempt {
+                Attempt::Response(Err(e))
+                    if retry::is_transient(&e) && self.container_stopped() =>
+                {
+                    Attempt::Unhealthy(format!("the container stopped: {e}"))
+                }
+                attempt => attempt,
+            };
+            let error = match attempt {
+                Attempt::Response(Ok(result)) => break result,
+                Attempt::Response(Err(e))
+                    if !retry::is_retryable(method, &e) || attempts >= policy.max_attempts =>
+                {
+                    return Err(GpuCallError::Client { method, attempts, source: e })
+                }
+                // The server may still be running an abandoned request, which is only sent again
+                // if running it twice is harmless.
+                Attempt::TimedOut if !method.is_idempotent() || attempts >= policy.max_attempts => {
+                    let timeout = policy.timeout.unwrap_or_default();
+                    return Err(GpuCallError::TimedOut { method, attempts, timeout });
+                }
+                Attempt::Response(Err(e)) => e.to_string(),
+                Attempt::TimedOut => "timed out".to_string(),
+                Attempt::Unhealthy(reason) => {
+                    // The restarts are bounded by the supervisor policy, not by the attempts.
+                    attempts -= 1;
+                    self.restart_container(client, generation.unwrap_or_default())
+                        .map_err(|e| GpuCallError::Unhealthy { method, reason, restart: e })?;
+                    continue;
+                }
+            };
+            let backoff = policy.backoff(attempts);
+            tracing::warn!(
+                "the {} request {} to the GPU server failed, retrying in {:?}: {}",
+                method,
+                request_id,
+                backoff,
+                error
+            );
+            metrics::retry(metrics::Retry::Request);
+            std::thread::sleep(backoff);
+        };
+        if let Some(recorder) = &self.recorder {
+            recorder.record(method, &data, &result);
+        }
+        Ok(result)
+    }
+
+    /// Whether Docker reports the managed container stopped.
+    fn container_stopped(&self) -> bool {
+        self.managed_container
+            .as_ref()
+            .is_some_and(|container| block_on(supervisor::server_running(container)) == Some(false))
+    }
+
+    /// Restarts the managed container, unless it was restarted since `generation` restarts.
+    ///
+    /// Fails if the container was restarted [SupervisorPolicy::max_restarts] times already, or does
+    /// not become ready again.
+    fn restart_container(&self, client: &Client, generation: u32) -> Result<(), String> {
+        let container = self.managed_container.as_ref().expect("the container is managed");
+        let mut restarts = container.restarts.lock().unwrap();
+        if *restarts != generation {
+            // Another request restarted the container while this one was in flight.
+            return Ok(());
+        }
+        let max_restarts = self.supervisor_policy.max_restarts;
+        if *restarts >= max_restarts {
+            return Err(format!("the container was restarted {max_restarts} times already"));
+        }
+        *restarts += 1;
+        tracing::warn!(
+            "restarting the GPU server container {} ({}/{})",
+            container.name,
+            *restarts,
+            max_restarts
+        );
+        metrics::container_restart();
+        container.stop();
+        container.run().map_err(|e| e.to_string())?;
+        block_on(wait_ready(client))
+    }
+
+    /// Queries the memory of the device the GPU server runs on.
+    ///
+    /// Returns `None` if the server does not support the query, or when replaying a recording.
+    pub fn device_memory(&self) -> Option<DeviceMemory> {
+        let client = match &self.backend {
+            Backend::Server(client) => client,
+            Backend::Replay(_) => return None,
+            Backend::Cluster(cluster) => return cluster.primary().device_memory(),
+        };
+        match block_on(async { client.device_info(DeviceInfoRequest {}).await }) {
+            Ok(response) => Some(DeviceMemory {
+                name: response.name,
+                total: response.total_memory,
+                free: response.free_memory,
+            }),
+            Err(e) => {
+                tracing::debug!("failed to query the device memory: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Checks that the GPU server has the VRAM the memory budget reserves for `stage`.
+    ///
+    /// Passes if the server does not report its memory.
+    pub fn check_memory(&self, stage: GpuStage) -> Result<(), GpuMemoryError> {
+        match self.device_memory() {
+            Some(device) => check_budget(&self.memory_budget, stage, &device),
+            None => Ok(()),
+        }
+    }
+
     /// Executes the [zkm_prover::ZKMProver::setup] method inside the container.
     pub fn setup(&self, elf: &[u8]) -> Result<(ZKMProvingKey, ZKMVerifyingKey), Box<dyn StdError>> {
         let payload = SetupRequestPayload { elf: elf.to_vec() };
-        let request = crate::api::SetupRequest { data: bincode::serialize(&payload).unwrap() };
-        let response = block_on(async { self.client.setup(request).await }).unwrap();
-        let payload: SetupResponsePayload = bincode::deserialize(&response.result).unwrap();
+        let response = self.call(GpuMethod::Setup, bincode::serialize(&payload).unwrap())?;
+        let payload: SetupResponsePayload = deserialize_limited(&response, self.max_proof_size)?;
         Ok((payload.pk, payload.vk))
     }
 
     /// Executes the [zkm_prover::ZKMProver::prove_core] method inside the container.
     ///
-    /// You will need at least 24GB of VRAM to run this method.
+    /// Fails without sending the request if the server has less free VRAM than the core stage
+    /// budget.
     pub fn prove_core(&self, stdin: &ZKMStdin) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        self.check_memory(GpuStage::Core).map_err(core_memory_error)?;
         let payload = ProveCoreRequestPayload { stdin: stdin.clone() };
-        let request = crate::api::ProveCoreRequest { data: bincode::serialize(&payload).unwrap() };
-        let response = block_on(async { self.client.prove_core(request).await }).unwrap();
-        let proof: ZKMCoreProof = bincode::deserialize(&response.result).unwrap();
+        let response = self
+            .call(GpuMethod::ProveCore, bincode::serialize(&payload).unwrap())
+            .map_err(core_call_error)?;
+        let proof: ZKMCoreProof = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMCoreProverError::DeserializationError)?;
         Ok(proof)
     }
 
     /// Executes the [zkm_prover::ZKMProver::prove_core] method inside the container.
     ///
-    /// You will need at least 24GB of VRAM to run this method.
+    /// Fails without sending the request if the server has less free VRAM than the core stage
+    /// budget. With [ZKMGpuServer::Cluster], the shards are split across the devices.
     pub fn prove_core_stateless(
         &self,
         pk: &ZKMProvingKey,
         stdin: &ZKMStdin,
     ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
         let payload = StatelessProveCoreRequestPayload { pk: pk.clone(), stdin: stdin.clone() };
-        let request = crate::api::ProveCoreRequest { data: bincode::serialize(&payload).unwrap() };
-        let response = block_on(async { self.client.prove_core_stateless(request).await }).unwrap();
-        let proof: ZKMCoreProof = bincode::deserialize(&response.result).unwrap();
+        if let Backend::Cluster(cluster) = &self.backend {
+            let proof = cluster.prove_core_stateless(pk, stdin)?;
+            // The partitions are recorded as the single call they replace, so that a recording of
+            // a cluster is replayed without one.
+            if let Some(recorder) = &self.recorder {
+                let request = bincode::serialize(&payload).unwrap();
+                let response = bincode::serialize(&proof).unwrap();
+                recorder.record(GpuMethod::ProveCoreStateless, &request, &response);
+            }
+            return Ok(proof);
+        }
+        self.check_memory(GpuStage::Core).map_err(core_memory_error)?;
+        let response = self
+            .call(GpuMethod::ProveCoreStateless, bincode::serialize(&payload).unwrap())
+            .map_err(core_call_error)?;
+        let proof: ZKMCoreProof = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMCoreProverError::DeserializationError)?;
         Ok(proof)
     }
 
     /// Executes the [zkm_prover::ZKMProver::compress] method inside the container.
     ///
-    /// You will need at least 24GB of VRAM to run this method.
+    /// Fails without sending the request if the server has less free VRAM than the compress stage
+    /// budget.
     pub fn compress(
         &self,
         vk: &ZKMVerifyingKey,
         proof: ZKMCoreProof,
         deferred_proofs: Vec<ZKMReduceProof<InnerSC>>,
     ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Compress)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
         let payload = CompressRequestPayload { vk: vk.clone(), proof, deferred_proofs };
-        let request = crate::api::CompressRequest { data: bincode::serialize(&payload).unwrap() };
+        let response = self
+            .call(GpuMethod::Compress, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<InnerSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
+        Ok(proof)
+    }
 
-        let response = block_on(async { self.client.compress(request).await }).unwrap();
-        let proof: ZKMReduceProof<InnerSC> = bincode::deserialize(&response.result).unwrap();
+    /// Executes the [zkm_prover::ZKMProver::compress] method inside the container, without relying
+    /// on the state of the server.
+    ///
+    /// Unlike [Self::compress], the server uses only the payload, so the request can be sent to
+    /// any server of a pool. Fails without sending the request if the server has less free VRAM
+    /// than the compress stage budget.
+    pub fn compress_stateless(
+        &self,
+        vk: &ZKMVerifyingKey,
+        proof: ZKMCoreProof,
+        deferred_proofs: Vec<ZKMReduceProof<InnerSC>>,
+    ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Compress)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let payload = CompressRequestPayload { vk: vk.clone(), proof, deferred_proofs };
+        let response = self
+            .call(GpuMethod::CompressStateless, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<InnerSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
         Ok(proof)
     }
 
     /// Executes the [zkm_prover::ZKMProver::shrink] method inside the container.
     ///
-    /// You will need at least 24GB of VRAM to run this method.
+    /// Fails without sending the request if the server has less free VRAM than the shrink stage
+    /// budget.
     pub fn shrink(
         &self,
         reduced_proof: ZKMReduceProof<InnerSC>,
     ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Shrink)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
         let payload = ShrinkRequestPayload { reduced_proof: reduced_proof.clone() };
-        let request = crate::api::ShrinkRequest { data: bincode::serialize(&payload).unwrap() };
+        let response = self
+            .call(GpuMethod::Shrink, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<InnerSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
+        Ok(proof)
+    }
 
-        let response = block_on(async { self.client.shrink(request).await }).unwrap();
-        let proof: ZKMReduceProof<InnerSC> = bincode::deserialize(&response.result).unwrap();
+    /// Executes the [zkm_prover::ZKMProver::shrink] method inside the container, sending the
+    /// verifying key `vk` of the program with the proof so any server of a pool can shrink it.
+    ///
+    /// Fails without sending the request if the server has less free VRAM than the shrink stage
+    /// budget.
+    pub fn shrink_stateless(
+        &self,
+        vk: &ZKMVerifyingKey,
+        reduced_proof: ZKMReduceProof<InnerSC>,
+    ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Shrink)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let payload = StatelessShrinkRequestPayload { vk: vk.clone(), reduced_proof };
+        let response = self
+            .call(GpuMethod::ShrinkStateless, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<InnerSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
         Ok(proof)
     }
 
     /// Executes the [zkm_prover::ZKMProver::wrap_bn254] method inside the container.
     ///
-    /// You will need at least 24GB of VRAM to run this method.
+    /// Fails without sending the request if the server has less free VRAM than the wrap stage
+    /// budget.
     pub fn wrap_bn254(
         &self,
         reduced_proof: ZKMReduceProof<InnerSC>,
     ) -> Result<ZKMReduceProof<OuterSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Wrap)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
         let payload = WrapRequestPayload { reduced_proof: reduced_proof.clone() };
-        let request = crate::api::WrapRequest { data: bincode::serialize(&payload).unwrap() };
+        let response = self
+            .call(GpuMethod::Wrap, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<OuterSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
+        Ok(proof)
+    }
 
-        let response = block_on(async { self.client.wrap(request).await }).unwrap();
-        let proof: ZKMReduceProof<OuterSC> = bincode::deserialize(&response.result).unwrap();
+    /// Executes the [zkm_prover::ZKMProver::wrap_bn254] method inside the container, sending the
+    /// verifying key `vk` of the program with the proof so any server of a pool can wrap it.
+    ///
+    /// Fails without sending the request if the server has less free VRAM than the wrap stage
+    /// budget.
+    pub fn wrap_bn254_stateless(
+        &self,
+        vk: &ZKMVerifyingKey,
+        reduced_proof: ZKMReduceProof<InnerSC>,
+    ) -> Result<ZKMReduceProof<OuterSC>, ZKMRecursionProverError> {
+        self.check_memory(GpuStage::Wrap)
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let payload = StatelessWrapRequestPayload { vk: vk.clone(), reduced_proof };
+        let response = self
+            .call(GpuMethod::WrapStateless, bincode::serialize(&payload).unwrap())
+            .map_err(|e| ZKMRecursionProverError::RuntimeError(e.to_string()))?;
+        let proof: ZKMReduceProof<OuterSC> = deserialize_limited(&response, self.max_proof_size)
+            .map_err(ZKMRecursionProverError::DeserializationError)?;
         Ok(proof)
     }
 }
@@ -389,18 +997,115 @@ impl Drop for ZKMCudaProver {
         if let Some(container) = &self.managed_container {
             if !container.cleaned_up.load(Ordering::SeqCst) {
                 tracing::debug!("dropping ZKMProverClient, cleaning up...");
-                cleanup_container(&container.name);
-                container.cleaned_up.store(true, Ordering::SeqCst);
+                container.cleanup();
+            }
+        }
+    }
+}
+
+fn core_memory_error(e: GpuMemoryError) -> ZKMCoreProverError {
+    ZKMCoreProverError::IoError(io::Error::new(io::ErrorKind::OutOfMemory, e))
+}
+
+fn core_call_error(e: GpuCallError) -> ZKMCoreProverError {
+    let kind = match e {
+        GpuCallError::TimedOut { .. } => io::ErrorKind::TimedOut,
+        _ => io::ErrorKind::Other,
+    };
+    ZKMCoreProverError::IoError(io::Error::new(kind, e))
+}
+
+/// The outcome of an attempt of a request to the GPU server.
+enum Attempt {
+    /// The server responded.
+    Response(Result<Vec<u8>>),
+    /// The server did not respond within the timeout of the retry policy.
+    TimedOut,
+    /// The managed container failed its health checks, for the given reason.
+    Unhealthy(String),
+}
+
+/// Sends one attempt of a request to `method` of the GPU server.
+async fn send(
+    client: &Client,
+    method: GpuMethod,
+    data: Vec<u8>,
+    request_id: String,
+) -> Result<Vec<u8>> {
+    use crate::api::{CompressRequest, ProveCoreRequest, SetupRequest, ShrinkRequest, WrapRequest};
+
+    match method {
+        GpuMethod::Setup => client.setup(SetupRequest { data, request_id }).await.map(|r| r.result),
+        GpuMethod::ProveCore => {
+            client.prove_core(ProveCoreRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::ProveCoreStateless => client
+            .prove_core_stateless(ProveCoreRequest { data, request_id })
+            .await
+            .map(|r| r.result),
+        GpuMethod::ProveCoreShards => {
+            client.prove_core_shards(ProveCoreRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::Compress => {
+            client.compress(CompressRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::Shrink => {
+            client.shrink(ShrinkRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::Wrap => client.wrap(WrapRequest { data, request_id }).await.map(|r| r.result),
+        GpuMethod::CompressStateless => {
+            client.compress_stateless(CompressRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::ShrinkStateless => {
+            client.shrink_stateless(ShrinkRequest { data, request_id }).await.map(|r| r.result)
+        }
+        GpuMethod::WrapStateless => {
+            client.wrap_stateless(WrapRequest { data, request_id }).await.map(|r| r.result)
+        }
+    }
+}
+
+/// A prefix of request ids unlikely to be used by another prover of the GPU server.
+fn new_session() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    format!("{:x}-{:x}", std::process::id(), nanos)
+}
+
+/// The time the GPU server has to become ready after its container is started.
+const READY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Polls the GPU server until it is ready, for up to [READY_TIMEOUT].
+async fn wait_ready(client: &Client) -> Result<(), String> {
+    let start_time = Instant::now();
+    tracing::info!("waiting for proving server to be ready");
+    loop {
+        if start_time.elapsed() > READY_TIMEOUT {
+            return Err("Timeout: proving server did not become ready within 300 seconds. Please check your Docker container and network settings.".to_string());
+        }
+
+        let request = ReadyRequest {};
+        match client.ready(request).await {
+            Ok(response) if response.ready => {
+                tracing::info!("proving server is ready");
+                return Ok(());
+            }
+            Ok(_) => {
+                tracing::info!("proving server is not ready, retrying...");
+            }
+            Err(e) => {
+                tracing::warn!("Error checking server readiness: {}", e);
             }
         }
+        metrics::retry(metrics::Retry::Ready);
+        tokio::time::sleep(Duration::from_secs(2)).await;
     }
 }
 
-/// Cleans up the a docker container with the given name.
-fn cleanup_container(container_name: &str) {
-    if let Err(e) = Command::new("docker").args(["rm", "-f", container_name]).output() {
+/// Cleans up the container of `program`, `docker` or `podman`, with the given name.
+fn cleanup_container(program: &str, container_name: &str) {
+    if let Err(e) = Command::new(program).args(["rm", "-f", container_name]).output() {
         eprintln!(
-            "Failed to remove container: {e}. You may need to manually remove it using 'docker rm -f {container_name}'"
+            "Failed to remove container: {e}. You may need to manually remove it using '{program} rm -f {container_name}'"
         );
     }
 }
@@ -437,3 +1142,108 @@ impl Middleware for LoggingMiddleware {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use p3_field::FieldAlgebra;
+    use p3_koala_bear::KoalaBear;
+    use zkm_prover::ZKMCoreProofData;
+    use zkm_stark::{septic_digest::SepticDigest, StarkVerifyingKey};
+
+    use super::*;
+
+    fn process_server(binary_path: PathBuf) -> CudaProverContainer {
+        CudaProverContainer {
+            name: "zkm-test-gpu-server".to_string(),
+            cleaned_up: AtomicBool::new(false),
+            runtime: ServerRuntime::Process { binary_path, child: Mutex::new(None) },
+            image: String::new(),
+            port: 3000,
+            device: Some(0),
+            restarts: Mutex::new(0),
+        }
+    }
+
+    #[test]
+    fn test_process_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary_path = dir.path().join("server");
+        fs::write(&binary_path, "#!/bin/sh\nsleep 60\n").unwrap();
+        fs::set_permissions(&binary_path, fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let server = process_server(binary_path);
+        server.run().unwrap();
+        assert_eq!(runtime.block_on(supervisor::server_running(&server)), Some(true));
+
+        // The process is killed when the server is cleaned up.
+        server.cleanup();
+        assert_eq!(runtime.block_on(supervisor::server_running(&server)), None);
+
+        assert!(process_server(dir.path().join("missing")).run().is_err());
+    }
+
+    #[test]
+    fn test_gpu_server_from_env() {
+        std::env::set_var("CUDA_CONTAINER_RUNTIME", "podman");
+        let podman = ZKMGpuServer::default();
+        std::env::set_var("CUDA_SERVER_BINARY", "/opt/zkm/server");
+        let process = ZKMGpuServer::default();
+        std::env::remove_var("CUDA_SERVER_BINARY");
+        std::env::remove_var("CUDA_CONTAINER_RUNTIME");
+
+        assert!(matches!(podman, ZKMGpuServer::Podman { .. }));
+        let ZKMGpuServer::Process { binary_path, .. } = process else {
+            panic!("expected a process server");
+        };
+        assert_eq!(binary_path, Path::new("/opt/zkm/server"));
+        assert!(matches!(ZKMGpuServer::default(), ZKMGpuServer::Local { .. }));
+    }
+
+    #[test]
+    fn test_compress_stateless() {
+        let vk = ZKMVerifyingKey {
+            vk: StarkVerifyingKey {
+                commit: [KoalaBear::ZERO; 8].into(),
+                pc_start: KoalaBear::ZERO,
+                initial_global_cumulative_sum: SepticDigest::zero(),
+                chip_information: vec![],
+                chip_ordering: HashMap::new(),
+            },
+        };
+        let proof = ZKMCoreProof {
+            proof: ZKMCoreProofData(vec![]),
+            stdin: ZKMStdin::new(),
+            public_values: Default::default(),
+            cycles: 0,
+        };
+        let payload = CompressRequestPayload {
+            vk: vk.clone(),
+            proof: proof.clone(),
+            deferred_proofs: vec![],
+        };
+
+        // The request carries the verifying key with the proof, and the response is checked.
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = SessionRecorder::new(dir.path()).unwrap();
+        recorder.record(
+            GpuMethod::CompressStateless,
+            &bincode::serialize(&payload).unwrap(),
+            b"not a proof",
+        );
+        recorder.record(GpuMethod::Compress, b"", b"");
+        let prover = ZKMCudaProver::new(ZKMGpuServer::Replay { dir: dir.path().into() }).unwrap();
+        assert!(matches!(
+            prover.compress_stateless(&vk, proof.clone(), vec![]),
+            Err(ZKMRecursionProverError::DeserializationError(_))
+        ));
+
+        // The request is not sent as a request of the stateful compress method.
+        assert!(matches!(
+            prover.compress_stateless(&vk, proof, vec![]),
+            Err(ZKMRecursionProverError::RuntimeError(_))
+        ));
+    }
+}
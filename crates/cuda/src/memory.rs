@@ -0,0 +1,156 @@
+use std::fmt;
+
+use thiserror::Error;
+
+/// A proving stage offloaded to the GPU server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuStage {
+    Core,
+    Compress,
+    Shrink,
+    Wrap,
+}
+
+impl fmt::Display for GpuStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GpuStage::Core => "core",
+            GpuStage::Compress => "compress",
+            GpuStage::Shrink => "shrink",
+            GpuStage::Wrap => "wrap",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The VRAM in bytes each proving stage needs on the GPU server.
+///
+/// Before running a stage, [crate::ZKMCudaProver] queries the free VRAM of its device and refuses
+/// the request if it is below the budget of the stage, instead of failing mid-proof. A budget of
+/// `None` skips the check for its stage.
+///
+/// Every check is skipped by default: the free VRAM of a device is always below its total, so a
+/// flat budget matching the size of a card would reject it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuMemoryBudget {
+    pub core: Option<u64>,
+    pub compress: Option<u64>,
+    pub shrink: Option<u64>,
+    pub wrap: Option<u64>,
+}
+
+impl GpuMemoryBudget {
+    /// A budget of `bytes` for every stage.
+    pub const fn uniform(bytes: u64) -> Self {
+        Self { core: Some(bytes), compress: Some(bytes), shrink: Some(bytes), wrap: Some(bytes) }
+    }
+
+    /// A budget skipping the check for every stage.
+    pub const fn unchecked() -> Self {
+        Self { core: None, compress: None, shrink: None, wrap: None }
+    }
+
+    /// The budget of `stage`.
+    pub fn get(&self, stage: GpuStage) -> Option<u64> {
+        match stage {
+            GpuStage::Core => self.core,
+            GpuStage::Compress => self.compress,
+            GpuStage::Shrink => self.shrink,
+            GpuStage::Wrap => self.wrap,
+        }
+    }
+
+    /// Reads the budget from the environment.
+    ///
+    /// `CUDA_VRAM_BUDGET_GB` sets the budget of every stage, and `CUDA_VRAM_BUDGET_<STAGE>_GB`
+    /// overrides it for one stage. A value of `0` skips the check, as do unset stages.
+    pub fn from_env() -> Self {
+        fn read(name: &str) -> Option<Option<u64>> {
+            let value = std::env::var(name).ok()?;
+            let gb: u64 = value.parse().unwrap_or_else(|_| panic!("Invalid {name}: {value}"));
+            Some((gb != 0).then_some(gb << 30))
+        }
+
+        let all = read("CUDA_VRAM_BUDGET_GB").flatten();
+        Self {
+            core: read("CUDA_VRAM_BUDGET_CORE_GB").unwrap_or(all),
+            compress: read("CUDA_VRAM_BUDGET_COMPRESS_GB").unwrap_or(all),
+            shrink: read("CUDA_VRAM_BUDGET_SHRINK_GB").unwrap_or(all),
+            wrap: read("CUDA_VRAM_BUDGET_WRAP_GB").unwrap_or(all),
+        }
+    }
+}
+
+impl Default for GpuMemoryBudget {
+    fn default() -> Self {
+        Self::unchecked()
+    }
+}
+
+/// The memory of the device the GPU server runs on, as reported by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceMemory {
+    /// The name of the device.
+    pub name: String,
+    /// The total VRAM of the device, in bytes.
+    pub total: u64,
+    /// The VRAM of the device available to the server, in bytes.
+    pub free: u64,
+}
+
+/// An error returned when the GPU server does not have the VRAM a stage needs.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "the {stage} stage needs {} GiB of VRAM, but only {} GiB of {} GiB are available on {device}",
+    gib(*required), gib(*available), gib(*total)
+)]
+pub struct GpuMemoryError {
+    pub stage: GpuStage,
+    pub device: String,
+    pub required: u64,
+    pub available: u64,
+    pub total: u64,
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1}", bytes as f64 / (1u64 << 30) as f64)
+}
+
+/// Checks that `device` has the VRAM `budget` reserves for `stage`.
+pub(crate) fn check_budget(
+    budget: &GpuMemoryBudget,
+    stage: GpuStage,
+    device: &DeviceMemory,
+) -> Result<(), GpuMemoryError> {
+    match budget.get(stage) {
+        Some(required) if device.free < required => Err(GpuMemoryError {
+            stage,
+            device: device.name.clone(),
+            required,
+            available: device.free,
+            total: device.total,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_budget() {
+        let device = DeviceMemory { name: "gpu".to_string(), total: 16 << 30, free: 12 << 30 };
+        let budget = GpuMemoryBudget { wrap: Some(8 << 30), ..GpuMemoryBudget::uniform(24 << 30) };
+
+        let err = check_budget(&budget, GpuStage::Core, &device).unwrap_err();
+        assert_eq!(err.required, 24 << 30);
+        assert_eq!(err.available, 12 << 30);
+        assert_eq!(
+            err.to_string(),
+            "the core stage needs 24.0 GiB of VRAM, but only 12.0 GiB of 16.0 GiB are available on gpu"
+        );
+        assert!(check_budget(&budget, GpuStage::Wrap, &device).is_ok());
+        assert!(check_budget(&GpuMemoryBudget::default(), GpuStage::Core, &device).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+//! Prometheus metrics of the GPU server client, registered in the default registry of the
+//! `prometheus` crate with the `metrics` feature. Without it, recording a metric does nothing.
+
+use std::time::Duration;
+
+use crate::session::GpuMethod;
+
+/// A reason a request to a GPU server is sent again.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Retry {
+    /// The server was polled again as it was not ready yet.
+    Ready,
+    /// A request failed with a transient error or timed out.
+    Request,
+    /// A shard partition of a cluster was queued again after a failure.
+    Partition,
+}
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use std::sync::LazyLock;
+
+    use prometheus::{
+        register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+        HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    };
+
+    pub(super) static REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec!(
+            "zkm_cuda_request_duration_seconds",
+            "The latency of the requests to the GPU servers.",
+            &["method", "outcome"],
+            vec![0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0]
+        )
+        .unwrap()
+    });
+
+    pub(super) static REQUESTS_IN_FLIGHT: LazyLock<IntGauge> = LazyLock::new(|| {
+        register_int_gauge!(
+            "zkm_cuda_requests_in_flight",
+            "The number of requests waiting for a response of a GPU server."
+        )
+        .unwrap()
+    });
+
+    pub(super) static QUEUE_DEPTH: LazyLock<IntGauge> = LazyLock::new(|| {
+        register_int_gauge!(
+            "zkm_cuda_queue_depth",
+            "The number of shard partitions queued for the devices of a GPU cluster."
+        )
+        .unwrap()
+    });
+
+    pub(super) static RETRIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec!(
+            "zkm_cuda_retries_total",
+            "The number of requests to the GPU servers sent again.",
+            &["reason"]
+        )
+        .unwrap()
+    });
+
+    pub(super) static CONTAINER_RESTARTS: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter!(
+            "zkm_cuda_container_restarts_total",
+            "The number of GPU server containers started again under the name of a previous one."
+        )
+        .unwrap()
+    });
+}
+
+/// Records the latency of a request to `method` of a GPU server.
+#[allow(unused_variables)]
+pub(crate) fn observe_request(method: GpuMethod, elapsed: Duration, ok: bool) {
+    #[cfg(feature = "metrics")]
+    imp::REQUEST_DURATION
+        .with_label_values(&[method.to_string().as_str(), if ok { "ok" } else { "error" }])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Counts a request as in flight until the returned guard is dropped.
+pub(crate) fn request_in_flight() -> InFlight {
+    #[cfg(feature = "metrics")]
+    imp::REQUESTS_IN_FLIGHT.inc();
+    InFlight
+}
+
+/// A request in flight, see [request_in_flight].
+pub(crate) struct InFlight;
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        imp::REQUESTS_IN_FLIGHT.dec();
+    }
+}
+
+/// Records the number of shard partitions queued for the devices of a cluster.
+#[allow(unused_variables)]
+pub(crate) fn set_queue_depth(depth: usize) {
+    #[cfg(feature = "metrics")]
+    imp::QUEUE_DEPTH.set(depth as i64);
+}
+
+/// Counts a request sent again.
+#[allow(unused_variables)]
+pub(crate) fn retry(reason: Retry) {
+    #[cfg(feature = "metrics")]
+    imp::RETRIES
+        .with_label_values(&[match reason {
+            Retry::Ready => "ready",
+            Retry::Request => "request",
+            Retry::Partition => "partition",
+        }])
+        .inc();
+}
+
+/// Counts a container started again under the name of a previous one.
+pub(crate) fn container_restart() {
+    #[cfg(feature = "metrics")]
+    imp::CONTAINER_RESTARTS.inc();
+}
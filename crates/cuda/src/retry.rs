@@ -0,0 +1,142 @@
+//! Retries of the requests to the GPU server.
+
+use std::time::Duration;
+
+use thiserror::Error;
+use twirp::ClientError;
+
+use crate::session::{GpuMethod, ReplayError};
+
+/// How the requests to the GPU server are retried.
+///
+/// A request which failed to connect to the server is sent again after a backoff doubling from
+/// `initial_backoff` up to `max_backoff`, until it was attempted `max_attempts` times. A request
+/// which may have reached the server, because it failed with another transport error or a server
+/// error, or timed out, is only sent again if its method is idempotent, see
+/// [GpuMethod::is_idempotent]: the GPU server does not deduplicate the attempts of a request.
+///
+/// Every attempt of a request carries the same request id, for servers which deduplicate them.
+/// The GPU server of this repository does not, and the retries do not rely on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of times a request is attempted, including the first one.
+    pub max_attempts: u32,
+    /// The backoff before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum backoff between two attempts.
+    pub max_backoff: Duration,
+    /// The time an attempt may take before it is abandoned, or `None` to wait for the response.
+    pub timeout: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// A policy attempting every request once, without a timeout.
+    pub const fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            timeout: None,
+        }
+    }
+
+    /// Reads the policy from the environment.
+    ///
+    /// `CUDA_RETRY_MAX_ATTEMPTS`, `CUDA_RETRY_BACKOFF_SECS`, `CUDA_RETRY_MAX_BACKOFF_SECS` and
+    /// `CUDA_REQUEST_TIMEOUT_SECS` override the fields of the default policy. A timeout of `0`
+    /// waits for the response.
+    pub fn from_env() -> Self {
+        fn read(name: &str) -> Option<u64> {
+            let value = std::env::var(name).ok()?;
+            Some(value.parse().unwrap_or_else(|_| panic!("Invalid {name}: {value}")))
+        }
+
+        let default = Self::default();
+        Self {
+            max_attempts: read("CUDA_RETRY_MAX_ATTEMPTS")
+                .map_or(default.max_attempts, |attempts| attempts.max(1) as u32),
+            initial_backoff: read("CUDA_RETRY_BACKOFF_SECS")
+                .map_or(default.initial_backoff, Duration::from_secs),
+            max_backoff: read("CUDA_RETRY_MAX_BACKOFF_SECS")
+                .map_or(default.max_backoff, Duration::from_secs),
+            timeout: read("CUDA_REQUEST_TIMEOUT_SECS")
+                .map_or(default.timeout, |secs| (secs != 0).then(|| Duration::from_secs(secs))),
+        }
+    }
+
+    /// The backoff before the `retry`-th retry, counting from 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(60),
+            timeout: None,
+        }
+    }
+}
+
+/// An error returned by a request to the GPU server.
+#[derive(Error, Debug)]
+pub enum GpuCallError {
+    #[error("the {method} request to the GPU server failed after {attempts} attempts: {source}")]
+    Client { method: GpuMethod, attempts: u32, source: ClientError },
+    #[error(
+        "the {method} request to the GPU server timed out after {timeout:?}, {attempts} times"
+    )]
+    TimedOut { method: GpuMethod, attempts: u32, timeout: Duration },
+    #[error(
+        "the GPU server container became unhealthy during the {method} request ({reason}) and could not be restarted: {restart}"
+    )]
+    Unhealthy { method: GpuMethod, reason: String, restart: String },
+    #[error("replay error: {0}")]
+    Replay(#[from] ReplayError),
+}
+
+/// Whether `error` may not happen again if the request is sent again.
+pub(crate) fn is_transient(error: &ClientError) -> bool {
+    match error {
+        ClientError::ReqwestError(_) => true,
+        ClientError::HttpError { status, .. } => status.is_server_error(),
+        _ => false,
+    }
+}
+
+/// Whether a request to `method` failing with `error` is sent again.
+///
+/// A request which failed to connect never reached the server, and is always sent again. Other
+/// transient errors are only retried for idempotent methods, since the server may have received
+/// the request, and may still be running it.
+pub(crate) fn is_retryable(method: GpuMethod, error: &ClientError) -> bool {
+    match error {
+        ClientError::ReqwestError(e) if e.is_connect() => true,
+        _ => is_transient(error) && method.is_idempotent(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(30),
+            timeout: None,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(policy.backoff(2), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(16));
+        assert_eq!(policy.backoff(5), Duration::from_secs(30));
+        assert_eq!(policy.backoff(64), Duration::from_secs(30));
+        assert_eq!(RetryPolicy::none().backoff(1), Duration::ZERO);
+    }
+}
@@ -0,0 +1,214 @@
+//! Recording and replay of the calls made to the GPU server.
+//!
+//! A [SessionRecorder] writes the request and response payloads of every call to a directory,
+//! one file per call. A [SessionReplayer] loads such a directory and answers the calls of a
+//! [crate::ZKMCudaProver] from it in order, so a proving session can be reproduced without the
+//! original GPU machine.
+
+use std::{
+    collections::VecDeque,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A method of the GPU server called with a serialized payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GpuMethod {
+    Setup,
+    ProveCore,
+    ProveCoreStateless,
+    ProveCoreShards,
+    Compress,
+    Shrink,
+    Wrap,
+    CompressStateless,
+    ShrinkStateless,
+    WrapStateless,
+}
+
+impl GpuMethod {
+    /// Whether sending a request to the method twice is harmless.
+    ///
+    /// The stateless methods only depend on their payload. The other methods read or write the
+    /// keys and proofs kept by the server for its session, so an attempt still running on the
+    /// server may race with a retry.
+    pub fn is_idempotent(self) -> bool {
+        matches!(
+            self,
+            GpuMethod::ProveCoreStateless
+                | GpuMethod::CompressStateless
+                | GpuMethod::ShrinkStateless
+                | GpuMethod::WrapStateless
+        )
+    }
+}
+
+impl fmt::Display for GpuMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GpuMethod::Setup => "setup",
+            GpuMethod::ProveCore => "prove_core",
+            GpuMethod::ProveCoreStateless => "prove_core_stateless",
+            GpuMethod::ProveCoreShards => "prove_core_shards",
+            GpuMethod::Compress => "compress",
+            GpuMethod::Shrink => "shrink",
+            GpuMethod::Wrap => "wrap",
+            GpuMethod::CompressStateless => "compress_stateless",
+            GpuMethod::ShrinkStateless => "shrink_stateless",
+            GpuMethod::WrapStateless => "wrap_stateless",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A recorded call to the GPU server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: GpuMethod,
+    pub request: Vec<u8>,
+    pub response: Vec<u8>,
+}
+
+/// An error returned when a call cannot be replayed.
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("malformed recording {path}: {source}")]
+    Malformed { path: PathBuf, source: bincode::Error },
+    #[error("call {index} is {actual}, but the recording has {expected}")]
+    MethodMismatch { index: usize, expected: GpuMethod, actual: GpuMethod },
+    #[error("call {index} ({method}) is past the end of the recording")]
+    Exhausted { index: usize, method: GpuMethod },
+}
+
+fn call_file_name(index: usize, method: GpuMethod) -> String {
+    format!("{index:06}-{method}.bin")
+}
+
+fn parse_call_index(name: &str) -> Option<usize> {
+    name.strip_suffix(".bin")?.split_once('-')?.0.parse().ok()
+}
+
+/// Records the calls made to the GPU server to a directory.
+#[derive(Debug)]
+pub struct SessionRecorder {
+    dir: PathBuf,
+    next: AtomicUsize,
+}
+
+impl SessionRecorder {
+    /// Creates a recorder writing to `dir`, creating it if it does not exist.
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, next: AtomicUsize::new(0) })
+    }
+
+    /// Records a call and its response.
+    ///
+    /// Failures are logged rather than returned, so recording never breaks the proving session.
+    pub fn record(&self, method: GpuMethod, request: &[u8], response: &[u8]) {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        let call = RecordedCall { method, request: request.to_vec(), response: response.to_vec() };
+        let path = self.dir.join(call_file_name(index, method));
+        let result = bincode::serialize(&call)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .and_then(|bytes| fs::write(&path, bytes));
+        if let Err(e) = result {
+            tracing::warn!("failed to record {} call to {}: {}", method, path.display(), e);
+        }
+    }
+}
+
+/// Answers the calls made to the GPU server from a recording.
+#[derive(Debug)]
+pub struct SessionReplayer {
+    calls: Mutex<VecDeque<RecordedCall>>,
+    next: AtomicUsize,
+}
+
+impl SessionReplayer {
+    /// Loads the recording in `dir`, written by a [SessionRecorder].
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(index) = path.file_name().and_then(|name| parse_call_index(name.to_str()?))
+            {
+                files.push((index, path));
+            }
+        }
+        files.sort();
+
+        let calls = files
+            .into_iter()
+            .map(|(_, path)| {
+                let bytes = fs::read(&path)?;
+                bincode::deserialize(&bytes)
+                    .map_err(|source| ReplayError::Malformed { path, source })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { calls: Mutex::new(calls), next: AtomicUsize::new(0) })
+    }
+
+    /// Returns the recorded response to the next call, which must be a call of `method`.
+    ///
+    /// A request differing from the recorded one is logged, but still answered, since the
+    /// recording is the closest reproduction of the session available.
+    pub fn replay(&self, method: GpuMethod, request: &[u8]) -> Result<Vec<u8>, ReplayError> {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        let call = self
+            .calls
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or(ReplayError::Exhausted { index, method })?;
+        if call.method != method {
+            return Err(ReplayError::MethodMismatch {
+                index,
+                expected: call.method,
+                actual: method,
+            });
+        }
+        if call.request != request {
+            tracing::warn!("call {} ({}) differs from the recorded request", index, method);
+        }
+        Ok(call.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = SessionRecorder::new(dir.path()).unwrap();
+        recorder.record(GpuMethod::Setup, b"elf", b"keys");
+        recorder.record(GpuMethod::ProveCoreStateless, b"stdin", b"proof");
+
+        let replayer = SessionReplayer::load(dir.path()).unwrap();
+        assert_eq!(replayer.replay(GpuMethod::Setup, b"elf").unwrap(), b"keys");
+        assert!(matches!(
+            replayer.replay(GpuMethod::Compress, b"stdin"),
+            Err(ReplayError::MethodMismatch {
+                index: 1,
+                expected: GpuMethod::ProveCoreStateless,
+                actual: GpuMethod::Compress,
+            })
+        ));
+        assert!(matches!(
+            replayer.replay(GpuMethod::Shrink, b""),
+            Err(ReplayError::Exhausted { index: 2, .. })
+        ));
+    }
+}
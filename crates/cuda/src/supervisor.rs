@@ -0,0 +1,127 @@
+//! Supervision of the GPU server container managed by a [crate::ZKMCudaProver].
+//!
+//! While a request is in flight, the container is checked periodically: its runtime must report
+//! it running, and the server must answer its readiness probe. A container failing the checks is
+//! restarted and the request sent again with the same request id, instead of waiting forever for
+//! the response of a server which ran out of memory.
+
+use std::time::Duration;
+
+use tokio::process::Command;
+use twirp::Client;
+
+use crate::{
+    api::{ProverServiceClient, ReadyRequest},
+    CudaProverContainer, ServerRuntime,
+};
+
+/// How the GPU server container managed by the prover is supervised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupervisorPolicy {
+    /// The interval between two checks of the container, and the timeout of a readiness probe.
+    pub health_interval: Duration,
+    /// The number of consecutive readiness probes failing before the container is restarted.
+    pub max_failed_probes: u32,
+    /// The number of times the container may be restarted over the lifetime of the prover.
+    pub max_restarts: u32,
+}
+
+impl SupervisorPolicy {
+    /// Reads the policy from the environment.
+    ///
+    /// `CUDA_HEALTH_INTERVAL_SECS`, `CUDA_HEALTH_MAX_FAILURES` and `CUDA_MAX_RESTARTS` override
+    /// the fields of the default policy.
+    pub fn from_env() -> Self {
+        fn read(name: &str) -> Option<u64> {
+            let value = std::env::var(name).ok()?;
+            Some(value.parse().unwrap_or_else(|_| panic!("Invalid {name}: {value}")))
+        }
+
+        let default = Self::default();
+        Self {
+            health_interval: read("CUDA_HEALTH_INTERVAL_SECS")
+                .map_or(default.health_interval, |secs| Duration::from_secs(secs.max(1))),
+            max_failed_probes: read("CUDA_HEALTH_MAX_FAILURES")
+                .map_or(default.max_failed_probes, |failures| failures.max(1) as u32),
+            max_restarts: read("CUDA_MAX_RESTARTS").map_or(default.max_restarts, |n| n as u32),
+        }
+    }
+}
+
+impl Default for SupervisorPolicy {
+    fn default() -> Self {
+        Self { health_interval: Duration::from_secs(30), max_failed_probes: 3, max_restarts: 3 }
+    }
+}
+
+/// Checks `container` and its server every [SupervisorPolicy::health_interval], returning why it
+/// is unhealthy once it is.
+pub(crate) async fn watch(
+    client: &Client,
+    container: &CudaProverContainer,
+    policy: &SupervisorPolicy,
+) -> String {
+    let name = &container.name;
+    let mut failed_probes = 0;
+    loop {
+        tokio::time::sleep(policy.health_interval).await;
+        if server_running(container).await == Some(false) {
+            return format!("the container {name} is not running");
+        }
+
+        let probe = tokio::time::timeout(policy.health_interval, client.ready(ReadyRequest {}));
+        match probe.await {
+            Ok(Ok(_)) => failed_probes = 0,
+            Ok(Err(e)) => {
+                failed_probes += 1;
+                tracing::warn!("readiness probe of the container {} failed: {}", name, e);
+            }
+            Err(_) => {
+                failed_probes += 1;
+                tracing::warn!("readiness probe of the container {} timed out", name);
+            }
+        }
+        if failed_probes >= policy.max_failed_probes {
+            return format!("{failed_probes} readiness probes of the container {name} failed");
+        }
+    }
+}
+
+/// Whether the server of `container` is running, or `None` if its container runtime cannot be
+/// queried.
+pub(crate) async fn server_running(container: &CudaProverContainer) -> Option<bool> {
+    let program = match &container.runtime {
+        ServerRuntime::Docker => "docker",
+        ServerRuntime::Podman => "podman",
+        ServerRuntime::Process { child, .. } => {
+            let mut child = child.lock().unwrap();
+            let child = child.as_mut()?;
+            return Some(matches!(child.try_wait(), Ok(None)));
+        }
+    };
+    let output = Command::new(program)
+        .args(["inspect", "--format", "{{.State.Running}}", &container.name])
+        .output()
+        .await
+        .ok()?;
+    // The inspection fails once the container is removed, which `--rm` does when it exits.
+    Some(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supervisor_policy_from_env() {
+        std::env::set_var("CUDA_HEALTH_INTERVAL_SECS", "0");
+        std::env::set_var("CUDA_MAX_RESTARTS", "5");
+        let policy = SupervisorPolicy::from_env();
+        std::env::remove_var("CUDA_HEALTH_INTERVAL_SECS");
+        std::env::remove_var("CUDA_MAX_RESTARTS");
+
+        assert_eq!(policy.health_interval, Duration::from_secs(1));
+        assert_eq!(policy.max_failed_probes, SupervisorPolicy::default().max_failed_probes);
+        assert_eq!(policy.max_restarts, 5);
+    }
+}
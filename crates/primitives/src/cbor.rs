@@ -0,0 +1,135 @@
+//! CBOR encodings of the inputs and outputs of the prover, for hosts not written in Rust.
+//!
+//! Unlike bincode, which follows the layout of the Rust types, the encodings follow the schemas
+//! in `docs/src/dev/cbor.md`, which only change with [CBOR_SCHEMA_VERSION]. Every encoding is a
+//! map with a `version` key, keys are written in the order of the schema, and decoders ignore the
+//! keys they don't know.
+
+pub use ciborium::value::Value;
+use thiserror::Error;
+
+use crate::io::ZKMPublicValues;
+
+/// The version of the schemas written by the CBOR encoders.
+pub const CBOR_SCHEMA_VERSION: u64 = 1;
+
+/// An error returned when encoding or decoding a CBOR value.
+#[derive(Error, Debug)]
+pub enum CborError {
+    #[error("malformed CBOR: {0}")]
+    Malformed(String),
+    #[error("unsupported schema version {0}")]
+    UnsupportedVersion(u64),
+    #[error("missing or invalid field {0:?}")]
+    InvalidField(&'static str),
+    #[error("{0} have no CBOR encoding")]
+    Unsupported(&'static str),
+}
+
+/// Encodes a CBOR value.
+pub fn to_vec(value: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).expect("failed to encode CBOR");
+    bytes
+}
+
+/// Decodes a CBOR value.
+pub fn from_slice(bytes: &[u8]) -> Result<Value, CborError> {
+    ciborium::from_reader(bytes).map_err(|e| CborError::Malformed(e.to_string()))
+}
+
+/// Builds the map of a schema, starting with its `version` key.
+pub fn versioned_map(fields: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+    let version = ("version", Value::Integer(CBOR_SCHEMA_VERSION.into()));
+    Value::Map(
+        std::iter::once(version)
+            .chain(fields)
+            .map(|(key, value)| (Value::Text(key.to_string()), value))
+            .collect(),
+    )
+}
+
+/// The fields of the map of a schema, checking its `version` key.
+pub struct Fields(Vec<(Value, Value)>);
+
+impl Fields {
+    /// Reads the fields of `value`, which must be a map of a supported version.
+    pub fn new(value: Value) -> Result<Self, CborError> {
+        let Value::Map(entries) = value else {
+            return Err(CborError::Malformed("expected a map".to_string()));
+        };
+        let fields = Self(entries);
+        let version = fields
+            .get("version")
+            .and_then(Value::as_integer)
+            .and_then(|version| u64::try_from(version).ok())
+            .ok_or(CborError::InvalidField("version"))?;
+        if version != CBOR_SCHEMA_VERSION {
+            return Err(CborError::UnsupportedVersion(version));
+        }
+        Ok(fields)
+    }
+
+    /// The value of the field `key`, if present.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.iter().find(|(k, _)| k.as_text() == Some(key)).map(|(_, value)| value)
+    }
+
+    /// The byte string of the required field `key`.
+    pub fn bytes(&self, key: &'static str) -> Result<&[u8], CborError> {
+        self.get(key)
+            .and_then(Value::as_bytes)
+            .map(Vec::as_slice)
+            .ok_or(CborError::InvalidField(key))
+    }
+}
+
+impl ZKMPublicValues {
+    /// Encodes the public values in CBOR:
+    ///
+    /// ```cddl
+    /// public-values = { "version": 1, "buffer": bstr }
+    /// ```
+    pub fn to_cbor(&self) -> Vec<u8> {
+        to_vec(&versioned_map([("buffer", Value::Bytes(self.to_vec()))]))
+    }
+
+    /// Decodes public values encoded with [Self::to_cbor].
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, CborError> {
+        let fields = Fields::new(from_slice(bytes)?)?;
+        Ok(Self::from(fields.bytes("buffer")?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_values_cbor() {
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write_slice(&[1, 2, 3]);
+        let bytes = public_values.to_cbor();
+        // A map of 2 entries: "version" => 1, "buffer" => h'010203'.
+        assert_eq!(hex::encode(&bytes), "a26776657273696f6e016662756666657243010203");
+        assert_eq!(ZKMPublicValues::from_cbor(&bytes).unwrap().as_slice(), &[1, 2, 3]);
+
+        let unknown = to_vec(&versioned_map([
+            ("buffer", Value::Bytes(vec![4])),
+            ("extra", Value::Bool(true)),
+        ]));
+        assert_eq!(ZKMPublicValues::from_cbor(&unknown).unwrap().as_slice(), &[4]);
+
+        let mut future = bytes.clone();
+        future[9] = 2;
+        assert!(matches!(
+            ZKMPublicValues::from_cbor(&future),
+            Err(CborError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            ZKMPublicValues::from_cbor(&to_vec(&versioned_map([]))),
+            Err(CborError::InvalidField("buffer"))
+        ));
+        assert!(matches!(ZKMPublicValues::from_cbor(&[0xff]), Err(CborError::Malformed(_))));
+    }
+}
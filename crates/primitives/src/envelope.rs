@@ -0,0 +1,110 @@
+//! The header of the versioned envelope in which the SDK encodes proofs.
+//!
+//! An envelope starts with [PROOF_ENVELOPE_MAGIC], followed by:
+//! - the envelope version [PROOF_ENVELOPE_VERSION], as a little-endian `u32`,
+//! - the circuit version of the build which encoded the proof, as its length in a little-endian
+//!   `u32` followed by its UTF-8 bytes,
+//! - the kind of the proof, as one byte,
+//! - the bincode encoding of the proof.
+//!
+//! The header is parsed here, so that every reader of proofs, in the SDK or in the verifier,
+//! accepts the same envelopes.
+
+use std::io::Read;
+
+use thiserror::Error;
+
+/// The magic bytes starting a proof envelope.
+pub const PROOF_ENVELOPE_MAGIC: [u8; 4] = *b"ZKMP";
+
+/// The version of the envelope written by this build.
+pub const PROOF_ENVELOPE_VERSION: u32 = 1;
+
+/// The longest circuit version accepted in an envelope header.
+pub const MAX_CIRCUIT_VERSION_LEN: usize = 256;
+
+/// An error returned when reading the header of a proof envelope.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProofEnvelopeError {
+    #[error("unsupported proof envelope version {0}")]
+    UnsupportedVersion(u32),
+    #[error("malformed envelope header")]
+    InvalidHeader,
+}
+
+/// The header of a proof envelope, following its magic bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofEnvelopeHeader {
+    /// The envelope version.
+    pub version: u32,
+    /// The circuit version of the build which encoded the proof.
+    pub circuit_version: String,
+    /// The byte of the kind of the proof, whose meaning is left to the reader.
+    pub kind: u8,
+}
+
+/// Reads the header of a proof envelope following its magic bytes, leaving `reader` at the
+/// bincode encoding of the proof.
+///
+/// The circuit version is not checked, so that the header of a proof encoded by another build can
+/// still be reported.
+pub fn read_proof_envelope_header(
+    reader: &mut impl Read,
+) -> Result<ProofEnvelopeHeader, ProofEnvelopeError> {
+    let mut read_exact =
+        |buf: &mut [u8]| reader.read_exact(buf).map_err(|_| ProofEnvelopeError::InvalidHeader);
+    let mut word = [0; 4];
+    read_exact(&mut word)?;
+    let version = u32::from_le_bytes(word);
+    if version != PROOF_ENVELOPE_VERSION {
+        return Err(ProofEnvelopeError::UnsupportedVersion(version));
+    }
+    read_exact(&mut word)?;
+    let len = u32::from_le_bytes(word) as usize;
+    if len > MAX_CIRCUIT_VERSION_LEN {
+        return Err(ProofEnvelopeError::InvalidHeader);
+    }
+    let mut circuit_version = vec![0; len];
+    read_exact(&mut circuit_version)?;
+    let circuit_version =
+        String::from_utf8(circuit_version).map_err(|_| ProofEnvelopeError::InvalidHeader)?;
+    let mut kind = [0];
+    read_exact(&mut kind)?;
+    Ok(ProofEnvelopeHeader { version, circuit_version, kind: kind[0] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_proof_envelope_header() {
+        let mut bytes = PROOF_ENVELOPE_VERSION.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(b"v1");
+        bytes.extend_from_slice(&[3, 0xAA]);
+
+        let mut reader = bytes.as_slice();
+        let header = read_proof_envelope_header(&mut reader).unwrap();
+        assert_eq!(
+            header,
+            ProofEnvelopeHeader {
+                version: PROOF_ENVELOPE_VERSION,
+                circuit_version: "v1".to_string(),
+                kind: 3
+            }
+        );
+        assert_eq!(reader, [0xAA]);
+
+        for len in 0..bytes.len() - 1 {
+            assert_eq!(
+                read_proof_envelope_header(&mut &bytes[..len]),
+                Err(ProofEnvelopeError::InvalidHeader)
+            );
+        }
+        assert_eq!(
+            read_proof_envelope_header(&mut &2u32.to_le_bytes()[..]),
+            Err(ProofEnvelopeError::UnsupportedVersion(2))
+        );
+    }
+}
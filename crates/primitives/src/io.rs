@@ -1,14 +1,208 @@
-use crate::types::Buffer;
+use crate::{merkle::MerkleRoot, poseidon2_hash_bytes, redact::Redacted, types::Buffer};
 use num_bigint::BigUint;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The magic bytes starting the header of a [PublicValuesTrailer].
+pub const PUBLIC_TRAILER_MAGIC: [u8; 8] = *b"ZKM_TRLR";
+
+/// The version of the layout of a [PublicValuesTrailer].
+pub const PUBLIC_TRAILER_VERSION: u32 = 2;
+
+/// The kind of the section of a [PublicValuesTrailer] holding the digest of the public logs.
+const TRAILER_LOG_DIGEST: u8 = 1;
+
+/// The kind of the section of a [PublicValuesTrailer] holding the seed of the random number
+/// generator.
+const TRAILER_RNG_SEED: u8 = 2;
+
+/// The kind of the section of a [PublicValuesTrailer] holding the table of the public value
+/// slots, which lists the index, the length and the record of every slot.
+const TRAILER_SLOTS: u8 = 3;
+
+/// The kind of the section of a [PublicValuesTrailer] holding the [PublicValuesHash] of the
+/// program, present unless it is SHA-256.
+const TRAILER_HASH: u8 = 4;
+
+/// The kind of the section of a [PublicValuesTrailer] holding the time base of the deterministic
+/// clock, as seconds and nanoseconds since the Unix epoch.
+const TRAILER_TIME_BASE: u8 = 5;
+
+/// The data appended by the program to its public values when it halts, so that it is committed
+/// along with them.
+///
+/// The trailer starts with a fixed header, [PUBLIC_TRAILER_MAGIC] and [PUBLIC_TRAILER_VERSION],
+/// followed by its sections, each a kind byte, a length and its bytes. It ends with its own length
+/// as a `u32`, from which it is found at the end of the public values.
+///
+/// The entrypoint appends the trailer when the program halts, after everything the program
+/// committed, even when it has no section. The length ending the public values is therefore always
+/// written by the entrypoint, and the program cannot forge a trailer: a trailer it commits itself
+/// is followed by the one of the entrypoint, and is read as committed bytes. Public values which do
+/// not end with a well formed trailer, such as the ones of programs built without the entrypoint,
+/// have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicValuesTrailer {
+    /// The digest of the public logs, if the program committed any.
+    pub log_digest: Option<[u8; 32]>,
+    /// The seed of the random number generator of the program, if it was provided by the host.
+    pub rng_seed: Option<[u8; 32]>,
+    /// The time base of the deterministic clock since the Unix epoch, if the program read the
+    /// clock.
+    pub time_base: Option<Duration>,
+    /// The records of the slots committed by the program with `zkm_zkvm::io::commit_slot`, by
+    /// index: the bincode serialized name of the type of the committed value and its serialized
+    /// bytes.
+    pub slots: BTreeMap<u32, Vec<u8>>,
+    /// The hash the program commits to its public values with.
+    pub hash: PublicValuesHash,
+}
+
+impl PublicValuesTrailer {
+    /// Encodes the trailer, followed by its length.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut trailer = Vec::new();
+        trailer.extend_from_slice(&PUBLIC_TRAILER_MAGIC);
+        trailer.extend_from_slice(&PUBLIC_TRAILER_VERSION.to_le_bytes());
+        let mut section = |kind: u8, bytes: &[u8]| {
+            trailer.push(kind);
+            trailer.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            trailer.extend_from_slice(bytes);
+        };
+        if let Some(digest) = &self.log_digest {
+            section(TRAILER_LOG_DIGEST, digest);
+        }
+        if let Some(seed) = &self.rng_seed {
+            section(TRAILER_RNG_SEED, seed);
+        }
+        if !self.slots.is_empty() {
+            let mut table = Vec::new();
+            for (idx, record) in &self.slots {
+                table.extend_from_slice(&idx.to_le_bytes());
+                table.extend_from_slice(&(record.len() as u32).to_le_bytes());
+                table.extend_from_slice(record);
+            }
+            section(TRAILER_SLOTS, &table);
+        }
+        if let Some(byte) = self.hash.trailer_byte() {
+            section(TRAILER_HASH, &[byte]);
+        }
+        if let Some(time_base) = &self.time_base {
+            let mut bytes = time_base.as_secs().to_le_bytes().to_vec();
+            bytes.extend_from_slice(&time_base.subsec_nanos().to_le_bytes());
+            section(TRAILER_TIME_BASE, &bytes);
+        }
+        let len = trailer.len() as u32;
+        trailer.extend_from_slice(&len.to_le_bytes());
+        trailer
+    }
+
+    /// Decodes the trailer ending `data`, returning the offset it starts at, or `None` if `data`
+    /// does not end with a well formed trailer.
+    pub fn decode(data: &[u8]) -> Option<(usize, Self)> {
+        let (rest, len) = data.split_last_chunk::<4>()?;
+        let start = rest.len().checked_sub(u32::from_le_bytes(*len) as usize)?;
+        let header = rest[start..].strip_prefix(&PUBLIC_TRAILER_MAGIC)?;
+        let (version, mut sections) = header.split_first_chunk::<4>()?;
+        if u32::from_le_bytes(*version) != PUBLIC_TRAILER_VERSION {
+            return None;
+        }
+
+        let mut trailer = Self::default();
+        let mut has_hash = false;
+        while let Some((&kind, rest)) = sections.split_first() {
+            let (len, rest) = rest.split_first_chunk::<4>()?;
+            let len = u32::from_le_bytes(*len) as usize;
+            if rest.len() < len {
+                return None;
+            }
+            let (bytes, rest) = rest.split_at(len);
+            match kind {
+                TRAILER_LOG_DIGEST if trailer.log_digest.is_none() => {
+                    trailer.log_digest = Some(bytes.try_into().ok()?);
+                }
+                TRAILER_RNG_SEED if trailer.rng_seed.is_none() => {
+                    trailer.rng_seed = Some(bytes.try_into().ok()?);
+                }
+                TRAILER_SLOTS if trailer.slots.is_empty() => {
+                    let mut table = bytes;
+                    while !table.is_empty() {
+                        let (idx, rest) = table.split_first_chunk::<4>()?;
+                        let (len, rest) = rest.split_first_chunk::<4>()?;
+                        let len = u32::from_le_bytes(*len) as usize;
+                        if rest.len() < len {
+                            return None;
+                        }
+                        let (record, rest) = rest.split_at(len);
+                        trailer.slots.insert(u32::from_le_bytes(*idx), record.to_vec());
+                        table = rest;
+                    }
+                }
+                TRAILER_HASH if !has_hash => {
+                    let [byte] = bytes else { return None };
+                    trailer.hash = PublicValuesHash::from_trailer_byte(*byte)?;
+                    has_hash = true;
+                }
+                TRAILER_TIME_BASE if trailer.time_base.is_none() => {
+                    let (secs, nanos) = bytes.split_first_chunk::<8>()?;
+                    let nanos = u32::from_le_bytes(nanos.try_into().ok()?);
+                    if nanos >= 1_000_000_000 {
+                        return None;
+                    }
+                    trailer.time_base = Some(Duration::new(u64::from_le_bytes(*secs), nanos));
+                }
+                // Unknown and repeated sections are malformed.
+                _ => return None,
+            }
+            sections = rest;
+        }
+        Some((start, trailer))
+    }
+}
+
+/// An error reading a public value slot with [ZKMPublicValues::get_slot].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotError {
+    /// The program did not commit the slot.
+    Missing(u32),
+    /// The program committed the slot as another type.
+    TypeMismatch { idx: u32, expected: String, committed: String },
+    /// The slot or the table of the slots could not be decoded.
+    Malformed(String),
+}
+
+impl std::fmt::Display for SlotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlotError::Missing(idx) => write!(f, "the program did not commit the slot {idx}"),
+            SlotError::TypeMismatch { idx, expected, committed } => write!(
+                f,
+                "the slot {idx} was committed as `{committed}`, but was read as `{expected}`"
+            ),
+            SlotError::Malformed(err) => write!(f, "malformed public value slots: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SlotError {}
 
 /// Public values for the prover.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ZKMPublicValues {
     buffer: Buffer,
 }
 
+impl std::fmt::Debug for ZKMPublicValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZKMPublicValues").field("buffer", &Redacted(self.as_slice())).finish()
+    }
+}
+
 impl ZKMPublicValues {
     /// Create a new `ZKMPublicValues`.
     pub const fn new() -> Self {
@@ -42,6 +236,14 @@ impl ZKMPublicValues {
         self.buffer.read_slice(slice);
     }
 
+    /// Read the root of an output committed by the program with `zkm_zkvm::io::commit_merkle`,
+    /// whose chunks are then checked with [MerkleRoot::verify].
+    pub fn read_merkle_root(&mut self) -> MerkleRoot {
+        let mut root = [0; 32];
+        self.buffer.read_slice(&mut root);
+        MerkleRoot(root)
+    }
+
     /// Write a value to the buffer.
     pub fn write<T: Serialize>(&mut self, data: &T) {
         self.buffer.write(data);
@@ -52,6 +254,77 @@ impl ZKMPublicValues {
         self.buffer.write_slice(slice);
     }
 
+    /// Returns the digest of the public logs committed by the program, if it committed any.
+    ///
+    /// The digest is part of the committed public values, so it is checked by proof verification.
+    pub fn log_digest(&self) -> Option<[u8; 32]> {
+        PublicValuesTrailer::decode(self.as_slice())?.1.log_digest
+    }
+
+    /// Returns the seed of the random number generator of the program, if it was provided by the
+    /// host with `ZKMContextBuilder::with_rng_seed`.
+    ///
+    /// The seed is part of the committed public values, so it is checked by proof verification.
+    pub fn rng_seed(&self) -> Option<[u8; 32]> {
+        PublicValuesTrailer::decode(self.as_slice())?.1.rng_seed
+    }
+
+    /// Returns the hash the program commits to its public values with, recorded in the
+    /// [PublicValuesTrailer] by programs built with the `poseidon2-commit` or `keccak-commit`
+    /// feature of `zkm-zkvm`, and SHA-256 otherwise.
+    ///
+    /// The mode is part of the committed public values, so it is bound to the proof, and the
+    /// digests of [Self::is_digest] and [Self::is_digest_bn254] are only accepted for this mode.
+    pub fn public_values_hash(&self) -> PublicValuesHash {
+        PublicValuesTrailer::decode(self.as_slice())
+            .map(|(_, trailer)| trailer.hash)
+            .unwrap_or_default()
+    }
+
+    /// Returns the time base of the deterministic clock, set by the host with
+    /// `ZKMContextBuilder::with_time_base`, if the program read the clock.
+    ///
+    /// The time base is part of the committed public values, so it is checked by proof
+    /// verification.
+    pub fn time_base(&self) -> Option<SystemTime> {
+        let time_base = PublicValuesTrailer::decode(self.as_slice())?.1.time_base?;
+        UNIX_EPOCH.checked_add(time_base)
+    }
+
+    /// Returns the value committed by the program to the slot `idx` with
+    /// `zkm_zkvm::io::commit_slot`.
+    ///
+    /// The slots are part of the committed public values, so they are checked by proof
+    /// verification. Types are compared by their names without their paths, as for named inputs.
+    pub fn get_slot<T: DeserializeOwned>(&self, idx: u32) -> Result<T, SlotError> {
+        let mut slots = PublicValuesTrailer::decode(self.as_slice())
+            .map(|(_, trailer)| trailer.slots)
+            .unwrap_or_default();
+        let record = slots.remove(&idx).ok_or(SlotError::Missing(idx))?;
+        let (committed, bytes): (String, Vec<u8>) =
+            bincode::deserialize(&record).map_err(|e| SlotError::Malformed(e.to_string()))?;
+        let expected = std::any::type_name::<T>();
+        if short_type_name(&committed) != short_type_name(expected) {
+            return Err(SlotError::TypeMismatch {
+                idx,
+                expected: short_type_name(expected),
+                committed: short_type_name(&committed),
+            });
+        }
+        bincode::deserialize(&bytes).map_err(|e| SlotError::Malformed(e.to_string()))
+    }
+
+    /// Returns the bytes committed by the program to the public values stream, without the
+    /// [PublicValuesTrailer] holding its slots, the seed of its random number generator, the time
+    /// base of its clock and the digest of the public logs.
+    pub fn committed_values(&self) -> &[u8] {
+        let data = self.as_slice();
+        match PublicValuesTrailer::decode(data) {
+            Some((start, _)) => &data[..start],
+            None => data,
+        }
+    }
+
     /// Hash the public values.
     pub fn hash(&self) -> Vec<u8> {
         let mut hasher = Sha256::new();
@@ -59,6 +332,23 @@ impl ZKMPublicValues {
         hasher.finalize().to_vec()
     }
 
+    /// Hash the public values with `hash`, as a program built with the matching feature of
+    /// `zkm-zkvm` commits them.
+    pub fn hash_with(&self, hash: PublicValuesHash) -> Vec<u8> {
+        let data = self.buffer.data.as_slice();
+        match hash {
+            PublicValuesHash::Sha256 => self.hash(),
+            PublicValuesHash::Poseidon2 => poseidon2_hash_bytes(data).to_vec(),
+            PublicValuesHash::Keccak256 => Keccak256::digest(data).to_vec(),
+        }
+    }
+
+    /// Returns true if `digest` is the digest a program built with the `hash` mode commits to
+    /// these public values, and they record that mode, see [Self::public_values_hash].
+    pub fn is_digest(&self, digest: &[u8], hash: PublicValuesHash) -> bool {
+        self.public_values_hash() == hash && digest == self.hash_with(hash)
+    }
+
     /// Hash the public values, mask the top 3 bits and return a BigUint. Matches the implementation
     /// of `hashPublicValues` in the Solidity verifier.
     ///
@@ -66,18 +356,59 @@ impl ZKMPublicValues {
     /// sha256(publicValues) & bytes32(uint256((1 << 253) - 1));
     /// ```
     pub fn hash_bn254(&self) -> BigUint {
-        // Hash the public values.
-        let mut hasher = Sha256::new();
-        hasher.update(self.buffer.data.as_slice());
-        let hash_result = hasher.finalize();
-        let mut hash = hash_result.to_vec();
+        self.hash_bn254_with(PublicValuesHash::Sha256)
+    }
 
-        // Mask the top 3 bits.
+    /// Like [Self::hash_bn254], for the digest of [Self::hash_with].
+    pub fn hash_bn254_with(&self, hash: PublicValuesHash) -> BigUint {
+        let mut hash = self.hash_with(hash);
         hash[0] &= 0b00011111;
-
-        // Return the masked hash as a BigUint.
         BigUint::from_bytes_be(&hash)
     }
+
+    /// Returns true if `digest` is the committed values digest public input of a Plonk or Groth16
+    /// proof of a program built with the `hash` mode committing to these public values.
+    pub fn is_digest_bn254(&self, digest: &BigUint, hash: PublicValuesHash) -> bool {
+        self.public_values_hash() == hash && *digest == self.hash_bn254_with(hash)
+    }
+}
+
+/// The hash a program commits to its public values with, selected by a feature of `zkm-zkvm`.
+///
+/// The circuits only carry the 32-byte digest, so they are the same in every mode. The program
+/// records its mode in the [PublicValuesTrailer] it commits, unless it is SHA-256, so the mode is
+/// bound to the proof: a verifier only accepts the digest of the mode the public values record,
+/// see [ZKMPublicValues::public_values_hash].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PublicValuesHash {
+    /// SHA-256, the default.
+    #[default]
+    Sha256,
+    /// Poseidon2 over KoalaBear, with the `poseidon2-commit` feature, see
+    /// [crate::poseidon2_hash_bytes].
+    Poseidon2,
+    /// Keccak-256, with the `keccak-commit` feature, which the EVM recomputes with a native
+    /// opcode.
+    Keccak256,
+}
+
+impl PublicValuesHash {
+    /// The byte of the mode in the [PublicValuesTrailer], which has no section for SHA-256.
+    fn trailer_byte(self) -> Option<u8> {
+        match self {
+            PublicValuesHash::Sha256 => None,
+            PublicValuesHash::Poseidon2 => Some(1),
+            PublicValuesHash::Keccak256 => Some(2),
+        }
+    }
+
+    fn from_trailer_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(PublicValuesHash::Poseidon2),
+            2 => Some(PublicValuesHash::Keccak256),
+            _ => None,
+        }
+    }
 }
 
 impl AsRef<[u8]> for ZKMPublicValues {
@@ -86,10 +417,234 @@ impl AsRef<[u8]> for ZKMPublicValues {
     }
 }
 
+/// Auxiliary values committed by the program with `zkm_zkvm::io::commit_log`.
+///
+/// Only the digest of the logs is committed in the [ZKMPublicValues], so the logs themselves are
+/// obtained from the execution, and checked against a proof with [Self::matches].
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct ZKMPublicLogs {
+    buffer: Buffer,
+}
+
+impl std::fmt::Debug for ZKMPublicLogs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZKMPublicLogs").field("buffer", &Redacted(self.as_slice())).finish()
+    }
+}
+
+impl ZKMPublicLogs {
+    /// Create a `ZKMPublicLogs` from a slice of bytes.
+    pub fn from(data: &[u8]) -> Self {
+        Self { buffer: Buffer::from(data) }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        self.buffer.data.as_slice()
+    }
+
+    /// Read a value from the buffer.
+    pub fn read<T: Serialize + DeserializeOwned>(&mut self) -> T {
+        self.buffer.read()
+    }
+
+    /// Read a slice of bytes from the buffer.
+    pub fn read_slice(&mut self, slice: &mut [u8]) {
+        self.buffer.read_slice(slice);
+    }
+
+    /// Hash the public logs.
+    pub fn digest(&self) -> [u8; 32] {
+        Sha256::digest(self.as_slice()).into()
+    }
+
+    /// Returns true if these are the logs committed in `public_values`.
+    ///
+    /// Empty logs match public values without a log digest, since programs which never call
+    /// `commit_log` do not commit one.
+    pub fn matches(&self, public_values: &ZKMPublicValues) -> bool {
+        match public_values.log_digest() {
+            Some(digest) => digest == self.digest(),
+            None => self.as_slice().is_empty(),
+        }
+    }
+}
+
+/// The name of a type without the paths of the types it names, so that the same type is named
+/// alike by the host and the program, whatever the path they import it from.
+pub fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    // The position in `short` of the path segment being read.
+    let mut segment_start = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            short.truncate(segment_start);
+        } else {
+            short.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                segment_start = short.len();
+            }
+        }
+    }
+    short
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_public_logs() {
+        let mut logs = ZKMPublicLogs::from(&bincode::serialize(&7u32).unwrap());
+
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u64);
+        assert_eq!(public_values.log_digest(), None);
+        assert_eq!(public_values.committed_values(), public_values.as_slice());
+        assert!(!logs.matches(&public_values));
+        assert!(ZKMPublicLogs::default().matches(&public_values));
+
+        let committed = public_values.to_vec();
+        let trailer = PublicValuesTrailer { log_digest: Some(logs.digest()), ..Default::default() };
+        public_values.write_slice(&trailer.encode());
+        assert_eq!(public_values.log_digest(), Some(logs.digest()));
+        assert_eq!(public_values.committed_values(), committed.as_slice());
+        assert!(logs.matches(&public_values));
+        assert_eq!(public_values.read::<u64>(), 42);
+        assert_eq!(logs.read::<u32>(), 7);
+    }
+
+    #[test]
+    fn test_rng_seed() {
+        let logs = ZKMPublicLogs::from(&bincode::serialize(&7u32).unwrap());
+
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u64);
+        assert_eq!(public_values.rng_seed(), None);
+
+        let committed = public_values.to_vec();
+        let mut seeded = public_values.clone();
+        let trailer = PublicValuesTrailer { rng_seed: Some([1; 32]), ..Default::default() };
+        seeded.write_slice(&trailer.encode());
+        assert_eq!(seeded.rng_seed(), Some([1; 32]));
+        assert_eq!(seeded.log_digest(), None);
+        assert_eq!(seeded.committed_values(), committed.as_slice());
+
+        let trailer = PublicValuesTrailer {
+            log_digest: Some(logs.digest()),
+            rng_seed: Some([1; 32]),
+            ..Default::default()
+        };
+        public_values.write_slice(&trailer.encode());
+        assert_eq!(public_values.rng_seed(), Some([1; 32]));
+        assert_eq!(public_values.committed_values(), committed.as_slice());
+        assert!(logs.matches(&public_values));
+    }
+
+    #[test]
+    fn test_time_base() {
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u64);
+        assert_eq!(public_values.time_base(), None);
+
+        let committed = public_values.to_vec();
+        let time_base = Duration::new(1_700_000_000, 5);
+        let trailer = PublicValuesTrailer {
+            rng_seed: Some([1; 32]),
+            time_base: Some(time_base),
+            ..Default::default()
+        };
+        public_values.write_slice(&trailer.encode());
+        assert_eq!(public_values.time_base(), Some(UNIX_EPOCH + time_base));
+        assert_eq!(public_values.rng_seed(), Some([1; 32]));
+        assert_eq!(public_values.committed_values(), committed.as_slice());
+
+        // A time base with a whole second of nanoseconds is malformed.
+        let mut trailer =
+            PublicValuesTrailer { time_base: Some(time_base), ..Default::default() }.encode();
+        let nanos = trailer.len() - 8;
+        trailer[nanos..nanos + 4].copy_from_slice(&1_000_000_000u32.to_le_bytes());
+        assert_eq!(PublicValuesTrailer::decode(&trailer), None);
+    }
+
+    #[test]
+    fn test_public_slots() {
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u64);
+        assert_eq!(public_values.get_slot::<u64>(0), Err(SlotError::Missing(0)));
+
+        let committed = public_values.to_vec();
+        let record = |type_name: &str, bytes: Vec<u8>| {
+            bincode::serialize(&(type_name.to_string(), bytes)).unwrap()
+        };
+        let slots = BTreeMap::from([
+            (0, record("u32", bincode::serialize(&7u32).unwrap())),
+            (3, record("alloc::string::String", bincode::serialize("root").unwrap())),
+        ]);
+        let trailer = PublicValuesTrailer { rng_seed: Some([1; 32]), slots, ..Default::default() };
+        public_values.write_slice(&trailer.encode());
+        assert_eq!(public_values.committed_values(), committed.as_slice());
+        assert_eq!(public_values.rng_seed(), Some([1; 32]));
+        assert_eq!(public_values.get_slot::<u32>(0), Ok(7));
+        assert_eq!(public_values.get_slot::<String>(3), Ok("root".to_string()));
+        assert_eq!(public_values.get_slot::<u32>(1), Err(SlotError::Missing(1)));
+        assert!(matches!(
+            public_values.get_slot::<u64>(0),
+            Err(SlotError::TypeMismatch { idx: 0, .. })
+        ));
+        assert_eq!(public_values.read::<u64>(), 42);
+    }
+
+    #[test]
+    fn test_public_values_trailer() {
+        let trailer = PublicValuesTrailer {
+            log_digest: Some([2; 32]),
+            rng_seed: Some([3; 32]),
+            time_base: Some(Duration::new(1_700_000_000, 999_999_999)),
+            slots: BTreeMap::from([(1, vec![4; 5]), (7, vec![])]),
+            hash: PublicValuesHash::Keccak256,
+        };
+        let mut data = b"committed".to_vec();
+        data.extend_from_slice(&trailer.encode());
+        assert_eq!(PublicValuesTrailer::decode(&data), Some((9, trailer.clone())));
+
+        // Committed bytes which only end like a trailer are not one.
+        assert_eq!(PublicValuesTrailer::decode(b"committed"), None);
+        assert_eq!(PublicValuesTrailer::decode(&[0xff; 4]), None);
+        let mut forged = trailer.encode();
+        forged[8] = 2;
+        assert_eq!(PublicValuesTrailer::decode(&forged), None);
+        let mut truncated = trailer.encode();
+        truncated.remove(20);
+        assert_eq!(PublicValuesTrailer::decode(&truncated), None);
+    }
+
+    #[test]
+    fn test_forged_public_values_trailer() {
+        // A trailer committed by the program is followed by the one appended at halt, so it is
+        // read as committed bytes.
+        let forged = PublicValuesTrailer { rng_seed: Some([5; 32]), ..Default::default() };
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u64);
+        public_values.write_slice(&forged.encode());
+        let committed = public_values.to_vec();
+        public_values.write_slice(&PublicValuesTrailer::default().encode());
+        assert_eq!(public_values.committed_values(), committed.as_slice());
+        assert_eq!(public_values.rng_seed(), None);
+        assert_eq!(public_values.public_values_hash(), PublicValuesHash::Sha256);
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("alloc::vec::Vec<u8>"), "Vec<u8>");
+        assert_eq!(
+            short_type_name("core::option::Option<(guest::Header, alloc::string::String)>"),
+            "Option<(Header, String)>"
+        );
+        assert_eq!(short_type_name("[u8; 32]"), "[u8; 32]");
+    }
+
     #[test]
     fn test_hash_public_values() {
         let test_hex = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
@@ -104,4 +659,49 @@ mod tests {
 
         assert_eq!(hash, expected_hash_biguint);
     }
+
+    #[test]
+    fn test_public_values_digests() {
+        let hashes =
+            [PublicValuesHash::Sha256, PublicValuesHash::Poseidon2, PublicValuesHash::Keccak256];
+        for hash in hashes {
+            let mut public_values = ZKMPublicValues::new();
+            public_values.write_slice(b"public values");
+            public_values.write_slice(&PublicValuesTrailer { hash, ..Default::default() }.encode());
+            assert_eq!(public_values.public_values_hash(), hash);
+            assert_eq!(public_values.committed_values(), b"public values");
+
+            assert!(public_values.is_digest(&public_values.hash_with(hash), hash));
+            assert!(public_values.is_digest_bn254(&public_values.hash_bn254_with(hash), hash));
+            // The digest of another mode is rejected.
+            for other in hashes.into_iter().filter(|&other| other != hash) {
+                assert!(!public_values.is_digest(&public_values.hash_with(other), hash));
+                let digest = public_values.hash_bn254_with(other);
+                assert!(!public_values.is_digest_bn254(&digest, hash));
+                // The digest of another mode is rejected even when it is the one expected, since
+                // the public values record the mode of the program.
+                assert!(!public_values.is_digest(&public_values.hash_with(other), other));
+            }
+        }
+
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write_slice(b"public values");
+        assert_ne!(
+            public_values.hash_with(PublicValuesHash::Poseidon2),
+            public_values.hash_with(PublicValuesHash::Sha256)
+        );
+        assert_eq!(
+            hex::encode(ZKMPublicValues::new().hash_with(PublicValuesHash::Keccak256)),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert!(!public_values.is_digest(&[0; 32], PublicValuesHash::Sha256));
+        assert!(!public_values.is_digest_bn254(&BigUint::from(0u32), PublicValuesHash::Sha256));
+
+        // Every length of padding hashes to a distinct digest.
+        let digests =
+            (0..=48).map(|len| crate::poseidon2_hash_bytes(&vec![0; len])).collect::<Vec<_>>();
+        for (i, digest) in digests.iter().enumerate() {
+            assert!(!digests[..i].contains(digest));
+        }
+    }
 }
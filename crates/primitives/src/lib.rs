@@ -2,13 +2,18 @@
 //! Because it is imported in the zkvm entrypoint, it should be kept minimal.
 
 use lazy_static::lazy_static;
-use p3_field::FieldAlgebra;
+use p3_field::{FieldAlgebra, PrimeField32};
 use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
 use p3_poseidon2::{ExternalLayerConstants, Poseidon2};
 //use p3_monty_31::{Poseidon2InternalLayerMonty31, Poseidon2ExternalLayerMonty31};
 
+#[cfg(feature = "cbor")]
+pub mod cbor;
 pub mod consts;
+pub mod envelope;
 pub mod io;
+pub mod merkle;
+pub mod redact;
 pub mod types;
 
 lazy_static! {
@@ -1120,12 +1125,46 @@ pub fn poseidon2_init() -> Poseidon2KoalaBear<16> {
     Poseidon2::new(external_round_constants, internal_round_constants)
 }
 
-use p3_symmetric::{CryptographicHasher, PaddingFreeSponge};
+use p3_symmetric::{CryptographicHasher, PaddingFreeSponge, Permutation};
 
 pub fn poseidon2_hash(input: Vec<KoalaBear>) -> [KoalaBear; 8] {
     POSEIDON2_HASHER.hash_iter(input)
 }
 
+/// Hashes bytes with Poseidon2 as `zkm_lib::poseidon2::poseidon2` does in the guest.
+///
+/// The input is padded with `1*01` bits to a multiple of 3 bytes, every 3 bytes are read as a
+/// little-endian field element, and the elements are absorbed 8 at a time by overwriting the rate
+/// of the state. The digest is the first 8 elements of the state, as little-endian `u32`s.
+pub fn poseidon2_hash_bytes(input: &[u8]) -> [u8; 32] {
+    let len = input.len();
+    let padded_len = (len + 3) / 3 * 3;
+    let mut padded = input.to_vec();
+    padded.resize(padded_len, 0);
+    if len % 3 == 2 {
+        padded[len] = 0b10000001;
+    } else {
+        padded[len] = 1;
+        padded[padded_len - 1] = 0b10000000;
+    }
+
+    let permutation = poseidon2_init();
+    let mut state = [KoalaBear::ZERO; 16];
+    for chunk in padded.chunks(24) {
+        for (element, bytes) in state.iter_mut().zip(chunk.chunks(3)) {
+            let value = bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u32);
+            *element = KoalaBear::from_canonical_u32(value);
+        }
+        permutation.permute_mut(&mut state);
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, element) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&element.as_canonical_u32().to_le_bytes());
+    }
+    digest
+}
+
 pub fn poseidon2_hasher() -> PaddingFreeSponge<Poseidon2KoalaBear<16>, 16, 8, 8> {
     let hasher = poseidon2_init();
     PaddingFreeSponge::<Poseidon2KoalaBear<16>, 16, 8, 8>::new(hasher)
@@ -0,0 +1,151 @@
+//! Merkle trees of the chunks of large outputs, committed by the program with
+//! `zkm_zkvm::io::commit_merkle`.
+//!
+//! Only the root of the tree is committed in the public values, so a verifier checks a chunk of
+//! the output against the root with a [MerkleOpening], without the other chunks.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The prefix of the hash of a leaf, so that a leaf cannot be opened as an inner node.
+const LEAF_PREFIX: u8 = 0;
+
+/// The prefix of the hash of an inner node.
+const NODE_PREFIX: u8 = 1;
+
+/// The root of a [MerkleTree], committed by the program with `zkm_zkvm::io::commit_merkle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MerkleRoot(pub [u8; 32]);
+
+impl MerkleRoot {
+    /// Returns true if `opening` proves that `chunk` is the chunk of index `opening.index` of
+    /// the output whose tree has this root.
+    pub fn verify(&self, chunk: &[u8], opening: &MerkleOpening) -> bool {
+        if opening.index >= opening.leaf_count {
+            return false;
+        }
+        let mut siblings = opening.siblings.iter();
+        let mut hash = hash_leaf(chunk);
+        let (mut index, mut width) = (opening.index, opening.leaf_count);
+        while width > 1 {
+            // The last node of a level of odd width is promoted to the next level as is.
+            if index % 2 == 1 || index + 1 < width {
+                let Some(sibling) = siblings.next() else { return false };
+                hash = if index % 2 == 1 {
+                    hash_node(sibling, &hash)
+                } else {
+                    hash_node(&hash, sibling)
+                };
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == self.0
+    }
+}
+
+/// The path from a chunk to the root of a [MerkleTree].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleOpening {
+    /// The index of the chunk.
+    pub index: u64,
+    /// The number of chunks of the output.
+    pub leaf_count: u64,
+    /// The siblings of the nodes on the path from the chunk to the root, from the bottom up.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// A Merkle tree of the chunks of an output.
+///
+/// The leaves are the SHA-256 hashes of the chunks, and the last node of a level of odd width is
+/// promoted to the next level. The tree of no chunks has the root `[0; 32]`.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// The levels of the tree, from the leaves up to the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree of `chunks`.
+    pub fn new<C: AsRef<[u8]>>(chunks: &[C]) -> Self {
+        let mut levels = vec![chunks.iter().map(|chunk| hash_leaf(chunk.as_ref())).collect()];
+        while levels.last().unwrap().len() > 1 {
+            let level: &Vec<[u8; 32]> = levels.last().unwrap();
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// The root of the tree.
+    pub fn root(&self) -> MerkleRoot {
+        MerkleRoot(self.levels.last().unwrap().first().copied().unwrap_or([0; 32]))
+    }
+
+    /// The number of chunks of the tree.
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Opens the chunk of index `index`, or returns `None` if the tree has no such chunk.
+    pub fn open(&self, index: usize) -> Option<MerkleOpening> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            position /= 2;
+        }
+        Some(MerkleOpening { index: index as u64, leaf_count: self.leaf_count() as u64, siblings })
+    }
+}
+
+fn hash_leaf(chunk: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update([LEAF_PREFIX]).chain_update(chunk).finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_openings() {
+        for leaf_count in 1..=9 {
+            let chunks: Vec<Vec<u8>> = (0..leaf_count).map(|i| vec![i as u8; 3]).collect();
+            let tree = MerkleTree::new(&chunks);
+            let root = tree.root();
+            for (index, chunk) in chunks.iter().enumerate() {
+                let opening = tree.open(index).unwrap();
+                assert!(root.verify(chunk, &opening));
+                assert!(!root.verify(&[9; 3], &opening));
+            }
+            assert_eq!(tree.open(leaf_count), None);
+        }
+
+        let tree = MerkleTree::new(&[b"a", b"b", b"c"]);
+        let mut opening = tree.open(1).unwrap();
+        opening.index = 0;
+        assert!(!tree.root().verify(b"b", &opening));
+        assert_eq!(MerkleTree::new::<&[u8]>(&[]).root(), MerkleRoot([0; 32]));
+    }
+}
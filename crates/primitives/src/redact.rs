@@ -0,0 +1,69 @@
+//! Redaction of sensitive guest data, such as stdin buffers, hints and public values, in logs.
+
+use std::{fmt, sync::LazyLock};
+
+use sha2::{Digest, Sha256};
+
+/// The environment variable that, when set to `1` or `true`, prints guest data in logs verbatim.
+pub const UNSAFE_LOG_INPUTS_ENV: &str = "ZKM_UNSAFE_LOG_INPUTS";
+
+static UNSAFE_LOG_INPUTS: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var(UNSAFE_LOG_INPUTS_ENV)
+        .map(|v| v == "1" || v.to_lowercase() == "true")
+        .unwrap_or(false)
+});
+
+/// Whether guest data may be printed verbatim, see [UNSAFE_LOG_INPUTS_ENV].
+pub fn unsafe_log_inputs() -> bool {
+    *UNSAFE_LOG_INPUTS
+}
+
+/// A wrapper that formats guest data as its length and SHA-256 digest, unless
+/// [UNSAFE_LOG_INPUTS_ENV] is set.
+pub struct Redacted<'a>(pub &'a [u8]);
+
+impl Redacted<'_> {
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, reveal: bool) -> fmt::Result {
+        if reveal {
+            write!(f, "0x{}", hex::encode(self.0))
+        } else {
+            let digest = Sha256::digest(self.0);
+            write!(f, "<redacted {} bytes, sha256: 0x{}>", self.0.len(), hex::encode(digest))
+        }
+    }
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, unsafe_log_inputs())
+    }
+}
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Reveal<'a>(Redacted<'a>, bool);
+
+    impl fmt::Display for Reveal<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.fmt_with(f, self.1)
+        }
+    }
+
+    #[test]
+    fn test_redacted() {
+        assert_eq!(Reveal(Redacted(&[0xab, 0xcd]), true).to_string(), "0xabcd");
+        assert_eq!(
+            Reveal(Redacted(&[]), false).to_string(),
+            "<redacted 0 bytes, sha256: \
+             0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855>"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::{path::PathBuf, thread::available_parallelism};
+
+use clap::Parser;
+use zkm_core_machine::utils::setup_logger;
+use zkm_prover::{
+    components::DefaultProverComponents,
+    shapes::{build_vk_maps, VkMapProgress},
+    REDUCE_BATCH_SIZE,
+};
+
+/// Regenerates `vk_map.bin` and `dummy_vk_map.bin` from every allowed shape.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(short, long, default_value = "crates/prover")]
+    build_dir: PathBuf,
+    #[clap(short, long, default_value_t = REDUCE_BATCH_SIZE)]
+    reduce_batch_size: usize,
+    #[clap(short, long)]
+    num_compiler_workers: Option<usize>,
+    #[clap(short, long)]
+    count_setup_workers: Option<usize>,
+}
+
+fn main() {
+    setup_logger();
+    let args = Args::parse();
+
+    let num_cpus = available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let num_compiler_workers = args.num_compiler_workers.unwrap_or(num_cpus);
+    let num_setup_workers = args.count_setup_workers.unwrap_or(num_cpus);
+
+    build_vk_maps::<DefaultProverComponents>(
+        args.build_dir,
+        args.reduce_batch_size,
+        num_compiler_workers,
+        num_setup_workers,
+        |VkMapProgress { done, failed, total }| {
+            tracing::info!("{done}/{total} verifying keys built, {failed} shapes failed");
+        },
+    )
+    .unwrap();
+}
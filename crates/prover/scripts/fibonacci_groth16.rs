@@ -61,6 +61,8 @@ fn main() {
             buffer: vec![bincode::serialize::<u32>(&iterations).unwrap()],
             ptr: 0,
             proofs: vec![],
+            files: Default::default(),
+            named_inputs: Default::default(),
         };
         let leaf_proving_start = Instant::now();
         let proof = prover
@@ -212,6 +212,8 @@ fn collect_maximal_shapes(
     let program = Program::from(elf).unwrap();
     let mut executor = Executor::with_context(program, opts, context);
     executor.write_vecs(&stdin.buffer);
+    executor.write_files(&stdin.files);
+    executor.write_named_inputs(&stdin.named_inputs);
     for (proof, vkey) in stdin.proofs.iter() {
         executor.write_proof(proof.clone(), vkey.clone());
     }
@@ -65,7 +65,7 @@ fn main() {
         // Read the program and stdin.
         let elf = std::fs::read(path.clone() + "/program.bin").expect("failed to read program");
         let stdin = std::fs::read(path.clone() + "/stdin.bin").expect("failed to read stdin");
-        let stdin: ZKMStdin = bincode::deserialize(&stdin).expect("failed to deserialize stdin");
+        let stdin = ZKMStdin::from_bytes(&stdin).expect("failed to deserialize stdin");
 
         // Collect the maximal shapes for each shard size.
         let elf = elf.clone();
@@ -95,6 +95,8 @@ fn test_shape_fixing(
         shape_config.maximal_core_shapes(log2_ceil_usize(opts.shard_size)).into_iter().collect(),
     );
     executor.write_vecs(&stdin.buffer);
+    executor.write_files(&stdin.files);
+    executor.write_named_inputs(&stdin.named_inputs);
     for (proof, vkey) in stdin.proofs.iter() {
         executor.write_proof(proof.clone(), vkey.clone());
     }
@@ -0,0 +1,313 @@
+//! Persistence of core proving progress, allowing [`ZKMProver::prove_core_resumable`] to resume
+//! an interrupted proof from the shards which were already proven, and
+//! [`ZKMProver::prove_core_incremental`] to reuse the proofs of the shards which did not change
+//! since a previous proof.
+
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use p3_field::PrimeField32;
+use p3_koala_bear::KoalaBear;
+use serde::{Deserialize, Serialize};
+use zkm_core_machine::{
+    io::ZKMStdin,
+    utils::{ShardProofCache, ZKMCoreProverError},
+};
+use zkm_stark::{
+    air::PublicValues, MachineProver, ShardProof, SplitOpts, StarkGenericConfig, Word, ZKMCoreOpts,
+};
+
+use crate::{
+    components::ZKMProverComponents, CoreSC, HashableKey, ZKMProver, ZKMVerifyingKey,
+    ZKM_CIRCUIT_VERSION,
+};
+
+/// The name of the file identifying the proving run of a checkpoint directory.
+const MANIFEST_FILE: &str = "manifest.bin";
+
+/// Identifies the proving run a checkpoint directory belongs to.
+///
+/// Every field affects how the execution is split into shards, so shard proofs are only reused
+/// when all of them match.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CheckpointManifest {
+    version: String,
+    vk_hash: [u32; 8],
+    shard_size: usize,
+    split_opts: SplitOpts,
+    stdin: Vec<u8>,
+}
+
+/// A directory holding the shard proofs of a core proof in progress.
+#[derive(Debug)]
+pub struct CheckpointDir {
+    path: PathBuf,
+}
+
+impl CheckpointDir {
+    /// Opens the checkpoint directory at `path` for proving the program of `vk` on `stdin` with
+    /// `opts`, creating it if it does not exist.
+    ///
+    /// Fails if the directory holds the checkpoints of a different proving run.
+    pub fn open(
+        path: &Path,
+        vk: &ZKMVerifyingKey,
+        stdin: &ZKMStdin,
+        opts: &ZKMCoreOpts,
+    ) -> Result<Self, ZKMCoreProverError> {
+        let manifest = CheckpointManifest {
+            version: ZKM_CIRCUIT_VERSION.to_string(),
+            vk_hash: vk.hash_u32(),
+            shard_size: opts.shard_size,
+            split_opts: opts.split_opts,
+            stdin: bincode::serialize(stdin).map_err(ZKMCoreProverError::SerializationError)?,
+        };
+
+        fs::create_dir_all(path).map_err(ZKMCoreProverError::IoError)?;
+        let manifest_path = path.join(MANIFEST_FILE);
+        match fs::read(&manifest_path) {
+            Ok(bytes) => {
+                let existing: CheckpointManifest =
+                    bincode::deserialize(&bytes).map_err(ZKMCoreProverError::SerializationError)?;
+                if existing != manifest {
+                    return Err(ZKMCoreProverError::IoError(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "checkpoint directory {} belongs to a different proving run",
+                            path.display()
+                        ),
+                    )));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let bytes = bincode::serialize(&manifest)
+                    .map_err(ZKMCoreProverError::SerializationError)?;
+                write_atomic(&manifest_path, &bytes).map_err(ZKMCoreProverError::IoError)?;
+            }
+            Err(e) => return Err(ZKMCoreProverError::IoError(e)),
+        }
+
+        Ok(Self { path: path.to_path_buf() })
+    }
+
+    /// Returns the path of the checkpoint directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the persisted shard proofs which are valid for `vk`, keyed by shard index.
+    ///
+    /// Proofs which cannot be read or do not verify are skipped, and will be proven again.
+    pub fn load_proofs<C: ZKMProverComponents>(
+        &self,
+        prover: &ZKMProver<C>,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<BTreeMap<u32, ShardProof<CoreSC>>, ZKMCoreProverError> {
+        let machine = prover.core_prover.machine();
+        let mut challenger = machine.config().challenger();
+        vk.vk.observe_into(&mut challenger);
+
+        let mut proofs = BTreeMap::new();
+        for entry in fs::read_dir(&self.path).map_err(ZKMCoreProverError::IoError)? {
+            let path = entry.map_err(ZKMCoreProverError::IoError)?.path();
+            let Some(shard) =
+                path.file_name().and_then(|name| parse_shard_file_name(name.to_str()?))
+            else {
+                continue;
+            };
+
+            let proof =
+                match fs::read(&path).map_err(ZKMCoreProverError::IoError).and_then(|bytes| {
+                    bincode::deserialize::<ShardProof<CoreSC>>(&bytes)
+                        .map_err(ZKMCoreProverError::SerializationError)
+                }) {
+                    Ok(proof) => proof,
+                    Err(e) => {
+                        tracing::warn!("skipping unreadable shard proof {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+
+            let public_values: &PublicValues<Word<KoalaBear>, KoalaBear> =
+                proof.public_values.as_slice().borrow();
+            if public_values.shard.as_canonical_u32() != shard {
+                tracing::warn!("skipping misplaced shard proof {}", path.display());
+                continue;
+            }
+            if let Err(e) = machine.verify_shard(&vk.vk, &proof, &challenger) {
+                tracing::warn!("skipping invalid shard proof {}: {}", path.display(), e);
+                continue;
+            }
+            proofs.insert(shard, proof);
+        }
+        Ok(proofs)
+    }
+
+    /// Persists the proof of the shard with index `shard`.
+    pub fn save_proof(
+        &self,
+        shard: u32,
+        proof: &ShardProof<CoreSC>,
+    ) -> Result<(), ZKMCoreProverError> {
+        let bytes = bincode::serialize(proof).map_err(ZKMCoreProverError::SerializationError)?;
+        write_atomic(&self.path.join(shard_file_name(shard)), &bytes)
+            .map_err(ZKMCoreProverError::IoError)
+    }
+}
+
+/// Identifies the program and options whose shard proofs a shard cache directory holds.
+///
+/// Unlike a [`CheckpointManifest`], the input is not part of it: the proofs are reused for any
+/// input, as long as the records of their shards are the same.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ShardCacheManifest {
+    version: String,
+    vk_hash: [u32; 8],
+}
+
+/// A directory holding shard proofs keyed by the digest of the execution record of their shard,
+/// shared by the proofs of a program on different inputs.
+#[derive(Debug)]
+pub struct ShardCacheDir {
+    path: PathBuf,
+}
+
+impl ShardCacheDir {
+    /// Opens the shard cache directory at `path` for proving the program of `vk`, creating it if
+    /// it does not exist.
+    ///
+    /// Fails if the directory holds the shard proofs of a different program.
+    pub fn open(path: &Path, vk: &ZKMVerifyingKey) -> Result<Self, ZKMCoreProverError> {
+        let manifest =
+            ShardCacheManifest { version: ZKM_CIRCUIT_VERSION.to_string(), vk_hash: vk.hash_u32() };
+
+        fs::create_dir_all(path).map_err(ZKMCoreProverError::IoError)?;
+        let manifest_path = path.join(MANIFEST_FILE);
+        match fs::read(&manifest_path) {
+            Ok(bytes) => {
+                let existing: ShardCacheManifest =
+                    bincode::deserialize(&bytes).map_err(ZKMCoreProverError::SerializationError)?;
+                if existing != manifest {
+                    return Err(ZKMCoreProverError::IoError(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "shard cache directory {} belongs to a different program",
+                            path.display()
+                        ),
+                    )));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let bytes = bincode::serialize(&manifest)
+                    .map_err(ZKMCoreProverError::SerializationError)?;
+                write_atomic(&manifest_path, &bytes).map_err(ZKMCoreProverError::IoError)?;
+            }
+            Err(e) => return Err(ZKMCoreProverError::IoError(e)),
+        }
+
+        Ok(Self { path: path.to_path_buf() })
+    }
+
+    /// Returns the path of the shard cache directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Loads the proof of the shard whose record has the digest `digest`, if it is cached.
+    pub fn load_proof(&self, digest: &[u8; 32]) -> Option<ShardProof<CoreSC>> {
+        let bytes = fs::read(self.path.join(record_file_name(digest))).ok()?;
+        bincode::deserialize(&bytes).ok()
+    }
+
+    /// Persists the proof of the shard whose record has the digest `digest`.
+    pub fn save_proof(
+        &self,
+        digest: &[u8; 32],
+        proof: &ShardProof<CoreSC>,
+    ) -> Result<(), ZKMCoreProverError> {
+        let bytes = bincode::serialize(proof).map_err(ZKMCoreProverError::SerializationError)?;
+        write_atomic(&self.path.join(record_file_name(digest)), &bytes)
+            .map_err(ZKMCoreProverError::IoError)
+    }
+}
+
+/// A [`ShardCacheDir`] whose proofs are verified before being reused.
+pub(crate) struct VerifiedShardCache<'a, C: ZKMProverComponents> {
+    pub(crate) dir: ShardCacheDir,
+    pub(crate) prover: &'a ZKMProver<C>,
+    pub(crate) vk: &'a ZKMVerifyingKey,
+}
+
+impl<C: ZKMProverComponents> ShardProofCache<CoreSC> for VerifiedShardCache<'_, C> {
+    fn get(&self, digest: &[u8; 32]) -> Option<ShardProof<CoreSC>> {
+        let proof = self.dir.load_proof(digest)?;
+        let machine = self.prover.core_prover.machine();
+        let mut challenger = machine.config().challenger();
+        self.vk.vk.observe_into(&mut challenger);
+        match machine.verify_shard(&self.vk.vk, &proof, &challenger) {
+            Ok(()) => Some(proof),
+            Err(e) => {
+                tracing::warn!("skipping invalid cached shard proof: {}", e);
+                None
+            }
+        }
+    }
+
+    fn insert(&self, digest: [u8; 32], proof: &ShardProof<CoreSC>) {
+        // A failure to cache a shard only loses the ability to reuse it.
+        if let Err(e) = self.dir.save_proof(&digest, proof) {
+            tracing::warn!("failed to cache a shard proof: {}", e);
+        }
+    }
+}
+
+fn record_file_name(digest: &[u8; 32]) -> String {
+    let digest = digest.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    format!("record-{digest}.bin")
+}
+
+fn shard_file_name(shard: u32) -> String {
+    format!("shard-{shard:06}.bin")
+}
+
+fn parse_shard_file_name(name: &str) -> Option<u32> {
+    name.strip_prefix("shard-")?.strip_suffix(".bin")?.parse().ok()
+}
+
+/// Writes `bytes` to `path` through a temporary file, so that an interrupted write never leaves a
+/// truncated file at `path`.
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_file_name() {
+        assert_eq!(shard_file_name(7), "shard-000007.bin");
+        assert_eq!(parse_shard_file_name(&shard_file_name(7)), Some(7));
+        assert_eq!(parse_shard_file_name(&shard_file_name(1 << 20)), Some(1 << 20));
+        assert_eq!(parse_shard_file_name("shard-000007.tmp"), None);
+        assert_eq!(parse_shard_file_name(MANIFEST_FILE), None);
+    }
+
+    #[test]
+    fn test_record_file_name() {
+        let mut digest = [0; 32];
+        digest[0] = 0xab;
+        digest[31] = 0x01;
+        let name = record_file_name(&digest);
+        assert_eq!(name.len(), "record-.bin".len() + 64);
+        assert!(name.starts_with("record-ab00"));
+        assert!(name.ends_with("01.bin"));
+        assert_eq!(parse_shard_file_name(&name), None);
+    }
+}
@@ -3,6 +3,11 @@ use zkm_stark::{CpuProver, MachineProver, StarkGenericConfig};
 
 use crate::{CompressAir, CoreSC, InnerSC, OuterSC, ShrinkAir, WrapAir};
 
+/// The machine provers used by [crate::ZKMProver] for each proving stage.
+///
+/// Every stage of [crate::ZKMProver], including the compress tree, proves through the
+/// [MachineProver] of its component, so a hardware accelerated backend is plugged in by
+/// implementing [MachineProver] for the stage's air and selecting it here.
 pub trait ZKMProverComponents: Send + Sync {
     /// The prover for making Ziren core proofs.
     type CoreProver: MachineProver<CoreSC, MipsAir<<CoreSC as StarkGenericConfig>::Val>>
@@ -0,0 +1,195 @@
+//! Continuations, proving one long execution of a program as a chain of core proofs of segments.
+//!
+//! Every segment but the last pauses after a number of cycles in a [`ContinuationState`], and
+//! the next segment resumes from it as a program whose verifying key commits to that state. The
+//! proofs are linked by [`ZKMProver::verify_continuation`], which derives the verifying key of
+//! every segment from the end state of the previous one.
+
+use std::{borrow::Borrow, collections::BTreeMap, sync::mpsc::channel};
+
+use p3_field::{FieldAlgebra, PrimeField32};
+use p3_koala_bear::KoalaBear;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zkm_core_executor::{ContinuationState, Program, ZKMContext};
+use zkm_core_machine::{
+    io::ZKMStdin,
+    utils::ZKMCoreProverError,
+    verify::{verify_core_proof, verify_segment_proof},
+};
+use zkm_primitives::io::{PublicValuesHash, ZKMPublicValues};
+use zkm_stark::{
+    air::{MachineProgram, PublicValues},
+    MachineProver, MachineVerificationError, StarkVerifyingKey, Word, ZKMProverOpts,
+};
+
+use crate::{
+    components::ZKMProverComponents, utils::words_to_bytes, CoreSC, ZKMCoreProofData, ZKMProver,
+    ZKMProvingKey, ZKMVerifyingKey,
+};
+
+/// The proof of one segment of a [`ZKMContinuationProof`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ZKMSegmentProof {
+    /// The core proof of the segment.
+    pub proof: ZKMCoreProofData,
+    /// The state the segment pauses in, from which the next segment resumes, or `None` for the
+    /// last segment, which halts.
+    pub end_state: Option<ContinuationState>,
+}
+
+/// A proof of one execution of a program as a chain of segments, see
+/// [`ZKMProver::prove_continuation`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ZKMContinuationProof {
+    /// The proofs of the segments, in order.
+    pub segments: Vec<ZKMSegmentProof>,
+    /// The public values committed by the program over all the segments.
+    pub public_values: ZKMPublicValues,
+    /// The number of cycles of the execution.
+    pub cycles: u64,
+}
+
+/// An error returned when proving a continuation.
+#[derive(Error, Debug)]
+pub enum ContinuationError {
+    #[error("continuations do not support deferred proofs")]
+    DeferredProofs,
+    #[error("invalid program: {0}")]
+    Program(eyre::Report),
+    #[error(transparent)]
+    Core(#[from] ZKMCoreProverError),
+}
+
+impl<C: ZKMProverComponents> ZKMProver<C> {
+    /// Proves the execution of the program of `pk` as a chain of core proofs of segments of at
+    /// least `segment_cycles` cycles, so that no single proof covers the whole execution.
+    ///
+    /// Every segment is executed once while it is proven, exporting the state it pauses in, and
+    /// the next segment is set up from that state. The end states are part of the proof, so its size
+    /// grows with the memory used by the program. The segments are core proofs and are verified
+    /// with [`Self::verify_continuation`]: they cannot be compressed, since the recursion program
+    /// expects a complete execution in every proof.
+    pub fn prove_continuation(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+        opts: ZKMProverOpts,
+        segment_cycles: u64,
+    ) -> Result<ZKMContinuationProof, ContinuationError> {
+        // The digest of the verified proofs is kept by the executor, not in the memory of the
+        // program, so it would not carry over to the next segment.
+        if !stdin.proofs.is_empty() {
+            return Err(ContinuationError::DeferredProofs);
+        }
+
+        let program = self.get_program(&pk.elf).map_err(ContinuationError::Program)?;
+        let mut segment_program = program.clone();
+        let mut segment_stdin = stdin.clone();
+        let mut pk_d = self.core_prover.pk_to_device(&pk.pk);
+        let mut segments = Vec::new();
+        let mut public_values = Vec::new();
+        let mut cycles = 0;
+        loop {
+            let (state_tx, state_rx) = channel();
+            let mut context = ZKMContext::builder().segment_cycles(segment_cycles).build();
+            context.continuation_tx = Some(state_tx);
+            let proof = self.prove_core_impl(
+                &pk_d,
+                segment_program,
+                &segment_stdin,
+                opts,
+                context,
+                None,
+                BTreeMap::new(),
+                None,
+            )?;
+            // The state is only sent if the segment paused instead of halting.
+            let end_state = state_rx.try_recv().ok();
+            tracing::info!("proved segment {} of {} cycles", segments.len(), proof.cycles);
+            public_values.extend_from_slice(proof.public_values.as_slice());
+            cycles += proof.cycles;
+            segments.push(ZKMSegmentProof { proof: proof.proof, end_state: end_state.clone() });
+
+            let Some(end_state) = end_state else {
+                break;
+            };
+            segment_program = end_state.resume(&program);
+            segment_stdin = ZKMStdin { buffer: end_state.input_stream, ptr: 0, ..stdin.clone() };
+            pk_d = self.core_prover.setup(&segment_program).0;
+        }
+
+        Ok(ZKMContinuationProof {
+            segments,
+            public_values: ZKMPublicValues::from(&public_values),
+            cycles,
+        })
+    }
+
+    /// Verifies a proof produced by [`Self::prove_continuation`] of the program with the
+    /// verifying key `vk`.
+    ///
+    /// Every segment is verified against the verifying key resuming from the end state of the
+    /// previous segment, and every end state against the memory the segment leaves unfinalized,
+    /// so the segments are linked into one execution. The public values are checked against the
+    /// digest committed by the last segment, computed with `hash`.
+    pub fn verify_continuation(
+        &self,
+        proof: &ZKMContinuationProof,
+        vk: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
+    ) -> Result<(), MachineVerificationError<CoreSC>> {
+        let Some((last, segments)) = proof.segments.split_last() else {
+            return Err(MachineVerificationError::EmptyProof);
+        };
+        if last.end_state.is_some() {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "the last segment should halt",
+            ));
+        }
+
+        let mut segment_vk = vk.vk.clone();
+        for segment in segments {
+            let Some(end_state) = &segment.end_state else {
+                return Err(MachineVerificationError::InvalidPublicValues(
+                    "only the last segment should halt",
+                ));
+            };
+            verify_segment_proof(&segment_vk, &segment.proof.0, end_state)?;
+            segment_vk = resumed_vk(&vk.vk, end_state);
+        }
+        verify_core_proof(&segment_vk, &last.proof.0)?;
+
+        // The hash of the public values is kept in the memory of the program across segments, so
+        // the last segment commits to the public values of all of them.
+        let shard_proof = last.proof.0.last().unwrap();
+        let public_values: &PublicValues<Word<KoalaBear>, KoalaBear> =
+            shard_proof.public_values.as_slice().borrow();
+        let digest = words_to_bytes(&public_values.committed_value_digest)
+            .into_iter()
+            .map(|byte| byte.as_canonical_u32() as u8)
+            .collect::<Vec<_>>();
+        if !proof.public_values.is_digest(&digest, hash) {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "committed_value_digest != hash of the public values",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The verifying key of the program of `vk` resuming from `state`, see
+/// [`ContinuationState::resume`].
+///
+/// The preprocessed traces only depend on the instructions of the program, so only the start pc
+/// and the commitment to the initial memory differ from `vk`.
+fn resumed_vk(
+    vk: &StarkVerifyingKey<CoreSC>,
+    state: &ContinuationState,
+) -> StarkVerifyingKey<CoreSC> {
+    let program = state.resume(&Program::default());
+    let mut vk = vk.clone();
+    vk.pc_start = KoalaBear::from_canonical_u32(program.pc_start);
+    vk.initial_global_cumulative_sum = program.initial_global_cumulative_sum();
+    vk
+}
@@ -0,0 +1,154 @@
+//! Estimation of the cost of proving a program without proving it.
+//!
+//! [crate::ZKMProver::estimate] executes the program with cost estimation enabled, which records
+//! the event counts of every shard as estimated by the executor. The trace area of every chip is
+//! then derived from the row costs in `mips_costs.json`, and the proving time and memory from
+//! rough throughputs and footprints of each backend.
+
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+use zkm_core_executor::{mips_costs, ExecutionReport, MipsAirId};
+
+/// The number of rows of the byte chip, which is included in every core shard.
+const BYTE_NUM_ROWS: u64 = 1 << 16;
+
+/// The memory taken by the recursion programs and their proving keys, on top of the core shards.
+const RECURSION_MEMORY: u64 = 4 << 30;
+
+/// A backend proving the core shards and compressing their proofs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProverBackend {
+    Cpu,
+    Cuda,
+}
+
+impl ProverBackend {
+    /// All the backends.
+    pub const ALL: [ProverBackend; 2] = [ProverBackend::Cpu, ProverBackend::Cuda];
+
+    /// The trace area the backend proves per second, in cells. Roughly measured on a 64-core
+    /// machine and on a single 24 GiB GPU respectively.
+    const fn cells_per_second(self) -> u64 {
+        match self {
+            ProverBackend::Cpu => 1 << 25,
+            ProverBackend::Cuda => 1 << 29,
+        }
+    }
+
+    /// The host memory the backend takes per cell of the shard being proven. The CPU prover keeps
+    /// the low-degree extensions, the permutation traces and their Merkle trees in memory, while
+    /// the GPU prover only needs the host to hold the traces it sends to the GPU.
+    const fn bytes_per_cell(self) -> u64 {
+        match self {
+            ProverBackend::Cpu => 64,
+            ProverBackend::Cuda => 8,
+        }
+    }
+
+    /// The time the backend spends on recursion per core shard.
+    const fn recursion_per_shard(self) -> Duration {
+        match self {
+            ProverBackend::Cpu => Duration::from_secs(20),
+            ProverBackend::Cuda => Duration::from_secs(1),
+        }
+    }
+}
+
+impl fmt::Display for ProverBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProverBackend::Cpu => f.write_str("cpu"),
+            ProverBackend::Cuda => f.write_str("cuda"),
+        }
+    }
+}
+
+/// The estimated cost of proving the execution of a program.
+#[derive(Debug, Clone)]
+pub struct CostEstimate {
+    /// The number of core shards.
+    pub num_shards: usize,
+    /// The trace area of each chip summed over all core shards, in cells.
+    pub chip_areas: BTreeMap<String, u64>,
+    /// The trace area of each core shard, in cells.
+    pub shard_areas: Vec<u64>,
+    /// The report of the execution the estimate is derived from.
+    pub report: ExecutionReport,
+}
+
+impl CostEstimate {
+    /// Derives the estimate from the report of an execution with cost estimation enabled, of a
+    /// program with `program_size` instructions.
+    pub fn new(program_size: usize, report: ExecutionReport) -> Self {
+        let costs = mips_costs();
+        let mut chip_areas = BTreeMap::<String, u64>::new();
+        let mut shard_areas = Vec::with_capacity(report.shard_event_counts.len());
+
+        for event_counts in &report.shard_event_counts {
+            let mut shard_area = 0;
+            let mut add = |air: MipsAirId, rows: u64| {
+                let area = rows * costs[&air] as u64;
+                *chip_areas.entry(air.as_str().to_string()).or_default() += area;
+                shard_area += area;
+            };
+            add(MipsAirId::Program, (program_size as u64).next_power_of_two());
+            add(MipsAirId::Byte, BYTE_NUM_ROWS);
+            for (air, &count) in event_counts.iter().filter(|(_, count)| **count > 0) {
+                add(air, count.next_power_of_two());
+            }
+            shard_areas.push(shard_area);
+        }
+
+        Self { num_shards: report.shard_event_counts.len(), chip_areas, shard_areas, report }
+    }
+
+    /// The number of cycles executed.
+    pub fn cycles(&self) -> u64 {
+        self.report.total_instruction_count()
+    }
+
+    /// The trace area of all chips and core shards, in cells.
+    pub fn trace_area(&self) -> u64 {
+        self.chip_areas.values().sum()
+    }
+
+    /// The trace area of the largest core shard, in cells.
+    pub fn max_shard_area(&self) -> u64 {
+        self.shard_areas.iter().copied().max().unwrap_or_default()
+    }
+
+    /// A rough estimate of the time `backend` takes to prove the core shards.
+    pub fn core_proving_time(&self, backend: ProverBackend) -> Duration {
+        Duration::from_secs_f64(self.trace_area() as f64 / backend.cells_per_second() as f64)
+    }
+
+    /// A rough estimate of the time `backend` takes to prove the core shards and compress their
+    /// proofs. Precompile chips are not accounted for.
+    pub fn proving_time(&self, backend: ProverBackend) -> Duration {
+        self.core_proving_time(backend) + backend.recursion_per_shard() * self.num_shards as u32
+    }
+
+    /// A rough estimate of the peak host memory `backend` takes to prove the core shards one at
+    /// a time and compress their proofs, in bytes.
+    pub fn peak_memory(&self, backend: ProverBackend) -> u64 {
+        self.max_shard_area() * backend.bytes_per_cell() + RECURSION_MEMORY
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "cycles: {}", self.cycles())?;
+        writeln!(f, "shards: {}", self.num_shards)?;
+        writeln!(f, "trace area: {} cells", self.trace_area())?;
+        let mut chips = self.chip_areas.iter().collect::<Vec<_>>();
+        chips.sort_by_key(|&(_, &area)| std::cmp::Reverse(area));
+        for (chip, area) in chips {
+            writeln!(f, "  {chip}: {area}")?;
+        }
+        for backend in ProverBackend::ALL {
+            writeln!(f, "{backend} proving time: ~{}s", self.proving_time(backend).as_secs())?;
+            writeln!(f, "{backend} peak memory: ~{} MiB", self.peak_memory(backend) >> 20)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+//! The proving keys of the compress programs, set up once instead of at every node of the reduce
+//! tree.
+//!
+//! The compress programs of the fixed recursion shapes are compiled when the [`ZKMProver`] is
+//! created, but their keys are set up again for every node they prove. A [`CompressKeystore`]
+//! sets them up once, so that proving a node only commits to its traces and opens them. The keys
+//! may also be stored in a directory, from which the next processes load them instead of setting
+//! them up, see [`CompressKeystore::load_or_setup`].
+//!
+//! The keys of the shrink and wrap programs are set up on their first use and kept by the
+//! [`ZKMProver`]. They can be exported to a directory with [`ZKMProver::export_recursion_keys`]
+//! and loaded back with [`ZKMProver::load_recursion_keys`], for example by a proving service
+//! shipping them with its container image.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use p3_koala_bear::KoalaBear;
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use zkm_recursion_circuit::machine::ZKMCompressWithVkeyShape;
+use zkm_recursion_core::RecursionProgram;
+use zkm_stark::{MachineProver, StarkProvingKey, StarkVerifyingKey, DIGEST_SIZE};
+
+use crate::{
+    components::ZKMProverComponents,
+    program_cache::{load_entry, store_entry},
+    shapes::{ZKMCompressProgramShape, ZKMProofShape},
+    CompressAir, InnerSC, OuterSC, ShrinkAir, WrapAir, ZKMProver, ZKM_CIRCUIT_VERSION,
+};
+
+/// The file of the shrink keys in a directory of recursion keys.
+const SHRINK_KEYS_FILE: &str = "shrink_keys.bin";
+
+/// The file of the wrap keys in a directory of recursion keys.
+const WRAP_KEYS_FILE: &str = "wrap_keys.bin";
+
+/// The proving key of a compress program on the device of the compress prover.
+pub type CompressProvingKey<C> = <<C as ZKMProverComponents>::CompressProver as MachineProver<
+    InnerSC,
+    CompressAir<KoalaBear>,
+>>::DeviceProvingKey;
+
+/// The proving and verifying keys of a compress program.
+pub type CompressKeys<C> = Arc<(CompressProvingKey<C>, StarkVerifyingKey<InnerSC>)>;
+
+/// The proving key of a shrink program on the device of the shrink prover.
+pub type ShrinkProvingKey<C> = <<C as ZKMProverComponents>::ShrinkProver as MachineProver<
+    InnerSC,
+    ShrinkAir<KoalaBear>,
+>>::DeviceProvingKey;
+
+/// The proving and verifying keys of a shrink program.
+pub type ShrinkKeys<C> = Arc<(ShrinkProvingKey<C>, StarkVerifyingKey<InnerSC>)>;
+
+/// The proving key of the wrap program on the device of the wrap prover.
+pub type WrapProvingKey<C> = <<C as ZKMProverComponents>::WrapProver as MachineProver<
+    OuterSC,
+    WrapAir<KoalaBear>,
+>>::DeviceProvingKey;
+
+/// The proving and verifying keys of the wrap program.
+pub type WrapKeys<C> = Arc<(WrapProvingKey<C>, StarkVerifyingKey<OuterSC>)>;
+
+/// The shrink keys in a directory of recursion keys, by the shape of the compressed proofs.
+type ShrinkKeysFile =
+    Vec<(ZKMCompressWithVkeyShape, StarkProvingKey<InnerSC>, StarkVerifyingKey<InnerSC>)>;
+
+/// The wrap keys in a directory of recursion keys.
+type WrapKeysFile = (StarkProvingKey<OuterSC>, StarkVerifyingKey<OuterSC>);
+
+/// An error exporting or loading recursion keys.
+#[derive(Debug, Error)]
+pub enum RecursionKeysError {
+    #[error("IO error: {0}")]
+    IO(#[from] io::Error),
+    #[error("Serialization error: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("the keys were exported by a prover of another circuit version or configuration")]
+    Mismatch,
+}
+
+/// Identifies the prover a directory of recursion keys was exported by: the shrink and wrap
+/// programs only depend on these.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct RecursionKeysHeader {
+    version: String,
+    vk_verification: bool,
+    vk_root: [KoalaBear; DIGEST_SIZE],
+}
+
+/// Identifies the keys of a compress program.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KeystoreKey {
+    version: String,
+    shape: ZKMCompressWithVkeyShape,
+    vk_verification: bool,
+}
+
+/// The keys of the compress programs of the fixed recursion shapes.
+pub struct CompressKeystore<C: ZKMProverComponents> {
+    keys: BTreeMap<ZKMCompressWithVkeyShape, CompressKeys<C>>,
+}
+
+impl<C: ZKMProverComponents> CompressKeystore<C> {
+    /// Sets up the keys of the compress programs of `prover`.
+    pub fn setup(prover: &ZKMProver<C>) -> Self {
+        Self::load_or_setup(prover, None)
+    }
+
+    /// Loads the keys of the compress programs of `prover` from `dir`, and sets up the ones which
+    /// are missing, storing them there for the next processes.
+    ///
+    /// The directory may be shared by several processes, see [`crate::program_cache`].
+    pub fn load_or_setup(prover: &ZKMProver<C>, dir: Option<&Path>) -> Self {
+        let dir = dir.filter(|dir| {
+            fs::create_dir_all(dir)
+                .inspect_err(|e| tracing::warn!("failed to open the compress keystore: {}", e))
+                .is_ok()
+        });
+        let keys = prover
+            .join_programs_map
+            .par_iter()
+            .map(|(shape, program)| {
+                let key = KeystoreKey {
+                    version: ZKM_CIRCUIT_VERSION.to_string(),
+                    shape: shape.clone(),
+                    vk_verification: prover.vk_verification,
+                };
+                let loaded = dir.and_then(|dir| {
+                    load_entry::<_, (StarkProvingKey<InnerSC>, StarkVerifyingKey<InnerSC>)>(
+                        dir, &key,
+                    )
+                });
+                let keys = match loaded {
+                    Some((pk, vk)) => (prover.compress_prover.pk_to_device(&pk), vk),
+                    None => {
+                        let (pk, vk) = prover.compress_prover.setup(program);
+                        if let Some(dir) = dir {
+                            let host = (prover.compress_prover.pk_to_host(&pk), vk.clone());
+                            if let Err(e) = store_entry(dir, &key, &host) {
+                                tracing::warn!("failed to store compress keys: {}", e);
+                            }
+                        }
+                        (pk, vk)
+                    }
+                };
+                (shape.clone(), Arc::new(keys))
+            })
+            .collect();
+        Self { keys }
+    }
+
+    /// The keys of the compress program of `shape`, if it is one of the fixed shapes.
+    pub fn get(&self, shape: &ZKMCompressWithVkeyShape) -> Option<CompressKeys<C>> {
+        self.keys.get(shape).cloned()
+    }
+
+    /// The number of compress programs with keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no compress program has keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<C: ZKMProverComponents> ZKMProver<C> {
+    /// The keys of `program`, the shrink program of compressed proofs of `shape`, set up on first
+    /// use.
+    pub fn shrink_keys(
+        &self,
+        shape: &ZKMCompressWithVkeyShape,
+        program: &RecursionProgram<KoalaBear>,
+    ) -> ShrinkKeys<C> {
+        if let Some(keys) = self.shrink_keys.lock().unwrap().get(shape) {
+            return keys.clone();
+        }
+        // Set up the keys without holding the lock, so different shapes are set up concurrently.
+        let keys = Arc::new(
+            tracing::debug_span!("setup shrink").in_scope(|| self.shrink_prover.setup(program)),
+        );
+        self.shrink_keys.lock().unwrap().entry(shape.clone()).or_insert(keys).clone()
+    }
+
+    /// The keys of the wrap program, set up on first use.
+    pub fn wrap_keys(&self) -> WrapKeys<C> {
+        self.wrap_keys
+            .get_or_init(|| {
+                let program = self.wrap_program();
+                Arc::new(
+                    tracing::debug_span!("setup wrap")
+                        .in_scope(|| self.wrap_prover.setup(&program)),
+                )
+            })
+            .clone()
+    }
+
+    /// Writes the keys of the shrink programs of all the fixed compress shapes and of the wrap
+    /// program to `dir`, setting up the ones which are not yet.
+    ///
+    /// Without fixed recursion shapes, only the shrink keys set up so far are written.
+    pub fn export_recursion_keys(&self, dir: impl AsRef<Path>) -> Result<(), RecursionKeysError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        if let Some(config) = &self.compress_shape_config {
+            let shapes = config.get_all_shape_combinations(1).collect::<Vec<_>>();
+            shapes.into_par_iter().for_each(|mut proof_shapes| {
+                let shape = ZKMCompressProgramShape::from_proof_shape(
+                    ZKMProofShape::Shrink(proof_shapes.pop().unwrap()),
+                    self.recursion_vk_tree.height,
+                );
+                let ZKMCompressProgramShape::Shrink(input_shape) = &shape else { unreachable!() };
+                let program = self.program_from_shape(shape.clone(), None);
+                self.shrink_keys(input_shape, &program);
+            });
+        }
+        let shrink_keys: ShrinkKeysFile = self
+            .shrink_keys
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(shape, keys)| {
+                (shape.clone(), self.shrink_prover.pk_to_host(&keys.0), keys.1.clone())
+            })
+            .collect();
+        write_keys(&dir.join(SHRINK_KEYS_FILE), &self.recursion_keys_header(), &shrink_keys)?;
+
+        let wrap_keys = self.wrap_keys();
+        let wrap_keys: WrapKeysFile =
+            (self.wrap_prover.pk_to_host(&wrap_keys.0), wrap_keys.1.clone());
+        write_keys(&dir.join(WRAP_KEYS_FILE), &self.recursion_keys_header(), &wrap_keys)?;
+        tracing::info!("exported the recursion keys to {}", dir.display());
+        Ok(())
+    }
+
+    /// Loads the keys written by [`Self::export_recursion_keys`] from `dir`, so that `shrink` and
+    /// `wrap_bn254` do not set them up.
+    ///
+    /// Fails if the keys were exported by a prover of another circuit version or configuration.
+    pub fn load_recursion_keys(&self, dir: impl AsRef<Path>) -> Result<(), RecursionKeysError> {
+        let dir = dir.as_ref();
+        let header = self.recursion_keys_header();
+        let shrink_keys: ShrinkKeysFile = read_keys(&dir.join(SHRINK_KEYS_FILE), &header)?;
+        let (wrap_pk, wrap_vk): WrapKeysFile = read_keys(&dir.join(WRAP_KEYS_FILE), &header)?;
+
+        let mut keys = self.shrink_keys.lock().unwrap();
+        for (shape, pk, vk) in shrink_keys {
+            keys.insert(shape, Arc::new((self.shrink_prover.pk_to_device(&pk), vk)));
+        }
+        let _ = self.wrap_vk.set(wrap_vk.clone());
+        let _ = self.wrap_keys.set(Arc::new((self.wrap_prover.pk_to_device(&wrap_pk), wrap_vk)));
+        tracing::info!("loaded the recursion keys from {}", dir.display());
+        Ok(())
+    }
+
+    fn recursion_keys_header(&self) -> RecursionKeysHeader {
+        RecursionKeysHeader {
+            version: ZKM_CIRCUIT_VERSION.to_string(),
+            vk_verification: self.vk_verification,
+            vk_root: self.recursion_vk_root,
+        }
+    }
+}
+
+fn write_keys<T: Serialize>(
+    path: &Path,
+    header: &RecursionKeysHeader,
+    keys: &T,
+) -> Result<(), RecursionKeysError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    bincode::serialize_into(&mut writer, &(header, keys))?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_keys<T: DeserializeOwned>(
+    path: &Path,
+    header: &RecursionKeysHeader,
+) -> Result<T, RecursionKeysError> {
+    let (stored, keys): (RecursionKeysHeader, T) =
+        bincode::deserialize_from(BufReader::new(File::open(path)?))?;
+    if stored != *header {
+        return Err(RecursionKeysError::Mismatch);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::components::DefaultProverComponents;
+
+    #[test]
+    #[ignore]
+    fn test_compress_keystore() {
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        let dir = env::temp_dir().join("zkm-test-compress-keystore");
+        let _ = fs::remove_dir_all(&dir);
+
+        let stored = CompressKeystore::load_or_setup(&prover, Some(&dir));
+        assert_eq!(stored.len(), prover.join_programs_map.len());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), stored.len());
+
+        // The next keystore loads the keys stored by the first one.
+        let loaded = CompressKeystore::load_or_setup(&prover, Some(&dir));
+        assert_eq!(loaded.len(), stored.len());
+        for shape in prover.join_programs_map.keys() {
+            let stored_vk = bincode::serialize(&stored.get(shape).unwrap().1).unwrap();
+            let loaded_vk = bincode::serialize(&loaded.get(shape).unwrap().1).unwrap();
+            assert_eq!(loaded_vk, stored_vk);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recursion_keys_header() {
+        let dir = env::temp_dir().join("zkm-test-recursion-keys-header");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(WRAP_KEYS_FILE);
+        let header = RecursionKeysHeader {
+            version: ZKM_CIRCUIT_VERSION.to_string(),
+            vk_verification: true,
+            vk_root: [KoalaBear::default(); DIGEST_SIZE],
+        };
+        write_keys(&path, &header, &vec![1u32, 2, 3]).unwrap();
+        assert_eq!(read_keys::<Vec<u32>>(&path, &header).unwrap(), vec![1, 2, 3]);
+
+        // The keys of a prover of another configuration are refused.
+        let other = RecursionKeysHeader { vk_verification: false, ..header };
+        assert!(matches!(read_keys::<Vec<u32>>(&path, &other), Err(RecursionKeysError::Mismatch)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_export_and_load_recursion_keys() {
+        let dir = env::temp_dir().join("zkm-test-recursion-keys");
+        let _ = fs::remove_dir_all(&dir);
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        prover.export_recursion_keys(&dir).unwrap();
+
+        let loaded = ZKMProver::<DefaultProverComponents>::new();
+        loaded.load_recursion_keys(&dir).unwrap();
+        assert_eq!(
+            loaded.shrink_keys.lock().unwrap().len(),
+            prover.shrink_keys.lock().unwrap().len()
+        );
+        assert_eq!(
+            bincode::serialize(&loaded.wrap_keys().1).unwrap(),
+            bincode::serialize(&prover.wrap_keys().1).unwrap()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,39 +12,56 @@
 #![allow(clippy::collapsible_else_if)]
 
 pub mod build;
+pub mod checkpoint;
 pub mod components;
+pub mod continuation;
+pub mod estimate;
+pub mod keystore;
+pub mod program_cache;
+pub mod proof_format;
 pub mod shapes;
 pub mod types;
 pub mod utils;
 pub mod verify;
+mod watchdog;
 
 use std::{
     borrow::Borrow,
     collections::BTreeMap,
     env,
     num::NonZeroUsize,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicUsize, Ordering},
-        mpsc::sync_channel,
+        mpsc::{channel, sync_channel, Sender},
         Arc, Mutex, OnceLock,
     },
     thread,
+    time::Duration,
 };
 
+use checkpoint::{CheckpointDir, ShardCacheDir, VerifiedShardCache};
+use estimate::CostEstimate;
+use keystore::{CompressKeys, CompressKeystore, ShrinkKeys, WrapKeys};
 use lru::LruCache;
 use p3_field::{FieldAlgebra, PrimeField, PrimeField32};
 use p3_koala_bear::KoalaBear;
 use p3_matrix::dense::RowMajorMatrix;
+use program_cache::ProgramCache;
+use rayon::prelude::*;
 use shapes::ZKMProofShape;
 use tracing::instrument;
+use watchdog::Watchdog;
 use zkm_core_executor::{ExecutionError, ExecutionReport, Executor, Program, ZKMContext};
 use zkm_core_machine::{
     io::ZKMStdin,
     mips::MipsAir,
     reduce::ZKMReduceProof,
     shape::CoreShapeConfig,
-    utils::{concurrency::TurnBasedSync, ZKMCoreProverError},
+    utils::{
+        concurrency::TurnBasedSync, tune::tune_shards, ShardProgress, ShardProofCache,
+        ZKMCoreProverError,
+    },
 };
 use zkm_primitives::{hash_deferred_proof, io::ZKMPublicValues};
 use zkm_recursion_circuit::{
@@ -73,12 +90,14 @@ use zkm_recursion_core::{
     stark::KoalaBearPoseidon2Outer,
     RecursionProgram, Runtime as RecursionRuntime,
 };
-pub use zkm_recursion_gnark_ffi::proof::{Groth16Bn254Proof, PlonkBn254Proof};
+pub use zkm_recursion_gnark_ffi::proof::{
+    EvmCalldataError, EvmProof, EvmProofSystem, Groth16Bn254Proof, PlonkBn254Proof,
+};
 use zkm_recursion_gnark_ffi::{groth16_bn254::Groth16Bn254Prover, plonk_bn254::PlonkBn254Prover};
 use zkm_stark::{
     air::PublicValues, koala_bear_poseidon2::KoalaBearPoseidon2, Challenge, MachineProver,
-    ShardProof, StarkGenericConfig, StarkVerifyingKey, Val, Word, ZKMCoreOpts, ZKMProverOpts,
-    DIGEST_SIZE,
+    Progress, ProgressEvent, ProverThreadPool, ShardProof, StarkGenericConfig, StarkVerifyingKey,
+    TraceBacking, Val, Word, ZKMCoreOpts, ZKMProverOpts, DIGEST_SIZE,
 };
 use zkm_stark::{shape::OrderedShape, MachineProvingKey};
 
@@ -88,6 +107,7 @@ use utils::{words_to_bytes, zkm_committed_values_digest_bn254, zkm_vkey_digest_b
 use components::{DefaultProverComponents, ZKMProverComponents};
 
 pub use zkm_core_machine::ZKM_CIRCUIT_VERSION;
+pub use zkm_recursion_core::{RecursionNodeStats, RecursionStats};
 
 /// The configuration for the core prover.
 pub type CoreSC = KoalaBearPoseidon2;
@@ -139,15 +159,27 @@ pub struct ZKMProver<C: ZKMProverComponents = DefaultProverComponents> {
     /// The cache of compiled recursion programs.
     pub lift_programs_lru: Mutex<LruCache<ZKMRecursionShape, Arc<RecursionProgram<KoalaBear>>>>,
 
+    /// The number of lookups of recursion programs.
+    pub lift_cache_lookups: AtomicUsize,
+
     /// The number of cache misses for recursion programs.
     pub lift_cache_misses: AtomicUsize,
 
+    /// The on-disk cache of compiled recursion programs, shared with other processes.
+    pub program_cache: Option<ProgramCache>,
+
     /// The cache of compiled compression programs.
     pub join_programs_map: BTreeMap<ZKMCompressWithVkeyShape, Arc<RecursionProgram<KoalaBear>>>,
 
+    /// The number of lookups of compression programs.
+    pub join_cache_lookups: AtomicUsize,
+
     /// The number of cache misses for compression programs.
     pub join_cache_misses: AtomicUsize,
 
+    /// The keys of the compression programs, if they were set up ahead of the reduce tree.
+    pub compress_keystore: Option<CompressKeystore<C>>,
+
     /// The root of the allowed recursion verification keys.
     pub recursion_vk_root: <InnerSC as FieldHasher<KoalaBear>>::Digest,
 
@@ -169,8 +201,24 @@ pub struct ZKMProver<C: ZKMProverComponents = DefaultProverComponents> {
     /// The verifying key for wrapping.
     pub wrap_vk: OnceLock<StarkVerifyingKey<OuterSC>>,
 
+    /// The keys of the shrink programs set up so far, by the shape of the compressed proofs.
+    pub shrink_keys: Mutex<BTreeMap<ZKMCompressWithVkeyShape, ShrinkKeys<C>>>,
+
+    /// The keys of the wrap program, once set up.
+    pub wrap_keys: OnceLock<WrapKeys<C>>,
+
     /// Whether to verify verification keys.
     pub vk_verification: bool,
+
+    /// The number of cycles of the opcode sample the recursion programs are prewarmed from, if
+    /// enabled.
+    pub prewarm_sample_cycles: Option<u64>,
+
+    /// Where the core prover keeps the traces of the shards waiting to be proven.
+    pub trace_backing: TraceBacking,
+
+    /// The thread pool the provers run their parallel work on.
+    pub thread_pool: ProverThreadPool,
 }
 
 impl<C: ZKMProverComponents> ZKMProver<C> {
@@ -204,10 +252,20 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         )
         .expect("PROVER_CORE_CACHE_SIZE must be a non-zero usize");
 
+        let tiny_program_shapes = env::var("TINY_PROGRAM_SHAPES")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
         let core_shape_config = env::var("FIX_CORE_SHAPES")
             .map(|v| v.eq_ignore_ascii_case("true"))
             .unwrap_or(true)
-            .then_some(CoreShapeConfig::default());
+            .then(|| {
+                let config = CoreShapeConfig::default();
+                if tiny_program_shapes {
+                    config.with_tiny_program_profile()
+                } else {
+                    config
+                }
+            });
 
         let recursion_shape_config = env::var("FIX_RECURSION_SHAPES")
             .map(|v| v.eq_ignore_ascii_case("true"))
@@ -217,14 +275,24 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         let vk_verification =
             env::var("VERIFY_VK").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(true);
 
+        let prewarm_sample_cycles =
+            env::var("PREWARM_SAMPLE_CYCLES").ok().and_then(|v| v.parse().ok()).filter(|&c| c > 0);
+
+        let compress_keys_dir = env::var_os("COMPRESS_KEYS_DIR").map(PathBuf::from);
+        let precompute_compress_keys = compress_keys_dir.is_some()
+            || env::var("PRECOMPUTE_COMPRESS_KEYS")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false);
+
         tracing::debug!("vk verification: {}", vk_verification);
 
         // Read the shapes from the shapes directory and deserialize them into memory.
         let allowed_vk_map: BTreeMap<[KoalaBear; DIGEST_SIZE], usize> = if vk_verification {
-            // Regenerate the vk_map.bin when the Ziren circuit is updated.
+            // Regenerate the vk_map.bin and dummy_vk_map.bin when the Ziren circuit or the shapes
+            // are updated.
             // ```
             // cd Ziren
-            // cargo run -r --bin build_compress_vks -- --num-compiler-workers 32 --count-setup-workers 32 --build-dir crates/prover
+            // cargo run -r --bin build_vk_maps -- --build-dir crates/prover
             // ```
             // It takes several days.
             bincode::deserialize(include_bytes!("../vk_map.bin")).unwrap()
@@ -256,15 +324,19 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
             });
         }
 
-        Self {
+        let prover = Self {
             core_prover,
             compress_prover,
             shrink_prover,
             wrap_prover,
             lift_programs_lru: Mutex::new(LruCache::new(core_cache_size)),
+            lift_cache_lookups: AtomicUsize::new(0),
             lift_cache_misses: AtomicUsize::new(0),
+            program_cache: ProgramCache::from_env(),
             join_programs_map: compress_programs,
+            join_cache_lookups: AtomicUsize::new(0),
             join_cache_misses: AtomicUsize::new(0),
+            compress_keystore: None,
             recursion_vk_root: root,
             recursion_vk_tree: merkle_tree,
             recursion_vk_map: allowed_vk_map,
@@ -273,7 +345,79 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
             vk_verification,
             wrap_program: OnceLock::new(),
             wrap_vk: OnceLock::new(),
+            shrink_keys: Mutex::new(BTreeMap::new()),
+            wrap_keys: OnceLock::new(),
+            prewarm_sample_cycles,
+            trace_backing: TraceBacking::from_env(),
+            thread_pool: ProverThreadPool::from_env(),
+        };
+        if let Some(dir) = env::var_os("RECURSION_KEYS_DIR") {
+            if let Err(e) = prover.load_recursion_keys(&dir) {
+                tracing::warn!("failed to load the recursion keys: {}", e);
+            }
         }
+        if precompute_compress_keys {
+            prover.with_compress_keystore(compress_keys_dir.as_deref())
+        } else {
+            prover
+        }
+    }
+
+    /// Enables the tiny program profile of the core shapes, which reduces the proving time of
+    /// hello-world-scale programs. Also enabled by setting `TINY_PROGRAM_SHAPES=true`.
+    ///
+    /// Proofs generated with the profile can only be compressed with vk verification disabled,
+    /// see [CoreShapeConfig::with_tiny_program_profile].
+    pub fn with_tiny_program_profile(mut self) -> Self {
+        self.core_shape_config =
+            self.core_shape_config.map(CoreShapeConfig::with_tiny_program_profile);
+        self
+    }
+
+    /// Prewarms the cache of recursion programs while the core shards are proven, from the shard
+    /// shapes predicted by an opcode sample of the first `sample_cycles` cycles of the execution.
+    /// Also enabled by setting `PREWARM_SAMPLE_CYCLES`.
+    ///
+    /// Otherwise the recursion programs are compiled on demand once the core proof is complete,
+    /// which adds to the latency of compressed proofs.
+    pub fn with_recursion_prewarm(mut self, sample_cycles: u64) -> Self {
+        self.prewarm_sample_cycles = Some(sample_cycles);
+        self
+    }
+
+    /// Keeps the traces of the shards waiting to be proven according to `trace_backing`. Also
+    /// set by `TRACE_SPILL_DIR`, which spills them to memory-mapped files in that directory.
+    pub fn with_trace_backing(mut self, trace_backing: TraceBacking) -> Self {
+        self.trace_backing = trace_backing;
+        self
+    }
+
+    /// Runs the parallel work of the provers on `thread_pool` instead of the global rayon pool.
+    /// Also set by `PROVER_THREADS` and `PROVER_CPUS`, see [ProverThreadPool::from_env].
+    pub fn with_thread_pool(mut self, thread_pool: ProverThreadPool) -> Self {
+        self.thread_pool = thread_pool;
+        self
+    }
+
+    /// Loads the recursion programs from `cache` when they are not in memory, and stores the ones
+    /// it compiles there. Also enabled by setting `RECURSION_PROGRAM_CACHE_DIR`.
+    pub fn with_program_cache(mut self, cache: ProgramCache) -> Self {
+        self.program_cache = Some(cache);
+        self
+    }
+
+    /// Sets up the keys of the compression programs of the fixed recursion shapes once, instead
+    /// of at every node of the reduce tree they prove. Also enabled by setting
+    /// `PRECOMPUTE_COMPRESS_KEYS=true`.
+    ///
+    /// With `dir`, or `COMPRESS_KEYS_DIR`, the keys are loaded from the directory if they were
+    /// stored there by a previous process, and stored there otherwise.
+    pub fn with_compress_keystore(mut self, dir: Option<&Path>) -> Self {
+        let keystore = tracing::debug_span!("setup compress keystore")
+            .in_scope(|| CompressKeystore::load_or_setup(&self, dir));
+        tracing::debug!("set up the keys of {} compress programs", keystore.len());
+        self.compress_keystore = Some(keystore);
+        self
     }
 
     /// Fully initializes the programs, proving keys, and verifying keys that are normally
@@ -298,9 +442,42 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         (pk, pk_d, program, vk)
     }
 
+    /// Creates the proving and verifying keys for many programs at once.
+    ///
+    /// The programs are parsed and set up in parallel, sharing the preprocessing which does not
+    /// depend on the program, and the keys are returned in the order of `elves`. Returns an error
+    /// if any of the programs is not a valid ELF or has no allowed preprocessed shape.
+    #[instrument(name = "setup_many", level = "debug", skip_all)]
+    pub fn setup_many(
+        &self,
+        elves: &[&[u8]],
+    ) -> eyre::Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        let programs = elves
+            .par_iter()
+            .enumerate()
+            .map(|(index, elf)| {
+                self.get_program(elf).map_err(|err| eyre::eyre!("program {index}: {err}"))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let keys = self.core_prover.setup_many(&programs);
+        Ok(elves
+            .iter()
+            .zip(keys)
+            .map(|(elf, (pk, vk))| {
+                let vk = ZKMVerifyingKey { vk };
+                let pk = ZKMProvingKey {
+                    pk: self.core_prover.pk_to_host(&pk),
+                    elf: elf.to_vec(),
+                    vk: vk.clone(),
+                };
+                (pk, vk)
+            })
+            .collect())
+    }
+
     /// Get a program with an allowed preprocessed shape.
     pub fn get_program(&self, elf: &[u8]) -> eyre::Result<Program> {
-        let mut program = Program::from(elf).unwrap();
+        let mut program = Program::from(elf).map_err(|err| eyre::eyre!(err))?;
         if let Some(core_shape_config) = &self.core_shape_config {
             core_shape_config.fix_preprocessed_shape(&mut program)?;
         }
@@ -317,20 +494,204 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
     ) -> Result<(ZKMPublicValues, ExecutionReport), ExecutionError> {
         context.subproof_verifier = Some(self);
         let program = self.get_program(elf).unwrap();
-        let opts = ZKMCoreOpts::default();
+        let mut opts = ZKMCoreOpts::default();
+        let shard_tuning =
+            if opts.auto_tune { tune_shards(&program, stdin, &mut opts) } else { None };
         let mut runtime = Executor::with_context(program, opts, context);
         runtime.write_vecs(&stdin.buffer);
+        runtime.write_files(&stdin.files);
+        runtime.write_named_inputs(&stdin.named_inputs);
         for (proof, vkey) in stdin.proofs.iter() {
             runtime.write_proof(proof.clone(), vkey.clone());
         }
         runtime.run_fast()?;
+        runtime.report.shard_tuning = shard_tuning;
         Ok((ZKMPublicValues::from(&runtime.state.public_values_stream), runtime.report))
     }
 
+    /// Executes a program once on the input `stdin` shared by all forks, then resumes it from
+    /// where it first reads past that input once for every input in `suffixes`.
+    ///
+    /// The memory of the program is shared between the forks and copied on write, so the prefix
+    /// is neither re-executed nor copied per fork. Returns the result of every fork, in order, or
+    /// an error if the execution of the prefix fails.
+    pub fn execute_forks<'a>(
+        &'a self,
+        elf: &[u8],
+        stdin: &ZKMStdin,
+        suffixes: impl IntoIterator<Item = ZKMStdin>,
+        mut context: ZKMContext<'a>,
+    ) -> Result<Vec<Result<(ZKMPublicValues, ExecutionReport), ExecutionError>>, ExecutionError>
+    {
+        context.subproof_verifier = Some(self);
+        let program = self.get_program(elf).unwrap();
+        let opts = ZKMCoreOpts::default();
+        let mut runtime = Executor::with_context(program, opts, context);
+        runtime.write_vecs(&stdin.buffer);
+        runtime.write_files(&stdin.files);
+        runtime.write_named_inputs(&stdin.named_inputs);
+        for (proof, vkey) in stdin.proofs.iter() {
+            runtime.write_proof(proof.clone(), vkey.clone());
+        }
+        if runtime.run_until_input_exhausted()? {
+            // The program did not read past the prefix, so the suffixes make no difference.
+            let result =
+                (ZKMPublicValues::from(&runtime.state.public_values_stream), runtime.report);
+            return Ok(suffixes.into_iter().map(|_| Ok(result.clone())).collect());
+        }
+
+        let snapshot = runtime.snapshot();
+        let results = suffixes
+            .into_iter()
+            .map(|suffix| -> Result<_, ExecutionError> {
+                runtime.restore(snapshot.clone());
+                runtime.write_vecs(&suffix.buffer);
+                runtime.write_files(&suffix.files);
+                runtime.write_named_inputs(&suffix.named_inputs);
+                for (proof, vkey) in suffix.proofs {
+                    runtime.write_proof(proof, vkey);
+                }
+                runtime.run_fast()?;
+                Ok((
+                    ZKMPublicValues::from(&runtime.state.public_values_stream),
+                    runtime.report.clone(),
+                ))
+            })
+            .collect();
+        Ok(results)
+    }
+
+    /// Estimates the number of shards, the trace areas and the proving time of a program on the
+    /// given input, by executing it with cost estimation enabled instead of proving it.
+    pub fn estimate(&self, elf: &[u8], stdin: &ZKMStdin) -> Result<CostEstimate, ExecutionError> {
+        let program_size = self.get_program(elf).unwrap().instructions.len();
+        let context = ZKMContext::builder().cost_estimation(true).build();
+        let (_, report) = self.execute(elf, stdin, context)?;
+        Ok(CostEstimate::new(program_size, report))
+    }
+
     /// Generate shard proofs which split up and prove the valid execution of a MIPS program with
     /// the core prover. Uses the provided context.
     #[instrument(name = "prove_core", level = "info", skip_all)]
     pub fn prove_core<'a>(
+        &'a self,
+        pk_d: &<<C as ZKMProverComponents>::CoreProver as MachineProver<
+            KoalaBearPoseidon2,
+            MipsAir<KoalaBear>,
+        >>::DeviceProvingKey,
+        program: Program,
+        stdin: &ZKMStdin,
+        opts: ZKMProverOpts,
+        context: ZKMContext<'a>,
+    ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        self.prove_core_impl(pk_d, program, stdin, opts, context, None, BTreeMap::new(), None)
+    }
+
+    /// Like [`Self::prove_core`], but additionally sends each shard proof to `shard_tx` together
+    /// with its [`ShardProgress`] as soon as it is proven.
+    ///
+    /// This blocks until all shards are proven, so the receiver should be drained on another
+    /// thread. The shards are proven concurrently and may arrive out of order.
+    #[instrument(name = "prove_core_stream", level = "info", skip_all)]
+    pub fn prove_core_stream<'a>(
+        &'a self,
+        pk_d: &<<C as ZKMProverComponents>::CoreProver as MachineProver<
+            KoalaBearPoseidon2,
+            MipsAir<KoalaBear>,
+        >>::DeviceProvingKey,
+        program: Program,
+        stdin: &ZKMStdin,
+        opts: ZKMProverOpts,
+        context: ZKMContext<'a>,
+        shard_tx: Sender<(ShardProgress, ShardProof<CoreSC>)>,
+    ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        self.prove_core_impl(
+            pk_d,
+            program,
+            stdin,
+            opts,
+            context,
+            Some(shard_tx),
+            BTreeMap::new(),
+            None,
+        )
+    }
+
+    /// Like [`Self::prove_core`], but persists each shard proof to `checkpoint_dir` as soon as it
+    /// is proven, and reuses the shard proofs already persisted there.
+    ///
+    /// If proving is interrupted, calling this again with the same arguments resumes from the
+    /// persisted shards. Execution is deterministic and is replayed from the start, which is cheap
+    /// compared to proving: only the shards which are not persisted yet have their traces
+    /// generated and proven. Persisted proofs are verified before being reused. The directory is
+    /// left in place once the proof is complete.
+    #[instrument(name = "prove_core_resumable", level = "info", skip_all)]
+    pub fn prove_core_resumable<'a>(
+        &'a self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+        opts: ZKMProverOpts,
+        context: ZKMContext<'a>,
+        checkpoint_dir: impl AsRef<Path>,
+    ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        let checkpoints =
+            CheckpointDir::open(checkpoint_dir.as_ref(), &pk.vk, stdin, &opts.core_opts)?;
+        let proven = checkpoints.load_proofs(self, &pk.vk)?;
+        tracing::info!("resuming with {} proven shards", proven.len());
+
+        let program = self.get_program(&pk.elf).unwrap();
+        let pk_d = self.core_prover.pk_to_device(&pk.pk);
+        let (shard_tx, shard_rx) = channel::<(ShardProgress, ShardProof<CoreSC>)>();
+        thread::scope(|s| {
+            // Persist the shards as they are proven. A failure to persist a shard only loses the
+            // ability to resume from it, so proving continues.
+            s.spawn(move || {
+                for (progress, proof) in shard_rx {
+                    if let Err(e) = checkpoints.save_proof(progress.shard, &proof) {
+                        tracing::warn!("failed to persist shard {}: {}", progress.shard, e);
+                    }
+                }
+            });
+            self.prove_core_impl(&pk_d, program, stdin, opts, context, Some(shard_tx), proven, None)
+        })
+    }
+
+    /// Like [`Self::prove_core`], but reuses the proofs of the shards whose execution record did
+    /// not change since a previous proof of the program, cached in `cache_dir`.
+    ///
+    /// This is an experimental mode for re-proving nearly identical executions, such as a state
+    /// transition with one transaction changed. A shard is only reused if its record is exactly
+    /// the same, including its index, its clock and the memory it initializes, so the shards
+    /// before the first change in the execution are typically reused and the ones after it are
+    /// proven again. The proofs of the new shards are added to the cache, and cached proofs are
+    /// verified before being reused.
+    #[instrument(name = "prove_core_incremental", level = "info", skip_all)]
+    pub fn prove_core_incremental<'a>(
+        &'a self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+        opts: ZKMProverOpts,
+        context: ZKMContext<'a>,
+        cache_dir: impl AsRef<Path>,
+    ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        let dir = ShardCacheDir::open(cache_dir.as_ref(), &pk.vk)?;
+        let cache = VerifiedShardCache { dir, prover: self, vk: &pk.vk };
+        let program = self.get_program(&pk.elf).unwrap();
+        let pk_d = self.core_prover.pk_to_device(&pk.pk);
+        self.prove_core_impl(
+            &pk_d,
+            program,
+            stdin,
+            opts,
+            context,
+            None,
+            BTreeMap::new(),
+            Some(&cache),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn prove_core_impl<'a>(
         &'a self,
         pk_d: &<<C as ZKMProverComponents>::CoreProver as MachineProver<
             KoalaBearPoseidon2,
@@ -340,19 +701,44 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         stdin: &ZKMStdin,
         opts: ZKMProverOpts,
         mut context: ZKMContext<'a>,
+        shard_tx: Option<Sender<(ShardProgress, ShardProof<CoreSC>)>>,
+        proven: BTreeMap<u32, ShardProof<CoreSC>>,
+        shard_cache: Option<&dyn ShardProofCache<CoreSC>>,
     ) -> Result<ZKMCoreProof, ZKMCoreProverError> {
+        // The proofs of shards with dynamic shapes can't be compressed against the allowed vks, so
+        // fail before proving any shard.
+        if opts.core_opts.dynamic_shapes && self.vk_verification {
+            return Err(ZKMCoreProverError::DynamicShapesWithVkVerification);
+        }
         context.subproof_verifier = Some(self);
         let pk = pk_d;
-        let (proof, public_values_stream, cycles) =
-            zkm_core_machine::utils::prove_with_context::<_, C::CoreProver>(
+        let (sample_tx, sample_rx) = channel();
+        context.opcode_sample = self.prewarm_sample_cycles.map(|cycles| (cycles, sample_tx));
+        let (proof, public_values_stream, cycles) = thread::scope(|s| {
+            // Compile the recursion programs of the predicted shapes while the shards are proven.
+            // The sample is never sent if prewarming is disabled or the execution fails early.
+            let (program, shard_size) = (&program, opts.core_opts.shard_size);
+            s.spawn(move || {
+                if let Ok(sample) = sample_rx.recv() {
+                    let shapes = self.prewarm_recursion_programs(program, &sample, shard_size);
+                    tracing::debug!("prewarmed recursion programs of {} shard shapes", shapes);
+                }
+            });
+            zkm_core_machine::utils::prove_with_context_streaming::<_, C::CoreProver>(
                 &self.core_prover,
                 pk,
-                program,
+                program.clone(),
                 stdin,
                 opts.core_opts,
                 context,
                 self.core_shape_config.as_ref(),
-            )?;
+                &self.trace_backing,
+                &self.thread_pool,
+                shard_tx,
+                proven,
+                shard_cache,
+            )
+        })?;
         Self::check_for_high_cycles(cycles);
         let public_values = ZKMPublicValues::from(&public_values_stream);
         Ok(ZKMCoreProof {
@@ -367,11 +753,21 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         &self,
         input: &ZKMRecursionWitnessValues<CoreSC>,
     ) -> Arc<RecursionProgram<KoalaBear>> {
+        let shape = input.shape();
+        let fixed_shape = self.compress_shape_config.is_some();
+        self.lift_cache_lookups.fetch_add(1, Ordering::Relaxed);
         let mut cache = self.lift_programs_lru.lock().unwrap_or_else(|e| e.into_inner());
         cache
-            .get_or_insert(input.shape(), || {
+            .get_or_insert(shape.clone(), || {
                 let misses = self.lift_cache_misses.fetch_add(1, Ordering::Relaxed);
                 tracing::debug!("core cache miss, misses: {}", misses);
+                if let Some(program) =
+                    self.program_cache.as_ref().and_then(|disk| disk.load(&shape, fixed_shape))
+                {
+                    tracing::debug!("loaded recursion program from the disk cache");
+                    return Arc::new(program);
+                }
+
                 // Get the operations.
                 let builder_span = tracing::debug_span!("build recursion program").entered();
                 let mut builder = Builder::<InnerConfig>::default();
@@ -388,9 +784,17 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                 if let Some(recursion_shape_config) = &self.compress_shape_config {
                     recursion_shape_config.fix_shape(&mut program);
                 }
-                let program = Arc::new(program);
                 compiler_span.exit();
-                program
+
+                if let Some(disk) = &self.program_cache {
+                    if let Err(e) = disk.store(&shape, fixed_shape, &program) {
+                        tracing::warn!(
+                            "failed to store recursion program in the disk cache: {}",
+                            e
+                        );
+                    }
+                }
+                Arc::new(program)
             })
             .clone()
     }
@@ -399,7 +803,9 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         &self,
         input: &ZKMCompressWithVKeyWitnessValues<InnerSC>,
     ) -> Arc<RecursionProgram<KoalaBear>> {
+        self.join_cache_lookups.fetch_add(1, Ordering::Relaxed);
         self.join_programs_map.get(&input.shape()).cloned().unwrap_or_else(|| {
+            self.join_cache_misses.fetch_add(1, Ordering::Relaxed);
             tracing::warn!("compress program not found in map, recomputing join program.");
             // Get the operations.
             Arc::new(compress_program_from_input::<C>(
@@ -610,7 +1016,6 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
     }
 
     /// Reduce shard proofs to a single shard proof using the recursion prover.
-    #[instrument(name = "compress", level = "info", skip_all)]
     pub fn compress(
         &self,
         vk: &ZKMVerifyingKey,
@@ -618,8 +1023,22 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         deferred_proofs: Vec<ZKMReduceProof<InnerSC>>,
         opts: ZKMProverOpts,
     ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
-        // The batch size for reducing two layers of recursion.
-        let batch_size = REDUCE_BATCH_SIZE;
+        self.compress_with_stats(vk, proof, deferred_proofs, opts, &Progress::default())
+            .map(|(proof, _)| proof)
+    }
+
+    /// Reduce shard proofs to a single shard proof using the recursion prover, also returning
+    /// the [RecursionStats] of every node of the compress tree. The proofs of the tree are
+    /// reported to `progress`.
+    #[instrument(name = "compress", level = "info", skip_all)]
+    pub fn compress_with_stats(
+        &self,
+        vk: &ZKMVerifyingKey,
+        proof: ZKMCoreProof,
+        deferred_proofs: Vec<ZKMReduceProof<InnerSC>>,
+        opts: ZKMProverOpts,
+        progress: &Progress,
+    ) -> Result<(ZKMReduceProof<InnerSC>, RecursionStats), ZKMRecursionProverError> {
         // The batch size for reducing the first layer of recursion.
         let first_layer_batch_size = 1;
 
@@ -627,20 +1046,120 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
 
         let first_layer_inputs =
             self.get_first_layer_inputs(vk, shard_proofs, &deferred_proofs, first_layer_batch_size);
+        self.reduce_with_stats(first_layer_inputs, true, opts, progress)
+    }
+
+    /// Aggregate the compressed proofs of independent programs into a single reduce proof.
+    ///
+    /// Every proof is verified as a deferred proof, and the proofs are reduced with the compress
+    /// tree. The public values of the aggregated proof commit to the `(vkey,
+    /// committed_value_digest)` pairs of the proofs, in order, as its
+    /// `end_reconstruct_deferred_digest`. It is not complete: verify it with
+    /// [Self::verify_aggregated] rather than [Self::verify_compressed].
+    #[instrument(name = "aggregate", level = "info", skip_all)]
+    pub fn aggregate(
+        &self,
+        proofs: Vec<(ZKMReduceProof<InnerSC>, ZKMVerifyingKey)>,
+        opts: ZKMProverOpts,
+    ) -> Result<ZKMReduceProof<InnerSC>, ZKMRecursionProverError> {
+        if proofs.is_empty() {
+            return Err(ZKMRecursionProverError::InvalidAggregateInput(
+                "no proofs to aggregate".to_string(),
+            ));
+        }
+        for (index, (proof, vk)) in proofs.iter().enumerate() {
+            let pv: &RecursionPublicValues<Val<InnerSC>> =
+                proof.proof.public_values.as_slice().borrow();
+            if pv.is_complete != KoalaBear::ONE {
+                return Err(ZKMRecursionProverError::InvalidAggregateInput(format!(
+                    "proof {index} is not a compressed proof"
+                )));
+            }
+            if pv.zkm_vk_digest != vk.hash_koalabear() {
+                return Err(ZKMRecursionProverError::InvalidAggregateInput(format!(
+                    "proof {index} does not match its verifying key"
+                )));
+            }
+        }
+
+        // The values passed through the deferred programs are those of an empty execution, so
+        // that the deferred proofs chain into each other in the compress tree.
+        let proofs = proofs.into_iter().map(|(proof, _)| proof).collect::<Vec<_>>();
+        let mut deferred_digest = [Val::<InnerSC>::ZERO; DIGEST_SIZE];
+        let mut first_layer_inputs = Vec::new();
+        for batch in proofs.chunks(1) {
+            let vks_and_proofs =
+                batch.iter().cloned().map(|proof| (proof.vk, proof.proof)).collect::<Vec<_>>();
+            let input = self
+                .make_merkle_proofs(ZKMCompressWitnessValues { vks_and_proofs, is_complete: true });
+            let ZKMCompressWithVKeyWitnessValues { compress_val, merkle_val } = input;
+
+            first_layer_inputs.push(ZKMCircuitWitness::Deferred(ZKMDeferredWitnessValues {
+                vks_and_proofs: compress_val.vks_and_proofs,
+                vk_merkle_data: merkle_val,
+                start_reconstruct_deferred_digest: deferred_digest,
+                is_complete: false,
+                zkm_vk_digest: [Val::<InnerSC>::ZERO; DIGEST_SIZE],
+                end_pc: Val::<InnerSC>::ZERO,
+                end_shard: Val::<InnerSC>::ZERO,
+                end_execution_shard: Val::<InnerSC>::ZERO,
+                init_addr_bits: [Val::<InnerSC>::ZERO; 32],
+                finalize_addr_bits: [Val::<InnerSC>::ZERO; 32],
+                committed_value_digest: Default::default(),
+                deferred_proofs_digest: [Val::<InnerSC>::ZERO; DIGEST_SIZE],
+            }));
+
+            deferred_digest = Self::hash_deferred_proofs(deferred_digest, batch);
+        }
+
+        self.reduce_with_stats(first_layer_inputs, false, opts, &Progress::default())
+            .map(|(proof, _)| proof)
+    }
+
+    /// Reduce the first layer inputs to a single proof with the compress tree, also returning the
+    /// [RecursionStats] of every node of the tree. The root is marked complete if `complete` is
+    /// set.
+    fn reduce_with_stats(
+        &self,
+        first_layer_inputs: Vec<ZKMCircuitWitness>,
+        complete: bool,
+        opts: ZKMProverOpts,
+        progress: &Progress,
+    ) -> Result<(ZKMReduceProof<InnerSC>, RecursionStats), ZKMRecursionProverError> {
+        // The batch size for reducing two layers of recursion.
+        let batch_size = REDUCE_BATCH_SIZE;
 
         // Calculate the expected height of the tree.
         let mut expected_height = if first_layer_inputs.len() == 1 { 0 } else { 1 };
         let num_first_layer_inputs = first_layer_inputs.len();
         let mut num_layer_inputs = num_first_layer_inputs;
+        let mut num_proofs = num_first_layer_inputs;
         while num_layer_inputs > batch_size {
             num_layer_inputs = num_layer_inputs.div_ceil(2);
+            num_proofs += num_layer_inputs;
             expected_height += 1;
         }
+        if expected_height > 0 {
+            num_proofs += 1;
+        }
+        let num_proven = AtomicUsize::new(0);
+        let (num_proven, thread_pool) = (&num_proven, &self.thread_pool);
+
+        // The stats of each recursion program executed in the tree.
+        let stats = Mutex::new(RecursionStats::default());
 
         // Generate the proofs.
+        let watchdog = Watchdog::new(
+            (opts.recursion_opts.stall_timeout_secs > 0)
+                .then(|| Duration::from_secs(opts.recursion_opts.stall_timeout_secs)),
+        );
         let span = tracing::Span::current().clone();
-        let (vk, proof) = thread::scope(|s| {
+        let root = thread::scope(|s| {
             let _span = span.enter();
+            let watchdog = &watchdog;
+
+            // Spawn the watchdog which aborts the pipeline if it stalls.
+            s.spawn(move || watchdog.monitor());
 
             // Spawn a worker that sends the first layer inputs to a bounded channel.
             let input_sync = Arc::new(TurnBasedSync::new());
@@ -652,11 +1171,16 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                 let input_tx = Arc::clone(&input_tx);
                 let input_sync = Arc::clone(&input_sync);
                 s.spawn(move || {
-                    for (index, input) in first_layer_inputs.into_iter().enumerate() {
-                        input_sync.wait_for_turn(index);
-                        input_tx.lock().unwrap().send((index, 0, input)).unwrap();
-                        input_sync.advance_turn();
-                    }
+                    watchdog.run_worker(CompressStage::Input, 0, || {
+                        for (index, input) in first_layer_inputs.into_iter().enumerate() {
+                            if !watchdog.wait_for_turn(&input_sync, index)
+                                || !watchdog.send(&input_tx, (index, 0, input))
+                            {
+                                break;
+                            }
+                            input_sync.advance_turn();
+                        }
+                    })
                 });
             }
 
@@ -667,36 +1191,40 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                     usize,
                     usize,
                     Arc<RecursionProgram<KoalaBear>>,
+                    Option<CompressKeys<C>>,
                     ExecutionRecord<KoalaBear>,
                     Vec<(String, RowMajorMatrix<KoalaBear>)>,
                 )>(opts.recursion_opts.records_and_traces_channel_capacity);
             let record_and_trace_tx = Arc::new(Mutex::new(record_and_trace_tx));
             let record_and_trace_rx = Arc::new(Mutex::new(record_and_trace_rx));
             let input_rx = Arc::new(Mutex::new(input_rx));
-            for _ in 0..opts.recursion_opts.trace_gen_workers {
+            for worker in 0..opts.recursion_opts.trace_gen_workers {
                 let record_and_trace_sync = Arc::clone(&record_and_trace_sync);
                 let record_and_trace_tx = Arc::clone(&record_and_trace_tx);
                 let input_rx = Arc::clone(&input_rx);
+                let stats = &stats;
                 let span = tracing::debug_span!("generate records and traces");
                 s.spawn(move || {
                     let _span = span.enter();
-                    loop {
-                        let received = { input_rx.lock().unwrap().recv() };
-                        if let Ok((index, height, input)) = received {
-                            // Get the program and witness stream.
-                            let (program, witness_stream) = tracing::debug_span!(
+                    watchdog.run_worker(CompressStage::RecordAndTrace, worker, || loop {
+                        let received = watchdog.recv(&input_rx);
+                        if let Some((index, height, input)) = received {
+                            watchdog.begin(CompressStage::RecordAndTrace, worker, index);
+
+                            // Get the program, its keys if they are set up, and the witness stream.
+                            let (program, keys, witness_stream) = tracing::debug_span!(
                                 "get program and witness stream"
                             )
                             .in_scope(|| match input {
                                 ZKMCircuitWitness::Core(input) => {
                                     let mut witness_stream = Vec::new();
                                     Witnessable::<InnerConfig>::write(&input, &mut witness_stream);
-                                    (self.recursion_program(&input), witness_stream)
+                                    (self.recursion_program(&input), None, witness_stream)
                                 }
                                 ZKMCircuitWitness::Deferred(input) => {
                                     let mut witness_stream = Vec::new();
                                     Witnessable::<InnerConfig>::write(&input, &mut witness_stream);
-                                    (self.deferred_program(&input), witness_stream)
+                                    (self.deferred_program(&input), None, witness_stream)
                                 }
                                 ZKMCircuitWitness::Compress(input) => {
                                     let mut witness_stream = Vec::new();
@@ -708,7 +1236,15 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                                         &mut witness_stream,
                                     );
 
-                                    (self.compress_program(&input_with_merkle), witness_stream)
+                                    let keys =
+                                        self.compress_keystore.as_ref().and_then(|keystore| {
+                                            keystore.get(&input_with_merkle.shape())
+                                        });
+                                    (
+                                        self.compress_program(&input_with_merkle),
+                                        keys,
+                                        witness_stream,
+                                    )
                                 }
                             });
 
@@ -726,40 +1262,49 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                                         ZKMRecursionProverError::RuntimeError(e.to_string())
                                     })
                                     .unwrap();
+                                stats.lock().unwrap().push(height, index, runtime.stats());
                                 runtime.record
                             });
 
                             // Generate the dependencies.
                             let mut records = vec![record];
                             tracing::debug_span!("generate dependencies").in_scope(|| {
-                                self.compress_prover.machine().generate_dependencies(
-                                    &mut records,
-                                    &opts.recursion_opts,
-                                    None,
-                                )
+                                thread_pool.install(|| {
+                                    self.compress_prover.machine().generate_dependencies(
+                                        &mut records,
+                                        &opts.recursion_opts,
+                                        None,
+                                    )
+                                })
                             });
 
                             // Generate the traces.
                             let record = records.into_iter().next().unwrap();
-                            let traces = tracing::debug_span!("generate traces")
-                                .in_scope(|| self.compress_prover.generate_traces(&record));
+                            let traces = tracing::debug_span!("generate traces").in_scope(|| {
+                                thread_pool
+                                    .install(|| self.compress_prover.generate_traces(&record))
+                            });
+                            watchdog.end(CompressStage::RecordAndTrace, worker);
 
                             // Wait for our turn to update the state.
-                            record_and_trace_sync.wait_for_turn(index);
+                            if !watchdog.wait_for_turn(&record_and_trace_sync, index) {
+                                break;
+                            }
 
                             // Send the record and traces to the worker.
-                            record_and_trace_tx
-                                .lock()
-                                .unwrap()
-                                .send((index, height, program, record, traces))
-                                .unwrap();
+                            if !watchdog.send(
+                                &record_and_trace_tx,
+                                (index, height, program, keys, record, traces),
+                            ) {
+                                break;
+                            }
 
                             // Advance the turn.
                             record_and_trace_sync.advance_turn();
                         } else {
                             break;
                         }
-                    }
+                    })
                 });
             }
 
@@ -772,20 +1317,25 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
             let proofs_tx = Arc::new(Mutex::new(proofs_tx));
             let proofs_rx = Arc::new(Mutex::new(proofs_rx));
             let mut prover_handles = Vec::new();
-            for _ in 0..opts.recursion_opts.shard_batch_size {
+            for worker in 0..opts.recursion_opts.shard_batch_size {
                 let prover_sync = Arc::clone(&proofs_sync);
                 let record_and_trace_rx = Arc::clone(&record_and_trace_rx);
                 let proofs_tx = Arc::clone(&proofs_tx);
                 let span = tracing::debug_span!("prove");
                 let handle = s.spawn(move || {
                     let _span = span.enter();
-                    loop {
-                        let received = { record_and_trace_rx.lock().unwrap().recv() };
-                        if let Ok((index, height, program, record, traces)) = received {
-                            tracing::debug_span!("batch").in_scope(|| {
-                                // Get the keys.
-                                let (pk, vk) = tracing::debug_span!("Setup compress program")
-                                    .in_scope(|| self.compress_prover.setup(&program));
+                    watchdog.run_worker(CompressStage::Prove, worker, || loop {
+                        let received = watchdog.recv(&record_and_trace_rx);
+                        if let Some((index, height, program, keys, record, traces)) = received {
+                            let sent = tracing::debug_span!("batch").in_scope(|| {
+                                watchdog.begin(CompressStage::Prove, worker, index);
+
+                                // Get the keys, setting them up if they are not in the keystore.
+                                let keys = keys.unwrap_or_else(|| {
+                                    tracing::debug_span!("Setup compress program")
+                                        .in_scope(|| Arc::new(self.compress_prover.setup(&program)))
+                                });
+                                let (pk, vk) = &*keys;
 
                                 // Observe the proving key.
                                 let mut challenger = self.compress_prover.config().challenger();
@@ -795,18 +1345,20 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
 
                                 #[cfg(feature = "debug")]
                                 self.compress_prover.debug_constraints(
-                                    &self.compress_prover.pk_to_host(&pk),
+                                    &self.compress_prover.pk_to_host(pk),
                                     vec![record.clone()],
                                     &mut challenger.clone(),
                                 );
 
-                                // Commit to the record and traces.
-                                let data = tracing::debug_span!("commit")
-                                    .in_scope(|| self.compress_prover.commit(&record, traces));
-
-                                // Generate the proof.
-                                let proof = tracing::debug_span!("open").in_scope(|| {
-                                    self.compress_prover.open(&pk, data, &mut challenger).unwrap()
+                                // Commit to the record and traces, and generate the proof.
+                                let proof = thread_pool.install(|| {
+                                    let data = tracing::debug_span!("commit")
+                                        .in_scope(|| self.compress_prover.commit(&record, traces));
+                                    tracing::debug_span!("open").in_scope(|| {
+                                        self.compress_prover
+                                            .open(pk, data, &mut challenger)
+                                            .unwrap()
+                                    })
                                 });
 
                                 // Verify the proof.
@@ -814,27 +1366,42 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                                 self.compress_prover
                                     .machine()
                                     .verify(
-                                        &vk,
+                                        vk,
                                         &zkm_stark::MachineProof {
                                             shard_proofs: vec![proof.clone()],
                                         },
                                         &mut self.compress_prover.config().challenger(),
                                     )
                                     .unwrap();
+                                watchdog.end(CompressStage::Prove, worker);
 
                                 // Wait for our turn to update the state.
-                                prover_sync.wait_for_turn(index);
+                                if !watchdog.wait_for_turn(&prover_sync, index) {
+                                    return false;
+                                }
 
                                 // Send the proof.
-                                proofs_tx.lock().unwrap().send((index, height, vk, proof)).unwrap();
+                                if !watchdog.send(&proofs_tx, (index, height, vk.clone(), proof)) {
+                                    return false;
+                                }
+                                progress.report(ProgressEvent::CompressProof {
+                                    layer: height,
+                                    num_layers: expected_height + 1,
+                                    num_proven: num_proven.fetch_add(1, Ordering::Relaxed) + 1,
+                                    num_proofs,
+                                });
 
                                 // Advance the turn.
                                 prover_sync.advance_turn();
+                                true
                             });
+                            if !sent {
+                                break;
+                            }
                         } else {
                             break;
                         }
-                    }
+                    })
                 });
                 prover_handles.push(handle);
             }
@@ -846,75 +1413,77 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
                 let span = tracing::debug_span!("generate next layer inputs");
                 s.spawn(move || {
                     let _span = span.enter();
-                    let mut count = num_first_layer_inputs;
-                    let mut batch: Vec<(
-                        usize,
-                        usize,
-                        StarkVerifyingKey<InnerSC>,
-                        ShardProof<InnerSC>,
-                    )> = Vec::new();
-                    loop {
-                        if expected_height == 0 {
-                            break;
-                        }
-                        let received = { proofs_rx.lock().unwrap().recv() };
-                        if let Ok((index, height, vk, proof)) = received {
-                            batch.push((index, height, vk, proof));
-
-                            // If we haven't reached the batch size, continue.
-                            if batch.len() < batch_size {
-                                continue;
+                    watchdog.run_worker(CompressStage::NextLayer, 0, || {
+                        let mut count = num_first_layer_inputs;
+                        let mut batch: Vec<(
+                            usize,
+                            usize,
+                            StarkVerifyingKey<InnerSC>,
+                            ShardProof<InnerSC>,
+                        )> = Vec::new();
+                        loop {
+                            if expected_height == 0 {
+                                break;
                             }
+                            let received = watchdog.recv(&proofs_rx);
+                            if let Some((index, height, vk, proof)) = received {
+                                batch.push((index, height, vk, proof));
 
-                            // Compute whether we're at the last input of a layer.
-                            let mut is_last = false;
-                            if let Some(first) = batch.first() {
-                                is_last = first.1 != height;
-                            }
+                                // If we haven't reached the batch size, continue.
+                                if batch.len() < batch_size {
+                                    continue;
+                                }
 
-                            // If we're at the last input of a layer, we need to only include the
-                            // first input, otherwise we include all inputs.
-                            let inputs =
-                                if is_last { vec![batch[0].clone()] } else { batch.clone() };
+                                // Compute whether we're at the last input of a layer.
+                                let mut is_last = false;
+                                if let Some(first) = batch.first() {
+                                    is_last = first.1 != height;
+                                }
 
-                            let next_input_height = inputs[0].1 + 1;
+                                // If we're at the last input of a layer, we need to only include
+                                // the first input, otherwise we include all inputs.
+                                let inputs =
+                                    if is_last { vec![batch[0].clone()] } else { batch.clone() };
 
-                            let is_complete = next_input_height == expected_height;
+                                let next_input_height = inputs[0].1 + 1;
 
-                            let vks_and_proofs = inputs
-                                .into_iter()
-                                .map(|(_, _, vk, proof)| (vk, proof))
-                                .collect::<Vec<_>>();
-                            let input = ZKMCircuitWitness::Compress(ZKMCompressWitnessValues {
-                                vks_and_proofs,
-                                is_complete,
-                            });
+                                let is_root = next_input_height == expected_height;
+                                let is_complete = complete && is_root;
 
-                            input_sync.wait_for_turn(count);
-                            input_tx
-                                .lock()
-                                .unwrap()
-                                .send((count, next_input_height, input))
-                                .unwrap();
-                            input_sync.advance_turn();
-                            count += 1;
+                                let vks_and_proofs = inputs
+                                    .into_iter()
+                                    .map(|(_, _, vk, proof)| (vk, proof))
+                                    .collect::<Vec<_>>();
+                                let input = ZKMCircuitWitness::Compress(ZKMCompressWitnessValues {
+                                    vks_and_proofs,
+                                    is_complete,
+                                });
 
-                            // If we're at the root of the tree, stop generating inputs.
-                            if is_complete {
-                                break;
-                            }
+                                if !watchdog.wait_for_turn(&input_sync, count)
+                                    || !watchdog.send(&input_tx, (count, next_input_height, input))
+                                {
+                                    break;
+                                }
+                                input_sync.advance_turn();
+                                count += 1;
+
+                                // If we're at the root of the tree, stop generating inputs.
+                                if is_root {
+                                    break;
+                                }
 
-                            // If we were at the last input of a layer, we keep everything but the
-                            // first input. Otherwise, we empty the batch.
-                            if is_last {
-                                batch = vec![batch[1].clone()];
+                                // If we were at the last input of a layer, we keep everything but
+                                // the first input. Otherwise, we empty the batch.
+                                if is_last {
+                                    batch = vec![batch[1].clone()];
+                                } else {
+                                    batch = Vec::new();
+                                }
                             } else {
-                                batch = Vec::new();
+                                break;
                             }
-                        } else {
-                            break;
                         }
-                    }
+                    })
                 })
             };
 
@@ -927,11 +1496,17 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
             }
             handle.join().unwrap();
 
-            let (_, _, vk, proof) = proofs_rx.lock().unwrap().recv().unwrap();
-            (vk, proof)
+            let root = watchdog.recv(&proofs_rx);
+            let result = watchdog.finish();
+            result.map(|()| root.expect("the compress pipeline should produce a root proof"))
         });
+        let (_, _, vk, proof) = root?;
+        let stats = stats.into_inner().unwrap();
+        for (height, layer) in stats.by_layer() {
+            tracing::debug!("compress layer {}: {:?}", height, layer);
+        }
 
-        Ok(ZKMReduceProof { vk, proof })
+        Ok((ZKMReduceProof { vk, proof }, stats))
     }
 
     /// Wrap a reduce proof into a STARK proven over a SNARK-friendly field.
@@ -969,17 +1544,27 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         runtime.print_stats();
         tracing::debug!("Shrink program executed successfully");
 
-        let (shrink_pk, shrink_vk) =
-            tracing::debug_span!("setup shrink").in_scope(|| self.shrink_prover.setup(&program));
+        let keys = self.shrink_keys(&input_with_merkle.shape(), &program);
+        let (shrink_pk, shrink_vk) = &*keys;
 
         // Prove the compress program.
         let mut compress_challenger = self.shrink_prover.config().challenger();
         let mut compress_proof = self
-            .shrink_prover
-            .prove(&shrink_pk, vec![runtime.record], &mut compress_challenger, opts.recursion_opts)
+            .thread_pool
+            .install(|| {
+                self.shrink_prover.prove(
+                    shrink_pk,
+                    vec![runtime.record],
+                    &mut compress_challenger,
+                    opts.recursion_opts,
+                )
+            })
             .unwrap();
 
-        Ok(ZKMReduceProof { vk: shrink_vk, proof: compress_proof.shard_proofs.pop().unwrap() })
+        Ok(ZKMReduceProof {
+            vk: shrink_vk.clone(),
+            proof: compress_proof.shard_proofs.pop().unwrap(),
+        })
     }
 
     /// Wrap a reduce proof into a STARK proven over a SNARK-friendly field.
@@ -1015,8 +1600,8 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         tracing::debug!("wrap program executed successfully");
 
         // Setup the wrap program.
-        let (wrap_pk, wrap_vk) =
-            tracing::debug_span!("setup wrap").in_scope(|| self.wrap_prover.setup(&program));
+        let keys = self.wrap_keys();
+        let (wrap_pk, wrap_vk) = &*keys;
 
         if self.wrap_vk.set(wrap_vk.clone()).is_ok() {
             tracing::debug!("wrap verifier key set");
@@ -1026,16 +1611,23 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         let mut wrap_challenger = self.wrap_prover.config().challenger();
         let time = std::time::Instant::now();
         let mut wrap_proof = self
-            .wrap_prover
-            .prove(&wrap_pk, vec![runtime.record], &mut wrap_challenger, opts.recursion_opts)
+            .thread_pool
+            .install(|| {
+                self.wrap_prover.prove(
+                    wrap_pk,
+                    vec![runtime.record],
+                    &mut wrap_challenger,
+                    opts.recursion_opts,
+                )
+            })
             .unwrap();
         let elapsed = time.elapsed();
         tracing::debug!("wrap proving time: {:?}", elapsed);
         let mut wrap_challenger = self.wrap_prover.config().challenger();
-        self.wrap_prover.machine().verify(&wrap_vk, &wrap_proof, &mut wrap_challenger).unwrap();
+        self.wrap_prover.machine().verify(wrap_vk, &wrap_proof, &mut wrap_challenger).unwrap();
         tracing::info!("wrapping successful");
 
-        Ok(ZKMReduceProof { vk: wrap_vk, proof: wrap_proof.shard_proofs.pop().unwrap() })
+        Ok(ZKMReduceProof { vk: wrap_vk.clone(), proof: wrap_proof.shard_proofs.pop().unwrap() })
     }
 
     /// Wrap the STARK proven over a SNARK-friendly field into a PLONK proof.
@@ -1229,11 +1821,13 @@ pub mod tests {
     use p3_field::PrimeField32;
 
     use shapes::ZKMProofShape;
+    use zkm_primitives::io::PublicValuesHash;
     use zkm_recursion_core::air::RecursionPublicValues;
 
     #[cfg(test)]
     use serial_test::serial;
     use utils::zkm_vkey_digest_koalabear;
+    use verify::BundleVerificationError;
     #[cfg(test)]
     use zkm_core_machine::utils::setup_logger;
 
@@ -1308,9 +1902,16 @@ pub mod tests {
 
         tracing::info!("compress");
         let compress_span = tracing::debug_span!("compress").entered();
-        let compressed_proof = prover.compress(&vk, core_proof, vec![], opts)?;
+        let num_shards = core_proof.proof.0.len();
+        let (compressed_proof, stats) =
+            prover.compress_with_stats(&vk, core_proof, vec![], opts, &Progress::default())?;
         compress_span.exit();
 
+        // Every node of the compress tree records the stats of its recursion program, starting
+        // with a node per shard proof in the first layer.
+        assert_eq!(stats.nodes.iter().filter(|(height, _, _)| *height == 0).count(), num_shards);
+        assert!(stats.nodes.iter().all(|(_, _, node)| node.cycles > 0));
+
         if verify {
             tracing::info!("verify compressed");
             prover.verify_compressed(&compressed_proof, &vk)?;
@@ -1383,7 +1984,16 @@ pub mod tests {
             prover.wrap_plonk_bn254(wrapped_bn254_proof.clone(), &artifacts_dir);
         println!("{plonk_bn254_proof:?}");
 
-        prover.verify_plonk_bn254(&plonk_bn254_proof, &vk, &public_values, &artifacts_dir)?;
+        let hash = PublicValuesHash::Sha256;
+        prover.verify_plonk_bn254(&plonk_bn254_proof, &vk, &public_values, hash, &artifacts_dir)?;
+        let plonk_vk = ZKMProver::<C>::export_plonk_vk(&artifacts_dir)?;
+        prover.verify_plonk_bn254_with_vk(
+            &plonk_bn254_proof,
+            &vk,
+            &public_values,
+            hash,
+            &plonk_vk,
+        )?;
 
         tracing::info!("generate groth16 bn254 proof");
         let artifacts_dir = try_build_groth16_bn254_artifacts_dev(
@@ -1398,8 +2008,17 @@ pub mod tests {
                 &groth16_bn254_proof,
                 &vk,
                 &public_values,
+                hash,
                 &artifacts_dir,
             )?;
+            let groth16_vk = ZKMProver::<C>::export_groth16_vk(&artifacts_dir)?;
+            prover.verify_groth16_bn254_with_vk(
+                &groth16_bn254_proof,
+                &vk,
+                &public_values,
+                hash,
+                &groth16_vk,
+            )?;
         }
 
         Ok(())
@@ -1480,7 +2099,7 @@ pub mod tests {
         let verify_reduce = prover.compress(
             &verify_vk,
             verify_proof,
-            vec![deferred_reduce_1, deferred_reduce_2.clone(), deferred_reduce_2],
+            vec![deferred_reduce_1.clone(), deferred_reduce_2.clone(), deferred_reduce_2.clone()],
             opts,
         )?;
         let reduce_pv: &RecursionPublicValues<_> =
@@ -1491,6 +2110,41 @@ pub mod tests {
         tracing::info!("verify verify program");
         prover.verify_compressed(&verify_reduce, &verify_vk)?;
 
+        tracing::info!("verify bundle");
+        let mut bundle = vec![
+            (deferred_reduce_1, keccak_vk.clone()),
+            (deferred_reduce_2.clone(), keccak_vk.clone()),
+            (deferred_reduce_2, keccak_vk.clone()),
+        ];
+        prover.verify_bundle(&verify_reduce, &verify_vk, &bundle)?;
+
+        // The deferred proofs must be given in the order they were verified in.
+        bundle.reverse();
+        assert!(matches!(
+            prover.verify_bundle(&verify_reduce, &verify_vk, &bundle),
+            Err(BundleVerificationError::DigestMismatch { num_deferred: 3 })
+        ));
+        bundle.reverse();
+
+        // Every deferred proof the outer proof verified must be given.
+        assert!(matches!(
+            prover.verify_bundle(&verify_reduce, &verify_vk, &bundle[..2]),
+            Err(BundleVerificationError::DigestMismatch { num_deferred: 2 })
+        ));
+
+        // Every deferred proof is verified against its own verifying key.
+        bundle[1].1 = verify_vk.clone();
+        assert!(matches!(
+            prover.verify_bundle(&verify_reduce, &verify_vk, &bundle),
+            Err(BundleVerificationError::Deferred { index: 1, .. })
+        ));
+
+        // The outer proof is verified against its verifying key.
+        assert!(matches!(
+            prover.verify_bundle(&verify_reduce, &keccak_vk, &bundle),
+            Err(BundleVerificationError::Outer(_))
+        ));
+
         let shrink_proof = prover.shrink(verify_reduce, opts)?;
 
         tracing::info!("verify shrink");
@@ -1568,4 +2222,173 @@ pub mod tests {
         setup_logger();
         test_e2e_with_deferred_proofs_prover::<DefaultProverComponents>(ZKMProverOpts::default())
     }
+
+    #[test]
+    #[serial]
+    #[ignore]
+    fn test_prove_core_stream() -> Result<()> {
+        setup_logger();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        let (_, pk_d, program, vk) = prover.setup(elf);
+        let opts = ZKMProverOpts::default();
+
+        let (shard_tx, shard_rx) = std::sync::mpsc::channel();
+        let (core_proof, streamed) = thread::scope(|s| {
+            let handle = s.spawn(|| shard_rx.into_iter().collect::<Vec<_>>());
+            let core_proof = prover.prove_core_stream(
+                &pk_d,
+                program,
+                &ZKMStdin::new(),
+                opts,
+                ZKMContext::default(),
+                shard_tx,
+            );
+            (core_proof, handle.join().unwrap())
+        });
+        let core_proof = core_proof?;
+
+        // Every shard is streamed exactly once, and the streamed proofs match the final proof.
+        assert_eq!(streamed.len(), core_proof.proof.0.len());
+        let mut streamed = streamed;
+        streamed.sort_by_key(|(progress, _)| progress.shard);
+        for ((progress, proof), expected) in streamed.iter().zip(core_proof.proof.0.iter()) {
+            let public_values: &PublicValues<Word<KoalaBear>, KoalaBear> =
+                expected.public_values.as_slice().borrow();
+            assert_eq!(progress.shard, public_values.shard.as_canonical_u32());
+            assert_eq!(bincode::serialize(proof)?, bincode::serialize(expected)?);
+        }
+        let mut num_proven =
+            streamed.iter().map(|(progress, _)| progress.num_proven).collect::<Vec<_>>();
+        num_proven.sort_unstable();
+        assert_eq!(num_proven, (1..=streamed.len()).collect::<Vec<_>>());
+        assert!(streamed.iter().any(|(progress, _)| progress.cycles > 0));
+
+        prover.verify(&core_proof.proof, &vk)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore]
+    fn test_prove_core_resumable() -> Result<()> {
+        setup_logger();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        let (pk, _, _, vk) = prover.setup(elf);
+        let mut opts = ZKMProverOpts::default();
+        opts.core_opts.shard_size = 1 << 12;
+        let stdin = ZKMStdin::new();
+
+        let dir = env::temp_dir().join("zkm-test-prove-core-resumable");
+        let _ = std::fs::remove_dir_all(&dir);
+        let core_proof =
+            prover.prove_core_resumable(&pk, &stdin, opts, ZKMContext::default(), &dir)?;
+        let num_shards = core_proof.proof.0.len();
+        assert!(num_shards > 1);
+
+        // Simulate an interruption by discarding the proofs of the last shards.
+        for shard in num_shards / 2 + 1..=num_shards {
+            std::fs::remove_file(dir.join(format!("shard-{shard:06}.bin")))?;
+        }
+        let resumed =
+            prover.prove_core_resumable(&pk, &stdin, opts, ZKMContext::default(), &dir)?;
+        assert_eq!(resumed.proof.0.len(), num_shards);
+        prover.verify(&resumed.proof, &vk)?;
+
+        // A different input cannot reuse the checkpoints.
+        let mut other_stdin = ZKMStdin::new();
+        other_stdin.write(&1u32);
+        assert!(prover
+            .prove_core_resumable(&pk, &other_stdin, opts, ZKMContext::default(), &dir)
+            .is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore]
+    fn test_prove_core_incremental() -> Result<()> {
+        setup_logger();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        let (pk, _, _, vk) = prover.setup(elf);
+        let mut opts = ZKMProverOpts::default();
+        opts.core_opts.shard_size = 1 << 12;
+        let stdin = ZKMStdin::new();
+
+        let dir = env::temp_dir().join("zkm-test-prove-core-incremental");
+        let _ = std::fs::remove_dir_all(&dir);
+        let num_cached = || std::fs::read_dir(&dir).unwrap().count() - 1;
+        let core_proof =
+            prover.prove_core_incremental(&pk, &stdin, opts, ZKMContext::default(), &dir)?;
+        assert_eq!(num_cached(), core_proof.proof.0.len());
+
+        // Every shard of the same execution is reused.
+        let reproven =
+            prover.prove_core_incremental(&pk, &stdin, opts, ZKMContext::default(), &dir)?;
+        assert_eq!(num_cached(), core_proof.proof.0.len());
+        prover.verify(&reproven.proof, &vk)?;
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore]
+    fn test_aggregate() -> Result<()> {
+        setup_logger();
+        let prover = ZKMProver::<DefaultProverComponents>::new();
+        let opts = ZKMProverOpts::default();
+
+        let mut programs = Vec::new();
+        let mut proofs = Vec::new();
+        for elf in [test_artifacts::FIBONACCI_ELF, test_artifacts::HELLO_WORLD_ELF] {
+            let (_, pk_d, program, vk) = prover.setup(elf);
+            let core_proof =
+                prover.prove_core(&pk_d, program, &ZKMStdin::new(), opts, Default::default())?;
+            programs.push((vk.clone(), core_proof.public_values.clone()));
+            proofs.push((prover.compress(&vk, core_proof, vec![], opts)?, vk));
+        }
+
+        let aggregated = prover.aggregate(proofs, opts)?;
+        prover.verify_aggregated(&aggregated, &programs)?;
+
+        // The proof commits to the order of the programs.
+        programs.reverse();
+        assert!(prover.verify_aggregated(&aggregated, &programs).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    #[ignore]
+    fn test_prove_deterministic() -> Result<()> {
+        setup_logger();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let prover = ZKMProver::<DefaultProverComponents>::new()
+            .with_thread_pool(ProverThreadPool::single_threaded());
+        let (_, pk_d, program, vk) = prover.setup(elf);
+        let opts = ZKMProverOpts::deterministic();
+
+        let mut proofs = Vec::new();
+        for _ in 0..2 {
+            let core_proof = prover.prove_core(
+                &pk_d,
+                program.clone(),
+                &ZKMStdin::new(),
+                opts,
+                Default::default(),
+            )?;
+            let core_bytes = bincode::serialize(&core_proof.proof)?;
+            let compressed = prover.compress(&vk, core_proof, vec![], opts)?;
+            proofs.push((core_bytes, bincode::serialize(&compressed)?));
+        }
+        assert_eq!(proofs[0].0, proofs[1].0);
+        assert_eq!(proofs[0].1, proofs[1].1);
+        Ok(())
+    }
 }
@@ -0,0 +1,167 @@
+//! A content-addressed on-disk cache of the compiled recursion programs lifting core shards.
+//!
+//! [crate::ZKMProver::recursion_program] keeps the programs of the recent shapes in memory, but
+//! every new process compiles them again. With a [`ProgramCache`], a program compiled once is
+//! stored under the hash of its shape and of the circuit version, and loaded by the next processes
+//! instead of being compiled. Several processes may share a cache directory: entries are written
+//! to a temporary file and renamed into place, so a reader never sees a partial entry, and two
+//! processes storing the same program write the same bytes.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use p3_koala_bear::KoalaBear;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zkm_recursion_circuit::machine::ZKMRecursionShape;
+use zkm_recursion_core::RecursionProgram;
+
+use crate::ZKM_CIRCUIT_VERSION;
+
+/// Identifies a compiled recursion program.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ProgramKey {
+    version: String,
+    shape: ZKMRecursionShape,
+    fixed_shape: bool,
+}
+
+impl ProgramKey {
+    fn new(shape: &ZKMRecursionShape, fixed_shape: bool) -> Self {
+        Self { version: ZKM_CIRCUIT_VERSION.to_string(), shape: shape.clone(), fixed_shape }
+    }
+}
+
+/// A directory of compiled recursion programs, shared by prover processes.
+#[derive(Debug, Clone)]
+pub struct ProgramCache {
+    dir: PathBuf,
+}
+
+impl ProgramCache {
+    /// Opens the cache in `dir`, creating the directory if it does not exist.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Opens the cache in the directory set with `RECURSION_PROGRAM_CACHE_DIR`, if any.
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var_os("RECURSION_PROGRAM_CACHE_DIR")?;
+        Self::new(&dir)
+            .inspect_err(|e| tracing::warn!("failed to open the recursion program cache: {}", e))
+            .ok()
+    }
+
+    /// The directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Loads the program of `shape`, compiled with its shape fixed if `fixed_shape` is set.
+    ///
+    /// Returns `None` if the program is not in the cache, or if its entry cannot be read.
+    pub fn load(
+        &self,
+        shape: &ZKMRecursionShape,
+        fixed_shape: bool,
+    ) -> Option<RecursionProgram<KoalaBear>> {
+        load_entry(&self.dir, &ProgramKey::new(shape, fixed_shape))
+    }
+
+    /// Stores the program of `shape`, replacing any existing entry.
+    pub fn store(
+        &self,
+        shape: &ZKMRecursionShape,
+        fixed_shape: bool,
+        program: &RecursionProgram<KoalaBear>,
+    ) -> io::Result<()> {
+        store_entry(&self.dir, &ProgramKey::new(shape, fixed_shape), program)
+    }
+}
+
+/// Loads the value stored under `key` in the cache directory `dir`.
+///
+/// Returns `None` if there is no entry for `key`, or if it cannot be read.
+pub(crate) fn load_entry<K, V>(dir: &Path, key: &K) -> Option<V>
+where
+    K: Serialize + DeserializeOwned + PartialEq,
+    V: DeserializeOwned,
+{
+    let file = File::open(entry_path(dir, key)).ok()?;
+    match bincode::deserialize_from::<_, (K, V)>(BufReader::new(file)) {
+        Ok((stored, value)) if stored == *key => Some(value),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!("failed to read a cache entry: {}", e);
+            None
+        }
+    }
+}
+
+/// Stores `value` under `key` in the cache directory `dir`, replacing any existing entry.
+///
+/// The key is stored along with the value, so that an entry is only used for the exact key it was
+/// stored for.
+pub(crate) fn store_entry<K: Serialize, V: Serialize>(
+    dir: &Path,
+    key: &K,
+    value: &V,
+) -> io::Result<()> {
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    let mut writer = BufWriter::new(file.as_file_mut());
+    bincode::serialize_into(&mut writer, &(key, value)).map_err(io::Error::other)?;
+    writer.flush()?;
+    drop(writer);
+    file.persist(entry_path(dir, key)).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// The path of the entry of `key`, named after the hash of the key.
+fn entry_path<K: Serialize>(dir: &Path, key: &K) -> PathBuf {
+    let bytes = bincode::serialize(key).expect("failed to serialize the cache key");
+    let digest = Sha256::digest(bytes);
+    let name = digest.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    dir.join(format!("{name}.bin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use zkm_stark::shape::OrderedShape;
+
+    use super::*;
+
+    #[test]
+    fn test_program_cache() {
+        let dir = env::temp_dir().join("zkm-test-program-cache");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ProgramCache::new(&dir).unwrap();
+        let shape = ZKMRecursionShape {
+            proof_shapes: vec![OrderedShape { inner: vec![("Cpu".to_string(), 20)] }],
+            is_complete: false,
+        };
+        let program = RecursionProgram::<KoalaBear> { total_memory: 42, ..Default::default() };
+
+        assert!(cache.load(&shape, true).is_none());
+        cache.store(&shape, true, &program).unwrap();
+        assert_eq!(cache.load(&shape, true).unwrap().total_memory, 42);
+
+        // The entries of other keys are distinct.
+        assert!(cache.load(&shape, false).is_none());
+        let other = ZKMRecursionShape { is_complete: true, ..shape.clone() };
+        assert!(cache.load(&other, true).is_none());
+
+        // A corrupted entry is a miss.
+        for entry in fs::read_dir(&dir).unwrap() {
+            fs::write(entry.unwrap().path(), b"corrupted").unwrap();
+        }
+        assert!(cache.load(&shape, true).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,470 @@
+//! A stable, versioned wire format for compressed proofs.
+//!
+//! Unlike the bincode encoding of [ZKMReduceProof], which follows the layout of the Rust types,
+//! this format is specified independently of the implementation, so verifiers and aggregators
+//! written in other languages can consume compressed proofs across releases. The specification
+//! is in `docs/src/dev/proof-format.md`, and `test_vectors/` holds an encoded proof together with
+//! its decoded values.
+//!
+//! All integers are little-endian `u32`s. A field element is its canonical value as a `u32`, and
+//! an extension field element is its 4 base field coefficients, lowest degree first. Lists are
+//! prefixed by their length, and strings by their length in bytes.
+
+use std::collections::HashMap;
+
+use p3_commit::TwoAdicMultiplicativeCoset;
+use p3_field::{FieldAlgebra, FieldExtensionAlgebra, PrimeField32};
+use p3_koala_bear::KoalaBear;
+use p3_matrix::Dimensions;
+use thiserror::Error;
+use zkm_core_machine::reduce::ZKMReduceProof;
+use zkm_stark::{
+    septic_curve::SepticCurve, septic_digest::SepticDigest, septic_extension::SepticExtension,
+    AirOpenedValues, ChipOpenedValues, InnerBatchOpening, InnerChallenge, InnerCommitPhaseStep,
+    InnerDigest, InnerDigestHash, InnerFriProof, InnerQueryProof, ShardCommitment,
+    ShardOpenedValues, ShardProof, StarkVerifyingKey, DIGEST_SIZE,
+};
+
+use crate::InnerSC;
+
+/// The magic bytes starting an encoded compressed proof.
+pub const PROOF_FORMAT_MAGIC: [u8; 4] = *b"ZKMR";
+
+/// The version of the wire format written by [encode_reduce_proof].
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
+/// An error returned when decoding a compressed proof.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ProofFormatError {
+    #[error("not an encoded Ziren compressed proof")]
+    InvalidMagic,
+    #[error("unsupported proof format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("unexpected end of input at offset {0}")]
+    UnexpectedEnd(usize),
+    #[error("length {len} at offset {offset} exceeds the remaining input")]
+    InvalidLength { len: u32, offset: usize },
+    #[error("non-canonical field element {value:#x} at offset {offset}")]
+    NonCanonical { value: u32, offset: usize },
+    #[error("invalid utf-8 string at offset {0}")]
+    InvalidString(usize),
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+}
+
+/// Encodes a compressed proof in the wire format.
+pub fn encode_reduce_proof(proof: &ZKMReduceProof<InnerSC>) -> Vec<u8> {
+    let mut w = Writer(Vec::new());
+    w.0.extend_from_slice(&PROOF_FORMAT_MAGIC);
+    w.u32(PROOF_FORMAT_VERSION);
+    w.vk(&proof.vk);
+    w.shard_proof(&proof.proof);
+    w.0
+}
+
+/// Decodes a compressed proof from the wire format.
+pub fn decode_reduce_proof(bytes: &[u8]) -> Result<ZKMReduceProof<InnerSC>, ProofFormatError> {
+    if bytes.get(..PROOF_FORMAT_MAGIC.len()) != Some(&PROOF_FORMAT_MAGIC[..]) {
+        return Err(ProofFormatError::InvalidMagic);
+    }
+    let mut r = Reader { bytes, offset: PROOF_FORMAT_MAGIC.len() };
+    let version = r.u32()?;
+    if version != PROOF_FORMAT_VERSION {
+        return Err(ProofFormatError::UnsupportedVersion(version));
+    }
+    let vk = r.vk()?;
+    let proof = r.shard_proof()?;
+    if r.offset != bytes.len() {
+        return Err(ProofFormatError::TrailingBytes(bytes.len() - r.offset));
+    }
+    Ok(ZKMReduceProof { vk, proof })
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u32(u32::try_from(value).expect("value exceeds u32"));
+    }
+
+    fn felt(&mut self, value: KoalaBear) {
+        self.u32(value.as_canonical_u32());
+    }
+
+    fn ext(&mut self, value: InnerChallenge) {
+        value.as_base_slice().iter().for_each(|&c| self.felt(c));
+    }
+
+    fn digest(&mut self, value: &[KoalaBear]) {
+        value.iter().for_each(|&c| self.felt(c));
+    }
+
+    fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn list<T>(&mut self, items: &[T], mut f: impl FnMut(&mut Self, &T)) {
+        self.usize(items.len());
+        items.iter().for_each(|item| f(self, item));
+    }
+
+    fn septic_digest(&mut self, value: &SepticDigest<KoalaBear>) {
+        value.0.x.0.iter().chain(&value.0.y.0).for_each(|&c| self.felt(c));
+    }
+
+    fn chip_ordering(&mut self, ordering: &HashMap<String, usize>) {
+        let mut entries = ordering.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(name, &index)| (index, *name));
+        self.list(&entries, |w, (name, &index)| {
+            w.string(name);
+            w.usize(index);
+        });
+    }
+
+    fn vk(&mut self, vk: &StarkVerifyingKey<InnerSC>) {
+        self.digest(vk.commit.as_ref());
+        self.felt(vk.pc_start);
+        self.septic_digest(&vk.initial_global_cumulative_sum);
+        self.list(&vk.chip_information, |w, (name, domain, dimensions)| {
+            w.string(name);
+            w.usize(domain.log_n);
+            w.felt(domain.shift);
+            w.usize(dimensions.width);
+            w.usize(dimensions.height);
+        });
+        self.chip_ordering(&vk.chip_ordering);
+    }
+
+    fn air_opened_values(&mut self, values: &AirOpenedValues<InnerChallenge>) {
+        self.list(&values.local, |w, &v| w.ext(v));
+        self.list(&values.next, |w, &v| w.ext(v));
+    }
+
+    fn shard_proof(&mut self, proof: &ShardProof<InnerSC>) {
+        self.digest(proof.commitment.main_commit.as_ref());
+        self.digest(proof.commitment.permutation_commit.as_ref());
+        self.digest(proof.commitment.quotient_commit.as_ref());
+
+        self.list(&proof.opened_values.chips, |w, chip| {
+            w.air_opened_values(&chip.preprocessed);
+            w.air_opened_values(&chip.main);
+            w.air_opened_values(&chip.permutation);
+            w.list(&chip.quotient, |w, chunk| w.list(chunk, |w, &v| w.ext(v)));
+            w.septic_digest(&chip.global_cumulative_sum);
+            w.ext(chip.local_cumulative_sum);
+            w.usize(chip.log_degree);
+        });
+
+        let fri_proof = &proof.opening_proof;
+        self.list(&fri_proof.commit_phase_commits, |w, commit| w.digest(commit.as_ref()));
+        self.list(&fri_proof.query_proofs, |w, query| {
+            w.list(&query.input_proof, |w, batch| {
+                w.list(&batch.opened_values, |w, values| w.list(values, |w, &v| w.felt(v)));
+                w.list(&batch.opening_proof, |w, digest| w.digest(digest));
+            });
+            w.list(&query.commit_phase_openings, |w, step| {
+                w.ext(step.sibling_value);
+                w.list(&step.opening_proof, |w, digest| w.digest(digest));
+            });
+        });
+        self.ext(fri_proof.final_poly);
+        self.felt(fri_proof.pow_witness);
+
+        self.chip_ordering(&proof.chip_ordering);
+        self.list(&proof.public_values, |w, &v| w.felt(v));
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], ProofFormatError> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or(ProofFormatError::UnexpectedEnd(self.offset))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, ProofFormatError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, ProofFormatError> {
+        Ok(self.u32()? as usize)
+    }
+
+    /// Reads a length prefix, which can't exceed the remaining input since every item takes at
+    /// least a byte.
+    fn len(&mut self) -> Result<usize, ProofFormatError> {
+        let offset = self.offset;
+        let len = self.u32()?;
+        if len as usize > self.bytes.len() - self.offset {
+            return Err(ProofFormatError::InvalidLength { len, offset });
+        }
+        Ok(len as usize)
+    }
+
+    fn felt(&mut self) -> Result<KoalaBear, ProofFormatError> {
+        let offset = self.offset;
+        let value = self.u32()?;
+        if value >= KoalaBear::ORDER_U32 {
+            return Err(ProofFormatError::NonCanonical { value, offset });
+        }
+        Ok(KoalaBear::from_canonical_u32(value))
+    }
+
+    fn felts<const N: usize>(&mut self) -> Result<[KoalaBear; N], ProofFormatError> {
+        let mut values = [KoalaBear::ZERO; N];
+        for value in values.iter_mut() {
+            *value = self.felt()?;
+        }
+        Ok(values)
+    }
+
+    fn ext(&mut self) -> Result<InnerChallenge, ProofFormatError> {
+        Ok(InnerChallenge::from_base_slice(&self.felts::<4>()?))
+    }
+
+    fn digest(&mut self) -> Result<InnerDigest, ProofFormatError> {
+        self.felts::<DIGEST_SIZE>()
+    }
+
+    fn commit(&mut self) -> Result<InnerDigestHash, ProofFormatError> {
+        Ok(self.digest()?.into())
+    }
+
+    fn string(&mut self) -> Result<String, ProofFormatError> {
+        let len = self.len()?;
+        let offset = self.offset;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| ProofFormatError::InvalidString(offset))
+    }
+
+    fn list<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, ProofFormatError>,
+    ) -> Result<Vec<T>, ProofFormatError> {
+        let len = self.len()?;
+        (0..len).map(|_| f(self)).collect()
+    }
+
+    fn septic_digest(&mut self) -> Result<SepticDigest<KoalaBear>, ProofFormatError> {
+        let x = self.felts::<7>()?;
+        let y = self.felts::<7>()?;
+        Ok(SepticDigest(SepticCurve { x: SepticExtension(x), y: SepticExtension(y) }))
+    }
+
+    fn chip_ordering(&mut self) -> Result<HashMap<String, usize>, ProofFormatError> {
+        Ok(self.list(|r| Ok((r.string()?, r.usize()?)))?.into_iter().collect())
+    }
+
+    fn vk(&mut self) -> Result<StarkVerifyingKey<InnerSC>, ProofFormatError> {
+        let commit = self.commit()?;
+        let pc_start = self.felt()?;
+        let initial_global_cumulative_sum = self.septic_digest()?;
+        let chip_information = self.list(|r| {
+            let name = r.string()?;
+            let log_n = r.usize()?;
+            let shift = r.felt()?;
+            let width = r.usize()?;
+            let height = r.usize()?;
+            Ok((name, TwoAdicMultiplicativeCoset { log_n, shift }, Dimensions { width, height }))
+        })?;
+        let chip_ordering = self.chip_ordering()?;
+        Ok(StarkVerifyingKey {
+            commit,
+            pc_start,
+            initial_global_cumulative_sum,
+            chip_information,
+            chip_ordering,
+        })
+    }
+
+    fn air_opened_values(&mut self) -> Result<AirOpenedValues<InnerChallenge>, ProofFormatError> {
+        let local = self.list(Self::ext)?;
+        let next = self.list(Self::ext)?;
+        Ok(AirOpenedValues { local, next })
+    }
+
+    fn shard_proof(&mut self) -> Result<ShardProof<InnerSC>, ProofFormatError> {
+        let commitment = ShardCommitment {
+            main_commit: self.commit()?,
+            permutation_commit: self.commit()?,
+            quotient_commit: self.commit()?,
+        };
+
+        let chips = self.list(|r| {
+            Ok(ChipOpenedValues {
+                preprocessed: r.air_opened_values()?,
+                main: r.air_opened_values()?,
+                permutation: r.air_opened_values()?,
+                quotient: r.list(|r| r.list(Self::ext))?,
+                global_cumulative_sum: r.septic_digest()?,
+                local_cumulative_sum: r.ext()?,
+                log_degree: r.usize()?,
+            })
+        })?;
+
+        let commit_phase_commits = self.list(Self::commit)?;
+        let query_proofs = self.list(|r| {
+            let input_proof = r.list(|r| {
+                Ok(InnerBatchOpening {
+                    opened_values: r.list(|r| r.list(Self::felt))?,
+                    opening_proof: r.list(Self::digest)?,
+                })
+            })?;
+            let commit_phase_openings = r.list(|r| {
+                Ok(InnerCommitPhaseStep {
+                    sibling_value: r.ext()?,
+                    opening_proof: r.list(Self::digest)?,
+                })
+            })?;
+            Ok(InnerQueryProof { input_proof, commit_phase_openings })
+        })?;
+        let final_poly = self.ext()?;
+        let pow_witness = self.felt()?;
+        let opening_proof =
+            InnerFriProof { commit_phase_commits, query_proofs, final_poly, pow_witness };
+
+        let chip_ordering = self.chip_ordering()?;
+        let public_values = self.list(Self::felt)?;
+
+        Ok(ShardProof {
+            commitment,
+            opened_values: ShardOpenedValues { chips },
+            opening_proof,
+            chip_ordering,
+            public_values,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The proof encoded in `test_vectors/reduce_proof_v1.bin`, whose values are listed in
+    /// `test_vectors/reduce_proof_v1.json`. It is not a valid proof, only a fixture of the format.
+    fn test_vector_proof() -> ZKMReduceProof<InnerSC> {
+        let felts = |start: u32, len: u32| -> Vec<KoalaBear> {
+            (start..start + len).map(KoalaBear::from_canonical_u32).collect()
+        };
+        let digest = |start: u32| -> InnerDigest { felts(start, 8).try_into().unwrap() };
+        let ext = |start: u32| InnerChallenge::from_base_slice(&felts(start, 4));
+        let septic = |x: u32, y: u32| {
+            SepticDigest(SepticCurve {
+                x: SepticExtension(felts(x, 7).try_into().unwrap()),
+                y: SepticExtension(felts(y, 7).try_into().unwrap()),
+            })
+        };
+        let ordering = HashMap::from([("Program".to_string(), 0)]);
+
+        let vk = StarkVerifyingKey {
+            commit: digest(1).into(),
+            pc_start: KoalaBear::from_canonical_u32(4096),
+            initial_global_cumulative_sum: septic(10, 20),
+            chip_information: vec![(
+                "Program".to_string(),
+                TwoAdicMultiplicativeCoset { log_n: 3, shift: KoalaBear::from_canonical_u32(3) },
+                Dimensions { width: 4, height: 8 },
+            )],
+            chip_ordering: ordering.clone(),
+        };
+
+        let chip = ChipOpenedValues {
+            preprocessed: AirOpenedValues { local: vec![ext(1)], next: vec![ext(5)] },
+            main: AirOpenedValues { local: vec![ext(9)], next: vec![ext(13)] },
+            permutation: AirOpenedValues { local: vec![], next: vec![] },
+            quotient: vec![vec![ext(17)]],
+            global_cumulative_sum: septic(30, 40),
+            local_cumulative_sum: ext(50),
+            log_degree: 3,
+        };
+        let query_proof = InnerQueryProof {
+            input_proof: vec![InnerBatchOpening {
+                opened_values: vec![felts(500, 2)],
+                opening_proof: vec![digest(600)],
+            }],
+            commit_phase_openings: vec![InnerCommitPhaseStep {
+                sibling_value: ext(700),
+                opening_proof: vec![digest(800)],
+            }],
+        };
+        let proof = ShardProof {
+            commitment: ShardCommitment {
+                main_commit: digest(100).into(),
+                permutation_commit: digest(200).into(),
+                quotient_commit: digest(300).into(),
+            },
+            opened_values: ShardOpenedValues { chips: vec![chip] },
+            opening_proof: InnerFriProof {
+                commit_phase_commits: vec![digest(400).into()],
+                query_proofs: vec![query_proof],
+                final_poly: ext(900),
+                pow_witness: KoalaBear::from_canonical_u32(999),
+            },
+            chip_ordering: ordering,
+            public_values: vec![
+                KoalaBear::ONE,
+                KoalaBear::TWO,
+                KoalaBear::from_canonical_u32(KoalaBear::ORDER_U32 - 1),
+            ],
+        };
+
+        ZKMReduceProof { vk, proof }
+    }
+
+    const TEST_VECTOR: &[u8] = include_bytes!("../test_vectors/reduce_proof_v1.bin");
+
+    #[test]
+    fn test_encode_test_vector() {
+        assert_eq!(encode_reduce_proof(&test_vector_proof()), TEST_VECTOR);
+    }
+
+    #[test]
+    fn test_decode_test_vector() {
+        let proof = decode_reduce_proof(TEST_VECTOR).unwrap();
+        assert_eq!(encode_reduce_proof(&proof), TEST_VECTOR);
+        assert_eq!(proof.vk.chip_information[0].0, "Program");
+        assert_eq!(proof.proof.public_values[2].as_canonical_u32(), KoalaBear::ORDER_U32 - 1);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(decode_reduce_proof(b"ZKMX").unwrap_err(), ProofFormatError::InvalidMagic);
+
+        let mut bytes = TEST_VECTOR.to_vec();
+        bytes[4] = 2;
+        assert_eq!(
+            decode_reduce_proof(&bytes).unwrap_err(),
+            ProofFormatError::UnsupportedVersion(2)
+        );
+
+        let bytes = &TEST_VECTOR[..TEST_VECTOR.len() - 1];
+        assert!(matches!(
+            decode_reduce_proof(bytes).unwrap_err(),
+            ProofFormatError::UnexpectedEnd(_)
+        ));
+
+        // The first element of the vk commitment, right after the version.
+        let mut bytes = TEST_VECTOR.to_vec();
+        bytes[8..12].copy_from_slice(&KoalaBear::ORDER_U32.to_le_bytes());
+        assert_eq!(
+            decode_reduce_proof(&bytes).unwrap_err(),
+            ProofFormatError::NonCanonical { value: KoalaBear::ORDER_U32, offset: 8 }
+        );
+
+        let mut bytes = TEST_VECTOR.to_vec();
+        bytes.push(0);
+        assert_eq!(decode_reduce_proof(&bytes).unwrap_err(), ProofFormatError::TrailingBytes(1));
+    }
+}
@@ -3,8 +3,11 @@ use std::{
     fs::File,
     hash::{DefaultHasher, Hash, Hasher},
     panic::{catch_unwind, AssertUnwindSafe},
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use eyre::Result;
@@ -13,6 +16,7 @@ use thiserror::Error;
 use p3_field::FieldAlgebra;
 use p3_koala_bear::KoalaBear;
 use serde::{Deserialize, Serialize};
+use zkm_core_executor::{OpcodeSample, Program};
 use zkm_core_machine::shape::CoreShapeConfig;
 use zkm_recursion_circuit::machine::{
     ZKMCompressWithVKeyWitnessValues, ZKMCompressWithVkeyShape, ZKMDeferredShape,
@@ -56,6 +60,20 @@ pub enum VkBuildError {
     IO(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     Bincode(#[from] bincode::Error),
+    #[error("Program generation failed for the shapes {0:?}")]
+    FailedShapes(Vec<usize>),
+}
+
+/// The progress of [build_vk_map], reported every time the verifying key of a shape is computed
+/// or its program fails to build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VkMapProgress {
+    /// The number of shapes whose verifying key was computed.
+    pub done: usize,
+    /// The number of shapes whose program failed to build.
+    pub failed: usize,
+    /// The number of shapes to build.
+    pub total: usize,
 }
 
 pub fn check_shapes<C: ZKMProverComponents>(
@@ -131,12 +149,19 @@ pub fn check_shapes<C: ZKMProverComponents>(
     compress_ok
 }
 
+/// Builds the verifying keys of the recursion programs of every allowed shape, or of the shapes of
+/// `indices` only, returning them along with the indices of the shapes whose program failed to
+/// build and the height of the Merkle tree of the keys.
+///
+/// The programs are compiled by `num_compiler_workers` threads and set up by `num_setup_workers`
+/// threads, and `progress` is called as the keys are computed.
 pub fn build_vk_map<C: ZKMProverComponents>(
     reduce_batch_size: usize,
     dummy: bool,
     num_compiler_workers: usize,
     num_setup_workers: usize,
     indices: Option<Vec<usize>>,
+    progress: impl Fn(VkMapProgress) + Sync,
 ) -> (BTreeSet<[KoalaBear; DIGEST_SIZE]>, Vec<usize>, usize) {
     let mut prover = ZKMProver::<C>::new();
     prover.vk_verification = !dummy;
@@ -155,6 +180,7 @@ pub fn build_vk_map<C: ZKMProverComponents>(
         .into_keys()
         .collect::<BTreeSet<_>>();
         let height = dummy_set.len().next_power_of_two().ilog2() as usize;
+        progress(VkMapProgress { done: dummy_set.len(), failed: 0, total: dummy_set.len() });
         (dummy_set, vec![], height)
     } else {
         let (vk_tx, vk_rx) = std::sync::mpsc::channel();
@@ -175,6 +201,9 @@ pub fn build_vk_map<C: ZKMProverComponents>(
 
         let height = num_shapes.next_power_of_two().ilog2() as usize;
         let chunk_size = indices_set.as_ref().map(|indices| indices.len()).unwrap_or(num_shapes);
+        let done = AtomicUsize::new(0);
+        let failed = AtomicUsize::new(0);
+        let progress = &progress;
 
         std::thread::scope(|s| {
             // Initialize compiler workers.
@@ -183,6 +212,7 @@ pub fn build_vk_map<C: ZKMProverComponents>(
                 let shape_rx = &shape_rx;
                 let prover = &prover;
                 let panic_tx = panic_tx.clone();
+                let (done, failed) = (&done, &failed);
                 s.spawn(move || {
                     while let Ok((i, shape)) = shape_rx.lock().unwrap().recv() {
                         tracing::info!("shape {i} is {shape:?}");
@@ -200,6 +230,11 @@ pub fn build_vk_map<C: ZKMProverComponents>(
                                     e
                                 );
                                 panic_tx.send(i).unwrap();
+                                progress(VkMapProgress {
+                                    done: done.load(Ordering::SeqCst),
+                                    failed: failed.fetch_add(1, Ordering::SeqCst) + 1,
+                                    total: chunk_size,
+                                });
                             }
                         }
                     }
@@ -211,8 +246,8 @@ pub fn build_vk_map<C: ZKMProverComponents>(
                 let vk_tx = vk_tx.clone();
                 let program_rx = &program_rx;
                 let prover = &prover;
+                let (done, failed) = (&done, &failed);
                 s.spawn(move || {
-                    let mut done = 0;
                     while let Ok((i, program, is_shrink)) = program_rx.lock().unwrap().recv() {
                         let vk = tracing::debug_span!("setup for program {}", i).in_scope(|| {
                             if is_shrink {
@@ -221,7 +256,7 @@ pub fn build_vk_map<C: ZKMProverComponents>(
                                 prover.compress_prover.setup(&program).1
                             }
                         });
-                        done += 1;
+                        let done = done.fetch_add(1, Ordering::SeqCst) + 1;
 
                         let vk_digest = vk.hash_koalabear();
                         tracing::info!(
@@ -231,6 +266,11 @@ pub fn build_vk_map<C: ZKMProverComponents>(
                             done * 100 / chunk_size
                         );
                         vk_tx.send(vk_digest).unwrap();
+                        progress(VkMapProgress {
+                            done,
+                            failed: failed.load(Ordering::SeqCst),
+                            total: chunk_size,
+                        });
                     }
                 });
             }
@@ -291,11 +331,59 @@ pub fn build_vk_map_to_file<C: ZKMProverComponents>(
         num_compiler_workers,
         num_setup_workers,
         range_start.and_then(|start| range_end.map(|end| (start..end).collect())),
+        |_| {},
     );
 
-    let vk_map = vk_set.into_iter().enumerate().map(|(i, vk)| (vk, i)).collect::<BTreeMap<_, _>>();
-
     tracing::info!("Save the vk set to file");
+    write_vk_map(&build_dir, vk_set, dummy)
+}
+
+/// Regenerates `vk_map.bin` and `dummy_vk_map.bin` in `build_dir` from every allowed shape, e.g.
+/// after adding shapes, see [build_vk_map].
+///
+/// Neither file is written if the program of a shape fails to build, so that an incomplete map
+/// never replaces the previous one.
+pub fn build_vk_maps<C: ZKMProverComponents>(
+    build_dir: PathBuf,
+    reduce_batch_size: usize,
+    num_compiler_workers: usize,
+    num_setup_workers: usize,
+    progress: impl Fn(VkMapProgress) + Sync,
+) -> Result<(), VkBuildError> {
+    std::fs::create_dir_all(&build_dir)?;
+
+    let (vk_set, panic_indices, _) = build_vk_map::<C>(
+        reduce_batch_size,
+        false,
+        num_compiler_workers,
+        num_setup_workers,
+        None,
+        &progress,
+    );
+    if !panic_indices.is_empty() {
+        return Err(VkBuildError::FailedShapes(panic_indices));
+    }
+    let (dummy_vk_set, _, _) = build_vk_map::<C>(
+        reduce_batch_size,
+        true,
+        num_compiler_workers,
+        num_setup_workers,
+        None,
+        |_| {},
+    );
+
+    write_vk_map(&build_dir, vk_set, false)?;
+    write_vk_map(&build_dir, dummy_vk_set, true)
+}
+
+/// Writes the vk map of `vk_set` to `vk_map.bin`, or `dummy_vk_map.bin` if `dummy`, in
+/// `build_dir`.
+fn write_vk_map(
+    build_dir: &Path,
+    vk_set: BTreeSet<[KoalaBear; DIGEST_SIZE]>,
+    dummy: bool,
+) -> Result<(), VkBuildError> {
+    let vk_map = vk_set.into_iter().enumerate().map(|(i, vk)| (vk, i)).collect::<BTreeMap<_, _>>();
     let mut file = if dummy {
         File::create(build_dir.join("dummy_vk_map.bin"))?
     } else {
@@ -428,11 +516,47 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
             }
         }
     }
+
+    /// Compiles the recursion programs of the core shard shapes predicted from an opcode sample
+    /// of the execution of `program`, so they are cached by the time the shards are lifted.
+    ///
+    /// Two shapes are predicted: one of a shard of `shard_size` cycles with the opcode mix of the
+    /// sample, and one of a shard as short as the sample, for programs halting within it. Returns
+    /// the number of distinct predicted shapes.
+    pub fn prewarm_recursion_programs(
+        &self,
+        program: &Program,
+        sample: &OpcodeSample,
+        shard_size: usize,
+    ) -> usize {
+        let (Some(config), Some(preprocessed_shape)) =
+            (&self.core_shape_config, &program.preprocessed_shape)
+        else {
+            return 0;
+        };
+        let full_shard_scale = (shard_size as u64 / sample.cycles.max(1)).max(1);
+        let shapes = [full_shard_scale, 1]
+            .into_iter()
+            .filter_map(|scale| {
+                let heights = sample
+                    .event_counts
+                    .iter()
+                    .map(|(air, &count)| (air, (count * scale) as usize))
+                    .collect::<Vec<_>>();
+                config.predict_core_shape(preprocessed_shape, &heights)
+            })
+            .collect::<BTreeSet<_>>();
+        for shape in &shapes {
+            self.program_from_shape(ZKMCompressProgramShape::Recursion(shape.clone().into()), None);
+        }
+        shapes.len()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{components::DefaultProverComponents, REDUCE_BATCH_SIZE};
 
     #[test]
     #[ignore]
@@ -446,4 +570,48 @@ mod tests {
 
         println!("Number of compress shapes: {}", all_shapes.len());
     }
+
+    #[test]
+    fn test_write_vk_map() {
+        let build_dir = std::env::temp_dir().join("zkm-test-write-vk-map");
+        std::fs::create_dir_all(&build_dir).unwrap();
+        let vk_set = BTreeSet::from([[KoalaBear::TWO; DIGEST_SIZE], [KoalaBear::ONE; DIGEST_SIZE]]);
+        write_vk_map(&build_dir, vk_set.clone(), false).unwrap();
+        write_vk_map(&build_dir, BTreeSet::new(), true).unwrap();
+
+        // The keys are indexed in their order, which is the order of the leaves of the Merkle tree.
+        let read = |name: &str| -> BTreeMap<[KoalaBear; DIGEST_SIZE], usize> {
+            bincode::deserialize(&std::fs::read(build_dir.join(name)).unwrap()).unwrap()
+        };
+        assert_eq!(
+            read("vk_map.bin"),
+            BTreeMap::from([
+                ([KoalaBear::ONE; DIGEST_SIZE], 0),
+                ([KoalaBear::TWO; DIGEST_SIZE], 1)
+            ])
+        );
+        assert!(read("dummy_vk_map.bin").is_empty());
+
+        std::fs::remove_dir_all(&build_dir).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_build_vk_map_progress() {
+        let reports = Mutex::new(Vec::new());
+        let (vk_set, failed, _) = build_vk_map::<DefaultProverComponents>(
+            REDUCE_BATCH_SIZE,
+            false,
+            1,
+            1,
+            Some(vec![0, 1]),
+            |progress| reports.lock().unwrap().push(progress),
+        );
+        assert!(failed.is_empty());
+        assert_eq!(vk_set.len(), 2);
+
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports.last(), Some(&VkMapProgress { done: 2, failed: 0, total: 2 }));
+    }
 }
@@ -1,4 +1,4 @@
-use std::{fs::File, path::Path};
+use std::{fs::File, path::Path, time::Duration};
 
 use anyhow::Result;
 use clap::ValueEnum;
@@ -7,7 +7,13 @@ use p3_commit::{Pcs, TwoAdicMultiplicativeCoset};
 use p3_field::{FieldAlgebra, PrimeField, PrimeField32, TwoAdicField};
 use p3_koala_bear::KoalaBear;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use zkm_core_machine::{io::ZKMStdin, reduce::ZKMReduceProof};
+use zkm_core_machine::{
+    io::{
+        deserialize_from_limited, open_limited, DeserializeError, LegacyZKMStdin, ZKMStdin,
+        MAX_PROOF_SIZE,
+    },
+    reduce::ZKMReduceProof,
+};
 use zkm_primitives::{io::ZKMPublicValues, poseidon2_hash};
 
 use zkm_recursion_circuit::machine::{
@@ -33,6 +39,23 @@ pub struct ZKMProvingKey {
     pub vk: ZKMVerifyingKey,
 }
 
+/// How the public values of a reduce proof are exposed as public inputs, see
+/// [crate::utils::zkm_public_inputs].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PublicInputsMode {
+    /// The verifying key digest and the committed values digest, each packed into one BN254
+    /// element: the public inputs of the Plonk and Groth16 proofs.
+    #[default]
+    Bn254Digest,
+    /// The 8 KoalaBear elements of the verifying key digest, followed by the 32 bytes of the
+    /// committed values digest as KoalaBear elements.
+    FieldElements,
+    /// The 8 KoalaBear elements of the verifying key digest, followed by the KoalaBear Poseidon2
+    /// digest of the verifying key digest and the committed values digest, which the wrap program
+    /// checks in its public values.
+    Poseidon2Digest,
+}
+
 /// The information necessary to verify a proof for a given MIPS program.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ZKMVerifyingKey {
@@ -125,9 +148,39 @@ impl<P: Serialize + DeserializeOwned + Clone> ZKMProofWithMetadata<P> {
     }
 
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
-        bincode::deserialize_from(File::open(path).expect("failed to open file"))
-            .map_err(Into::into)
+        Self::load_with_limit(path, MAX_PROOF_SIZE)
     }
+
+    /// Loads a proof from a path, rejecting files larger than `limit` bytes.
+    ///
+    /// Proofs saved before the virtual files and the named inputs of the stdin are still loaded,
+    /// with a [LegacyZKMStdin].
+    pub fn load_with_limit(path: impl AsRef<Path>, limit: u64) -> Result<Self> {
+        match deserialize_from_limited(open_limited(path.as_ref(), limit)?, limit) {
+            Ok(proof) => Ok(proof),
+            Err(err) => {
+                let legacy: LegacyProofWithMetadata<P> =
+                    deserialize_from_limited(open_limited(path.as_ref(), limit)?, limit)
+                        .map_err(|_| err)?;
+                Ok(Self {
+                    proof: legacy.proof,
+                    stdin: legacy.stdin.into(),
+                    public_values: legacy.public_values,
+                    cycles: legacy.cycles,
+                })
+            }
+        }
+    }
+}
+
+/// The layout of [ZKMProofWithMetadata] with a [LegacyZKMStdin].
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "P: DeserializeOwned"))]
+struct LegacyProofWithMetadata<P> {
+    proof: P,
+    stdin: LegacyZKMStdin,
+    public_values: ZKMPublicValues,
+    cycles: u64,
 }
 
 impl<P: std::fmt::Debug + Clone> std::fmt::Debug for ZKMProofWithMetadata<P> {
@@ -222,6 +275,42 @@ pub enum ZKMReduceProofWrapper {
 pub enum ZKMRecursionProverError {
     #[error("Runtime error: {0}")]
     RuntimeError(String),
+    #[error("Deserialization error: {0}")]
+    DeserializationError(DeserializeError),
+    #[error("{stage} worker {worker} panicked: {message}")]
+    WorkerPanicked { stage: CompressStage, worker: usize, message: String },
+    #[error(
+        "{stage} worker {worker} made no progress for {elapsed:?}{}",
+        index.map(|index| format!(" on input {index}")).unwrap_or_default()
+    )]
+    Stalled { stage: CompressStage, worker: usize, index: Option<usize>, elapsed: Duration },
+    #[error("Invalid aggregation input: {0}")]
+    InvalidAggregateInput(String),
+}
+
+/// A stage of the threaded compress pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressStage {
+    /// Sends the first layer inputs to the pipeline.
+    Input,
+    /// Executes the recursion programs and generates their traces.
+    RecordAndTrace,
+    /// Proves the recursion programs.
+    Prove,
+    /// Batches proofs into the inputs of the next layer.
+    NextLayer,
+}
+
+impl std::fmt::Display for CompressStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CompressStage::Input => "input",
+            CompressStage::RecordAndTrace => "record and trace",
+            CompressStage::Prove => "prove",
+            CompressStage::NextLayer => "next layer",
+        };
+        f.write_str(name)
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
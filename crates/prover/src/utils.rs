@@ -7,7 +7,7 @@ use std::{
 
 use itertools::Itertools;
 use p3_bn254_fr::Bn254Fr;
-use p3_field::{FieldAlgebra, PrimeField32};
+use p3_field::{FieldAlgebra, PrimeField, PrimeField32};
 use p3_koala_bear::KoalaBear;
 use p3_symmetric::CryptographicHasher;
 use zkm_core_executor::{Executor, Program};
@@ -17,13 +17,13 @@ use zkm_recursion_core::{
     air::{RecursionPublicValues, NUM_PV_ELMS_TO_HASH},
     stark::KoalaBearPoseidon2Outer,
 };
-use zkm_stark::{koala_bear_poseidon2::MyHash as InnerHash, Word, ZKMCoreOpts};
+use zkm_stark::{koala_bear_poseidon2::MyHash as InnerHash, StarkGenericConfig, Word, ZKMCoreOpts};
 
-use crate::{InnerSC, ZKMCoreProofData};
+use crate::{InnerSC, PublicInputsMode, ZKMCoreProofData};
 
 /// Get the Ziren vkey KoalaBear Poseidon2 digest this reduce proof is representing.
-pub fn zkm_vkey_digest_koalabear(
-    proof: &ZKMReduceProof<KoalaBearPoseidon2Outer>,
+pub fn zkm_vkey_digest_koalabear<SC: StarkGenericConfig<Val = KoalaBear>>(
+    proof: &ZKMReduceProof<SC>,
 ) -> [KoalaBear; 8] {
     let proof = &proof.proof;
     let pv: &RecursionPublicValues<KoalaBear> = proof.public_values.as_slice().borrow();
@@ -31,7 +31,9 @@ pub fn zkm_vkey_digest_koalabear(
 }
 
 /// Get the Ziren vkey Bn Poseidon2 digest this reduce proof is representing.
-pub fn zkm_vkey_digest_bn254(proof: &ZKMReduceProof<KoalaBearPoseidon2Outer>) -> Bn254Fr {
+pub fn zkm_vkey_digest_bn254<SC: StarkGenericConfig<Val = KoalaBear>>(
+    proof: &ZKMReduceProof<SC>,
+) -> Bn254Fr {
     koalabears_to_bn254(&zkm_vkey_digest_koalabear(proof))
 }
 
@@ -88,15 +90,49 @@ pub fn is_recursion_public_values_valid(
     true
 }
 
-/// Get the committed values Bn Poseidon2 digest this reduce proof is representing.
-pub fn zkm_committed_values_digest_bn254(
-    proof: &ZKMReduceProof<KoalaBearPoseidon2Outer>,
-) -> Bn254Fr {
+/// Get the committed values digest this reduce proof is representing, as 32 KoalaBear bytes.
+pub fn zkm_committed_values_digest_koalabear<SC: StarkGenericConfig<Val = KoalaBear>>(
+    proof: &ZKMReduceProof<SC>,
+) -> [KoalaBear; 32] {
     let proof = &proof.proof;
     let pv: &RecursionPublicValues<KoalaBear> = proof.public_values.as_slice().borrow();
-    let committed_values_digest_bytes: [KoalaBear; 32] =
-        words_to_bytes(&pv.committed_value_digest).try_into().unwrap();
-    koalabear_bytes_to_bn254(&committed_values_digest_bytes)
+    words_to_bytes(&pv.committed_value_digest).try_into().unwrap()
+}
+
+/// Get the committed values Bn Poseidon2 digest this reduce proof is representing.
+pub fn zkm_committed_values_digest_bn254<SC: StarkGenericConfig<Val = KoalaBear>>(
+    proof: &ZKMReduceProof<SC>,
+) -> Bn254Fr {
+    koalabear_bytes_to_bn254(&zkm_committed_values_digest_koalabear(proof))
+}
+
+/// Get the public inputs exposing the public values of this reduce proof in `mode`, as decimal
+/// field elements.
+///
+/// The KoalaBear elements are read from the public values of the proof, so a circuit verifying
+/// the proof over KoalaBear exposes them without hashing into BN254.
+pub fn zkm_public_inputs<SC: StarkGenericConfig<Val = KoalaBear>>(
+    proof: &ZKMReduceProof<SC>,
+    mode: PublicInputsMode,
+) -> Vec<String> {
+    let vk_digest = zkm_vkey_digest_koalabear(proof);
+    let elements = match mode {
+        PublicInputsMode::Bn254Digest => {
+            return [zkm_vkey_digest_bn254(proof), zkm_committed_values_digest_bn254(proof)]
+                .iter()
+                .map(|element| element.as_canonical_biguint().to_string())
+                .collect();
+        }
+        PublicInputsMode::FieldElements => {
+            [vk_digest.as_slice(), &zkm_committed_values_digest_koalabear(proof)].concat()
+        }
+        PublicInputsMode::Poseidon2Digest => {
+            let pv: &RootPublicValues<KoalaBear> = proof.proof.public_values.as_slice().borrow();
+            let digest = root_public_values_digest(&InnerSC::default(), pv);
+            [vk_digest, digest].concat()
+        }
+    };
+    elements.iter().map(|element| element.as_canonical_u32().to_string()).collect()
 }
 
 impl ZKMCoreProofData {
@@ -112,6 +148,8 @@ pub fn get_cycles(elf: &[u8], stdin: &ZKMStdin) -> u64 {
     let program = Program::from(elf).unwrap();
     let mut runtime = Executor::new(program, ZKMCoreOpts::default());
     runtime.write_vecs(&stdin.buffer);
+    runtime.write_files(&stdin.files);
+    runtime.write_named_inputs(&stdin.named_inputs);
     runtime.run_fast().unwrap();
     runtime.state.global_clk
 }
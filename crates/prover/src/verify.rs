@@ -1,12 +1,18 @@
-use std::{borrow::Borrow, path::Path, str::FromStr};
+use std::{borrow::Borrow, io::Read, iter::once, path::Path, str::FromStr};
 
 use anyhow::Result;
 use num_bigint::BigUint;
 use p3_field::{FieldAlgebra, PrimeField};
 use p3_koala_bear::KoalaBear;
 use zkm_core_executor::{subproof::SubproofVerifier, ZKMReduceProof};
-use zkm_core_machine::cpu::MAX_CPU_LOG_DEGREE;
-use zkm_primitives::{consts::WORD_SIZE, io::ZKMPublicValues};
+use zkm_core_machine::{
+    io::{deserialize_from_limited, DeserializeError},
+    verify::{ShardSequenceChecker, MAX_NUM_SHARDS},
+};
+use zkm_primitives::{
+    hash_deferred_proof,
+    io::{PublicValuesHash, ZKMPublicValues},
+};
 
 use thiserror::Error;
 use zkm_recursion_circuit::machine::RootPublicValues;
@@ -15,9 +21,10 @@ use zkm_recursion_gnark_ffi::{
     Groth16Bn254Proof, Groth16Bn254Prover, PlonkBn254Proof, PlonkBn254Prover,
 };
 use zkm_stark::{
-    air::{PublicValues, POSEIDON_NUM_WORDS, PV_DIGEST_NUM_WORDS},
+    air::{LookupScope, POSEIDON_NUM_WORDS},
     koala_bear_poseidon2::KoalaBearPoseidon2,
-    MachineProof, MachineProver, MachineVerificationError, StarkGenericConfig, Word,
+    septic_digest::SepticDigest,
+    MachineProof, MachineProver, MachineVerificationError, ShardProof, StarkGenericConfig,
 };
 
 use crate::{
@@ -50,6 +57,32 @@ pub enum Groth16VerificationError {
     InvalidPublicValues,
 }
 
+/// An error returned when verifying a core proof from a reader.
+#[derive(Error, Debug)]
+pub enum StreamingVerificationError {
+    #[error("failed to read shard proof: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("invalid core proof: {0}")]
+    Verification(#[from] MachineVerificationError<CoreSC>),
+}
+
+/// An error returned by [ZKMProver::verify_bundle], naming the link of the bundle which failed.
+#[derive(Error, Debug)]
+pub enum BundleVerificationError {
+    #[error("invalid outer proof: {0}")]
+    Outer(MachineVerificationError<CoreSC>),
+    #[error("invalid deferred proof {index}: {source}")]
+    Deferred {
+        index: usize,
+        #[source]
+        source: MachineVerificationError<CoreSC>,
+    },
+    #[error(
+        "the {num_deferred} deferred proofs do not hash to the deferred proofs digest of the outer proof"
+    )]
+    DigestMismatch { num_deferred: usize },
+}
+
 impl<C: ZKMProverComponents> ZKMProver<C> {
     /// Verify a core proof by verifying the shards, verifying lookup bus, verifying that the
     /// shards are contiguous and complete.
@@ -62,233 +95,78 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         if proof.0.is_empty() {
             return Err(MachineVerificationError::EmptyProof);
         }
-        // First shard has a "CPU" constraint.
-        //
-        // Check that the first shard has a "CPU".
-        let first_shard = proof.0.first().unwrap();
-        if !first_shard.contains_cpu() {
-            return Err(MachineVerificationError::MissingCpuInFirstShard);
-        }
 
-        // CPU log degree bound constraints.
-        //
-        // Check that the CPU log degree does not exceed `MAX_CPU_LOG_DEGREE`. This is to ensure
-        // that the lookup argument's multiplicities do not overflow.
+        // Check the public values of the shards are contiguous and complete.
+        let mut checker = ShardSequenceChecker::new(vk.vk.pc_start, proof.0.len());
         for shard_proof in proof.0.iter() {
-            if shard_proof.contains_cpu() {
-                let log_degree_cpu = shard_proof.log_degree_cpu();
-                if log_degree_cpu > MAX_CPU_LOG_DEGREE {
-                    return Err(MachineVerificationError::CpuLogDegreeTooLarge(log_degree_cpu));
-                }
-            }
+            checker.check(shard_proof)?;
         }
 
-        // Shard constraints.
-        //
-        // Initialization:
-        // - Shard should start at one.
-        //
-        // Transition:
-        // - Shard should increment by one for each shard.
-        let mut current_shard = KoalaBear::ZERO;
-        for shard_proof in proof.0.iter() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            current_shard += KoalaBear::ONE;
-            if public_values.shard != current_shard {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "shard index should be the previous shard index + 1 and start at 1",
-                ));
-            }
+        // Verify that the number of shards is not too large.
+        if proof.0.len() > MAX_NUM_SHARDS {
+            return Err(MachineVerificationError::TooManyShards);
         }
 
-        // Execution shard constraints.
-        //
-        // Initialization:
-        // - Execution shard should start at one.
-        //
-        // Transition:
-        // - Execution shard should increment by one for each shard with "CPU".
-        // - Execution shard should stay the same for non-CPU shards.
-        // - For the other shards, execution shard does not matter.
-        let mut current_execution_shard = KoalaBear::ZERO;
-        for shard_proof in proof.0.iter() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            if shard_proof.contains_cpu() {
-                current_execution_shard += KoalaBear::ONE;
-                if public_values.execution_shard != current_execution_shard {
-                    return Err(MachineVerificationError::InvalidPublicValues(
-                        "execution shard index should be the previous execution shard index + 1 if cpu exists and start at 1",
-                    ));
-                }
-            }
-        }
+        // Verify the shard proof.
+        let mut challenger = self.core_prover.config().challenger();
+        let machine_proof = MachineProof { shard_proofs: proof.0.to_vec() };
+        self.core_prover.machine().verify(&vk.vk, &machine_proof, &mut challenger)?;
 
-        // Program counter constraints.
-        //
-        // Initialization:
-        // - `start_pc` should start as `vk.start_pc`.
-        //
-        // Transition:
-        // - `next_pc` of the previous shard should equal `start_pc`.
-        // - If it's not a shard with "CPU", then `start_pc` equals `next_pc`.
-        // - If it's a shard with "CPU", then `start_pc` should never equal zero.
-        //
-        // Finalization:
-        // - `next_pc` should equal zero.
-        let mut prev_next_pc = KoalaBear::ZERO;
-        for (i, shard_proof) in proof.0.iter().enumerate() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            if i == 0 && public_values.start_pc != vk.vk.pc_start {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "start_pc != vk.start_pc: program counter should start at vk.start_pc",
-                ));
-            } else if i != 0 && public_values.start_pc != prev_next_pc {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "start_pc != next_pc_prev: start_pc should equal next_pc_prev for all shards",
-                ));
-            } else if !shard_proof.contains_cpu() && public_values.start_pc != public_values.next_pc
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "start_pc != next_pc: start_pc should equal next_pc for non-cpu shards",
-                ));
-            } else if shard_proof.contains_cpu() && public_values.start_pc == KoalaBear::ZERO {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "start_pc == 0: execution should never start at halted state",
-                ));
-            } else if i == proof.0.len() - 1 && public_values.next_pc != KoalaBear::ZERO {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "next_pc != 0: execution should have halted",
-                ));
-            }
-            prev_next_pc = public_values.next_pc;
-        }
+        Ok(())
+    }
 
-        // Exit code constraints.
-        //
-        // - In every shard, the exit code should be zero.
-        for shard_proof in proof.0.iter() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            if public_values.exit_code != KoalaBear::ZERO {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "exit_code != 0: exit code should be zero for all shards",
-                ));
-            }
+    /// Verify a bincode-serialized core proof, reading and verifying one shard proof at a time.
+    ///
+    /// This performs the same checks as [`Self::verify`], but only keeps a single shard proof in
+    /// memory, so proofs larger than the available memory can be verified from a file or a network
+    /// stream. Each shard proof is rejected if its encoding exceeds `max_shard_size` bytes.
+    ///
+    /// Returns the public values of the last shard, whose `committed_value_digest` commits to the
+    /// public values of the program.
+    pub fn verify_from_reader<R: Read>(
+        &self,
+        mut reader: R,
+        vk: &ZKMVerifyingKey,
+        max_shard_size: u64,
+    ) -> Result<Vec<KoalaBear>, StreamingVerificationError> {
+        // The proof is encoded as a length-prefixed sequence of shard proofs.
+        let num_shards: u64 = deserialize_from_limited(&mut reader, 8)?;
+        if num_shards == 0 {
+            return Err(MachineVerificationError::EmptyProof.into());
         }
-
-        // Memory initialization & finalization constraints.
-        //
-        // Initialization:
-        // - `previous_init_addr_bits` should be zero.
-        // - `previous_finalize_addr_bits` should be zero.
-        //
-        // Transition:
-        // - For all shards, `previous_init_addr_bits` should equal `last_init_addr_bits` of the
-        //   previous shard.
-        // - For all shards, `previous_finalize_addr_bits` should equal `last_finalize_addr_bits` of
-        //   the previous shard.
-        // - For shards without "MemoryInit", `previous_init_addr_bits` should equal
-        //   `last_init_addr_bits`.
-        // - For shards without "MemoryFinalize", `previous_finalize_addr_bits` should equal
-        //   `last_finalize_addr_bits`.
-        let mut last_init_addr_bits_prev = [KoalaBear::ZERO; 32];
-        let mut last_finalize_addr_bits_prev = [KoalaBear::ZERO; 32];
-        for shard_proof in proof.0.iter() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            if public_values.previous_init_addr_bits != last_init_addr_bits_prev {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "previous_init_addr_bits != last_init_addr_bits_prev",
-                ));
-            } else if public_values.previous_finalize_addr_bits != last_finalize_addr_bits_prev {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "last_init_addr_bits != last_finalize_addr_bits_prev",
-                ));
-            } else if !shard_proof.contains_global_memory_init()
-                && public_values.previous_init_addr_bits != public_values.last_init_addr_bits
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "previous_init_addr_bits != last_init_addr_bits",
-                ));
-            } else if !shard_proof.contains_global_memory_finalize()
-                && public_values.previous_finalize_addr_bits
-                    != public_values.last_finalize_addr_bits
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "previous_finalize_addr_bits != last_finalize_addr_bits",
-                ));
-            }
-            last_init_addr_bits_prev = public_values.last_init_addr_bits;
-            last_finalize_addr_bits_prev = public_values.last_finalize_addr_bits;
+        if num_shards > MAX_NUM_SHARDS as u64 {
+            return Err(MachineVerificationError::TooManyShards.into());
         }
 
-        // Digest constraints.
-        //
-        // Initialization:
-        // - `committed_value_digest` should be zero.
-        // - `deferred_proofs_digest` should be zero.
-        //
-        // Transition:
-        // - If `committed_value_digest_prev` is not zero, then `committed_value_digest` should equal
-        //  `committed_value_digest_prev`. Otherwise, `committed_value_digest` should equal zero.
-        // - If `deferred_proofs_digest_prev` is not zero, then `deferred_proofs_digest` should
-        //   equal
-        //  `deferred_proofs_digest_prev`. Otherwise, `deferred_proofs_digest` should equal zero.
-        // - If it's not a shard with "CPU", then `committed_value_digest` should not change from the
-        //  previous shard.
-        // - If it's not a shard with "CPU", then `deferred_proofs_digest` should not change from
-        //   the
-        //  previous shard.
-        let zero_committed_value_digest = [Word([KoalaBear::ZERO; WORD_SIZE]); PV_DIGEST_NUM_WORDS];
-        let zero_deferred_proofs_digest = [KoalaBear::ZERO; POSEIDON_NUM_WORDS];
-        let mut committed_value_digest_prev = zero_committed_value_digest;
-        let mut deferred_proofs_digest_prev = zero_deferred_proofs_digest;
-        for shard_proof in proof.0.iter() {
-            let public_values: &PublicValues<Word<_>, _> =
-                shard_proof.public_values.as_slice().borrow();
-            if committed_value_digest_prev != zero_committed_value_digest
-                && public_values.committed_value_digest != committed_value_digest_prev
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "committed_value_digest != committed_value_digest_prev",
-                ));
-            } else if deferred_proofs_digest_prev != zero_deferred_proofs_digest
-                && public_values.deferred_proofs_digest != deferred_proofs_digest_prev
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "deferred_proofs_digest != deferred_proofs_digest_prev",
-                ));
-            } else if !shard_proof.contains_cpu()
-                && public_values.committed_value_digest != committed_value_digest_prev
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "committed_value_digest != committed_value_digest_prev",
-                ));
-            } else if !shard_proof.contains_cpu()
-                && public_values.deferred_proofs_digest != deferred_proofs_digest_prev
-            {
-                return Err(MachineVerificationError::InvalidPublicValues(
-                    "deferred_proofs_digest != deferred_proofs_digest_prev",
-                ));
-            }
-            committed_value_digest_prev = public_values.committed_value_digest;
-            deferred_proofs_digest_prev = public_values.deferred_proofs_digest;
+        let machine = self.core_prover.machine();
+        let mut challenger = self.core_prover.config().challenger();
+        vk.vk.observe_into(&mut challenger);
+
+        let mut checker = ShardSequenceChecker::new(vk.vk.pc_start, num_shards as usize);
+        let mut global_cumulative_sums = Vec::with_capacity(num_shards as usize);
+        let mut last_public_values = Vec::new();
+        for i in 0..num_shards {
+            let shard_proof: ShardProof<CoreSC> =
+                deserialize_from_limited(&mut reader, max_shard_size)?;
+            checker.check(&shard_proof)?;
+            tracing::debug_span!("verifying shard", shard = i)
+                .in_scope(|| machine.verify_shard(&vk.vk, &shard_proof, &challenger))?;
+            global_cumulative_sums.push(shard_proof.global_cumulative_sum());
+            last_public_values = shard_proof.public_values;
         }
 
-        // Verify that the number of shards is not too large.
-        if proof.0.len() > 1 << 16 {
-            return Err(MachineVerificationError::TooManyShards);
+        // Verify the cumulative sum is 0.
+        let sum = global_cumulative_sums
+            .into_iter()
+            .chain(once(vk.vk.initial_global_cumulative_sum))
+            .sum::<SepticDigest<KoalaBear>>();
+        if !sum.is_zero() {
+            return Err(
+                MachineVerificationError::NonZeroCumulativeSum(LookupScope::Global, 0).into()
+            );
         }
 
-        // Verify the shard proof.
-        let mut challenger = self.core_prover.config().challenger();
-        let machine_proof = MachineProof { shard_proofs: proof.0.to_vec() };
-        self.core_prover.machine().verify(&vk.vk, &machine_proof, &mut challenger)?;
-
-        Ok(())
+        Ok(last_public_values)
     }
 
     /// Verify a compressed proof.
@@ -336,6 +214,90 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         Ok(())
     }
 
+    /// Verify a proof produced by [ZKMProver::aggregate] of the programs with the verifying keys
+    /// of `programs`, which committed the given public values, in order.
+    pub fn verify_aggregated(
+        &self,
+        proof: &ZKMReduceProof<KoalaBearPoseidon2>,
+        programs: &[(ZKMVerifyingKey, ZKMPublicValues)],
+    ) -> Result<(), MachineVerificationError<CoreSC>> {
+        let ZKMReduceProof { vk: compress_vk, proof } = proof;
+        let mut challenger = self.compress_prover.config().challenger();
+        let machine_proof = MachineProof { shard_proofs: vec![proof.clone()] };
+        self.compress_prover.machine().verify(compress_vk, &machine_proof, &mut challenger)?;
+
+        // Validate public values
+        let public_values: &RecursionPublicValues<_> = proof.public_values.as_slice().borrow();
+        if !is_recursion_public_values_valid(self.compress_prover.machine().config(), public_values)
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "recursion public values are invalid",
+            ));
+        }
+
+        if public_values.vk_root != self.recursion_vk_root {
+            return Err(MachineVerificationError::InvalidPublicValues("vk_root mismatch"));
+        }
+
+        if self.vk_verification
+            && !self.recursion_vk_map.contains_key(&compress_vk.hash_koalabear())
+        {
+            return Err(MachineVerificationError::InvalidVerificationKey);
+        }
+
+        // The aggregated proof should verify all the deferred proofs from the start, and them
+        // only, in order.
+        if public_values.start_reconstruct_deferred_digest != [KoalaBear::ZERO; POSEIDON_NUM_WORDS]
+        {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "start_reconstruct_deferred_digest is not zero",
+            ));
+        }
+        let digest = aggregated_programs_digest(
+            programs.iter().map(|(vk, committed_values)| (vk.hash_koalabear(), committed_values)),
+        );
+        if public_values.end_reconstruct_deferred_digest != digest {
+            return Err(MachineVerificationError::InvalidPublicValues(
+                "the aggregated programs or public values do not match",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Verify a compressed proof together with the deferred proofs it verified, in the order the
+    /// program verified them.
+    ///
+    /// [Self::verify_compressed] only checks that the outer proof verified some deferred proofs,
+    /// whose digest it commits to. Every deferred proof is also verified against its verifying
+    /// key, and the digest chain of their verifying keys and public values, see
+    /// [Self::hash_deferred_proofs], is checked against the digest of the outer proof, so the
+    /// whole bundle is verified end-to-end.
+    pub fn verify_bundle(
+        &self,
+        proof: &ZKMReduceProof<KoalaBearPoseidon2>,
+        vk: &ZKMVerifyingKey,
+        deferred_proofs: &[(ZKMReduceProof<KoalaBearPoseidon2>, ZKMVerifyingKey)],
+    ) -> Result<(), BundleVerificationError> {
+        self.verify_compressed(proof, vk).map_err(BundleVerificationError::Outer)?;
+
+        let mut digest = [KoalaBear::ZERO; POSEIDON_NUM_WORDS];
+        for (index, (deferred_proof, deferred_vk)) in deferred_proofs.iter().enumerate() {
+            self.verify_compressed(deferred_proof, deferred_vk)
+                .map_err(|source| BundleVerificationError::Deferred { index, source })?;
+            digest = Self::hash_deferred_proofs(digest, std::slice::from_ref(deferred_proof));
+        }
+
+        let public_values: &RecursionPublicValues<_> =
+            proof.proof.public_values.as_slice().borrow();
+        if public_values.deferred_proofs_digest != digest {
+            return Err(BundleVerificationError::DigestMismatch {
+                num_deferred: deferred_proofs.len(),
+            });
+        }
+        Ok(())
+    }
+
     /// Verify a shrink proof.
     pub fn verify_shrink(
         &self,
@@ -401,12 +363,14 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         Ok(())
     }
 
-    /// Verifies a PLONK proof using the circuit artifacts in the build directory.
+    /// Verifies a PLONK proof using the circuit artifacts in the build directory, for a program
+    /// committing to its public values with the `hash` mode.
     pub fn verify_plonk_bn254(
         &self,
         proof: &PlonkBn254Proof,
         vk: &ZKMVerifyingKey,
         public_values: &ZKMPublicValues,
+        hash: PublicValuesHash,
         build_dir: &Path,
     ) -> Result<()> {
         let prover = PlonkBn254Prover::new();
@@ -417,17 +381,49 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         // Verify the proof with the corresponding public inputs.
         prover.verify(proof, &vkey_hash, &committed_values_digest, build_dir)?;
 
-        verify_plonk_bn254_public_inputs(vk, public_values, &proof.public_inputs)?;
+        verify_plonk_bn254_public_inputs(vk, public_values, hash, &proof.public_inputs)?;
 
         Ok(())
     }
 
-    /// Verifies a Groth16 proof using the circuit artifacts in the build directory.
+    /// Verifies a Plonk proof using the serialized Plonk verifying key `plonk_vk`, as returned
+    /// by [`Self::export_plonk_vk`], instead of the circuit artifacts.
+    ///
+    /// The program commits to its public values with the `hash` mode.
+    pub fn verify_plonk_bn254_with_vk(
+        &self,
+        proof: &PlonkBn254Proof,
+        vk: &ZKMVerifyingKey,
+        public_values: &ZKMPublicValues,
+        hash: PublicValuesHash,
+        plonk_vk: &[u8],
+    ) -> Result<()> {
+        let prover = PlonkBn254Prover::new();
+
+        let vkey_hash = BigUint::from_str(&proof.public_inputs[0])?;
+        let committed_values_digest = BigUint::from_str(&proof.public_inputs[1])?;
+
+        // Verify the proof with the corresponding public inputs.
+        prover.verify_with_vk(proof, &vkey_hash, &committed_values_digest, plonk_vk)?;
+
+        verify_plonk_bn254_public_inputs(vk, public_values, hash, &proof.public_inputs)?;
+
+        Ok(())
+    }
+
+    /// Reads the serialized Plonk verifying key from the circuit artifacts in the build directory.
+    pub fn export_plonk_vk(build_dir: &Path) -> Result<Vec<u8>> {
+        PlonkBn254Prover::export_vk(build_dir)
+    }
+
+    /// Verifies a Groth16 proof using the circuit artifacts in the build directory, for a program
+    /// committing to its public values with the `hash` mode.
     pub fn verify_groth16_bn254(
         &self,
         proof: &Groth16Bn254Proof,
         vk: &ZKMVerifyingKey,
         public_values: &ZKMPublicValues,
+        hash: PublicValuesHash,
         build_dir: &Path,
     ) -> Result<()> {
         let prover = Groth16Bn254Prover::new();
@@ -438,17 +434,60 @@ impl<C: ZKMProverComponents> ZKMProver<C> {
         // Verify the proof with the corresponding public inputs.
         prover.verify(proof, &vkey_hash, &committed_values_digest, build_dir)?;
 
-        verify_groth16_bn254_public_inputs(vk, public_values, &proof.public_inputs)?;
+        verify_groth16_bn254_public_inputs(vk, public_values, hash, &proof.public_inputs)?;
 
         Ok(())
     }
+
+    /// Verifies a Groth16 proof using the serialized Groth16 verifying key `groth16_vk`, as returned
+    /// by [`Self::export_groth16_vk`], instead of the circuit artifacts.
+    ///
+    /// The program commits to its public values with the `hash` mode.
+    pub fn verify_groth16_bn254_with_vk(
+        &self,
+        proof: &Groth16Bn254Proof,
+        vk: &ZKMVerifyingKey,
+        public_values: &ZKMPublicValues,
+        hash: PublicValuesHash,
+        groth16_vk: &[u8],
+    ) -> Result<()> {
+        let prover = Groth16Bn254Prover::new();
+
+        let vkey_hash = BigUint::from_str(&proof.public_inputs[0])?;
+        let committed_values_digest = BigUint::from_str(&proof.public_inputs[1])?;
+
+        // Verify the proof with the corresponding public inputs.
+        prover.verify_with_vk(proof, &vkey_hash, &committed_values_digest, groth16_vk)?;
+
+        verify_groth16_bn254_public_inputs(vk, public_values, hash, &proof.public_inputs)?;
+
+        Ok(())
+    }
+
+    /// Reads the serialized Groth16 verifying key from the circuit artifacts in the build directory.
+    pub fn export_groth16_vk(build_dir: &Path) -> Result<Vec<u8>> {
+        Groth16Bn254Prover::export_vk(build_dir)
+    }
+}
+
+/// The digest committed to by a proof of [ZKMProver::aggregate] of the programs with the given
+/// verifying key digests, which committed the given public values, in order.
+fn aggregated_programs_digest<'a>(
+    programs: impl IntoIterator<Item = ([KoalaBear; POSEIDON_NUM_WORDS], &'a ZKMPublicValues)>,
+) -> [KoalaBear; POSEIDON_NUM_WORDS] {
+    programs.into_iter().fold([KoalaBear::ZERO; POSEIDON_NUM_WORDS], |digest, (vk_digest, pv)| {
+        let committed_value_digest =
+            pv.hash().into_iter().map(KoalaBear::from_canonical_u8).collect::<Vec<_>>();
+        hash_deferred_proof(&digest, &vk_digest, &committed_value_digest.try_into().unwrap())
+    })
 }
 
 /// Verify the vk_hash and public_values_hash in the public inputs of the PlonkBn254Proof match the
-/// expected values.
+/// expected values, the public values being hashed with the `hash` mode.
 pub fn verify_plonk_bn254_public_inputs(
     vk: &ZKMVerifyingKey,
     public_values: &ZKMPublicValues,
+    hash: PublicValuesHash,
     plonk_bn254_public_inputs: &[String],
 ) -> Result<()> {
     let expected_vk_hash = BigUint::from_str(&plonk_bn254_public_inputs[0])?;
@@ -459,8 +498,7 @@ pub fn verify_plonk_bn254_public_inputs(
         return Err(PlonkVerificationError::InvalidVerificationKey.into());
     }
 
-    let public_values_hash = public_values.hash_bn254();
-    if public_values_hash != expected_public_values_hash {
+    if !public_values.is_digest_bn254(&expected_public_values_hash, hash) {
         return Err(PlonkVerificationError::InvalidPublicValues.into());
     }
 
@@ -468,10 +506,11 @@ pub fn verify_plonk_bn254_public_inputs(
 }
 
 /// Verify the vk_hash and public_values_hash in the public inputs of the Groth16Bn254Proof match
-/// the expected values.
+/// the expected values, the public values being hashed with the `hash` mode.
 pub fn verify_groth16_bn254_public_inputs(
     vk: &ZKMVerifyingKey,
     public_values: &ZKMPublicValues,
+    hash: PublicValuesHash,
     groth16_bn254_public_inputs: &[String],
 ) -> Result<()> {
     let expected_vk_hash = BigUint::from_str(&groth16_bn254_public_inputs[0])?;
@@ -482,8 +521,7 @@ pub fn verify_groth16_bn254_public_inputs(
         return Err(Groth16VerificationError::InvalidVerificationKey.into());
     }
 
-    let public_values_hash = public_values.hash_bn254();
-    if public_values_hash != expected_public_values_hash {
+    if !public_values.is_digest_bn254(&expected_public_values_hash, hash) {
         return Err(Groth16VerificationError::InvalidPublicValues.into());
     }
 
@@ -525,3 +563,43 @@ impl<C: ZKMProverComponents> SubproofVerifier for ZKMProver<C> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregated_programs_digest() {
+        let vks = [[KoalaBear::ONE; POSEIDON_NUM_WORDS], [KoalaBear::TWO; POSEIDON_NUM_WORDS]];
+        let public_values = [ZKMPublicValues::from(&[1, 2, 3]), ZKMPublicValues::from(&[4, 5])];
+        let programs = || vks.into_iter().zip(&public_values);
+
+        assert_eq!(aggregated_programs_digest([]), [KoalaBear::ZERO; POSEIDON_NUM_WORDS]);
+
+        // Each program chains its verifying key and the digest of its public values.
+        let first_value_digest = public_values[0]
+            .hash()
+            .into_iter()
+            .map(KoalaBear::from_canonical_u8)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            aggregated_programs_digest(programs().take(1)),
+            hash_deferred_proof(
+                &[KoalaBear::ZERO; POSEIDON_NUM_WORDS],
+                &vks[0],
+                &first_value_digest
+            )
+        );
+
+        // The digest commits to the order of the programs and to their public values.
+        let digest = aggregated_programs_digest(programs());
+        assert_ne!(aggregated_programs_digest(programs().rev()), digest);
+        let other_values = ZKMPublicValues::from(&[4, 6]);
+        assert_ne!(
+            aggregated_programs_digest([(vks[0], &public_values[0]), (vks[1], &other_values)]),
+            digest
+        );
+    }
+}
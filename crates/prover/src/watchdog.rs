@@ -0,0 +1,253 @@
+//! Liveness tracking for the threaded compress pipeline.
+//!
+//! Workers report the inputs they start and finish, and use the [Watchdog] to wait on channels
+//! and turns. When a worker panics, or no progress is made for the stall timeout, the pipeline is
+//! aborted: every waiting worker gives up, and the compress call returns the recorded error
+//! instead of hanging.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, SyncSender, TrySendError},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use zkm_core_machine::utils::concurrency::TurnBasedSync;
+
+use crate::{CompressStage, ZKMRecursionProverError};
+
+/// How often waiting workers check whether the pipeline was aborted.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An input being processed by a worker.
+struct Task {
+    stage: CompressStage,
+    worker: usize,
+    index: usize,
+    started: Instant,
+}
+
+struct State {
+    last_progress: Instant,
+    last_worker: (CompressStage, usize),
+    tasks: Vec<Task>,
+    error: Option<ZKMRecursionProverError>,
+}
+
+/// Detects panicked and stalled workers of a threaded pipeline.
+pub(crate) struct Watchdog {
+    timeout: Option<Duration>,
+    aborted: AtomicBool,
+    finished: AtomicBool,
+    state: Mutex<State>,
+}
+
+impl Watchdog {
+    /// Creates a new [Watchdog] aborting the pipeline after `timeout` without progress, if set.
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            aborted: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            state: Mutex::new(State {
+                last_progress: Instant::now(),
+                last_worker: (CompressStage::Input, 0),
+                tasks: Vec::new(),
+                error: None,
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        // The state is never left inconsistent, so a poisoned lock can be recovered.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs the body of a worker, aborting the pipeline if it panics.
+    pub(crate) fn run_worker(&self, stage: CompressStage, worker: usize, f: impl FnOnce()) {
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            self.state().tasks.retain(|task| (task.stage, task.worker) != (stage, worker));
+            self.abort(ZKMRecursionProverError::WorkerPanicked { stage, worker, message });
+        }
+    }
+
+    /// Records that `worker` of `stage` started processing the input `index`.
+    pub(crate) fn begin(&self, stage: CompressStage, worker: usize, index: usize) {
+        let mut state = self.state();
+        state.last_progress = Instant::now();
+        state.last_worker = (stage, worker);
+        state.tasks.push(Task { stage, worker, index, started: Instant::now() });
+    }
+
+    /// Records that `worker` of `stage` finished processing its input.
+    pub(crate) fn end(&self, stage: CompressStage, worker: usize) {
+        let mut state = self.state();
+        state.last_progress = Instant::now();
+        state.last_worker = (stage, worker);
+        state.tasks.retain(|task| (task.stage, task.worker) != (stage, worker));
+    }
+
+    /// Aborts the pipeline with `error`, unless it was already aborted.
+    pub(crate) fn abort(&self, error: ZKMRecursionProverError) {
+        let mut state = self.state();
+        if state.error.is_none() {
+            tracing::error!("aborting the compress pipeline: {}", error);
+            state.error = Some(error);
+        }
+        self.aborted.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the pipeline was aborted.
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Receives a value from `rx`. Returns `None` if the channel is disconnected or the pipeline
+    /// is aborted.
+    pub(crate) fn recv<T>(&self, rx: &Mutex<Receiver<T>>) -> Option<T> {
+        let rx = rx.lock().unwrap();
+        loop {
+            if self.is_aborted() {
+                return None;
+            }
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(value) => return Some(value),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    /// Sends `value` to `tx`. Returns false if the channel is disconnected or the pipeline is
+    /// aborted.
+    pub(crate) fn send<T>(&self, tx: &Mutex<SyncSender<T>>, mut value: T) -> bool {
+        loop {
+            if self.is_aborted() {
+                return false;
+            }
+            match tx.lock().unwrap().try_send(value) {
+                Ok(()) => return true,
+                Err(TrySendError::Full(returned)) => value = returned,
+                Err(TrySendError::Disconnected(_)) => return false,
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Waits for the turn `turn` of `sync`. Returns false if the pipeline is aborted.
+    pub(crate) fn wait_for_turn(&self, sync: &TurnBasedSync, turn: usize) -> bool {
+        sync.wait_for_turn_or_abort(turn, POLL_INTERVAL, || self.is_aborted())
+    }
+
+    /// Aborts the pipeline if it makes no progress for the timeout. Returns once the pipeline is
+    /// finished or aborted.
+    pub(crate) fn monitor(&self) {
+        let Some(timeout) = self.timeout else {
+            return;
+        };
+        while !self.finished.load(Ordering::SeqCst) && !self.is_aborted() {
+            thread::sleep(POLL_INTERVAL);
+            let state = self.state();
+            let elapsed = state.last_progress.elapsed();
+            if elapsed < timeout {
+                continue;
+            }
+            // Blame the longest running task if there is one: the other workers are most likely
+            // waiting on it. Otherwise, blame the last worker which made progress.
+            let error = match state.tasks.iter().min_by_key(|task| task.started) {
+                Some(task) => ZKMRecursionProverError::Stalled {
+                    stage: task.stage,
+                    worker: task.worker,
+                    index: Some(task.index),
+                    elapsed: task.started.elapsed(),
+                },
+                None => ZKMRecursionProverError::Stalled {
+                    stage: state.last_worker.0,
+                    worker: state.last_worker.1,
+                    index: None,
+                    elapsed,
+                },
+            };
+            drop(state);
+            self.abort(error);
+        }
+    }
+
+    /// Marks the pipeline as finished, stopping the monitor, and returns the error which
+    /// aborted it, if any.
+    pub(crate) fn finish(&self) -> Result<(), ZKMRecursionProverError> {
+        self.finished.store(true, Ordering::SeqCst);
+        match self.state().error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::sync_channel;
+
+    use super::*;
+
+    #[test]
+    fn test_panicked_worker_aborts() {
+        let watchdog = Watchdog::new(None);
+        let (_tx, rx) = sync_channel::<()>(1);
+        let rx = Mutex::new(rx);
+        thread::scope(|s| {
+            // This worker would wait forever, since the sender is never used.
+            s.spawn(|| {
+                watchdog
+                    .run_worker(CompressStage::Prove, 0, || assert!(watchdog.recv(&rx).is_none()))
+            });
+            s.spawn(|| {
+                watchdog.run_worker(CompressStage::RecordAndTrace, 1, || panic!("boom"));
+            });
+        });
+        let err = watchdog.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            ZKMRecursionProverError::WorkerPanicked {
+                stage: CompressStage::RecordAndTrace,
+                worker: 1,
+                ref message,
+            } if message == "boom"
+        ));
+    }
+
+    #[test]
+    fn test_stalled_worker_aborts() {
+        let watchdog = Watchdog::new(Some(Duration::from_millis(200)));
+        let sync = TurnBasedSync::new();
+        thread::scope(|s| {
+            s.spawn(|| watchdog.monitor());
+            s.spawn(|| {
+                watchdog.run_worker(CompressStage::Prove, 2, || {
+                    watchdog.begin(CompressStage::Prove, 2, 3);
+                    // Turn 1 never comes, since nobody advances turn 0.
+                    assert!(!watchdog.wait_for_turn(&sync, 1));
+                })
+            });
+        });
+        let err = watchdog.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            ZKMRecursionProverError::Stalled {
+                stage: CompressStage::Prove,
+                worker: 2,
+                index: Some(3),
+                ..
+            }
+        ));
+    }
+}
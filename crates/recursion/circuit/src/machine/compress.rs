@@ -67,7 +67,7 @@ pub struct ZKMCompressWitnessValues<SC: StarkGenericConfig> {
     pub is_complete: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ZKMCompressShape {
     proof_shapes: Vec<OrderedShape>,
 }
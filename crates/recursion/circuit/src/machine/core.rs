@@ -68,7 +68,7 @@ pub struct ZKMRecursionWitnessValues<SC: StarkGenericConfig> {
     pub vk_root: [SC::Val; DIGEST_SIZE],
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ZKMRecursionShape {
     pub proof_shapes: Vec<OrderedShape>,
     pub is_complete: bool,
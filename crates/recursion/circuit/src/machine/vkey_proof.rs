@@ -35,7 +35,7 @@ pub struct ZKMMerkleProofVerifier<C, SC> {
 }
 
 /// The shape of the compress proof with vk validation proofs.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ZKMCompressWithVkeyShape {
     pub compress_shape: ZKMCompressShape,
     pub merkle_tree_height: usize,
@@ -3,6 +3,7 @@ mod memory;
 mod opcode;
 mod program;
 mod record;
+mod stats;
 
 // Avoid triggering annoying branch of thiserror derive macro.
 use backtrace::Backtrace as Trace;
@@ -16,6 +17,7 @@ use memory::*;
 pub use opcode::*;
 pub use program::*;
 pub use record::*;
+pub use stats::*;
 
 use std::{
     array,
@@ -221,6 +223,23 @@ where
         }
     }
 
+    /// Returns the operation counts collected so far.
+    pub fn stats(&self) -> RecursionNodeStats {
+        RecursionNodeStats {
+            cycles: self.timestamp,
+            poseidons: self.nb_poseidons,
+            wide_poseidons: self.nb_wide_poseidons,
+            exp_reverse_bits: self.nb_exp_reverse_bits,
+            fri_fold: self.nb_fri_fold,
+            base_ops: self.nb_base_ops,
+            select: self.nb_select,
+            ext_ops: self.nb_ext_ops,
+            batch_fri: self.nb_batch_fri,
+            memory_ops: self.nb_memory_ops,
+            branch_ops: self.nb_branch_ops,
+        }
+    }
+
     pub fn print_stats(&self) {
         tracing::debug!("Total Cycles: {}", self.timestamp);
         tracing::debug!("Poseidon Skinny Operations: {}", self.nb_poseidons);
@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, ops::AddAssign};
+
+use serde::{Deserialize, Serialize};
+
+/// Operation counts collected while executing a single recursion program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursionNodeStats {
+    pub cycles: usize,
+    pub poseidons: usize,
+    pub wide_poseidons: usize,
+    pub exp_reverse_bits: usize,
+    pub fri_fold: usize,
+    pub base_ops: usize,
+    pub select: usize,
+    pub ext_ops: usize,
+    pub batch_fri: usize,
+    pub memory_ops: usize,
+    pub branch_ops: usize,
+}
+
+impl AddAssign for RecursionNodeStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.cycles += rhs.cycles;
+        self.poseidons += rhs.poseidons;
+        self.wide_poseidons += rhs.wide_poseidons;
+        self.exp_reverse_bits += rhs.exp_reverse_bits;
+        self.fri_fold += rhs.fri_fold;
+        self.base_ops += rhs.base_ops;
+        self.select += rhs.select;
+        self.ext_ops += rhs.ext_ops;
+        self.batch_fri += rhs.batch_fri;
+        self.memory_ops += rhs.memory_ops;
+        self.branch_ops += rhs.branch_ops;
+    }
+}
+
+/// Statistics of every recursion program executed while reducing a proof, indexed by the height
+/// of the node in the compress tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursionStats {
+    /// The `(height, index, stats)` of each node of the tree, in execution order.
+    pub nodes: Vec<(usize, usize, RecursionNodeStats)>,
+}
+
+impl RecursionStats {
+    /// Records the stats of the node at `index` of the layer at `height`.
+    pub fn push(&mut self, height: usize, index: usize, stats: RecursionNodeStats) {
+        self.nodes.push((height, index, stats));
+    }
+
+    /// Returns the stats summed over each layer of the tree.
+    pub fn by_layer(&self) -> BTreeMap<usize, RecursionNodeStats> {
+        let mut layers = BTreeMap::<usize, RecursionNodeStats>::new();
+        for (height, _, stats) in &self.nodes {
+            *layers.entry(*height).or_default() += *stats;
+        }
+        layers
+    }
+
+    /// Returns the stats summed over all the nodes of the tree.
+    pub fn total(&self) -> RecursionNodeStats {
+        let mut total = RecursionNodeStats::default();
+        for (_, _, stats) in &self.nodes {
+            total += *stats;
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_layer() {
+        let node = RecursionNodeStats { cycles: 10, poseidons: 2, ..Default::default() };
+        let mut stats = RecursionStats::default();
+        stats.push(0, 0, node);
+        stats.push(0, 1, node);
+        stats.push(1, 2, node);
+
+        let layers = stats.by_layer();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[&0].cycles, 20);
+        assert_eq!(layers[&1].poseidons, 2);
+        assert_eq!(stats.total().cycles, 30);
+    }
+}
@@ -18,6 +18,7 @@ typedef struct {
 */
 import "C"
 import (
+	"encoding/hex"
 	"encoding/json"
 	"fmt"
 	"os"
@@ -89,6 +90,23 @@ func VerifyPlonkBn254(dataDir *C.char, proof *C.char, vkeyHash *C.char, committe
 	return nil
 }
 
+//export VerifyPlonkBn254WithVk
+func VerifyPlonkBn254WithVk(vk *C.char, proof *C.char, vkeyHash *C.char, committedValuesDigest *C.char) *C.char {
+	vkBytes, err := hex.DecodeString(C.GoString(vk))
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	proofString := C.GoString(proof)
+	vkeyHashString := C.GoString(vkeyHash)
+	committedValuesDigestString := C.GoString(committedValuesDigest)
+
+	err = zkm.VerifyPlonkWithVk(vkBytes, proofString, vkeyHashString, committedValuesDigestString)
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	return nil
+}
+
 var testMutex = &sync.Mutex{}
 
 //export TestPlonkBn254
@@ -158,6 +176,23 @@ func VerifyGroth16Bn254(dataDir *C.char, proof *C.char, vkeyHash *C.char, commit
 	return nil
 }
 
+//export VerifyGroth16Bn254WithVk
+func VerifyGroth16Bn254WithVk(vk *C.char, proof *C.char, vkeyHash *C.char, committedValuesDigest *C.char) *C.char {
+	vkBytes, err := hex.DecodeString(C.GoString(vk))
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	proofString := C.GoString(proof)
+	vkeyHashString := C.GoString(vkeyHash)
+	committedValuesDigestString := C.GoString(committedValuesDigest)
+
+	err = zkm.VerifyGroth16WithVk(vkBytes, proofString, vkeyHashString, committedValuesDigestString)
+	if err != nil {
+		return C.CString(err.Error())
+	}
+	return nil
+}
+
 //export TestGroth16Bn254
 func TestGroth16Bn254(witnessJson *C.char, constraintsJson *C.char) *C.char {
 	// Because of the global env variables used here, we need to lock this function
@@ -18,6 +18,17 @@ func VerifyPlonk(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkeyHa
 		panic("--data is required")
 	}
 
+	// Read the verifier key.
+	vkBytes, err := os.ReadFile(verifyCmdDataDir + "/" + plonkVkPath)
+	if err != nil {
+		panic(err)
+	}
+
+	return VerifyPlonkWithVk(vkBytes, verifyCmdProof, verifyCmdVkeyHash, verifyCmdCommittedValuesDigest)
+}
+
+// VerifyPlonkWithVk verifies a proof against the given serialized verifier key.
+func VerifyPlonkWithVk(vkBytes []byte, verifyCmdProof string, verifyCmdVkeyHash string, verifyCmdCommittedValuesDigest string) error {
 	// Decode the proof.
 	proofDecodedBytes, err := hex.DecodeString(verifyCmdProof)
 	if err != nil {
@@ -29,12 +40,10 @@ func VerifyPlonk(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkeyHa
 	}
 
 	// Read the verifier key.
-	vkFile, err := os.Open(verifyCmdDataDir + "/" + plonkVkPath)
-	if err != nil {
-		panic(err)
-	}
 	vk := plonk.NewVerifyingKey(ecc.BN254)
-	vk.ReadFrom(vkFile)
+	if _, err := vk.ReadFrom(bytes.NewReader(vkBytes)); err != nil {
+		return err
+	}
 
 	// Compute the public witness.
 	circuit := Circuit{
@@ -64,6 +73,17 @@ func VerifyGroth16(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkey
 		panic("--data is required")
 	}
 
+	// Read the verifier key.
+	vkBytes, err := os.ReadFile(verifyCmdDataDir + "/" + groth16VkPath)
+	if err != nil {
+		panic(err)
+	}
+
+	return VerifyGroth16WithVk(vkBytes, verifyCmdProof, verifyCmdVkeyHash, verifyCmdCommittedValuesDigest)
+}
+
+// VerifyGroth16WithVk verifies a proof against the given serialized verifier key.
+func VerifyGroth16WithVk(vkBytes []byte, verifyCmdProof string, verifyCmdVkeyHash string, verifyCmdCommittedValuesDigest string) error {
 	// Decode the proof.
 	proofDecodedBytes, err := hex.DecodeString(verifyCmdProof)
 	if err != nil {
@@ -75,12 +95,10 @@ func VerifyGroth16(verifyCmdDataDir string, verifyCmdProof string, verifyCmdVkey
 	}
 
 	// Read the verifier key.
-	vkFile, err := os.Open(verifyCmdDataDir + "/" + groth16VkPath)
-	if err != nil {
-		panic(err)
-	}
 	vk := groth16.NewVerifyingKey(ecc.BN254)
-	vk.ReadFrom(vkFile)
+	if _, err := vk.ReadFrom(bytes.NewReader(vkBytes)); err != nil {
+		return err
+	}
 
 	// Compute the public witness.
 	circuit := Circuit{
@@ -54,6 +54,16 @@ impl ProofSystem {
         }
     }
 
+    fn verify_with_vk_fn(
+        &self,
+    ) -> unsafe extern "C" fn(*mut c_char, *mut c_char, *mut c_char, *mut c_char) -> *mut c_char
+    {
+        match self {
+            ProofSystem::Plonk => bind::VerifyPlonkBn254WithVk,
+            ProofSystem::Groth16 => bind::VerifyGroth16Bn254WithVk,
+        }
+    }
+
     fn test_fn(&self) -> unsafe extern "C" fn(*mut c_char, *mut c_char) -> *mut c_char {
         match self {
             ProofSystem::Plonk => bind::TestPlonkBn254,
@@ -125,6 +135,37 @@ fn verify(
     }
 }
 
+fn verify_with_vk(
+    system: ProofSystem,
+    vk: &[u8],
+    proof: &str,
+    vkey_hash: &str,
+    committed_values_digest: &str,
+) -> Result<(), String> {
+    let vk = CString::new(hex::encode(vk)).expect("CString::new failed");
+    let proof = CString::new(proof).expect("CString::new failed");
+    let vkey_hash = CString::new(vkey_hash).expect("CString::new failed");
+    let committed_values_digest =
+        CString::new(committed_values_digest).expect("CString::new failed");
+
+    let err_ptr = unsafe {
+        (system.verify_with_vk_fn())(
+            vk.as_ptr() as *mut c_char,
+            proof.as_ptr() as *mut c_char,
+            vkey_hash.as_ptr() as *mut c_char,
+            committed_values_digest.as_ptr() as *mut c_char,
+        )
+    };
+    if err_ptr.is_null() {
+        Ok(())
+    } else {
+        unsafe {
+            // Safety: The error message is returned from the go code and is guaranteed to be valid.
+            Err(ptr_to_string_freed(err_ptr))
+        }
+    }
+}
+
 fn test(system: ProofSystem, witness_json: &str, constraints_json: &str) {
     unsafe {
         let witness_json = CString::new(witness_json).expect("CString::new failed");
@@ -162,6 +203,15 @@ pub fn verify_plonk_bn254(
     verify(ProofSystem::Plonk, data_dir, proof, vkey_hash, committed_values_digest)
 }
 
+pub fn verify_plonk_bn254_with_vk(
+    vk: &[u8],
+    proof: &str,
+    vkey_hash: &str,
+    committed_values_digest: &str,
+) -> Result<(), String> {
+    verify_with_vk(ProofSystem::Plonk, vk, proof, vkey_hash, committed_values_digest)
+}
+
 pub fn test_plonk_bn254(witness_json: &str, constraints_json: &str) {
     test(ProofSystem::Plonk, witness_json, constraints_json)
 }
@@ -186,6 +236,15 @@ pub fn verify_groth16_bn254(
     verify(ProofSystem::Groth16, data_dir, proof, vkey_hash, committed_values_digest)
 }
 
+pub fn verify_groth16_bn254_with_vk(
+    vk: &[u8],
+    proof: &str,
+    vkey_hash: &str,
+    committed_values_digest: &str,
+) -> Result<(), String> {
+    verify_with_vk(ProofSystem::Groth16, vk, proof, vkey_hash, committed_values_digest)
+}
+
 pub fn test_groth16_bn254(witness_json: &str, constraints_json: &str) {
     test(ProofSystem::Groth16, witness_json, constraints_json)
 }
@@ -5,7 +5,10 @@ use std::{
 };
 
 use crate::{
-    ffi::{build_groth16_bn254, prove_groth16_bn254, test_groth16_bn254, verify_groth16_bn254},
+    ffi::{
+        build_groth16_bn254, prove_groth16_bn254, test_groth16_bn254, verify_groth16_bn254,
+        verify_groth16_bn254_with_vk,
+    },
     witness::GnarkWitness,
     Groth16Bn254Proof,
 };
@@ -31,9 +34,15 @@ impl Groth16Bn254Prover {
     }
 
     pub fn get_vkey_hash(build_dir: &Path) -> [u8; 32] {
-        let vkey_path = build_dir.join("groth16_vk.bin");
-        let vk_bin_bytes = std::fs::read(vkey_path).unwrap();
-        Sha256::digest(vk_bin_bytes).into()
+        Sha256::digest(Self::export_vk(build_dir).unwrap()).into()
+    }
+
+    /// Reads the serialized Groth16 verifying key from the build directory.
+    ///
+    /// The bytes may be passed to [Self::verify_with_vk] to verify proofs without the build
+    /// directory.
+    pub fn export_vk(build_dir: &Path) -> Result<Vec<u8>> {
+        Ok(std::fs::read(build_dir.join("groth16_vk.bin"))?)
     }
 
     /// Executes the prover in testing mode with a circuit definition and witness.
@@ -133,6 +142,29 @@ impl Groth16Bn254Prover {
         .map_err(|e| anyhow::anyhow!("failed to verify proof: {e}"))
     }
 
+    /// Verify a Groth16 proof against the serialized verifying key `vk` and verify that the
+    /// supplied vkey_hash and committed_values_digest match.
+    pub fn verify_with_vk(
+        &self,
+        proof: &Groth16Bn254Proof,
+        vkey_hash: &BigUint,
+        committed_values_digest: &BigUint,
+        vk: &[u8],
+    ) -> Result<()> {
+        if proof.groth16_vkey_hash != <[u8; 32]>::from(Sha256::digest(vk)) {
+            return Err(anyhow::anyhow!(
+                "Proof vkey hash does not match circuit vkey hash, it was generated with a different circuit."
+            ));
+        }
+        verify_groth16_bn254_with_vk(
+            vk,
+            &proof.raw_proof,
+            &vkey_hash.to_string(),
+            &committed_values_digest.to_string(),
+        )
+        .map_err(|e| anyhow::anyhow!("failed to verify proof: {e}"))
+    }
+
     /// Modify the Groth16Verifier so that it works with the ZKMVerifier.
     fn modify_groth16_verifier(file_path: &Path) {
         let mut content = String::new();
@@ -153,3 +185,37 @@ impl Default for Groth16Bn254Prover {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_vk() {
+        let build_dir = tempfile::tempdir().unwrap();
+        assert!(Groth16Bn254Prover::export_vk(build_dir.path()).is_err());
+
+        let vk = b"groth16 verifying key".to_vec();
+        std::fs::write(build_dir.path().join("groth16_vk.bin"), &vk).unwrap();
+        assert_eq!(Groth16Bn254Prover::export_vk(build_dir.path()).unwrap(), vk);
+        assert_eq!(
+            Groth16Bn254Prover::get_vkey_hash(build_dir.path()),
+            <[u8; 32]>::from(Sha256::digest(&vk))
+        );
+    }
+
+    #[test]
+    fn test_verify_with_vk_of_another_circuit() {
+        let proof = Groth16Bn254Proof {
+            groth16_vkey_hash: Sha256::digest(b"another circuit").into(),
+            ..Default::default()
+        };
+        let result = Groth16Bn254Prover::new().verify_with_vk(
+            &proof,
+            &BigUint::default(),
+            &BigUint::default(),
+            b"groth16 verifying key",
+        );
+        assert!(result.unwrap_err().to_string().contains("different circuit"));
+    }
+}
@@ -10,3 +10,25 @@ pub use groth16_bn254::*;
 pub use plonk_bn254::*;
 pub use proof::*;
 pub use witness::*;
+
+/// The `IZKMVerifier` interface implemented by the Solidity verifiers built from the artifacts.
+pub const IZKM_VERIFIER_SOL: &str = include_str!("../assets/IZKMVerifier.sol");
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_verifier_templates_have_digest_entry_points() {
+        for template in [
+            include_str!("../assets/ZKMVerifierGroth16.txt"),
+            include_str!("../assets/ZKMVerifierPlonk.txt"),
+        ] {
+            assert!(template
+                .contains("return keccak256(publicValues) & bytes32(uint256((1 << 253) - 1));"));
+            assert!(template.contains("function verifyProofKeccak("));
+            assert!(template.contains("function verifyProofWithDigest("));
+            assert!(template.contains(
+                "verifyProofWithDigest(programVKey, hashPublicValues(publicValues), proofBytes);"
+            ));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProofBn254 {
@@ -24,3 +25,128 @@ pub struct Groth16Bn254Proof {
     pub raw_proof: String,
     pub groth16_vkey_hash: [u8; 32],
 }
+
+/// An error returned when encoding a proof for the onchain verifier.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EvmCalldataError {
+    /// The proof is a mock proof, with an empty proof or verifier hash, which the mock verifier
+    /// expects to be encoded as no bytes.
+    #[error("mock proofs have no EVM calldata")]
+    MockProof,
+    #[error("the encoded proof is not valid hex: {0}")]
+    InvalidEncodedProof(#[from] hex::FromHexError),
+}
+
+/// The proof system of an [EvmProof].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EvmProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// The JSON form of a proof for the onchain verifier, with `0x`-prefixed hex byte strings.
+///
+/// `proof` is the `proofBytes` argument of `IZKMVerifier.verifyProof`, and `public_inputs` are the
+/// vkey digest and the committed values digest the verifier recomputes from the other arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmProof {
+    pub system: EvmProofSystem,
+    pub verifier_hash: String,
+    pub public_inputs: [String; 2],
+    pub proof: String,
+}
+
+impl PlonkBn254Proof {
+    /// Returns the `proofBytes` expected by the Solidity PLONK verifier: the first four bytes of
+    /// the verifier hash followed by the encoded proof.
+    ///
+    /// Fails with [EvmCalldataError::MockProof] for a mock proof, with an empty proof or verifier
+    /// hash.
+    pub fn to_evm_calldata(&self) -> Result<Vec<u8>, EvmCalldataError> {
+        evm_calldata(&self.plonk_vkey_hash, &self.encoded_proof)
+    }
+
+    /// Returns [Self::to_evm_calldata] as a `0x`-prefixed hex string.
+    pub fn to_evm_calldata_hex(&self) -> Result<String, EvmCalldataError> {
+        Ok(format!("0x{}", hex::encode(self.to_evm_calldata()?)))
+    }
+
+    /// Returns the JSON form of the proof for the onchain verifier.
+    pub fn to_evm_proof(&self) -> Result<EvmProof, EvmCalldataError> {
+        Ok(EvmProof {
+            system: EvmProofSystem::Plonk,
+            verifier_hash: format!("0x{}", hex::encode(self.plonk_vkey_hash)),
+            public_inputs: self.public_inputs.clone(),
+            proof: self.to_evm_calldata_hex()?,
+        })
+    }
+}
+
+impl Groth16Bn254Proof {
+    /// Returns the `proofBytes` expected by the Solidity Groth16 verifier: the first four bytes
+    /// of the verifier hash followed by the encoded proof.
+    ///
+    /// Fails with [EvmCalldataError::MockProof] for a mock proof, with an empty proof or verifier
+    /// hash.
+    pub fn to_evm_calldata(&self) -> Result<Vec<u8>, EvmCalldataError> {
+        evm_calldata(&self.groth16_vkey_hash, &self.encoded_proof)
+    }
+
+    /// Returns [Self::to_evm_calldata] as a `0x`-prefixed hex string.
+    pub fn to_evm_calldata_hex(&self) -> Result<String, EvmCalldataError> {
+        Ok(format!("0x{}", hex::encode(self.to_evm_calldata()?)))
+    }
+
+    /// Returns the JSON form of the proof for the onchain verifier.
+    pub fn to_evm_proof(&self) -> Result<EvmProof, EvmCalldataError> {
+        Ok(EvmProof {
+            system: EvmProofSystem::Groth16,
+            verifier_hash: format!("0x{}", hex::encode(self.groth16_vkey_hash)),
+            public_inputs: self.public_inputs.clone(),
+            proof: self.to_evm_calldata_hex()?,
+        })
+    }
+}
+
+fn evm_calldata(vkey_hash: &[u8; 32], encoded_proof: &str) -> Result<Vec<u8>, EvmCalldataError> {
+    if encoded_proof.is_empty() || *vkey_hash == [0; 32] {
+        return Err(EvmCalldataError::MockProof);
+    }
+    let proof_bytes = hex::decode(encoded_proof)?;
+    Ok([&vkey_hash[..4], &proof_bytes].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evm_proof() {
+        let proof = Groth16Bn254Proof {
+            public_inputs: ["1".to_string(), "2".to_string()],
+            encoded_proof: "abcd".to_string(),
+            raw_proof: "".to_string(),
+            groth16_vkey_hash: [0x11; 32],
+        };
+        assert_eq!(proof.to_evm_calldata(), Ok(vec![0x11, 0x11, 0x11, 0x11, 0xab, 0xcd]));
+        assert_eq!(proof.to_evm_calldata_hex().unwrap(), "0x11111111abcd");
+
+        let json = serde_json::to_value(proof.to_evm_proof().unwrap()).unwrap();
+        assert_eq!(json["system"], "groth16");
+        assert_eq!(json["verifierHash"], format!("0x{}", "11".repeat(32)));
+        assert_eq!(json["publicInputs"][1], "2");
+        assert_eq!(json["proof"], "0x11111111abcd");
+
+        // Mock proofs and malformed proofs are errors.
+        let mock = PlonkBn254Proof { encoded_proof: "abcd".to_string(), ..Default::default() };
+        assert_eq!(mock.to_evm_calldata(), Err(EvmCalldataError::MockProof));
+        assert_eq!(mock.to_evm_proof(), Err(EvmCalldataError::MockProof));
+        let malformed = Groth16Bn254Proof { encoded_proof: "xyz".to_string(), ..proof };
+        assert!(matches!(
+            malformed.to_evm_calldata(),
+            Err(EvmCalldataError::InvalidEncodedProof(_))
+        ));
+    }
+}
@@ -0,0 +1,16 @@
+//! Python bindings for the Ziren SDK.
+//!
+//! The bindings are only built with the `python` feature, usually through `maturin`, which builds
+//! the `zkm_sdk` Python module from this crate:
+//!
+//! ```bash
+//! cd crates/sdk-py
+//! maturin develop --release
+//! ```
+//!
+//! The module exposes [zkm_sdk::ProverClient], [zkm_sdk::ZKMStdin], the proving and verifying
+//! keys, and proofs with their public values. The public values implement the buffer protocol,
+//! so that `numpy.frombuffer(proof.public_values, dtype=numpy.uint8)` views them without a copy.
+
+#[cfg(feature = "python")]
+mod python;
@@ -0,0 +1,273 @@
+use std::{ffi::c_int, path::PathBuf};
+
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    ffi,
+    prelude::*,
+    types::PyBytes,
+};
+use zkm_sdk::{
+    action::Prove, HashableKey, ProverClient, ZKMProofKind, ZKMProofWithPublicValues,
+    ZKMProvingKey, ZKMPublicValues, ZKMStdin, ZKMVerifyingKey,
+};
+
+fn runtime_error(e: impl ToString) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// The input of a program, read by the program in the order it was written.
+#[pyclass(name = "ZKMStdin")]
+#[derive(Clone, Default)]
+struct PyStdin(ZKMStdin);
+
+#[pymethods]
+impl PyStdin {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes raw bytes, read by the program with `zkm_zkvm::io::read_vec`.
+    fn write_slice(&mut self, data: &[u8]) {
+        self.0.write_slice(data);
+    }
+
+    /// Writes a `Vec<u8>`, read by the program with `zkm_zkvm::io::read::<Vec<u8>>`.
+    fn write_bytes(&mut self, data: Vec<u8>) {
+        self.0.write(&data);
+    }
+
+    /// Writes a `String`, read by the program with `zkm_zkvm::io::read::<String>`.
+    fn write_str(&mut self, value: String) {
+        self.0.write(&value);
+    }
+
+    /// Writes a `u32`, read by the program with `zkm_zkvm::io::read::<u32>`.
+    fn write_u32(&mut self, value: u32) {
+        self.0.write(&value);
+    }
+
+    /// Writes a `u64`, read by the program with `zkm_zkvm::io::read::<u64>`.
+    fn write_u64(&mut self, value: u64) {
+        self.0.write(&value);
+    }
+
+    /// Writes a virtual file at `path`, read by the program with `zkm_zkvm::lib::fs`.
+    fn write_file(&mut self, path: String, data: Vec<u8>) {
+        self.0.write_file(path, data);
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.buffer.len()
+    }
+}
+
+/// The public values committed by a program.
+///
+/// Implements the buffer protocol, e.g. `numpy.frombuffer(public_values, dtype=numpy.uint8)`.
+#[pyclass(name = "ZKMPublicValues", frozen)]
+struct PyPublicValues(ZKMPublicValues);
+
+#[pymethods]
+impl PyPublicValues {
+    /// The bytes committed by the program, without the seed of its random number generator and
+    /// the digest of its public logs.
+    fn committed_values<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.0.committed_values())
+    }
+
+    /// The public values as a `0x`-prefixed hex string.
+    fn hex(&self) -> String {
+        self.0.raw()
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.0.as_slice())
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.as_slice().len()
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        // The class is frozen, so the bytes are not moved while the view holds a reference to it.
+        let bytes = slf.get().0.as_slice();
+        let result = ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            bytes.as_ptr() as *mut _,
+            bytes.len() as ffi::Py_ssize_t,
+            1,
+            flags,
+        );
+        if result == -1 {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+/// The key to prove the execution of a program.
+#[pyclass(name = "ZKMProvingKey", frozen)]
+struct PyProvingKey(ZKMProvingKey);
+
+/// The key to verify the proofs of a program.
+#[pyclass(name = "ZKMVerifyingKey", frozen)]
+struct PyVerifyingKey(ZKMVerifyingKey);
+
+#[pymethods]
+impl PyVerifyingKey {
+    /// The hash of the key, as the `programVKey` of the onchain verifier.
+    fn bytes32(&self) -> String {
+        self.0.bytes32()
+    }
+}
+
+/// A proof, together with the public values it commits to.
+#[pyclass(name = "ZKMProofWithPublicValues", frozen)]
+struct PyProof(ZKMProofWithPublicValues);
+
+#[pymethods]
+impl PyProof {
+    /// The kind of the proof: `core`, `compressed`, `plonk`, `groth16` or `compresstogroth16`.
+    #[getter]
+    fn kind(&self) -> String {
+        format!("{:?}", ZKMProofKind::from(&self.0.proof)).to_lowercase()
+    }
+
+    #[getter]
+    fn public_values(&self) -> PyPublicValues {
+        PyPublicValues(self.0.public_values.clone())
+    }
+
+    #[getter]
+    fn zkm_version(&self) -> String {
+        self.0.zkm_version.clone()
+    }
+
+    /// The proof bytes expected by the onchain verifier, for Plonk and Groth16 proofs.
+    fn evm_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        match ZKMProofKind::from(&self.0.proof) {
+            ZKMProofKind::Plonk | ZKMProofKind::Groth16 => {
+                Ok(PyBytes::new_bound(py, &self.0.bytes()))
+            }
+            kind => {
+                Err(PyValueError::new_err(format!("{kind:?} proofs are not verifiable onchain")))
+            }
+        }
+    }
+
+    /// Encodes the proof in the versioned envelope of `ZKMProofWithPublicValues::to_bytes`.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.0.to_bytes())
+    }
+
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        ZKMProofWithPublicValues::from_bytes(bytes)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        self.0.save(path).map_err(runtime_error)
+    }
+
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        ZKMProofWithPublicValues::load(path).map(Self).map_err(runtime_error)
+    }
+}
+
+/// A client proving and verifying the execution of programs, see `zkm_sdk::ProverClient`.
+///
+/// The methods release the GIL while they run, so other Python threads are not blocked.
+#[pyclass(name = "ProverClient", frozen)]
+struct PyProverClient(ProverClient);
+
+#[pymethods]
+impl PyProverClient {
+    /// Creates a client of the prover selected by the `ZKM_PROVER` environment variable.
+    #[new]
+    fn new() -> Self {
+        Self(ProverClient::new())
+    }
+
+    /// Creates a client proving on the CPU.
+    #[staticmethod]
+    fn cpu() -> Self {
+        Self(ProverClient::cpu())
+    }
+
+    /// Creates a client generating mock proofs, for tests.
+    #[staticmethod]
+    fn mock() -> Self {
+        Self(ProverClient::mock())
+    }
+
+    fn setup(&self, py: Python<'_>, elf: &[u8]) -> (PyProvingKey, PyVerifyingKey) {
+        let (pk, vk) = py.allow_threads(|| self.0.setup(elf));
+        (PyProvingKey(pk), PyVerifyingKey(vk))
+    }
+
+    /// Executes the program without proving it, returning its public values and the number of
+    /// instructions it executed.
+    fn execute(
+        &self,
+        py: Python<'_>,
+        elf: &[u8],
+        stdin: &PyStdin,
+    ) -> PyResult<(PyPublicValues, u64)> {
+        let stdin = stdin.0.clone();
+        let (public_values, report) =
+            py.allow_threads(|| self.0.execute(elf, stdin).run()).map_err(runtime_error)?;
+        Ok((PyPublicValues(public_values), report.total_instruction_count()))
+    }
+
+    /// Proves the execution of the program of `pk`, with a `mode` of `core`, `compressed`,
+    /// `plonk` or `groth16`.
+    #[pyo3(signature = (pk, stdin, mode = "core"))]
+    fn prove(
+        &self,
+        py: Python<'_>,
+        pk: &PyProvingKey,
+        stdin: &PyStdin,
+        mode: &str,
+    ) -> PyResult<PyProof> {
+        let with_mode: fn(Prove<'_>) -> Prove<'_> = match mode {
+            "core" => Prove::core,
+            "compressed" => Prove::compressed,
+            "plonk" => Prove::plonk,
+            "groth16" => Prove::groth16,
+            _ => return Err(PyValueError::new_err(format!("unknown proof mode {mode:?}"))),
+        };
+        let stdin = stdin.0.clone();
+        py.allow_threads(|| with_mode(self.0.prove(&pk.0, stdin)).run())
+            .map(PyProof)
+            .map_err(runtime_error)
+    }
+
+    /// Verifies the proof against `vk`, raising a `ValueError` if it is invalid.
+    fn verify(&self, py: Python<'_>, proof: &PyProof, vk: &PyVerifyingKey) -> PyResult<()> {
+        py.allow_threads(|| self.0.verify(&proof.0, &vk.0))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "zkm_sdk")]
+fn zkm_sdk_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyProverClient>()?;
+    m.add_class::<PyStdin>()?;
+    m.add_class::<PyPublicValues>()?;
+    m.add_class::<PyProvingKey>()?;
+    m.add_class::<PyVerifyingKey>()?;
+    m.add_class::<PyProof>()?;
+    Ok(())
+}
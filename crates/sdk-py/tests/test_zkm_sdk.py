@@ -0,0 +1,67 @@
+"""Tests of the Python bindings, run with `pytest` after `maturin develop`.
+
+The tests proving a program read its ELF from the `ZKM_TEST_ELF` environment variable, e.g. the
+fibonacci program of `crates/test-artifacts`, and are skipped when it is not set.
+"""
+
+import os
+
+import pytest
+
+from zkm_sdk import ProverClient, ZKMProofWithPublicValues, ZKMStdin
+
+
+@pytest.fixture
+def elf():
+    path = os.environ.get("ZKM_TEST_ELF")
+    if path is None:
+        pytest.skip("ZKM_TEST_ELF is not set")
+    with open(path, "rb") as f:
+        return f.read()
+
+
+def test_stdin_writes():
+    stdin = ZKMStdin()
+    assert len(stdin) == 0
+    stdin.write_u32(10)
+    stdin.write_bytes(b"abc")
+    stdin.write_slice(b"raw")
+    assert len(stdin) == 3
+
+
+def test_proof_from_invalid_bytes():
+    with pytest.raises(ValueError):
+        ZKMProofWithPublicValues.from_bytes(b"not a proof")
+
+
+def test_mock_prove_and_verify(elf, tmp_path):
+    client = ProverClient.mock()
+    pk, vk = client.setup(elf)
+    assert vk.bytes32().startswith("0x")
+
+    stdin = ZKMStdin()
+    stdin.write_u32(10)
+    public_values, cycles = client.execute(elf, stdin)
+    assert cycles > 0
+
+    proof = client.prove(pk, stdin)
+    assert proof.kind == "core"
+    assert bytes(proof.public_values) == bytes(public_values)
+    assert memoryview(proof.public_values).tobytes() == bytes(public_values)
+    client.verify(proof, vk)
+
+    path = tmp_path / "proof.bin"
+    proof.save(path)
+    loaded = ZKMProofWithPublicValues.load(path)
+    assert loaded.to_bytes() == proof.to_bytes()
+    client.verify(ZKMProofWithPublicValues.from_bytes(proof.to_bytes()), vk)
+
+    with pytest.raises(ValueError):
+        proof.evm_bytes()
+
+
+def test_prove_rejects_unknown_mode(elf):
+    client = ProverClient.mock()
+    pk, _ = client.setup(elf)
+    with pytest.raises(ValueError):
+        client.prove(pk, ZKMStdin(), mode="stark")
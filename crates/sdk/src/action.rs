@@ -1,13 +1,30 @@
-use zkm_core_executor::{ExecutionReport, HookEnv, ZKMContextBuilder};
+use zkm_core_executor::{
+    syscalls::UserSyscallArgs, ExecutionReport, HookEnv, WatchEvent, ZKMContextBuilder,
+    ZKMReduceProof, DEFAULT_PROFILER_SAMPLE_INTERVAL,
+};
 use zkm_core_machine::io::ZKMStdin;
 use zkm_primitives::io::ZKMPublicValues;
-use zkm_prover::{components::DefaultProverComponents, ZKMProvingKey};
+use zkm_prover::{components::DefaultProverComponents, InnerSC, ZKMProvingKey};
 
-use anyhow::{Ok, Result};
-use std::time::Duration;
-use zkm_stark::{ZKMCoreOpts, ZKMProverOpts};
+use anyhow::{anyhow, Ok, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    ops::Range,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, SystemTime},
+};
+use thiserror::Error;
+use zkm_prover::{HashableKey, ZKMVerifyingKey};
+use zkm_stark::{Progress, ProgressHandler, ZKMCoreOpts, ZKMProverOpts};
 
-use crate::{provers::ProofOpts, Prover, ZKMProofKind, ZKMProofWithPublicValues};
+use crate::{
+    metrics::{BackendReport, MetricsRecorder, ProvingMetrics},
+    plan::{PlanBackend, PlanBudget, ProofPlan, ProofTarget},
+    provers::ProofOpts,
+    Prover, ZKMProofKind, ZKMProofWithPublicValues,
+};
 
 /// Builder to prepare and configure execution of a program on an input.
 /// May be run with [Self::run].
@@ -16,6 +33,27 @@ pub struct Execute<'a> {
     context_builder: ZKMContextBuilder<'a>,
     elf: &'a [u8],
     stdin: ZKMStdin,
+    check_determinism: bool,
+}
+
+/// The number of cycles between the state digests compared by [Execute::check_determinism].
+pub const DETERMINISM_CHECK_INTERVAL: u64 = 1 << 16;
+
+/// An error returned by [Execute::run] when [Execute::check_determinism] finds that two runs of
+/// the same program on the same input diverged.
+#[derive(Error, Debug)]
+pub enum DeterminismError {
+    #[error(
+        "the two runs consumed different input bytes; a hook, hint or syscall handler returned \
+         nondeterministic data"
+    )]
+    Input,
+    #[error("the execution state diverged between cycles {start} and {end}")]
+    State { start: u64, end: u64 },
+    #[error("the cycle counts differ: {0} != {1}")]
+    Cycles(u64, u64),
+    #[error("the public values differ")]
+    PublicValues,
 }
 
 impl<'a> Execute<'a> {
@@ -28,14 +66,45 @@ impl<'a> Execute<'a> {
         elf: &'a [u8],
         stdin: ZKMStdin,
     ) -> Self {
-        Self { prover, elf, stdin, context_builder: Default::default() }
+        Self { prover, elf, stdin, context_builder: Default::default(), check_determinism: false }
     }
 
     /// Execute the program on the input, consuming the built action `self`.
     pub fn run(self) -> Result<(ZKMPublicValues, ExecutionReport)> {
-        let Self { prover, elf, stdin, mut context_builder } = self;
+        let Self { prover, elf, stdin, mut context_builder, check_determinism } = self;
+        if !check_determinism {
+            let context = context_builder.build();
+            return Ok(prover.zkm_prover().execute(elf, &stdin, context)?);
+        }
+
+        let context = context_builder
+            .input_transcript(true)
+            .state_digests(DETERMINISM_CHECK_INTERVAL)
+            .build();
+        let first = prover.zkm_prover().execute(elf, &stdin, context.clone())?;
+        let second = prover.zkm_prover().execute(elf, &stdin, context)?;
+        compare_runs(&first, &second)?;
+        Ok(first)
+    }
+
+    /// Execute the program on the input once, then fork the execution once for every input in
+    /// `suffixes`, consuming the built action `self`.
+    ///
+    /// The input of the action is the prefix shared by all forks. The program runs until it
+    /// first reads past it, and every fork resumes from there with its suffix appended to the
+    /// input, so the work on the prefix is done only once. Returns the result of every fork, in
+    /// order.
+    pub fn run_forks(
+        self,
+        suffixes: impl IntoIterator<Item = ZKMStdin>,
+    ) -> Result<Vec<Result<(ZKMPublicValues, ExecutionReport)>>> {
+        let Self { prover, elf, stdin, mut context_builder, check_determinism } = self;
+        if check_determinism {
+            return Err(anyhow!("forked executions cannot check determinism"));
+        }
         let context = context_builder.build();
-        Ok(prover.zkm_prover().execute(elf, &stdin, context)?)
+        let results = prover.zkm_prover().execute_forks(elf, &stdin, suffixes, context)?;
+        Ok(results.into_iter().map(|result| result.map_err(Into::into)).collect())
     }
 
     /// Add a runtime [Hook](super::Hook) into the context.
@@ -52,6 +121,32 @@ impl<'a> Execute<'a> {
         self
     }
 
+    /// Add a typed runtime hook into the context.
+    ///
+    /// The program sends a request of type `Req` to the hook by calling
+    /// [`zkm_zkvm::io::call_hook`] with `fd`, and reads back the response of type `Resp`.
+    pub fn with_typed_hook<Req: DeserializeOwned, Resp: Serialize>(
+        mut self,
+        fd: u32,
+        f: impl FnMut(&HookEnv, Req) -> Resp + Send + Sync + 'a,
+    ) -> Self {
+        self.context_builder.typed_hook(fd, f);
+        self
+    }
+
+    /// Add a handler for the user-defined syscalls with codes in `code_range`.
+    ///
+    /// See [`ZKMContextBuilder::with_syscall_handler`]. The data returned by the handler is not
+    /// constrained by the proof, so the program must check it.
+    pub fn with_syscall_handler(
+        mut self,
+        code_range: Range<u32>,
+        f: impl FnMut(HookEnv, UserSyscallArgs) -> Vec<Vec<u8>> + Send + Sync + 'a,
+    ) -> Self {
+        self.context_builder.with_syscall_handler(code_range, f);
+        self
+    }
+
     /// Avoid registering the default hooks in the runtime.
     ///
     /// It is not necessary to call this to override hooks --- instead, simply
@@ -70,11 +165,144 @@ impl<'a> Execute<'a> {
         self
     }
 
+    /// Set the maximum number of cpu cycles of every run of the cycle tracker region `region`.
+    ///
+    /// If the budget is exceeded, execution will return
+    /// [`zkm_core_executor::ExecutionError::RegionBudgetExceeded`].
+    pub fn max_cycles_between_markers(
+        mut self,
+        region: impl Into<String>,
+        max_cycles: u64,
+    ) -> Self {
+        self.context_builder.max_cycles_between_markers(region, max_cycles);
+        self
+    }
+
+    /// Set the highest address, exclusive, which the program may access below its stack.
+    ///
+    /// If the limit is exceeded, execution will return
+    /// [`zkm_core_executor::ExecutionError::OutOfMemory`]. The peak usage is reported in
+    /// [`ExecutionReport::memory_usage`].
+    pub fn max_memory(mut self, max_memory: u32) -> Self {
+        self.context_builder.max_memory(max_memory);
+        self
+    }
+
+    /// Set the maximum size of the stack of the program in bytes.
+    ///
+    /// If the limit is exceeded, execution will return
+    /// [`zkm_core_executor::ExecutionError::StackOverflow`]. The peak usage is reported in
+    /// [`ExecutionReport::memory_usage`].
+    pub fn max_stack_size(mut self, max_stack_size: u32) -> Self {
+        self.context_builder.max_stack_size(max_stack_size);
+        self
+    }
+
     /// Skip deferred proof verification.
     pub fn set_skip_deferred_proof_verification(mut self, value: bool) -> Self {
         self.context_builder.set_skip_deferred_proof_verification(value);
         self
     }
+
+    /// Seed the random number generator of the program, used by `getrandom` when the program is
+    /// built with the `seeded-rng` feature of `zkm-zkvm`. The seed is committed with the public
+    /// values.
+    pub fn with_rng_seed(mut self, seed: [u8; 32]) -> Self {
+        self.context_builder.with_rng_seed(seed);
+        self
+    }
+
+    /// Start the deterministic clock read with `zkm_zkvm::lib::time::now` at `time`. The time base
+    /// is committed with the public values once the program reads the clock.
+    pub fn with_time_base(mut self, time: SystemTime) -> Self {
+        self.context_builder.with_time_base(time);
+        self
+    }
+
+    /// Compute a digest of all input bytes consumed by the program.
+    ///
+    /// The digest is returned as [`ExecutionReport::input_digest`].
+    pub fn input_transcript(mut self) -> Self {
+        self.context_builder.input_transcript(true);
+        self
+    }
+
+    /// Estimate the number of events of each chip in every shard.
+    ///
+    /// The estimates are returned as [`ExecutionReport::shard_event_counts`], from which
+    /// [`zkm_core_machine::shape::CoreShapeConfig::shape_coverage`] checks that the shards fit
+    /// the allowed shapes.
+    pub fn cost_estimation(mut self) -> Self {
+        self.context_builder.cost_estimation(true);
+        self
+    }
+
+    /// Profile the cycles of the program, writing the cycles spent in every guest call stack to
+    /// `output` in the folded stack format.
+    ///
+    /// The file can be rendered as a flamegraph with `inferno-flamegraph` or opened in
+    /// speedscope. The guest should be built with debug information to attribute cycles to
+    /// inlined functions. See [`ZKMContextBuilder::profiling`].
+    pub fn with_profiling(mut self, output: impl Into<PathBuf>) -> Self {
+        self.context_builder.profiling(self.elf, output, DEFAULT_PROFILER_SAMPLE_INTERVAL);
+        self
+    }
+
+    /// Record which lines of the guest source the program executes.
+    ///
+    /// The coverage is returned as [`ExecutionReport::coverage`], and can be written as an lcov
+    /// tracefile with [`zkm_core_executor::Coverage::write_lcov`]. The guest must be built with
+    /// debug information.
+    pub fn with_coverage(mut self) -> Self {
+        self.context_builder.coverage(self.elf);
+        self
+    }
+
+    /// Call `f` on every read and write by the program of a word of memory overlapping `range`.
+    /// See [`ZKMContextBuilder::watchpoint`].
+    pub fn with_watchpoint(
+        mut self,
+        range: Range<u32>,
+        f: impl FnMut(&WatchEvent) + Send + Sync + 'a,
+    ) -> Self {
+        self.context_builder.watchpoint(range, f);
+        self
+    }
+
+    /// Run the program twice with an identical context and check that both runs agree.
+    ///
+    /// The runs are compared on the consumed input bytes, a rolling digest of the execution state
+    /// taken every [DETERMINISM_CHECK_INTERVAL] cycles, the cycle count and the public values.
+    /// On a mismatch, [Self::run] returns a [DeterminismError] locating the divergence.
+    pub fn check_determinism(mut self) -> Self {
+        self.check_determinism = true;
+        self
+    }
+}
+
+/// Compares two runs of the same program on the same input, returning the first difference.
+fn compare_runs(
+    (first_values, first): &(ZKMPublicValues, ExecutionReport),
+    (second_values, second): &(ZKMPublicValues, ExecutionReport),
+) -> Result<(), DeterminismError> {
+    if first.input_digest != second.input_digest {
+        return Err(DeterminismError::Input);
+    }
+    let mut start = 0;
+    for (&(clk, a), &(_, b)) in first.state_digests.iter().zip(&second.state_digests) {
+        if a != b {
+            return Err(DeterminismError::State { start, end: clk });
+        }
+        start = clk;
+    }
+    let cycles = |report: &ExecutionReport| report.state_digests.last().map_or(0, |&(clk, _)| clk);
+    if cycles(first) != cycles(second) {
+        return Err(DeterminismError::Cycles(cycles(first), cycles(second)));
+    }
+    if first_values.as_slice() != second_values.as_slice() {
+        return Err(DeterminismError::PublicValues);
+    }
+    Result::Ok(())
 }
 
 /// Builder to prepare and configure proving execution of a program on an input.
@@ -87,7 +315,9 @@ pub struct Prove<'a> {
     stdin: ZKMStdin,
     core_opts: ZKMCoreOpts,
     recursion_opts: ZKMCoreOpts,
+    progress: Progress,
     timeout: Option<Duration>,
+    backend: BackendReport,
 }
 
 impl<'a> Prove<'a> {
@@ -108,12 +338,40 @@ impl<'a> Prove<'a> {
             context_builder: Default::default(),
             core_opts: ZKMCoreOpts::default(),
             recursion_opts: ZKMCoreOpts::recursion(),
+            progress: Progress::default(),
             timeout: None,
+            backend: BackendReport::default(),
         }
     }
 
     /// Prove the execution of the program on the input, consuming the built action `self`.
     pub fn run(self) -> Result<ZKMProofWithPublicValues> {
+        self.run_impl().map(|(proof, _)| proof)
+    }
+
+    /// Prove the execution of the program on the input as in [Self::run], also returning the
+    /// [ProvingMetrics] of the proof.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProverClient, ZKMStdin};
+    ///
+    /// let client = ProverClient::cpu();
+    /// let (pk, _) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let (proof, metrics) = client.prove(&pk, ZKMStdin::new()).compressed().run_with_metrics().unwrap();
+    /// println!("{}", metrics.to_json());
+    /// ```
+    pub fn run_with_metrics(mut self) -> Result<(ZKMProofWithPublicValues, ProvingMetrics)> {
+        let (prover, kind) = (self.prover, self.kind);
+        let recorder = MetricsRecorder::start(prover.zkm_prover());
+        self.progress = recorder.progress(self.progress);
+        self.backend = recorder.backend();
+        let (proof, cycles) = self.run_impl()?;
+        let metrics = recorder.finish(prover.zkm_prover(), prover.id(), kind, cycles);
+        Ok((proof, metrics))
+    }
+
+    fn run_impl(self) -> Result<(ZKMProofWithPublicValues, u64)> {
         let Self {
             prover,
             kind,
@@ -122,16 +380,19 @@ impl<'a> Prove<'a> {
             mut context_builder,
             core_opts,
             recursion_opts,
+            progress,
             timeout,
+            backend,
         } = self;
         let opts = ZKMProverOpts { core_opts, recursion_opts };
-        let proof_opts = ProofOpts { zkm_prover_opts: opts, timeout };
-        let context = context_builder.build();
+        let proof_opts = ProofOpts { zkm_prover_opts: opts, timeout, backend };
+        let mut context = context_builder.build();
+        context.progress = progress;
 
         // Dump the program and stdin to files for debugging if `ZKM_DUMP` is set.
         crate::utils::zkm_dump(&pk.elf, &stdin);
 
-        Ok(prover.prove_impl(pk, stdin, proof_opts, context, kind, None)?.0)
+        prover.prove_impl(pk, stdin, proof_opts, context, kind, None)
     }
 
     /// Set the proof kind to the core mode. This is the default.
@@ -164,6 +425,21 @@ impl<'a> Prove<'a> {
         self
     }
 
+    /// Add a compressed proof of the program with the verifying key `vk`, to be verified by the
+    /// program with `zkm_zkvm::lib::verify::verify_zkm_proof`.
+    ///
+    /// Deferred proofs are not read by the program: they are witnessed by the prover, and
+    /// verified recursively when the proof of the program is compressed. The program must read
+    /// the vkey hash and public values of each proof from the input.
+    pub fn with_deferred_proof(
+        mut self,
+        proof: ZKMReduceProof<InnerSC>,
+        vk: &ZKMVerifyingKey,
+    ) -> Self {
+        self.stdin.write_proof(proof, vk.vk.clone());
+        self
+    }
+
     /// Add a runtime [Hook](super::Hook) into the context.
     ///
     /// Hooks may be invoked from within Ziren by writing to the specified file descriptor `fd`
@@ -178,6 +454,19 @@ impl<'a> Prove<'a> {
         self
     }
 
+    /// Add a typed runtime hook into the context.
+    ///
+    /// The program sends a request of type `Req` to the hook by calling
+    /// [`zkm_zkvm::io::call_hook`] with `fd`, and reads back the response of type `Resp`.
+    pub fn with_typed_hook<Req: DeserializeOwned, Resp: Serialize>(
+        mut self,
+        fd: u32,
+        f: impl FnMut(&HookEnv, Req) -> Resp + Send + Sync + 'a,
+    ) -> Self {
+        self.context_builder.typed_hook(fd, f);
+        self
+    }
+
     /// Avoid registering the default hooks in the runtime.
     ///
     /// It is not necessary to call this to override hooks --- instead, simply
@@ -205,6 +494,19 @@ impl<'a> Prove<'a> {
         self
     }
 
+    /// Prove with the options of [`ZKMProverOpts::deterministic`], so that the proof of the
+    /// program and its input is the same on every machine and every run.
+    ///
+    /// This replaces the options set so far. The local prover must also run on
+    /// [`crate::ProverThreadPool::single_threaded`], set with
+    /// [`crate::ProverClientBuilder::thread_pool`]. Groth16 and Plonk proofs stay randomized.
+    pub fn deterministic(mut self) -> Self {
+        let opts = ZKMProverOpts::deterministic();
+        self.core_opts = opts.core_opts;
+        self.recursion_opts = opts.recursion_opts;
+        self
+    }
+
     /// Set the maximum number of cpu cycles to use for execution.
     ///
     /// If the cycle limit is exceeded, execution will return
@@ -214,6 +516,46 @@ impl<'a> Prove<'a> {
         self
     }
 
+    /// Set the maximum number of cpu cycles of every run of the cycle tracker region `region`.
+    ///
+    /// If the budget is exceeded, execution will return
+    /// [`zkm_core_executor::ExecutionError::RegionBudgetExceeded`].
+    pub fn max_cycles_between_markers(
+        mut self,
+        region: impl Into<String>,
+        max_cycles: u64,
+    ) -> Self {
+        self.context_builder.max_cycles_between_markers(region, max_cycles);
+        self
+    }
+
+    /// Set the handler of the progress events of the proof: the execution checkpoints, the proven
+    /// shards, the proofs of the compress tree and the start of the final proofs.
+    ///
+    /// The events are only reported by the local provers.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProgressEvent, ProverClient, ZKMStdin};
+    ///
+    /// let client = ProverClient::cpu();
+    /// let (pk, _) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let proof = client
+    ///     .prove(&pk, ZKMStdin::new())
+    ///     .compressed()
+    ///     .progress(|event: ProgressEvent| {
+    ///         if let Some(fraction) = event.fraction() {
+    ///             println!("{event:?}: {:.0}%", fraction * 100.0);
+    ///         }
+    ///     })
+    ///     .run()
+    ///     .unwrap();
+    /// ```
+    pub fn progress(mut self, handler: impl ProgressHandler + 'static) -> Self {
+        self.progress = Progress::new(handler);
+        self
+    }
+
     /// Set the timeout for the proof's generation.
     ///
     /// This parameter is only used when the prover is run in network mode.
@@ -227,4 +569,458 @@ impl<'a> Prove<'a> {
         self.context_builder.set_skip_deferred_proof_verification(value);
         self
     }
+
+    /// Seed the random number generator of the program, used by `getrandom` when the program is
+    /// built with the `seeded-rng` feature of `zkm-zkvm`. The seed is committed with the public
+    /// values.
+    pub fn with_rng_seed(mut self, seed: [u8; 32]) -> Self {
+        self.context_builder.with_rng_seed(seed);
+        self
+    }
+
+    /// Start the deterministic clock read with `zkm_zkvm::lib::time::now` at `time`. The time base
+    /// is committed with the public values once the program reads the clock.
+    pub fn with_time_base(mut self, time: SystemTime) -> Self {
+        self.context_builder.with_time_base(time);
+        self
+    }
+}
+
+/// Builder to prepare and configure the planning of the proof of a program on an input.
+/// May be run with [Self::run].
+pub struct Plan<'a> {
+    prover: &'a dyn Prover<DefaultProverComponents>,
+    pk: &'a ZKMProvingKey,
+    stdin: ZKMStdin,
+    target: ProofTarget,
+    budget: PlanBudget,
+    backends: Vec<PlanBackend>,
+}
+
+impl<'a> Plan<'a> {
+    /// Prepare to plan the proof of the execution of the given program with the given input.
+    ///
+    /// Prefer using [ProverClient::plan](super::ProverClient::plan).
+    /// See there for more documentation.
+    pub fn new(
+        prover: &'a dyn Prover<DefaultProverComponents>,
+        pk: &'a ZKMProvingKey,
+        stdin: ZKMStdin,
+    ) -> Self {
+        Self {
+            prover,
+            pk,
+            stdin,
+            target: ProofTarget::Core,
+            budget: PlanBudget { max_time: None, max_memory: None },
+            backends: PlanBackend::ALL.to_vec(),
+        }
+    }
+
+    /// Execute the program with cost estimation and plan its proof, consuming the built action
+    /// `self`.
+    ///
+    /// Returns a [crate::plan::PlanError] listing the pipelines considered if none fits the
+    /// budget.
+    pub fn run(self) -> Result<ProofPlan> {
+        let Self { prover, pk, stdin, target, budget, backends } = self;
+        let estimate = prover.zkm_prover().estimate(&pk.elf, &stdin)?;
+        Ok(ProofPlan::new(estimate, target, budget, &backends)?)
+    }
+
+    /// Set the proof the plan must produce. Defaults to [ProofTarget::Core].
+    pub fn target(mut self, target: ProofTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set the maximum time the proof may take.
+    pub fn max_time(mut self, max_time: Duration) -> Self {
+        self.budget.max_time = Some(max_time);
+        self
+    }
+
+    /// Set the maximum peak memory the proof may take on the local machine, in bytes.
+    pub fn max_memory(mut self, max_memory: u64) -> Self {
+        self.budget.max_memory = Some(max_memory);
+        self
+    }
+
+    /// Set the backends the plan may use, in the order ties are broken. Defaults to
+    /// [PlanBackend::ALL].
+    pub fn backends(mut self, backends: &[PlanBackend]) -> Self {
+        self.backends = backends.to_vec();
+        self
+    }
+}
+
+/// The default number of item proofs of [ProveParallelMap].
+pub const DEFAULT_PARALLEL_MAP_CHUNKS: usize = 4;
+
+/// Builder to prepare and configure proving a program over a set of independent items, split
+/// across several proofs which are aggregated into one.
+/// May be run with [Self::run].
+///
+/// The items are split into contiguous chunks. The item program is proven once per chunk, and
+/// reads its chunk with `zkm_zkvm::io::read::<Vec<T>>()`. The aggregation program then verifies
+/// the item proofs as deferred proofs. It reads the verifying key hashes of the item proofs with
+/// `zkm_zkvm::io::read::<Vec<[u32; 8]>>()` and their public values with
+/// `zkm_zkvm::io::read::<Vec<Vec<u8>>>()`, in chunk order, and calls
+/// `zkm_zkvm::lib::verify::verify_zkm_proof` for each of them, as in `examples/aggregation`.
+pub struct ProveParallelMap<'a, T> {
+    prover: &'a dyn Prover<DefaultProverComponents>,
+    items: &'a [T],
+    elf_item: &'a [u8],
+    elf_aggregate: &'a [u8],
+    kind: ZKMProofKind,
+    num_chunks: usize,
+    max_concurrency: Option<usize>,
+}
+
+/// The output of [ProveParallelMap::run].
+pub struct ParallelMapProof {
+    /// The proof of the aggregation program.
+    pub proof: ZKMProofWithPublicValues,
+    /// The verifying key of the aggregation program.
+    pub vk: ZKMVerifyingKey,
+    /// The verifying key of the item program.
+    pub item_vk: ZKMVerifyingKey,
+    /// The item proofs, in chunk order.
+    pub item_proofs: Vec<ZKMProofWithPublicValues>,
+}
+
+impl<'a, T: Serialize + Sync> ProveParallelMap<'a, T> {
+    /// Prepare to prove the item program over `items`, and aggregate the proofs with the
+    /// aggregation program.
+    ///
+    /// Prefer using [ProverClient::prove_parallel_map](super::ProverClient::prove_parallel_map).
+    /// See there for more documentation.
+    pub fn new(
+        prover: &'a dyn Prover<DefaultProverComponents>,
+        items: &'a [T],
+        elf_item: &'a [u8],
+        elf_aggregate: &'a [u8],
+    ) -> Self {
+        Self {
+            prover,
+            items,
+            elf_item,
+            elf_aggregate,
+            kind: ZKMProofKind::Compressed,
+            num_chunks: DEFAULT_PARALLEL_MAP_CHUNKS,
+            max_concurrency: None,
+        }
+    }
+
+    /// Prove the items and aggregate the proofs, consuming the built action `self`.
+    pub fn run(self) -> Result<ParallelMapProof> {
+        let Self { prover, items, elf_item, elf_aggregate, kind, num_chunks, max_concurrency } =
+            self;
+        if items.is_empty() {
+            return Err(anyhow!("no items to prove"));
+        }
+
+        let (item_pk, item_vk) = prover.setup(elf_item);
+        let (aggregate_pk, vk) = prover.setup(elf_aggregate);
+
+        // Prove the chunks in parallel, then aggregate their proofs.
+        let chunks = chunk_ranges(items.len(), num_chunks);
+        let item_proofs = prove_concurrently(chunks.len(), max_concurrency, |index| {
+            let mut stdin = ZKMStdin::new();
+            stdin.write(&&items[chunks[index].clone()]);
+            Prove::new(prover, &item_pk, stdin)
+                .compressed()
+                .run()
+                .map_err(|e| e.context(format!("failed to prove chunk {index}")))
+        })?;
+
+        let proof = aggregate(prover, &aggregate_pk, &item_proofs, &item_vk, kind)?;
+
+        Ok(ParallelMapProof { proof, vk, item_vk, item_proofs })
+    }
+
+    /// Set the number of chunks the items are split into, each proven separately.
+    ///
+    /// Defaults to [DEFAULT_PARALLEL_MAP_CHUNKS]. There are fewer chunks if there are fewer
+    /// items.
+    pub fn chunks(mut self, num_chunks: usize) -> Self {
+        assert!(num_chunks > 0, "the number of chunks must be positive");
+        self.num_chunks = num_chunks;
+        self
+    }
+
+    /// Set the maximum number of chunks proven at the same time.
+    ///
+    /// Defaults to proving all chunks at the same time. Every concurrent proof holds its own
+    /// traces in memory, so this should be lowered when proving locally.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Set the proof kind of the aggregation proof to the compressed mode. This is the default.
+    pub fn compressed(mut self) -> Self {
+        self.kind = ZKMProofKind::Compressed;
+        self
+    }
+
+    /// Set the proof mode of the aggregation proof to the plonk bn254 mode.
+    pub fn plonk(mut self) -> Self {
+        self.kind = ZKMProofKind::Plonk;
+        self
+    }
+
+    /// Set the proof mode of the aggregation proof to the groth16 bn254 mode.
+    pub fn groth16(mut self) -> Self {
+        self.kind = ZKMProofKind::Groth16;
+        self
+    }
+}
+
+/// The default number of proofs of [ProveBatch] generated at the same time.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 1;
+
+/// Builder to prepare and configure proving one program over many inputs, sharing its proving
+/// key.
+/// May be run with [Self::run].
+///
+/// The proofs are generated in parallel, up to a concurrency limit. With [Self::aggregate], they
+/// are also aggregated into a single proof by an aggregation program, which verifies them as
+/// deferred proofs. It reads their verifying key hashes with
+/// `zkm_zkvm::io::read::<Vec<[u32; 8]>>()` and their public values with
+/// `zkm_zkvm::io::read::<Vec<Vec<u8>>>()`, in input order, as in `examples/aggregation`.
+pub struct ProveBatch<'a> {
+    prover: &'a dyn Prover<DefaultProverComponents>,
+    pk: &'a ZKMProvingKey,
+    stdins: Vec<ZKMStdin>,
+    kind: ZKMProofKind,
+    max_concurrency: usize,
+    aggregate: Option<(&'a ZKMProvingKey, ZKMProofKind)>,
+}
+
+/// The output of [ProveBatch::run].
+pub struct BatchProof {
+    /// The proofs of the inputs, in input order.
+    pub proofs: Vec<ZKMProofWithPublicValues>,
+    /// The proof of the aggregation program, if the proofs were aggregated.
+    pub aggregated: Option<ZKMProofWithPublicValues>,
+}
+
+impl<'a> ProveBatch<'a> {
+    /// Prepare to prove the program of `pk` over each of `stdins`.
+    ///
+    /// Prefer using [ProverClient::prove_batch](super::ProverClient::prove_batch).
+    /// See there for more documentation.
+    pub fn new(
+        prover: &'a dyn Prover<DefaultProverComponents>,
+        pk: &'a ZKMProvingKey,
+        stdins: Vec<ZKMStdin>,
+    ) -> Self {
+        Self {
+            prover,
+            pk,
+            stdins,
+            kind: ZKMProofKind::Core,
+            max_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            aggregate: None,
+        }
+    }
+
+    /// Prove the inputs, and aggregate the proofs if requested, consuming the built action
+    /// `self`.
+    pub fn run(self) -> Result<BatchProof> {
+        let Self { prover, pk, stdins, kind, max_concurrency, aggregate: aggregation } = self;
+        if stdins.is_empty() {
+            return Err(anyhow!("no inputs to prove"));
+        }
+
+        // Deferred proofs must be compressed.
+        let kind = if aggregation.is_some() { ZKMProofKind::Compressed } else { kind };
+        let proofs = prove_concurrently(stdins.len(), Some(max_concurrency), |index| {
+            let mut prove = Prove::new(prover, pk, stdins[index].clone());
+            prove.kind = kind;
+            prove.run().map_err(|e| e.context(format!("failed to prove input {index}")))
+        })?;
+
+        let aggregated = aggregation
+            .map(|(aggregate_pk, kind)| aggregate(prover, aggregate_pk, &proofs, &pk.vk, kind))
+            .transpose()?;
+
+        Ok(BatchProof { proofs, aggregated })
+    }
+
+    /// Set the maximum number of inputs proven at the same time.
+    ///
+    /// Defaults to [DEFAULT_BATCH_CONCURRENCY]. Every concurrent proof holds its own traces in
+    /// memory, so this should only be raised when the machine has memory to spare, or with the
+    /// network prover.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "the concurrency limit must be positive");
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Set the proof mode of the proofs of the inputs to the core mode. This is the default.
+    pub fn core(mut self) -> Self {
+        self.kind = ZKMProofKind::Core;
+        self
+    }
+
+    /// Set the proof mode of the proofs of the inputs to the compressed mode.
+    pub fn compressed(mut self) -> Self {
+        self.kind = ZKMProofKind::Compressed;
+        self
+    }
+
+    /// Set the proof mode of the proofs of the inputs to the plonk bn254 mode.
+    pub fn plonk(mut self) -> Self {
+        self.kind = ZKMProofKind::Plonk;
+        self
+    }
+
+    /// Set the proof mode of the proofs of the inputs to the groth16 bn254 mode.
+    pub fn groth16(mut self) -> Self {
+        self.kind = ZKMProofKind::Groth16;
+        self
+    }
+
+    /// Aggregate the proofs into a single compressed proof of the aggregation program of
+    /// `aggregate_pk`, returned in [BatchProof::aggregated].
+    ///
+    /// The proofs of the inputs are then generated in the compressed mode, whatever their
+    /// configured mode, as deferred proofs must be compressed.
+    pub fn aggregate(self, aggregate_pk: &'a ZKMProvingKey) -> Self {
+        self.aggregate_with_kind(aggregate_pk, ZKMProofKind::Compressed)
+    }
+
+    /// Aggregate the proofs as in [Self::aggregate], into a proof of the given kind, e.g.
+    /// [ZKMProofKind::Groth16] to verify the aggregation onchain.
+    pub fn aggregate_with_kind(
+        mut self,
+        aggregate_pk: &'a ZKMProvingKey,
+        kind: ZKMProofKind,
+    ) -> Self {
+        self.aggregate = Some((aggregate_pk, kind));
+        self
+    }
+}
+
+/// Runs `prove` for the indices `0..num_tasks` on a pool of at most `max_concurrency` workers,
+/// each taking the next index, until all are proven or one fails. Returns the proofs in index
+/// order.
+fn prove_concurrently<T: Send>(
+    num_tasks: usize,
+    max_concurrency: Option<usize>,
+    prove: impl Fn(usize) -> Result<T> + Sync,
+) -> Result<Vec<T>> {
+    let workers = max_concurrency.unwrap_or(num_tasks).clamp(1, num_tasks.max(1));
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let mut proofs = thread::scope(|s| {
+        let handles = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut proofs = Vec::new();
+                    while !failed.load(Ordering::SeqCst) {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        if index >= num_tasks {
+                            break;
+                        }
+                        match prove(index) {
+                            Result::Ok(proof) => proofs.push((index, proof)),
+                            Err(e) => {
+                                failed.store(true, Ordering::SeqCst);
+                                return Err(e);
+                            }
+                        }
+                    }
+                    Ok(proofs)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Result<Vec<_>>>()
+    })?
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    proofs.sort_by_key(|(index, _)| *index);
+    Ok(proofs.into_iter().map(|(_, proof)| proof).collect())
+}
+
+/// Proves the program of `aggregate_pk` with the compressed `proofs` of the program of `vk` as
+/// deferred proofs, writing their verifying key hashes and public values to its input.
+fn aggregate(
+    prover: &dyn Prover<DefaultProverComponents>,
+    aggregate_pk: &ZKMProvingKey,
+    proofs: &[ZKMProofWithPublicValues],
+    vk: &ZKMVerifyingKey,
+    kind: ZKMProofKind,
+) -> Result<ZKMProofWithPublicValues> {
+    let mut stdin = ZKMStdin::new();
+    stdin.write::<Vec<[u32; 8]>>(&vec![vk.hash_u32(); proofs.len()]);
+    stdin.write::<Vec<Vec<u8>>>(&proofs.iter().map(|proof| proof.public_values.to_vec()).collect());
+    let mut prove = Prove::new(prover, aggregate_pk, stdin);
+    for proof in proofs {
+        let proof =
+            proof.proof.clone().try_as_compressed().ok_or_else(|| {
+                anyhow!("the prover did not return a compressed proof to aggregate")
+            })?;
+        prove = prove.with_deferred_proof(*proof, vk);
+    }
+    prove.kind = kind;
+    prove.run()
+}
+
+/// Splits `len` items into at most `num_chunks` contiguous ranges of nearly equal length.
+fn chunk_ranges(len: usize, num_chunks: usize) -> Vec<Range<usize>> {
+    let num_chunks = num_chunks.min(len);
+    (0..num_chunks).map(|i| (i * len / num_chunks)..((i + 1) * len / num_chunks)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_ranges() {
+        assert_eq!(chunk_ranges(10, 4), vec![0..2, 2..5, 5..7, 7..10]);
+        assert_eq!(chunk_ranges(3, 4), vec![0..1, 1..2, 2..3]);
+        assert_eq!(chunk_ranges(8, 1), vec![0..8]);
+    }
+
+    #[test]
+    fn test_prove_concurrently() {
+        let proofs = prove_concurrently(10, Some(3), |index| Ok(index * 2)).unwrap();
+        assert_eq!(proofs, (0..10).map(|index| index * 2).collect::<Vec<_>>());
+
+        let proven = AtomicUsize::new(0);
+        let result = prove_concurrently(10, Some(1), |index| {
+            proven.fetch_add(1, Ordering::SeqCst);
+            if index == 3 {
+                return Err(anyhow!("failed"));
+            }
+            Ok(index)
+        });
+        assert!(result.is_err());
+        assert_eq!(proven.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_with_deferred_proof() {
+        let client = crate::ProverClient::mock();
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let proof = client.prove(&pk, ZKMStdin::new()).compressed().run().unwrap();
+        let reduce_proof = *proof.proof.try_as_compressed().unwrap();
+
+        // The proof is witnessed by the prover, not written to the input the program reads.
+        let prove = client.prove(&pk, ZKMStdin::new()).with_deferred_proof(reduce_proof, &vk);
+        assert_eq!(prove.stdin.proofs.len(), 1);
+        assert!(prove.stdin.buffer.is_empty());
+
+        // Only compressed proofs can be deferred.
+        let core_proof = client.prove(&pk, ZKMStdin::new()).core().run().unwrap();
+        let err =
+            client.compress_deferred(&pk, ZKMStdin::new(), vec![(core_proof, vk)]).unwrap_err();
+        assert!(err.to_string().contains("proof 0 is not a compressed proof"));
+    }
 }
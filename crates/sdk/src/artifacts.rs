@@ -0,0 +1,5 @@
+//! # Ziren Artifacts
+//!
+//! Code generation from the Ziren circuit artifacts.
+
+pub mod solidity;
@@ -0,0 +1,367 @@
+//! Generation of Solidity verifier contracts for Ziren programs.
+//!
+//! [generate] combines the verifier contracts built alongside the Plonk or Groth16 circuit
+//! artifacts with a `ZKMProgramVerifier` contract pinning the verification key of one program, so
+//! the output can be deployed as is. [SolidityVerifier::simulate] compiles the contracts with
+//! `solc`, deploys them to a local `anvil` node and checks a proof against them.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::{
+    abi::{encode, Token},
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::TransactionRequest,
+    utils::{id, Anvil},
+};
+use zkm_prover::{HashableKey, ZKMVerifyingKey};
+use zkm_recursion_gnark_ffi::IZKM_VERIFIER_SOL;
+
+use crate::{
+    install::{groth16_circuit_artifacts_dir, plonk_circuit_artifacts_dir},
+    PublicValuesHash, ZKMProof, ZKMProofWithPublicValues, ZKMPublicValues, ZKM_CIRCUIT_VERSION,
+};
+
+/// The name of the generated contract verifying proofs of a single program.
+pub const PROGRAM_VERIFIER_CONTRACT: &str = "ZKMProgramVerifier";
+
+/// The signature of `ZKMProgramVerifier.verifyProgramProof`.
+pub const VERIFY_PROGRAM_PROOF_SIGNATURE: &str = "verifyProgramProof(bytes,bytes)";
+
+/// The signature of `ZKMProgramVerifier.verifyProgramProofKeccak`.
+pub const VERIFY_PROGRAM_PROOF_KECCAK_SIGNATURE: &str = "verifyProgramProofKeccak(bytes,bytes)";
+
+/// The signature of `ZKMProgramVerifier.verifyProgramProofWithDigest`.
+pub const VERIFY_PROGRAM_PROOF_WITH_DIGEST_SIGNATURE: &str =
+    "verifyProgramProofWithDigest(bytes32,bytes)";
+
+const PROGRAM_VERIFIER_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {ZKMVerifier} from "./{PROOF_SYSTEM_DIR}/ZKMVerifier{PROOF_SYSTEM}.sol";
+
+/// @title Ziren Program Verifier
+/// @notice This contract verifies {PROOF_SYSTEM} proofs of a single Ziren program, generated
+/// for Ziren {ZKM_CIRCUIT_VERSION}.
+contract ZKMProgramVerifier is ZKMVerifier {
+    /// @notice The verification key of the program.
+    bytes32 public constant PROGRAM_VKEY = {PROGRAM_VKEY};
+
+    /// @notice Verifies a proof of the program with the given public values.
+    /// @param publicValues The public values encoded as bytes.
+    /// @param proofBytes The proof of the program execution encoded as bytes.
+    function verifyProgramProof(
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) external view {
+        this.verifyProof(PROGRAM_VKEY, publicValues, proofBytes);
+    }
+
+    /// @notice Verifies a proof of the program with the given public values, for a program built
+    /// with the `keccak-commit` feature of `zkm-zkvm`.
+    /// @param publicValues The public values encoded as bytes.
+    /// @param proofBytes The proof of the program execution encoded as bytes.
+    function verifyProgramProofKeccak(
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) external view {
+        this.verifyProofKeccak(PROGRAM_VKEY, publicValues, proofBytes);
+    }
+
+    /// @notice Verifies a proof of the program with the given digest of its public values,
+    /// masked to 253 bits.
+    /// @param publicValuesDigest The digest of the public values.
+    /// @param proofBytes The proof of the program execution encoded as bytes.
+    function verifyProgramProofWithDigest(
+        bytes32 publicValuesDigest,
+        bytes calldata proofBytes
+    ) external view {
+        this.verifyProofWithDigest(PROGRAM_VKEY, publicValuesDigest, proofBytes);
+    }
+}
+"#;
+
+/// The proof system a Solidity verifier is generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+impl ProofSystem {
+    /// The name of the proof system, as used in the contract file names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProofSystem::Plonk => "Plonk",
+            ProofSystem::Groth16 => "Groth16",
+        }
+    }
+
+    /// The directory where the circuit artifacts of the proof system are installed.
+    pub fn artifacts_dir(&self) -> PathBuf {
+        match self {
+            ProofSystem::Plonk => plonk_circuit_artifacts_dir(),
+            ProofSystem::Groth16 => groth16_circuit_artifacts_dir(),
+        }
+    }
+
+    fn dir_name(&self) -> &'static str {
+        match self {
+            ProofSystem::Plonk => "plonk",
+            ProofSystem::Groth16 => "groth16",
+        }
+    }
+}
+
+/// The Solidity sources of a verifier for a single program.
+#[derive(Debug, Clone)]
+pub struct SolidityVerifier {
+    system: ProofSystem,
+    program_vkey: String,
+    sources: BTreeMap<PathBuf, String>,
+}
+
+/// Generates a Solidity verifier for the program of `vk`, from the verifier contracts in the
+/// Plonk or Groth16 `artifacts_dir`.
+///
+/// The artifacts must be the ones the proofs are generated with, e.g. the directory returned by
+/// [crate::install::try_install_circuit_artifacts].
+pub fn generate(
+    vk: &ZKMVerifyingKey,
+    system: ProofSystem,
+    artifacts_dir: &Path,
+) -> Result<SolidityVerifier> {
+    let program_vkey = vk.bytes32();
+    let mut sources = BTreeMap::new();
+    sources.insert(PathBuf::from("IZKMVerifier.sol"), IZKM_VERIFIER_SOL.to_string());
+    for name in
+        [format!("ZKMVerifier{}.sol", system.name()), format!("{}Verifier.sol", system.name())]
+    {
+        let path = artifacts_dir.join(&name);
+        let source = fs::read_to_string(&path).with_context(|| {
+            format!("failed to read {}, are the circuit artifacts built?", path.display())
+        })?;
+        sources.insert(Path::new(system.dir_name()).join(name), source);
+    }
+    sources.insert(
+        PathBuf::from(format!("{PROGRAM_VERIFIER_CONTRACT}.sol")),
+        program_verifier_source(system, &program_vkey),
+    );
+
+    Ok(SolidityVerifier { system, program_vkey, sources })
+}
+
+fn program_verifier_source(system: ProofSystem, program_vkey: &str) -> String {
+    PROGRAM_VERIFIER_TEMPLATE
+        .replace("{PROOF_SYSTEM_DIR}", system.dir_name())
+        .replace("{PROOF_SYSTEM}", system.name())
+        .replace("{ZKM_CIRCUIT_VERSION}", ZKM_CIRCUIT_VERSION)
+        .replace("{PROGRAM_VKEY}", program_vkey)
+}
+
+impl SolidityVerifier {
+    /// The proof system the verifier is generated for.
+    pub fn system(&self) -> ProofSystem {
+        self.system
+    }
+
+    /// The verification key hash of the program, as pinned in the contract.
+    pub fn program_vkey(&self) -> &str {
+        &self.program_vkey
+    }
+
+    /// The contract sources, keyed by their path relative to the output directory.
+    pub fn sources(&self) -> &BTreeMap<PathBuf, String> {
+        &self.sources
+    }
+
+    /// Writes the contract sources to `dir`, creating it if it does not exist.
+    ///
+    /// `ZKMProgramVerifier.sol` at the root of `dir` is the contract to deploy.
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        for (path, source) in &self.sources {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, source)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Checks that `proof` is accepted by the generated contract, by compiling it with `solc`,
+    /// deploying it to a fresh `anvil` node and calling `verifyProgramProof`.
+    ///
+    /// Both `solc` and `anvil` must be installed. Must be called from within a tokio runtime.
+    pub async fn simulate(&self, proof: &ZKMProofWithPublicValues) -> Result<()> {
+        self.simulate_with_hash(proof, PublicValuesHash::Sha256).await
+    }
+
+    /// Checks that `proof`, of a program committing its public values with `hash`, is accepted by
+    /// the generated contract.
+    ///
+    /// SHA-256 proofs are checked with `verifyProgramProof`, Keccak-256 proofs with
+    /// `verifyProgramProofKeccak`, and proofs of any other hash with
+    /// `verifyProgramProofWithDigest`, given the digest computed on the host.
+    pub async fn simulate_with_hash(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        hash: PublicValuesHash,
+    ) -> Result<()> {
+        match (&proof.proof, self.system) {
+            (ZKMProof::Plonk(_), ProofSystem::Plonk)
+            | (ZKMProof::Groth16(_), ProofSystem::Groth16) => {}
+            _ => bail!("the proof is not a {} proof", self.system.name()),
+        }
+        let proof_bytes = proof.bytes();
+        if proof_bytes.is_empty() {
+            bail!("mock proofs cannot be verified onchain");
+        }
+
+        let dir = tempfile::tempdir()?;
+        self.write_to(dir.path())?;
+        let bytecode = compile(dir.path())?;
+
+        let anvil = Anvil::new().spawn();
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+        let client = SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id()));
+
+        let receipt = client
+            .send_transaction(TransactionRequest::new().data(bytecode), None)
+            .await?
+            .await?
+            .ok_or_else(|| anyhow!("the deployment transaction was dropped"))?;
+        let address = receipt
+            .contract_address
+            .ok_or_else(|| anyhow!("the deployment did not create a contract"))?;
+
+        let calldata = program_proof_calldata(&proof.public_values, proof_bytes, hash);
+        client
+            .call(&TransactionRequest::new().to(address).data(calldata).into(), None)
+            .await
+            .map_err(|e| anyhow!("the verifier contract rejected the proof: {e}"))?;
+
+        Ok(())
+    }
+}
+
+/// Encodes the call of the `ZKMProgramVerifier` entry point checking a proof of a program
+/// committing its public values with `hash`.
+fn program_proof_calldata(
+    public_values: &ZKMPublicValues,
+    proof_bytes: Vec<u8>,
+    hash: PublicValuesHash,
+) -> Vec<u8> {
+    let (signature, args) = match hash {
+        PublicValuesHash::Sha256 => (
+            VERIFY_PROGRAM_PROOF_SIGNATURE,
+            encode(&[Token::Bytes(public_values.to_vec()), Token::Bytes(proof_bytes)]),
+        ),
+        PublicValuesHash::Keccak256 => (
+            VERIFY_PROGRAM_PROOF_KECCAK_SIGNATURE,
+            encode(&[Token::Bytes(public_values.to_vec()), Token::Bytes(proof_bytes)]),
+        ),
+        PublicValuesHash::Poseidon2 => {
+            // The digest masked to 253 bits, as in `hashPublicValues`.
+            let mut digest = public_values.hash_with(hash);
+            digest[0] &= 0b00011111;
+            (
+                VERIFY_PROGRAM_PROOF_WITH_DIGEST_SIGNATURE,
+                encode(&[Token::FixedBytes(digest), Token::Bytes(proof_bytes)]),
+            )
+        }
+    };
+    [&id(signature)[..], &args].concat()
+}
+
+/// Compiles the `ZKMProgramVerifier` contract in `dir` with `solc`, returning its creation
+/// bytecode.
+fn compile(dir: &Path) -> Result<Vec<u8>> {
+    let source = format!("{PROGRAM_VERIFIER_CONTRACT}.sol");
+    let output = Command::new("solc")
+        .current_dir(dir)
+        .args(["--optimize", "--combined-json", "bin", "--base-path", "."])
+        .arg(&source)
+        .output()
+        .context("failed to run solc, is it installed?")?;
+    if !output.status.success() {
+        bail!("solc failed: {}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let bin = json["contracts"][format!("{source}:{PROGRAM_VERIFIER_CONTRACT}")]["bin"]
+        .as_str()
+        .ok_or_else(|| anyhow!("solc did not output the {PROGRAM_VERIFIER_CONTRACT} bytecode"))?;
+    Ok(hex::decode(bin)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    #[test]
+    fn test_program_verifier_source() {
+        let vkey = format!("0x{}", "ab".repeat(32));
+        let source = program_verifier_source(ProofSystem::Groth16, &vkey);
+        assert!(source.contains(r#"import {ZKMVerifier} from "./groth16/ZKMVerifierGroth16.sol";"#));
+        assert!(source.contains(&format!("bytes32 public constant PROGRAM_VKEY = {vkey};")));
+        assert!(!source.contains("{PROOF_SYSTEM") && !source.contains("{ZKM_CIRCUIT_VERSION}"));
+        assert!(source.contains("this.verifyProofKeccak(PROGRAM_VKEY, publicValues, proofBytes);"));
+        assert!(source
+            .contains("this.verifyProofWithDigest(PROGRAM_VKEY, publicValuesDigest, proofBytes);"));
+    }
+
+    #[test]
+    fn test_program_proof_calldata() {
+        let mut public_values = ZKMPublicValues::new();
+        public_values.write(&42u32);
+        let proof_bytes = vec![1, 2, 3, 4];
+
+        let calldata =
+            program_proof_calldata(&public_values, proof_bytes.clone(), PublicValuesHash::Sha256);
+        assert_eq!(calldata[..4], id(VERIFY_PROGRAM_PROOF_SIGNATURE));
+        let calldata = program_proof_calldata(
+            &public_values,
+            proof_bytes.clone(),
+            PublicValuesHash::Keccak256,
+        );
+        assert_eq!(calldata[..4], id(VERIFY_PROGRAM_PROOF_KECCAK_SIGNATURE));
+
+        // Other hashes pass the digest masked to 253 bits, the BN254 public input of the proof.
+        let calldata =
+            program_proof_calldata(&public_values, proof_bytes, PublicValuesHash::Poseidon2);
+        assert_eq!(calldata[..4], id(VERIFY_PROGRAM_PROOF_WITH_DIGEST_SIGNATURE));
+        let digest = BigUint::from_bytes_be(&calldata[4..36]);
+        assert!(public_values.is_digest_bn254(&digest, PublicValuesHash::Poseidon2));
+    }
+
+    #[cfg(feature = "network")]
+    #[test]
+    #[ignore]
+    fn test_simulate_groth16() {
+        use crate::{install::try_install_circuit_artifacts, utils::block_on, ProverClient};
+        use zkm_core_machine::io::ZKMStdin;
+
+        let client = ProverClient::cpu();
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        let proof = client.prove(&pk, stdin).groth16().run().unwrap();
+
+        let verifier =
+            generate(&vk, ProofSystem::Groth16, &try_install_circuit_artifacts("groth16").unwrap())
+                .unwrap();
+        block_on(verifier.simulate(&proof)).unwrap();
+    }
+}
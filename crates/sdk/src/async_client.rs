@@ -0,0 +1,493 @@
+//! # Ziren SDK Async Client
+//!
+//! An asynchronous counterpart of [ProverClient](crate::ProverClient), for use from async
+//! runtimes such as tokio.
+
+use std::{
+    future::{Future, IntoFuture},
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use futures::{channel::oneshot, future::BoxFuture};
+use zkm_core_executor::ExecutionReport;
+use zkm_core_machine::io::ZKMStdin;
+use zkm_primitives::io::ZKMPublicValues;
+use zkm_prover::{components::DefaultProverComponents, ZKMProvingKey, ZKMVerifyingKey};
+
+#[cfg(feature = "network")]
+use crate::NetworkProver;
+use crate::{
+    action, provers::Prover, ProverClient, ZKMProofKind, ZKMProofWithPublicValues,
+    ZKMVerificationError,
+};
+
+/// An asynchronous client for interacting with Ziren.
+///
+/// Every method returns a future instead of blocking the calling thread. Local work (setup,
+/// execution and proving) runs on a dedicated thread, so awaiting it never blocks the runtime.
+/// Proofs requested from the network are awaited natively.
+///
+/// Dropping a future cancels it: network requests stop being polled, and the result of local
+/// work is discarded. Local work which has already started runs to completion in the
+/// background, since the prover can not be interrupted.
+#[derive(Clone)]
+pub struct AsyncProverClient {
+    prover: Arc<dyn Prover<DefaultProverComponents>>,
+    #[cfg(feature = "network")]
+    network: Option<Arc<NetworkProver>>,
+}
+
+impl AsyncProverClient {
+    /// Creates a new [AsyncProverClient].
+    ///
+    /// The prover is selected with the `ZKM_PROVER` environment variable, as in
+    /// [ProverClient::new].
+    ///
+    /// ### Examples
+    ///
+    /// ```no_run
+    /// use zkm_sdk::AsyncProverClient;
+    ///
+    /// std::env::set_var("ZKM_PROVER", "local");
+    /// let client = AsyncProverClient::new();
+    /// ```
+    pub fn new() -> Self {
+        #[cfg(feature = "network")]
+        if std::env::var("ZKM_PROVER").is_ok_and(|prover| prover.eq_ignore_ascii_case("network")) {
+            return Self::network();
+        }
+        ProverClient::new().into()
+    }
+
+    /// Creates a new [AsyncProverClient] with the mock prover.
+    pub fn mock() -> Self {
+        ProverClient::mock().into()
+    }
+
+    /// Creates a new [AsyncProverClient] with the local prover, using the CPU.
+    pub fn cpu() -> Self {
+        ProverClient::cpu().into()
+    }
+
+    /// Creates a new [AsyncProverClient] with the local prover, using the GPU.
+    pub fn cuda() -> Self {
+        ProverClient::cuda().into()
+    }
+
+    /// Creates a new [AsyncProverClient] with the network prover.
+    #[cfg(feature = "network")]
+    pub fn network() -> Self {
+        let network = Arc::new(NetworkProver::from_env().unwrap());
+        Self { prover: network.clone(), network: Some(network) }
+    }
+
+    /// Generates the proving and verifying keys for the given program.
+    pub async fn setup(&self, elf: &[u8]) -> (ZKMProvingKey, ZKMVerifyingKey) {
+        let prover = self.prover.clone();
+        let elf = elf.to_vec();
+        spawn_blocking(move || prover.setup(&elf)).await
+    }
+
+    /// Prepare to execute the given program on the given input (without generating a proof).
+    ///
+    /// The returned [AsyncExecute] may be configured via its methods, and is run by awaiting it.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{AsyncProverClient, ZKMStdin};
+    ///
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = AsyncProverClient::new();
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    /// let (public_values, report) = client.execute(elf, stdin).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn execute(&self, elf: &[u8], stdin: ZKMStdin) -> AsyncExecute {
+        AsyncExecute {
+            prover: self.prover.clone(),
+            elf: elf.to_vec(),
+            stdin,
+            max_cycles: None,
+            skip_deferred_proof_verification: false,
+            check_determinism: false,
+        }
+    }
+
+    /// Prepare to prove the execution of the given program with the given input in the default
+    /// mode.
+    ///
+    /// The returned [AsyncProve] may be configured via its methods, and is run by awaiting it.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{AsyncProverClient, ZKMStdin};
+    ///
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = AsyncProverClient::new();
+    /// let (pk, vk) = client.setup(elf).await;
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    /// let proof = client.prove(&pk, stdin).compressed().await?;
+    /// client.verify(&proof, &vk).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prove(&self, pk: &ZKMProvingKey, stdin: ZKMStdin) -> AsyncProve {
+        AsyncProve {
+            client: self.clone(),
+            pk: pk.clone(),
+            stdin,
+            kind: Default::default(),
+            shard_size: None,
+            shard_batch_size: None,
+            cycle_limit: None,
+            timeout: None,
+            skip_deferred_proof_verification: false,
+        }
+    }
+
+    /// Verifies that the given proof is valid and matches the given verification key produced by
+    /// [Self::setup].
+    pub async fn verify(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<(), ZKMVerificationError> {
+        let prover = self.prover.clone();
+        let (proof, vk) = (proof.clone(), vk.clone());
+        spawn_blocking(move || prover.verify(&proof, &vk)).await
+    }
+}
+
+impl Default for AsyncProverClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<ProverClient> for AsyncProverClient {
+    fn from(client: ProverClient) -> Self {
+        Self {
+            prover: Arc::from(client.prover),
+            #[cfg(feature = "network")]
+            network: None,
+        }
+    }
+}
+
+/// Builder to prepare and configure execution of a program on an input.
+/// May be run by awaiting it.
+pub struct AsyncExecute {
+    prover: Arc<dyn Prover<DefaultProverComponents>>,
+    elf: Vec<u8>,
+    stdin: ZKMStdin,
+    max_cycles: Option<u64>,
+    skip_deferred_proof_verification: bool,
+    check_determinism: bool,
+}
+
+impl AsyncExecute {
+    /// Execute the program on the input, consuming the built action `self`.
+    pub async fn run(self) -> Result<(ZKMPublicValues, ExecutionReport)> {
+        spawn_blocking(move || {
+            let Self {
+                prover,
+                elf,
+                stdin,
+                max_cycles,
+                skip_deferred_proof_verification,
+                check_determinism,
+            } = self;
+            let mut execute = action::Execute::new(prover.as_ref(), &elf, stdin)
+                .set_skip_deferred_proof_verification(skip_deferred_proof_verification);
+            if let Some(max_cycles) = max_cycles {
+                execute = execute.max_cycles(max_cycles);
+            }
+            if check_determinism {
+                execute = execute.check_determinism();
+            }
+            execute.run()
+        })
+        .await
+    }
+
+    /// Set the maximum number of cpu cycles to use for execution.
+    ///
+    /// See [action::Execute::max_cycles].
+    pub fn max_cycles(mut self, max_cycles: u64) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+
+    /// Skip deferred proof verification.
+    pub fn set_skip_deferred_proof_verification(mut self, value: bool) -> Self {
+        self.skip_deferred_proof_verification = value;
+        self
+    }
+
+    /// Run the program twice and check that both runs agree.
+    ///
+    /// See [action::Execute::check_determinism].
+    pub fn check_determinism(mut self) -> Self {
+        self.check_determinism = true;
+        self
+    }
+}
+
+impl IntoFuture for AsyncExecute {
+    type Output = Result<(ZKMPublicValues, ExecutionReport)>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.run())
+    }
+}
+
+/// Builder to prepare and configure proving execution of a program on an input.
+/// May be run by awaiting it.
+pub struct AsyncProve {
+    client: AsyncProverClient,
+    pk: ZKMProvingKey,
+    stdin: ZKMStdin,
+    kind: ZKMProofKind,
+    shard_size: Option<usize>,
+    shard_batch_size: Option<usize>,
+    cycle_limit: Option<u64>,
+    timeout: Option<Duration>,
+    skip_deferred_proof_verification: bool,
+}
+
+impl AsyncProve {
+    /// Prove the execution of the program on the input, consuming the built action `self`.
+    pub async fn run(self) -> Result<ZKMProofWithPublicValues> {
+        #[cfg(feature = "network")]
+        if let Some(network) = &self.client.network {
+            let unsupported = self.unsupported_network_opts();
+            if !unsupported.is_empty() {
+                anyhow::bail!(
+                    "the network prover does not support the options: {}",
+                    unsupported.join(", ")
+                );
+            }
+            let request = crate::network::ProofRequest::new(self.kind);
+            // The verifying key of a wrapped compressed proof is not the one of the program.
+            let vk = (self.kind != ZKMProofKind::CompressToGroth16).then_some(&self.pk.vk);
+            let (proof, _) =
+                network.prove_request(&self.pk.elf, self.stdin, &request, self.timeout, vk).await?;
+            return Ok(proof);
+        }
+
+        spawn_blocking(move || {
+            let Self {
+                client,
+                pk,
+                stdin,
+                kind,
+                shard_size,
+                shard_batch_size,
+                cycle_limit,
+                timeout,
+                skip_deferred_proof_verification,
+            } = self;
+            let mut prove = action::Prove::new(client.prover.as_ref(), &pk, stdin)
+                .set_skip_deferred_proof_verification(skip_deferred_proof_verification);
+            prove = match kind {
+                ZKMProofKind::Core => prove.core(),
+                ZKMProofKind::Compressed => prove.compressed(),
+                ZKMProofKind::Plonk => prove.plonk(),
+                ZKMProofKind::Groth16 => prove.groth16(),
+                ZKMProofKind::CompressToGroth16 => prove.compress_to_groth16(),
+            };
+            if let Some(shard_size) = shard_size {
+                prove = prove.shard_size(shard_size);
+            }
+            if let Some(shard_batch_size) = shard_batch_size {
+                prove = prove.shard_batch_size(shard_batch_size);
+            }
+            if let Some(cycle_limit) = cycle_limit {
+                prove = prove.cycle_limit(cycle_limit);
+            }
+            if let Some(timeout) = timeout {
+                prove = prove.timeout(timeout);
+            }
+            prove.run()
+        })
+        .await
+    }
+
+    /// The options set on the action which the network prover can not honor, since the proof
+    /// network does not take them. Requesting a network proof with any of them set fails, rather
+    /// than proving without them.
+    #[cfg(any(feature = "network", test))]
+    fn unsupported_network_opts(&self) -> Vec<&'static str> {
+        [
+            ("shard_size", self.shard_size.is_some()),
+            ("shard_batch_size", self.shard_batch_size.is_some()),
+            ("cycle_limit", self.cycle_limit.is_some()),
+            ("skip_deferred_proof_verification", self.skip_deferred_proof_verification),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+
+    /// Set the proof kind to the core mode. This is the default.
+    pub fn core(mut self) -> Self {
+        self.kind = ZKMProofKind::Core;
+        self
+    }
+
+    /// Set the proof kind to the compressed mode.
+    pub fn compressed(mut self) -> Self {
+        self.kind = ZKMProofKind::Compressed;
+        self
+    }
+
+    /// Set the proof mode to the plonk bn254 mode.
+    pub fn plonk(mut self) -> Self {
+        self.kind = ZKMProofKind::Plonk;
+        self
+    }
+
+    /// Set the proof mode to the groth16 bn254 mode.
+    pub fn groth16(mut self) -> Self {
+        self.kind = ZKMProofKind::Groth16;
+        self
+    }
+
+    /// Set the proof mode to the compressed-proof-to-groth16 mode.
+    pub fn compress_to_groth16(mut self) -> Self {
+        self.kind = ZKMProofKind::CompressToGroth16;
+        self
+    }
+
+    /// Set the shard size for proving.
+    ///
+    /// Not supported by the network prover, which fails the proof if it is set.
+    pub fn shard_size(mut self, value: usize) -> Self {
+        self.shard_size = Some(value);
+        self
+    }
+
+    /// Set the shard batch size for proving.
+    ///
+    /// Not supported by the network prover, which fails the proof if it is set.
+    pub fn shard_batch_size(mut self, value: usize) -> Self {
+        self.shard_batch_size = Some(value);
+        self
+    }
+
+    /// Set the maximum number of cpu cycles to use for execution.
+    ///
+    /// See [action::Prove::cycle_limit]. Not supported by the network prover, which fails the
+    /// proof if it is set.
+    pub fn cycle_limit(mut self, cycle_limit: u64) -> Self {
+        self.cycle_limit = Some(cycle_limit);
+        self
+    }
+
+    /// Set the timeout for the proof's generation.
+    ///
+    /// This parameter is only used when the prover is run in network mode.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the skip deferred proof verification flag.
+    ///
+    /// Not supported by the network prover, which fails the proof if it is set.
+    pub fn set_skip_deferred_proof_verification(mut self, value: bool) -> Self {
+        self.skip_deferred_proof_verification = value;
+        self
+    }
+}
+
+impl IntoFuture for AsyncProve {
+    type Output = Result<ZKMProofWithPublicValues>;
+    type IntoFuture = BoxFuture<'static, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.run())
+    }
+}
+
+/// Runs `f` on a dedicated thread, returning a future which resolves to its output.
+///
+/// A panic in `f` is resumed when the future is polled. If the future is dropped before the
+/// thread starts, `f` is skipped.
+fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> impl Future<Output = T> + Send {
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || {
+        if tx.is_canceled() {
+            return;
+        }
+        let _ = tx.send(panic::catch_unwind(AssertUnwindSafe(f)));
+    });
+    async move {
+        match rx.await.expect("the worker thread exited without a result") {
+            Ok(output) => output,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::utils;
+
+    #[test]
+    fn test_async_execute() {
+        utils::setup_logger();
+        let client = AsyncProverClient::cpu();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        let (public_values, _) = block_on(client.execute(elf, stdin.clone()).run()).unwrap();
+        let (expected, _) = ProverClient::cpu().execute(elf, stdin).run().unwrap();
+        assert_eq!(public_values.as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn test_unsupported_network_opts() {
+        let client = AsyncProverClient::mock();
+        let (pk, _) = block_on(client.setup(test_artifacts::FIBONACCI_ELF));
+        let prove = client.prove(&pk, ZKMStdin::new()).compressed().timeout(Duration::from_secs(1));
+        assert!(prove.unsupported_network_opts().is_empty());
+
+        let prove = prove.shard_size(1 << 20).cycle_limit(1000);
+        assert_eq!(prove.unsupported_network_opts(), ["shard_size", "cycle_limit"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "boom")]
+    fn test_spawn_blocking_resumes_panic() {
+        block_on(spawn_blocking(|| panic!("boom")));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_async_prove_core() {
+        utils::setup_logger();
+        let client = AsyncProverClient::cpu();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = block_on(client.setup(elf));
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        let proof = block_on(client.prove(&pk, stdin).core().run()).unwrap();
+        block_on(client.verify(&proof, &vk)).unwrap();
+    }
+}
@@ -1,9 +1,28 @@
 //! # Ziren Install
 //!
 //! A library for installing the Ziren circuit artifacts.
+//!
+//! The artifacts of every circuit version are installed once in a cache shared by all the
+//! projects of the machine, `~/.zkm/circuits/<groth16|plonk>/<circuit version>` by default, by an
+//! [ArtifactManager]. The tarballs are either downloaded or, on air-gapped machines, read from a
+//! local bundle, and their SHA-256 checksums are checked before they are extracted.
+//!
+//! A tarball is only installed if its checksum is pinned, either in [PINNED_CHECKSUMS] for the
+//! current circuit version, or with [ArtifactManager::pin]. The checksum published next to a
+//! tarball comes from the same server as the tarball, so it detects corrupted downloads but does
+//! not authenticate them. Unpinned tarballs are refused unless
+//! [ArtifactManager::allow_unpinned] is set.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
-use cfg_if::cfg_if;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 
 #[cfg(any(feature = "network", feature = "network"))]
 use {
@@ -11,91 +30,407 @@ use {
     futures::StreamExt,
     indicatif::{ProgressBar, ProgressStyle},
     reqwest::Client,
-    std::{cmp::min, process::Command},
+    std::cmp::min,
 };
 
-use crate::ZKM_CIRCUIT_VERSION;
+use crate::{ZKMProofKind, ZKM_CIRCUIT_VERSION};
 
 /// The base URL for the S3 bucket containing the circuit artifacts.
 pub const CIRCUIT_ARTIFACTS_URL_BASE: &str = "https://zkm-toolchain.s3.us-west-2.amazonaws.com";
 
+/// The name of the file recording the checksum of the tarball an artifacts directory was
+/// extracted from.
+const CHECKSUM_FILE: &str = "artifacts.sha256";
+
+/// The SHA-256 checksums, in hex, of the published tarballs of the circuit artifacts, as
+/// `(circuit version, artifacts name, checksum)`.
+///
+/// The checksums of the current [ZKM_CIRCUIT_VERSION] are pinned by every [ArtifactManager].
+/// None is recorded yet: the checksums of the tarballs of a circuit version are to be added here
+/// when its artifacts are published. Until then, the artifacts are only installed with a checksum
+/// pinned at runtime, or with [ArtifactManager::allow_unpinned].
+pub const PINNED_CHECKSUMS: &[(&str, &str, &str)] = &[];
+
 /// The directory where the groth16 circuit artifacts will be stored.
 #[must_use]
 pub fn groth16_circuit_artifacts_dir() -> PathBuf {
-    dirs::home_dir().unwrap().join(".zkm").join("circuits/groth16").join(ZKM_CIRCUIT_VERSION)
+    circuits_dir().join("groth16").join(ZKM_CIRCUIT_VERSION)
 }
 
 /// The directory where the plonk circuit artifacts will be stored.
 #[must_use]
 pub fn plonk_circuit_artifacts_dir() -> PathBuf {
-    dirs::home_dir().unwrap().join(".zkm").join("circuits/plonk").join(ZKM_CIRCUIT_VERSION)
+    circuits_dir().join("plonk").join(ZKM_CIRCUIT_VERSION)
 }
 
-/// Tries to install the groth16 circuit artifacts if they are not already installed.
-#[must_use]
-pub fn try_install_circuit_artifacts(artifacts_type: &str) -> PathBuf {
-    let build_dir = if artifacts_type == "groth16" {
-        groth16_circuit_artifacts_dir()
-    } else if artifacts_type == "plonk" {
-        plonk_circuit_artifacts_dir()
-    } else {
-        unimplemented!("unsupported artifacts type: {}", artifacts_type);
-    };
-
-    if build_dir.exists() {
-        println!(
-            "[zkm] {} circuit artifacts already seem to exist at {}. if you want to re-download them, delete the directory",
-            artifacts_type,
-            build_dir.display()
-        );
-    } else {
-        cfg_if! {
-            if #[cfg(feature = "network")] {
+/// The directory of the shared cache of circuit artifacts, from the `ZKM_CIRCUITS_DIR`
+/// environment variable or `~/.zkm/circuits`.
+fn circuits_dir() -> PathBuf {
+    env::var_os("ZKM_CIRCUITS_DIR")
+        .map_or_else(|| dirs::home_dir().unwrap().join(".zkm").join("circuits"), PathBuf::from)
+}
+
+/// An error returned when installing circuit artifacts.
+#[derive(Error, Debug)]
+pub enum ArtifactError {
+    #[error("{0:?} proofs do not use circuit artifacts")]
+    NoArtifacts(ZKMProofKind),
+    #[error("unknown circuit artifacts {0}")]
+    UnknownArtifacts(String),
+    #[error(
+        "no checksum is pinned for {name}, whose SHA-256 is {actual}; pin it, or set \
+         ZKM_ALLOW_UNPINNED_ARTIFACTS=true to install it unpinned"
+    )]
+    Unpinned { name: String, actual: String },
+    #[error("the {0} circuit artifacts are not installed and downloads are disabled")]
+    Offline(&'static str),
+    #[error("the artifacts bundle has no {0}")]
+    MissingBundleFile(PathBuf),
+    #[error("the checksum of {name} is {actual}, expected {expected}")]
+    ChecksumMismatch { name: String, expected: String, actual: String },
+    #[error("the artifacts at {0} were installed without a checksum to check the pin against")]
+    Unverified(PathBuf),
+    #[error("failed to download {url}: {reason}")]
+    Download { url: String, reason: String },
+    #[error("failed to extract {0}")]
+    Extract(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The manager of the Groth16 and Plonk circuit artifacts, see [ArtifactManager::ensure].
+#[derive(Debug, Clone)]
+pub struct ArtifactManager {
+    root: PathBuf,
+    bundle: Option<PathBuf>,
+    offline: bool,
+    allow_unpinned: bool,
+    pins: HashMap<&'static str, String>,
+}
+
+impl ArtifactManager {
+    /// A manager of the artifacts installed in `root`, which downloads the missing ones, pinning
+    /// the [PINNED_CHECKSUMS] of the current circuit version.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let pins = PINNED_CHECKSUMS
+            .iter()
+            .filter(|(version, _, _)| *version == ZKM_CIRCUIT_VERSION)
+            .map(|&(_, name, sha256)| (name, sha256.to_ascii_lowercase()))
+            .collect();
+        Self { root: root.into(), bundle: None, offline: false, allow_unpinned: false, pins }
+    }
+
+    /// The manager configured by the environment:
+    ///
+    /// - `ZKM_CIRCUITS_DIR` sets the directory of the shared cache, `~/.zkm/circuits` by default.
+    /// - `ZKM_ARTIFACTS_BUNDLE` sets the bundle the artifacts are installed from, see
+    ///   [Self::bundle].
+    /// - `ZKM_OFFLINE=true` disables downloads.
+    /// - `ZKM_GROTH16_ARTIFACTS_SHA256` and `ZKM_PLONK_ARTIFACTS_SHA256` pin the checksums of the
+    ///   tarballs, see [Self::pin].
+    /// - `ZKM_ALLOW_UNPINNED_ARTIFACTS=true` installs tarballs without a pinned checksum, see
+    ///   [Self::allow_unpinned].
+    pub fn from_env() -> Self {
+        let mut manager = Self::new(circuits_dir());
+        if let Some(bundle) = env::var_os("ZKM_ARTIFACTS_BUNDLE") {
+            manager = manager.bundle(bundle);
+        }
+        if env::var("ZKM_OFFLINE").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+            manager = manager.offline(true);
+        }
+        if env::var("ZKM_ALLOW_UNPINNED_ARTIFACTS").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+            manager = manager.allow_unpinned(true);
+        }
+        for (name, var) in
+            [("groth16", "ZKM_GROTH16_ARTIFACTS_SHA256"), ("plonk", "ZKM_PLONK_ARTIFACTS_SHA256")]
+        {
+            if let Ok(sha256) = env::var(var) {
+                manager.pins.insert(name, sha256.to_ascii_lowercase());
+            }
+        }
+        manager
+    }
+
+    /// Install the artifacts from a local bundle instead of downloading them, for air-gapped
+    /// machines.
+    ///
+    /// The bundle is a directory holding the tarballs as they are published, such as
+    /// `<circuit version>-groth16.tar.gz`, and optionally their checksums, such as
+    /// `<circuit version>-groth16.tar.gz.sha256`.
+    pub fn bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bundle = Some(path.into());
+        self
+    }
+
+    /// Set whether downloads are disabled, so that missing artifacts are an error unless they are
+    /// in the bundle.
+    pub fn offline(mut self, value: bool) -> Self {
+        self.offline = value;
+        self
+    }
+
+    /// Set whether tarballs without a pinned checksum are installed, after checking them against
+    /// the checksum published with them, if any.
+    pub fn allow_unpinned(mut self, value: bool) -> Self {
+        self.allow_unpinned = value;
+        self
+    }
+
+    /// Pin the SHA-256 checksum, in hex, of the tarball of the artifacts of `kind`, overriding the
+    /// one in [PINNED_CHECKSUMS].
+    ///
+    /// The tarball is then only extracted if it matches, whatever checksum is published with it,
+    /// and installed artifacts extracted from another tarball, or installed without recording
+    /// their checksum, are rejected. Kinds using no artifacts are ignored.
+    pub fn pin(mut self, kind: ZKMProofKind, sha256: impl Into<String>) -> Self {
+        if let Ok(name) = artifacts_name(kind) {
+            self.pins.insert(name, sha256.into().to_ascii_lowercase());
+        }
+        self
+    }
+
+    /// The directory of the artifacts of `kind` for the current circuit version.
+    pub fn artifacts_dir(&self, kind: ZKMProofKind) -> Result<PathBuf, ArtifactError> {
+        Ok(self.root.join(artifacts_name(kind)?).join(ZKM_CIRCUIT_VERSION))
+    }
+
+    /// Returns the directory of the artifacts of `kind`, installing them first if needed.
+    ///
+    /// The tarball is read from the bundle if there is one, and downloaded otherwise. It is
+    /// checked against the pinned checksum, and extracted to a temporary directory renamed once
+    /// complete, so an interrupted install is started over, and concurrent installs keep the
+    /// first complete one. A tarball without a pinned checksum is refused, unless
+    /// [Self::allow_unpinned] is set.
+    pub fn ensure(&self, kind: ZKMProofKind) -> Result<PathBuf, ArtifactError> {
+        let name = artifacts_name(kind)?;
+        let dir = self.artifacts_dir(kind)?;
+        if dir.exists() {
+            self.check_installed(name, &dir)?;
+        } else {
+            self.install(name, &dir)?;
+        }
+        Ok(dir)
+    }
+
+    /// Checks the artifacts named `name` installed in `dir` against the pinned checksum, if any.
+    fn check_installed(&self, name: &'static str, dir: &Path) -> Result<(), ArtifactError> {
+        let Some(expected) = self.pins.get(name) else {
+            return Ok(());
+        };
+        // Artifacts installed before the checksums were recorded cannot be checked.
+        let recorded = fs::read_to_string(dir.join(CHECKSUM_FILE))
+            .map_err(|_| ArtifactError::Unverified(dir.to_path_buf()))?;
+        if *expected != recorded.trim() {
+            return Err(ArtifactError::ChecksumMismatch {
+                name: dir.display().to_string(),
+                expected: expected.clone(),
+                actual: recorded.trim().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Installs the artifacts named `name` to `dir`.
+    ///
+    /// If another process installs them first, its complete install is used instead.
+    fn install(&self, name: &'static str, dir: &Path) -> Result<(), ArtifactError> {
+        let tarball = self.verified_tarball(name, dir)?;
+
+        // Extract next to the final directory, so that it is only renamed once complete.
+        let parent = dir.parent().expect("the artifacts directory has a parent");
+        fs::create_dir_all(parent)?;
+        let staging = tempfile::tempdir_in(parent)?;
+        tarball.extract(staging.path())?;
+        if let Err(err) = fs::rename(staging.path(), dir) {
+            // The checksum is written last, so its presence means the other install is complete.
+            if !dir.join(CHECKSUM_FILE).exists() {
+                return Err(err.into());
+            }
+            return self.check_installed(name, dir);
+        }
+
+        println!("[zkm] installed {} to {}", tarball.name, dir.display());
+        Ok(())
+    }
+
+    /// Reads the tarball of the artifacts named `name` from the bundle, or downloads it, and
+    /// checks its checksum. `dir` is only used to report the download.
+    fn verified_tarball(
+        &self,
+        name: &'static str,
+        dir: &Path,
+    ) -> Result<VerifiedTarball, ArtifactError> {
+        let tarball_name = format!("{ZKM_CIRCUIT_VERSION}-{name}.tar.gz");
+        let mut download = None;
+        let (tarball, published) = match &self.bundle {
+            Some(bundle) => {
+                let tarball = bundle.join(&tarball_name);
+                if !tarball.exists() {
+                    return Err(ArtifactError::MissingBundleFile(tarball));
+                }
+                let published = fs::read_to_string(bundle.join(format!("{tarball_name}.sha256")))
+                    .ok()
+                    .and_then(|sha256| parse_checksum(&sha256));
+                (tarball, published)
+            }
+            None if self.offline => return Err(ArtifactError::Offline(name)),
+            None => {
                 println!(
-                    "[zkm] {} circuit artifacts for version {} do not exist at {}. downloading...",
-                    artifacts_type,
-                    ZKM_CIRCUIT_VERSION,
-                    build_dir.display()
+                    "[zkm] {name} circuit artifacts for version {ZKM_CIRCUIT_VERSION} do not exist \
+                     at {}. downloading...",
+                    dir.display()
                 );
-                install_circuit_artifacts(build_dir.clone(), artifacts_type);
+                let (file, published) = download_artifacts(&tarball_name)?;
+                let tarball = download.insert(file).path().to_path_buf();
+                (tarball, published)
+            }
+        };
+
+        let actual = sha256_file(&tarball)?;
+        let pin = self.pins.get(name);
+        if pin.is_none() && !self.allow_unpinned {
+            return Err(ArtifactError::Unpinned { name: tarball_name, actual });
+        }
+        match pin.or(published.as_ref()) {
+            Some(expected) if *expected != actual => {
+                return Err(ArtifactError::ChecksumMismatch {
+                    name: tarball_name,
+                    expected: expected.clone(),
+                    actual,
+                })
             }
+            Some(_) if pin.is_some() => {}
+            _ => println!(
+                "[zkm] warning: installing {tarball_name} without a pinned checksum; its SHA-256 \
+                 is {actual}, pin it to verify future installs"
+            ),
+        }
+        Ok(VerifiedTarball {
+            name: tarball_name,
+            path: tarball,
+            sha256: actual,
+            _download: download,
+        })
+    }
+}
+
+/// A tarball of circuit artifacts whose checksum was checked.
+struct VerifiedTarball {
+    name: String,
+    path: PathBuf,
+    sha256: String,
+    /// The downloaded file, deleted once the tarball is dropped.
+    _download: Option<tempfile::NamedTempFile>,
+}
+
+impl VerifiedTarball {
+    /// Extracts the tarball to `dir`, recording its checksum once complete.
+    fn extract(&self, dir: &Path) -> Result<(), ArtifactError> {
+        let status = Command::new("tar")
+            .args(["-xzf", self.path.to_str().unwrap(), "-C", dir.to_str().unwrap()])
+            .status()?;
+        if !status.success() {
+            return Err(ArtifactError::Extract(self.name.clone()));
         }
+        fs::write(dir.join(CHECKSUM_FILE), &self.sha256)?;
+        Ok(())
     }
-    build_dir
+}
+
+/// Returns the directory of the circuit artifacts of `kind`, installing them first if needed,
+/// with the [ArtifactManager::from_env] manager.
+pub fn ensure_artifacts(kind: ZKMProofKind) -> Result<PathBuf, ArtifactError> {
+    ArtifactManager::from_env().ensure(kind)
+}
+
+/// Tries to install the `groth16` or `plonk` circuit artifacts if they are not already
+/// installed, returning their directory.
+pub fn try_install_circuit_artifacts(artifacts_type: &str) -> Result<PathBuf, ArtifactError> {
+    ensure_artifacts(artifacts_kind(artifacts_type)?)
 }
 
 /// Install the latest circuit artifacts.
 ///
-/// This function will download the latest circuit artifacts from the S3 bucket and extract them
-/// to the directory specified by [`groth16_bn254_artifacts_dir()`].
+/// This function will download the latest circuit artifacts from the S3 bucket, verify their
+/// checksum and extract them to `build_dir`, over any files already there.
 #[cfg(feature = "network")]
 #[allow(clippy::needless_pass_by_value)]
-pub fn install_circuit_artifacts(build_dir: PathBuf, artifacts_type: &str) {
-    // Create the build directory.
-    std::fs::create_dir_all(&build_dir).expect("failed to create build directory");
-
-    // Download the artifacts.
-    let download_url =
-        format!("{CIRCUIT_ARTIFACTS_URL_BASE}/{ZKM_CIRCUIT_VERSION}-{artifacts_type}.tar.gz");
-    let mut artifacts_tar_gz_file =
-        tempfile::NamedTempFile::new().expect("failed to create tempfile");
+pub fn install_circuit_artifacts(
+    build_dir: PathBuf,
+    artifacts_type: &str,
+) -> Result<(), ArtifactError> {
+    let name = artifacts_name(artifacts_kind(artifacts_type)?)?;
+    fs::create_dir_all(&build_dir)?;
+    ArtifactManager::from_env().verified_tarball(name, &build_dir)?.extract(&build_dir)
+}
+
+/// The proof kind using the circuit artifacts named `artifacts_type`.
+fn artifacts_kind(artifacts_type: &str) -> Result<ZKMProofKind, ArtifactError> {
+    match artifacts_type {
+        "groth16" => Ok(ZKMProofKind::Groth16),
+        "plonk" => Ok(ZKMProofKind::Plonk),
+        _ => Err(ArtifactError::UnknownArtifacts(artifacts_type.to_string())),
+    }
+}
+
+/// The name of the circuit artifacts of `kind`.
+fn artifacts_name(kind: ZKMProofKind) -> Result<&'static str, ArtifactError> {
+    match kind {
+        ZKMProofKind::Groth16 | ZKMProofKind::CompressToGroth16 => Ok("groth16"),
+        ZKMProofKind::Plonk => Ok("plonk"),
+        ZKMProofKind::Core | ZKMProofKind::Compressed => Err(ArtifactError::NoArtifacts(kind)),
+    }
+}
+
+/// Parses a checksum file in the format of `sha256sum`, whose first word is the checksum.
+fn parse_checksum(contents: &str) -> Option<String> {
+    contents.split_whitespace().next().map(str::to_ascii_lowercase)
+}
+
+/// The SHA-256 checksum of the file at `path`, in hex.
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let len = file.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Downloads the tarball `tarball_name` to a temporary file, along with the checksum published
+/// next to it, if any.
+#[cfg(feature = "network")]
+fn download_artifacts(
+    tarball_name: &str,
+) -> Result<(tempfile::NamedTempFile, Option<String>), ArtifactError> {
+    let url = format!("{CIRCUIT_ARTIFACTS_URL_BASE}/{tarball_name}");
     let client = Client::builder().build().expect("failed to create reqwest client");
-    block_on(download_file(&client, &download_url, &mut artifacts_tar_gz_file))
-        .expect("failed to download file");
+    let mut file = tempfile::NamedTempFile::new()?;
+    block_on(download_file(&client, &url, &mut file))
+        .map_err(|reason| ArtifactError::Download { url: url.clone(), reason })?;
 
-    // Extract the tarball to the build directory.
-    let mut res = Command::new("tar")
-        .args([
-            "-Pxzf",
-            artifacts_tar_gz_file.path().to_str().unwrap(),
-            "-C",
-            build_dir.to_str().unwrap(),
-        ])
-        .spawn()
-        .expect("failed to extract tarball");
-    res.wait().unwrap();
+    let published = block_on(async {
+        let res = client.get(format!("{url}.sha256")).send().await.ok()?;
+        res.error_for_status().ok()?.text().await.ok()
+    })
+    .and_then(|sha256| parse_checksum(&sha256));
+    Ok((file, published))
+}
 
-    println!("[zkm] downloaded {} to {:?}", download_url, build_dir.to_str().unwrap(),);
+/// Without the `network` feature, the artifacts can only be installed from a bundle.
+#[cfg(not(feature = "network"))]
+fn download_artifacts(
+    tarball_name: &str,
+) -> Result<(tempfile::NamedTempFile, Option<String>), ArtifactError> {
+    Err(ArtifactError::Download {
+        url: format!("{CIRCUIT_ARTIFACTS_URL_BASE}/{tarball_name}"),
+        reason: "the network feature is disabled".to_string(),
+    })
 }
 
 /// Download the file with a progress bar that indicates the progress.
@@ -128,3 +463,73 @@ pub async fn download_file(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_manager_bundle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (src, bundle) = (tmp.path().join("src"), tmp.path().join("bundle"));
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&bundle).unwrap();
+        fs::write(src.join("groth16_pk.bin"), b"pk").unwrap();
+        let tarball = bundle.join(format!("{ZKM_CIRCUIT_VERSION}-groth16.tar.gz"));
+        let status = Command::new("tar")
+            .args(["-czf", tarball.to_str().unwrap(), "-C", src.to_str().unwrap(), "."])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let sha256 = sha256_file(&tarball).unwrap();
+
+        // A tarball without a pinned checksum is refused, even with a published one, unless
+        // unpinned installs are allowed.
+        fs::write(bundle.join(format!("{ZKM_CIRCUIT_VERSION}-groth16.tar.gz.sha256")), &sha256)
+            .unwrap();
+        let manager =
+            ArtifactManager::new(tmp.path().join("unpinned")).bundle(&bundle).offline(true);
+        assert!(matches!(
+            manager.ensure(ZKMProofKind::Groth16),
+            Err(ArtifactError::Unpinned { .. })
+        ));
+        assert!(manager.allow_unpinned(true).ensure(ZKMProofKind::Groth16).is_ok());
+
+        // A tarball not matching its pin is not installed.
+        let manager = ArtifactManager::new(tmp.path().join("bad")).bundle(&bundle).offline(true);
+        let manager = manager.pin(ZKMProofKind::Groth16, "00");
+        assert!(matches!(
+            manager.ensure(ZKMProofKind::Groth16),
+            Err(ArtifactError::ChecksumMismatch { .. })
+        ));
+        assert!(!manager.artifacts_dir(ZKMProofKind::Groth16).unwrap().exists());
+
+        let manager = ArtifactManager::new(tmp.path().join("circuits")).bundle(&bundle);
+        let manager = manager.offline(true).pin(ZKMProofKind::CompressToGroth16, &sha256);
+        let dir = manager.ensure(ZKMProofKind::Groth16).unwrap();
+        assert_eq!(dir, tmp.path().join("circuits/groth16").join(ZKM_CIRCUIT_VERSION));
+        assert_eq!(fs::read(dir.join("groth16_pk.bin")).unwrap(), b"pk");
+        assert_eq!(manager.ensure(ZKMProofKind::Groth16).unwrap(), dir);
+
+        // A concurrent install finding the artifacts installed uses them.
+        manager.install("groth16", &dir).unwrap();
+
+        // Installed artifacts are checked against the pin.
+        let manager = manager.pin(ZKMProofKind::Groth16, "00");
+        assert!(manager.ensure(ZKMProofKind::Groth16).is_err());
+
+        // Artifacts installed without a checksum are only accepted without a pin.
+        fs::remove_file(dir.join(CHECKSUM_FILE)).unwrap();
+        assert!(matches!(manager.ensure(ZKMProofKind::Groth16), Err(ArtifactError::Unverified(_))));
+        let manager = ArtifactManager::new(tmp.path().join("circuits")).offline(true);
+        assert_eq!(manager.ensure(ZKMProofKind::Groth16).unwrap(), dir);
+
+        let manager = ArtifactManager::new(tmp.path().join("circuits")).offline(true);
+        assert!(matches!(manager.ensure(ZKMProofKind::Plonk), Err(ArtifactError::Offline(_))));
+        assert!(matches!(manager.ensure(ZKMProofKind::Core), Err(ArtifactError::NoArtifacts(_))));
+        assert!(matches!(
+            try_install_circuit_artifacts("stark"),
+            Err(ArtifactError::UnknownArtifacts(_))
+        ));
+    }
+}
@@ -3,38 +3,51 @@
 //! A library for interacting with the Ziren zkVM.
 
 pub mod action;
-// pub mod artifacts;
+pub mod artifacts;
+pub mod async_client;
 pub mod install;
+pub mod metrics;
 
 #[cfg(feature = "network")]
 pub use crate::network::prover::NetworkProver;
 use cfg_if::cfg_if;
-use std::env;
+use std::{env, io::Read};
 use zkm_cuda::ZKMGpuServer;
 
 pub mod network;
+pub mod onchain;
+pub mod plan;
 pub mod proof;
 pub mod provers;
 pub mod utils;
 
+pub use async_client::AsyncProverClient;
+pub use metrics::ProvingMetrics;
 pub use proof::*;
-pub use provers::ZKMVerificationError;
+pub use provers::{PublicValuesVerificationError, ZKMVerificationError};
 use zkm_prover::components::DefaultProverComponents;
 
 #[cfg(feature = "network")]
-pub use provers::{CpuProver, MockProver, Prover};
+pub use provers::{CpuProver, FallbackProver, MockProver, Prover};
 
 pub use zkm_build::include_elf;
-pub use zkm_core_executor::{ExecutionReport, HookEnv, ZKMContext, ZKMContextBuilder};
+pub use zkm_core_executor::{
+    ExecutionReport, HookEnv, ZKMContext, ZKMContextBuilder, ZKMReduceProof,
+};
 pub use zkm_core_machine::{io::ZKMStdin, ZKM_CIRCUIT_VERSION};
-pub use zkm_primitives::io::ZKMPublicValues;
+pub use zkm_primitives::io::{PublicValuesHash, ZKMPublicLogs, ZKMPublicValues};
+pub use zkm_primitives::merkle::{MerkleOpening, MerkleRoot, MerkleTree};
 pub use zkm_prover::{
-    CoreSC, HashableKey, InnerSC, OuterSC, PlonkBn254Proof, ProverMode, ZKMProver, ZKMProvingKey,
+    continuation::{ZKMContinuationProof, ZKMSegmentProof},
+    estimate::{CostEstimate, ProverBackend},
+    CoreSC, EvmCalldataError, EvmProof, EvmProofSystem, Groth16Bn254Proof, HashableKey, InnerSC,
+    OuterSC, PlonkBn254Proof, ProverMode, PublicInputsMode, ZKMProver, ZKMProvingKey,
     ZKMVerifyingKey,
 };
+pub use zkm_stark::{ProgressEvent, ProgressHandler, ProverThreadPool, TraceBacking};
 
 // Re-export the utilities.
-use crate::{provers::CudaProver, utils::block_on};
+use crate::{proof::open_envelope, provers::CudaProver, utils::block_on};
 pub use utils::setup_logger;
 
 /// A client for interacting with Ziren.
@@ -200,6 +213,62 @@ impl ProverClient {
         action::Execute::new(self.prover.as_ref(), elf, stdin)
     }
 
+    /// Estimates the cost of proving the given program on the given input, without proving it.
+    ///
+    /// The program is executed with cost estimation enabled, and the returned [CostEstimate] holds
+    /// the number of shards, the trace area of each chip and a rough proving time for each backend.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProverBackend, ProverClient, ZKMStdin};
+    ///
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = ProverClient::new();
+    ///
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    ///
+    /// let estimate = client.estimate(elf, stdin).unwrap();
+    /// let gpu_time = estimate.proving_time(ProverBackend::Cuda);
+    /// println!("{} shards, ~{:?} on a GPU", estimate.num_shards, gpu_time);
+    /// ```
+    pub fn estimate(&self, elf: &[u8], stdin: ZKMStdin) -> anyhow::Result<CostEstimate> {
+        Ok(self.prover.zkm_prover().estimate(elf, &stdin)?)
+    }
+
+    /// Prepare to plan the proof of the execution of the given program with the given input.
+    /// The returned [action::Plan] may be configured with the target proof and a time and memory
+    /// budget before running.
+    ///
+    /// To plan, call [action::Plan::run], which executes the program with cost estimation and
+    /// returns a [plan::ProofPlan] selecting the fastest backend and proof kind fitting the
+    /// budget, together with the estimated cost of every alternative.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use zkm_sdk::{plan::ProofTarget, ProverClient, ZKMStdin};
+    ///
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = ProverClient::new();
+    /// let (pk, _) = client.setup(elf);
+    ///
+    /// let plan = client
+    ///     .plan(&pk, ZKMStdin::new())
+    ///     .target(ProofTarget::OnChainGroth16)
+    ///     .max_time(Duration::from_secs(30 * 60))
+    ///     .max_memory(64 << 30)
+    ///     .run()
+    ///     .unwrap();
+    /// println!("{plan}");
+    ///
+    /// let client = plan.client();
+    /// let proof = plan.prove(&client, &pk, ZKMStdin::new()).run().unwrap();
+    /// ```
+    pub fn plan<'a>(&'a self, pk: &'a ZKMProvingKey, stdin: ZKMStdin) -> action::Plan<'a> {
+        action::Plan::new(self.prover.as_ref(), pk, stdin)
+    }
+
     /// Prepare to prove the execution of the given program with the given input in the default
     /// mode. The returned [action::Prove] may be configured via its methods before running.
     /// For example, calling [action::Prove::compressed] sets the mode to compressed mode.
@@ -233,6 +302,162 @@ impl ProverClient {
         action::Prove::new(self.prover.as_ref(), pk, stdin)
     }
 
+    /// Proves the execution of the program compressed, verifying the compressed `proofs` of other
+    /// programs in it. The returned proof is compressed too, so it can itself be deferred to
+    /// another program, to build recursive aggregation pipelines.
+    ///
+    /// The program verifies each proof with `zkm_zkvm::lib::verify::verify_zkm_proof`, reading
+    /// the vkey hash and public values of the proofs from `stdin`. See
+    /// [action::Prove::with_deferred_proof] to configure the proof further.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{include_elf, HashableKey, ProverClient, ZKMStdin};
+    ///
+    /// const AGGREGATION_ELF: &[u8] = include_elf!("aggregation");
+    ///
+    /// let client = ProverClient::new();
+    /// let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let (aggregation_pk, _) = client.setup(AGGREGATION_ELF);
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    /// let proof = client.prove(&pk, stdin).compressed().run().unwrap();
+    ///
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write::<Vec<[u32; 8]>>(&vec![vk.hash_u32()]);
+    /// stdin.write::<Vec<Vec<u8>>>(&vec![proof.public_values.to_vec()]);
+    /// let aggregated = client.compress_deferred(&aggregation_pk, stdin, vec![(proof, vk)]).unwrap();
+    /// ```
+    pub fn compress_deferred(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: ZKMStdin,
+        proofs: Vec<(ZKMProofWithPublicValues, ZKMVerifyingKey)>,
+    ) -> anyhow::Result<ZKMProofWithPublicValues> {
+        let mut prove = self.prove(pk, stdin).compressed();
+        for (index, (proof, vk)) in proofs.into_iter().enumerate() {
+            let proof = proof
+                .proof
+                .try_as_compressed()
+                .ok_or_else(|| anyhow::anyhow!("proof {index} is not a compressed proof"))?;
+            prove = prove.with_deferred_proof(*proof, &vk);
+        }
+        prove.run()
+    }
+
+    /// Prepare to prove the program `elf_item` over independent `items`, splitting them across
+    /// several proofs generated in parallel, and aggregate these proofs with the program
+    /// `elf_aggregate`. The returned [action::ProveParallelMap] may be configured via its methods
+    /// before running.
+    ///
+    /// See [action::ProveParallelMap] for the inputs read by both programs.
+    ///
+    /// ### Examples
+    /// ```ignore
+    /// use zkm_sdk::{include_elf, ProverClient};
+    ///
+    /// const ITEM_ELF: &[u8] = include_elf!("check-signature");
+    /// const AGGREGATE_ELF: &[u8] = include_elf!("aggregation");
+    ///
+    /// let client = ProverClient::new();
+    /// let signatures: Vec<Vec<u8>> = vec![vec![0; 64]; 16];
+    /// let output = client
+    ///     .prove_parallel_map(&signatures, ITEM_ELF, AGGREGATE_ELF)
+    ///     .chunks(4)
+    ///     .run()
+    ///     .unwrap();
+    /// client.verify(&output.proof, &output.vk).unwrap();
+    /// ```
+    pub fn prove_parallel_map<'a, T: serde::Serialize + Sync>(
+        &'a self,
+        items: &'a [T],
+        elf_item: &'a [u8],
+        elf_aggregate: &'a [u8],
+    ) -> action::ProveParallelMap<'a, T> {
+        action::ProveParallelMap::new(self.prover.as_ref(), items, elf_item, elf_aggregate)
+    }
+
+    /// Prepare to prove the program of `pk` over many inputs, sharing its proving key. The
+    /// returned [action::ProveBatch] may be configured via its methods before running.
+    ///
+    /// The proofs are generated in parallel, up to [action::ProveBatch::max_concurrency] at a
+    /// time, and may be aggregated into a single proof with [action::ProveBatch::aggregate].
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{include_elf, ProverClient, ZKMStdin};
+    ///
+    /// const AGGREGATION_ELF: &[u8] = include_elf!("aggregation");
+    ///
+    /// let client = ProverClient::new();
+    /// let (pk, _) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let (aggregation_pk, aggregation_vk) = client.setup(AGGREGATION_ELF);
+    /// let stdins = (0..100usize)
+    ///     .map(|n| {
+    ///         let mut stdin = ZKMStdin::new();
+    ///         stdin.write(&n);
+    ///         stdin
+    ///     })
+    ///     .collect();
+    /// let batch =
+    ///     client.prove_batch(&pk, stdins).max_concurrency(2).aggregate(&aggregation_pk).run().unwrap();
+    /// client.verify(&batch.aggregated.unwrap(), &aggregation_vk).unwrap();
+    /// ```
+    pub fn prove_batch<'a>(
+        &'a self,
+        pk: &'a ZKMProvingKey,
+        stdins: Vec<ZKMStdin>,
+    ) -> action::ProveBatch<'a> {
+        action::ProveBatch::new(self.prover.as_ref(), pk, stdins)
+    }
+
+    /// Proves the execution of the program of `pk` as a chain of core proofs of segments of at
+    /// least `segment_cycles` cycles each, for executions too long to prove at once.
+    ///
+    /// Every segment pauses in a state committed by its proof, from which the next segment
+    /// resumes. The proof is verified with [Self::verify_continuation]. See
+    /// [ZKMProver::prove_continuation] for the limitations.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProverClient, PublicValuesHash, ZKMStdin};
+    ///
+    /// let client = ProverClient::cpu();
+    /// let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&1_000_000usize);
+    /// let proof = client.prove_continuation(&pk, stdin, 1 << 24).unwrap();
+    /// client.verify_continuation(&proof, &vk, PublicValuesHash::Sha256).unwrap();
+    /// ```
+    pub fn prove_continuation(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: ZKMStdin,
+        segment_cycles: u64,
+    ) -> anyhow::Result<ZKMContinuationProof> {
+        Ok(self.prover.zkm_prover().prove_continuation(
+            pk,
+            &stdin,
+            Default::default(),
+            segment_cycles,
+        )?)
+    }
+
+    /// Verifies a proof produced by [Self::prove_continuation] of the program with the verifying
+    /// key `vk`, checking that its segments form one execution and that the public values hash
+    /// with `hash` to the digest committed by the program.
+    pub fn verify_continuation(
+        &self,
+        proof: &ZKMContinuationProof,
+        vk: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
+    ) -> Result<(), ZKMVerificationError> {
+        self.prover
+            .zkm_prover()
+            .verify_continuation(proof, vk, hash)
+            .map_err(ZKMVerificationError::Core)
+    }
+
     /// Verifies that the given proof is valid and matches the given verification key produced by
     /// [Self::setup].
     ///
@@ -248,6 +473,10 @@ impl ProverClient {
     /// let proof = client.prove(&pk, stdin).run().unwrap();
     /// client.verify(&proof, &vk).unwrap();
     /// ```
+    ///
+    /// The public values are checked against a digest computed with the hash they record, SHA-256
+    /// unless the program is built with another public values hash, see
+    /// [ZKMProofWithPublicValues::public_values_hash].
     pub fn verify(
         &self,
         proof: &ZKMProofWithPublicValues,
@@ -256,6 +485,137 @@ impl ProverClient {
         self.prover.verify(proof, vk)
     }
 
+    /// Verifies that the given proof is valid and matches the given verification key, checking
+    /// the public values against a digest computed with `hash`.
+    ///
+    /// `hash` must be the [PublicValuesHash] the program commits with, selected by the
+    /// `poseidon2-commit` and `keccak-commit` features of the entrypoint and recorded in the public
+    /// values: a proof of a program committing with another hash is rejected with
+    /// [ZKMVerificationError::PublicValuesHashMismatch].
+    pub fn verify_with_hash(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
+    ) -> Result<(), ZKMVerificationError> {
+        self.prover.verify_with_hash(proof, vk, hash)
+    }
+
+    /// Verifies that the given proof is valid and matches the given verification key, and that
+    /// the public values committed by the program are `expected_values`.
+    ///
+    /// The digest of `expected_values` is recomputed with `hash`, the mode the program commits
+    /// with, and compared to the digest bound by the proof, so the public values attached to the
+    /// proof are not trusted. A mismatch is reported as
+    /// [PublicValuesVerificationError::DigestMismatch].
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProverClient, PublicValuesHash, ZKMStdin};
+    ///
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = ProverClient::new();
+    /// let (pk, vk) = client.setup(elf);
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    /// let proof = client.prove(&pk, stdin).groth16().run().unwrap();
+    /// let expected_values = proof.public_values.to_vec();
+    /// client
+    ///     .verify_with_public_values(&proof, &vk, &expected_values, PublicValuesHash::Sha256)
+    ///     .unwrap();
+    /// ```
+    pub fn verify_with_public_values(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+        expected_values: &[u8],
+        hash: PublicValuesHash,
+    ) -> Result<(), PublicValuesVerificationError> {
+        self.prover.verify_with_public_values(proof, vk, expected_values, hash)
+    }
+
+    /// Verifies that the given proof is valid, using the serialized Plonk or Groth16 verifying key
+    /// `bn254_vk` instead of the circuit artifacts.
+    ///
+    /// The key can be obtained once with [Self::export_plonk_vk] or [Self::export_groth16_vk],
+    /// so verifier-only deployments don't need the artifacts directory. Other proof kinds are
+    /// verified as in [Self::verify_with_hash].
+    pub fn verify_with_bn254_vk(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
+        bn254_vk: &[u8],
+    ) -> Result<(), ZKMVerificationError> {
+        if proof.zkm_version != self.prover.version() {
+            return Err(ZKMVerificationError::VersionMismatch(proof.zkm_version.clone()));
+        }
+        provers::check_public_values_hash(&proof.public_values, hash)?;
+        match &proof.proof {
+            ZKMProof::Plonk(plonk) => self
+                .prover
+                .zkm_prover()
+                .verify_plonk_bn254_with_vk(plonk, vk, &proof.public_values, hash, bn254_vk)
+                .map_err(ZKMVerificationError::Plonk),
+            ZKMProof::Groth16(groth16) => self
+                .prover
+                .zkm_prover()
+                .verify_groth16_bn254_with_vk(groth16, vk, &proof.public_values, hash, bn254_vk)
+                .map_err(ZKMVerificationError::Groth16),
+            _ => self.verify_with_hash(proof, vk, hash),
+        }
+    }
+
+    /// Returns the serialized Plonk verifying key of the circuit artifacts, downloading the
+    /// artifacts if needed.
+    pub fn export_plonk_vk(&self) -> anyhow::Result<Vec<u8>> {
+        ZKMProver::<DefaultProverComponents>::export_plonk_vk(&if zkm_prover::build::zkm_dev_mode()
+        {
+            zkm_prover::build::plonk_bn254_artifacts_dev_dir()
+        } else {
+            install::try_install_circuit_artifacts("plonk")?
+        })
+    }
+
+    /// Returns the serialized Groth16 verifying key of the circuit artifacts, downloading the
+    /// artifacts if needed.
+    pub fn export_groth16_vk(&self) -> anyhow::Result<Vec<u8>> {
+        ZKMProver::<DefaultProverComponents>::export_groth16_vk(
+            &if zkm_prover::build::zkm_dev_mode() {
+                zkm_prover::build::groth16_bn254_artifacts_dev_dir()
+            } else {
+                install::try_install_circuit_artifacts("groth16")?
+            },
+        )
+    }
+
+    /// Verifies a core proof saved with [ZKMProofWithPublicValues::save], reading it from `reader`
+    /// one shard proof at a time.
+    ///
+    /// This allows verifying proofs whose serialized size exceeds the available memory. Returns
+    /// the public values of the proof once it is verified.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use std::{fs::File, io::BufReader};
+    ///
+    /// use zkm_sdk::ProverClient;
+    ///
+    /// let elf = test_artifacts::FIBONACCI_ELF;
+    /// let client = ProverClient::new();
+    /// let (_, vk) = client.setup(elf);
+    /// let reader = BufReader::new(File::open("proof.bin").unwrap());
+    /// let public_values = client.verify_core_from_reader(reader, &vk).unwrap();
+    /// ```
+    pub fn verify_core_from_reader(
+        &self,
+        reader: impl Read,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<ZKMPublicValues, ZKMVerificationError> {
+        let (_, mut reader) = open_envelope(reader).map_err(ZKMVerificationError::ProofFormat)?;
+        self.prover.verify_core_from_reader(&mut reader, vk)
+    }
+
     /// Gets the current version of the Ziren zkVM.
     ///
     /// Note: This is not the same as the version of the Ziren SDK.
@@ -282,6 +642,83 @@ impl ProverClient {
     pub fn setup(&self, elf: &[u8]) -> (ZKMProvingKey, ZKMVerifyingKey) {
         self.prover.setup(elf)
     }
+
+    /// Setup many programs at once, returning the proving and verifying keys in the same order.
+    ///
+    /// The local provers set up the programs in parallel and share the preprocessed work common
+    /// to all programs, which is much faster than calling [Self::setup] for each program. Returns
+    /// an error if any of the programs is not a valid ELF.
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::ProverClient;
+    ///
+    /// let elves = [test_artifacts::FIBONACCI_ELF, test_artifacts::HELLO_WORLD_ELF];
+    /// let client = ProverClient::new();
+    /// let keys = client.setup_many(&elves).unwrap();
+    /// ```
+    pub fn setup_many(
+        &self,
+        elves: &[&[u8]],
+    ) -> anyhow::Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        self.prover.setup_many(elves)
+    }
+
+    /// Aggregates the compressed proofs of independent programs into a single proof, committing
+    /// to the verifying key and public values of each of them.
+    ///
+    /// The aggregated proof is verified with [Self::verify_aggregated].
+    ///
+    /// ### Examples
+    /// ```no_run
+    /// use zkm_sdk::{ProverClient, ZKMStdin};
+    ///
+    /// let client = ProverClient::new();
+    /// let (fibonacci_pk, fibonacci_vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+    /// let (hello_pk, hello_vk) = client.setup(test_artifacts::HELLO_WORLD_ELF);
+    /// let mut stdin = ZKMStdin::new();
+    /// stdin.write(&10usize);
+    /// let fibonacci = client.prove(&fibonacci_pk, stdin).compressed().run().unwrap();
+    /// let hello = client.prove(&hello_pk, ZKMStdin::new()).compressed().run().unwrap();
+    ///
+    /// let programs = [
+    ///     (fibonacci_vk.clone(), fibonacci.public_values.clone()),
+    ///     (hello_vk.clone(), hello.public_values.clone()),
+    /// ];
+    /// let aggregated =
+    ///     client.aggregate(vec![(fibonacci, fibonacci_vk), (hello, hello_vk)]).unwrap();
+    /// client.verify_aggregated(&aggregated, &programs).unwrap();
+    /// ```
+    pub fn aggregate(
+        &self,
+        proofs: Vec<(ZKMProofWithPublicValues, ZKMVerifyingKey)>,
+    ) -> anyhow::Result<ZKMReduceProof<InnerSC>> {
+        let proofs = proofs
+            .into_iter()
+            .enumerate()
+            .map(|(index, (proof, vk))| {
+                let proof = proof
+                    .proof
+                    .try_as_compressed()
+                    .ok_or_else(|| anyhow::anyhow!("proof {index} is not a compressed proof"))?;
+                Ok((*proof, vk))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(self.prover.zkm_prover().aggregate(proofs, Default::default())?)
+    }
+
+    /// Verifies a proof produced by [Self::aggregate] of the programs with the given verifying
+    /// keys, which committed the given public values, in order.
+    pub fn verify_aggregated(
+        &self,
+        proof: &ZKMReduceProof<InnerSC>,
+        programs: &[(ZKMVerifyingKey, ZKMPublicValues)],
+    ) -> Result<(), ZKMVerificationError> {
+        self.prover
+            .zkm_prover()
+            .verify_aggregated(proof, programs)
+            .map_err(ZKMVerificationError::Recursion)
+    }
 }
 
 impl Default for ProverClient {
@@ -297,6 +734,11 @@ pub struct ProverClientBuilder {
     private_key: Option<String>,
     rpc_url: Option<String>,
     skip_simulation: bool,
+    max_proof_size: Option<u64>,
+    tiny_programs: bool,
+    assume_valid: bool,
+    trace_backing: Option<TraceBacking>,
+    thread_pool: Option<ProverThreadPool>,
 }
 
 impl ProverClientBuilder {
@@ -324,22 +766,97 @@ impl ProverClientBuilder {
         self
     }
 
+    /// Sets the maximum size in bytes of a proof received from a remote prover.
+    ///
+    /// Defaults to [zkm_core_machine::io::MAX_PROOF_SIZE].
+    pub fn max_proof_size(mut self, max_proof_size: u64) -> Self {
+        self.max_proof_size = Some(max_proof_size);
+        self
+    }
+
+    /// Uses the tiny program profile of the core shapes, which reduces the proving time of
+    /// hello-world-scale programs such as the ones used in tests. Only applies to the CPU prover.
+    ///
+    /// Proofs generated with the profile can only be compressed with vk verification disabled
+    /// (`VERIFY_VK=false`), and their verifying keys differ from the default ones.
+    pub fn tiny_programs(mut self) -> Self {
+        self.tiny_programs = true;
+        self
+    }
+
+    /// Accepts mock proofs with valid public values when verifying. Only applies to the mock
+    /// prover, see [MockProver::assume_valid].
+    pub fn assume_valid(mut self) -> Self {
+        self.assume_valid = true;
+        self
+    }
+
+    /// Sets where the local provers keep the traces of the shards waiting to be proven.
+    ///
+    /// With [`TraceBacking::Disk`], they are copied to memory-mapped temporary files, so that the
+    /// memory of the prover is bounded by the shards being proven. Defaults to
+    /// [`TraceBacking::from_env`].
+    pub fn trace_backing(mut self, trace_backing: TraceBacking) -> Self {
+        self.trace_backing = Some(trace_backing);
+        self
+    }
+
+    /// Sets the thread pool the local provers run their parallel work on, instead of the global
+    /// rayon pool.
+    ///
+    /// The pool may be built with [`ProverThreadPool::new`] to pin its threads to some CPUs, or
+    /// shared with the host with [`ProverThreadPool::from_pool`]. Defaults to
+    /// [`ProverThreadPool::from_env`].
+    pub fn thread_pool(mut self, thread_pool: ProverThreadPool) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// The [ZKMProver] of the local provers, with the trace backing and the thread pool set.
+    fn local_prover(&self) -> ZKMProver {
+        let mut prover = ZKMProver::new();
+        if let Some(trace_backing) = &self.trace_backing {
+            prover = prover.with_trace_backing(trace_backing.clone());
+        }
+        if let Some(thread_pool) = &self.thread_pool {
+            prover = prover.with_thread_pool(thread_pool.clone());
+        }
+        prover
+    }
+
     /// Builds a [ProverClient], using the provided private key.
     pub fn build(self) -> ProverClient {
         match self.mode.expect("The prover mode is required") {
-            ProverMode::Cpu => ProverClient::cpu(),
-            ProverMode::Cuda => ProverClient::cuda(),
+            ProverMode::Cpu => {
+                let mut prover = self.local_prover();
+                if self.tiny_programs {
+                    prover = prover.with_tiny_program_profile();
+                }
+                ProverClient { prover: Box::new(CpuProver::from_prover(prover)) }
+            }
+            ProverMode::Cuda => {
+                let mut prover = CudaProver::new(self.local_prover(), ZKMGpuServer::default());
+                if let Some(max_proof_size) = self.max_proof_size {
+                    prover.cuda_prover.set_max_proof_size(max_proof_size);
+                }
+                ProverClient { prover: Box::new(prover) }
+            }
             ProverMode::Network => {
                 cfg_if! {
                    if #[cfg(feature = "network")] {
-                        ProverClient {
-                            prover: Box::new(NetworkProver::from_env().unwrap()),
+                        let mut prover = NetworkProver::from_env().unwrap();
+                        if let Some(max_proof_size) = self.max_proof_size {
+                            prover.max_proof_size = max_proof_size;
                         }
+                        ProverClient { prover: Box::new(prover) }
                     } else {
                         panic!("network feature is not enabled")
                     }
                 }
             }
+            ProverMode::Mock if self.assume_valid => {
+                ProverClient { prover: Box::new(MockProver::new().assume_valid()) }
+            }
             ProverMode::Mock => ProverClient::mock(),
         }
     }
@@ -396,7 +913,10 @@ mod tests {
     use crate::utils::committed_public_values;
     use crate::ZKMProof;
     use crate::ZKMProof::Groth16;
-    use crate::{utils, ProverClient, ZKMStdin};
+    use crate::{
+        utils, ProverClient, ProverMode, PublicValuesHash, PublicValuesVerificationError, ZKMStdin,
+        ZKMVerificationError,
+    };
     use p3_field::PrimeField;
     use zkm_primitives::io::ZKMPublicValues;
     use zkm_prover::HashableKey;
@@ -412,6 +932,35 @@ mod tests {
         // tracing::info!("gas = {}", report.estimate_gas());
     }
 
+    #[test]
+    fn test_execute_check_determinism() {
+        utils::setup_logger();
+        let client = ProverClient::cpu();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        let (_, report) = client.execute(elf, stdin).check_determinism().run().unwrap();
+        assert!(report.input_digest.is_some());
+        assert!(!report.state_digests.is_empty());
+    }
+
+    #[test]
+    fn test_setup_many() {
+        utils::setup_logger();
+        let client = ProverClient::cpu();
+        let elves = [test_artifacts::FIBONACCI_ELF, test_artifacts::HELLO_WORLD_ELF];
+        let keys = client.setup_many(&elves).unwrap();
+        assert_eq!(keys.len(), elves.len());
+        for (elf, (pk, vk)) in elves.iter().zip(keys) {
+            let (_, expected_vk) = client.setup(elf);
+            assert_eq!(pk.elf, *elf);
+            assert_eq!(vk.bytes32(), expected_vk.bytes32());
+        }
+
+        // An invalid program is an error rather than a panic.
+        assert!(client.setup_many(&[test_artifacts::FIBONACCI_ELF, b"not an elf"]).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_execute_panic() {
@@ -454,6 +1003,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_e2e_core_tiny_programs() {
+        utils::setup_logger();
+        let client = ProverClient::builder().mode(ProverMode::Cpu).tiny_programs().build();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+
+        let proof = client.prove(&pk, stdin).run().unwrap();
+        client.verify(&proof, &vk).unwrap();
+    }
+
+    #[test]
+    fn test_e2e_core_from_reader() {
+        utils::setup_logger();
+        let client = ProverClient::cpu();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+
+        // Generate proof & verify it from its serialized form.
+        let mut proof = client.prove(&pk, stdin).run().unwrap();
+        let bytes = proof.to_bytes();
+        let public_values = client.verify_core_from_reader(bytes.as_slice(), &vk).unwrap();
+        assert_eq!(public_values.as_slice(), proof.public_values.as_slice());
+
+        // Test proof saved before the envelope format.
+        let legacy = bincode::serialize(&proof).unwrap();
+        assert!(client.verify_core_from_reader(legacy.as_slice(), &vk).is_ok());
+
+        // Test truncated proof.
+        assert!(client.verify_core_from_reader(&bytes[..bytes.len() / 2], &vk).is_err());
+
+        // Test invalid public values.
+        proof.public_values = ZKMPublicValues::from(&[255, 4, 84]);
+        let bytes = proof.to_bytes();
+        if client.verify_core_from_reader(bytes.as_slice(), &vk).is_ok() {
+            panic!("verified proof with invalid public values")
+        }
+    }
+
     #[test]
     fn test_e2e_compressed() {
         utils::setup_logger();
@@ -510,7 +1102,7 @@ mod tests {
     #[test]
     fn test_e2e_prove_plonk_mock() {
         utils::setup_logger();
-        let client = ProverClient::mock();
+        let client = ProverClient::builder().mode(ProverMode::Mock).assume_valid().build();
         let elf = test_artifacts::FIBONACCI_ELF;
         let (pk, vk) = client.setup(elf);
         let mut stdin = ZKMStdin::new();
@@ -519,6 +1111,108 @@ mod tests {
         client.verify(&proof, &vk).unwrap();
     }
 
+    #[test]
+    fn test_mock_proofs_shape() {
+        let client = ProverClient::mock();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+
+        let proof = client.prove(&pk, stdin.clone()).groth16().run().unwrap();
+        let Groth16(groth16_proof) = &proof.proof else { panic!("expected a groth16 proof") };
+        assert_eq!(hex::decode(&groth16_proof.encoded_proof).unwrap().len(), 256);
+        assert_eq!(
+            groth16_proof.public_inputs[0],
+            vk.hash_bn254().as_canonical_biguint().to_string()
+        );
+        assert_eq!(
+            groth16_proof.public_inputs[1],
+            committed_public_values(proof.public_values.as_ref())
+        );
+        assert!(proof.bytes().is_empty());
+        // Mock proofs are rejected unless assumed valid.
+        assert!(matches!(client.verify(&proof, &vk), Err(ZKMVerificationError::MockProof)));
+
+        let client = ProverClient::builder().mode(ProverMode::Mock).assume_valid().build();
+        client.verify(&proof, &vk).unwrap();
+        let proof = client.prove(&pk, stdin.clone()).core().run().unwrap();
+        client.verify(&proof, &vk).unwrap();
+        let mut proof = client.prove(&pk, stdin).compressed().run().unwrap();
+        client.verify(&proof, &vk).unwrap();
+
+        proof.proof = ZKMProof::CompressToGroth16;
+        assert!(matches!(
+            client.verify(&proof, &vk),
+            Err(ZKMVerificationError::UnsupportedProof(ZKMProofKind::CompressToGroth16))
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_public_values() {
+        let client = ProverClient::builder().mode(ProverMode::Mock).assume_valid().build();
+        let elf = test_artifacts::FIBONACCI_ELF;
+        let (pk, vk) = client.setup(elf);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+
+        for proof in [
+            client.prove(&pk, stdin.clone()).core().run().unwrap(),
+            client.prove(&pk, stdin.clone()).groth16().run().unwrap(),
+        ] {
+            let expected_values = proof.public_values.to_vec();
+            let hash = PublicValuesHash::Sha256;
+            client.verify_with_public_values(&proof, &vk, &expected_values, hash).unwrap();
+            assert!(matches!(
+                client.verify_with_public_values(&proof, &vk, &[0; 4], hash),
+                Err(PublicValuesVerificationError::DigestMismatch { .. })
+            ));
+            // The SHA-256 digest is not accepted for a program committing with Keccak-256.
+            assert!(client
+                .verify_with_public_values(
+                    &proof,
+                    &vk,
+                    &expected_values,
+                    PublicValuesHash::Keccak256
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_public_values_hash_mismatch() {
+        let client = ProverClient::builder().mode(ProverMode::Mock).assume_valid().build();
+        let (pk, vk) = client.setup(test_artifacts::KECCAK_COMMIT_ELF);
+        for proof in [
+            client.prove(&pk, ZKMStdin::new()).core().run().unwrap(),
+            client.prove(&pk, ZKMStdin::new()).groth16().run().unwrap(),
+        ] {
+            // The program records its mode in its public values, so it is bound to the proof.
+            assert_eq!(proof.public_values_hash(), PublicValuesHash::Keccak256);
+            assert_eq!(proof.public_values.clone().read::<u32>(), 42);
+            client.verify(&proof, &vk).unwrap();
+            client.verify_with_hash(&proof, &vk, PublicValuesHash::Keccak256).unwrap();
+            assert!(matches!(
+                client.verify_with_hash(&proof, &vk, PublicValuesHash::Sha256),
+                Err(ZKMVerificationError::PublicValuesHashMismatch {
+                    expected: PublicValuesHash::Sha256,
+                    committed: PublicValuesHash::Keccak256,
+                })
+            ));
+        }
+
+        // A program committing with SHA-256 records no mode.
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+        let proof = client.prove(&pk, stdin).core().run().unwrap();
+        assert_eq!(proof.public_values_hash(), PublicValuesHash::Sha256);
+        assert!(matches!(
+            client.verify_with_hash(&proof, &vk, PublicValuesHash::Poseidon2),
+            Err(ZKMVerificationError::PublicValuesHashMismatch { .. })
+        ));
+    }
+
     #[test]
     fn test_groth16_public_values() {
         let client = ProverClient::cpu();
@@ -0,0 +1,302 @@
+//! Machine-readable metrics of the generation of a proof, for fleet monitoring.
+//!
+//! [ProvingMetrics] are returned alongside a proof by [crate::action::Prove::run_with_metrics].
+//! They serialize to JSON with [ProvingMetrics::to_json], and with the `otel` feature are recorded
+//! as OpenTelemetry metrics with [ProvingMetrics::record].
+//!
+//! With the `metrics` feature, the [prometheus] module serves the metrics of the GPU server client
+//! and of the network prover to Prometheus.
+
+use std::{
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use zkm_prover::{components::DefaultProverComponents, ZKMProver};
+use zkm_stark::{Progress, ProgressEvent};
+
+use crate::{provers::ProverType, ZKMProofKind};
+
+#[cfg(feature = "metrics")]
+pub mod prometheus;
+
+/// The metrics of the generation of a proof.
+///
+/// The stage times are only measured by the local provers, and are `None` for the stages the
+/// proof did not go through. The core proof overlaps the execution, and its time counts from the
+/// start of the proof. The cache counters are the lookups and misses of the prover during the
+/// proof, so they include the lookups of other proofs generated concurrently by the same prover.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProvingMetrics {
+    /// The kind of the proof.
+    pub kind: String,
+    /// The type of the prover which generated the proof, such as `cpu` or `network`.
+    pub backend: String,
+    /// Why the proof network was given up for a local prover, if it was.
+    pub fallback_reason: Option<String>,
+    /// The number of cycles executed by the program.
+    pub cycles: u64,
+    /// The number of shards of the core proof.
+    pub num_shards: usize,
+    /// The wall time of the whole proof, in seconds.
+    pub total_secs: f64,
+    /// The wall time of the execution, in seconds.
+    pub execution_secs: Option<f64>,
+    /// The wall time of the core proof, in seconds.
+    pub core_secs: Option<f64>,
+    /// The wall time of the compress tree, in seconds.
+    pub compress_secs: Option<f64>,
+    /// The wall time of the shrink proof, in seconds.
+    pub shrink_secs: Option<f64>,
+    /// The wall time of the wrap proof, in seconds.
+    pub wrap_secs: Option<f64>,
+    /// The wall time of the Plonk or Groth16 proof, in seconds.
+    pub snark_secs: Option<f64>,
+    /// The peak resident set size of the process, in bytes, if known.
+    pub peak_rss_bytes: Option<u64>,
+    /// The number of lookups of recursion programs.
+    pub lift_cache_lookups: usize,
+    /// The number of lookups of recursion programs which missed the cache.
+    pub lift_cache_misses: usize,
+    /// The number of lookups of compression programs.
+    pub join_cache_lookups: usize,
+    /// The number of lookups of compression programs which missed the cache.
+    pub join_cache_misses: usize,
+}
+
+impl ProvingMetrics {
+    /// The fraction of the lookups of recursion programs which hit the cache, if any.
+    #[must_use]
+    pub fn lift_cache_hit_rate(&self) -> Option<f64> {
+        hit_rate(self.lift_cache_lookups, self.lift_cache_misses)
+    }
+
+    /// The fraction of the lookups of compression programs which hit the cache, if any.
+    #[must_use]
+    pub fn join_cache_hit_rate(&self) -> Option<f64> {
+        hit_rate(self.join_cache_lookups, self.join_cache_misses)
+    }
+
+    /// Serializes the metrics to a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize the metrics")
+    }
+
+    /// Records the metrics with the instruments of `meter`, with the proof kind as an attribute.
+    ///
+    /// The times are recorded in the `zkm.proof.duration` histogram, with the stage as an
+    /// attribute, and the other metrics in `zkm.proof.*` histograms and counters.
+    #[cfg(feature = "otel")]
+    pub fn record(&self, meter: &opentelemetry::metrics::Meter) {
+        use opentelemetry::KeyValue;
+
+        let kind = KeyValue::new("kind", self.kind.clone());
+        let duration = meter.f64_histogram("zkm.proof.duration").with_unit("s").build();
+        let stages = [
+            ("total", Some(self.total_secs)),
+            ("execution", self.execution_secs),
+            ("core", self.core_secs),
+            ("compress", self.compress_secs),
+            ("shrink", self.shrink_secs),
+            ("wrap", self.wrap_secs),
+            ("snark", self.snark_secs),
+        ];
+        for (stage, secs) in stages {
+            if let Some(secs) = secs {
+                duration.record(secs, &[kind.clone(), KeyValue::new("stage", stage)]);
+            }
+        }
+
+        let attributes = [kind];
+        meter.u64_histogram("zkm.proof.cycles").build().record(self.cycles, &attributes);
+        meter.u64_histogram("zkm.proof.shards").build().record(self.num_shards as u64, &attributes);
+        if let Some(peak_rss_bytes) = self.peak_rss_bytes {
+            meter
+                .u64_gauge("zkm.process.peak_rss")
+                .with_unit("By")
+                .build()
+                .record(peak_rss_bytes, &attributes);
+        }
+        let counters = [
+            ("zkm.prover.lift_cache.lookups", self.lift_cache_lookups),
+            ("zkm.prover.lift_cache.misses", self.lift_cache_misses),
+            ("zkm.prover.join_cache.lookups", self.join_cache_lookups),
+            ("zkm.prover.join_cache.misses", self.join_cache_misses),
+        ];
+        for (name, count) in counters {
+            meter.u64_counter(name).build().add(count as u64, &attributes);
+        }
+    }
+}
+
+fn hit_rate(lookups: usize, misses: usize) -> Option<f64> {
+    (lookups > 0).then(|| lookups.saturating_sub(misses) as f64 / lookups as f64)
+}
+
+/// The times of the progress events which end the stages of a proof, since its start.
+#[derive(Default)]
+struct StageEnds {
+    execution: Option<Duration>,
+    core: Option<Duration>,
+    compress: Option<Duration>,
+    shrink_start: Option<Duration>,
+    wrap_start: Option<Duration>,
+    snark_start: Option<Duration>,
+    num_shards: usize,
+}
+
+impl StageEnds {
+    fn record(&mut self, event: ProgressEvent, now: Duration) {
+        let now = Some(now);
+        match event {
+            ProgressEvent::Execution { done: true, .. } => self.execution = now,
+            ProgressEvent::Execution { .. } => {}
+            ProgressEvent::CoreShard { num_proven, .. } => {
+                self.core = now;
+                self.num_shards = self.num_shards.max(num_proven);
+            }
+            ProgressEvent::CompressProof { .. } => self.compress = now,
+            ProgressEvent::ShrinkStarted => self.shrink_start = now,
+            ProgressEvent::WrapStarted => self.wrap_start = now,
+            ProgressEvent::SnarkStarted => self.snark_start = now,
+        }
+    }
+}
+
+/// The prover which generated a proof, reported by a prover choosing among several, and why it
+/// fell back to it.
+#[derive(Clone, Default)]
+pub(crate) struct BackendReport(Arc<Mutex<Option<(ProverType, Option<String>)>>>);
+
+impl BackendReport {
+    /// Reports that the proof was generated by a prover of type `backend`.
+    pub(crate) fn report(&self, backend: ProverType, fallback_reason: Option<String>) {
+        *self.0.lock().unwrap() = Some((backend, fallback_reason));
+    }
+}
+
+/// Records the metrics of a proof, from its progress events and the counters of the prover.
+pub(crate) struct MetricsRecorder {
+    start: Instant,
+    stages: Arc<Mutex<StageEnds>>,
+    backend: BackendReport,
+    lift_cache: (usize, usize),
+    join_cache: (usize, usize),
+}
+
+impl MetricsRecorder {
+    /// Starts recording the metrics of a proof by `prover`.
+    pub(crate) fn start(prover: &ZKMProver<DefaultProverComponents>) -> Self {
+        Self {
+            start: Instant::now(),
+            stages: Arc::default(),
+            backend: BackendReport::default(),
+            lift_cache: lift_cache(prover),
+            join_cache: join_cache(prover),
+        }
+    }
+
+    /// A progress handler recording the stage times of the proof, and forwarding the events to
+    /// `inner`.
+    pub(crate) fn progress(&self, inner: Progress) -> Progress {
+        let (start, stages) = (self.start, Arc::clone(&self.stages));
+        Progress::new(move |event: ProgressEvent| {
+            stages.lock().unwrap().record(event, start.elapsed());
+            inner.report(event);
+        })
+    }
+
+    /// The report of the prover which generated the proof, to be passed to the prover.
+    pub(crate) fn backend(&self) -> BackendReport {
+        self.backend.clone()
+    }
+
+    /// The metrics of the proof generated by a prover of type `backend`, once it is generated.
+    pub(crate) fn finish(
+        self,
+        prover: &ZKMProver<DefaultProverComponents>,
+        backend: ProverType,
+        kind: ZKMProofKind,
+        cycles: u64,
+    ) -> ProvingMetrics {
+        let total = self.start.elapsed();
+        let (backend, fallback_reason) =
+            self.backend.0.lock().unwrap().take().unwrap_or((backend, None));
+        let stages = self.stages.lock().unwrap();
+        let secs = |from: Option<Duration>, to: Option<Duration>| {
+            Some(to?.saturating_sub(from?).as_secs_f64())
+        };
+        let zero = Some(Duration::ZERO);
+        let (lift_lookups, lift_misses) = lift_cache(prover);
+        let (join_lookups, join_misses) = join_cache(prover);
+        ProvingMetrics {
+            kind: format!("{kind:?}").to_lowercase(),
+            backend: format!("{backend:?}").to_lowercase(),
+            fallback_reason,
+            cycles,
+            num_shards: stages.num_shards,
+            total_secs: total.as_secs_f64(),
+            execution_secs: secs(zero, stages.execution),
+            core_secs: secs(zero, stages.core),
+            compress_secs: secs(stages.core, stages.compress),
+            shrink_secs: secs(stages.shrink_start, stages.wrap_start),
+            wrap_secs: secs(stages.wrap_start, stages.snark_start.or(Some(total))),
+            snark_secs: secs(stages.snark_start, Some(total)),
+            peak_rss_bytes: peak_rss_bytes(),
+            lift_cache_lookups: lift_lookups.saturating_sub(self.lift_cache.0),
+            lift_cache_misses: lift_misses.saturating_sub(self.lift_cache.1),
+            join_cache_lookups: join_lookups.saturating_sub(self.join_cache.0),
+            join_cache_misses: join_misses.saturating_sub(self.join_cache.1),
+        }
+    }
+}
+
+fn lift_cache(prover: &ZKMProver<DefaultProverComponents>) -> (usize, usize) {
+    (
+        prover.lift_cache_lookups.load(Ordering::Relaxed),
+        prover.lift_cache_misses.load(Ordering::Relaxed),
+    )
+}
+
+fn join_cache(prover: &ZKMProver<DefaultProverComponents>) -> (usize, usize) {
+    (
+        prover.join_cache_lookups.load(Ordering::Relaxed),
+        prover.join_cache_misses.load(Ordering::Relaxed),
+    )
+}
+
+/// The peak resident set size of the process, from `VmHWM` in `/proc/self/status` on Linux.
+fn peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb = line.trim_start_matches("VmHWM:").trim().trim_end_matches("kB").trim();
+    kb.parse::<u64>().ok().map(|kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proving_metrics_json() {
+        let metrics = ProvingMetrics {
+            kind: "compressed".to_string(),
+            cycles: 1000,
+            num_shards: 2,
+            total_secs: 1.5,
+            core_secs: Some(1.0),
+            lift_cache_lookups: 4,
+            lift_cache_misses: 1,
+            ..Default::default()
+        };
+        assert_eq!(metrics.lift_cache_hit_rate(), Some(0.75));
+        assert_eq!(metrics.join_cache_hit_rate(), None);
+
+        let json = metrics.to_json();
+        assert!(json.contains(r#""cycles":1000"#));
+        assert!(json.contains(r#""compress_secs":null"#));
+        assert_eq!(serde_json::from_str::<ProvingMetrics>(&json).unwrap(), metrics);
+    }
+}
@@ -0,0 +1,58 @@
+//! A Prometheus endpoint serving the metrics of the GPU server client and of the network prover.
+//!
+//! The metrics are registered in the default registry of the `prometheus` crate, so the metrics
+//! of the application registered there are served too.
+
+use std::{convert::Infallible, net::SocketAddr};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use prometheus::{Encoder, TextEncoder};
+
+/// Encodes the metrics of the default registry in the Prometheus text format.
+pub fn encode() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("failed to encode metrics");
+    String::from_utf8(buffer).expect("the text format is UTF-8")
+}
+
+/// Serves the metrics on `GET /metrics` at `addr`, until the returned future is dropped.
+///
+/// ### Examples
+/// ```no_run
+/// # async fn run() -> Result<(), hyper::Error> {
+/// tokio::spawn(zkm_sdk::metrics::prometheus::serve(([0, 0, 0, 0], 9100).into()));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn serve(addr: SocketAddr) -> Result<(), hyper::Error> {
+    let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+    Server::try_bind(&addr)?.serve(make_service).await
+}
+
+async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = if request.method() == Method::GET && request.uri().path() == "/metrics" {
+        Response::builder()
+            .header(CONTENT_TYPE, TextEncoder::new().format_type())
+            .body(Body::from(encode()))
+    } else {
+        Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty())
+    };
+    Ok(response.expect("the response is valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        crate::network::metrics::job_submitted(crate::ZKMProofKind::Groth16);
+        assert!(encode().contains(r#"zkm_network_jobs_submitted_total{kind="groth16"}"#));
+    }
+}
@@ -0,0 +1,267 @@
+//! Authentication of the requests to the proof network, and of the proofs it returns.
+//!
+//! Requests are signed by a [RequestSigner] with EIP-191 personal signatures. Every signed message
+//! ends with a nonce increasing strictly, across restarts of the client too. Checking the nonce
+//! requires a proof network server which supports it, and that server is not part of this
+//! repository. The signing key can be rotated without restarting the client: the
+//! keys are reloaded from their file when it is modified, and a retired key still signs the
+//! cancellations of the jobs it submitted.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::Address,
+};
+use p3_field::PrimeField;
+use zkm_prover::{components::DefaultProverComponents, HashableKey, ZKMProver, ZKMVerifyingKey};
+
+use crate::ZKMProof;
+
+/// The signature of a request to the proof network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestSignature {
+    /// The address of the key which signed the request.
+    pub signer: Address,
+    /// The nonce ending the signed message.
+    pub nonce: u64,
+    /// The signature, hex encoded.
+    pub signature: String,
+}
+
+struct Keys {
+    active: LocalWallet,
+    retired: Vec<LocalWallet>,
+    /// The modification time of the key file the keys were read from.
+    modified: Option<SystemTime>,
+}
+
+/// The keys signing the requests to the proof network.
+pub struct RequestSigner {
+    keys: Mutex<Keys>,
+    key_file: Option<PathBuf>,
+    nonce: AtomicU64,
+    /// The signers of the jobs submitted by the client.
+    submitted: Mutex<HashMap<String, Address>>,
+}
+
+impl RequestSigner {
+    /// A signer signing the requests with `key`.
+    pub fn new(key: LocalWallet) -> Self {
+        Self {
+            keys: Mutex::new(Keys { active: key, retired: Vec::new(), modified: None }),
+            key_file: None,
+            nonce: AtomicU64::new(0),
+            submitted: Mutex::default(),
+        }
+    }
+
+    /// Reads the keys from the environment: from the file `ZKM_PRIVATE_KEY_FILE` if it is set, as
+    /// in [Self::from_file], or else the single key `ZKM_PRIVATE_KEY`.
+    pub fn from_env() -> Result<Self> {
+        if let Ok(path) = std::env::var("ZKM_PRIVATE_KEY_FILE") {
+            return Self::from_file(path);
+        }
+        let key = std::env::var("ZKM_PRIVATE_KEY")
+            .map_err(|_| anyhow!("ZKM_PRIVATE_KEY must be set for remote proving"))?;
+        if key.is_empty() {
+            bail!("Please set the ZKM_PRIVATE_KEY");
+        }
+        Ok(Self::new(key.parse()?))
+    }
+
+    /// Reads the keys from the file at `path`, holding a hex private key per line.
+    ///
+    /// The first key signs the requests, and the others are retired keys. The file is read again
+    /// whenever it is modified, and the key it replaces is retired.
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let (keys, modified) = read_keys(&path)?;
+        let mut keys = keys.into_iter();
+        let active = keys.next().ok_or_else(|| anyhow!("no key in {}", path.display()))?;
+        Ok(Self {
+            keys: Mutex::new(Keys { active, retired: keys.collect(), modified }),
+            key_file: Some(path),
+            nonce: AtomicU64::new(0),
+            submitted: Mutex::default(),
+        })
+    }
+
+    /// The address of the key signing the requests.
+    pub fn address(&self) -> Address {
+        self.reload();
+        self.keys.lock().unwrap().active.address()
+    }
+
+    /// Signs the requests with `key` from now on. The key it replaces is retired.
+    pub fn rotate(&self, key: LocalWallet) {
+        let mut keys = self.keys.lock().unwrap();
+        let previous = std::mem::replace(&mut keys.active, key);
+        keys.retired.retain(|key| key.address() != previous.address());
+        keys.retired.push(previous);
+        tracing::info!("rotated the network signing key to {:?}", keys.active.address());
+    }
+
+    /// Signs the message `data` of a request, followed by a fresh nonce.
+    pub async fn sign(&self, data: &str) -> Result<RequestSignature> {
+        self.reload();
+        let key = self.keys.lock().unwrap().active.clone();
+        self.sign_with(&key, data).await
+    }
+
+    /// Signs the message `data` of a request about `job`, with the key which submitted the job
+    /// if it is known, or else the key signing the requests.
+    pub async fn sign_for_job(&self, job: &str, data: &str) -> Result<RequestSignature> {
+        self.reload();
+        let signer = self.submitted.lock().unwrap().get(job).copied();
+        let key = {
+            let keys = self.keys.lock().unwrap();
+            std::iter::once(&keys.active)
+                .chain(&keys.retired)
+                .find(|key| Some(key.address()) == signer)
+                .unwrap_or(&keys.active)
+                .clone()
+        };
+        self.sign_with(&key, data).await
+    }
+
+    /// Records that `job` was submitted by `signer`.
+    pub(crate) fn submitted(&self, job: &str, signer: Address) {
+        self.submitted.lock().unwrap().insert(job.to_string(), signer);
+    }
+
+    async fn sign_with(&self, key: &LocalWallet, data: &str) -> Result<RequestSignature> {
+        let nonce = self.next_nonce();
+        let signature = key.sign_message(format!("{data}&{nonce}")).await?;
+        Ok(RequestSignature { signer: key.address(), nonce, signature: signature.to_string() })
+    }
+
+    /// A nonce greater than the previous ones, and than the nonces of the previous runs of the
+    /// client, which counted from the time in microseconds too.
+    fn next_nonce(&self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_micros();
+        let mut previous = self.nonce.load(Ordering::Relaxed);
+        loop {
+            let nonce = (now as u64).max(previous + 1);
+            match self.nonce.compare_exchange(previous, nonce, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return nonce,
+                Err(current) => previous = current,
+            }
+        }
+    }
+
+    /// Reads the key file again if it was modified since it was read.
+    fn reload(&self) {
+        let Some(path) = &self.key_file else { return };
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        let mut keys = self.keys.lock().unwrap();
+        if modified.is_none() || modified == keys.modified {
+            return;
+        }
+        match read_keys(path) {
+            Ok((new_keys, modified)) if !new_keys.is_empty() => {
+                let mut new_keys = new_keys.into_iter();
+                let active = new_keys.next().unwrap();
+                let mut retired: Vec<_> = new_keys.collect();
+                // Keep the replaced keys, to cancel the jobs they submitted.
+                for key in std::iter::once(&keys.active).chain(&keys.retired) {
+                    if key.address() != active.address()
+                        && retired.iter().all(|retired| retired.address() != key.address())
+                    {
+                        retired.push(key.clone());
+                    }
+                }
+                if active.address() != keys.active.address() {
+                    tracing::info!("rotated the network signing key to {:?}", active.address());
+                }
+                *keys = Keys { active, retired, modified };
+            }
+            Ok(_) => tracing::warn!("ignoring {}, which holds no key", path.display()),
+            Err(e) => tracing::warn!("failed to reload the keys from {}: {e:#}", path.display()),
+        }
+    }
+}
+
+fn read_keys(path: &PathBuf) -> Result<(Vec<LocalWallet>, Option<SystemTime>)> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read the keys from {}", path.display()))?;
+    let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let keys = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse::<LocalWallet>().map_err(|e| anyhow!("invalid key: {e}")))
+        .collect::<Result<_>>()?;
+    Ok((keys, modified))
+}
+
+/// Checks that `proof`, returned by the network, proves the program with the verifying key `vk`.
+///
+/// The digest of the verifying key committed by a Plonk or Groth16 proof must be
+/// `vk.hash_bn254()`, and a compressed proof must verify against `vk`.
+pub(crate) fn check_proof_vk(
+    prover: &ZKMProver<DefaultProverComponents>,
+    proof: &ZKMProof,
+    vk: &ZKMVerifyingKey,
+) -> Result<()> {
+    let committed = match proof {
+        ZKMProof::Groth16(proof) => &proof.public_inputs[0],
+        ZKMProof::Plonk(proof) => &proof.public_inputs[0],
+        ZKMProof::Compressed(proof) => {
+            return prover
+                .verify_compressed(proof, vk)
+                .map_err(|e| anyhow!("the network returned an invalid compressed proof: {e}"));
+        }
+        _ => return Ok(()),
+    };
+    let expected = vk.hash_bn254().as_canonical_biguint().to_string();
+    if *committed != expected {
+        bail!("the network returned a proof of the vkey {committed}, instead of {expected}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use ethers::types::Signature;
+
+    use super::*;
+
+    const KEY_1: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    const KEY_2: &str = "0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a";
+
+    #[test]
+    fn test_request_signer_rotation() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{KEY_1}").unwrap();
+        let signer = RequestSigner::from_file(file.path()).unwrap();
+        let first = crate::block_on(signer.sign("job-1&0")).unwrap();
+        signer.submitted("job-1", first.signer);
+
+        let signature: Signature = first.signature.parse().unwrap();
+        let message = format!("job-1&0&{}", first.nonce);
+        assert_eq!(signature.recover(message).unwrap(), first.signer);
+
+        signer.rotate(KEY_2.parse().unwrap());
+        let second = crate::block_on(signer.sign("job-2&0")).unwrap();
+        assert_ne!(second.signer, first.signer);
+        assert!(second.nonce > first.nonce);
+
+        // The retired key still signs the requests about the job it submitted.
+        let cancel = crate::block_on(signer.sign_for_job("job-1", "job-1")).unwrap();
+        assert_eq!(cancel.signer, first.signer);
+    }
+}
@@ -0,0 +1,115 @@
+//! Jobs submitted to the proof network.
+//!
+//! A [JobId] is all a client needs to follow a job: it can be saved, and the job polled with
+//! [crate::NetworkProver::status], cancelled or its proof downloaded after a restart of the client.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::network::prover::stage_service::{GetStatusResponse, Status, Step};
+
+/// The id of a job submitted to the proof network.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JobId(pub String);
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for JobId {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(JobId(s.to_string()))
+    }
+}
+
+/// The stage a job submitted to the proof network is at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    /// The job waits for a prover.
+    Queued,
+    /// The program is executed and split into shards.
+    Executing,
+    /// The core shards are proven.
+    ProvingCore,
+    /// The shard proofs are compressed into a single proof.
+    Compressing,
+    /// The compressed proof is wrapped into a Groth16 proof.
+    Wrapping,
+    /// The proof is ready to be downloaded.
+    Completed,
+    /// The job was cancelled.
+    Cancelled,
+    /// The job failed, with the status reported by the network.
+    Failed(String),
+}
+
+impl JobStatus {
+    /// Whether the job is over, successfully or not.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Cancelled | JobStatus::Failed(_))
+    }
+}
+
+impl From<&GetStatusResponse> for JobStatus {
+    fn from(response: &GetStatusResponse) -> Self {
+        match Status::from_i32(response.status) {
+            Some(Status::Success) => JobStatus::Completed,
+            Some(Status::Cancelled) => JobStatus::Cancelled,
+            Some(Status::Computing) => match Step::from_i32(response.step) {
+                Some(Step::Init) | None => JobStatus::Queued,
+                Some(Step::InSplit) => JobStatus::Executing,
+                Some(Step::InProve) => JobStatus::ProvingCore,
+                Some(Step::InAgg) => JobStatus::Compressing,
+                Some(Step::InSnark) => JobStatus::Wrapping,
+                Some(Step::End) => JobStatus::Completed,
+            },
+            Some(status) => JobStatus::Failed(format!("{status:?}")),
+            None => JobStatus::Failed(format!("unknown status {}", response.status)),
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobStatus::Queued => f.write_str("queued"),
+            JobStatus::Executing => f.write_str("executing"),
+            JobStatus::ProvingCore => f.write_str("proving core shards"),
+            JobStatus::Compressing => f.write_str("compressing"),
+            JobStatus::Wrapping => f.write_str("wrapping"),
+            JobStatus::Completed => f.write_str("completed"),
+            JobStatus::Cancelled => f.write_str("cancelled"),
+            JobStatus::Failed(status) => write!(f, "failed: {status}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_status_from_response() {
+        let response = |status: Status, step: Step| GetStatusResponse {
+            status: status.into(),
+            step: step.into(),
+            ..Default::default()
+        };
+        assert_eq!(JobStatus::from(&response(Status::Computing, Step::Init)), JobStatus::Queued);
+        assert_eq!(
+            JobStatus::from(&response(Status::Computing, Step::InProve)),
+            JobStatus::ProvingCore
+        );
+        assert_eq!(JobStatus::from(&response(Status::Success, Step::End)), JobStatus::Completed);
+        assert_eq!(
+            JobStatus::from(&response(Status::ProveError, Step::InProve)),
+            JobStatus::Failed("ProveError".to_string())
+        );
+        assert!(JobStatus::from(&response(Status::Cancelled, Step::InAgg)).is_terminal());
+    }
+}
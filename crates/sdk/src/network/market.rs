@@ -0,0 +1,149 @@
+//! Prices and terms of the proofs requested from the proof network.
+//!
+//! [crate::NetworkProver::quote] prices a proof before it is requested. A [ProofRequest] carries
+//! the terms a job is submitted with by [crate::NetworkProver::submit_proof_request]: the network
+//! rejects a job it cannot prove before its deadline, or for at most its maximum price.
+//!
+//! Quotes and terms require a proof network server which supports them, and that server is not
+//! part of this repository. A server which does not support them fails the quotes, and ignores the
+//! terms of the jobs.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+
+use crate::{network::prover::stage_service::GetQuoteResponse, ZKMProofKind};
+
+/// The price the proof network asks to prove a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceQuote {
+    /// The id of the quote, to request a proof at its price.
+    pub id: String,
+    /// The price of the proof, in the smallest unit of `currency`.
+    pub price: u64,
+    /// The currency of the price.
+    pub currency: String,
+    /// The time the network expects to take to generate the proof.
+    pub estimated_time: Duration,
+    /// The time until which the network honors the quote.
+    pub expires_at: SystemTime,
+}
+
+impl PriceQuote {
+    /// Whether the network no longer honors the quote.
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() >= self.expires_at
+    }
+}
+
+impl From<&GetQuoteResponse> for PriceQuote {
+    fn from(response: &GetQuoteResponse) -> Self {
+        Self {
+            id: response.quote_id.clone(),
+            price: response.price,
+            currency: response.currency.clone(),
+            estimated_time: Duration::from_millis(response.estimated_proving_time),
+            expires_at: UNIX_EPOCH + Duration::from_secs(response.expires_at),
+        }
+    }
+}
+
+/// The terms of a proof requested from the proof network.
+#[derive(Debug, Clone)]
+pub struct ProofRequest {
+    /// The kind of the proof.
+    pub kind: ZKMProofKind,
+    /// The SHA-256 hash of the ELF, without the 0x prefix, for the network to use the ELF it
+    /// cached instead of uploading it.
+    pub elf_id: Option<String>,
+    /// The time by which the proof must be generated.
+    pub deadline: Option<SystemTime>,
+    /// The maximum price of the proof, in the smallest unit of the currency of the network.
+    pub max_price: Option<u64>,
+    /// The quote whose price the proof is requested at.
+    pub quote: Option<PriceQuote>,
+}
+
+impl ProofRequest {
+    /// A request of a proof of `kind`, without a deadline or a maximum price.
+    pub fn new(kind: ZKMProofKind) -> Self {
+        Self { kind, elf_id: None, deadline: None, max_price: None, quote: None }
+    }
+
+    /// Uses the ELF cached by the network under its SHA-256 hash `elf_id`.
+    pub fn elf_id(mut self, elf_id: impl Into<String>) -> Self {
+        self.elf_id = Some(elf_id.into());
+        self
+    }
+
+    /// Requires the proof to be generated by `deadline`.
+    pub fn deadline(mut self, deadline: SystemTime) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Refuses to pay more than `max_price` for the proof.
+    pub fn max_price(mut self, max_price: u64) -> Self {
+        self.max_price = Some(max_price);
+        self
+    }
+
+    /// Requests the proof at the price of `quote`, which also becomes the maximum price unless
+    /// one is set.
+    pub fn quote(mut self, quote: &PriceQuote) -> Self {
+        self.max_price.get_or_insert(quote.price);
+        self.quote = Some(quote.clone());
+        self
+    }
+
+    /// Checks the terms before the request is sent.
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.deadline.is_some_and(|deadline| deadline <= SystemTime::now()) {
+            bail!("the deadline of the proof request has passed");
+        }
+        if let Some(quote) = &self.quote {
+            if quote.is_expired() {
+                bail!("the quote {} has expired", quote.id);
+            }
+            if self.max_price.is_some_and(|max_price| quote.price > max_price) {
+                bail!(
+                    "the quote {} asks {} {}, above the maximum price {}",
+                    quote.id,
+                    quote.price,
+                    quote.currency,
+                    self.max_price.unwrap()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_request_terms() {
+        let quote = PriceQuote::from(&GetQuoteResponse {
+            quote_id: "q1".to_string(),
+            price: 500,
+            currency: "ZKM".to_string(),
+            estimated_proving_time: 90_000,
+            expires_at: u64::MAX >> 32,
+            ..Default::default()
+        });
+        assert_eq!(quote.estimated_time, Duration::from_secs(90));
+        assert!(!quote.is_expired());
+
+        let request = ProofRequest::new(ZKMProofKind::Groth16).quote(&quote);
+        assert_eq!(request.max_price, Some(500));
+        assert!(request.check().is_ok());
+
+        let cheaper = ProofRequest::new(ZKMProofKind::Groth16).max_price(400).quote(&quote);
+        assert!(cheaper.check().is_err());
+
+        let late = ProofRequest::new(ZKMProofKind::Groth16).deadline(UNIX_EPOCH);
+        assert!(late.check().is_err());
+    }
+}
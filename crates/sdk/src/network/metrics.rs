@@ -0,0 +1,90 @@
+//! Prometheus metrics of the network prover, registered in the default registry of the
+//! `prometheus` crate with the `metrics` feature. Without it, recording a metric does nothing.
+
+use std::time::Duration;
+
+use crate::ZKMProofKind;
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use std::sync::LazyLock;
+
+    use prometheus::{
+        register_histogram_vec, register_int_counter, register_int_counter_vec, HistogramVec,
+        IntCounter, IntCounterVec,
+    };
+
+    pub(super) static JOBS_SUBMITTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec!(
+            "zkm_network_jobs_submitted_total",
+            "The number of jobs submitted to the proof network.",
+            &["kind"]
+        )
+        .unwrap()
+    });
+
+    pub(super) static JOBS_FINISHED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec!(
+            "zkm_network_jobs_finished_total",
+            "The number of jobs of the proof network waited for until they finished.",
+            &["outcome"]
+        )
+        .unwrap()
+    });
+
+    pub(super) static JOB_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec!(
+            "zkm_network_job_duration_seconds",
+            "The time waited for a job of the proof network until it finished.",
+            &["outcome"],
+            vec![10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0]
+        )
+        .unwrap()
+    });
+
+    pub(super) static RETRIES: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter!(
+            "zkm_network_retries_total",
+            "The number of requests to the proof network sent again after a failure."
+        )
+        .unwrap()
+    });
+}
+
+/// How a job of the proof network finished, as seen by the client waiting for it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum JobOutcome {
+    Completed,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+/// Counts a job submitted to the network.
+#[allow(unused_variables)]
+pub(crate) fn job_submitted(kind: ZKMProofKind) {
+    #[cfg(feature = "metrics")]
+    imp::JOBS_SUBMITTED.with_label_values(&[format!("{kind:?}").to_lowercase().as_str()]).inc();
+}
+
+/// Counts a job which finished with `outcome`, after it was waited for during `elapsed`.
+#[allow(unused_variables)]
+pub(crate) fn job_finished(outcome: JobOutcome, elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        let outcome = match outcome {
+            JobOutcome::Completed => "completed",
+            JobOutcome::Failed => "failed",
+            JobOutcome::Cancelled => "cancelled",
+            JobOutcome::TimedOut => "timed_out",
+        };
+        imp::JOBS_FINISHED.with_label_values(&[outcome]).inc();
+        imp::JOB_DURATION.with_label_values(&[outcome]).observe(elapsed.as_secs_f64());
+    }
+}
+
+/// Counts a request sent again after a failure.
+pub(crate) fn retry() {
+    #[cfg(feature = "metrics")]
+    imp::RETRIES.inc();
+}
@@ -2,8 +2,15 @@ use crate::network::prover::stage_service::Step;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod auth;
+mod job;
+mod market;
+pub(crate) mod metrics;
 pub mod prover;
 
+pub use job::*;
+pub use market::*;
+
 #[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct ProverInput {
     pub elf: Vec<u8>,
@@ -1,23 +1,27 @@
 use stage_service::stage_service_client::StageServiceClient;
-use stage_service::{GenerateProofRequest, GetStatusRequest};
+use stage_service::{
+    CancelProofRequest, GenerateProofRequest, GetQuoteRequest, GetStatusRequest, GetStatusResponse,
+};
 
+use std::future::Future;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Instant, UNIX_EPOCH};
 use std::{env, fs};
 
-use ethers::signers::{LocalWallet, Signer};
 use tokio::time::sleep;
 use tokio::time::Duration;
 use tonic::transport::Endpoint;
 use tonic::transport::{Certificate, Identity};
 use tonic::transport::{Channel, ClientTlsConfig};
 
-use crate::network::ProverInput;
+use crate::network::auth::{check_proof_vk, RequestSignature, RequestSigner};
+use crate::network::metrics::{self, JobOutcome};
+use crate::network::{JobId, JobStatus, PriceQuote, ProofRequest, ProverInput};
 use crate::{block_on, CpuProver, Prover, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues};
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use zkm_core_executor::ZKMContext;
-use zkm_core_machine::io::ZKMStdin;
+use zkm_core_machine::io::{DeserializeError, ZKMStdin, MAX_PROOF_SIZE};
 use zkm_core_machine::ZKM_CIRCUIT_VERSION;
 use zkm_primitives::io::ZKMPublicValues;
 use zkm_prover::components::DefaultProverComponents;
@@ -38,21 +42,24 @@ use crate::provers::{ProofOpts, ProverType};
 
 const DEFAULT_POLL_INTERVAL: u64 = 3000; // 3s
 const MIN_POLL_INTERVAL: u64 = 100; // 100ms
+const MAX_RETRIES: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
 
 pub struct NetworkProver {
     pub endpoint: Endpoint,
-    pub wallet: LocalWallet,
+    pub signer: RequestSigner,
     pub local_prover: CpuProver,
     // Polling interval (milliseconds) for checking proof status,
     // default is 3000 milliseconds
     pub poll_interval: u64,
+    // Maximum size (bytes) of a proof or public values downloaded from the network,
+    // default is MAX_PROOF_SIZE
+    pub max_proof_size: u64,
 }
 
 impl NetworkProver {
     pub fn from_env() -> anyhow::Result<NetworkProver> {
-        let proof_network_privkey = Some(
-            env::var("ZKM_PRIVATE_KEY").expect("ZKM_PRIVATE_KEY must be set for remote proving"),
-        );
+        let signer = RequestSigner::from_env()?;
         let endpoint =
             Some(env::var("ENDPOINT").unwrap_or("https://152.32.186.45:20002".to_string()));
         let domain_name = Some(env::var("DOMAIN_NAME").unwrap_or("stage".to_string()));
@@ -91,11 +98,6 @@ impl NetworkProver {
             None => Endpoint::new(endpoint_para.to_owned())?,
         };
 
-        let private_key = proof_network_privkey.to_owned().expect("ZKM_PRIVATE_KEY must be set");
-        if private_key.is_empty() {
-            panic!("Please set the ZKM_PRIVATE_KEY");
-        }
-        let wallet = private_key.parse::<LocalWallet>()?;
         let local_prover = CpuProver::new();
         let mut poll_interval = env::var("ZKM_PROOF_POLL_INTERVAL")
             .ok()
@@ -106,10 +108,16 @@ impl NetworkProver {
             poll_interval = MIN_POLL_INTERVAL;
         }
 
-        Ok(NetworkProver { endpoint, wallet, local_prover, poll_interval })
+        let max_proof_size = env::var("ZKM_MAX_PROOF_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(MAX_PROOF_SIZE);
+
+        Ok(NetworkProver { endpoint, signer, local_prover, poll_interval, max_proof_size })
     }
 
-    pub async fn sign_ecdsa(&self, request: &mut GenerateProofRequest) -> Result<()> {
+    /// Signs `request` with the key signing the requests, and a fresh nonce.
+    pub async fn sign_ecdsa(&self, request: &mut GenerateProofRequest) -> Result<RequestSignature> {
         let sign_data = match request.block_no {
             Some(block_no) => {
                 format!("{}&{}&{}", request.proof_id, block_no, request.seg_size)
@@ -118,15 +126,42 @@ impl NetworkProver {
                 format!("{}&{}", request.proof_id, request.seg_size)
             }
         };
-        let signature = self.wallet.sign_message(sign_data).await?;
-        request.signature = signature.to_string();
-        Ok(())
+        let signature = self.signer.sign(&sign_data).await?;
+        request.signature = signature.signature.clone();
+        request.nonce = Some(signature.nonce);
+        request.signer = Some(format!("{:?}", signature.signer));
+        Ok(signature)
     }
 
+    /// Downloads a file, failing if it is larger than [MAX_PROOF_SIZE] bytes.
     pub async fn download_file(url: &str) -> Result<Vec<u8>> {
-        let response = reqwest::get(url).await?;
-        let content = response.bytes().await?;
-        Ok(content.to_vec())
+        Self::download_file_with_limit(url, MAX_PROOF_SIZE).await
+    }
+
+    /// Downloads a file, failing if it is larger than `limit` bytes.
+    ///
+    /// The file is streamed, and the download stops as soon as it exceeds the limit, whether or
+    /// not the server sent a `Content-Length`. An interrupted download is retried, resuming from
+    /// the bytes already received when the server supports range requests.
+    pub async fn download_file_with_limit(url: &str, limit: u64) -> Result<Vec<u8>> {
+        let client = reqwest::Client::new();
+        let mut content = Vec::new();
+        let mut retries = 0;
+        loop {
+            match download_remaining(&client, url, limit, &mut content).await {
+                Ok(()) => return Ok(content),
+                Err(e) if retries < MAX_RETRIES && !e.is::<DeserializeError>() => {
+                    retries += 1;
+                    metrics::retry();
+                    log::warn!(
+                        "download of {url} interrupted after {} bytes, retrying: {e}",
+                        content.len()
+                    );
+                    sleep(RETRY_DELAY * retries).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     pub async fn connect(&self) -> StageServiceClient<Channel> {
@@ -135,7 +170,184 @@ impl NetworkProver {
             .expect("connect: {self.endpoint:?}")
     }
 
-    async fn request_proof(&self, input: ProverInput, kind: ZKMProofKind) -> Result<String> {
+    /// Submits a job proving the execution of `elf` on `stdin` to the network, without waiting for
+    /// it. The returned [JobId] can be saved to follow the job after a restart of the client.
+    ///
+    /// The network proves [ZKMProofKind::Compressed] and [ZKMProofKind::Groth16] proofs.
+    pub async fn submit(
+        &self,
+        elf: &[u8],
+        stdin: &ZKMStdin,
+        kind: ZKMProofKind,
+        // The SHA-256 hash of the ELF, without the 0x prefix.
+        // If this field is not none, the network prover will use it to index the cached ELF.
+        elf_id: Option<String>,
+    ) -> Result<JobId> {
+        let request = ProofRequest { elf_id, ..ProofRequest::new(kind) };
+        self.submit_proof_request(elf, stdin, &request).await
+    }
+
+    /// Asks the network the price of a proof of `kind` of the program whose ELF has the SHA-256
+    /// hash `elf_digest`, without the 0x prefix, executing for `cycles` cycles.
+    ///
+    /// The quote can be passed to [ProofRequest::quote] to request the proof at its price.
+    pub async fn quote(
+        &self,
+        elf_digest: &str,
+        cycles: u64,
+        kind: ZKMProofKind,
+    ) -> Result<PriceQuote> {
+        let request = GetQuoteRequest {
+            elf_id: elf_digest.to_string(),
+            cycles,
+            target_step: target_step(kind)?.into(),
+        };
+        let response =
+            retry(|| async { Ok(self.try_connect().await?.get_quote(request.clone()).await?) })
+                .await?
+                .into_inner();
+        match Status::from_i32(response.status) {
+            Some(Status::Success) => Ok(PriceQuote::from(&response)),
+            _ => bail!("failed to quote the proof of {elf_digest}: {}", response.error_message),
+        }
+    }
+
+    /// Submits a job proving the execution of `elf` on `stdin` to the network on the terms of
+    /// `request`, without waiting for it. [NetworkProver::await_proof] waits for its proof.
+    ///
+    /// Fails without submitting the job if its deadline has passed, or if the quote it uses has
+    /// expired or is above its maximum price.
+    pub async fn submit_proof_request(
+        &self,
+        elf: &[u8],
+        stdin: &ZKMStdin,
+        request: &ProofRequest,
+    ) -> Result<JobId> {
+        request.check()?;
+        if !stdin.files.is_empty() {
+            bail!("virtual files are not supported by the proof network");
+        }
+        if !stdin.named_inputs.is_empty() {
+            bail!("named inputs are not supported by the proof network");
+        }
+
+        let mut pri_buf = Vec::new();
+        bincode::serialize_into(&mut pri_buf, &stdin.buffer)?;
+
+        let mut receipts = Vec::new();
+        // todo: adapt to proof network after its updating
+        for proof in &stdin.proofs {
+            let mut receipt = Vec::new();
+            bincode::serialize_into(&mut receipt, proof)?;
+            receipts.push(receipt);
+        }
+
+        let elf_id = request.elf_id.clone();
+        let elf = if elf_id.is_none() { elf.to_vec() } else { Default::default() };
+
+        let prover_input = ProverInput { elf, private_inputstream: pri_buf, elf_id, receipts };
+        let job = JobId(self.request_proof(prover_input, request).await?);
+        metrics::job_submitted(request.kind);
+        Ok(job)
+    }
+
+    /// Waits for the job to complete, and downloads its proof with its public values.
+    ///
+    /// Fails if the job fails, is cancelled, or does not complete within `timeout`.
+    pub async fn await_proof(
+        &self,
+        job: &JobId,
+        timeout: Option<Duration>,
+    ) -> Result<ZKMProofWithPublicValues> {
+        let (proof, public_values, _) = self.wait_proof(job, timeout).await?;
+        Ok(ZKMProofWithPublicValues {
+            proof,
+            public_values,
+            zkm_version: ZKM_CIRCUIT_VERSION.to_string(),
+        })
+    }
+
+    /// Returns the stage the job is at.
+    pub async fn status(&self, job: &JobId) -> Result<JobStatus> {
+        Ok(JobStatus::from(&self.get_status(job).await?))
+    }
+
+    /// Cancels the job. The provers working on it are released, and its proof can no longer be
+    /// downloaded.
+    ///
+    /// Requires a proof network server which supports cancellation, and that server is not part
+    /// of this repository. Fails if the server does not support it.
+    pub async fn cancel(&self, job: &JobId) -> Result<()> {
+        let signature = self.signer.sign_for_job(&job.0, &job.0).await?;
+        let request = CancelProofRequest {
+            proof_id: job.0.clone(),
+            signature: signature.signature,
+            nonce: Some(signature.nonce),
+            signer: Some(format!("{:?}", signature.signer)),
+        };
+        let response = self.try_connect().await?.cancel_proof(request).await?.into_inner();
+        match Status::from_i32(response.status) {
+            Some(Status::Success) | Some(Status::Cancelled) => Ok(()),
+            _ => bail!("failed to cancel job {job}: {}", response.error_message),
+        }
+    }
+
+    /// Downloads the proof of a completed job, with its public values.
+    ///
+    /// Failed requests are retried, and interrupted downloads resumed.
+    pub async fn download_proof(&self, job: &JobId) -> Result<ZKMProofWithPublicValues> {
+        let (proof, public_values, _) = self.download_proof_with_cycles(job).await?;
+        Ok(ZKMProofWithPublicValues {
+            proof,
+            public_values,
+            zkm_version: ZKM_CIRCUIT_VERSION.to_string(),
+        })
+    }
+
+    async fn download_proof_with_cycles(
+        &self,
+        job: &JobId,
+    ) -> Result<(ZKMProof, ZKMPublicValues, u64)> {
+        let response = retry(|| self.get_status(job)).await?;
+        match JobStatus::from(&response) {
+            JobStatus::Completed => {}
+            status => bail!("the proof of job {job} is not available, the job is {status}"),
+        }
+
+        // Jobs wrapping a compressed proof take its public values as input, and upload none.
+        let public_values = if response.public_values_url.is_empty() {
+            ZKMPublicValues::default()
+        } else {
+            let public_values_bytes = NetworkProver::download_file_with_limit(
+                &response.public_values_url,
+                self.max_proof_size,
+            )
+            .await?;
+            ZKMPublicValues::from(&public_values_bytes)
+        };
+
+        // proof
+        let proof_bytes = &response.proof_with_public_inputs;
+        let size = proof_bytes.len() as u64;
+        if size > self.max_proof_size {
+            return Err(DeserializeError::TooLarge { size, limit: self.max_proof_size }.into());
+        }
+        let proof: ZKMProof = serde_json::from_slice(proof_bytes)
+            .map_err(|e| anyhow!("failed to deserialize proof {job}: {e}"))?;
+        Ok((proof, public_values, response.total_steps))
+    }
+
+    async fn get_status(&self, job: &JobId) -> Result<GetStatusResponse> {
+        let request = GetStatusRequest { proof_id: job.0.clone() };
+        Ok(self.try_connect().await?.get_status(request).await?.into_inner())
+    }
+
+    async fn try_connect(&self) -> Result<StageServiceClient<Channel>> {
+        Ok(StageServiceClient::connect(self.endpoint.clone()).await?)
+    }
+
+    async fn request_proof(&self, input: ProverInput, terms: &ProofRequest) -> Result<String> {
+        let kind = terms.kind;
         let seg_size =
             env::var("SHARD_SIZE").ok().and_then(|s| s.parse::<u32>().ok()).unwrap_or_default();
 
@@ -152,13 +364,10 @@ impl NetworkProver {
         let from_step =
             if kind == ZKMProofKind::CompressToGroth16 { Some(Step::InAgg.into()) } else { None };
 
-        let target_step = if kind == ZKMProofKind::Compressed {
-            Step::InAgg
-        } else if kind == ZKMProofKind::Groth16 || kind == ZKMProofKind::CompressToGroth16 {
-            Step::InSnark
-        } else {
-            unimplemented!("unsupported ZKMProofKind")
-        };
+        let target_step = target_step(kind)?;
+        let deadline = terms
+            .deadline
+            .map(|deadline| deadline.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs());
 
         let mut request = GenerateProofRequest {
             proof_id: uuid::Uuid::new_v4().to_string(),
@@ -171,72 +380,65 @@ impl NetworkProver {
             receipt_inputs: input.receipts,
             max_prover_num,
             single_node,
+            deadline,
+            max_price: terms.max_price,
+            quote_id: terms.quote.as_ref().map(|quote| quote.id.clone()),
             ..Default::default()
         };
 
-        self.sign_ecdsa(&mut request).await?;
+        let signature = self.sign_ecdsa(&mut request).await?;
         let mut client = self.connect().await;
 
         let start = tokio::time::Instant::now();
         let response = client.generate_proof(request).await?.into_inner();
         tracing::info!("[request proof] get response: {:?}", start.elapsed());
+        self.signer.submitted(&response.proof_id, signature.signer);
 
         Ok(response.proof_id)
     }
 
+    /// Waits for the job to complete, and downloads its proof.
     async fn wait_proof(
         &self,
-        proof_id: &str,
-        kind: ZKMProofKind,
+        job: &JobId,
         timeout: Option<Duration>,
     ) -> Result<(ZKMProof, ZKMPublicValues, u64)> {
         let start_time = Instant::now();
-        let mut client = self.connect().await;
         loop {
             if let Some(timeout) = timeout {
                 if start_time.elapsed() > timeout {
+                    metrics::job_finished(JobOutcome::TimedOut, start_time.elapsed());
                     bail!("Proof generation timed out.");
                 }
             }
 
-            let get_status_request = GetStatusRequest { proof_id: proof_id.to_string() };
-            let get_status_response = client.get_status(get_status_request).await?.into_inner();
-
-            match Status::from_i32(get_status_response.status) {
-                Some(Status::Computing) => {
-                    match Step::from_i32(get_status_response.step) {
-                        Some(step) => log::info!("Generate_proof: {step}"),
-                        None => todo!(),
-                    }
-                    sleep(Duration::from_millis(self.poll_interval)).await;
+            match retry(|| self.status(job)).await? {
+                JobStatus::Completed => {
+                    metrics::job_finished(JobOutcome::Completed, start_time.elapsed());
+                    break;
                 }
-                Some(Status::Success) => {
-                    let public_values = if kind == ZKMProofKind::CompressToGroth16 {
-                        ZKMPublicValues::default()
+                status @ (JobStatus::Cancelled | JobStatus::Failed(_)) => {
+                    let outcome = if status == JobStatus::Cancelled {
+                        JobOutcome::Cancelled
                     } else {
-                        let public_values_bytes =
-                            NetworkProver::download_file(&get_status_response.public_values_url)
-                                .await?;
-                        ZKMPublicValues::from(&public_values_bytes)
+                        JobOutcome::Failed
                     };
-
-                    // proof
-                    let proof: ZKMProof =
-                        serde_json::from_slice(&get_status_response.proof_with_public_inputs)
-                            .expect("Failed to deserialize proof");
-                    let cycles = get_status_response.total_steps;
-                    let proving_time = get_status_response.proving_time;
-                    tracing::info!(
-                        "Proof generation completed successfully, proof_id: {proof_id}, cycles: {cycles}, proving time: {proving_time}ms"
-                    );
-                    return Ok((proof, public_values, cycles));
+                    metrics::job_finished(outcome, start_time.elapsed());
+                    log::error!("generate_proof {status}");
+                    bail!("generate_proof {status}");
                 }
-                _ => {
-                    log::error!("generate_proof failed status: {}", get_status_response.status);
-                    bail!("generate_proof failed status: {}", get_status_response.status);
+                status => {
+                    log::info!("Generate_proof: {status}");
+                    sleep(Duration::from_millis(self.poll_interval)).await;
                 }
             }
         }
+
+        let (proof, public_values, cycles) = self.download_proof_with_cycles(job).await?;
+        tracing::info!(
+            "Proof generation completed successfully, proof_id: {job}, cycles: {cycles}"
+        );
+        Ok((proof, public_values, cycles))
     }
 
     pub async fn prove_with_cycles(
@@ -249,32 +451,46 @@ impl NetworkProver {
         elf_id: Option<String>,
         timeout: Option<Duration>,
     ) -> Result<(ZKMProofWithPublicValues, u64)> {
-        let private_input = stdin.buffer.clone();
-        let mut pri_buf = Vec::new();
-        bincode::serialize_into(&mut pri_buf, &private_input)?;
-
-        let mut receipts = Vec::new();
-        let proofs = stdin.proofs.clone();
-        // todo: adapt to proof network after its updating
-        for proof in proofs {
-            let mut receipt = Vec::new();
-            bincode::serialize_into(&mut receipt, &proof)?;
-            receipts.push(receipt);
-        }
-
-        let elf = if elf_id.is_none() { elf.to_vec() } else { Default::default() };
-
-        let prover_input = ProverInput { elf, private_inputstream: pri_buf, elf_id, receipts };
+        let request = ProofRequest { elf_id, ..ProofRequest::new(kind) };
+        self.prove_request(elf, stdin, &request, timeout, None).await
+    }
 
+    /// Proves the execution of `elf` on `stdin` on the terms of `request`, returning the cycle
+    /// count in addition to the proof.
+    ///
+    /// A job which fails to complete within `timeout` is cancelled, not to leave the network
+    /// proving a job given up on. The proof is only accepted if it proves the program with the
+    /// verifying key `vk`, if given.
+    pub(crate) async fn prove_request(
+        &self,
+        elf: &[u8],
+        stdin: ZKMStdin,
+        request: &ProofRequest,
+        timeout: Option<Duration>,
+        vk: Option<&ZKMVerifyingKey>,
+    ) -> Result<(ZKMProofWithPublicValues, u64)> {
         log::info!("calling request_proof.");
-        let proof_id = self.request_proof(prover_input, kind).await?;
-
-        log::info!("calling wait_proof, proof_id={proof_id}");
-        let (proof, mut public_values, cycles) = self.wait_proof(&proof_id, kind, timeout).await?;
+        let job = self.submit_proof_request(elf, &stdin, request).await?;
+
+        log::info!("calling wait_proof, proof_id={job}");
+        let (proof, mut public_values, cycles) = match self.wait_proof(&job, timeout).await {
+            Ok(proof) => proof,
+            Err(e) => {
+                if self.status(&job).await.is_ok_and(|status| !status.is_terminal()) {
+                    if let Err(cancel) = self.cancel(&job).await {
+                        log::warn!("failed to cancel the job {job}: {cancel}");
+                    }
+                }
+                return Err(e);
+            }
+        };
+        if let Some(vk) = vk {
+            check_proof_vk(self.local_prover.zkm_prover(), &proof, vk)?;
+        }
 
-        if kind == ZKMProofKind::CompressToGroth16 {
-            assert_eq!(private_input.len(), 1);
-            public_values = bincode::deserialize(private_input.last().unwrap())?;
+        if request.kind == ZKMProofKind::CompressToGroth16 {
+            assert_eq!(stdin.buffer.len(), 1);
+            public_values = bincode::deserialize(stdin.buffer.last().unwrap())?;
         }
 
         Ok((
@@ -312,7 +528,82 @@ impl Prover<DefaultProverComponents> for NetworkProver {
         kind: ZKMProofKind,
         elf_id: Option<String>,
     ) -> Result<(ZKMProofWithPublicValues, u64)> {
-        block_on(self.prove_with_cycles(&pk.elf, stdin, kind, elf_id, None))
+        let request = ProofRequest { elf_id, ..ProofRequest::new(kind) };
+        // The verifying key of a wrapped compressed proof is not the one of the program.
+        let vk = (kind != ZKMProofKind::CompressToGroth16).then_some(&pk.vk);
+        block_on(self.prove_request(&pk.elf, stdin, &request, None, vk))
+    }
+}
+
+/// Downloads the rest of the file at `url` into `content`, resuming after the bytes it holds.
+async fn download_remaining(
+    client: &reqwest::Client,
+    url: &str,
+    limit: u64,
+    content: &mut Vec<u8>,
+) -> Result<()> {
+    let mut request = client.get(url);
+    if !content.is_empty() {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", content.len()));
+    }
+    let mut response = request.send().await?.error_for_status()?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        // The server ignored the range, and sends the whole file again.
+        content.clear();
+    }
+    // Reject the file early if the server announces its size, and count the bytes received
+    // otherwise, since the announced size is not authenticated.
+    if let Some(remaining) = response.content_length() {
+        let size = content.len() as u64 + remaining;
+        if size > limit {
+            return Err(DeserializeError::TooLarge { size, limit }.into());
+        }
+    }
+    while let Some(chunk) = response.chunk().await? {
+        content.extend_from_slice(&chunk);
+        let size = content.len() as u64;
+        if size > limit {
+            return Err(DeserializeError::TooLarge { size, limit }.into());
+        }
+    }
+    Ok(())
+}
+
+/// Whether the network generates proofs of `kind`.
+pub(crate) fn supports(kind: ZKMProofKind) -> bool {
+    target_step(kind).is_ok()
+}
+
+/// The step of the network after which a proof of `kind` is complete.
+fn target_step(kind: ZKMProofKind) -> Result<Step> {
+    match kind {
+        ZKMProofKind::Compressed => Ok(Step::InAgg),
+        ZKMProofKind::Groth16 | ZKMProofKind::CompressToGroth16 => Ok(Step::InSnark),
+        _ => bail!("the proof network does not generate {kind:?} proofs"),
+    }
+}
+
+/// Runs `f` until it succeeds, up to [MAX_RETRIES] more times.
+///
+/// Only idempotent requests, such as quotes and status queries, are retried: a `GenerateProof`
+/// request which failed may still have created a job, and is never sent again.
+async fn retry<T, F, Fut>(mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) if retries < MAX_RETRIES => {
+                retries += 1;
+                metrics::retry();
+                log::warn!("request to the proof network failed, retrying: {e}");
+                sleep(RETRY_DELAY * retries).await;
+            }
+            Err(e) => return Err(e),
+        }
     }
 }
 
@@ -0,0 +1,206 @@
+//! # Ziren Onchain Relayer
+//!
+//! Helpers for submitting Ziren proofs to the onchain `IZKMVerifier` contracts.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use ethers::{
+    abi::{encode, Token},
+    providers::Middleware,
+    types::{Address, Bytes, TransactionRequest, TxHash, U256},
+    utils::id,
+};
+use zkm_prover::{HashableKey, ZKMVerifyingKey};
+
+use crate::{async_client::AsyncProve, ZKMProof, ZKMProofWithPublicValues};
+
+/// The signature of `IZKMVerifier.verifyProof`.
+pub const VERIFY_PROOF_SIGNATURE: &str = "verifyProof(bytes32,bytes,bytes)";
+
+/// The default gas buffer added on top of the estimated gas, in percent.
+pub const DEFAULT_GAS_BUFFER_PERCENT: u64 = 20;
+
+/// A transport used by the [Relayer] to estimate and submit verification transactions.
+///
+/// Implement this trait to plug in a custom signer or RPC client. [MiddlewareSubmitter] provides
+/// an implementation for any `ethers` [Middleware].
+#[async_trait]
+pub trait OnchainSubmitter: Send + Sync {
+    /// Estimates the gas required to call `to` with `calldata`.
+    async fn estimate_gas(&self, to: Address, calldata: &Bytes) -> Result<u64>;
+
+    /// Signs and submits a transaction calling `to` with `calldata`, returning its hash.
+    async fn send_transaction(&self, to: Address, calldata: Bytes, gas: u64) -> Result<TxHash>;
+}
+
+/// An [OnchainSubmitter] backed by an `ethers` [Middleware], e.g. a `SignerMiddleware`.
+pub struct MiddlewareSubmitter<M> {
+    middleware: Arc<M>,
+}
+
+impl<M: Middleware> MiddlewareSubmitter<M> {
+    /// Creates a new [MiddlewareSubmitter].
+    pub fn new(middleware: Arc<M>) -> Self {
+        Self { middleware }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware + 'static> OnchainSubmitter for MiddlewareSubmitter<M> {
+    async fn estimate_gas(&self, to: Address, calldata: &Bytes) -> Result<u64> {
+        let tx = TransactionRequest::new().to(to).data(calldata.clone());
+        let gas = self.middleware.estimate_gas(&tx.into(), None).await?;
+        Ok(gas.as_u64())
+    }
+
+    async fn send_transaction(&self, to: Address, calldata: Bytes, gas: u64) -> Result<TxHash> {
+        let tx = TransactionRequest::new().to(to).data(calldata).gas(U256::from(gas));
+        let pending = self.middleware.send_transaction(tx, None).await?;
+        Ok(pending.tx_hash())
+    }
+}
+
+/// Formats Ziren proofs as calldata for the onchain verifier and submits them.
+pub struct Relayer<S> {
+    submitter: S,
+    verifier: Address,
+    gas_buffer_percent: u64,
+}
+
+impl<S: OnchainSubmitter> Relayer<S> {
+    /// Creates a new [Relayer] submitting to the verifier contract at `verifier`.
+    pub fn new(submitter: S, verifier: Address) -> Self {
+        Self { submitter, verifier, gas_buffer_percent: DEFAULT_GAS_BUFFER_PERCENT }
+    }
+
+    /// Set the gas buffer added on top of the estimated gas, in percent.
+    pub fn gas_buffer_percent(mut self, value: u64) -> Self {
+        self.gas_buffer_percent = value;
+        self
+    }
+
+    /// Returns the address of the verifier contract.
+    pub fn verifier(&self) -> Address {
+        self.verifier
+    }
+
+    /// Estimates the gas required to verify the given proof onchain, without the gas buffer.
+    pub async fn estimate_gas(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<u64> {
+        let calldata = verify_proof_calldata(proof, vk)?;
+        self.submitter.estimate_gas(self.verifier, &calldata).await
+    }
+
+    /// Submits a transaction verifying the given proof onchain, returning the transaction hash.
+    pub async fn submit(
+        &self,
+        proof: &ZKMProofWithPublicValues,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<TxHash> {
+        let calldata = verify_proof_calldata(proof, vk)?;
+        let gas = self.submitter.estimate_gas(self.verifier, &calldata).await?;
+        let gas = gas.saturating_mul(100 + self.gas_buffer_percent) / 100;
+        tracing::info!("submitting proof to verifier {:?} with gas limit {}", self.verifier, gas);
+        self.submitter.send_transaction(self.verifier, calldata, gas).await
+    }
+
+    /// Runs the given [AsyncProve] and submits the resulting proof onchain, returning the proof
+    /// together with the transaction hash.
+    ///
+    /// The proof mode of `prove` must be set to [AsyncProve::plonk] or [AsyncProve::groth16].
+    /// Local proving runs on a dedicated thread, so awaiting this does not block the runtime.
+    pub async fn prove_and_submit(
+        &self,
+        prove: AsyncProve,
+        vk: &ZKMVerifyingKey,
+    ) -> Result<(ZKMProofWithPublicValues, TxHash)> {
+        let proof = prove.await?;
+        let tx_hash = self.submit(&proof, vk).await?;
+        Ok((proof, tx_hash))
+    }
+}
+
+/// Encodes a call to `IZKMVerifier.verifyProof` for the given proof and verifying key.
+///
+/// Only Plonk and Groth16 proofs can be verified onchain.
+pub fn verify_proof_calldata(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<Bytes> {
+    if !matches!(proof.proof, ZKMProof::Plonk(_) | ZKMProof::Groth16(_)) {
+        bail!("only Plonk and Groth16 proofs are verifiable onchain");
+    }
+
+    let program_vkey: [u8; 32] = hex::decode(vk.bytes32().trim_start_matches("0x"))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("invalid program vkey length"))?;
+    let args = encode(&[
+        Token::FixedBytes(program_vkey.to_vec()),
+        Token::Bytes(proof.public_values.to_vec()),
+        Token::Bytes(proof.bytes()),
+    ]);
+
+    Ok([&id(VERIFY_PROOF_SIGNATURE)[..], &args].concat().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::executor::block_on;
+    use zkm_core_machine::io::ZKMStdin;
+
+    use super::*;
+    use crate::AsyncProverClient;
+
+    /// A submitter recording the transactions it is asked to send.
+    #[derive(Default)]
+    struct RecordingSubmitter {
+        sent: Mutex<Vec<(Address, Bytes, u64)>>,
+    }
+
+    #[async_trait]
+    impl OnchainSubmitter for RecordingSubmitter {
+        async fn estimate_gas(&self, _to: Address, _calldata: &Bytes) -> Result<u64> {
+            Ok(100_000)
+        }
+
+        async fn send_transaction(&self, to: Address, calldata: Bytes, gas: u64) -> Result<TxHash> {
+            self.sent.lock().unwrap().push((to, calldata, gas));
+            Ok(TxHash::repeat_byte(1))
+        }
+    }
+
+    #[test]
+    fn test_verify_proof_selector() {
+        // The selector of `verifyProof(bytes32,bytes,bytes)`.
+        assert_eq!(id(VERIFY_PROOF_SIGNATURE), [0x41, 0x49, 0x3c, 0x60]);
+    }
+
+    #[test]
+    fn test_prove_and_submit() {
+        let client = AsyncProverClient::mock();
+        let (pk, vk) = block_on(client.setup(test_artifacts::FIBONACCI_ELF));
+        let verifier = Address::repeat_byte(2);
+        let relayer = Relayer::new(RecordingSubmitter::default(), verifier);
+
+        let prove = client.prove(&pk, ZKMStdin::new()).plonk();
+        let (proof, tx_hash) = block_on(relayer.prove_and_submit(prove, &vk)).unwrap();
+        assert_eq!(tx_hash, TxHash::repeat_byte(1));
+
+        let sent = relayer.submitter.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, verifier);
+        assert_eq!(sent[0].1, verify_proof_calldata(&proof, &vk).unwrap());
+        assert_eq!(sent[0].2, 120_000);
+
+        // Only proofs verifiable onchain are submitted.
+        let prove = client.prove(&pk, ZKMStdin::new()).compressed();
+        assert!(block_on(relayer.prove_and_submit(prove, &vk)).is_err());
+    }
+}
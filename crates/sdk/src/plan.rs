@@ -0,0 +1,374 @@
+//! Planning of the proving pipeline of a program under a time and memory budget.
+//!
+//! [crate::ProverClient::plan] estimates the cost of proving a program with every backend and
+//! proof kind reaching a [ProofTarget], rejects the pipelines exceeding the budget, and selects
+//! the fastest of the remaining ones. The returned [ProofPlan] lists every pipeline considered,
+//! with its estimated cost and the reason it was rejected, so operators can see why a pipeline
+//! was chosen before running it.
+//!
+//! The costs are derived from the [CostEstimate] of the execution. The wrapping costs are rough
+//! measurements of the gnark circuits, which run on the CPU whatever the backend.
+
+use std::{fmt, time::Duration};
+
+use cfg_if::cfg_if;
+use thiserror::Error;
+use zkm_prover::estimate::{CostEstimate, ProverBackend};
+
+use crate::{action, ProverClient, ZKMProofKind, ZKMProvingKey, ZKMStdin};
+
+/// The time the network takes to accept the program and input, and to schedule the proof.
+const NETWORK_OVERHEAD: Duration = Duration::from_secs(120);
+
+/// The proof a plan must produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofTarget {
+    /// A core proof, verified off-chain.
+    Core,
+    /// A compressed proof, verified off-chain or recursively in another program.
+    Compressed,
+    /// A Groth16 proof, verified on-chain.
+    OnChainGroth16,
+    /// A PLONK proof, verified on-chain.
+    OnChainPlonk,
+    /// Either a Groth16 or a PLONK proof, verified on-chain.
+    OnChain,
+}
+
+impl ProofTarget {
+    /// The proof kinds reaching the target.
+    pub fn kinds(self) -> &'static [ZKMProofKind] {
+        match self {
+            ProofTarget::Core => &[ZKMProofKind::Core],
+            ProofTarget::Compressed => &[ZKMProofKind::Compressed],
+            ProofTarget::OnChainGroth16 => &[ZKMProofKind::Groth16],
+            ProofTarget::OnChainPlonk => &[ZKMProofKind::Plonk],
+            ProofTarget::OnChain => &[ZKMProofKind::Groth16, ZKMProofKind::Plonk],
+        }
+    }
+}
+
+/// Where the proof of a plan is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlanBackend {
+    /// The local CPU prover.
+    Cpu,
+    /// The local GPU prover.
+    Cuda,
+    /// The ZKM Proof Network.
+    Network,
+}
+
+impl PlanBackend {
+    /// All the backends, in the order ties between plans are broken.
+    pub const ALL: [PlanBackend; 3] = [PlanBackend::Cpu, PlanBackend::Cuda, PlanBackend::Network];
+
+    /// Whether the backend can generate proofs of `kind`.
+    pub fn supports(self, kind: ZKMProofKind) -> bool {
+        match self {
+            PlanBackend::Cpu | PlanBackend::Cuda => kind != ZKMProofKind::CompressToGroth16,
+            PlanBackend::Network => {
+                matches!(kind, ZKMProofKind::Compressed | ZKMProofKind::Groth16)
+            }
+        }
+    }
+
+    /// Creates a [ProverClient] proving with the backend.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [PlanBackend::Network] if the `network` feature is not enabled.
+    pub fn client(self) -> ProverClient {
+        match self {
+            PlanBackend::Cpu => ProverClient::cpu(),
+            PlanBackend::Cuda => ProverClient::cuda(),
+            PlanBackend::Network => {
+                cfg_if! {
+                    if #[cfg(feature = "network")] {
+                        ProverClient::network()
+                    } else {
+                        panic!("network feature is not enabled")
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for PlanBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanBackend::Cpu => f.write_str("cpu"),
+            PlanBackend::Cuda => f.write_str("cuda"),
+            PlanBackend::Network => f.write_str("network"),
+        }
+    }
+}
+
+/// A pipeline considered by a plan, with its estimated cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanCandidate {
+    pub backend: PlanBackend,
+    pub kind: ZKMProofKind,
+    /// The estimated time to generate the proof.
+    pub time: Duration,
+    /// The estimated peak memory of the local machine, in bytes.
+    pub memory: u64,
+    /// Why the pipeline can't be used, if it can't.
+    pub rejection: Option<String>,
+}
+
+impl PlanCandidate {
+    /// Whether the pipeline fits the budget.
+    pub fn is_feasible(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+impl fmt::Display for PlanCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: ~{}s, ~{} MiB",
+            self.backend,
+            kind_name(self.kind),
+            self.time.as_secs(),
+            self.memory >> 20
+        )?;
+        if let Some(rejection) = &self.rejection {
+            write!(f, " (rejected: {rejection})")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when no pipeline fits the budget.
+#[derive(Error, Debug)]
+pub enum PlanError {
+    #[error("no proving pipeline fits the budget:\n{}", list_candidates(.0))]
+    Infeasible(Vec<PlanCandidate>),
+}
+
+/// The budget a plan must fit in.
+#[derive(Debug, Clone, Copy)]
+pub struct PlanBudget {
+    pub max_time: Option<Duration>,
+    /// The maximum peak memory of the local machine, in bytes.
+    pub max_memory: Option<u64>,
+}
+
+/// The pipeline selected to prove a program, with the alternatives considered.
+#[derive(Debug, Clone)]
+pub struct ProofPlan {
+    pub target: ProofTarget,
+    pub budget: PlanBudget,
+    /// The cost estimate of the execution the plan is derived from.
+    pub estimate: CostEstimate,
+    /// Every pipeline considered, including the selected one.
+    pub candidates: Vec<PlanCandidate>,
+    selected: usize,
+}
+
+impl ProofPlan {
+    /// Plans the proof of an execution, considering `backends` in order.
+    ///
+    /// Selects the fastest pipeline fitting the budget, preferring earlier backends on ties.
+    pub fn new(
+        estimate: CostEstimate,
+        target: ProofTarget,
+        budget: PlanBudget,
+        backends: &[PlanBackend],
+    ) -> Result<Self, PlanError> {
+        let candidates = backends
+            .iter()
+            .flat_map(|&backend| target.kinds().iter().map(move |&kind| (backend, kind)))
+            .map(|(backend, kind)| candidate(&estimate, budget, backend, kind))
+            .collect::<Vec<_>>();
+
+        let selected = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.is_feasible())
+            .min_by_key(|(i, candidate)| (candidate.time, *i))
+            .map(|(i, _)| i);
+        match selected {
+            Some(selected) => Ok(Self { target, budget, estimate, candidates, selected }),
+            None => Err(PlanError::Infeasible(candidates)),
+        }
+    }
+
+    /// The selected pipeline.
+    pub fn selected(&self) -> &PlanCandidate {
+        &self.candidates[self.selected]
+    }
+
+    /// The backend of the selected pipeline.
+    pub fn backend(&self) -> PlanBackend {
+        self.selected().backend
+    }
+
+    /// The proof kind of the selected pipeline.
+    pub fn kind(&self) -> ZKMProofKind {
+        self.selected().kind
+    }
+
+    /// Creates a [ProverClient] for the backend of the selected pipeline.
+    pub fn client(&self) -> ProverClient {
+        self.backend().client()
+    }
+
+    /// Prepares to prove with `client` in the proof kind of the selected pipeline.
+    pub fn prove<'a>(
+        &self,
+        client: &'a ProverClient,
+        pk: &'a ZKMProvingKey,
+        stdin: ZKMStdin,
+    ) -> action::Prove<'a> {
+        let prove = client.prove(pk, stdin);
+        match self.kind() {
+            ZKMProofKind::Core => prove.core(),
+            ZKMProofKind::Compressed => prove.compressed(),
+            ZKMProofKind::Plonk => prove.plonk(),
+            ZKMProofKind::Groth16 => prove.groth16(),
+            ZKMProofKind::CompressToGroth16 => prove.compress_to_groth16(),
+        }
+    }
+}
+
+impl fmt::Display for ProofPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "selected: {}", self.selected())?;
+        writeln!(
+            f,
+            "workload: {} cycles, {} shards, {} cells, largest shard {} cells",
+            self.estimate.cycles(),
+            self.estimate.num_shards,
+            self.estimate.trace_area(),
+            self.estimate.max_shard_area()
+        )?;
+        write!(f, "{}", list_candidates(&self.candidates))
+    }
+}
+
+fn list_candidates(candidates: &[PlanCandidate]) -> String {
+    candidates.iter().map(|candidate| format!("  {candidate}\n")).collect()
+}
+
+fn kind_name(kind: ZKMProofKind) -> &'static str {
+    match kind {
+        ZKMProofKind::Core => "core",
+        ZKMProofKind::Compressed => "compressed",
+        ZKMProofKind::Plonk => "plonk",
+        ZKMProofKind::Groth16 => "groth16",
+        ZKMProofKind::CompressToGroth16 => "compress-to-groth16",
+    }
+}
+
+/// The time and memory of wrapping a compressed proof into a proof of `kind`, including the
+/// shrinking and wrapping of the STARK proof before the gnark circuit.
+fn wrap_cost(kind: ZKMProofKind) -> (Duration, u64) {
+    match kind {
+        ZKMProofKind::Groth16 => (Duration::from_secs(150), 16 << 30),
+        ZKMProofKind::Plonk => (Duration::from_secs(360), 40 << 30),
+        _ => (Duration::ZERO, 0),
+    }
+}
+
+fn candidate(
+    estimate: &CostEstimate,
+    budget: PlanBudget,
+    backend: PlanBackend,
+    kind: ZKMProofKind,
+) -> PlanCandidate {
+    let (wrap_time, wrap_memory) = wrap_cost(kind);
+    let prove_time = |backend| match kind {
+        ZKMProofKind::Core => estimate.core_proving_time(backend),
+        _ => estimate.proving_time(backend) + wrap_time,
+    };
+    let (time, memory) = match backend {
+        PlanBackend::Cpu => (
+            prove_time(ProverBackend::Cpu),
+            estimate.peak_memory(ProverBackend::Cpu).max(wrap_memory),
+        ),
+        PlanBackend::Cuda => (
+            prove_time(ProverBackend::Cuda),
+            estimate.peak_memory(ProverBackend::Cuda).max(wrap_memory),
+        ),
+        // The network proves on GPUs, and nothing runs locally.
+        PlanBackend::Network => (prove_time(ProverBackend::Cuda) + NETWORK_OVERHEAD, 0),
+    };
+
+    let rejection = if !backend.supports(kind) {
+        Some(format!("the {backend} backend can't generate {} proofs", kind_name(kind)))
+    } else if backend == PlanBackend::Network && !cfg!(feature = "network") {
+        Some("the `network` feature is not enabled".to_string())
+    } else if let Some(max_time) = budget.max_time.filter(|&max_time| time > max_time) {
+        Some(format!("exceeds the time budget of {}s", max_time.as_secs()))
+    } else {
+        budget
+            .max_memory
+            .filter(|&max_memory| memory > max_memory)
+            .map(|max_memory| format!("exceeds the memory budget of {} MiB", max_memory >> 20))
+    };
+
+    PlanCandidate { backend, kind, time, memory, rejection }
+}
+
+#[cfg(test)]
+mod tests {
+    use zkm_core_executor::{ExecutionReport, MipsAirId};
+
+    use super::*;
+
+    fn estimate(num_shards: usize) -> CostEstimate {
+        let mut report = ExecutionReport::default();
+        report.shard_event_counts = vec![Default::default(); num_shards];
+        for event_counts in &mut report.shard_event_counts {
+            event_counts[MipsAirId::Cpu] = 1 << 21;
+            event_counts[MipsAirId::AddSub] = 1 << 20;
+        }
+        CostEstimate::new(1 << 12, report)
+    }
+
+    #[test]
+    fn test_plan_selects_fastest_feasible() {
+        let budget = PlanBudget { max_time: None, max_memory: None };
+        let plan =
+            ProofPlan::new(estimate(4), ProofTarget::OnChain, budget, &PlanBackend::ALL).unwrap();
+        assert_eq!(plan.candidates.len(), 6);
+        assert_eq!(plan.backend(), PlanBackend::Cuda);
+        assert_eq!(plan.kind(), ZKMProofKind::Groth16);
+
+        let network_plonk = plan
+            .candidates
+            .iter()
+            .find(|c| c.backend == PlanBackend::Network && c.kind == ZKMProofKind::Plonk)
+            .unwrap();
+        assert!(!network_plonk.is_feasible());
+    }
+
+    #[test]
+    fn test_plan_budget() {
+        let estimate = estimate(4);
+        let budget = PlanBudget {
+            max_time: None,
+            max_memory: Some(estimate.peak_memory(ProverBackend::Cpu) - 1),
+        };
+        let plan = ProofPlan::new(
+            estimate.clone(),
+            ProofTarget::Compressed,
+            budget,
+            &[PlanBackend::Cpu, PlanBackend::Cuda],
+        )
+        .unwrap();
+        assert_eq!(plan.backend(), PlanBackend::Cuda);
+
+        let budget = PlanBudget { max_time: Some(Duration::from_secs(1)), max_memory: None };
+        let err =
+            ProofPlan::new(estimate, ProofTarget::OnChainGroth16, budget, &[PlanBackend::Cpu])
+                .unwrap_err();
+        let PlanError::Infeasible(candidates) = err;
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].rejection.as_ref().unwrap().contains("time budget"));
+    }
+}
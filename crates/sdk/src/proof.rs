@@ -1,14 +1,35 @@
-use std::{fmt::Debug, fs::File, path::Path};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{self, BufWriter, Chain, Cursor, Read, Write},
+    path::Path,
+};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use strum_macros::{EnumDiscriminants, EnumTryAs};
+use thiserror::Error;
 use zkm_core_executor::ZKMReduceProof;
-use zkm_primitives::io::ZKMPublicValues;
+use zkm_core_machine::{
+    io::{
+        deserialize_from_limited, deserialize_limited, open_limited, DeserializeError,
+        MAX_PROOF_SIZE,
+    },
+    ZKM_CIRCUIT_VERSION,
+};
+use zkm_primitives::{
+    envelope::{read_proof_envelope_header, ProofEnvelopeError},
+    io::{PublicValuesHash, ZKMPublicValues},
+};
 
-use zkm_prover::{CoreSC, Groth16Bn254Proof, InnerSC, PlonkBn254Proof};
+use zkm_prover::{
+    utils::zkm_public_inputs, CoreSC, EvmCalldataError, Groth16Bn254Proof, InnerSC,
+    PlonkBn254Proof, PublicInputsMode,
+};
 use zkm_stark::{MachineVerificationError, ShardProof};
 
+pub mod inspect;
+
 /// A proof generated with Ziren of a particular proof mode.
 /// Consistent with the definition in file crates/verifier/src/stark/mod.rs
 #[derive(Debug, Clone, Serialize, Deserialize, EnumDiscriminants, EnumTryAs)]
@@ -32,7 +53,59 @@ pub enum ZKMProof {
     CompressToGroth16,
 }
 
+pub use zkm_primitives::envelope::{PROOF_ENVELOPE_MAGIC, PROOF_ENVELOPE_VERSION};
+
+/// The proof kinds, in the order of their byte in the envelope header.
+const PROOF_KINDS: [ZKMProofKind; 5] = [
+    ZKMProofKind::Core,
+    ZKMProofKind::Compressed,
+    ZKMProofKind::Plonk,
+    ZKMProofKind::Groth16,
+    ZKMProofKind::CompressToGroth16,
+];
+
+/// An error returned when decoding a proof encoded with [ZKMProofWithPublicValues::to_bytes].
+#[derive(Error, Debug)]
+pub enum ProofFormatError {
+    #[error("not an encoded Ziren proof")]
+    InvalidMagic,
+    #[error("unsupported proof envelope version {0}")]
+    UnsupportedVersion(u32),
+    #[error("proof encoded by circuit version {found}, expected {expected}")]
+    CircuitVersionMismatch { expected: String, found: String },
+    #[error("unknown proof kind {0}")]
+    UnknownKind(u8),
+    #[error("the envelope declares a {declared:?} proof, but contains a {found:?} proof")]
+    KindMismatch { declared: ZKMProofKind, found: ZKMProofKind },
+    #[error("malformed envelope header")]
+    InvalidHeader,
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to read the proof: {0}")]
+    Payload(#[from] DeserializeError),
+}
+
+impl From<ProofEnvelopeError> for ProofFormatError {
+    fn from(err: ProofEnvelopeError) -> Self {
+        match err {
+            ProofEnvelopeError::UnsupportedVersion(version) => Self::UnsupportedVersion(version),
+            ProofEnvelopeError::InvalidHeader => Self::InvalidHeader,
+        }
+    }
+}
+
 /// A proof generated with ZKM, bundled together with stdin, public values, and the Ziren version.
+///
+/// [Self::save] and [Self::to_bytes] encode the proof in a versioned envelope:
+/// - the magic bytes [PROOF_ENVELOPE_MAGIC],
+/// - the envelope version [PROOF_ENVELOPE_VERSION], as a little-endian `u32`,
+/// - the [ZKM_CIRCUIT_VERSION] of the build which encoded the proof, as its length in a
+///   little-endian `u32` followed by its UTF-8 bytes,
+/// - the kind of the proof, as one byte,
+/// - the bincode encoding of the proof.
+///
+/// The bincode encoding follows the layout of the Rust types, so a proof is only decoded by a
+/// build of the circuit version it was encoded by.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZKMProofWithPublicValues {
     pub proof: ZKMProof,
@@ -41,16 +114,111 @@ pub struct ZKMProofWithPublicValues {
 }
 
 impl ZKMProofWithPublicValues {
-    /// Saves the proof to a path.
+    /// Saves the proof to a path, in the envelope format of [Self::to_bytes].
     pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
-        bincode::serialize_into(File::create(path).expect("failed to open file"), self)
-            .map_err(Into::into)
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
     }
 
     /// Loads a proof from a path.
+    ///
+    /// Files larger than [MAX_PROOF_SIZE] bytes are rejected.
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
-        bincode::deserialize_from(File::open(path).expect("failed to open file"))
-            .map_err(Into::into)
+        Self::load_with_limit(path, MAX_PROOF_SIZE)
+    }
+
+    /// Loads a proof from a path, rejecting files larger than `limit` bytes.
+    ///
+    /// Proofs saved before the envelope format are still loaded, see [Self::from_legacy_bytes].
+    pub fn load_with_limit(path: impl AsRef<Path>, limit: u64) -> Result<Self> {
+        let (kind, reader) = open_envelope(open_limited(path, limit)?)?;
+        if kind.is_none() {
+            tracing::warn!("loading a proof saved without a version envelope, save it to upgrade");
+        }
+        Ok(Self::read_payload(kind, reader, limit)?)
+    }
+
+    /// Encodes the proof in the versioned envelope format.
+    ///
+    /// The encoding is deterministic: equal proofs are encoded to the same bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes).expect("failed to encode the proof");
+        bytes
+    }
+
+    /// Decodes a proof encoded with [Self::to_bytes].
+    ///
+    /// Fails if the proof was encoded by a build of another [ZKM_CIRCUIT_VERSION].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        match open_envelope(bytes)? {
+            (Some(kind), reader) => Self::read_payload(Some(kind), reader, MAX_PROOF_SIZE),
+            (None, _) => Err(ProofFormatError::InvalidMagic),
+        }
+    }
+
+    /// Decodes a proof saved as bare bincode, before the envelope format.
+    ///
+    /// Re-encode the proof with [Self::to_bytes] to upgrade it. Nothing records the version such
+    /// a proof was saved by, so it may fail to decode if it was saved by another version.
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<Self, ProofFormatError> {
+        Ok(deserialize_limited(bytes, MAX_PROOF_SIZE)?)
+    }
+
+    fn write_to(&self, writer: &mut impl Write) -> Result<(), ProofFormatError> {
+        let kind = ZKMProofKind::from(&self.proof);
+        let kind = PROOF_KINDS.iter().position(|&k| k == kind).unwrap() as u8;
+        writer.write_all(&PROOF_ENVELOPE_MAGIC)?;
+        writer.write_all(&PROOF_ENVELOPE_VERSION.to_le_bytes())?;
+        writer.write_all(&(ZKM_CIRCUIT_VERSION.len() as u32).to_le_bytes())?;
+        writer.write_all(ZKM_CIRCUIT_VERSION.as_bytes())?;
+        writer.write_all(&[kind])?;
+        bincode::serialize_into(writer, self).map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    /// Reads the bincode encoding of a proof following the envelope header declaring `kind`, if
+    /// any.
+    fn read_payload(
+        kind: Option<ZKMProofKind>,
+        reader: impl Read,
+        limit: u64,
+    ) -> Result<Self, ProofFormatError> {
+        let proof: Self = deserialize_from_limited(reader, limit)?;
+        let found = ZKMProofKind::from(&proof.proof);
+        match kind {
+            Some(declared) if declared != found => {
+                Err(ProofFormatError::KindMismatch { declared, found })
+            }
+            _ => Ok(proof),
+        }
+    }
+
+    /// Returns the hash the program commits to its public values with, which they record, so
+    /// that it is bound to the proof. See [ZKMPublicValues::public_values_hash].
+    pub fn public_values_hash(&self) -> PublicValuesHash {
+        self.public_values.public_values_hash()
+    }
+
+    /// Returns the public inputs exposing the public values of the proof in `mode`, see
+    /// [PublicInputsMode].
+    ///
+    /// Compressed proofs expose them in every mode. Plonk and Groth16 proofs only expose the BN254
+    /// digests their circuits take as public inputs, so they return `None` in the other modes, as
+    /// do core proofs in every mode.
+    pub fn public_inputs(&self, mode: PublicInputsMode) -> Option<Vec<String>> {
+        match (&self.proof, mode) {
+            (ZKMProof::Compressed(proof), _) => Some(zkm_public_inputs(proof, mode)),
+            (ZKMProof::Plonk(proof), PublicInputsMode::Bn254Digest) => {
+                Some(proof.public_inputs.to_vec())
+            }
+            (ZKMProof::Groth16(proof), PublicInputsMode::Bn254Digest) => {
+                Some(proof.public_inputs.to_vec())
+            }
+            _ => None,
+        }
     }
 
     /// Returns the raw proof as a string.
@@ -70,33 +238,51 @@ impl ZKMProofWithPublicValues {
             ZKMProof::Compressed(_) => {
                 bincode::serialize(&self.proof).expect("Invalid stark proof")
             }
-            ZKMProof::Plonk(plonk_proof) => {
-                if plonk_proof.encoded_proof.is_empty() {
-                    // If the proof is empty, then this is a mock proof. The mock Ziren verifier
-                    // expects an empty byte array for verification, so return an empty byte array.
-                    return Vec::new();
-                }
-
-                let proof_bytes =
-                    hex::decode(&plonk_proof.encoded_proof).expect("Invalid Plonk proof");
-                [plonk_proof.plonk_vkey_hash[..4].to_vec(), proof_bytes].concat()
-            }
-            ZKMProof::Groth16(groth16_proof) => {
-                if groth16_proof.encoded_proof.is_empty() {
-                    // If the proof is empty, then this is a mock proof. The mock Ziren verifier
-                    // expects an empty byte array for verification, so return an empty byte array.
-                    return Vec::new();
-                }
-
-                let proof_bytes =
-                    hex::decode(&groth16_proof.encoded_proof).expect("Invalid Groth16 proof");
-                [groth16_proof.groth16_vkey_hash[..4].to_vec(), proof_bytes].concat()
-            }
+            ZKMProof::Plonk(plonk_proof) => evm_bytes(plonk_proof.to_evm_calldata()),
+            ZKMProof::Groth16(groth16_proof) => evm_bytes(groth16_proof.to_evm_calldata()),
             _ => unimplemented!("only Stark, Plonk and Groth16 proofs are verifiable onchain"),
         }
     }
 }
 
+/// The bytes of [ZKMProofWithPublicValues::bytes] for a Plonk or Groth16 proof.
+///
+/// The mock Ziren verifier expects no bytes for a mock proof.
+fn evm_bytes(calldata: Result<Vec<u8>, EvmCalldataError>) -> Vec<u8> {
+    match calldata {
+        Ok(bytes) => bytes,
+        Err(EvmCalldataError::MockProof) => Vec::new(),
+        Err(err) => panic!("Invalid proof: {err}"),
+    }
+}
+
+/// Reads the envelope header of an encoded proof from `reader`, returning the declared kind of
+/// the proof and a reader of its bincode encoding.
+///
+/// A proof saved as bare bincode, before the envelope format, starts with the index of its
+/// variant instead of the magic bytes: its kind is `None`, and the returned reader starts with the
+/// bytes read to tell it apart.
+pub(crate) fn open_envelope<R: Read>(
+    mut reader: R,
+) -> Result<(Option<ZKMProofKind>, Chain<Cursor<Vec<u8>>, R>), ProofFormatError> {
+    let mut magic = Vec::with_capacity(PROOF_ENVELOPE_MAGIC.len());
+    (&mut reader).take(PROOF_ENVELOPE_MAGIC.len() as u64).read_to_end(&mut magic)?;
+    if magic != PROOF_ENVELOPE_MAGIC {
+        return Ok((None, Cursor::new(magic).chain(reader)));
+    }
+
+    let header = read_proof_envelope_header(&mut reader)?;
+    if header.circuit_version != ZKM_CIRCUIT_VERSION {
+        return Err(ProofFormatError::CircuitVersionMismatch {
+            expected: ZKM_CIRCUIT_VERSION.to_string(),
+            found: header.circuit_version,
+        });
+    }
+    let kind =
+        *PROOF_KINDS.get(header.kind as usize).ok_or(ProofFormatError::UnknownKind(header.kind))?;
+    Ok((Some(kind), Cursor::new(Vec::new()).chain(reader)))
+}
+
 pub type ZKMCoreProofVerificationError = MachineVerificationError<CoreSC>;
 
 pub type ZKMCompressedProofVerificationError = MachineVerificationError<InnerSC>;
@@ -137,6 +323,26 @@ mod tests {
         assert_eq!(groth16_proof.bytes(), expected_bytes);
     }
 
+    #[test]
+    fn test_public_inputs() {
+        let groth16_proof = ZKMProofWithPublicValues {
+            proof: ZKMProof::Groth16(Groth16Bn254Proof {
+                encoded_proof: "ab".to_string(),
+                groth16_vkey_hash: [0; 32],
+                public_inputs: ["1".to_string(), "2".to_string()],
+                raw_proof: "".to_string(),
+            }),
+            public_values: ZKMPublicValues::new(),
+            zkm_version: "".to_string(),
+        };
+        assert_eq!(
+            groth16_proof.public_inputs(PublicInputsMode::Bn254Digest),
+            Some(vec!["1".to_string(), "2".to_string()])
+        );
+        assert_eq!(groth16_proof.public_inputs(PublicInputsMode::FieldElements), None);
+        assert_eq!(groth16_proof.public_inputs(PublicInputsMode::Poseidon2Digest), None);
+    }
+
     #[test]
     fn test_mock_plonk_proof_bytes() {
         let mock_plonk_proof = ZKMProofWithPublicValues {
@@ -177,4 +383,90 @@ mod tests {
         };
         core_proof.bytes();
     }
+
+    #[test]
+    fn test_load_with_limit() {
+        let proof = ZKMProofWithPublicValues {
+            proof: ZKMProof::CompressToGroth16,
+            public_values: ZKMPublicValues::from(&[1, 2, 3]),
+            zkm_version: "".to_string(),
+        };
+        let file = tempfile::NamedTempFile::new().unwrap();
+        proof.save(file.path()).unwrap();
+
+        let loaded = ZKMProofWithPublicValues::load(file.path()).unwrap();
+        assert_eq!(loaded.public_values.as_slice(), &[1, 2, 3]);
+        assert!(ZKMProofWithPublicValues::load_with_limit(file.path(), 4).is_err());
+
+        // Truncated files are reported as errors instead of panicking.
+        std::fs::write(file.path(), [1, 0, 0]).unwrap();
+        assert!(ZKMProofWithPublicValues::load(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_proof_envelope() {
+        let proof = ZKMProofWithPublicValues {
+            proof: ZKMProof::Groth16(Groth16Bn254Proof {
+                encoded_proof: "ab".to_string(),
+                groth16_vkey_hash: [1; 32],
+                public_inputs: ["".to_string(), "".to_string()],
+                raw_proof: "".to_string(),
+            }),
+            public_values: ZKMPublicValues::from(&[1, 2, 3]),
+            zkm_version: ZKM_CIRCUIT_VERSION.to_string(),
+        };
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes[..4], PROOF_ENVELOPE_MAGIC);
+        assert_eq!(bytes, proof.to_bytes());
+        let decoded = ZKMProofWithPublicValues::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.public_values.as_slice(), &[1, 2, 3]);
+        assert!(matches!(decoded.proof, ZKMProof::Groth16(_)));
+
+        // The header is the magic bytes, the envelope version and the circuit version.
+        let kind_offset = 12 + ZKM_CIRCUIT_VERSION.len();
+        assert_eq!(bytes[kind_offset], 3);
+
+        let mut other = bytes.clone();
+        other[4] = 2;
+        assert!(matches!(
+            ZKMProofWithPublicValues::from_bytes(&other),
+            Err(ProofFormatError::UnsupportedVersion(2))
+        ));
+
+        let mut other = bytes.clone();
+        other[kind_offset - 1] = b'x';
+        assert!(matches!(
+            ZKMProofWithPublicValues::from_bytes(&other),
+            Err(ProofFormatError::CircuitVersionMismatch { .. })
+        ));
+
+        let mut other = bytes.clone();
+        other[kind_offset] = 1;
+        assert!(matches!(
+            ZKMProofWithPublicValues::from_bytes(&other),
+            Err(ProofFormatError::KindMismatch {
+                declared: ZKMProofKind::Compressed,
+                found: ZKMProofKind::Groth16
+            })
+        ));
+
+        assert!(matches!(
+            ZKMProofWithPublicValues::from_bytes(&bytes[..kind_offset]),
+            Err(ProofFormatError::InvalidHeader)
+        ));
+
+        // Proofs saved as bare bincode are only decoded as legacy proofs.
+        let legacy = bincode::serialize(&proof).unwrap();
+        assert!(matches!(
+            ZKMProofWithPublicValues::from_bytes(&legacy),
+            Err(ProofFormatError::InvalidMagic)
+        ));
+        let decoded = ZKMProofWithPublicValues::from_legacy_bytes(&legacy).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &legacy).unwrap();
+        let loaded = ZKMProofWithPublicValues::load(file.path()).unwrap();
+        assert_eq!(loaded.to_bytes(), bytes);
+    }
 }
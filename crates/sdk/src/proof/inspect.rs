@@ -0,0 +1,269 @@
+//! Inspection of encoded proofs, to triage proof files without knowing how they were produced.
+//!
+//! [inspect] decodes a proof as leniently as possible and reports what it could read: a proof
+//! encoded by another circuit version still reports its header, and a proof which fails to decode
+//! reports the error instead of the fields of the proof.
+
+use std::{fmt, fs, path::Path};
+
+use num_bigint::BigUint;
+use p3_field::PrimeField;
+use zkm_core_machine::{io::MAX_PROOF_SIZE, ZKM_CIRCUIT_VERSION};
+use zkm_primitives::envelope::{read_proof_envelope_header, ProofEnvelopeError};
+use zkm_prover::utils::zkm_vkey_digest_bn254;
+
+use super::{
+    ProofFormatError, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues, PROOF_ENVELOPE_MAGIC,
+    PROOF_KINDS,
+};
+use crate::{ProverClient, ZKMVerifyingKey};
+
+/// The number of bytes of the public values shown in the previews of a [ProofInspection].
+pub const PUBLIC_VALUES_PREVIEW_LEN: usize = 64;
+
+/// The encoding of an inspected proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofEncoding {
+    /// The versioned envelope of [ZKMProofWithPublicValues::to_bytes], of the given version.
+    Envelope(u32),
+    /// Bare bincode, as saved before the envelope format.
+    Legacy,
+}
+
+/// The report of [inspect] on an encoded proof.
+#[derive(Debug, Clone)]
+pub struct ProofInspection {
+    /// The encoding of the proof.
+    pub encoding: ProofEncoding,
+    /// The circuit version declared by the envelope header, if any.
+    pub circuit_version: Option<String>,
+    /// The kind of the proof, as declared by the envelope header or else decoded.
+    pub kind: Option<ZKMProofKind>,
+    /// The Ziren version recorded in the proof.
+    pub zkm_version: Option<String>,
+    /// The digest of the verifying key the proof is for, in the format of
+    /// [crate::HashableKey::bytes32]. Core proofs do not commit to it.
+    pub vkey_digest: Option<String>,
+    /// The number of shards of a core proof.
+    pub num_shards: Option<usize>,
+    /// The length of the public values, in bytes.
+    pub public_values_len: usize,
+    /// The first [PUBLIC_VALUES_PREVIEW_LEN] bytes of the public values, in hex.
+    pub public_values_hex: String,
+    /// The first [PUBLIC_VALUES_PREVIEW_LEN] bytes of the public values, as lossy UTF-8.
+    pub public_values_utf8: String,
+    /// The error the proof failed to decode with, if any.
+    pub error: Option<String>,
+    /// The result of [Self::verify], if it was run.
+    pub verification: Option<Result<(), String>>,
+    proof: Option<ZKMProofWithPublicValues>,
+}
+
+/// Inspects a proof encoded with [ZKMProofWithPublicValues::to_bytes], or saved as bare bincode
+/// before the envelope format.
+///
+/// The proof is not verified, see [ProofInspection::verify].
+pub fn inspect(bytes: &[u8]) -> ProofInspection {
+    let mut inspection = ProofInspection {
+        encoding: ProofEncoding::Legacy,
+        circuit_version: None,
+        kind: None,
+        zkm_version: None,
+        vkey_digest: None,
+        num_shards: None,
+        public_values_len: 0,
+        public_values_hex: String::new(),
+        public_values_utf8: String::new(),
+        error: None,
+        verification: None,
+        proof: None,
+    };
+
+    let decoded = if bytes.starts_with(&PROOF_ENVELOPE_MAGIC) {
+        match read_header(&bytes[PROOF_ENVELOPE_MAGIC.len()..]) {
+            Ok((version, circuit_version, kind)) => {
+                inspection.encoding = ProofEncoding::Envelope(version);
+                inspection.circuit_version = circuit_version;
+                inspection.kind = kind;
+                ZKMProofWithPublicValues::from_bytes(bytes)
+            }
+            Err(err) => Err(err),
+        }
+    } else {
+        ZKMProofWithPublicValues::from_legacy_bytes(bytes)
+    };
+    match decoded {
+        Ok(proof) => inspection.read_proof(proof),
+        Err(err) => inspection.error = Some(err.to_string()),
+    }
+    inspection
+}
+
+/// Inspects the proof saved at `path`, see [inspect].
+///
+/// Files larger than [MAX_PROOF_SIZE] bytes are reported as such instead of being read.
+pub fn inspect_file(path: impl AsRef<Path>) -> std::io::Result<ProofInspection> {
+    let size = fs::metadata(&path)?.len();
+    if size > MAX_PROOF_SIZE {
+        let mut inspection = inspect(&[]);
+        inspection.error =
+            Some(format!("the file is {size} bytes, larger than the limit of {MAX_PROOF_SIZE}"));
+        return Ok(inspection);
+    }
+    Ok(inspect(&fs::read(path)?))
+}
+
+impl ProofInspection {
+    /// Verifies the inspected proof against `vk` with `client`, recording the result in
+    /// [Self::verification].
+    ///
+    /// A proof which failed to decode is reported as failing to verify.
+    pub fn verify(&mut self, client: &ProverClient, vk: &ZKMVerifyingKey) -> &Result<(), String> {
+        let result = match &self.proof {
+            Some(proof) => client.verify(proof, vk).map_err(|err| err.to_string()),
+            None => Err("the proof could not be decoded".to_string()),
+        };
+        self.verification.insert(result)
+    }
+
+    /// The decoded proof, if it could be decoded.
+    pub fn proof(&self) -> Option<&ZKMProofWithPublicValues> {
+        self.proof.as_ref()
+    }
+
+    fn read_proof(&mut self, proof: ZKMProofWithPublicValues) {
+        self.kind = Some(ZKMProofKind::from(&proof.proof));
+        self.zkm_version = Some(proof.zkm_version.clone());
+        match &proof.proof {
+            ZKMProof::Core(shards) => self.num_shards = Some(shards.len()),
+            ZKMProof::Compressed(proof) => {
+                self.vkey_digest =
+                    Some(bytes32(&zkm_vkey_digest_bn254(proof).as_canonical_biguint().to_string()));
+            }
+            ZKMProof::Plonk(proof) => self.vkey_digest = Some(bytes32(&proof.public_inputs[0])),
+            ZKMProof::Groth16(proof) => self.vkey_digest = Some(bytes32(&proof.public_inputs[0])),
+            ZKMProof::CompressToGroth16 => {}
+        }
+
+        let public_values = proof.public_values.as_slice();
+        let preview = &public_values[..public_values.len().min(PUBLIC_VALUES_PREVIEW_LEN)];
+        self.public_values_len = public_values.len();
+        self.public_values_hex = hex::encode(preview);
+        self.public_values_utf8 = String::from_utf8_lossy(preview).into_owned();
+        self.proof = Some(proof);
+    }
+}
+
+impl fmt::Display for ProofInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_unknown = |value: Option<String>| value.unwrap_or_else(|| "unknown".to_string());
+        let truncated = if self.public_values_len > PUBLIC_VALUES_PREVIEW_LEN { "..." } else { "" };
+
+        match self.encoding {
+            ProofEncoding::Envelope(version) => {
+                writeln!(f, "encoding:        envelope v{version}")?
+            }
+            ProofEncoding::Legacy => writeln!(f, "encoding:        legacy bincode")?,
+        }
+        writeln!(
+            f,
+            "circuit version: {} (this build: {ZKM_CIRCUIT_VERSION})",
+            or_unknown(self.circuit_version.clone())
+        )?;
+        writeln!(f, "kind:            {}", or_unknown(self.kind.map(|kind| format!("{kind:?}"))))?;
+        writeln!(f, "zkm version:     {}", or_unknown(self.zkm_version.clone()))?;
+        writeln!(f, "vkey digest:     {}", or_unknown(self.vkey_digest.clone()))?;
+        if let Some(num_shards) = self.num_shards {
+            writeln!(f, "shards:          {num_shards}")?;
+        }
+        if self.proof.is_some() {
+            writeln!(f, "public values:   {} bytes", self.public_values_len)?;
+            writeln!(f, "  hex:           0x{}{truncated}", self.public_values_hex)?;
+            writeln!(f, "  utf8:          {:?}{truncated}", self.public_values_utf8)?;
+        }
+        match &self.verification {
+            Some(Ok(())) => writeln!(f, "verification:    ok")?,
+            Some(Err(err)) => writeln!(f, "verification:    failed: {err}")?,
+            None => writeln!(f, "verification:    not run")?,
+        }
+        if let Some(err) = &self.error {
+            writeln!(f, "error:           {err}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the envelope header following the magic bytes, without checking the circuit version,
+/// returning the envelope version, the circuit version and the declared kind.
+///
+/// The circuit version and the kind are `None` if the envelope version is not supported.
+fn read_header(
+    mut bytes: &[u8],
+) -> Result<(u32, Option<String>, Option<ZKMProofKind>), ProofFormatError> {
+    match read_proof_envelope_header(&mut bytes) {
+        Ok(header) => {
+            let kind = PROOF_KINDS.get(header.kind as usize).copied();
+            Ok((header.version, Some(header.circuit_version), kind))
+        }
+        Err(ProofEnvelopeError::UnsupportedVersion(version)) => Ok((version, None, None)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Formats a decimal field element as [crate::HashableKey::bytes32] does.
+fn bytes32(decimal: &str) -> String {
+    match decimal.parse::<BigUint>() {
+        Ok(value) => format!("0x{:0>64}", value.to_str_radix(16)),
+        Err(_) => decimal.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zkm_primitives::io::ZKMPublicValues;
+    use zkm_prover::Groth16Bn254Proof;
+
+    use super::*;
+    use crate::proof::PROOF_ENVELOPE_VERSION;
+
+    #[test]
+    fn test_inspect() {
+        let proof = ZKMProofWithPublicValues {
+            proof: ZKMProof::Groth16(Groth16Bn254Proof {
+                encoded_proof: "ab".to_string(),
+                groth16_vkey_hash: [0; 32],
+                public_inputs: ["255".to_string(), "0".to_string()],
+                raw_proof: "".to_string(),
+            }),
+            public_values: ZKMPublicValues::from(b"hello".as_slice()),
+            zkm_version: "v1".to_string(),
+        };
+        let bytes = proof.to_bytes();
+        let inspection = inspect(&bytes);
+        assert_eq!(inspection.encoding, ProofEncoding::Envelope(PROOF_ENVELOPE_VERSION));
+        assert_eq!(inspection.circuit_version.as_deref(), Some(ZKM_CIRCUIT_VERSION));
+        assert_eq!(inspection.kind, Some(ZKMProofKind::Groth16));
+        assert_eq!(inspection.vkey_digest, Some(format!("0x{:0>64}", "ff")));
+        assert_eq!(inspection.public_values_hex, hex::encode(b"hello"));
+        assert_eq!(inspection.public_values_utf8, "hello");
+        assert!(inspection.error.is_none());
+        assert!(inspection.to_string().contains("verification:    not run"));
+
+        // A proof of another circuit version still reports its header.
+        let mut header = PROOF_ENVELOPE_MAGIC.to_vec();
+        header.extend_from_slice(&PROOF_ENVELOPE_VERSION.to_le_bytes());
+        header.extend_from_slice(&2u32.to_le_bytes());
+        header.extend_from_slice(b"v0");
+        header.push(3);
+        let inspection = inspect(&header);
+        assert_eq!(inspection.circuit_version.as_deref(), Some("v0"));
+        assert_eq!(inspection.kind, Some(ZKMProofKind::Groth16));
+        assert!(inspection.proof().is_none());
+        assert!(inspection.error.unwrap().contains("circuit version v0"));
+
+        let inspection = inspect(&[0xff; 8]);
+        assert_eq!(inspection.encoding, ProofEncoding::Legacy);
+        assert!(inspection.kind.is_none());
+        assert!(inspection.error.is_some());
+    }
+}
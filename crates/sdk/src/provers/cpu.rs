@@ -2,6 +2,7 @@ use anyhow::Result;
 use zkm_core_executor::ZKMContext;
 use zkm_core_machine::io::ZKMStdin;
 use zkm_prover::{components::DefaultProverComponents, ZKMProver};
+use zkm_stark::{Progress, ProgressEvent};
 
 use crate::install::try_install_circuit_artifacts;
 use crate::{
@@ -32,6 +33,7 @@ impl CpuProver {
         &self,
         mut stdin: ZKMStdin,
         opts: ProofOpts,
+        progress: &Progress,
     ) -> Result<ZKMProofWithPublicValues> {
         assert_eq!(stdin.buffer.len(), 1);
         let public_values = bincode::deserialize(stdin.buffer.last().unwrap())?;
@@ -40,9 +42,11 @@ impl CpuProver {
         let (proof, _) = stdin.proofs.pop().unwrap();
 
         // Generate the shrink proof.
+        progress.report(ProgressEvent::ShrinkStarted);
         let shrink_proof = self.prover.shrink(proof, opts.zkm_prover_opts)?;
 
         // Generate the wrap proof.
+        progress.report(ProgressEvent::WrapStarted);
         let outer_proof = self.prover.wrap_bn254(shrink_proof, opts.zkm_prover_opts)?;
 
         let groth16_bn254_artifacts = if zkm_prover::build::zkm_dev_mode() {
@@ -51,9 +55,10 @@ impl CpuProver {
                 &outer_proof.proof,
             )
         } else {
-            try_install_circuit_artifacts("groth16")
+            try_install_circuit_artifacts("groth16")?
         };
 
+        progress.report(ProgressEvent::SnarkStarted);
         let proof = self.prover.wrap_groth16_bn254(outer_proof, &groth16_bn254_artifacts);
         Ok(ZKMProofWithPublicValues {
             proof: ZKMProof::Groth16(proof),
@@ -73,6 +78,10 @@ impl Prover<DefaultProverComponents> for CpuProver {
         (pk, vk)
     }
 
+    fn setup_many(&self, elves: &[&[u8]]) -> Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        self.prover.setup_many(elves).map_err(anyhow::Error::msg)
+    }
+
     fn zkm_prover(&self) -> &ZKMProver<DefaultProverComponents> {
         &self.prover
     }
@@ -86,8 +95,9 @@ impl Prover<DefaultProverComponents> for CpuProver {
         kind: ZKMProofKind,
         _elf_id: Option<String>,
     ) -> Result<(ZKMProofWithPublicValues, u64)> {
+        let progress = context.progress.clone();
         if kind == ZKMProofKind::CompressToGroth16 {
-            return Ok((self.compress_to_groth16(stdin, opts)?, 0));
+            return Ok((self.compress_to_groth16(stdin, opts, &progress)?, 0));
         }
 
         let program = self.prover.get_program(&pk.elf).unwrap();
@@ -112,8 +122,13 @@ impl Prover<DefaultProverComponents> for CpuProver {
         let public_values = proof.public_values.clone();
 
         // Generate the compressed proof.
-        let reduce_proof =
-            self.prover.compress(&pk.vk, proof, deferred_proofs, opts.zkm_prover_opts)?;
+        let (reduce_proof, _) = self.prover.compress_with_stats(
+            &pk.vk,
+            proof,
+            deferred_proofs,
+            opts.zkm_prover_opts,
+            &progress,
+        )?;
         if kind == ZKMProofKind::Compressed {
             return Ok((
                 ZKMProofWithPublicValues {
@@ -126,9 +141,11 @@ impl Prover<DefaultProverComponents> for CpuProver {
         }
 
         // Generate the shrink proof.
+        progress.report(ProgressEvent::ShrinkStarted);
         let compress_proof = self.prover.shrink(reduce_proof, opts.zkm_prover_opts)?;
 
         // Generate the wrap proof.
+        progress.report(ProgressEvent::WrapStarted);
         let outer_proof = self.prover.wrap_bn254(compress_proof, opts.zkm_prover_opts)?;
 
         if kind == ZKMProofKind::Plonk {
@@ -138,8 +155,9 @@ impl Prover<DefaultProverComponents> for CpuProver {
                     &outer_proof.proof,
                 )
             } else {
-                try_install_circuit_artifacts("plonk")
+                try_install_circuit_artifacts("plonk")?
             };
+            progress.report(ProgressEvent::SnarkStarted);
             let proof = self.prover.wrap_plonk_bn254(outer_proof, &plonk_bn254_artifacts);
 
             return Ok((
@@ -157,9 +175,10 @@ impl Prover<DefaultProverComponents> for CpuProver {
                     &outer_proof.proof,
                 )
             } else {
-                try_install_circuit_artifacts("groth16")
+                try_install_circuit_artifacts("groth16")?
             };
 
+            progress.report(ProgressEvent::SnarkStarted);
             let proof = self.prover.wrap_groth16_bn254(outer_proof, &groth16_bn254_artifacts);
             return Ok((
                 ZKMProofWithPublicValues {
@@ -58,7 +58,7 @@ impl CudaProver {
         let deferred_proofs =
             stdin.proofs.iter().map(|(reduce_proof, _)| reduce_proof.clone()).collect();
         let public_values = proof.public_values.clone();
-        let reduce_proof = self.cuda_prover.compress(&pk.vk, proof, deferred_proofs)?;
+        let reduce_proof = self.cuda_prover.compress_stateless(&pk.vk, proof, deferred_proofs)?;
         if kind == ZKMProofKind::Compressed {
             let proof_with_pv = ZKMProofWithPublicValues {
                 proof: ZKMProof::Compressed(Box::new(reduce_proof)),
@@ -69,10 +69,10 @@ impl CudaProver {
         }
 
         // Generate the shrink proof.
-        let compress_proof = self.cuda_prover.shrink(reduce_proof)?;
+        let compress_proof = self.cuda_prover.shrink_stateless(&pk.vk, reduce_proof)?;
 
         // Generate the wrap proof.
-        let outer_proof = self.cuda_prover.wrap_bn254(compress_proof)?;
+        let outer_proof = self.cuda_prover.wrap_bn254_stateless(&pk.vk, compress_proof)?;
 
         if kind == ZKMProofKind::Plonk {
             let plonk_bn254_artifacts = if zkm_prover::build::zkm_dev_mode() {
@@ -81,7 +81,7 @@ impl CudaProver {
                     &outer_proof.proof,
                 )
             } else {
-                try_install_circuit_artifacts("plonk")
+                try_install_circuit_artifacts("plonk")?
             };
             let proof = self.cpu_prover.wrap_plonk_bn254(outer_proof, &plonk_bn254_artifacts);
             let proof_with_pv = ZKMProofWithPublicValues {
@@ -97,7 +97,7 @@ impl CudaProver {
                     &outer_proof.proof,
                 )
             } else {
-                try_install_circuit_artifacts("groth16")
+                try_install_circuit_artifacts("groth16")?
             };
 
             let proof = self.cpu_prover.wrap_groth16_bn254(outer_proof, &groth16_bn254_artifacts);
@@ -117,13 +117,14 @@ impl CudaProver {
         let public_values = bincode::deserialize(stdin.buffer.last().unwrap())?;
 
         assert_eq!(stdin.proofs.len(), 1);
-        let (proof, _) = stdin.proofs.pop().unwrap();
+        let (proof, vk) = stdin.proofs.pop().unwrap();
+        let vk = ZKMVerifyingKey { vk };
 
         // Generate the shrink proof.
-        let shrink_proof = self.cuda_prover.shrink(proof)?;
+        let shrink_proof = self.cuda_prover.shrink_stateless(&vk, proof)?;
 
         // Generate the wrap proof.
-        let outer_proof = self.cuda_prover.wrap_bn254(shrink_proof)?;
+        let outer_proof = self.cuda_prover.wrap_bn254_stateless(&vk, shrink_proof)?;
 
         let groth16_bn254_artifacts = if zkm_prover::build::zkm_dev_mode() {
             zkm_prover::build::try_build_groth16_bn254_artifacts_dev(
@@ -131,7 +132,7 @@ impl CudaProver {
                 &outer_proof.proof,
             )
         } else {
-            try_install_circuit_artifacts("groth16")
+            try_install_circuit_artifacts("groth16")?
         };
 
         let proof = self.cpu_prover.wrap_groth16_bn254(outer_proof, &groth16_bn254_artifacts);
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use alloy_signer::k256::sha2::{Digest, Sha256};
+use anyhow::Result;
+use zkm_core_executor::ZKMContext;
+use zkm_core_machine::io::ZKMStdin;
+use zkm_prover::{components::DefaultProverComponents, ZKMProver};
+
+use crate::network::{prover::supports, PriceQuote, ProofRequest};
+use crate::{
+    block_on, provers::ProofOpts, NetworkProver, Prover, ZKMProofKind, ZKMProofWithPublicValues,
+    ZKMProvingKey, ZKMVerifyingKey,
+};
+
+use super::ProverType;
+
+/// An implementation of [crate::ProverClient] that generates proofs on the proof network, and
+/// falls back to a local prover when the network cannot generate them.
+///
+/// A proof is generated locally if the network fails its job or does not complete it within the
+/// timeout, if the network asks more than the price cap for it, or if the network does not
+/// generate proofs of its kind. The [crate::ProvingMetrics] of the proof record which prover
+/// generated it, and why the network was given up.
+pub struct FallbackProver {
+    network: NetworkProver,
+    local: Box<dyn Prover<DefaultProverComponents>>,
+    timeout: Option<Duration>,
+    max_price: Option<u64>,
+}
+
+impl FallbackProver {
+    /// Creates a new [`FallbackProver`] falling back from `network` to `local`, without a timeout
+    /// or a price cap.
+    pub fn new(
+        network: NetworkProver,
+        local: impl Prover<DefaultProverComponents> + 'static,
+    ) -> Self {
+        Self { network, local: Box::new(local), timeout: None, max_price: None }
+    }
+
+    /// Falls back to the local prover if the network does not complete a job within `timeout`.
+    /// The job is then cancelled.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Falls back to the local prover if the network asks more than `max_price` for a proof, in
+    /// the smallest unit of its currency.
+    ///
+    /// The program is executed locally before the proof is requested, to get the price of its
+    /// cycles.
+    pub fn max_price(mut self, max_price: u64) -> Self {
+        self.max_price = Some(max_price);
+        self
+    }
+
+    /// Generates the proof on the network, or returns why it cannot.
+    async fn prove_on_network(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+        kind: ZKMProofKind,
+        elf_id: Option<String>,
+    ) -> Result<(ZKMProofWithPublicValues, u64)> {
+        if !supports(kind) {
+            anyhow::bail!("the proof network does not generate {kind:?} proofs");
+        }
+
+        let mut request = ProofRequest { elf_id: elf_id.clone(), ..ProofRequest::new(kind) };
+        if let Some(max_price) = self.max_price {
+            let quote = self.quote(pk, stdin, kind, elf_id).await?;
+            // The request is refused before it is submitted if the quote is above the cap.
+            request = request.max_price(max_price).quote(&quote);
+        }
+        let vk = (kind != ZKMProofKind::CompressToGroth16).then_some(&pk.vk);
+        self.network.prove_request(&pk.elf, stdin.clone(), &request, self.timeout, vk).await
+    }
+
+    async fn quote(
+        &self,
+        pk: &ZKMProvingKey,
+        stdin: &ZKMStdin,
+        kind: ZKMProofKind,
+        elf_id: Option<String>,
+    ) -> Result<PriceQuote> {
+        let cycles = if kind == ZKMProofKind::CompressToGroth16 {
+            0
+        } else {
+            self.local.execute(&pk.elf, stdin)?.1.total_instruction_count()
+        };
+        let elf_digest = elf_id.unwrap_or_else(|| hex::encode(Sha256::digest(&pk.elf)));
+        self.network.quote(&elf_digest, cycles, kind).await
+    }
+}
+
+impl Prover<DefaultProverComponents> for FallbackProver {
+    fn id(&self) -> ProverType {
+        ProverType::Fallback
+    }
+
+    fn zkm_prover(&self) -> &ZKMProver<DefaultProverComponents> {
+        self.local.zkm_prover()
+    }
+
+    fn setup(&self, elf: &[u8]) -> (ZKMProvingKey, ZKMVerifyingKey) {
+        self.local.setup(elf)
+    }
+
+    fn setup_many(&self, elves: &[&[u8]]) -> Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        self.local.setup_many(elves)
+    }
+
+    fn prove_impl<'a>(
+        &'a self,
+        pk: &ZKMProvingKey,
+        stdin: ZKMStdin,
+        opts: ProofOpts,
+        context: ZKMContext<'a>,
+        kind: ZKMProofKind,
+        elf_id: Option<String>,
+    ) -> Result<(ZKMProofWithPublicValues, u64)> {
+        let reason = match block_on(self.prove_on_network(pk, &stdin, kind, elf_id.clone())) {
+            Ok(proof) => {
+                opts.backend.report(ProverType::Network, None);
+                return Ok(proof);
+            }
+            Err(e) => format!("{e:#}"),
+        };
+        tracing::warn!("Proving locally, as the proof network failed: {reason}");
+
+        let (backend, report) = (self.local.id(), opts.backend.clone());
+        let proof = self.local.prove_impl(pk, stdin, opts, context, kind, elf_id)?;
+        report.report(backend, Some(reason));
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::transport::Endpoint;
+    use zkm_core_machine::io::MAX_PROOF_SIZE;
+
+    use super::*;
+    use crate::{network::auth::RequestSigner, CpuProver, MockProver, ProverClient};
+
+    const KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    /// A client falling back to a mock prover from a proof network nothing listens on.
+    fn client() -> ProverClient {
+        let network = NetworkProver {
+            endpoint: Endpoint::from_static("http://127.0.0.1:1"),
+            signer: RequestSigner::new(KEY.parse().unwrap()),
+            local_prover: CpuProver::new(),
+            poll_interval: 100,
+            max_proof_size: MAX_PROOF_SIZE,
+        };
+        ProverClient { prover: Box::new(FallbackProver::new(network, MockProver::new())) }
+    }
+
+    #[test]
+    fn test_fallback_prover() {
+        let client = client();
+        let (pk, vk) = client.setup(test_artifacts::FIBONACCI_ELF);
+        let mut stdin = ZKMStdin::new();
+        stdin.write(&10usize);
+
+        // The network does not generate core proofs, so they are generated locally.
+        let (proof, metrics) = client.prove(&pk, stdin.clone()).core().run_with_metrics().unwrap();
+        client.verify(&proof, &vk).unwrap();
+        assert_eq!(metrics.backend, "mock");
+        assert!(metrics.fallback_reason.unwrap().contains("does not generate Core proofs"));
+
+        // The network is unreachable, so the compressed proof is generated locally.
+        let (proof, metrics) = client.prove(&pk, stdin).compressed().run_with_metrics().unwrap();
+        client.verify(&proof, &vk).unwrap();
+        assert_eq!(metrics.backend, "mock");
+        assert!(metrics.fallback_reason.is_some());
+    }
+}
@@ -1,15 +1,18 @@
 #![allow(unused_variables)]
+use std::{borrow::Borrow, io::Read};
+
 use hashbrown::HashMap;
 use zkm_core_executor::{ZKMContext, ZKMReduceProof};
-use zkm_core_machine::io::ZKMStdin;
+use zkm_core_machine::io::{deserialize_from_limited, ZKMStdin, MAX_PROOF_SIZE};
 use zkm_stark::{ShardCommitment, ShardOpenedValues, ShardProof, StarkVerifyingKey};
 
 use crate::{
-    Prover, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues, ZKMProvingKey, ZKMVerificationError,
-    ZKMVerifyingKey,
+    CoreSC, Prover, PublicValuesHash, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues,
+    ZKMProvingKey, ZKMPublicValues, ZKMVerificationError, ZKMVerifyingKey,
 };
 use anyhow::Result;
-use p3_field::{FieldAlgebra, PrimeField};
+use itertools::Itertools;
+use p3_field::{FieldAlgebra, PrimeField, PrimeField32};
 use p3_fri::FriProof;
 use p3_koala_bear::KoalaBear;
 use zkm_prover::{
@@ -17,20 +20,50 @@ use zkm_prover::{
     verify::{verify_groth16_bn254_public_inputs, verify_plonk_bn254_public_inputs},
     Groth16Bn254Proof, HashableKey, PlonkBn254Proof, ZKMProver,
 };
-use zkm_stark::septic_digest::SepticDigest;
+use zkm_stark::{air::PublicValues, septic_digest::SepticDigest, Word};
+
+use super::{check_public_values_hash, ProofOpts, ProverType};
 
-use super::{ProofOpts, ProverType};
+/// The length of a gnark Groth16 proof: two uncompressed G1 points and an uncompressed G2 point.
+const GROTH16_PROOF_LEN: usize = 256;
+
+/// The length of a gnark PLONK proof of the Ziren circuit, which has a single BSB22 commitment.
+const PLONK_PROOF_LEN: usize = 864;
 
 /// An implementation of [crate::ProverClient] that can generate mock proofs.
+///
+/// Mock proofs are deterministic and shaped like real proofs: core and compressed proofs commit
+/// to the public values, and Groth16 and PLONK proofs have the program vkey hash and the committed
+/// values digest as public inputs, and a zero-filled encoded proof of the right length. Their
+/// vkey hash is zero, which the onchain encoding maps to the empty proof expected by the mock
+/// verifier contract.
+///
+/// Verifying a mock proof checks its public values, and fails unless the prover was created with
+/// [MockProver::assume_valid].
 pub struct MockProver {
     pub(crate) prover: ZKMProver,
+    assume_valid: bool,
 }
 
 impl MockProver {
     /// Creates a new [MockProver].
     pub fn new() -> Self {
         let prover = ZKMProver::new();
-        Self { prover }
+        Self { prover, assume_valid: false }
+    }
+
+    /// Accepts mock proofs with valid public values in [Prover::verify].
+    pub fn assume_valid(mut self) -> Self {
+        self.assume_valid = true;
+        self
+    }
+
+    fn check_assume_valid(&self) -> Result<(), ZKMVerificationError> {
+        if self.assume_valid {
+            Ok(())
+        } else {
+            Err(ZKMVerificationError::MockProof)
+        }
     }
 }
 
@@ -44,6 +77,10 @@ impl Prover<DefaultProverComponents> for MockProver {
         (pk, vk)
     }
 
+    fn setup_many(&self, elves: &[&[u8]]) -> Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        self.prover.setup_many(elves).map_err(anyhow::Error::msg)
+    }
+
     fn zkm_prover(&self) -> &ZKMProver {
         &self.prover
     }
@@ -62,7 +99,7 @@ impl Prover<DefaultProverComponents> for MockProver {
                 let (public_values, _) = self.prover.execute(&pk.elf, &stdin, context)?;
                 Ok((
                     ZKMProofWithPublicValues {
-                        proof: ZKMProof::Core(vec![]),
+                        proof: ZKMProof::Core(vec![mock_shard_proof(&public_values)]),
                         public_values,
                         zkm_version: self.version().to_string(),
                     },
@@ -71,23 +108,7 @@ impl Prover<DefaultProverComponents> for MockProver {
             }
             ZKMProofKind::Compressed => {
                 let (public_values, _) = self.prover.execute(&pk.elf, &stdin, context)?;
-
-                let shard_proof = ShardProof {
-                    commitment: ShardCommitment {
-                        main_commit: [KoalaBear::ZERO; 8].into(),
-                        permutation_commit: [KoalaBear::ZERO; 8].into(),
-                        quotient_commit: [KoalaBear::ZERO; 8].into(),
-                    },
-                    opened_values: ShardOpenedValues { chips: vec![] },
-                    opening_proof: FriProof {
-                        commit_phase_commits: vec![],
-                        query_proofs: vec![],
-                        final_poly: Default::default(),
-                        pow_witness: KoalaBear::ZERO,
-                    },
-                    chip_ordering: HashMap::new(),
-                    public_values: vec![],
-                };
+                let shard_proof = mock_shard_proof(&public_values);
 
                 let reduce_vk = StarkVerifyingKey {
                     commit: [KoalaBear::ZERO; 8].into(),
@@ -118,10 +139,12 @@ impl Prover<DefaultProverComponents> for MockProver {
                         proof: ZKMProof::Plonk(PlonkBn254Proof {
                             public_inputs: [
                                 pk.vk.hash_bn254().as_canonical_biguint().to_string(),
-                                public_values.hash_bn254().to_string(),
+                                public_values
+                                    .hash_bn254_with(public_values.public_values_hash())
+                                    .to_string(),
                             ],
-                            encoded_proof: "".to_string(),
-                            raw_proof: "".to_string(),
+                            encoded_proof: hex::encode([0u8; PLONK_PROOF_LEN]),
+                            raw_proof: hex::encode([0u8; PLONK_PROOF_LEN]),
                             plonk_vkey_hash: [0; 32],
                         }),
                         public_values,
@@ -137,10 +160,12 @@ impl Prover<DefaultProverComponents> for MockProver {
                         proof: ZKMProof::Groth16(Groth16Bn254Proof {
                             public_inputs: [
                                 pk.vk.hash_bn254().as_canonical_biguint().to_string(),
-                                public_values.hash_bn254().to_string(),
+                                public_values
+                                    .hash_bn254_with(public_values.public_values_hash())
+                                    .to_string(),
                             ],
-                            encoded_proof: "".to_string(),
-                            raw_proof: "".to_string(),
+                            encoded_proof: hex::encode([0u8; GROTH16_PROOF_LEN]),
+                            raw_proof: hex::encode([0u8; GROTH16_PROOF_LEN]),
                             groth16_vkey_hash: [0; 32],
                         }),
                         public_values,
@@ -153,23 +178,120 @@ impl Prover<DefaultProverComponents> for MockProver {
         }
     }
 
-    fn verify(
+    fn verify_with_hash(
         &self,
         bundle: &ZKMProofWithPublicValues,
         vkey: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
     ) -> Result<(), ZKMVerificationError> {
+        if bundle.zkm_version != self.version() {
+            return Err(ZKMVerificationError::VersionMismatch(bundle.zkm_version.clone()));
+        }
+        check_public_values_hash(&bundle.public_values, hash)?;
         match &bundle.proof {
+            ZKMProof::Core(proof) => {
+                let shard_proof = proof.last().ok_or(ZKMVerificationError::InvalidPublicValues)?;
+                verify_committed_value_digest(
+                    &shard_proof.public_values,
+                    &bundle.public_values,
+                    hash,
+                )?;
+            }
+            ZKMProof::Compressed(proof) => {
+                verify_committed_value_digest(
+                    &proof.proof.public_values,
+                    &bundle.public_values,
+                    hash,
+                )?;
+            }
             ZKMProof::Plonk(PlonkBn254Proof { public_inputs, .. }) => {
-                verify_plonk_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
-                    .map_err(ZKMVerificationError::Plonk)
+                verify_plonk_bn254_public_inputs(vkey, &bundle.public_values, hash, public_inputs)
+                    .map_err(ZKMVerificationError::Plonk)?;
             }
             ZKMProof::Groth16(Groth16Bn254Proof { public_inputs, .. }) => {
-                verify_groth16_bn254_public_inputs(vkey, &bundle.public_values, public_inputs)
-                    .map_err(ZKMVerificationError::Groth16)
+                verify_groth16_bn254_public_inputs(
+                    vkey,
+                    &bundle.public_values,
+                    hash,
+                    public_inputs,
+                )
+                .map_err(ZKMVerificationError::Groth16)?;
+            }
+            proof @ ZKMProof::CompressToGroth16 => {
+                return Err(ZKMVerificationError::UnsupportedProof(proof.into()));
             }
-            _ => Ok(()),
         }
+        self.check_assume_valid()
+    }
+
+    fn verify_core_from_reader(
+        &self,
+        reader: &mut dyn Read,
+        _: &ZKMVerifyingKey,
+    ) -> Result<ZKMPublicValues, ZKMVerificationError> {
+        let bundle: ZKMProofWithPublicValues = deserialize_from_limited(reader, MAX_PROOF_SIZE)
+            .map_err(ZKMVerificationError::Deserialize)?;
+        let ZKMProof::Core(proof) = &bundle.proof else {
+            return Err(ZKMVerificationError::InvalidPublicValues);
+        };
+        let shard_proof = proof.last().ok_or(ZKMVerificationError::InvalidPublicValues)?;
+        verify_committed_value_digest(
+            &shard_proof.public_values,
+            &bundle.public_values,
+            bundle.public_values_hash(),
+        )?;
+        self.check_assume_valid()?;
+        Ok(bundle.public_values)
+    }
+}
+
+/// A shard proof with zero commitments and openings, whose public values commit to
+/// `public_values`.
+fn mock_shard_proof(public_values: &ZKMPublicValues) -> ShardProof<CoreSC> {
+    let mut shard_public_values = PublicValues::<u32, u32>::default();
+    for (word, bytes) in shard_public_values
+        .committed_value_digest
+        .iter_mut()
+        .zip(public_values.hash_with(public_values.public_values_hash()).chunks(4))
+    {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    ShardProof {
+        commitment: ShardCommitment {
+            main_commit: [KoalaBear::ZERO; 8].into(),
+            permutation_commit: [KoalaBear::ZERO; 8].into(),
+            quotient_commit: [KoalaBear::ZERO; 8].into(),
+        },
+        opened_values: ShardOpenedValues { chips: vec![] },
+        opening_proof: FriProof {
+            commit_phase_commits: vec![],
+            query_proofs: vec![],
+            final_poly: Default::default(),
+            pow_witness: KoalaBear::ZERO,
+        },
+        chip_ordering: HashMap::new(),
+        public_values: shard_public_values.to_vec(),
+    }
+}
+
+/// Checks the committed value digest in the public values of a shard proof against the hash of
+/// `public_values` computed with `hash`.
+fn verify_committed_value_digest(
+    shard_public_values: &[KoalaBear],
+    public_values: &ZKMPublicValues,
+    hash: PublicValuesHash,
+) -> Result<(), ZKMVerificationError> {
+    let shard_public_values: &PublicValues<Word<_>, _> = shard_public_values.borrow();
+    let committed_value_digest_bytes = shard_public_values
+        .committed_value_digest
+        .iter()
+        .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
+        .collect_vec();
+    if !public_values.is_digest(&committed_value_digest_bytes, hash) {
+        return Err(ZKMVerificationError::InvalidPublicValues);
     }
+    Ok(())
 }
 
 impl Default for MockProver {
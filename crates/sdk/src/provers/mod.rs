@@ -1,14 +1,19 @@
 mod cpu;
 mod cuda;
+#[cfg(feature = "network")]
+mod fallback;
 mod mock;
 
 pub use cpu::CpuProver;
 pub use cuda::CudaProver;
+#[cfg(feature = "network")]
+pub use fallback::FallbackProver;
 pub use mock::MockProver;
 
 use itertools::Itertools;
 use p3_field::PrimeField32;
 use std::borrow::Borrow;
+use std::io::Read;
 use std::time::Duration;
 
 use anyhow::Result;
@@ -16,17 +21,22 @@ use strum_macros::EnumString;
 use thiserror::Error;
 use zkm_core_executor::ExecutionReport;
 use zkm_core_executor::ZKMContext;
-use zkm_core_machine::{io::ZKMStdin, ZKM_CIRCUIT_VERSION};
-use zkm_primitives::io::ZKMPublicValues;
+use zkm_core_machine::{
+    io::{deserialize_from_limited, DeserializeError, ZKMStdin, MAX_PROOF_SIZE},
+    ZKM_CIRCUIT_VERSION,
+};
+use zkm_primitives::io::{PublicValuesHash, ZKMPublicValues};
 use zkm_prover::{
     components::{DefaultProverComponents, ZKMProverComponents},
+    verify::StreamingVerificationError,
     CoreSC, InnerSC, ZKMCoreProofData, ZKMProver, ZKMProvingKey, ZKMVerifyingKey,
 };
 use zkm_stark::{air::PublicValues, MachineVerificationError, Word, ZKMProverOpts};
 
 use crate::install::try_install_circuit_artifacts;
+use crate::metrics::BackendReport;
 use crate::ProverClient;
-use crate::{ZKMProof, ZKMProofKind, ZKMProofWithPublicValues};
+use crate::{ProofFormatError, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues};
 
 /// The type of prover.
 #[derive(Debug, PartialEq, EnumString)]
@@ -35,6 +45,7 @@ pub enum ProverType {
     Cuda,
     Mock,
     Network,
+    Fallback,
 }
 
 /// Options to configure proof generation.
@@ -44,6 +55,8 @@ pub struct ProofOpts {
     pub zkm_prover_opts: ZKMProverOpts,
     /// Optional timeout duration for proof generation.
     pub timeout: Option<Duration>,
+    /// Where a prover choosing among several reports the one which generated the proof.
+    pub(crate) backend: BackendReport,
 }
 
 #[derive(Error, Debug)]
@@ -60,6 +73,43 @@ pub enum ZKMVerificationError {
     Plonk(anyhow::Error),
     #[error("Groth16 verification error: {0}")]
     Groth16(anyhow::Error),
+    #[error("Failed to read proof: {0}")]
+    Deserialize(DeserializeError),
+    #[error("Invalid proof envelope: {0}")]
+    ProofFormat(ProofFormatError),
+    #[error("Mock proofs are only accepted by a mock prover assuming them valid")]
+    MockProof,
+    #[error("Proofs of kind {0:?} cannot be verified")]
+    UnsupportedProof(ZKMProofKind),
+    /// The public values of the proof record another hash than the one the proof is verified
+    /// with, see [ZKMPublicValues::public_values_hash].
+    #[error("The program commits its public values with {committed:?}, but the proof was verified with {expected:?}")]
+    PublicValuesHashMismatch { expected: PublicValuesHash, committed: PublicValuesHash },
+}
+
+/// An error returned by [Prover::verify_with_public_values].
+#[derive(Error, Debug)]
+pub enum PublicValuesVerificationError {
+    #[error("Invalid proof: {0}")]
+    Proof(#[from] ZKMVerificationError),
+    /// The digest of the public values committed by the proof is not the digest of the expected
+    /// values. The digests are the hex SHA-256 hashes for core and compressed proofs, and the
+    /// decimal BN254 public inputs for Plonk and Groth16 proofs.
+    #[error("Unexpected public values: the proof commits to the digest {committed}, expected {expected}")]
+    DigestMismatch { expected: String, committed: String },
+    #[error("The proof has no committed values digest in its public inputs")]
+    MissingDigest,
+    #[error("Proofs of kind {0:?} do not commit to public values")]
+    UnsupportedProof(ZKMProofKind),
+}
+
+impl From<StreamingVerificationError> for ZKMVerificationError {
+    fn from(err: StreamingVerificationError) -> Self {
+        match err {
+            StreamingVerificationError::Deserialize(err) => Self::Deserialize(err),
+            StreamingVerificationError::Verification(err) => Self::Core(err),
+        }
+    }
 }
 
 /// An implementation of [crate::ProverClient].
@@ -80,6 +130,11 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
     /// Generate the proving and verifying keys for the given program.
     fn setup(&self, elf: &[u8]) -> (ZKMProvingKey, ZKMVerifyingKey);
 
+    /// Generate the proving and verifying keys for each of the given programs.
+    fn setup_many(&self, elves: &[&[u8]]) -> Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        Ok(elves.iter().map(|elf| self.setup(elf)).collect())
+    }
+
     /// Prove the execution of a MIPS ELF with the given inputs, according to the given proof mode.
     fn prove(
         &self,
@@ -127,14 +182,32 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
     /// Verify that a Ziren proof is valid given its vkey and metadata.
     /// For Plonk proofs, verifies that the public inputs of the PlonkBn254 proof match
     /// the hash of the VK and the committed public values of the ZKMProofWithPublicValues.
+    ///
+    /// The public values are checked against a digest computed with the hash they record, see
+    /// [ZKMProofWithPublicValues::public_values_hash].
     fn verify(
         &self,
         bundle: &ZKMProofWithPublicValues,
         vkey: &ZKMVerifyingKey,
+    ) -> Result<(), ZKMVerificationError> {
+        self.verify_with_hash(bundle, vkey, bundle.public_values_hash())
+    }
+
+    /// Verify that a Ziren proof is valid given its vkey and metadata, checking the public values
+    /// against a digest computed with `hash`.
+    ///
+    /// Fails with [ZKMVerificationError::PublicValuesHashMismatch] if the public values record
+    /// another hash.
+    fn verify_with_hash(
+        &self,
+        bundle: &ZKMProofWithPublicValues,
+        vkey: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
     ) -> Result<(), ZKMVerificationError> {
         if bundle.zkm_version != self.version() {
             return Err(ZKMVerificationError::VersionMismatch(bundle.zkm_version.clone()));
         }
+        check_public_values_hash(&bundle.public_values, hash)?;
         match &bundle.proof {
             ZKMProof::Core(proof) => {
                 let public_values: &PublicValues<Word<_>, _> =
@@ -148,12 +221,8 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
                     .collect_vec();
 
                 // Make sure the committed value digest matches the public values hash.
-                for (a, b) in
-                    committed_value_digest_bytes.iter().zip_eq(bundle.public_values.hash())
-                {
-                    if *a != b {
-                        return Err(ZKMVerificationError::InvalidPublicValues);
-                    }
+                if !bundle.public_values.is_digest(&committed_value_digest_bytes, hash) {
+                    return Err(ZKMVerificationError::InvalidPublicValues);
                 }
 
                 // Verify the core proof.
@@ -173,12 +242,8 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
                     .collect_vec();
 
                 // Make sure the committed value digest matches the public values hash.
-                for (a, b) in
-                    committed_value_digest_bytes.iter().zip_eq(bundle.public_values.hash())
-                {
-                    if *a != b {
-                        return Err(ZKMVerificationError::InvalidPublicValues);
-                    }
+                if !bundle.public_values.is_digest(&committed_value_digest_bytes, hash) {
+                    return Err(ZKMVerificationError::InvalidPublicValues);
                 }
 
                 self.zkm_prover()
@@ -191,10 +256,12 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
                     proof,
                     vkey,
                     &bundle.public_values,
+                    hash,
                     &if zkm_prover::build::zkm_dev_mode() {
                         zkm_prover::build::plonk_bn254_artifacts_dev_dir()
                     } else {
                         try_install_circuit_artifacts("plonk")
+                            .map_err(|err| ZKMVerificationError::Plonk(err.into()))?
                     },
                 )
                 .map_err(ZKMVerificationError::Plonk),
@@ -204,16 +271,141 @@ pub trait Prover<C: ZKMProverComponents>: Send + Sync {
                     proof,
                     vkey,
                     &bundle.public_values,
+                    hash,
                     &if zkm_prover::build::zkm_dev_mode() {
                         zkm_prover::build::groth16_bn254_artifacts_dev_dir()
                     } else {
                         try_install_circuit_artifacts("groth16")
+                            .map_err(|err| ZKMVerificationError::Groth16(err.into()))?
                     },
                 )
                 .map_err(ZKMVerificationError::Groth16),
-            ZKMProof::CompressToGroth16 => unreachable!(),
+            proof @ ZKMProof::CompressToGroth16 => {
+                Err(ZKMVerificationError::UnsupportedProof(proof.into()))
+            }
         }
     }
+
+    /// Verify that a Ziren proof is valid given its vkey, and that the public values it commits
+    /// to are `expected_values`.
+    ///
+    /// The digest of `expected_values` is recomputed with `hash`, the mode the program commits
+    /// with, and compared to the digest in the public values of the last shard for core and
+    /// compressed proofs, or to the committed values digest public input for Plonk and Groth16
+    /// proofs, as computed by [crate::utils::committed_public_values].
+    fn verify_with_public_values(
+        &self,
+        bundle: &ZKMProofWithPublicValues,
+        vkey: &ZKMVerifyingKey,
+        expected_values: &[u8],
+        hash: PublicValuesHash,
+    ) -> Result<(), PublicValuesVerificationError> {
+        self.verify_with_hash(bundle, vkey, hash)?;
+
+        let public_values = ZKMPublicValues::from(expected_values);
+        let hex_digest = || hex::encode(public_values.hash_with(hash));
+        let bn254_digest = || public_values.hash_bn254_with(hash).to_string();
+        let (committed, expected) = match &bundle.proof {
+            ZKMProof::Core(proof) => {
+                let shard_proof =
+                    proof.last().ok_or(PublicValuesVerificationError::MissingDigest)?;
+                (committed_value_digest_hex(&shard_proof.public_values), hex_digest())
+            }
+            ZKMProof::Compressed(proof) => {
+                (committed_value_digest_hex(&proof.proof.public_values), hex_digest())
+            }
+            ZKMProof::Plonk(proof) => (
+                proof
+                    .public_inputs
+                    .get(1)
+                    .ok_or(PublicValuesVerificationError::MissingDigest)?
+                    .clone(),
+                bn254_digest(),
+            ),
+            ZKMProof::Groth16(proof) => (
+                proof
+                    .public_inputs
+                    .get(1)
+                    .ok_or(PublicValuesVerificationError::MissingDigest)?
+                    .clone(),
+                bn254_digest(),
+            ),
+            proof @ ZKMProof::CompressToGroth16 => {
+                return Err(PublicValuesVerificationError::UnsupportedProof(proof.into()));
+            }
+        };
+        if committed != expected {
+            return Err(PublicValuesVerificationError::DigestMismatch { expected, committed });
+        }
+        Ok(())
+    }
+
+    /// Verify a core proof saved with [ZKMProofWithPublicValues::save], reading one shard proof
+    /// at a time from `reader`.
+    ///
+    /// Unlike [Self::verify], the whole proof is never held in memory. Returns the public values
+    /// of the proof once it is verified against a digest computed with the hash they record.
+    fn verify_core_from_reader(
+        &self,
+        reader: &mut dyn Read,
+        vkey: &ZKMVerifyingKey,
+    ) -> Result<ZKMPublicValues, ZKMVerificationError> {
+        // The proof is encoded first, starting with the index of its variant.
+        let kind: u32 =
+            deserialize_from_limited(&mut *reader, 4).map_err(ZKMVerificationError::Deserialize)?;
+        if kind != ZKMProofKind::Core as u32 {
+            return Err(ZKMVerificationError::Deserialize(DeserializeError::Malformed(Box::new(
+                bincode::ErrorKind::Custom(format!("expected a core proof, found variant {kind}")),
+            ))));
+        }
+        let last_public_values =
+            self.zkm_prover().verify_from_reader(&mut *reader, vkey, MAX_PROOF_SIZE)?;
+
+        let public_values: ZKMPublicValues = deserialize_from_limited(&mut *reader, MAX_PROOF_SIZE)
+            .map_err(ZKMVerificationError::Deserialize)?;
+        let zkm_version: String = deserialize_from_limited(&mut *reader, MAX_PROOF_SIZE)
+            .map_err(ZKMVerificationError::Deserialize)?;
+        if zkm_version != self.version() {
+            return Err(ZKMVerificationError::VersionMismatch(zkm_version));
+        }
+
+        // Make sure the committed value digest matches the public values hash.
+        let last_public_values: &PublicValues<Word<_>, _> = last_public_values.as_slice().borrow();
+        let committed_value_digest_bytes = last_public_values
+            .committed_value_digest
+            .iter()
+            .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
+            .collect_vec();
+        let hash = public_values.public_values_hash();
+        if !public_values.is_digest(&committed_value_digest_bytes, hash) {
+            return Err(ZKMVerificationError::InvalidPublicValues);
+        }
+
+        Ok(public_values)
+    }
+}
+
+/// Checks that `public_values` record `hash` as the hash the program commits them with.
+pub(crate) fn check_public_values_hash(
+    public_values: &ZKMPublicValues,
+    hash: PublicValuesHash,
+) -> Result<(), ZKMVerificationError> {
+    let committed = public_values.public_values_hash();
+    if committed != hash {
+        return Err(ZKMVerificationError::PublicValuesHashMismatch { expected: hash, committed });
+    }
+    Ok(())
+}
+
+/// The committed values digest in the public values of a shard, in hex.
+fn committed_value_digest_hex<F: PrimeField32>(public_values: &[F]) -> String {
+    let public_values: &PublicValues<Word<F>, F> = public_values.borrow();
+    let bytes = public_values
+        .committed_value_digest
+        .iter()
+        .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
+        .collect_vec();
+    hex::encode(bytes)
 }
 
 impl Prover<DefaultProverComponents> for ProverClient {
@@ -229,6 +421,10 @@ impl Prover<DefaultProverComponents> for ProverClient {
         self.prover.setup(elf)
     }
 
+    fn setup_many(&self, elves: &[&[u8]]) -> Result<Vec<(ZKMProvingKey, ZKMVerifyingKey)>> {
+        self.prover.setup_many(elves)
+    }
+
     fn prove_impl<'a>(
         &'a self,
         pk: &ZKMProvingKey,
@@ -241,11 +437,20 @@ impl Prover<DefaultProverComponents> for ProverClient {
         self.prover.prove_impl(pk, stdin, opts, context, kind, elf_id)
     }
 
-    fn verify(
+    fn verify_with_hash(
         &self,
         bundle: &ZKMProofWithPublicValues,
         vkey: &ZKMVerifyingKey,
+        hash: PublicValuesHash,
     ) -> Result<(), ZKMVerificationError> {
-        self.prover.verify(bundle, vkey)
+        self.prover.verify_with_hash(bundle, vkey, hash)
+    }
+
+    fn verify_core_from_reader(
+        &self,
+        reader: &mut dyn Read,
+        vkey: &ZKMVerifyingKey,
+    ) -> Result<ZKMPublicValues, ZKMVerificationError> {
+        self.prover.verify_core_from_reader(reader, vkey)
     }
 }
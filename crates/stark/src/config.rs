@@ -74,6 +74,23 @@ pub trait StarkGenericConfig: 'static + Send + Sync + Serialize + DeserializeOwn
     fn challenger(&self) -> Self::Challenger;
 }
 
+/// Builds the challenger that drives the Fiat-Shamir transcript of a configuration.
+///
+/// A configuration's transcript schedule is fully determined by its challenger, so supplying a
+/// different builder, e.g. one absorbing a domain separator or using other permutation constants,
+/// allows matching the transcript of another STARK ecosystem without changing the rest of the
+/// configuration.
+pub trait ChallengerBuilder<Challenger>: Send + Sync {
+    /// Returns a fresh challenger which has not observed any value.
+    fn build(&self) -> Challenger;
+}
+
+impl<Challenger, F: Fn() -> Challenger + Send + Sync> ChallengerBuilder<Challenger> for F {
+    fn build(&self) -> Challenger {
+        self()
+    }
+}
+
 pub trait ZeroCommitment<SC: StarkGenericConfig> {
     fn zero_commitment(&self) -> Com<SC>;
 }
@@ -152,6 +152,8 @@ impl ZeroCommitment<KoalaBearPoseidon2Inner> for InnerPcs {
 
 pub mod koala_bear_poseidon2 {
 
+    use std::sync::Arc;
+
     use p3_challenger::DuplexChallenger;
     use p3_commit::ExtensionMmcs;
     use p3_dft::Radix2DitParallel;
@@ -164,7 +166,7 @@ pub mod koala_bear_poseidon2 {
     use serde::{Deserialize, Serialize};
     use zkm_primitives::RC_16_30;
 
-    use crate::{Com, StarkGenericConfig, ZeroCommitment, DIGEST_SIZE};
+    use crate::{ChallengerBuilder, Com, StarkGenericConfig, ZeroCommitment, DIGEST_SIZE};
 
     pub type Val = KoalaBear;
     pub type Challenge = BinomialExtensionField<Val, 4>;
@@ -240,6 +242,26 @@ pub mod koala_bear_poseidon2 {
         FriConfig { log_blowup: 3, num_queries, proof_of_work_bits: 16, mmcs: challenge_mmcs }
     }
 
+    /// The default [ChallengerBuilder]: a duplex sponge over [my_perm] with rate 8, starting
+    /// from the all-zero state.
+    #[derive(Clone)]
+    pub struct DefaultChallengerBuilder {
+        perm: Perm,
+    }
+
+    impl DefaultChallengerBuilder {
+        #[must_use]
+        pub fn new(perm: Perm) -> Self {
+            Self { perm }
+        }
+    }
+
+    impl ChallengerBuilder<Challenger> for DefaultChallengerBuilder {
+        fn build(&self) -> Challenger {
+            Challenger::new(self.perm.clone())
+        }
+    }
+
     enum KoalaBearPoseidon2Type {
         Default,
         Compressed,
@@ -251,6 +273,7 @@ pub mod koala_bear_poseidon2 {
         pub perm: Perm,
         pcs: Pcs,
         config_type: KoalaBearPoseidon2Type,
+        challenger_builder: Arc<dyn ChallengerBuilder<Challenger>>,
     }
 
     impl KoalaBearPoseidon2 {
@@ -263,7 +286,8 @@ pub mod koala_bear_poseidon2 {
             let dft = Dft::default();
             let fri_config = default_fri_config();
             let pcs = Pcs::new(dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Default }
+            let challenger_builder = Arc::new(DefaultChallengerBuilder::new(perm.clone()));
+            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Default, challenger_builder }
         }
 
         #[must_use]
@@ -275,7 +299,8 @@ pub mod koala_bear_poseidon2 {
             let dft = Dft::default();
             let fri_config = compressed_fri_config();
             let pcs = Pcs::new(dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Compressed }
+            let challenger_builder = Arc::new(DefaultChallengerBuilder::new(perm.clone()));
+            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Compressed, challenger_builder }
         }
 
         #[must_use]
@@ -287,16 +312,32 @@ pub mod koala_bear_poseidon2 {
             let dft = Dft::default();
             let fri_config = ultra_compressed_fri_config();
             let pcs = Pcs::new(dft, val_mmcs, fri_config);
-            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Compressed }
+            let challenger_builder = Arc::new(DefaultChallengerBuilder::new(perm.clone()));
+            Self { pcs, perm, config_type: KoalaBearPoseidon2Type::Compressed, challenger_builder }
+        }
+
+        /// Replaces the builder of the challenger returned by [StarkGenericConfig::challenger].
+        ///
+        /// The recursion programs verify core proofs with the default transcript, so proofs
+        /// generated with a custom builder can only be verified directly, e.g. by
+        /// [`crate::StarkMachine::verify`] with a configuration using the same builder.
+        #[must_use]
+        pub fn with_challenger_builder(
+            mut self,
+            challenger_builder: impl ChallengerBuilder<Challenger> + 'static,
+        ) -> Self {
+            self.challenger_builder = Arc::new(challenger_builder);
+            self
         }
     }
 
     impl Clone for KoalaBearPoseidon2 {
         fn clone(&self) -> Self {
-            match self.config_type {
+            let config = match self.config_type {
                 KoalaBearPoseidon2Type::Default => Self::new(),
                 KoalaBearPoseidon2Type::Compressed => Self::compressed(),
-            }
+            };
+            Self { challenger_builder: self.challenger_builder.clone(), ..config }
         }
     }
 
@@ -334,7 +375,7 @@ pub mod koala_bear_poseidon2 {
         }
 
         fn challenger(&self) -> Self::Challenger {
-            Challenger::new(self.perm.clone())
+            self.challenger_builder.build()
         }
     }
 
@@ -344,3 +385,158 @@ pub mod koala_bear_poseidon2 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_challenger::{CanObserve, CanSample, CanSampleBits, DuplexChallenger, FieldChallenger};
+    use p3_field::{FieldAlgebra, FieldExtensionAlgebra, PrimeField32};
+    use p3_symmetric::Permutation;
+
+    use super::{koala_bear_poseidon2::*, KoalaBearPoseidon2Inner};
+    use crate::StarkGenericConfig;
+
+    /// An operation of a transcript conformance vector.
+    #[derive(Debug, Clone, Copy)]
+    enum Op {
+        Observe(u32),
+        Sample,
+        SampleExt,
+        SampleBits(usize),
+    }
+
+    /// The transcript conformance vectors. Each vector is replayed on a fresh challenger.
+    fn vectors() -> Vec<Vec<Op>> {
+        use Op::*;
+        vec![
+            vec![Sample],
+            vec![Observe(1), Sample],
+            vec![Observe(1), Observe(2), Observe(2), Observe(2), Sample, SampleExt],
+            // Exactly one rate of input triggers a permutation before any sample.
+            (0..8).map(Observe).chain([Sample, Sample]).collect(),
+            // More than one rate of input, then enough samples to exhaust the output buffer.
+            (0..13).map(Observe).chain([SampleExt, SampleExt, Sample]).collect(),
+            // Observing after a partial squeeze discards the remaining outputs.
+            vec![Observe(7), Sample, Observe(8), SampleExt, SampleBits(16), SampleBits(1)],
+            vec![SampleBits(30), Observe(0x7f000000), SampleBits(24), Sample],
+        ]
+    }
+
+    /// A reference model of the default transcript: a duplex sponge of width 16 and rate 8 over
+    /// the Poseidon2 permutation. Observed values overwrite the first positions of the state,
+    /// and samples are squeezed from the end of the rate portion.
+    struct ReferenceTranscript {
+        perm: Perm,
+        state: [Val; 16],
+        input: Vec<Val>,
+        output: Vec<Val>,
+    }
+
+    impl ReferenceTranscript {
+        fn new() -> Self {
+            Self { perm: my_perm(), state: [Val::ZERO; 16], input: vec![], output: vec![] }
+        }
+
+        fn duplex(&mut self) {
+            for (i, value) in self.input.drain(..).enumerate() {
+                self.state[i] = value;
+            }
+            self.perm.permute_mut(&mut self.state);
+            self.output = self.state[..8].to_vec();
+        }
+
+        fn observe(&mut self, value: Val) {
+            self.output.clear();
+            self.input.push(value);
+            if self.input.len() == 8 {
+                self.duplex();
+            }
+        }
+
+        fn sample(&mut self) -> Val {
+            if !self.input.is_empty() || self.output.is_empty() {
+                self.duplex();
+            }
+            self.output.pop().unwrap()
+        }
+    }
+
+    /// Replays `ops` on `challenger`, returning the sampled values in order.
+    fn replay(challenger: &mut Challenger, ops: &[Op]) -> Vec<u32> {
+        let mut samples = vec![];
+        for op in ops {
+            match *op {
+                Op::Observe(value) => challenger.observe(Val::from_canonical_u32(value)),
+                Op::Sample => {
+                    let value: Val = challenger.sample();
+                    samples.push(value.as_canonical_u32());
+                }
+                Op::SampleExt => {
+                    let value: Challenge = challenger.sample_ext_element();
+                    samples.extend(value.as_base_slice().iter().map(|x| x.as_canonical_u32()));
+                }
+                Op::SampleBits(bits) => samples.push(challenger.sample_bits(bits) as u32),
+            }
+        }
+        samples
+    }
+
+    /// Replays `ops` on the reference model, returning the sampled values in order.
+    fn replay_reference(ops: &[Op]) -> Vec<u32> {
+        let mut transcript = ReferenceTranscript::new();
+        let mut samples = vec![];
+        for op in ops {
+            match *op {
+                Op::Observe(value) => transcript.observe(Val::from_canonical_u32(value)),
+                Op::Sample => samples.push(transcript.sample().as_canonical_u32()),
+                Op::SampleExt => {
+                    samples.extend((0..4).map(|_| transcript.sample().as_canonical_u32()))
+                }
+                Op::SampleBits(bits) => {
+                    let value = transcript.sample().as_canonical_u32();
+                    samples.push(value & ((1 << bits) - 1));
+                }
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_default_transcript_conformance() {
+        let configs = [
+            KoalaBearPoseidon2::new(),
+            KoalaBearPoseidon2::compressed(),
+            KoalaBearPoseidon2::ultra_compressed(),
+            KoalaBearPoseidon2::new().clone(),
+        ];
+        for ops in vectors() {
+            let expected = replay_reference(&ops);
+            for config in configs.iter() {
+                assert_eq!(replay(&mut config.challenger(), &ops), expected, "{ops:?}");
+            }
+            // The recursion config shares the transcript of the core config.
+            let mut inner = KoalaBearPoseidon2Inner::new().challenger();
+            assert_eq!(replay(&mut inner, &ops), expected, "{ops:?}");
+        }
+    }
+
+    #[test]
+    fn test_custom_challenger_builder() {
+        // A transcript absorbing a domain separator before any other value.
+        let perm = my_perm();
+        let config = KoalaBearPoseidon2::new().with_challenger_builder(move || {
+            let mut challenger = DuplexChallenger::new(perm.clone());
+            challenger.observe(Val::from_canonical_u32(0x5a4b4d));
+            challenger
+        });
+
+        for ops in vectors() {
+            let mut separated = vec![Op::Observe(0x5a4b4d)];
+            separated.extend(ops.iter().copied());
+            let expected = replay_reference(&separated);
+            assert_eq!(replay(&mut config.challenger(), &ops), expected);
+            // Cloning the config preserves the builder.
+            assert_eq!(replay(&mut config.clone().challenger(), &ops), expected);
+            assert_ne!(replay(&mut KoalaBearPoseidon2::new().challenger(), &ops), expected);
+        }
+    }
+}
@@ -14,6 +14,7 @@ mod lookup;
 mod machine;
 mod opts;
 mod permutation;
+mod progress;
 mod proof;
 mod prover;
 mod quotient;
@@ -24,6 +25,7 @@ pub mod septic_extension;
 pub mod shape;
 #[cfg(test)]
 mod stark_testing;
+mod threads;
 mod types;
 mod verifier;
 mod word;
@@ -39,10 +41,12 @@ pub use lookup::*;
 pub use machine::*;
 pub use opts::*;
 pub use permutation::*;
+pub use progress::*;
 pub use proof::*;
 pub use prover::*;
 pub use quotient::*;
 pub use record::*;
+pub use threads::*;
 pub use types::*;
 pub use verifier::*;
 pub use word::*;
@@ -23,6 +23,7 @@ use crate::{
     septic_curve::SepticCurve,
     septic_digest::SepticDigest,
     septic_extension::SepticExtension,
+    types::serialize_chip_ordering,
     DebugConstraintBuilder, ShardProof, VerifierConstraintFolder,
 };
 
@@ -100,6 +101,7 @@ pub struct StarkVerifyingKey<SC: StarkGenericConfig> {
     /// The chip information.
     pub chip_information: Vec<(String, Dom<SC>, Dimensions)>,
     /// The chip ordering.
+    #[serde(serialize_with = "serialize_chip_ordering")]
     pub chip_ordering: HashMap<String, usize>,
 }
 
@@ -190,106 +192,11 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
         global_cumulative_sums.push(pk.initial_global_cumulative_sum);
 
         for shard in records.iter() {
-            // Filter the chips based on what is used.
-            let chips = self.shard_chips(shard).collect::<Vec<_>>();
-
-            // Generate the main trace for each chip.
-            let pre_traces = chips
-                .iter()
-                .map(|chip| pk.chip_ordering.get(&chip.name()).map(|index| &pk.traces[*index]))
-                .collect::<Vec<_>>();
-            let mut traces = chips
-                .par_iter()
-                .map(|chip| chip.generate_trace(shard, &mut A::Record::default()))
-                .zip(pre_traces)
-                .collect::<Vec<_>>();
-
-            // Generate the permutation traces.
-            let mut permutation_traces = Vec::with_capacity(chips.len());
-            let mut chip_cumulative_sums = Vec::with_capacity(chips.len());
-            tracing::debug_span!("generate permutation traces").in_scope(|| {
-                chips
-                    .par_iter()
-                    .zip(traces.par_iter_mut())
-                    .map(|(chip, (main_trace, pre_trace))| {
-                        let (trace, local_sum) = chip.generate_permutation_trace(
-                            *pre_trace,
-                            main_trace,
-                            &permutation_challenges,
-                        );
-                        let global_sum = if chip.commit_scope() == LookupScope::Local {
-                            SepticDigest::<Val<SC>>::zero()
-                        } else {
-                            let main_trace_size = main_trace.height() * main_trace.width();
-                            let last_row =
-                                &main_trace.values[main_trace_size - 14..main_trace_size];
-                            SepticDigest(SepticCurve {
-                                x: SepticExtension::<Val<SC>>::from_base_fn(|i| last_row[i]),
-                                y: SepticExtension::<Val<SC>>::from_base_fn(|i| last_row[i + 7]),
-                            })
-                        };
-                        (trace, (global_sum, local_sum))
-                    })
-                    .unzip_into_vecs(&mut permutation_traces, &mut chip_cumulative_sums);
-            });
-
-            let global_cumulative_sum =
-                chip_cumulative_sums.iter().map(|sums| sums.0).sum::<SepticDigest<Val<SC>>>();
-            global_cumulative_sums.push(global_cumulative_sum);
-
-            let local_cumulative_sum =
-                chip_cumulative_sums.iter().map(|sums| sums.1).sum::<SC::Challenge>();
-
-            if !local_cumulative_sum.is_zero() {
-                tracing::warn!("Local cumulative sum is not zero");
-                tracing::debug_span!("debug local lookups").in_scope(|| {
-                    debug_lookups_with_all_chips::<SC, A>(
-                        self,
-                        pk,
-                        std::slice::from_ref(shard),
-                        LookupKind::all_kinds(),
-                        LookupScope::Local,
-                    )
-                });
-                panic!("Local cumulative sum is not zero");
-            }
-
-            // Compute some statistics.
-            for i in 0..chips.len() {
-                let trace_width = traces[i].0.width();
-                let pre_width = traces[i].1.map_or(0, p3_matrix::Matrix::width);
-                let permutation_width = permutation_traces[i].width()
-                    * <SC::Challenge as FieldExtensionAlgebra<SC::Val>>::D;
-                let total_width = trace_width + pre_width + permutation_width;
-                tracing::debug!(
-                    "{:<11} | Main Cols = {:<5} | Pre Cols = {:<5} | Perm Cols = {:<5} | Rows = {:<10} | Cells = {:<10}",
-                    chips[i].name(),
-                    trace_width,
-                    pre_width,
-                    permutation_width,
-                    traces[i].0.height(),
-                    total_width * traces[i].0.height(),
-                );
-            }
-
-            if env::var("SKIP_CONSTRAINTS").is_err() {
-                tracing::info_span!("debug constraints").in_scope(|| {
-                    for i in 0..chips.len() {
-                        let preprocessed_trace =
-                            pk.chip_ordering.get(&chips[i].name()).map(|index| &pk.traces[*index]);
-                        debug_constraints::<SC, A>(
-                            chips[i],
-                            preprocessed_trace,
-                            &traces[i].0,
-                            &permutation_traces[i],
-                            &permutation_challenges,
-                            &shard.public_values(),
-                            &chip_cumulative_sums[i].1,
-                            &chip_cumulative_sums[i].0,
-                        );
-                    }
-                });
-            }
+            global_cumulative_sums.push(self.debug_shard_constraints(
+                pk,
+                shard,
+                &permutation_challenges,
+            ));
         }
 
         tracing::info!("Constraints verified successfully");
@@ -317,6 +224,136 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>>> StarkMachine<SC, A> {
             panic!("Global cumulative sum is not zero");
         }
     }
+
+    /// Debugs the constraints of a single shard, without checking the global lookups which span
+    /// several shards.
+    pub fn debug_shard(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        shard: &A::Record,
+        challenger: &mut SC::Challenger,
+    ) where
+        SC::Val: PrimeField32,
+        A: for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    {
+        let permutation_challenges =
+            (0..4).map(|_| challenger.sample_ext_element()).collect::<Vec<SC::Challenge>>();
+        self.debug_shard_constraints(pk, shard, &permutation_challenges);
+        tracing::info!("Constraints verified successfully");
+    }
+
+    /// Debugs the constraints and local lookups of a shard, returning its global cumulative sum.
+    fn debug_shard_constraints(
+        &self,
+        pk: &StarkProvingKey<SC>,
+        shard: &A::Record,
+        permutation_challenges: &[SC::Challenge],
+    ) -> SepticDigest<Val<SC>>
+    where
+        SC::Val: PrimeField32,
+        A: for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, SC::Challenge>>,
+    {
+        // Filter the chips based on what is used.
+        let chips = self.shard_chips(shard).collect::<Vec<_>>();
+
+        // Generate the main trace for each chip.
+        let pre_traces = chips
+            .iter()
+            .map(|chip| pk.chip_ordering.get(&chip.name()).map(|index| &pk.traces[*index]))
+            .collect::<Vec<_>>();
+        let mut traces = chips
+            .par_iter()
+            .map(|chip| chip.generate_trace(shard, &mut A::Record::default()))
+            .zip(pre_traces)
+            .collect::<Vec<_>>();
+
+        // Generate the permutation traces.
+        let mut permutation_traces = Vec::with_capacity(chips.len());
+        let mut chip_cumulative_sums = Vec::with_capacity(chips.len());
+        tracing::debug_span!("generate permutation traces").in_scope(|| {
+            chips
+                .par_iter()
+                .zip(traces.par_iter_mut())
+                .map(|(chip, (main_trace, pre_trace))| {
+                    let (trace, local_sum) = chip.generate_permutation_trace(
+                        *pre_trace,
+                        main_trace,
+                        permutation_challenges,
+                    );
+                    let global_sum = if chip.commit_scope() == LookupScope::Local {
+                        SepticDigest::<Val<SC>>::zero()
+                    } else {
+                        let main_trace_size = main_trace.height() * main_trace.width();
+                        let last_row = &main_trace.values[main_trace_size - 14..main_trace_size];
+                        SepticDigest(SepticCurve {
+                            x: SepticExtension::<Val<SC>>::from_base_fn(|i| last_row[i]),
+                            y: SepticExtension::<Val<SC>>::from_base_fn(|i| last_row[i + 7]),
+                        })
+                    };
+                    (trace, (global_sum, local_sum))
+                })
+                .unzip_into_vecs(&mut permutation_traces, &mut chip_cumulative_sums);
+        });
+
+        let global_cumulative_sum =
+            chip_cumulative_sums.iter().map(|sums| sums.0).sum::<SepticDigest<Val<SC>>>();
+
+        let local_cumulative_sum =
+            chip_cumulative_sums.iter().map(|sums| sums.1).sum::<SC::Challenge>();
+
+        if !local_cumulative_sum.is_zero() {
+            tracing::warn!("Local cumulative sum is not zero");
+            tracing::debug_span!("debug local lookups").in_scope(|| {
+                debug_lookups_with_all_chips::<SC, A>(
+                    self,
+                    pk,
+                    std::slice::from_ref(shard),
+                    LookupKind::all_kinds(),
+                    LookupScope::Local,
+                )
+            });
+            panic!("Local cumulative sum is not zero");
+        }
+
+        // Compute some statistics.
+        for i in 0..chips.len() {
+            let trace_width = traces[i].0.width();
+            let pre_width = traces[i].1.map_or(0, p3_matrix::Matrix::width);
+            let permutation_width = permutation_traces[i].width()
+                * <SC::Challenge as FieldExtensionAlgebra<SC::Val>>::D;
+            let total_width = trace_width + pre_width + permutation_width;
+            tracing::debug!(
+                "{:<11} | Main Cols = {:<5} | Pre Cols = {:<5} | Perm Cols = {:<5} | Rows = {:<10} | Cells = {:<10}",
+                chips[i].name(),
+                trace_width,
+                pre_width,
+                permutation_width,
+                traces[i].0.height(),
+                total_width * traces[i].0.height(),
+            );
+        }
+
+        if env::var("SKIP_CONSTRAINTS").is_err() {
+            tracing::info_span!("debug constraints").in_scope(|| {
+                for i in 0..chips.len() {
+                    let preprocessed_trace =
+                        pk.chip_ordering.get(&chips[i].name()).map(|index| &pk.traces[*index]);
+                    debug_constraints::<SC, A>(
+                        chips[i],
+                        preprocessed_trace,
+                        &traces[i].0,
+                        &permutation_traces[i],
+                        permutation_challenges,
+                        &shard.public_values(),
+                        &chip_cumulative_sums[i].1,
+                        &chip_cumulative_sums[i].0,
+                    );
+                }
+            });
+        }
+
+        global_cumulative_sum
+    }
 }
 
 impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val<SC>>>>
@@ -327,54 +364,89 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
     /// Given a program, this function generates the proving and verifying keys. The keys correspond
     /// to the program code and other preprocessed columns such as lookup tables.
     #[instrument("setup machine", level = "debug", skip_all)]
-    #[allow(clippy::map_unwrap_or)]
-    #[allow(clippy::redundant_closure_for_method_calls)]
     pub fn setup(&self, program: &A::Program) -> (StarkProvingKey<SC>, StarkVerifyingKey<SC>) {
-        let parent_span = tracing::debug_span!("generate preprocessed traces");
-        let (named_preprocessed_traces, num_constraints): (Vec<_>, Vec<_>) =
-            parent_span.in_scope(|| {
-                self.chips()
-                    .par_iter()
-                    .map(|chip| {
-                        let chip_name = chip.name();
-                        let begin = Instant::now();
-                        let prep_trace = chip.generate_preprocessed_trace(program);
-                        tracing::debug!(
-                            parent: &parent_span,
-                            "generated preprocessed trace for chip {} in {:?}",
-                            chip_name,
-                            begin.elapsed()
-                        );
-                        // Assert that the chip width data is correct.
-                        let expected_width = prep_trace.as_ref().map(|t| t.width()).unwrap_or(0);
-                        assert_eq!(
-                            expected_width,
-                            chip.preprocessed_width(),
-                            "Incorrect number of preprocessed columns for chip {chip_name}"
-                        );
+        self.setup_with_constraints(program, self.constraints_map())
+    }
 
-                        // Count the number of constraints.
-                        let num_main_constraints = get_symbolic_constraints(
-                            &chip.air,
-                            chip.preprocessed_width(),
-                            PROOF_MAX_NUM_PVS,
-                        )
-                        .len();
+    /// The setup preprocessing phase of many programs at once.
+    ///
+    /// The number of constraints of the chips does not depend on the program, so it is counted
+    /// once for all the programs, which are then set up in parallel. The keys are returned in the
+    /// order of `programs`.
+    #[instrument("setup machine for many programs", level = "debug", skip_all)]
+    pub fn setup_many(
+        &self,
+        programs: &[A::Program],
+    ) -> Vec<(StarkProvingKey<SC>, StarkVerifyingKey<SC>)> {
+        let constraints_map = self.constraints_map();
+        programs
+            .par_iter()
+            .map(|program| self.setup_with_constraints(program, constraints_map.clone()))
+            .collect()
+    }
 
-                        let num_permutation_constraints = count_permutation_constraints(
-                            &chip.sends,
-                            &chip.receives,
-                            chip.logup_batch_size(),
-                            chip.air.commit_scope(),
-                        );
+    /// Counts the constraints of each chip, by chip name.
+    fn constraints_map(&self) -> HashMap<String, usize> {
+        tracing::debug_span!("count constraints").in_scope(|| {
+            self.chips()
+                .par_iter()
+                .map(|chip| {
+                    let num_main_constraints = get_symbolic_constraints(
+                        &chip.air,
+                        chip.preprocessed_width(),
+                        PROOF_MAX_NUM_PVS,
+                    )
+                    .len();
+
+                    let num_permutation_constraints = count_permutation_constraints(
+                        &chip.sends,
+                        &chip.receives,
+                        chip.logup_batch_size(),
+                        chip.air.commit_scope(),
+                    );
+
+                    (chip.name(), num_main_constraints + num_permutation_constraints)
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .collect()
+        })
+    }
 
-                        (
-                            prep_trace.map(move |t| (chip.name(), chip.local_only(), t)),
-                            (chip_name, num_main_constraints + num_permutation_constraints),
-                        )
-                    })
-                    .unzip()
-            });
+    /// The setup preprocessing phase, given the number of constraints of each chip.
+    #[allow(clippy::map_unwrap_or)]
+    #[allow(clippy::redundant_closure_for_method_calls)]
+    fn setup_with_constraints(
+        &self,
+        program: &A::Program,
+        constraints_map: HashMap<String, usize>,
+    ) -> (StarkProvingKey<SC>, StarkVerifyingKey<SC>) {
+        let parent_span = tracing::debug_span!("generate preprocessed traces");
+        let named_preprocessed_traces: Vec<_> = parent_span.in_scope(|| {
+            self.chips()
+                .par_iter()
+                .map(|chip| {
+                    let chip_name = chip.name();
+                    let begin = Instant::now();
+                    let prep_trace = chip.generate_preprocessed_trace(program);
+                    tracing::debug!(
+                        parent: &parent_span,
+                        "generated preprocessed trace for chip {} in {:?}",
+                        chip_name,
+                        begin.elapsed()
+                    );
+                    // Assert that the chip width data is correct.
+                    let expected_width = prep_trace.as_ref().map(|t| t.width()).unwrap_or(0);
+                    assert_eq!(
+                        expected_width,
+                        chip.preprocessed_width(),
+                        "Incorrect number of preprocessed columns for chip {chip_name}"
+                    );
+
+                    prep_trace.map(move |t| (chip_name, chip.local_only(), t))
+                })
+                .collect()
+        });
 
         let mut named_preprocessed_traces =
             named_preprocessed_traces.into_iter().flatten().collect::<Vec<_>>();
@@ -408,8 +480,6 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
             .map(|(_, local_only, _)| local_only.to_owned())
             .collect::<Vec<_>>();
 
-        let constraints_map: HashMap<_, _> = num_constraints.into_iter().collect();
-
         // Get the preprocessed traces
         let traces =
             named_preprocessed_traces.into_iter().map(|(_, _, trace)| trace).collect::<Vec<_>>();
@@ -607,21 +677,8 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
 
         tracing::debug_span!("verify shard proofs").in_scope(|| {
             for (i, shard_proof) in proof.shard_proofs.iter().enumerate() {
-                tracing::debug_span!("verifying shard", shard = i).in_scope(|| {
-                    let chips =
-                        self.shard_chips_ordered(&shard_proof.chip_ordering).collect::<Vec<_>>();
-                    let mut shard_challenger = challenger.clone();
-                    shard_challenger
-                        .observe_slice(&shard_proof.public_values[0..self.num_pv_elts()]);
-                    Verifier::verify_shard(
-                        &self.config,
-                        vk,
-                        &chips,
-                        &mut shard_challenger,
-                        shard_proof,
-                    )
-                    .map_err(MachineVerificationError::InvalidShardProof)
-                })?;
+                tracing::debug_span!("verifying shard", shard = i)
+                    .in_scope(|| self.verify_shard(vk, shard_proof, challenger))?;
             }
 
             Ok(())
@@ -644,6 +701,27 @@ impl<SC: StarkGenericConfig, A: MachineAir<Val<SC>> + Air<SymbolicAirBuilder<Val
             Ok(())
         })
     }
+
+    /// Verify a single shard proof, without checking the global cumulative sum.
+    ///
+    /// The `challenger` should have observed the verifying key, and is left unchanged. Since the
+    /// shards are verified independently, this allows checking a proof one shard at a time.
+    pub fn verify_shard(
+        &self,
+        vk: &StarkVerifyingKey<SC>,
+        shard_proof: &ShardProof<SC>,
+        challenger: &SC::Challenger,
+    ) -> Result<(), MachineVerificationError<SC>>
+    where
+        SC::Challenger: Clone,
+        A: for<'a> Air<VerifierConstraintFolder<'a, SC>>,
+    {
+        let chips = self.shard_chips_ordered(&shard_proof.chip_ordering).collect::<Vec<_>>();
+        let mut shard_challenger = challenger.clone();
+        shard_challenger.observe_slice(&shard_proof.public_values[0..self.num_pv_elts()]);
+        Verifier::verify_shard(&self.config, vk, &chips, &mut shard_challenger, shard_proof)
+            .map_err(MachineVerificationError::InvalidShardProof)
+    }
 }
 
 /// Errors that can occur during machine verification.
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
@@ -9,6 +9,11 @@ const MAX_SHARD_BATCH_SIZE: usize = 8;
 const DEFAULT_TRACE_GEN_WORKERS: usize = 1;
 const DEFAULT_CHECKPOINTS_CHANNEL_CAPACITY: usize = 128;
 const DEFAULT_RECORDS_AND_TRACES_CHANNEL_CAPACITY: usize = 1;
+const DEFAULT_STALL_TIMEOUT_SECS: u64 = 30 * 60;
+const DEFAULT_SHARD_PROVER_WORKERS: usize = 1;
+const MIN_TUNED_LOG2_SHARD_SIZE: usize = 16;
+const MAX_TUNED_LOG2_SHARD_SIZE: usize = 22;
+const DETERMINISTIC_CPU_RAM_GB: usize = 48;
 
 /// The threshold for splitting deferred events.
 pub const MAX_DEFERRED_SPLIT_THRESHOLD: usize = 1 << 15;
@@ -28,6 +33,30 @@ impl Default for ZKMProverOpts {
     }
 }
 
+/// Where the core prover keeps the main traces of the shards between their generation and their
+/// commitment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceBacking {
+    /// Keep the traces in memory.
+    #[default]
+    Memory,
+    /// Copy the traces to memory-mapped temporary files in the given directory, and only load
+    /// them back when their shard is proven. The pages of the waiting traces are backed by the
+    /// files rather than by the memory of the prover, so the operating system writes them out
+    /// under memory pressure, and the memory budget of the core prover only holds the shards
+    /// being proven.
+    Disk(PathBuf),
+}
+
+impl TraceBacking {
+    /// Spills the traces to the directory in the `TRACE_SPILL_DIR` environment variable, if it
+    /// is set.
+    #[must_use]
+    pub fn from_env() -> Self {
+        env::var_os("TRACE_SPILL_DIR").map_or(Self::Memory, |dir| Self::Disk(dir.into()))
+    }
+}
+
 impl ZKMProverOpts {
     /// Get the default prover options.
     #[must_use]
@@ -36,6 +65,33 @@ impl ZKMProverOpts {
         ZKMProverOpts::cpu(cpu_ram_gb as usize)
     }
 
+    /// Get the prover options producing the same proofs of a program and its input on every
+    /// machine and every run.
+    ///
+    /// The challenger of every proof is seeded by the verifying key and the commitments of the
+    /// proof only, so its challenges are fixed by the shards being proven. These options fix the
+    /// shards whatever the environment and the RAM of the machine, see
+    /// [`ZKMCoreOpts::deterministic`], and the stall timeouts are disabled, so a slow run is not
+    /// aborted. The prover must also run on [`crate::ProverThreadPool::single_threaded`], so the
+    /// proof-of-work witnesses of FRI, searched in parallel otherwise, are the first ones found
+    /// in order.
+    ///
+    /// The proofs are much slower than with the default options. The Groth16 and Plonk proofs
+    /// wrapping the compressed proofs stay randomized by gnark.
+    #[must_use]
+    pub fn deterministic() -> Self {
+        Self {
+            core_opts: ZKMCoreOpts::deterministic(),
+            recursion_opts: ZKMCoreOpts {
+                split_opts: SplitOpts::new(MAX_DEFERRED_SPLIT_THRESHOLD),
+                stall_timeout_secs: 0,
+                auto_tune: false,
+                dynamic_shapes: false,
+                ..ZKMCoreOpts::recursion()
+            },
+        }
+    }
+
     /// Get the memory options (shard size, shard batch size, and divisor) for a prover on CPU based
     /// on the amount of CPU memory.
     #[must_use]
@@ -64,11 +120,7 @@ impl ZKMProverOpts {
         opts.core_opts.trace_gen_workers = 1;
 
         let divisor = 1 << log2_divisor;
-        opts.core_opts.split_opts.deferred /= divisor;
-        opts.core_opts.split_opts.keccak /= divisor;
-        opts.core_opts.split_opts.sha_extend /= divisor;
-        opts.core_opts.split_opts.sha_compress /= divisor;
-        opts.core_opts.split_opts.memory /= divisor;
+        opts.core_opts.split_opts.scale_down(divisor);
 
         opts.recursion_opts.shard_batch_size = 2;
         opts.recursion_opts.records_and_traces_channel_capacity = 1;
@@ -128,6 +180,23 @@ pub struct ZKMCoreOpts {
     pub records_and_traces_channel_capacity: usize,
     /// The frequency for shape checks.
     pub shape_check_frequency: u64,
+    /// The number of seconds without progress after which the compress pipeline is considered
+    /// stalled and aborted. Zero disables stall detection.
+    pub stall_timeout_secs: u64,
+    /// The number of shards committed to and opened concurrently by the core prover, one by
+    /// default.
+    pub shard_prover_workers: usize,
+    /// The number of bytes the traces of the shards in flight in the core prover may take, from
+    /// their generation until their opening. Zero, the default, disables the limit.
+    pub memory_budget: usize,
+    /// Whether the prover overrides `shard_size` and `shard_batch_size` with the values picked by
+    /// [`Self::tune_shards`] for the program being proven.
+    pub auto_tune: bool,
+    /// Whether the core prover proves the shards fitting no allowed shape with the natural
+    /// heights of their chips instead of failing. Their proofs can only be compressed with vk
+    /// verification disabled, since their shapes are not part of the allowed verification keys,
+    /// so the prover rejects this option unless `VERIFY_VK=false`.
+    pub dynamic_shapes: bool,
 }
 
 impl Default for ZKMCoreOpts {
@@ -162,6 +231,17 @@ impl Default for ZKMCoreOpts {
             shape_check_frequency: env::var("SHAPE_CHECK_FREQUENCY")
                 .map_or_else(|_| 16, |s| s.parse::<u64>().unwrap_or(16)),
             reconstruct_commitments: true,
+            stall_timeout_secs: env::var("STALL_TIMEOUT_SECS").map_or_else(
+                |_| DEFAULT_STALL_TIMEOUT_SECS,
+                |s| s.parse::<u64>().unwrap_or(DEFAULT_STALL_TIMEOUT_SECS),
+            ),
+            shard_prover_workers: env::var("SHARD_PROVER_WORKERS").map_or_else(
+                |_| DEFAULT_SHARD_PROVER_WORKERS,
+                |s| s.parse::<usize>().unwrap_or(DEFAULT_SHARD_PROVER_WORKERS),
+            ),
+            memory_budget: memory_budget(),
+            auto_tune: auto_tune(),
+            dynamic_shapes: dynamic_shapes(),
         };
 
         tracing::info!(
@@ -171,17 +251,88 @@ impl Default for ZKMCoreOpts {
         );
 
         let divisor = 1 << default_log2_divisor;
-        opts.split_opts.deferred /= divisor;
-        opts.split_opts.keccak /= divisor;
-        opts.split_opts.sha_extend /= divisor;
-        opts.split_opts.sha_compress /= divisor;
-        opts.split_opts.memory /= divisor;
+        opts.split_opts.scale_down(divisor);
 
         opts
     }
 }
 
+/// The memory budget of the core prover in bytes, from the `MEMORY_BUDGET_GB` environment
+/// variable. The budget is unlimited if it is not set.
+fn memory_budget() -> usize {
+    let gb = env::var("MEMORY_BUDGET_GB").map_or(0, |s| s.parse().unwrap_or(0));
+    gb.saturating_mul(1 << 30)
+}
+
+/// Whether shard auto-tuning is enabled with `AUTO_TUNE_SHARDS=true`.
+fn auto_tune() -> bool {
+    env::var("AUTO_TUNE_SHARDS").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+/// Whether the shards fitting no allowed shape are proven with dynamic shapes with
+/// `DYNAMIC_SHAPES=true`.
+fn dynamic_shapes() -> bool {
+    env::var("DYNAMIC_SHAPES").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+/// The shard parameters picked by [`ZKMCoreOpts::tune_shards`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardTuning {
+    /// The shard size.
+    pub shard_size: usize,
+    /// The shard batch size.
+    pub shard_batch_size: usize,
+    /// The number of bytes the shards were sized for.
+    pub memory: usize,
+    /// The estimated peak memory of proving a shard, in bytes.
+    pub shard_memory: usize,
+}
+
 impl ZKMCoreOpts {
+    /// Sets the largest shard size whose shards in flight fit in `memory` bytes, and the shard
+    /// batch size filling the rest of it, given the peak memory `shard_memory(shard_size)` of
+    /// proving a shard.
+    ///
+    /// A shard is in flight while its traces are generated by one of the `trace_gen_workers`
+    /// workers or proven by one of the `shard_prover_workers` workers. The shard size stays
+    /// between `2^16` and `2^22` cycles.
+    pub fn tune_shards(
+        &mut self,
+        memory: usize,
+        shard_memory: impl Fn(usize) -> usize,
+    ) -> ShardTuning {
+        let provers = self.shard_prover_workers.max(1);
+        let generators = self.trace_gen_workers.max(1);
+        let log2_shard_size = (MIN_TUNED_LOG2_SHARD_SIZE..=MAX_TUNED_LOG2_SHARD_SIZE)
+            .rev()
+            .find(|log2| shard_memory(1 << log2).saturating_mul(provers + generators) <= memory)
+            .unwrap_or(MIN_TUNED_LOG2_SHARD_SIZE);
+        self.shard_size = 1 << log2_shard_size;
+
+        // Every trace generator holds the records of a whole batch of shards.
+        let bytes = shard_memory(self.shard_size).max(1);
+        let spare = (memory / bytes).saturating_sub(provers) / generators;
+        self.shard_batch_size = spare.clamp(1, MAX_SHARD_BATCH_SIZE);
+
+        ShardTuning {
+            shard_size: self.shard_size,
+            shard_batch_size: self.shard_batch_size,
+            memory,
+            shard_memory: bytes,
+        }
+    }
+
+    /// The number of bytes [`Self::tune_shards`] sizes the shards for: the memory budget, or half
+    /// of the RAM of the machine if the budget is unlimited.
+    #[must_use]
+    pub fn tuning_memory(&self) -> usize {
+        if self.memory_budget > 0 {
+            self.memory_budget
+        } else {
+            (System::new_all().total_memory() / 2) as usize
+        }
+    }
+
     /// Get the default options for the recursion prover.
     #[must_use]
     pub fn recursion() -> Self {
@@ -192,6 +343,37 @@ impl ZKMCoreOpts {
         opts
     }
 
+    /// Get the options of the core prover of [`ZKMProverOpts::deterministic`].
+    ///
+    /// The shard size and the split thresholds, which set where the shards are cut, are those of
+    /// a machine with 48GB of RAM whatever the environment, and so is the frequency of the shape
+    /// checks. Auto-tuning and dynamic shapes, which depend on the machine, are disabled.
+    #[must_use]
+    pub fn deterministic() -> Self {
+        Self::default().into_deterministic()
+    }
+
+    /// Overrides the options of `self` setting where the shards are cut with the ones of
+    /// [`Self::deterministic`].
+    fn into_deterministic(self) -> Self {
+        let (log2_shard_size, shard_batch_size, log2_divisor) =
+            ZKMProverOpts::get_memory_opts(DETERMINISTIC_CPU_RAM_GB);
+        let mut split_opts = SplitOpts::new(MAX_DEFERRED_SPLIT_THRESHOLD);
+        split_opts.scale_down(1 << log2_divisor);
+
+        Self {
+            shard_size: 1 << log2_shard_size,
+            shard_batch_size,
+            split_opts,
+            shape_check_frequency: 16,
+            stall_timeout_secs: 0,
+            shard_prover_workers: 1,
+            auto_tune: false,
+            dynamic_shapes: false,
+            ..self
+        }
+    }
+
     /// Get the maximum options for the core prover.
     #[must_use]
     pub fn max() -> Self {
@@ -226,6 +408,17 @@ impl ZKMCoreOpts {
             shape_check_frequency: env::var("SHAPE_CHECK_FREQUENCY")
                 .map_or_else(|_| 16, |s| s.parse::<u64>().unwrap_or(16)),
             reconstruct_commitments: true,
+            stall_timeout_secs: env::var("STALL_TIMEOUT_SECS").map_or_else(
+                |_| DEFAULT_STALL_TIMEOUT_SECS,
+                |s| s.parse::<u64>().unwrap_or(DEFAULT_STALL_TIMEOUT_SECS),
+            ),
+            shard_prover_workers: env::var("SHARD_PROVER_WORKERS").map_or_else(
+                |_| DEFAULT_SHARD_PROVER_WORKERS,
+                |s| s.parse::<usize>().unwrap_or(DEFAULT_SHARD_PROVER_WORKERS),
+            ),
+            memory_budget: memory_budget(),
+            auto_tune: auto_tune(),
+            dynamic_shapes: dynamic_shapes(),
         }
     }
 }
@@ -241,6 +434,12 @@ pub struct SplitOpts {
     pub sha_extend: usize,
     /// The threshold for sha compress events.
     pub sha_compress: usize,
+    /// The threshold for blake3 compress events.
+    pub blake3_compress: usize,
+    /// The threshold for sha512 extend events.
+    pub sha512_extend: usize,
+    /// The threshold for sha512 compress events.
+    pub sha512_compress: usize,
     /// The threshold for memory events.
     pub memory: usize,
     /// The threshold for combining the memory init/finalize events in to the current shard in
@@ -257,10 +456,25 @@ impl SplitOpts {
             keccak: 8 * deferred_split_threshold / 24,
             sha_extend: 32 * deferred_split_threshold / 48,
             sha_compress: 32 * deferred_split_threshold / 80,
+            blake3_compress: 32 * deferred_split_threshold / 56,
+            sha512_extend: 32 * deferred_split_threshold / 64,
+            sha512_compress: 32 * deferred_split_threshold / 96,
             memory: 64 * deferred_split_threshold,
             combine_memory_threshold: 1 << 17,
         }
     }
+
+    /// Divides the thresholds of the deferred events by `divisor`.
+    fn scale_down(&mut self, divisor: usize) {
+        self.deferred /= divisor;
+        self.keccak /= divisor;
+        self.sha_extend /= divisor;
+        self.sha_compress /= divisor;
+        self.blake3_compress /= divisor;
+        self.sha512_extend /= divisor;
+        self.sha512_compress /= divisor;
+        self.memory /= divisor;
+    }
 }
 
 #[cfg(test)]
@@ -301,4 +515,57 @@ mod tests {
         let opts = ZKMProverOpts::auto();
         println!("auto: {:?}", opts.core_opts);
     }
+
+    #[test]
+    fn test_deterministic_opts() {
+        // The shards are cut the same way whatever the RAM of the machine.
+        let small = ZKMProverOpts::cpu(8).core_opts.into_deterministic();
+        let large = ZKMProverOpts::cpu(512).core_opts.into_deterministic();
+        assert_ne!(
+            ZKMProverOpts::cpu(8).core_opts.shard_size,
+            ZKMProverOpts::cpu(512).core_opts.shard_size
+        );
+        assert_eq!(small.shard_size, large.shard_size);
+        assert_eq!(small.shard_batch_size, large.shard_batch_size);
+        assert_eq!(small.split_opts, large.split_opts);
+        assert_eq!(small.shape_check_frequency, large.shape_check_frequency);
+        assert_eq!(small.shard_size, 1 << 20);
+        assert_eq!(small.split_opts.deferred, MAX_DEFERRED_SPLIT_THRESHOLD / 4);
+
+        let opts = ZKMProverOpts::deterministic();
+        assert_eq!(opts.core_opts.stall_timeout_secs, 0);
+        assert!(!opts.recursion_opts.auto_tune);
+    }
+
+    #[test]
+    fn test_pipeline_opts_are_opt_in() {
+        let opts = ZKMCoreOpts::default();
+        if env::var_os("SHARD_PROVER_WORKERS").is_none() {
+            assert_eq!(opts.shard_prover_workers, 1);
+        }
+        if env::var_os("MEMORY_BUDGET_GB").is_none() {
+            assert_eq!(opts.memory_budget, 0);
+            assert_eq!(ZKMCoreOpts::recursion().memory_budget, 0);
+        }
+    }
+
+    #[test]
+    fn test_tune_shards() {
+        let mut opts = ZKMCoreOpts::default();
+        opts.shard_prover_workers = 2;
+        opts.trace_gen_workers = 1;
+        let shard_memory = |shard_size: usize| shard_size * 1000;
+
+        // Three shards of 2^21 cycles fit, and one more per batch.
+        let tuning = opts.tune_shards(8000 << 20, shard_memory);
+        assert_eq!((opts.shard_size, opts.shard_batch_size), (1 << 21, 2));
+        assert_eq!(tuning.shard_memory, 1000 << 21);
+
+        let tuning = opts.tune_shards(3000 << 20, shard_memory);
+        assert_eq!((tuning.shard_size, tuning.shard_batch_size), (1 << 20, 1));
+
+        // The shards do not get smaller than the minimum size.
+        let tuning = opts.tune_shards(1 << 20, shard_memory);
+        assert_eq!((tuning.shard_size, tuning.shard_batch_size), (1 << 16, 1));
+    }
 }
@@ -0,0 +1,106 @@
+use std::{fmt, sync::Arc};
+
+/// An event reported to a [ProgressHandler] while a proof is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// The core prover executed the program up to a checkpoint, after `cycles` cycles in total.
+    /// `done` is set at the last checkpoint, once the program halted.
+    Execution { cycles: u64, done: bool },
+    /// A shard of the core proof was proven. `num_shards` is the number of shards of the proof,
+    /// known once the whole execution is traced.
+    CoreShard { shard: u32, num_proven: usize, num_shards: Option<usize> },
+    /// A proof of the compress tree was proven, at `layer` of the `num_layers` layers of the tree,
+    /// the first layer being `0`. `num_proofs` is the number of proofs of the whole tree.
+    CompressProof { layer: usize, num_layers: usize, num_proven: usize, num_proofs: usize },
+    /// The shrink proof of the compressed proof started.
+    ShrinkStarted,
+    /// The wrap proof over the BN254 field started.
+    WrapStarted,
+    /// The Plonk or Groth16 proof of the wrap proof started.
+    SnarkStarted,
+}
+
+impl ProgressEvent {
+    /// The fraction of its stage completed at this event, between `0` and `1`, if known.
+    ///
+    /// The stages are the execution, the core proof, the compress tree and the final proofs.
+    /// The fraction of the execution is never known, as the number of cycles of the program is
+    /// only known once it halted.
+    #[must_use]
+    pub fn fraction(&self) -> Option<f64> {
+        match *self {
+            Self::Execution { done, .. } => done.then_some(1.0),
+            Self::CoreShard { num_proven, num_shards, .. } => {
+                num_shards.map(|num_shards| num_proven as f64 / num_shards as f64)
+            }
+            Self::CompressProof { num_proven, num_proofs, .. } => {
+                Some(num_proven as f64 / num_proofs as f64)
+            }
+            Self::ShrinkStarted | Self::WrapStarted | Self::SnarkStarted => None,
+        }
+    }
+}
+
+/// A handler of the [ProgressEvent]s of a proof, e.g. to render progress bars and estimate the
+/// remaining time.
+///
+/// The events are reported from the threads of the prover as they happen, so the handler should
+/// return quickly. Closures taking a [ProgressEvent] are handlers.
+pub trait ProgressHandler: Send + Sync {
+    /// Handles an event of the proof.
+    fn on_event(&self, event: ProgressEvent);
+}
+
+impl<F: Fn(ProgressEvent) + Send + Sync> ProgressHandler for F {
+    fn on_event(&self, event: ProgressEvent) {
+        self(event);
+    }
+}
+
+/// The [ProgressHandler] of a proof, if any.
+#[derive(Clone, Default)]
+pub struct Progress(Option<Arc<dyn ProgressHandler>>);
+
+impl Progress {
+    /// Reports the events to `handler`.
+    pub fn new(handler: impl ProgressHandler + 'static) -> Self {
+        Self(Some(Arc::new(handler)))
+    }
+
+    /// Reports `event` to the handler, if any.
+    pub fn report(&self, event: ProgressEvent) {
+        if let Some(handler) = &self.0 {
+            handler.on_event(event);
+        }
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Progress(Some(..))" } else { "Progress(None)" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let progress = {
+            let events = Arc::clone(&events);
+            Progress::new(move |event: ProgressEvent| events.lock().unwrap().push(event))
+        };
+        progress.report(ProgressEvent::WrapStarted);
+        Progress::default().report(ProgressEvent::ShrinkStarted);
+        assert_eq!(*events.lock().unwrap(), vec![ProgressEvent::WrapStarted]);
+
+        let event = ProgressEvent::CoreShard { shard: 3, num_proven: 1, num_shards: Some(4) };
+        assert_eq!(event.fraction(), Some(0.25));
+        let event = ProgressEvent::CoreShard { shard: 3, num_proven: 1, num_shards: None };
+        assert_eq!(event.fraction(), None);
+    }
+}
@@ -51,6 +51,15 @@ pub trait MachineProver<SC: StarkGenericConfig, A: MachineAir<SC::Val>>:
     /// Setup the preprocessed data into a proving and verifying key.
     fn setup(&self, program: &A::Program) -> (Self::DeviceProvingKey, StarkVerifyingKey<SC>);
 
+    /// Setup the preprocessed data of many programs, sharing the work which does not depend on
+    /// the program.
+    fn setup_many(
+        &self,
+        programs: &[A::Program],
+    ) -> Vec<(Self::DeviceProvingKey, StarkVerifyingKey<SC>)> {
+        programs.iter().map(|program| self.setup(program)).collect()
+    }
+
     /// Setup the proving key given a verifying key. This is similar to `setup` but faster since
     /// some computed information is already in the verifying key.
     fn pk_from_vk(
@@ -222,6 +231,13 @@ where
         self.machine().setup(program)
     }
 
+    fn setup_many(
+        &self,
+        programs: &[A::Program],
+    ) -> Vec<(Self::DeviceProvingKey, StarkVerifyingKey<SC>)> {
+        self.machine().setup_many(programs)
+    }
+
     fn pk_from_vk(
         &self,
         program: &A::Program,
@@ -46,6 +46,12 @@ impl<K: Debug + Clone + Eq + Hash + FromStr> ShapeCluster<K> {
         Some(shape)
     }
 
+    /// The largest log2 height allowed for `air`, or `None` if the chip must be empty or is not
+    /// part of the cluster.
+    pub fn max_log2_height(&self, air: &K) -> Option<usize> {
+        self.inner.get(air)?.iter().flatten().max().copied()
+    }
+
     /// Iterate over the inner map.
     pub fn iter(&self) -> impl Iterator<Item = (&K, &Vec<Option<usize>>)> {
         self.inner.iter()
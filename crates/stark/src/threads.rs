@@ -0,0 +1,141 @@
+use std::{
+    env, fmt,
+    sync::{Arc, OnceLock},
+};
+
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// The thread pool of a prover, on which it runs its parallel work instead of the global rayon
+/// pool.
+///
+/// The trace generation, commitments and openings of the core and recursion provers run on it,
+/// so a prover sharing a machine with other services only uses the threads it is given. The
+/// number of threads of every stage is set by the workers of the options: `trace_gen_workers`
+/// and `shard_prover_workers`, which commit to and open their shards, for the core prover, and
+/// `trace_gen_workers` and `shard_batch_size` for the recursion prover.
+#[derive(Clone, Default)]
+pub struct ProverThreadPool(Option<Arc<ThreadPool>>);
+
+impl ProverThreadPool {
+    /// Runs the provers on a pool of `num_threads` threads, or of one thread per CPU of `cpus` if
+    /// `num_threads` is zero.
+    ///
+    /// If `cpus` is not empty, the threads are pinned to its CPUs in turn.
+    pub fn new(num_threads: usize, cpus: &[usize]) -> Result<Self, ThreadPoolBuildError> {
+        let num_threads = if num_threads == 0 { cpus.len() } else { num_threads };
+        let cpus = cpus.to_vec();
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|index| format!("zkm-prover-{index}"))
+            .start_handler(move |index| {
+                if !cpus.is_empty() {
+                    pin_current_thread(cpus[index % cpus.len()]);
+                }
+            })
+            .build()?;
+        Ok(Self::from_pool(Arc::new(pool)))
+    }
+
+    /// Runs the provers on `pool`, which may be shared with the rest of the host.
+    pub fn from_pool(pool: Arc<ThreadPool>) -> Self {
+        Self(Some(pool))
+    }
+
+    /// The pool of `PROVER_THREADS` threads pinned to the CPUs of `PROVER_CPUS`, a list of CPUs
+    /// and ranges of CPUs such as `0-7,16`, or the global rayon pool if neither is set.
+    ///
+    /// The pool is built once per process.
+    pub fn from_env() -> Self {
+        static POOL: OnceLock<ProverThreadPool> = OnceLock::new();
+        POOL.get_or_init(|| {
+            let num_threads =
+                env::var("PROVER_THREADS").map_or(0, |threads| threads.parse().unwrap_or(0));
+            let cpus = env::var("PROVER_CPUS").map_or(Ok(Vec::new()), |cpus| parse_cpus(&cpus));
+            let cpus = cpus.unwrap_or_else(|err| {
+                tracing::warn!("ignoring PROVER_CPUS: {err}");
+                Vec::new()
+            });
+            if num_threads == 0 && cpus.is_empty() {
+                return Self::default();
+            }
+            Self::new(num_threads, &cpus).unwrap_or_else(|err| {
+                tracing::warn!("failed to build the prover thread pool: {err}");
+                Self::default()
+            })
+        })
+        .clone()
+    }
+
+    /// The pool of a single thread, on which the parallel work of the provers runs in a fixed
+    /// order, as required by [`crate::ZKMProverOpts::deterministic`].
+    ///
+    /// The pool is built once per process.
+    pub fn single_threaded() -> Self {
+        static POOL: OnceLock<ProverThreadPool> = OnceLock::new();
+        POOL.get_or_init(|| Self::new(1, &[]).expect("failed to build the prover thread pool"))
+            .clone()
+    }
+
+    /// The number of threads the provers run on.
+    pub fn num_threads(&self) -> usize {
+        self.0.as_ref().map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads())
+    }
+
+    /// Runs `op` on the pool, or on the current thread if the provers use the global pool.
+    ///
+    /// `op` must not wait for other work run on the pool, such as the items of a channel fed by
+    /// it, or the threads of the pool may all end up waiting.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.0 {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+impl fmt::Debug for ProverThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(pool) => write!(f, "ProverThreadPool({} threads)", pool.current_num_threads()),
+            None => f.write_str("ProverThreadPool(global)"),
+        }
+    }
+}
+
+/// Pins the current thread to `cpu`, warning if the CPU does not exist or cannot be used.
+fn pin_current_thread(cpu: usize) {
+    if !core_affinity::set_for_current(core_affinity::CoreId { id: cpu }) {
+        tracing::warn!("failed to pin a prover thread to CPU {cpu}");
+    }
+}
+
+/// Parses a list of CPUs and ranges of CPUs such as `0-7,16`.
+fn parse_cpus(cpus: &str) -> Result<Vec<usize>, String> {
+    let mut parsed = Vec::new();
+    for entry in cpus.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let parse =
+            |cpu: &str| cpu.trim().parse::<usize>().map_err(|_| format!("invalid CPU {entry}"));
+        match entry.split_once('-') {
+            Some((start, end)) => parsed.extend(parse(start)?..=parse(end)?),
+            None => parsed.push(parse(entry)?),
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prover_thread_pool() {
+        assert_eq!(parse_cpus("0-3, 8,"), Ok(vec![0, 1, 2, 3, 8]));
+        assert!(parse_cpus("0-x").is_err());
+
+        let pool = ProverThreadPool::new(2, &[]).unwrap();
+        assert_eq!(pool.num_threads(), 2);
+        assert_eq!(ProverThreadPool::single_threaded().num_threads(), 1);
+        assert!(pool.install(|| rayon::current_thread_index().is_some()));
+        assert!(ProverThreadPool::default().install(|| rayon::current_thread_index().is_none()));
+    }
+}
@@ -5,7 +5,7 @@ use std::fmt::Debug;
 use hashbrown::HashMap;
 use itertools::Itertools;
 use p3_matrix::{dense::RowMajorMatrixView, stack::VerticalPair};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 use super::{Challenge, Com, OpeningProof, StarkGenericConfig, Val};
 use crate::septic_digest::SepticDigest;
@@ -78,10 +78,20 @@ pub struct ShardProof<SC: StarkGenericConfig> {
     pub commitment: ShardCommitment<Com<SC>>,
     pub opened_values: ShardOpenedValues<Val<SC>, Challenge<SC>>,
     pub opening_proof: OpeningProof<SC>,
+    #[serde(serialize_with = "serialize_chip_ordering")]
     pub chip_ordering: HashMap<String, usize>,
     pub public_values: Vec<Val<SC>>,
 }
 
+/// Serializes a chip ordering as a map with its entries sorted by chip name, so that equal
+/// orderings serialize to the same bytes.
+pub(crate) fn serialize_chip_ordering<S: Serializer>(
+    ordering: &HashMap<String, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(ordering.iter().sorted_by_key(|(name, _)| *name))
+}
+
 impl<SC: StarkGenericConfig> Debug for ShardProof<SC> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ShardProof").finish()
@@ -0,0 +1,25 @@
+#![no_main]
+zkm_zkvm::entrypoint!(main);
+
+use hex_literal::hex;
+use zkm_zkvm::lib::blake;
+
+/// The input of the official BLAKE3 test vectors of length `len`, whose byte `i` is `i % 251`.
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+pub fn main() {
+    // The hash of the empty input, from the BLAKE3 test vectors.
+    assert_eq!(
+        blake::blake3(&[]),
+        hex!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262")
+    );
+
+    // One block, several blocks of one chunk, and several chunks, with lengths both multiples of
+    // the 64-byte block and not, checked against the reference implementation.
+    for len in [1, 63, 64, 65, 100, 1023, 1024, 1025, 2048, 2049, 3072, 3073, 5000] {
+        let data = input(len);
+        assert_eq!(blake::blake3(&data), *blake3::hash(&data).as_bytes(), "length {len}");
+    }
+}
@@ -0,0 +1,10 @@
+//! A program committing its public values with Keccak-256, which it records in the trailer of its
+//! public values.
+
+#![no_std]
+#![no_main]
+zkm_zkvm::entrypoint!(main);
+
+pub fn main() {
+    zkm_zkvm::io::commit(&42u32);
+}
@@ -0,0 +1,40 @@
+#![no_main]
+zkm_zkvm::entrypoint!(main);
+
+use hex_literal::hex;
+use sha2::{Digest, Sha512};
+use zkm_zkvm::lib::sha512::sha512;
+
+pub fn main() {
+    // The SHA-512 examples of FIPS 180-2, and the empty message.
+    assert_eq!(
+        sha512(b""),
+        hex!(
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce"
+            "47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        )
+    );
+    assert_eq!(
+        sha512(b"abc"),
+        hex!(
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a"
+            "2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        )
+    );
+    assert_eq!(
+        sha512(
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopq\
+              klmnopqrlmnopqrsmnopqrstnopqrstu"
+        ),
+        hex!(
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018"
+            "501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        )
+    );
+
+    // The lengths around the padding boundaries, checked against the reference implementation.
+    for len in [111, 112, 127, 128, 129, 239, 240, 256, 1000] {
+        let data = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+        assert_eq!(sha512(&data)[..], Sha512::digest(&data)[..], "length {len}");
+    }
+}
@@ -5,6 +5,7 @@ use zkm_build::include_elf;
 pub const SHA2_RUST_ELF: &[u8] = include_elf!("sha2-rust");
 pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci");
 pub const HELLO_WORLD_ELF: &[u8] = include_elf!("hello-world");
+pub const HINT_IO_ELF: &[u8] = include_elf!("hint-io-test");
 
 pub const POSEIDON2_PERMUTE_ELF: &[u8] = include_elf!("poseidon2-permute-test");
 
@@ -12,6 +13,12 @@ pub const SHA2_ELF: &[u8] = include_elf!("sha2-test");
 pub const SHA_EXTEND_ELF: &[u8] = include_elf!("sha-extend-test");
 pub const SHA_COMPRESS_ELF: &[u8] = include_elf!("sha-compress-test");
 
+pub const BLAKE3_ELF: &[u8] = include_elf!("blake3-test");
+
+pub const SHA512_ELF: &[u8] = include_elf!("sha512-test");
+
+pub const KECCAK_COMMIT_ELF: &[u8] = include_elf!("keccak-commit-test");
+
 pub const SHA3_CHAIN_ELF: &[u8] = include_elf!("sha3-chain");
 pub const KECCAK_SPONGE_ELF: &[u8] = include_elf!("keccak-sponge-test");
 pub const PANIC_ELF: &[u8] = include_elf!("panic-test");
@@ -4,10 +4,11 @@ mod verify;
 
 use substrate_bn::Fr;
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::{decode_zkm_vkey_hash, error::Error, hash_public_inputs};
+use crate::{decode_zkm_vkey_hash, error::Error, hash_public_inputs, GROTH16_VK_BYTES};
 pub(crate) use converter::{load_groth16_proof_from_bytes, load_groth16_verifying_key_from_bytes};
 pub(crate) use verify::*;
 
@@ -24,6 +25,16 @@ use zkm_sdk::ZKMProofWithPublicValues;
 #[cfg(feature = "ark")]
 pub mod ark_converter;
 
+/// A zero-knowledge proof generated by the Groth16 protocol with a Base64 encoded gnark Groth16
+/// proof, as embedded in the Groth16 proofs saved by the SDK.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Groth16Bn254Proof {
+    pub public_inputs: [String; 2],
+    pub encoded_proof: String,
+    pub raw_proof: String,
+    pub groth16_vkey_hash: [u8; 32],
+}
+
 /// A verifier for Groth16 zero-knowledge proofs.
 #[derive(Debug)]
 pub struct Groth16Verifier;
@@ -66,7 +77,7 @@ impl Groth16Verifier {
         //
         // Ziren prepends the raw Groth16 proof with the first 4 bytes of the groth16 vkey to
         // facilitate this check.
-        if groth16_vk_hash != proof[..4] {
+        if proof.len() < 4 || groth16_vk_hash != proof[..4] {
             return Err(Groth16Error::Groth16VkeyHashMismatch);
         }
 
@@ -79,6 +90,28 @@ impl Groth16Verifier {
         )
     }
 
+    /// Verifies the Groth16 proof of a Ziren proof saved by the SDK against the Groth16 verifying
+    /// key embedded in this crate, [`static@crate::GROTH16_VK_BYTES`].
+    ///
+    /// Unlike [`crate::verify_groth16`], this only needs `alloc`, so it runs in `no_std`
+    /// environments, such as another zkVM or a Substrate runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The Groth16 proof of a `ZKMProof::Groth16` proof.
+    /// * `zkm_public_inputs` - The Ziren public values of the proof.
+    /// * `zkm_vkey_hash` - The Ziren vkey hash, from a call to `vk.bytes32()`.
+    pub fn verify_bn254_proof(
+        proof: &Groth16Bn254Proof,
+        zkm_public_inputs: &[u8],
+        zkm_vkey_hash: &str,
+    ) -> Result<(), Groth16Error> {
+        let encoded_proof = hex::decode(&proof.encoded_proof)
+            .map_err(|_| Groth16Error::GeneralError(Error::InvalidData))?;
+        let bytes = [&proof.groth16_vkey_hash[..4], &encoded_proof].concat();
+        Self::verify(&bytes, zkm_public_inputs, zkm_vkey_hash, &GROTH16_VK_BYTES)
+    }
+
     #[cfg(feature = "ark")]
     pub fn ark_verify(
         proof_with_pub_values: &ZKMProofWithPublicValues,
@@ -121,11 +154,13 @@ impl Groth16Verifier {
         public_inputs: &[[u8; 32]],
         groth16_vk: &[u8],
     ) -> Result<(), Groth16Error> {
-        let proof = load_groth16_proof_from_bytes(proof).unwrap();
-        let groth16_vk = load_groth16_verifying_key_from_bytes(groth16_vk).unwrap();
+        let proof = load_groth16_proof_from_bytes(proof)?;
+        let groth16_vk = load_groth16_verifying_key_from_bytes(groth16_vk)?;
 
-        let public_inputs =
-            public_inputs.iter().map(|input| Fr::from_slice(input).unwrap()).collect::<Vec<_>>();
+        let public_inputs = public_inputs
+            .iter()
+            .map(|input| Fr::from_slice(input).map_err(Error::Field))
+            .collect::<Result<Vec<_>, _>>()?;
         verify_groth16_algebraic(&groth16_vk, &proof, &public_inputs)
     }
 }
@@ -1,5 +1,9 @@
 //! This crate provides verifiers for Ziren Groth16 and Plonk BN254 proofs in a no-std environment.
 //! It is patched for efficient verification within the Ziren zkVM context.
+//!
+//! With the `std` feature, enabled by default, it also verifies the core and compressed proofs
+//! saved by the SDK, without pulling in the prover, see `verify`. With the `wasm` feature, the
+//! Groth16 and Plonk verifiers are exposed to JavaScript without `std`, see the `wasm` module.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
@@ -24,18 +28,31 @@ mod utils;
 pub use utils::*;
 
 pub use groth16::error::Groth16Error;
-pub use groth16::Groth16Verifier;
+pub use groth16::{Groth16Bn254Proof, Groth16Verifier};
 mod groth16;
 
+#[cfg(feature = "std")]
 pub use stark::error::StarkError;
-pub use stark::StarkVerifier;
+#[cfg(feature = "std")]
+pub use stark::{
+    HashableKey, StarkVerifier, ZKMProof, ZKMProofKind, ZKMProofWithPublicValues, ZKMVerifyingKey,
+};
+#[cfg(feature = "std")]
 mod stark;
 
+#[cfg(feature = "std")]
+pub use proof::*;
+#[cfg(feature = "std")]
+mod proof;
+
 #[cfg(feature = "ark")]
 pub use groth16::ark_converter::*;
 
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 pub use plonk::error::PlonkError;
-pub use plonk::PlonkVerifier;
+pub use plonk::{PlonkBn254Proof, PlonkVerifier};
 mod plonk;
 
 #[cfg(test)]
@@ -17,12 +17,24 @@ pub(crate) use converter::{load_plonk_proof_from_bytes, load_plonk_verifying_key
 pub(crate) use proof::PlonkProof;
 pub(crate) use verify::verify_plonk_algebraic;
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use error::PlonkError;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use substrate_bn::Fr;
 
-use crate::{decode_zkm_vkey_hash, error::Error, hash_public_inputs};
+use crate::{decode_zkm_vkey_hash, error::Error, hash_public_inputs, PLONK_VK_BYTES};
+
+/// A zero-knowledge proof generated by the PLONK protocol with a Base64 encoded gnark PLONK proof,
+/// as embedded in the PLONK proofs saved by the SDK.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlonkBn254Proof {
+    pub public_inputs: [String; 2],
+    pub encoded_proof: String,
+    pub raw_proof: String,
+    pub plonk_vkey_hash: [u8; 32],
+}
+
 /// A verifier for Plonk zero-knowledge proofs.
 #[derive(Debug)]
 pub struct PlonkVerifier;
@@ -65,7 +77,7 @@ impl PlonkVerifier {
         //
         // Ziren prepends the raw Plonk proof with the first 4 bytes of the plonk vkey to
         // facilitate this check.
-        if plonk_vk_hash != proof[..4] {
+        if proof.len() < 4 || plonk_vk_hash != proof[..4] {
             return Err(PlonkError::PlonkVkeyHashMismatch);
         }
 
@@ -78,6 +90,28 @@ impl PlonkVerifier {
         )
     }
 
+    /// Verifies the PLONK proof of a Ziren proof saved by the SDK against the PLONK verifying key
+    /// embedded in this crate, [`static@crate::PLONK_VK_BYTES`].
+    ///
+    /// Unlike [`crate::verify_plonk`], this only needs `alloc`, so it runs in `no_std`
+    /// environments.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The PLONK proof of a `ZKMProof::Plonk` proof.
+    /// * `zkm_public_inputs` - The Ziren public values of the proof.
+    /// * `zkm_vkey_hash` - The Ziren vkey hash, from a call to `vk.bytes32()`.
+    pub fn verify_bn254_proof(
+        proof: &PlonkBn254Proof,
+        zkm_public_inputs: &[u8],
+        zkm_vkey_hash: &str,
+    ) -> Result<(), PlonkError> {
+        let encoded_proof = hex::decode(&proof.encoded_proof)
+            .map_err(|_| PlonkError::GeneralError(Error::InvalidData))?;
+        let bytes = [&proof.plonk_vkey_hash[..4], &encoded_proof].concat();
+        Self::verify(&bytes, zkm_public_inputs, zkm_vkey_hash, &PLONK_VK_BYTES)
+    }
+
     /// Verifies a Gnark PLONK proof using raw byte inputs.
     ///
     /// WARNING: if you're verifying a Ziren proof, you should use [`verify`] instead.
@@ -99,11 +133,13 @@ impl PlonkVerifier {
         public_inputs: &[[u8; 32]],
         plonk_vk: &[u8],
     ) -> Result<(), PlonkError> {
-        let plonk_vk = load_plonk_verifying_key_from_bytes(plonk_vk).unwrap();
-        let proof = load_plonk_proof_from_bytes(proof, plonk_vk.qcp.len()).unwrap();
+        let plonk_vk = load_plonk_verifying_key_from_bytes(plonk_vk)?;
+        let proof = load_plonk_proof_from_bytes(proof, plonk_vk.qcp.len())?;
 
-        let public_inputs =
-            public_inputs.iter().map(|input| Fr::from_slice(input).unwrap()).collect::<Vec<_>>();
+        let public_inputs = public_inputs
+            .iter()
+            .map(|input| Fr::from_slice(input).map_err(Error::Field))
+            .collect::<Result<Vec<_>, _>>()?;
         verify_plonk_algebraic(&plonk_vk, &proof, &public_inputs)
     }
 }
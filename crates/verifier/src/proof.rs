@@ -0,0 +1,120 @@
+//! Verification of the proofs saved by the Ziren SDK, without the prover.
+//!
+//! Services which only consume proofs can verify a [ZKMProofWithPublicValues] of any kind with
+//! this crate alone, against the artifacts embedded in it: the allowed recursion verifying keys
+//! for compressed proofs, and the [static@crate::GROTH16_VK_BYTES] and
+//! [static@crate::PLONK_VK_BYTES] verifying keys for Groth16 and Plonk proofs.
+
+use thiserror::Error;
+use zkm_core_machine::{verify::verify_core_proof, ZKM_CIRCUIT_VERSION};
+
+use crate::{
+    stark::{verify_committed_value_digest, verify_stark_compressed_proof},
+    Groth16Error, Groth16Verifier, HashableKey, PlonkError, PlonkVerifier, StarkError, ZKMProof,
+    ZKMProofKind, ZKMProofWithPublicValues, ZKMVerifyingKey,
+};
+
+/// An error returned when a proof fails to verify.
+#[derive(Error, Debug)]
+pub enum ProofVerificationError {
+    #[error("expected a {expected:?} proof, found a {actual:?} proof")]
+    UnexpectedKind { expected: ZKMProofKind, actual: ZKMProofKind },
+    #[error("proof generated with Ziren {0}, expected {ZKM_CIRCUIT_VERSION}")]
+    VersionMismatch(String),
+    #[error("stark verification error: {0}")]
+    Stark(#[from] StarkError),
+    #[error("groth16 verification error: {0}")]
+    Groth16(#[from] Groth16Error),
+    #[error("plonk verification error: {0}")]
+    Plonk(#[from] PlonkError),
+}
+
+/// Verifies a proof of any kind against the verifying key of its program.
+pub fn verify(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<(), ProofVerificationError> {
+    match ZKMProofKind::from(&proof.proof) {
+        ZKMProofKind::Core => verify_core(proof, vk),
+        ZKMProofKind::Compressed => verify_compressed(proof, vk),
+        ZKMProofKind::Groth16 => verify_groth16(proof, vk),
+        ZKMProofKind::Plonk => verify_plonk(proof, vk),
+        ZKMProofKind::CompressToGroth16 => Err(unexpected_kind(ZKMProofKind::Groth16, proof)),
+    }
+}
+
+/// Verifies a core proof against the verifying key of its program.
+pub fn verify_core(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<(), ProofVerificationError> {
+    check_version(proof)?;
+    let ZKMProof::Core(shard_proofs) = &proof.proof else {
+        return Err(unexpected_kind(ZKMProofKind::Core, proof));
+    };
+    let last_shard = shard_proofs.last().ok_or(StarkError::InvalidPublicValues)?;
+    verify_committed_value_digest(&last_shard.public_values, &proof.public_values)?;
+    verify_core_proof(&vk.vk, shard_proofs).map_err(StarkError::Core)?;
+    Ok(())
+}
+
+/// Verifies a compressed proof against the verifying key of its program, and the recursion
+/// verifying keys embedded in this crate.
+pub fn verify_compressed(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<(), ProofVerificationError> {
+    check_version(proof)?;
+    let ZKMProof::Compressed(reduce_proof) = &proof.proof else {
+        return Err(unexpected_kind(ZKMProofKind::Compressed, proof));
+    };
+    verify_committed_value_digest(&reduce_proof.proof.public_values, &proof.public_values)?;
+    verify_stark_compressed_proof(vk, reduce_proof).map_err(StarkError::Recursion)?;
+    Ok(())
+}
+
+/// Verifies a Groth16 proof against the verifying key of its program, and the Groth16 verifying
+/// key embedded in this crate.
+pub fn verify_groth16(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<(), ProofVerificationError> {
+    check_version(proof)?;
+    let ZKMProof::Groth16(groth16_proof) = &proof.proof else {
+        return Err(unexpected_kind(ZKMProofKind::Groth16, proof));
+    };
+    Groth16Verifier::verify_bn254_proof(
+        groth16_proof,
+        proof.public_values.as_slice(),
+        &vk.bytes32(),
+    )?;
+    Ok(())
+}
+
+/// Verifies a Plonk proof against the verifying key of its program, and the Plonk verifying key
+/// embedded in this crate.
+pub fn verify_plonk(
+    proof: &ZKMProofWithPublicValues,
+    vk: &ZKMVerifyingKey,
+) -> Result<(), ProofVerificationError> {
+    check_version(proof)?;
+    let ZKMProof::Plonk(plonk_proof) = &proof.proof else {
+        return Err(unexpected_kind(ZKMProofKind::Plonk, proof));
+    };
+    PlonkVerifier::verify_bn254_proof(plonk_proof, proof.public_values.as_slice(), &vk.bytes32())?;
+    Ok(())
+}
+
+fn check_version(proof: &ZKMProofWithPublicValues) -> Result<(), ProofVerificationError> {
+    if proof.zkm_version != ZKM_CIRCUIT_VERSION {
+        return Err(ProofVerificationError::VersionMismatch(proof.zkm_version.clone()));
+    }
+    Ok(())
+}
+
+fn unexpected_kind(
+    expected: ZKMProofKind,
+    proof: &ZKMProofWithPublicValues,
+) -> ProofVerificationError {
+    ProofVerificationError::UnexpectedKind { expected, actual: (&proof.proof).into() }
+}
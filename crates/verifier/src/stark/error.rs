@@ -14,4 +14,8 @@ pub enum StarkError {
     Core(MachineVerificationError<CoreSC>),
     #[error("Recursion verification error: {0}")]
     Recursion(MachineVerificationError<InnerSC>),
+    #[error("Failed to deserialize the {0}")]
+    Deserialize(String),
+    #[error("Expected a {0} proof")]
+    WrongProofKind(&'static str),
 }
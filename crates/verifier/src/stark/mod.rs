@@ -9,18 +9,23 @@ use p3_field::FieldAlgebra;
 use p3_field::PrimeField32;
 use p3_field::TwoAdicField;
 use p3_koala_bear::KoalaBear;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use strum_macros::{EnumDiscriminants, EnumTryAs};
 use zkm_core_executor::ZKMReduceProof;
-use zkm_primitives::{io::ZKMPublicValues, poseidon2_hash};
+use zkm_core_machine::verify::verify_core_proof;
+use zkm_primitives::{
+    io::{PublicValuesHash, ZKMPublicValues},
+    poseidon2_hash,
+};
 use zkm_stark::ShardProof;
 use zkm_stark::{
     air::PublicValues, koala_bear_poseidon2::KoalaBearPoseidon2, StarkGenericConfig,
     StarkVerifyingKey, Word, DIGEST_SIZE,
 };
 
+use crate::{Groth16Bn254Proof, PlonkBn254Proof};
 use error::StarkError;
-use verify::verify_stark_compressed_proof;
+pub(crate) use verify::verify_stark_compressed_proof;
 
 pub mod error;
 mod verify;
@@ -48,23 +53,13 @@ pub enum ZKMProof {
     CompressToGroth16,
 }
 
-/// A zero-knowledge proof generated by the PLONK protocol with a Base64 encoded gnark PLONK proof.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct PlonkBn254Proof {
-    pub public_inputs: [String; 2],
-    pub encoded_proof: String,
-    pub raw_proof: String,
-    pub plonk_vkey_hash: [u8; 32],
-}
-
-/// A zero-knowledge proof generated by the Groth16 protocol with a Base64 encoded gnark Groth16
-/// proof.
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Groth16Bn254Proof {
-    pub public_inputs: [String; 2],
-    pub encoded_proof: String,
-    pub raw_proof: String,
-    pub groth16_vkey_hash: [u8; 32],
+/// A proof generated with Ziren, bundled together with stdin, public values, and the Ziren version.
+/// Redefined due to SDK's non-no_std limitation, and is used only for deserialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZKMProofWithPublicValues {
+    pub proof: ZKMProof,
+    pub public_values: ZKMPublicValues,
+    pub zkm_version: String,
 }
 
 /// The configuration for the core prover.
@@ -82,6 +77,30 @@ pub struct ZKMVerifyingKey {
 pub trait HashableKey {
     /// Hash the key into a digest of KoalaBear elements.
     fn hash_koalabear(&self) -> [KoalaBear; DIGEST_SIZE];
+
+    /// Hash the key into a BN254 field element, formatted as a 32-byte hex string, as expected by
+    /// the Groth16 and Plonk verifiers.
+    ///
+    /// The digest elements are below 2^31, so packing them 31 bits at a time fits in 248 bits,
+    /// below the BN254 modulus.
+    fn bytes32(&self) -> String {
+        let mut bytes = [0u8; 32];
+        for (i, word) in self.hash_koalabear().iter().enumerate() {
+            let word = word.as_canonical_u32();
+            let shift = 31 * (DIGEST_SIZE - 1 - i);
+            for bit in (0..31).filter(|bit| (word >> bit) & 1 == 1) {
+                let pos = shift + bit;
+                bytes[31 - pos / 8] |= 1 << (pos % 8);
+            }
+        }
+        format!("0x{}", hex::encode(bytes))
+    }
+}
+
+impl HashableKey for ZKMVerifyingKey {
+    fn hash_koalabear(&self) -> [KoalaBear; DIGEST_SIZE] {
+        self.vk.hash_koalabear()
+    }
 }
 
 /// A verifier for stark zero-knowledge proofs.
@@ -111,32 +130,45 @@ impl StarkVerifier {
     /// Compared to `verify_proof()`, it performs a consistency check between
     /// user-supplied public values and those committed in the proof.
     pub fn verify(proof: &[u8], zkm_public_inputs: &[u8], zkm_vk: &[u8]) -> Result<(), StarkError> {
-        let proof: ZKMProof = bincode::deserialize(proof).expect("failed to deserialize the proof");
-        let ZKMProof::Compressed(proof) = proof else { panic!("expected a compressed proof") };
+        let proof: ZKMProof = deserialize(proof, "proof")?;
+        let ZKMProof::Compressed(proof) = proof else {
+            return Err(StarkError::WrongProofKind("compressed"));
+        };
         let public_inputs = ZKMPublicValues::from(zkm_public_inputs);
-        let vk: ZKMVerifyingKey =
-            bincode::deserialize(zkm_vk).expect("failed to deserialize the vk");
-
-        let proof_public_values: &PublicValues<Word<_>, _> =
-            proof.proof.public_values.as_slice().borrow();
-
-        // Get the committed value digest bytes.
-        let committed_value_digest_bytes = proof_public_values
-            .committed_value_digest
-            .iter()
-            .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
-            .collect_vec();
-
-        // Make sure the committed value digest matches the public values hash.
-        for (a, b) in committed_value_digest_bytes.iter().zip_eq(public_inputs.hash()) {
-            if *a != b {
-                return Err(StarkError::InvalidPublicValues);
-            }
-        }
+        let vk: ZKMVerifyingKey = deserialize(zkm_vk, "vk")?;
 
+        verify_committed_value_digest(&proof.proof.public_values, &public_inputs)?;
         verify_stark_compressed_proof(&vk, &proof).map_err(StarkError::Recursion)
     }
 
+    /// Verifies a Ziren core proof, as generated by the Ziren SDK.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The bincode-serialized [ZKMProof], which must be a core proof.
+    /// * `zkm_public_inputs` - The Ziren public inputs, which are committed by the guest as a
+    ///   bincode-serialized byte array.
+    /// * `zkm_vk` - The Ziren vkey bytes.
+    ///
+    /// Unlike compressed proofs, core proofs are verified shard by shard, so their verification
+    /// takes time and memory linear in the number of shards.
+    pub fn verify_core(
+        proof: &[u8],
+        zkm_public_inputs: &[u8],
+        zkm_vk: &[u8],
+    ) -> Result<(), StarkError> {
+        let proof: ZKMProof = deserialize(proof, "proof")?;
+        let ZKMProof::Core(shard_proofs) = proof else {
+            return Err(StarkError::WrongProofKind("core"));
+        };
+        let public_inputs = ZKMPublicValues::from(zkm_public_inputs);
+        let vk: ZKMVerifyingKey = deserialize(zkm_vk, "vk")?;
+
+        let last_shard = shard_proofs.last().ok_or(StarkError::InvalidPublicValues)?;
+        verify_committed_value_digest(&last_shard.public_values, &public_inputs)?;
+        verify_core_proof(&vk.vk, &shard_proofs).map_err(StarkError::Core)
+    }
+
     /// Verifies a Ziren compressed proof, as generated by the Ziren SDK.
     ///
     /// # Arguments
@@ -147,15 +179,43 @@ impl StarkVerifier {
     /// Compared to `verify()`, it does not perform a consistency check between
     /// user-supplied public values and those committed in the proof.
     pub fn verify_proof(proof: &[u8], zkm_vk: &[u8]) -> Result<(), StarkError> {
-        let proof: ZKMProof = bincode::deserialize(proof).expect("failed to deserialize the proof");
-        let ZKMProof::Compressed(proof) = proof else { panic!("expected a compressed proof") };
-        let vk: ZKMVerifyingKey =
-            bincode::deserialize(zkm_vk).expect("failed to deserialize the vk");
+        let proof: ZKMProof = deserialize(proof, "proof")?;
+        let ZKMProof::Compressed(proof) = proof else {
+            return Err(StarkError::WrongProofKind("compressed"));
+        };
+        let vk: ZKMVerifyingKey = deserialize(zkm_vk, "vk")?;
 
         verify_stark_compressed_proof(&vk, &proof).map_err(StarkError::Recursion)
     }
 }
 
+/// Deserializes the untrusted `bytes` of the `what` passed to a verifier.
+fn deserialize<T: DeserializeOwned>(bytes: &[u8], what: &str) -> Result<T, StarkError> {
+    bincode::deserialize(bytes).map_err(|err| StarkError::Deserialize(format!("{what}: {err}")))
+}
+
+/// Checks that the `committed_value_digest` of the public values of a shard is the SHA-256 digest
+/// of the public inputs.
+pub(crate) fn verify_committed_value_digest(
+    public_values: &[KoalaBear],
+    public_inputs: &ZKMPublicValues,
+) -> Result<(), StarkError> {
+    let public_values: &PublicValues<Word<_>, _> = public_values.borrow();
+
+    // Get the committed value digest bytes.
+    let committed_value_digest_bytes = public_values
+        .committed_value_digest
+        .iter()
+        .flat_map(|w| w.0.iter().map(|x| x.as_canonical_u32() as u8))
+        .collect_vec();
+
+    // Make sure the committed value digest matches the public values hash.
+    if !public_inputs.is_digest(&committed_value_digest_bytes, PublicValuesHash::Sha256) {
+        return Err(StarkError::InvalidPublicValues);
+    }
+    Ok(())
+}
+
 impl<SC: StarkGenericConfig<Val = KoalaBear, Domain = TwoAdicMultiplicativeCoset<KoalaBear>>>
     HashableKey for StarkVerifyingKey<SC>
 where
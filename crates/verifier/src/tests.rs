@@ -37,6 +37,21 @@ fn test_verify_groth16() {
     }
 }
 
+#[test]
+fn test_verify_bn254_proof_malformed() {
+    let vkey_hash = format!("0x{}", "00".repeat(32));
+    let groth16_proof =
+        crate::Groth16Bn254Proof { encoded_proof: "not hex".to_string(), ..Default::default() };
+    assert!(crate::Groth16Verifier::verify_bn254_proof(&groth16_proof, &[], &vkey_hash).is_err());
+
+    // A proof of another verifying key is rejected before it is decoded.
+    let plonk_proof = crate::PlonkBn254Proof::default();
+    assert!(matches!(
+        crate::PlonkVerifier::verify_bn254_proof(&plonk_proof, &[], &vkey_hash),
+        Err(crate::PlonkError::PlonkVkeyHashMismatch)
+    ));
+}
+
 #[test]
 fn test_verify_plonk() {
     // Set up the pk and vk.
@@ -77,6 +92,40 @@ fn test_verify_stark() {
         .expect("Stark proof is invalid");
 
     crate::StarkVerifier::verify_proof(&proof, &vk_bytes).expect("Stark proof is invalid");
+
+    // Malformed inputs and proofs of another kind are rejected with an error.
+    assert!(matches!(
+        crate::StarkVerifier::verify_core(&proof, &public_inputs, &vk_bytes),
+        Err(crate::StarkError::WrongProofKind("core"))
+    ));
+    assert!(matches!(
+        crate::StarkVerifier::verify_core(&proof[..proof.len() / 2], &public_inputs, &vk_bytes),
+        Err(crate::StarkError::Deserialize(_))
+    ));
+    assert!(matches!(
+        crate::StarkVerifier::verify(&proof, &public_inputs, &[0xff; 4]),
+        Err(crate::StarkError::Deserialize(_))
+    ));
+}
+
+#[test]
+fn test_verify_without_prover() {
+    // Set up the pk and vk.
+    let client = ProverClient::cpu();
+    let (pk, vk) = client.setup(HELLO_WORLD_ELF);
+
+    // The verifier only deserializes the types of the SDK.
+    let light_vk: crate::ZKMVerifyingKey =
+        bincode::deserialize(&bincode::serialize(&vk).unwrap()).unwrap();
+    assert_eq!(crate::HashableKey::bytes32(&light_vk), vk.bytes32());
+
+    let core_proof = client.prove(&pk, ZKMStdin::new()).run().unwrap();
+    let compressed_proof = client.prove(&pk, ZKMStdin::new()).compressed().run().unwrap();
+    for proof in [core_proof, compressed_proof] {
+        let proof: crate::ZKMProofWithPublicValues =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        crate::verify(&proof, &light_vk).expect("proof is invalid");
+    }
 }
 
 // ZKM_DEV=true RUST_LOG=debug cargo test -r test_e2e_verify_groth16 --features ark -- --nocapture
@@ -125,12 +174,12 @@ fn test_e2e_verify_groth16() {
 #[test]
 #[ignore]
 fn test_vkeys() {
-    let groth16_path = try_install_circuit_artifacts("groth16");
+    let groth16_path = try_install_circuit_artifacts("groth16").unwrap();
     let s3_vkey_path = groth16_path.join("groth16_vk.bin");
     let s3_vkey_bytes = std::fs::read(s3_vkey_path).unwrap();
     assert_eq!(s3_vkey_bytes, *crate::GROTH16_VK_BYTES);
 
-    let plonk_path = try_install_circuit_artifacts("plonk");
+    let plonk_path = try_install_circuit_artifacts("plonk").unwrap();
     let s3_vkey_path = plonk_path.join("plonk_vk.bin");
     let s3_vkey_bytes = std::fs::read(s3_vkey_path).unwrap();
     assert_eq!(s3_vkey_bytes, *crate::PLONK_VK_BYTES);
@@ -0,0 +1,109 @@
+//! Bindings of the verifier for `wasm32-unknown-unknown`, to verify proofs in a browser.
+//!
+//! Built with the `wasm` feature, which does not enable `std`: only the no-std Groth16 and Plonk
+//! verifiers are pulled in, and none of the STARK machines, gnark or docker dependencies of the
+//! prover:
+//!
+//! ```bash
+//! cargo rustc -p zkm-verifier --release --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/zkm_verifier.wasm
+//! ```
+//!
+//! With `std` enabled as well, `verifyProof` verifies the proofs saved by the SDK, of any kind.
+//! This pulls in the STARK machines, and is not checked to build for `wasm32-unknown-unknown`.
+
+use alloc::string::{String, ToString};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+
+/// The circuit version of the proofs this build verifies, which is the version of the workspace.
+const CIRCUIT_VERSION: &str = concat!("v", env!("CARGO_PKG_VERSION"));
+
+/// Verifies a proof saved by the SDK, of any kind, against the bincode-serialized verifying key
+/// of its program.
+#[cfg(feature = "std")]
+#[wasm_bindgen(js_name = verifyProof)]
+pub fn verify_proof(proof: &[u8], vk: &[u8]) -> Result<(), JsError> {
+    use zkm_primitives::envelope::{read_proof_envelope_header, PROOF_ENVELOPE_MAGIC};
+
+    use crate::{ZKMProofWithPublicValues, ZKMVerifyingKey};
+
+    // Proofs saved as bare bincode, before the envelope format, have no header.
+    let mut payload = proof;
+    if let Some(mut rest) = proof.strip_prefix(&PROOF_ENVELOPE_MAGIC) {
+        let header = read_proof_envelope_header(&mut rest)?;
+        if header.circuit_version != CIRCUIT_VERSION {
+            return Err(JsError::new(&format!(
+                "proof encoded by circuit version {}, expected {CIRCUIT_VERSION}",
+                header.circuit_version
+            )));
+        }
+        payload = rest;
+    }
+    let proof: ZKMProofWithPublicValues = bincode::deserialize(payload)?;
+    let vk: ZKMVerifyingKey = bincode::deserialize(vk)?;
+    crate::verify(&proof, &vk)?;
+    Ok(())
+}
+
+/// Verifies the onchain bytes of a Groth16 proof, as returned by `proof.bytes()`, against its
+/// public values and the vkey hash of its program, from `vk.bytes32()`.
+#[wasm_bindgen(js_name = verifyGroth16)]
+pub fn verify_groth16(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<(), JsError> {
+    Groth16Verifier::verify(proof, public_values, vkey_hash, &GROTH16_VK_BYTES)?;
+    Ok(())
+}
+
+/// Verifies the onchain bytes of a Plonk proof, as returned by `proof.bytes()`, against its
+/// public values and the vkey hash of its program, from `vk.bytes32()`.
+#[wasm_bindgen(js_name = verifyPlonk)]
+pub fn verify_plonk(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<(), JsError> {
+    PlonkVerifier::verify(proof, public_values, vkey_hash, &PLONK_VK_BYTES)?;
+    Ok(())
+}
+
+/// The circuit version of the proofs this build verifies.
+#[wasm_bindgen(js_name = circuitVersion)]
+pub fn circuit_version() -> String {
+    CIRCUIT_VERSION.to_string()
+}
+
+// The errors are `JsError`s, which can only be created on wasm32, so only valid proofs are
+// verified by these tests.
+#[cfg(test)]
+mod tests {
+    use test_artifacts::HELLO_WORLD_ELF;
+    use zkm_sdk::{HashableKey, ProverClient, ZKMStdin, ZKM_CIRCUIT_VERSION};
+
+    use super::*;
+
+    #[test]
+    fn test_circuit_version() {
+        assert_eq!(circuit_version(), ZKM_CIRCUIT_VERSION);
+    }
+
+    #[test]
+    fn test_verify_groth16() {
+        let client = ProverClient::cpu();
+        let (pk, vk) = client.setup(HELLO_WORLD_ELF);
+        let proof = client.prove(&pk, ZKMStdin::new()).groth16().run().unwrap();
+        verify_groth16(&proof.bytes(), &proof.public_values.to_vec(), &vk.bytes32()).unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_verify_proof() {
+        let client = ProverClient::cpu();
+        let (pk, vk) = client.setup(HELLO_WORLD_ELF);
+        let proof = client.prove(&pk, ZKMStdin::new()).compressed().run().unwrap();
+        let vk = bincode::serialize(&vk).unwrap();
+
+        // Both the envelope format and the bare bincode of the older proofs are verified.
+        verify_proof(&proof.to_bytes(), &vk).unwrap();
+        verify_proof(&bincode::serialize(&proof).unwrap(), &vk).unwrap();
+    }
+}
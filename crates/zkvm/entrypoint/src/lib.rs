@@ -150,6 +150,7 @@ mod zkvm {
     use cfg_if::cfg_if;
     use getrandom::{register_custom_getrandom, Error};
     use sha2::{Digest, Sha256};
+    use zkm_primitives::io::PublicValuesHash;
 
     cfg_if! {
         if #[cfg(feature = "verify")] {
@@ -160,7 +161,40 @@ mod zkvm {
         }
     }
 
-    pub static mut PUBLIC_VALUES_HASHER: Option<Sha256> = None;
+    /// The hasher of the public values, whose digest the program commits to when it halts.
+    ///
+    /// With the `poseidon2-commit` feature, the public values are hashed with the Poseidon2
+    /// precompile instead of SHA-256, which costs fewer cycles per byte committed. With the
+    /// `keccak-commit` feature, they are hashed with Keccak-256, which the EVM recomputes with a
+    /// native opcode. The mode, [PUBLIC_VALUES_HASH], is recorded in the trailer of the public
+    /// values, so that verifiers only accept the digest of this mode.
+    #[cfg(not(any(feature = "poseidon2-commit", feature = "keccak-commit")))]
+    pub type PublicValuesHasher = Sha256;
+    #[cfg(feature = "poseidon2-commit")]
+    pub type PublicValuesHasher = zkm_lib::poseidon2::Poseidon2Sponge;
+    #[cfg(feature = "keccak-commit")]
+    pub type PublicValuesHasher = zkm_lib::keccak256::Keccak256Hasher;
+
+    /// The hash of [PublicValuesHasher].
+    #[cfg(not(any(feature = "poseidon2-commit", feature = "keccak-commit")))]
+    pub const PUBLIC_VALUES_HASH: PublicValuesHash = PublicValuesHash::Sha256;
+    #[cfg(feature = "poseidon2-commit")]
+    pub const PUBLIC_VALUES_HASH: PublicValuesHash = PublicValuesHash::Poseidon2;
+    #[cfg(feature = "keccak-commit")]
+    pub const PUBLIC_VALUES_HASH: PublicValuesHash = PublicValuesHash::Keccak256;
+
+    #[cfg(all(feature = "poseidon2-commit", feature = "keccak-commit"))]
+    compile_error!("the poseidon2-commit and keccak-commit features are mutually exclusive");
+
+    pub static mut PUBLIC_VALUES_HASHER: Option<PublicValuesHasher> = None;
+
+    /// The hasher of the public logs, set once the program commits its first log.
+    pub static mut PUBLIC_LOGS_HASHER: Option<Sha256> = None;
+
+    /// The records of the public value slots, by index, set once the program commits its first
+    /// slot.
+    pub static mut PUBLIC_SLOTS: Option<alloc::collections::BTreeMap<u32, alloc::vec::Vec<u8>>> =
+        None;
 
     #[no_mangle]
     fn _main() {
@@ -168,11 +202,13 @@ mod zkvm {
         crate::allocators::init();
 
         unsafe {
-            PUBLIC_VALUES_HASHER = Some(Sha256::new());
+            PUBLIC_VALUES_HASHER = Some(PublicValuesHasher::new());
             #[cfg(feature = "verify")]
             {
                 DEFERRED_PROOFS_DIGEST = Some([KoalaBear::ZERO; 8]);
             }
+            #[cfg(feature = "seeded-rng")]
+            crate::syscalls::seed_rng_from_host();
             extern "C" {
                 fn start();
             }
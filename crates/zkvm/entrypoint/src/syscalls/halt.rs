@@ -4,6 +4,10 @@ cfg_if::cfg_if! {
         use sha2::Digest;
         use crate::zkvm;
         use crate::{PV_DIGEST_NUM_WORDS, POSEIDON_NUM_WORDS};
+        use zkm_primitives::{
+            consts::fd::FD_PUBLIC_VALUES,
+            io::PublicValuesTrailer,
+        };
     }
 }
 
@@ -21,6 +25,23 @@ cfg_if::cfg_if! {
 pub extern "C" fn syscall_halt(exit_code: u8) -> ! {
     #[cfg(target_os = "zkvm")]
     unsafe {
+        // Append the trailer holding the digest of the public logs, the public value slots, the
+        // seed of the random number generator, the time base of the clock and the hash of the
+        // public values, so that they are committed along with them. It is appended even when
+        // empty, so that the length ending the public values is always written here, and a
+        // trailer committed by the program is never read as this one.
+        let trailer = PublicValuesTrailer {
+            log_digest: core::mem::take(&mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_LOGS_HASHER))
+                .map(|hasher| hasher.finalize().into()),
+            rng_seed: *core::ptr::addr_of!(super::sys::HOST_RNG_SEED),
+            time_base: zkm_lib::time::time_base(),
+            slots: core::mem::take(&mut *core::ptr::addr_of_mut!(zkvm::PUBLIC_SLOTS))
+                .unwrap_or_default(),
+            hash: zkvm::PUBLIC_VALUES_HASH,
+        };
+        let trailer = trailer.encode();
+        crate::syscalls::syscall_write(FD_PUBLIC_VALUES, trailer.as_ptr(), trailer.len());
+
         // When we halt, we retrieve the public values finalized digest.  This is the hash of all
         // the bytes written to the public values fd.
         let pv_digest_bytes =
@@ -3,7 +3,7 @@ cfg_if::cfg_if! {
         use core::arch::asm;
         use crate::zkvm;
         use sha2::digest::Update;
-        use zkm_primitives::consts::fd::FD_PUBLIC_VALUES;
+        use zkm_primitives::consts::fd::{FD_PUBLIC_LOGS, FD_PUBLIC_SLOTS, FD_PUBLIC_VALUES};
     }
 }
 
@@ -13,6 +13,22 @@ cfg_if::cfg_if! {
 pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
     cfg_if::cfg_if! {
         if #[cfg(target_os = "zkvm")] {
+            // Writes to the public slots fd are kept by the program, and appended to the public
+            // values when it halts, so that a slot committed again is only committed once. Each
+            // write is the index of the slot followed by its record.
+            if fd == FD_PUBLIC_SLOTS {
+                let slot: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
+                let Some((idx, record)) = slot.split_first_chunk::<4>() else {
+                    panic!("a write of {nbytes} bytes to the public slots fd is too short");
+                };
+                unsafe {
+                    (*core::ptr::addr_of_mut!(zkvm::PUBLIC_SLOTS))
+                        .get_or_insert_with(Default::default)
+                        .insert(u32::from_le_bytes(*idx), record.to_vec())
+                };
+                return;
+            }
+
             unsafe {
                 asm!(
                     "syscall",
@@ -30,6 +46,17 @@ pub extern "C" fn syscall_write(fd: u32, write_buf: *const u8, nbytes: usize) {
                 let pi_slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
                 unsafe { zkvm::PUBLIC_VALUES_HASHER.as_mut().unwrap().update(pi_slice) };
             }
+
+            // Writes to the public logs fd are hashed separately. The digest is appended to the
+            // public values when the program halts.
+            if fd == FD_PUBLIC_LOGS {
+                let log_slice: &[u8] = unsafe { core::slice::from_raw_parts(write_buf, nbytes) };
+                unsafe {
+                    (*core::ptr::addr_of_mut!(zkvm::PUBLIC_LOGS_HASHER))
+                        .get_or_insert_with(Default::default)
+                        .update(log_slice)
+                };
+            }
         } else {
             unreachable!()
         }
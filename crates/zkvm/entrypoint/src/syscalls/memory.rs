@@ -12,7 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// Memory addresses must be lower than KoalaBear prime.
+// Memory addresses must be lower than KoalaBear prime, since the memory chips store each address
+// in a single field element.
 pub const MAX_MEMORY: usize = 0x7f000000;
 
 #[allow(clippy::missing_safety_doc)]
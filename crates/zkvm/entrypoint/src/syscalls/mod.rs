@@ -1,4 +1,5 @@
 mod bigint;
+mod blake3_compress;
 mod bls12381;
 mod bn254;
 mod ed25519;
@@ -10,6 +11,8 @@ mod memory;
 mod poseidon2_permute;
 mod secp256k1;
 mod secp256r1;
+mod sha512_compress;
+mod sha512_extend;
 mod sha_compress;
 mod sha_extend;
 mod sys;
@@ -20,6 +23,7 @@ mod unconstrained;
 mod verify;
 
 pub use bigint::*;
+pub use blake3_compress::*;
 pub use bls12381::*;
 pub use bn254::*;
 pub use ed25519::*;
@@ -31,6 +35,8 @@ pub use memory::*;
 pub use poseidon2_permute::*;
 pub use secp256k1::*;
 pub use secp256r1::*;
+pub use sha512_compress::*;
+pub use sha512_extend::*;
 pub use sha_compress::*;
 pub use sha_extend::*;
 pub use sys::*;
@@ -162,3 +168,12 @@ pub const BN254_FP2_MUL: u32 = 0x01_01_00_2B;
 
 /// Executes the `POSEIDON2_PERMUTE` precompile.
 pub const POSEIDON2_PERMUTE: u32 = 0x00_01_00_30;
+
+/// Executes the `BLAKE3_COMPRESS_INNER` precompile.
+pub const BLAKE3_COMPRESS_INNER: u32 = 0x38_01_00_31;
+
+/// Executes the `SHA512_EXTEND` precompile.
+pub const SHA512_EXTEND: u32 = 0x40_01_00_32;
+
+/// Executes the `SHA512_COMPRESS` precompile.
+pub const SHA512_COMPRESS: u32 = 0x01_01_00_33;
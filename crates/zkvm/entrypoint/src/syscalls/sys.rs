@@ -4,10 +4,10 @@ use lazy_static::lazy_static;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::syscalls::{syscall_halt, syscall_write};
+#[cfg(feature = "seeded-rng")]
+use zkm_primitives::consts::fd::FD_RNG_SEED;
 
-/// The random number generator seed for the zkVM.
-///
-/// In the future, we can pass in this seed from the host or have the verifier generate it.
+/// The random number generator seed for the zkVM, unless the host provides one.
 const PRNG_SEED: u64 = 0x123456789abcdef0;
 
 lazy_static! {
@@ -15,6 +15,22 @@ lazy_static! {
     static ref RNG: Mutex<StdRng> = Mutex::new(StdRng::seed_from_u64(PRNG_SEED));
 }
 
+/// The seed of [RNG] provided by the host, which is committed in the trailer of the public values
+/// when the program halts, see [zkm_primitives::io::PublicValuesTrailer].
+pub(crate) static mut HOST_RNG_SEED: Option<[u8; 32]> = None;
+
+/// Seeds [RNG] with the seed set by the host with `ZKMContextBuilder::with_rng_seed`, if any.
+///
+/// The seed is read before the program starts, as hints cannot be read in unconstrained blocks.
+#[cfg(feature = "seeded-rng")]
+pub(crate) fn seed_rng_from_host() {
+    let seed: Option<[u8; 32]> = zkm_lib::io::call_hook(FD_RNG_SEED, &());
+    if let Some(seed) = seed {
+        *RNG.lock().unwrap() = StdRng::from_seed(seed);
+        unsafe { HOST_RNG_SEED = Some(seed) };
+    }
+}
+
 /// A lazy static to print a warning once for using the `sys_rand` system call.
 static SYS_RAND_WARNING: std::sync::Once = std::sync::Once::new();
 
@@ -25,9 +41,11 @@ static SYS_RAND_WARNING: std::sync::Once = std::sync::Once::new();
 /// Make sure that `buf` has at least `nwords` words.
 #[no_mangle]
 pub unsafe extern "C" fn sys_rand(recv_buf: *mut u8, words: usize) {
-    SYS_RAND_WARNING.call_once(|| {
-        eprintln!("WARNING: Using insecure random number generator.");
-    });
+    if (*core::ptr::addr_of!(HOST_RNG_SEED)).is_none() {
+        SYS_RAND_WARNING.call_once(|| {
+            eprintln!("WARNING: Using insecure random number generator.");
+        });
+    }
     let mut rng = RNG.lock().unwrap();
     for i in 0..words {
         let element = recv_buf.add(i);
@@ -0,0 +1,126 @@
+use crate::syscall_blake3_compress_inner;
+
+pub use zkm_primitives::consts::blake3::BLAKE3_IV;
+
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// Executes the BLAKE3 compression function on the chaining value `cv` and the message `block`,
+/// returning the 16-word output. Its first 8 words are the next chaining value.
+pub fn blake3_compress(
+    cv: &[u32; 8],
+    block: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[..8].copy_from_slice(cv);
+    state[8..12].copy_from_slice(&BLAKE3_IV[..4]);
+    state[12..].copy_from_slice(&[counter as u32, (counter >> 32) as u32, block_len, flags]);
+    unsafe {
+        syscall_blake3_compress_inner(&mut state, block);
+    }
+    for (i, cv) in cv.iter().enumerate() {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= cv;
+    }
+    state
+}
+
+/// Computes the 32-byte BLAKE3 hash of the given input.
+pub fn blake3(input: &[u8]) -> [u8; 32] {
+    let mut cv_stack: Vec<[u32; 8]> = Vec::new();
+    let mut chunks = input.chunks(CHUNK_LEN).peekable();
+    let mut chunk_counter = 0u64;
+
+    // The output of the last chunk is not compressed here, as it may be the root.
+    let mut output = loop {
+        let chunk = chunks.next().unwrap_or_default();
+        let output = chunk_output(chunk, chunk_counter);
+        chunk_counter += 1;
+        if chunks.peek().is_none() {
+            break output;
+        }
+
+        // Merge the completed subtrees, one for every trailing zero bit of the chunk count.
+        let mut cv = output.chaining_value();
+        let mut total_chunks = chunk_counter;
+        while total_chunks & 1 == 0 {
+            cv = parent_output(&cv_stack.pop().unwrap(), &cv).chaining_value();
+            total_chunks >>= 1;
+        }
+        cv_stack.push(cv);
+    };
+
+    while let Some(left) = cv_stack.pop() {
+        output = parent_output(&left, &output.chaining_value());
+    }
+
+    let root = blake3_compress(&output.cv, &output.block, 0, output.block_len, output.flags | ROOT);
+    let mut hash = [0u8; 32];
+    for (bytes, word) in hash.chunks_exact_mut(4).zip(root) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    hash
+}
+
+/// The inputs of a compression whose output is either a chaining value or the root.
+struct Output {
+    cv: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        let out = blake3_compress(&self.cv, &self.block, self.counter, self.block_len, self.flags);
+        out[..8].try_into().unwrap()
+    }
+}
+
+/// Compresses all the blocks of a chunk but the last one, whose compression is the output of the
+/// chunk.
+fn chunk_output(chunk: &[u8], chunk_counter: u64) -> Output {
+    let mut cv = BLAKE3_IV;
+    let num_blocks = chunk.len().div_ceil(BLOCK_LEN).max(1);
+    let mut flags = CHUNK_START;
+    for block in chunk.chunks(BLOCK_LEN).take(num_blocks - 1) {
+        let out = blake3_compress(&cv, &block_words(block), chunk_counter, BLOCK_LEN as u32, flags);
+        cv = out[..8].try_into().unwrap();
+        flags = 0;
+    }
+    let last = &chunk[(num_blocks - 1) * BLOCK_LEN..];
+    Output {
+        cv,
+        block: block_words(last),
+        counter: chunk_counter,
+        block_len: last.len() as u32,
+        flags: flags | CHUNK_END,
+    }
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8]) -> Output {
+    let mut block = [0u32; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+    Output { cv: BLAKE3_IV, block, counter: 0, block_len: BLOCK_LEN as u32, flags: PARENT }
+}
+
+/// Reads a block of at most 64 bytes as little-endian words, padded with zeros.
+fn block_words(block: &[u8]) -> [u32; 16] {
+    let mut bytes = [0u8; BLOCK_LEN];
+    bytes[..block.len()].copy_from_slice(block);
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    words
+}
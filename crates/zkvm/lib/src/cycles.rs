@@ -0,0 +1,29 @@
+//! The cycles executed by the program, and the markers of the cycle tracker regions.
+//!
+//! The host may bound the cycles of a region with `ZKMContextBuilder::max_cycles_between_markers`,
+//! for example to cap an untrusted plugin run by the program. Like any hint, the cycle count is not
+//! committed, and the budgets are only enforced by the executor.
+
+use crate::io::FD_CYCLE_COUNT;
+
+crate::define_hooks! {
+    /// Reads the number of cycles executed by the program.
+    fn cycle_count_hint(FD_CYCLE_COUNT, ()) -> u64;
+}
+
+/// Returns the number of cycles executed by the program so far.
+///
+/// The cycle count cannot be read in an unconstrained block.
+pub fn cycle_count() -> u64 {
+    cycle_count_hint(&())
+}
+
+/// Marks the start of the cycle tracker region `name`, as `cycle-tracker-start: {name}`.
+pub fn region_start(name: &str) {
+    println!("cycle-tracker-start: {name}");
+}
+
+/// Marks the end of the cycle tracker region `name`, as `cycle-tracker-end: {name}`.
+pub fn region_end(name: &str) {
+    println!("cycle-tracker-end: {name}");
+}
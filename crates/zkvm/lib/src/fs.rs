@@ -0,0 +1,74 @@
+//! Read-only access to the virtual files written with `ZKMStdin::write_file`.
+//!
+//! The files are read through the `FD_FS_READ` hook rather than the `open`/`read` Linux
+//! syscalls, so `std::fs` does not see them. Like any hint, their contents are not committed: a
+//! program whose verifier must know them should commit them, or their digest.
+
+use std::io::{self, BufRead, Cursor, Read, Seek, SeekFrom};
+
+use crate::io::FD_FS_READ;
+
+crate::define_hooks! {
+    /// Reads the virtual file at the given path.
+    fn read_hint(FD_FS_READ, String) -> Option<Vec<u8>>;
+}
+
+/// Reads the entire contents of the virtual file at `path`, like [std::fs::read].
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+    read_hint(&path.to_string()).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("no virtual file at {path}"))
+    })
+}
+
+/// Reads the entire contents of the virtual file at `path` into a string, like
+/// [std::fs::read_to_string].
+pub fn read_to_string(path: &str) -> io::Result<String> {
+    String::from_utf8(read(path)?).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// An open virtual file, like [std::fs::File] opened for reading.
+///
+/// The whole file is read when it is opened, so reads and seeks do not call the host.
+#[derive(Debug, Clone)]
+pub struct File {
+    contents: Cursor<Vec<u8>>,
+}
+
+impl File {
+    /// Opens the virtual file at `path`.
+    pub fn open(path: &str) -> io::Result<Self> {
+        Ok(Self { contents: Cursor::new(read(path)?) })
+    }
+
+    /// The length of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.contents.get_ref().len() as u64
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.contents.get_ref().is_empty()
+    }
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.contents.read(buf)
+    }
+}
+
+impl BufRead for File {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.contents.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.contents.consume(amt)
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.contents.seek(pos)
+    }
+}
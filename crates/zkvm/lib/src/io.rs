@@ -3,6 +3,7 @@ use crate::{read_vec_raw, syscall_write, ReadVecResult};
 use serde::{de::DeserializeOwned, Serialize};
 use std::io::{Result, Write};
 pub use zkm_primitives::consts::fd::*;
+use zkm_primitives::merkle::MerkleTree;
 
 /// A writer that writes to a file descriptor inside the zkVM.
 struct SyscallWriter {
@@ -63,6 +64,27 @@ pub fn read<T: DeserializeOwned>() -> T {
     bincode::deserialize(&vec).expect("deserialization failed")
 }
 
+/// Read the deserializable object written by the host under `name` with
+/// `ZKMStdin::write_named`.
+///
+/// Panics, failing the execution with a readable error, if the host did not write an input under
+/// `name`, or wrote it as another type than `T`. Types are compared by their names without their
+/// paths, so the host and the program may import them from different crates.
+///
+/// ### Examples
+/// ```ignore
+/// let header: BlockHeader = zkm_zkvm::io::read_named("block_header");
+/// ```
+pub fn read_named<T: DeserializeOwned>(name: &str) -> T {
+    let request = (name, std::any::type_name::<T>());
+    let response: core::result::Result<Vec<u8>, String> = call_hook(FD_NAMED_INPUT, &request);
+    match response {
+        Ok(bytes) => bincode::deserialize(&bytes)
+            .unwrap_or_else(|err| panic!("failed to deserialize the input `{name}`: {err}")),
+        Err(err) => panic!("{err}"),
+    }
+}
+
 /// Commit a serializable object to the public values stream.
 ///
 /// ### Examples
@@ -98,6 +120,74 @@ pub fn commit_slice(buf: &[u8]) {
     my_writer.write_all(buf).unwrap();
 }
 
+/// Commit a serializable object to the public logs stream.
+///
+/// Unlike the public values, the logs are not part of the proof: only their digest is appended to
+/// the public values when the program halts. Verifiers may check the logs against the digest, or
+/// ignore them.
+///
+/// ### Examples
+/// ```ignore
+/// let gas_used: u64 = 21000;
+/// zkm_zkvm::io::commit_log(&gas_used);
+/// ```
+pub fn commit_log<T: Serialize>(value: &T) {
+    let writer = SyscallWriter { fd: FD_PUBLIC_LOGS };
+    bincode::serialize_into(writer, value).expect("serialization failed");
+}
+
+/// Commit bytes to the public logs stream.
+///
+/// ### Examples
+/// ```ignore
+/// let data = vec![1, 2, 3, 4];
+/// zkm_zkvm::io::commit_log_slice(&data);
+/// ```
+pub fn commit_log_slice(buf: &[u8]) {
+    let mut my_writer = SyscallWriter { fd: FD_PUBLIC_LOGS };
+    my_writer.write_all(buf).unwrap();
+}
+
+/// Commit a large output by the root of the Merkle tree of its chunks, and return the root.
+///
+/// Only the root is committed to the public values stream. The chunks are output to the host,
+/// which opens them with `zkm_primitives::merkle::MerkleTree`, so a verifier checks any chunk
+/// against the root read with `ZKMPublicValues::read_merkle_root`, without the others.
+///
+/// ### Examples
+/// ```ignore
+/// let rows: Vec<Vec<u8>> = compute_table();
+/// zkm_zkvm::io::commit_merkle(&rows);
+/// ```
+pub fn commit_merkle<C: AsRef<[u8]>>(chunks: &[C]) -> [u8; 32] {
+    let root = MerkleTree::new(chunks).root().0;
+    let chunks: Vec<&[u8]> = chunks.iter().map(AsRef::as_ref).collect();
+    write(FD_MERKLE_CHUNKS, &bincode::serialize(&chunks).expect("serialization failed"));
+    commit_slice(&root);
+    root
+}
+
+/// Commit a serializable object to the public value slot `idx`.
+///
+/// The slots are appended to the public values when the program halts, along with the name of
+/// the type of their values, so the host reads them back by index with
+/// `ZKMPublicValues::get_slot`, which checks the type. Committing a slot again replaces its value.
+/// The public values stream written with [commit] is unaffected.
+///
+/// ### Examples
+/// ```ignore
+/// zkm_zkvm::io::commit_slot(0, &block_hash);
+/// zkm_zkvm::io::commit_slot(1, &gas_used);
+/// ```
+pub fn commit_slot<T: Serialize>(idx: u32, value: &T) {
+    let bytes = bincode::serialize(value).expect("serialization failed");
+    let record =
+        bincode::serialize(&(std::any::type_name::<T>(), bytes)).expect("serialization failed");
+    let mut payload = idx.to_le_bytes().to_vec();
+    payload.extend_from_slice(&record);
+    write(FD_PUBLIC_SLOTS, &payload);
+}
+
 /// Hint a serializable object to the hint stream.
 ///
 /// ### Examples
@@ -143,3 +233,48 @@ pub fn hint_slice(buf: &[u8]) {
 pub fn write(fd: u32, buf: &[u8]) {
     SyscallWriter { fd }.write_all(buf).unwrap();
 }
+
+/// Call the typed hook registered by the host at `fd` with `request`, and read back its response.
+///
+/// The host registers the hook with `ZKMContextBuilder::typed_hook`. The response is not
+/// constrained: the program must check it, as for any hint.
+///
+/// ### Examples
+/// ```ignore
+/// const FD_FACTOR: u32 = 1000;
+/// let (p, q): (u64, u64) = zkm_zkvm::io::call_hook(FD_FACTOR, &n);
+/// assert_eq!(p * q, n);
+/// ```
+pub fn call_hook<Req: Serialize, Resp: DeserializeOwned>(fd: u32, request: &Req) -> Resp {
+    let buf = bincode::serialize(request).expect("serialization failed");
+    crate::unconstrained! {
+        write(fd, &buf);
+    }
+    read()
+}
+
+/// Define functions calling the typed hooks registered by the host, see [call_hook].
+///
+/// ### Examples
+/// ```ignore
+/// zkm_zkvm::lib::define_hooks! {
+///     /// Factors `n` into two primes.
+///     pub fn factor(1000, u64) -> (u64, u64);
+///     /// Looks up the balance of an account.
+///     pub fn balance(1001, [u8; 20]) -> u64;
+/// }
+///
+/// let (p, q) = factor(&n);
+/// assert_eq!(p * q, n);
+/// ```
+#[macro_export]
+macro_rules! define_hooks {
+    ($($(#[$attr:meta])* $vis:vis fn $name:ident($fd:expr, $req:ty) -> $resp:ty;)*) => {
+        $(
+            $(#[$attr])*
+            $vis fn $name(request: &$req) -> $resp {
+                $crate::io::call_hook::<$req, $resp>($fd, request)
+            }
+        )*
+    };
+}
@@ -54,3 +54,26 @@ pub fn keccak256(data: &[u8]) -> [u8; 32] {
     keccak256_result.copy_from_slice(&tmp[..32]);
     keccak256_result
 }
+
+/// A Keccak-256 hasher of its input as it is written, to the same digest as [keccak256].
+///
+/// The keccak sponge precompile absorbs its whole input in one call, so the input is buffered
+/// until [Self::finalize].
+#[derive(Clone, Default)]
+pub struct Keccak256Hasher {
+    data: Vec<u8>,
+}
+
+impl Keccak256Hasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, input: &[u8]) {
+        self.data.extend_from_slice(input);
+    }
+
+    pub fn finalize(self) -> [u8; 32] {
+        keccak256(&self.data)
+    }
+}
@@ -3,18 +3,24 @@
 //! Documentation for these syscalls can be found in the zkVM entrypoint
 //! `zkm_zkvm::syscalls` module.
 
+pub mod blake;
 pub mod bls12381;
 pub mod bn254;
+pub mod cycles;
 #[cfg(feature = "ecdsa")]
 pub mod ecdsa;
 
 pub mod ed25519;
+pub mod fs;
 pub mod io;
 pub mod keccak256;
+pub mod modexp;
 pub mod poseidon2;
 pub mod secp256k1;
 pub mod secp256r1;
 pub mod sha3;
+pub mod sha512;
+pub mod time;
 pub mod unconstrained;
 pub mod utils;
 #[cfg(feature = "verify")]
@@ -36,6 +42,12 @@ extern "C" {
     /// Executes the SHA-256 compress operation on the given word array and a given state.
     pub fn syscall_sha256_compress(w: *mut [u32; 64], state: *mut [u32; 8]);
 
+    /// Executes the SHA-512 extend operation on the given word array.
+    pub fn syscall_sha512_extend(w: *mut [u64; 80]);
+
+    /// Executes the SHA-512 compress operation on the given word array and the given state.
+    pub fn syscall_sha512_compress(w: *mut [u64; 80], state: *mut [u64; 8]);
+
     /// Executes an Ed25519 curve addition on the given points.
     pub fn syscall_ed_add(p: *mut [u32; 16], q: *const [u32; 16]);
 
@@ -78,6 +90,9 @@ extern "C" {
     /// Executes the Poseidon2 permutation
     pub fn syscall_poseidon2_permute(state: *mut [u32; 16]);
 
+    /// Executes the rounds of the BLAKE3 compression function on the given state and message.
+    pub fn syscall_blake3_compress_inner(state: *mut [u32; 16], msg: *const [u32; 16]);
+
     /// Executes an uint256 multiplication on the given inputs.
     pub fn syscall_uint256_mulmod(x: *mut [u32; 8], y: *const [u32; 8]);
 
@@ -0,0 +1,248 @@
+use crate::{io::FD_BIGINT_DIV_REM, syscall_u256x2048_mul};
+
+/// The maximum length in bytes of the base, the exponent and the modulus of [modexp].
+pub const MODEXP_MAX_LEN: usize = 512;
+
+/// The number of words of the left operand of the `U256XU2048_MUL` syscall.
+const LHS_WORDS: usize = 8;
+
+/// The number of words of the right operand of the `U256XU2048_MUL` syscall.
+const RHS_WORDS: usize = 64;
+
+crate::define_hooks! {
+    /// Divides `a` by `m`, returning the quotient and the remainder.
+    ///
+    /// The slices are serialized like the `(Vec<u32>, Vec<u32>)` expected by the host hook.
+    fn div_rem_hint(FD_BIGINT_DIV_REM, (&[u32], &[u32])) -> (Vec<u32>, Vec<u32>);
+}
+
+/// The multiplication and the division hint [modexp] is computed with.
+struct Arith {
+    /// Multiplies two little endian integers.
+    mul: fn(&[u32], &[u32]) -> Vec<u32>,
+    /// Returns the quotient and the remainder of the division of `a` by `m`, unchecked.
+    div_rem: fn(&[u32], &[u32]) -> (Vec<u32>, Vec<u32>),
+}
+
+/// Computes `base ^ exponent % modulus` with the semantics of the Ethereum `MODEXP` precompile.
+///
+/// This is a guest routine, not a precompile: a dedicated `MODEXP` syscall and chip were
+/// declined, see the precompiles chapter of the book. It costs about two modular
+/// multiplications per bit of the exponent, each of which is 8 `U256XU2048_MUL` syscalls for a
+/// 2048-bit modulus and 32 for a 4096-bit one, plus as many again to check the reduction, and the
+/// guest code adding the partial products.
+///
+/// The operands are big endian integers of at most [MODEXP_MAX_LEN] bytes, and the result is a
+/// big endian integer of `modulus.len()` bytes. The result is zero if the modulus is zero.
+///
+/// Every modular multiplication is a product computed with the `U256XU2048_MUL` precompile, whose
+/// quotient and remainder by the modulus are hinted by the host and checked against the product.
+pub fn modexp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    let arith = Arith { mul, div_rem: |a, m| div_rem_hint(&(a, m)) };
+    modexp_with(&arith, base, exponent, modulus)
+}
+
+/// Computes [modexp] with the multiplication and the division hint of `arith`.
+fn modexp_with(arith: &Arith, base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    assert!(
+        base.len() <= MODEXP_MAX_LEN
+            && exponent.len() <= MODEXP_MAX_LEN
+            && modulus.len() <= MODEXP_MAX_LEN,
+        "modexp operands are limited to {MODEXP_MAX_LEN} bytes"
+    );
+
+    let modulus_words = words_from_be_bytes(modulus);
+    if modulus_words.is_empty() {
+        return vec![0; modulus.len()];
+    }
+
+    let base = reduce(arith, words_from_be_bytes(base), &modulus_words);
+    let mut result = reduce(arith, vec![1], &modulus_words);
+    let bits = exponent
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .skip_while(|bit| !bit);
+    for bit in bits {
+        result = reduce(arith, (arith.mul)(&result, &result), &modulus_words);
+        if bit {
+            result = reduce(arith, (arith.mul)(&result, &base), &modulus_words);
+        }
+    }
+
+    be_bytes_from_words(&result, modulus.len())
+}
+
+/// Reduces `a` modulo the nonzero `m`, from the quotient and the remainder hinted by the host.
+fn reduce(arith: &Arith, a: Vec<u32>, m: &[u32]) -> Vec<u32> {
+    let (q, r) = (arith.div_rem)(&a, m);
+    let r = trim(r);
+    assert!(less_than(&r, m), "modexp: the hinted remainder is not reduced");
+
+    let mut qm = (arith.mul)(&q, m);
+    add_at(&mut qm, 0, &r);
+    assert!(trim(qm) == trim(a), "modexp: the hinted quotient and remainder are invalid");
+    r
+}
+
+/// Multiplies `a` by `b`, in chunks of 256 by 2048 bits.
+fn mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let a = trim(a.to_vec());
+    let b = trim(b.to_vec());
+    let mut product = vec![0; a.len() + b.len() + LHS_WORDS + RHS_WORDS];
+    for (i, a_chunk) in a.chunks(LHS_WORDS).enumerate() {
+        let mut lhs = [0u32; LHS_WORDS];
+        lhs[..a_chunk.len()].copy_from_slice(a_chunk);
+        for (j, b_chunk) in b.chunks(RHS_WORDS).enumerate() {
+            let mut rhs = [0u32; RHS_WORDS];
+            rhs[..b_chunk.len()].copy_from_slice(b_chunk);
+            let mut lo = [0u32; RHS_WORDS];
+            let mut hi = [0u32; LHS_WORDS];
+            unsafe {
+                syscall_u256x2048_mul(&lhs, &rhs, &mut lo, &mut hi);
+            }
+            let offset = i * LHS_WORDS + j * RHS_WORDS;
+            add_at(&mut product, offset, &lo);
+            add_at(&mut product, offset + RHS_WORDS, &hi);
+        }
+    }
+    trim(product)
+}
+
+/// Adds `b` to `a` shifted by `offset` words, growing `a` if the sum overflows it.
+fn add_at(a: &mut Vec<u32>, offset: usize, b: &[u32]) {
+    if a.len() < offset + b.len() {
+        a.resize(offset + b.len(), 0);
+    }
+    let mut carry = false;
+    for (i, &word) in b.iter().enumerate() {
+        let (sum, c1) = a[offset + i].overflowing_add(word);
+        let (sum, c2) = sum.overflowing_add(carry as u32);
+        a[offset + i] = sum;
+        carry = c1 || c2;
+    }
+    let mut i = offset + b.len();
+    while carry {
+        if i == a.len() {
+            a.push(0);
+        }
+        let (sum, c) = a[i].overflowing_add(1);
+        a[i] = sum;
+        carry = c;
+        i += 1;
+    }
+}
+
+/// Whether `a` is less than `b`, both without leading zeros.
+fn less_than(a: &[u32], b: &[u32]) -> bool {
+    a.len() < b.len() || (a.len() == b.len() && a.iter().rev().lt(b.iter().rev()))
+}
+
+/// Removes the leading zero words of `a`.
+fn trim(mut a: Vec<u32>) -> Vec<u32> {
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+    a
+}
+
+/// Reads a big endian integer as little endian words without leading zeros.
+fn words_from_be_bytes(bytes: &[u8]) -> Vec<u32> {
+    let words = bytes
+        .rchunks(4)
+        .map(|chunk| chunk.iter().fold(0u32, |word, &byte| (word << 8) | u32::from(byte)))
+        .collect();
+    trim(words)
+}
+
+/// Writes little endian words as a big endian integer of `len` bytes.
+fn be_bytes_from_words(words: &[u32], len: usize) -> Vec<u8> {
+    let mut bytes = words.iter().flat_map(|word| word.to_le_bytes()).collect::<Vec<_>>();
+    bytes.resize(len, 0);
+    bytes.reverse();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    /// Multiplies `a` by `b` word by word, standing in for the `U256XU2048_MUL` precompile.
+    fn schoolbook_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut product = vec![0u32; a.len() + b.len()];
+        for (i, &x) in a.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &y) in b.iter().enumerate() {
+                let t = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+                product[i + j] = t as u32;
+                carry = t >> 32;
+            }
+            product[i + b.len()] = carry as u32;
+        }
+        trim(product)
+    }
+
+    /// Divides `a` by `m` like the `FD_BIGINT_DIV_REM` hook of the executor.
+    fn div_rem(a: &[u32], m: &[u32]) -> (Vec<u32>, Vec<u32>) {
+        let (a, m) = (BigUint::from_slice(a), BigUint::from_slice(m));
+        ((&a / &m).to_u32_digits(), (a % m).to_u32_digits())
+    }
+
+    const ARITH: Arith = Arith { mul: schoolbook_mul, div_rem };
+
+    /// Checks [modexp_with] against [BigUint::modpow].
+    fn check(base: &[u8], exponent: &[u8], modulus: &[u8]) {
+        let result = modexp_with(&ARITH, base, exponent, modulus);
+        assert_eq!(result.len(), modulus.len());
+        let modulus_int = BigUint::from_bytes_be(modulus);
+        let expected = if modulus_int == BigUint::from(0u32) {
+            BigUint::from(0u32)
+        } else {
+            BigUint::from_bytes_be(base).modpow(&BigUint::from_bytes_be(exponent), &modulus_int)
+        };
+        assert_eq!(BigUint::from_bytes_be(&result), expected);
+    }
+
+    #[test]
+    fn test_modexp_edge_cases() {
+        let modulus = [0xff; 256];
+
+        // A zero exponent gives one, reduced by the modulus.
+        check(&[7], &[], &modulus);
+        check(&[7], &[0, 0], &modulus);
+        check(&[0], &[0], &[5]);
+        check(&[7], &[0], &[1]);
+
+        // Any power modulo one is zero.
+        check(&[7], &[3], &[1]);
+        check(&[0xab; 64], &[0xcd; 32], &[0, 0, 1]);
+
+        // A base larger than or equal to the modulus is reduced first.
+        check(&[5], &[3], &[5]);
+        check(&[0x01, 0x00], &[2], &[0xfb]);
+        check(&[0xff; 512], &[0x01, 0x00, 0x01], &[0x12; 256]);
+
+        // A zero modulus gives zero, with its length.
+        check(&[7], &[3], &[]);
+        check(&[7], &[3], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn test_modexp_rsa_sizes() {
+        for len in [32, 256, 512] {
+            let base = (0..len).map(|i| (i * 7 + 3) as u8).collect::<Vec<_>>();
+            let mut modulus = (0..len).map(|i| (i * 13 + 1) as u8).collect::<Vec<_>>();
+            modulus[0] |= 0x80;
+            check(&base, &[0x01, 0x00, 0x01], &modulus);
+            check(&base, &base[..32], &modulus);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the hinted remainder is not reduced")]
+    fn test_modexp_rejects_unreduced_hint() {
+        let arith = Arith { mul: schoolbook_mul, div_rem: |a, _| (vec![], a.to_vec()) };
+        modexp_with(&arith, &[9], &[2], &[5]);
+    }
+}
@@ -68,6 +68,64 @@ pub fn poseidon2_impl(input: &[u8], out_byte_len: usize) -> Vec<u8> {
     result[..out_byte_len].to_vec()
 }
 
+/// A Poseidon2 sponge hashing its input as it is written, to the same digest as [poseidon2].
+#[derive(Clone, Default)]
+pub struct Poseidon2Sponge {
+    state: [u32; WIDTH],
+    /// The bytes written since the last permutation, fewer than a rate of field elements.
+    pending: [u8; RATE * FIELD_SIZE],
+    pending_len: usize,
+}
+
+impl Poseidon2Sponge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Absorbs `input`, permuting the state every time a rate of field elements is written.
+    pub fn update(&mut self, mut input: &[u8]) {
+        while !input.is_empty() {
+            let len = (self.pending.len() - self.pending_len).min(input.len());
+            self.pending[self.pending_len..self.pending_len + len].copy_from_slice(&input[..len]);
+            self.pending_len += len;
+            input = &input[len..];
+            if self.pending_len == self.pending.len() {
+                let pending = self.pending;
+                self.absorb(&pending);
+                self.pending_len = 0;
+            }
+        }
+    }
+
+    /// Pads the pending bytes as [poseidon2_impl] does, and returns the 32-byte digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        // The pending bytes are fewer than a rate, so they are still a rate at most once padded.
+        let l = self.pending_len;
+        let new_size = (l + FIELD_SIZE) / FIELD_SIZE * FIELD_SIZE;
+        let mut padded = [0u8; RATE * FIELD_SIZE];
+        padded[..l].copy_from_slice(&self.pending[..l]);
+        if l % FIELD_SIZE == FIELD_SIZE - 1 {
+            padded[l] = 0b10000001;
+        } else {
+            padded[l] = 1;
+            padded[new_size - 1] = 0b10000000;
+        }
+        self.absorb(&padded[..new_size]);
+
+        let mut digest = [0u8; DEFAULT_OUT_FIELD_LEN * 4];
+        for (bytes, word) in digest.chunks_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    fn absorb(&mut self, bytes: &[u8]) {
+        let field_input = bytes_to_field_elements(bytes);
+        self.state[..field_input.len()].clone_from_slice(&field_input);
+        poseidon2_permute(&mut self.state);
+    }
+}
+
 // each 3 bytes of input can be safely converted to a field element
 #[inline]
 fn bytes_to_field_elements(bytes: &[u8]) -> Vec<u32> {
@@ -0,0 +1,38 @@
+use crate::{syscall_sha512_compress, syscall_sha512_extend};
+
+pub use zkm_primitives::consts::sha512::SHA512_IV;
+
+const BLOCK_LEN: usize = 128;
+
+/// Executes the SHA-512 compression function on the state with the given 128-byte block.
+pub fn sha512_compress(state: &mut [u64; 8], block: &[u8; BLOCK_LEN]) {
+    let mut w = [0u64; 80];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+    unsafe {
+        syscall_sha512_extend(&mut w);
+        syscall_sha512_compress(&mut w, state);
+    }
+}
+
+/// Computes the 64-byte SHA-512 hash of the given input.
+pub fn sha512(input: &[u8]) -> [u8; 64] {
+    let mut state = SHA512_IV;
+
+    // Pad the input with a one bit, zeros and its length in bits as a 128-bit integer.
+    let mut padded = input.to_vec();
+    padded.push(0x80);
+    padded.resize((input.len() + 17).next_multiple_of(BLOCK_LEN) - 16, 0);
+    padded.extend_from_slice(&((input.len() as u128) * 8).to_be_bytes());
+
+    for block in padded.chunks_exact(BLOCK_LEN) {
+        sha512_compress(&mut state, block.try_into().unwrap());
+    }
+
+    let mut hash = [0u8; 64];
+    for (bytes, word) in hash.chunks_exact_mut(8).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
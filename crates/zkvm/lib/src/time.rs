@@ -0,0 +1,51 @@
+//! A deterministic clock, started by the host with `ZKMContextBuilder::with_time_base`.
+//!
+//! The clock advances with the cycles of the program, so every execution of a program on the same
+//! input reads the same times. The time base is committed at the end of the public values once
+//! the program reads the clock, so a verifier can check which time the program started at, see
+//! `ZKMPublicValues::time_base`. The time elapsed since is a hint derived from the cycle count,
+//! which is not constrained: a program whose verifier must know a time it read should commit it.
+//!
+//! `std::time::SystemTime::now` is not supported: the executor handles its `clock_gettime` system
+//! call as a no-op, which does not write the time, so the time it returns is unspecified. Read the
+//! clock with [now] instead.
+
+use std::{
+    sync::OnceLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::io::FD_CLOCK;
+
+crate::define_hooks! {
+    /// Reads the time base of the clock as seconds and nanoseconds since the Unix epoch, and the
+    /// nanoseconds elapsed since.
+    fn clock_hint(FD_CLOCK, ()) -> Option<(u64, u32, u64)>;
+}
+
+/// The time base of the clock, set when the program first reads it.
+static TIME_BASE: OnceLock<Duration> = OnceLock::new();
+
+/// Returns the current time of the deterministic clock, like [SystemTime::now], or `None` if the
+/// host did not set a time base.
+///
+/// The clock cannot be read in an unconstrained block.
+///
+/// # Panics
+///
+/// Panics if the host returns another time base than the one it returned first, or if the time
+/// overflows.
+pub fn now() -> Option<SystemTime> {
+    let (secs, nanos, elapsed) = clock_hint(&())?;
+    let base = Duration::new(secs, nanos);
+    assert_eq!(*TIME_BASE.get_or_init(|| base), base, "the time base of the clock changed");
+    let time = base.checked_add(Duration::from_nanos(elapsed)).expect("the clock overflowed");
+    Some(UNIX_EPOCH.checked_add(time).expect("the clock overflowed"))
+}
+
+/// Returns the time base of the clock since the Unix epoch, if the program read it.
+///
+/// The entrypoint commits it at the end of the public values when the program halts.
+pub fn time_base() -> Option<Duration> {
+    TIME_BASE.get().copied()
+}
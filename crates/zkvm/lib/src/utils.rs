@@ -84,6 +84,9 @@ pub trait AffinePoint<const N: usize>: Clone + Sized {
 
     /// Performs multi-scalar multiplication (MSM) on slices of bit vectors and points. Note:
     /// a_bits_le and b_bits_le should be in little endian order.
+    ///
+    /// The bits are processed from the most significant one with a single doubling per bit,
+    /// adding `a`, `b` or the precomputed `a + b` when either bit is set (Shamir's trick).
     fn multi_scalar_multiplication(
         a_bits_le: &[bool],
         a: Self,
@@ -93,18 +96,21 @@ pub trait AffinePoint<const N: usize>: Clone + Sized {
         // The length of the bit vectors must be the same.
         debug_assert!(a_bits_le.len() == b_bits_le.len());
 
+        let mut sum = a.clone();
+        sum.complete_add_assign(&b);
+
         let mut res: Self = Self::identity();
-        let mut temp_a = a.clone();
-        let mut temp_b = b.clone();
-        for (a_bit, b_bit) in a_bits_le.iter().zip(b_bits_le.iter()) {
-            if *a_bit {
-                res.complete_add_assign(&temp_a);
+        for (a_bit, b_bit) in a_bits_le.iter().zip(b_bits_le.iter()).rev() {
+            // Doubling the identity is not supported, and would leave it unchanged anyway.
+            if !res.is_identity() {
+                res.double();
             }
-            if *b_bit {
-                res.complete_add_assign(&temp_b);
+            match (a_bit, b_bit) {
+                (true, true) => res.complete_add_assign(&sum),
+                (true, false) => res.complete_add_assign(&a),
+                (false, true) => res.complete_add_assign(&b),
+                (false, false) => {}
             }
-            temp_a.double();
-            temp_b.double();
         }
         res
     }
@@ -190,3 +196,116 @@ pub trait WeierstrassAffinePoint<const N: usize>: AffinePoint<N> {
         self.add_assign(other);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The order of the additive group [`Fp`] used to check the scalar multiplications.
+    const P: u64 = (1 << 61) - 1;
+
+    /// The additive group of integers modulo [`P`], as a stand-in for a curve group.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Fp([u32; 2]);
+
+    impl Fp {
+        fn value(&self) -> u64 {
+            u64::from(self.0[0]) | (u64::from(self.0[1]) << 32)
+        }
+
+        fn from_value(value: u64) -> Self {
+            Self([value as u32, (value >> 32) as u32])
+        }
+    }
+
+    impl AffinePoint<2> for Fp {
+        const GENERATOR: [u32; 2] = [1, 0];
+
+        const GENERATOR_T: Self = Self([1, 0]);
+
+        fn new(limbs: [u32; 2]) -> Self {
+            Self(limbs)
+        }
+
+        fn identity() -> Self {
+            Self([0, 0])
+        }
+
+        fn limbs_ref(&self) -> &[u32; 2] {
+            &self.0
+        }
+
+        fn limbs_mut(&mut self) -> &mut [u32; 2] {
+            &mut self.0
+        }
+
+        fn is_identity(&self) -> bool {
+            self.value() == 0
+        }
+
+        fn add_assign(&mut self, other: &Self) {
+            *self = Self::from_value((self.value() + other.value()) % P);
+        }
+
+        fn double(&mut self) {
+            assert!(!self.is_identity(), "doubling the identity");
+            *self = Self::from_value(2 * self.value() % P);
+        }
+    }
+
+    /// The double-and-add multi-scalar multiplication replaced by Shamir's trick.
+    fn double_and_add(a_bits_le: &[bool], a: Fp, b_bits_le: &[bool], b: Fp) -> Fp {
+        let mut res = Fp::identity();
+        let mut temp_a = a;
+        let mut temp_b = b;
+        for (a_bit, b_bit) in a_bits_le.iter().zip(b_bits_le.iter()) {
+            if *a_bit {
+                res.complete_add_assign(&temp_a);
+            }
+            if *b_bit {
+                res.complete_add_assign(&temp_b);
+            }
+            if !temp_a.is_identity() {
+                temp_a.double();
+            }
+            if !temp_b.is_identity() {
+                temp_b.double();
+            }
+        }
+        res
+    }
+
+    fn bits_le(scalar: u64) -> Vec<bool> {
+        (0..64).map(|i| (scalar >> i) & 1 == 1).collect()
+    }
+
+    #[test]
+    fn test_multi_scalar_multiplication() {
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % P
+        };
+
+        let mut scalars = vec![0, 1, 2, P - 1];
+        scalars.extend((0..8).map(|_| next()));
+        let mut points = vec![0, 1, P - 1];
+        points.extend((0..4).map(|_| next()));
+
+        for &a in &points {
+            for &b in &points {
+                for &a_scalar in &scalars {
+                    for &b_scalar in &scalars {
+                        let (a, b) = (Fp::from_value(a), Fp::from_value(b));
+                        let (a_bits, b_bits) = (bits_le(a_scalar), bits_le(b_scalar));
+                        let expected = double_and_add(&a_bits, a.clone(), &b_bits, b.clone());
+                        let res = Fp::multi_scalar_multiplication(&a_bits, a, &b_bits, b);
+                        assert_eq!(res, expected);
+                    }
+                }
+            }
+        }
+    }
+}
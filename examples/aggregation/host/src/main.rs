@@ -66,16 +66,18 @@ fn main() {
             inputs.iter().map(|input| input.proof.public_values.to_vec()).collect::<Vec<_>>();
         stdin.write::<Vec<Vec<u8>>>(&public_values);
 
-        // Write the proofs.
+        // Add the proofs.
         //
-        // Note: this data will not actually be read by the aggregation program, instead it will be
-        // witnessed by the prover during the recursive aggregation process inside Ziren itself.
+        // Note: the proofs will not actually be read by the aggregation program, instead they
+        // will be witnessed by the prover during the recursive aggregation process inside Ziren
+        // itself.
+        let mut prove = client.prove(&aggregation_pk, stdin);
         for input in inputs {
             let ZKMProof::Compressed(proof) = input.proof.proof else { panic!() };
-            stdin.write_proof(*proof, input.vk.vk);
+            prove = prove.with_deferred_proof(*proof, &input.vk);
         }
 
         // Generate the plonk bn254 proof.
-        client.prove(&aggregation_pk, stdin).plonk().run().expect("proving failed");
+        prove.plonk().run().expect("proving failed");
     });
 }